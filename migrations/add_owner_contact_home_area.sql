@@ -0,0 +1,5 @@
+-- Optional home area of owner contacts, shown on the public profile of lost pets.
+-- Coordinates are stored already rounded to about 1 km.
+ALTER TABLE owner_contact ADD COLUMN address TEXT DEFAULT NULL;
+ALTER TABLE owner_contact ADD COLUMN latitude REAL DEFAULT NULL;
+ALTER TABLE owner_contact ADD COLUMN longitude REAL DEFAULT NULL;
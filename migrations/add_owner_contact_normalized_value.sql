@@ -0,0 +1,17 @@
+-- Contact value without case, spaces, dashes or parentheses, so repeated submits
+-- of the same contact are detected. The duplicates already stored are removed,
+-- keeping the oldest one, after copying them to owner_contact_duplicate.
+ALTER TABLE owner_contact ADD COLUMN normalized_value TEXT GENERATED ALWAYS AS (
+  lower(replace(replace(replace(replace(trim(contact_value),' ',''),'-',''),'(',''),')',''))
+) VIRTUAL;
+CREATE TABLE IF NOT EXISTS owner_contact_duplicate AS
+SELECT * FROM owner_contact
+WHERE id NOT IN (
+  SELECT MIN(id) FROM owner_contact GROUP BY user_app_id, normalized_value
+);
+DELETE FROM owner_contact
+WHERE id NOT IN (
+  SELECT MIN(id) FROM owner_contact GROUP BY user_app_id, normalized_value
+);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_owner_contact_normalized_value
+ON owner_contact (user_app_id, normalized_value);
@@ -0,0 +1,3 @@
+-- Primary contact shown first on the public profile, the rest follow the owner's order.
+ALTER TABLE owner_contact ADD COLUMN is_primary BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE owner_contact ADD COLUMN sort_order INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,3 @@
+-- Adds the type of owner contacts, used to build tel/mailto/WhatsApp links.
+ALTER TABLE owner_contact ADD COLUMN contact_type TEXT NOT NULL DEFAULT('other');
+UPDATE owner_contact SET contact_type='email' WHERE contact_value LIKE '%_@_%._%';
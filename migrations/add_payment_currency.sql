@@ -0,0 +1,2 @@
+-- Adds the currency of subscription payments, legacy rows are MXN.
+ALTER TABLE user_sub_payment ADD COLUMN currency TEXT NOT NULL DEFAULT('MXN');
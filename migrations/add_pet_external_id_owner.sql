@@ -0,0 +1,12 @@
+-- User who bought or was gifted the tag, NULL for tags not handed out yet.
+ALTER TABLE pet_external_id ADD COLUMN owner_user_id INTEGER NULL REFERENCES user_app(id) ON DELETE SET NULL;
+CREATE INDEX IF NOT EXISTS idx_external_id_owner
+ON pet_external_id (owner_user_id);
+
+-- Tags already linked belong to the owner of their pet
+UPDATE pet_external_id SET owner_user_id = (
+    SELECT p.user_app_id FROM pet_linked AS plink
+    INNER JOIN pet AS p ON (p.id = plink.pet_id)
+    WHERE plink.id_pet_external_id = pet_external_id.id
+)
+WHERE owner_user_id IS NULL;
@@ -0,0 +1,2 @@
+-- Months until the booster of a vaccine is due, used by the weekly vaccine digest.
+ALTER TABLE pet_health ADD COLUMN booster_months INTEGER NULL;
@@ -0,0 +1,2 @@
+-- Microchip number of the pet, 15 ISO digits or a 10 char legacy code.
+ALTER TABLE pet ADD COLUMN microchip TEXT DEFAULT NULL;
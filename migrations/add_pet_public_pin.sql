@@ -0,0 +1,2 @@
+-- Argon2 hash of the PIN that unlocks the owner contacts on the public profile.
+ALTER TABLE pet ADD COLUMN public_pin TEXT DEFAULT NULL;
@@ -0,0 +1,2 @@
+-- Reminders about a pet (e.g. vaccine boosters) are removed along with it, older reminders keep a NULL pet.
+ALTER TABLE reminder ADD COLUMN pet_id INTEGER NULL REFERENCES pet(id) ON DELETE SET NULL;
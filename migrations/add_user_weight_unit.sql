@@ -0,0 +1,2 @@
+-- Unit the user sees the weights in, they are always stored in kilograms.
+ALTER TABLE user_app ADD COLUMN weight_unit TEXT NOT NULL DEFAULT 'kg';
@@ -5,6 +5,7 @@ CREATE TABLE IF NOT EXISTS user_app(
     account_role    TEXT NOT NULL DEFAULT('user'),
     is_subscribed   BOOLEAN NOT NULL DEFAULT(0),
     is_enabled      BOOLEAN NOT NULL DEFAULT(1),
+    weight_unit     TEXT NOT NULL DEFAULT('kg'),
     created_at      TEXT NOT NULL DEFAULT (datetime('now','utc')),
     updated_at      TEXT NOT NULL DEFAULT (datetime('now','utc'))
 );
@@ -18,6 +19,7 @@ CREATE TABLE IF NOT EXISTS user_sub_payment(
     mp_paym_id              TEXT NOT NULL,
     payment_idempotency_h   TEXT NOT NULL,
     transaction_amount      TEXT NOT NULL,
+    currency                TEXT NOT NULL DEFAULT('MXN'),
     installments            NUMERIC NOT NULL DEFAULT(1),
     payment_method_id       TEXT NOT NULL,
     issuer_id               TEXT NOT NULL,
@@ -48,6 +50,8 @@ CREATE TABLE IF NOT EXISTS pet(
     is_lost                 BOOLEAN NOT NULL,
     is_spaying_neutering    BOOLEAN NOT NULL,
     pic                     TEXT DEFAULT NULL,
+    public_pin              TEXT DEFAULT NULL,
+    microchip               TEXT DEFAULT NULL,
     created_at              TEXT NOT NULL DEFAULT (datetime('now','utc')),
     updated_at              TEXT NOT NULL DEFAULT (datetime('now','utc'))
 );
@@ -63,10 +67,13 @@ CREATE TABLE IF NOT EXISTS pet_linked(
 CREATE TABLE IF NOT EXISTS pet_external_id(
   id                      INTEGER PRIMARY KEY,
   external_id             TEXT NOT NULL,
+  owner_user_id           INTEGER NULL REFERENCES user_app(id) ON DELETE SET NULL,
   created_at              TEXT NOT NULL DEFAULT (datetime('now','utc'))
 );
 CREATE INDEX IF NOT EXISTS idx_external_id_pet
 ON pet_external_id (external_id);
+CREATE INDEX IF NOT EXISTS idx_external_id_owner
+ON pet_external_id (owner_user_id);
 
 
 CREATE TABLE IF NOT EXISTS pet_weight(
@@ -82,7 +89,8 @@ CREATE TABLE IF NOT EXISTS pet_health(
     pet_id          INTEGER REFERENCES pet(id) ON DELETE CASCADE,
     health_record   TEXT NOT NULL,
     description     TEXT NOT NULL,
-    created_at      TEXT NOT NULL DEFAULT (datetime('now','utc'))
+    created_at      TEXT NOT NULL DEFAULT (datetime('now','utc')),
+    booster_months  INTEGER NULL
 );
 CREATE INDEX IF NOT EXISTS idx_pet_health_record_type
 ON pet_health (health_record);
@@ -93,9 +101,20 @@ CREATE TABLE IF NOT EXISTS owner_contact(
   user_app_id   INTEGER NOT NULL REFERENCES user_app(id) ON DELETE CASCADE,
   full_name     TEXT NOT NULL,
   contact_value TEXT NOT NULL,
+  contact_type  TEXT NOT NULL DEFAULT('other'),
+  is_primary    BOOLEAN NOT NULL DEFAULT 0,
+  sort_order    INTEGER NOT NULL DEFAULT 0,
+  address       TEXT DEFAULT NULL,
+  latitude      REAL DEFAULT NULL,
+  longitude     REAL DEFAULT NULL,
   created_at    TEXT NOT NULL DEFAULT (datetime('now','utc')),
+  normalized_value TEXT GENERATED ALWAYS AS (
+    lower(replace(replace(replace(replace(trim(contact_value),' ',''),'-',''),'(',''),')',''))
+  ) VIRTUAL,
   UNIQUE(contact_value)
 );
+CREATE UNIQUE INDEX IF NOT EXISTS idx_owner_contact_normalized_value
+ON owner_contact (user_app_id, normalized_value);
 
 
 CREATE TABLE IF NOT EXISTS pet_note(
@@ -118,8 +137,124 @@ CREATE TABLE IF NOT EXISTS reminder(
   notification_type     TEXT NOT NULL,
   send_at               TEXT NOT NULL DEFAULT (datetime('now','utc')),
   user_timezone         TEXT NOT NULL,
+  pet_id                INTEGER NULL REFERENCES pet(id) ON DELETE SET NULL,
   created_at            TEXT NOT NULL DEFAULT (datetime('now','utc')),
   UNIQUE(execution_id)
 );
 CREATE INDEX IF NOT EXISTS idx_reminder_execution
 ON reminder (execution_id);
+
+
+CREATE TABLE IF NOT EXISTS pet_creation_token(
+  token         TEXT PRIMARY KEY,
+  user_app_id   INTEGER NOT NULL REFERENCES user_app(id) ON DELETE CASCADE,
+  created_at    TEXT NOT NULL DEFAULT (datetime('now','utc'))
+);
+
+
+CREATE TABLE IF NOT EXISTS found_report(
+  id                INTEGER PRIMARY KEY,
+  pet_id            INTEGER NOT NULL REFERENCES pet(id) ON DELETE CASCADE,
+  message           TEXT NOT NULL,
+  callback_contact  TEXT NULL DEFAULT(NULL),
+  created_at        TEXT NOT NULL DEFAULT (datetime('now','utc'))
+);
+CREATE INDEX IF NOT EXISTS idx_found_report_pet
+ON found_report (pet_id);
+
+
+CREATE TABLE IF NOT EXISTS pet_access(
+  pet_id        INTEGER NOT NULL REFERENCES pet(id) ON DELETE CASCADE,
+  user_id       INTEGER NOT NULL REFERENCES user_app(id) ON DELETE CASCADE,
+  role          TEXT NOT NULL,
+  created_at    TEXT NOT NULL DEFAULT (datetime('now','utc')),
+  UNIQUE(pet_id, user_id)
+);
+CREATE INDEX IF NOT EXISTS idx_pet_access_user
+ON pet_access (user_id);
+
+-- Accounts that can see a pet, its owner and the ones it was shared with.
+-- Owners and editors can change it.
+CREATE VIEW IF NOT EXISTS pet_member AS
+SELECT p.id AS pet_id, p.user_app_id AS user_id, 1 AS can_edit FROM pet AS p
+UNION ALL
+SELECT pa.pet_id, pa.user_id, pa.role = 'editor' AS can_edit FROM pet_access AS pa;
+
+
+CREATE TABLE IF NOT EXISTS pet_transfer(
+  pet_id        INTEGER PRIMARY KEY REFERENCES pet(id) ON DELETE CASCADE,
+  from_user_id  INTEGER NOT NULL REFERENCES user_app(id) ON DELETE CASCADE,
+  to_user_id    INTEGER NOT NULL REFERENCES user_app(id) ON DELETE CASCADE,
+  created_at    TEXT NOT NULL DEFAULT (datetime('now','utc'))
+);
+CREATE INDEX IF NOT EXISTS idx_pet_transfer_to_user
+ON pet_transfer (to_user_id);
+
+
+CREATE TABLE IF NOT EXISTS pet_scan(
+  id            INTEGER PRIMARY KEY,
+  external_id   TEXT NOT NULL,
+  user_agent    TEXT NULL DEFAULT(NULL),
+  scanned_at    TEXT NOT NULL DEFAULT (datetime('now','utc'))
+);
+CREATE INDEX IF NOT EXISTS idx_pet_scan_external_id
+ON pet_scan (external_id, scanned_at);
+
+
+CREATE TABLE IF NOT EXISTS pet_share_token(
+  token         TEXT PRIMARY KEY,
+  pet_id        INTEGER NOT NULL REFERENCES pet(id) ON DELETE CASCADE,
+  expires_at    TEXT NOT NULL,
+  created_by    INTEGER NOT NULL REFERENCES user_app(id) ON DELETE CASCADE,
+  created_at    TEXT NOT NULL DEFAULT (datetime('now','utc'))
+);
+CREATE INDEX IF NOT EXISTS idx_pet_share_token_pet
+ON pet_share_token (pet_id);
+
+
+CREATE TABLE IF NOT EXISTS pet_pin_attempt(
+  pet_id        INTEGER PRIMARY KEY REFERENCES pet(id) ON DELETE CASCADE,
+  failed_count  INTEGER NOT NULL DEFAULT 0,
+  locked_until  TEXT NULL DEFAULT(NULL)
+);
+
+
+CREATE TABLE IF NOT EXISTS pet_audit(
+  id            INTEGER PRIMARY KEY,
+  pet_id        INTEGER NOT NULL REFERENCES pet(id) ON DELETE CASCADE,
+  user_id       INTEGER NOT NULL REFERENCES user_app(id) ON DELETE CASCADE,
+  action        TEXT NOT NULL,
+  field         TEXT NOT NULL,
+  created_at    TEXT NOT NULL DEFAULT (datetime('now','utc'))
+);
+CREATE INDEX IF NOT EXISTS idx_pet_audit_pet
+ON pet_audit (pet_id, created_at);
+
+
+CREATE TABLE IF NOT EXISTS message_delivery(
+  wamid         TEXT PRIMARY KEY,
+  reminder_id   INTEGER NULL REFERENCES reminder(id) ON DELETE CASCADE,
+  status        TEXT NOT NULL,
+  updated_at    TEXT NOT NULL DEFAULT (datetime('now','utc'))
+);
+CREATE INDEX IF NOT EXISTS idx_message_delivery_reminder
+ON message_delivery (reminder_id);
+
+
+CREATE TABLE IF NOT EXISTS reminder_template(
+  id            INTEGER PRIMARY KEY,
+  user_app_id   INTEGER NOT NULL REFERENCES user_app(id) ON DELETE CASCADE,
+  name          TEXT NOT NULL,
+  body          TEXT NOT NULL,
+  created_at    TEXT NOT NULL DEFAULT (datetime('now','utc'))
+);
+CREATE INDEX IF NOT EXISTS idx_reminder_template_user
+ON reminder_template (user_app_id);
+
+
+CREATE TABLE IF NOT EXISTS vaccine_digest_sent(
+  pet_health_id INTEGER NOT NULL REFERENCES pet_health(id) ON DELETE CASCADE,
+  status        TEXT NOT NULL,
+  sent_at       TEXT NOT NULL DEFAULT (datetime('now','utc')),
+  PRIMARY KEY(pet_health_id, status)
+);
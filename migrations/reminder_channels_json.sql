@@ -0,0 +1,2 @@
+-- Reminders can be sent through several channels, notification_type stores them as a JSON array.
+UPDATE reminder SET notification_type=json_array(notification_type) WHERE notification_type NOT LIKE '[%';
@@ -8,9 +8,18 @@ pub struct RunMigrationsArgs {
     file: String,
 }
 
+#[derive(Args, Debug, Clone)]
+pub struct MaintenanceArgs {
+    /// Prints the statements without running them
+    #[arg(long)]
+    dry_run: bool,
+}
+
 #[derive(Subcommand, Clone, Debug)]
 pub enum Action {
     RunMigrations(RunMigrationsArgs),
+    /// Integrity check, wal checkpoint, vacuum and analyze of the database
+    Maintenance(MaintenanceArgs),
 }
 
 /// Simple program to greet a person
@@ -29,6 +38,61 @@ impl AppArgs {
 
                 utils::run_migrations(&db_pool, file).await
             }
+            Action::Maintenance(MaintenanceArgs { dry_run: true }) => {
+                for statement in utils::MAINTENANCE_STATEMENTS {
+                    println!("{statement}");
+                }
+                Ok(())
+            }
+            Action::Maintenance(MaintenanceArgs { dry_run: false }) => {
+                let db_pool = utils::setup_sqlite_db_pool(config::APP_CONFIG.is_prod()).await?;
+
+                utils::run_maintenance(&db_pool).await
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_maintenance_action() {
+        let args = AppArgs::try_parse_from(["scripts", "maintenance", "--dry-run"]).unwrap();
+        assert!(matches!(
+            args.action,
+            Action::Maintenance(MaintenanceArgs { dry_run: true })
+        ));
+
+        let args = AppArgs::try_parse_from(["scripts", "maintenance"]).unwrap();
+        assert!(matches!(
+            args.action,
+            Action::Maintenance(MaintenanceArgs { dry_run: false })
+        ));
+    }
+
+    #[test]
+    fn test_maintenance_statements_order() {
+        assert_eq!(
+            utils::MAINTENANCE_STATEMENTS,
+            [
+                "PRAGMA integrity_check",
+                "PRAGMA wal_checkpoint(TRUNCATE)",
+                "VACUUM",
+                "ANALYZE",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_maintenance_on_healthy_db() {
+        let db_pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::query("CREATE TABLE pet (id INTEGER PRIMARY KEY, name TEXT)")
+            .execute(&db_pool)
+            .await
+            .unwrap();
+
+        assert!(utils::run_maintenance(&db_pool).await.is_ok());
+    }
+}
@@ -6,6 +6,13 @@ pub struct AppConfig {
     pub env: String,
     pub db_host: String,
     pub db_pass_encrypt: String,
+    /// Must match the SQLCipher settings of the web app
+    #[envconfig(default = "64000")]
+    pub db_cipher_kdf_iter: u64,
+    #[envconfig(default = "1024")]
+    pub db_cipher_page_size: u64,
+    #[envconfig(default = "SHA1")]
+    pub db_cipher_hmac_algorithm: String,
 }
 
 impl AppConfig {
@@ -15,15 +15,53 @@ pub async fn run_migrations(db_pool: &sqlx::SqlitePool, file_name: &str) -> anyh
     Ok(())
 }
 
+/// Statements of the scheduled maintenance, in the order they run. The
+/// integrity check goes first so a corrupted database isn't rewritten by
+/// the vacuum.
+pub const MAINTENANCE_STATEMENTS: [&str; 4] = [
+    "PRAGMA integrity_check",
+    "PRAGMA wal_checkpoint(TRUNCATE)",
+    "VACUUM",
+    "ANALYZE",
+];
+
+pub async fn run_maintenance(db_pool: &sqlx::SqlitePool) -> anyhow::Result<()> {
+    let [integrity_check, statements @ ..] = MAINTENANCE_STATEMENTS;
+
+    let problems: Vec<(String,)> = sqlx::query_as(integrity_check).fetch_all(db_pool).await?;
+    if problems.len() != 1 || problems[0].0 != "ok" {
+        let problems: Vec<String> = problems.into_iter().map(|(problem,)| problem).collect();
+        anyhow::bail!("integrity check failed:\n{}", problems.join("\n"));
+    }
+    println!("{integrity_check}: ok");
+
+    for statement in statements {
+        sqlx::query(statement).execute(db_pool).await?;
+        println!("{statement}: done");
+    }
+
+    Ok(())
+}
+
 pub async fn setup_sqlite_db_pool(encrypted: bool) -> anyhow::Result<SqlitePool> {
     if encrypted {
+        let hmac = config::APP_CONFIG
+            .db_cipher_hmac_algorithm
+            .trim()
+            .to_uppercase();
         return Ok(SqlitePool::connect_with(
             SqliteConnectOptions::from_str(&config::APP_CONFIG.db_host)?
                 .pragma("key", &config::APP_CONFIG.db_pass_encrypt)
-                .pragma("cipher_page_size", "1024")
-                .pragma("kdf_iter", "64000")
-                .pragma("cipher_hmac_algorithm", "HMAC_SHA1")
-                .pragma("cipher_kdf_algorithm", "PBKDF2_HMAC_SHA1")
+                .pragma(
+                    "cipher_page_size",
+                    config::APP_CONFIG.db_cipher_page_size.to_string(),
+                )
+                .pragma(
+                    "kdf_iter",
+                    config::APP_CONFIG.db_cipher_kdf_iter.to_string(),
+                )
+                .pragma("cipher_hmac_algorithm", format!("HMAC_{hmac}"))
+                .pragma("cipher_kdf_algorithm", format!("PBKDF2_HMAC_{hmac}"))
                 .pragma("foreign_keys", "ON")
                 .journal_mode(SqliteJournalMode::Delete),
         )
@@ -5,6 +5,11 @@ use std::sync::LazyLock;
 pub struct AppConfig {
     pub whatsapp_business_phone_number_id: u64,
     pub whatsapp_business_auth: String,
+    /// HTTP endpoint of the email provider, the email channel fails without it
+    pub email_send_endpoint: Option<String>,
+    pub email_api_token: Option<String>,
+    #[envconfig(default = "recordatorios@pet-info.link")]
+    pub email_from: String,
 }
 
 impl AppConfig {
@@ -4,19 +4,44 @@ use serde_json::json;
 
 use crate::config;
 
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    WhatsApp,
+    Email,
+}
+
+fn default_channels() -> Vec<Channel> {
+    vec![Channel::WhatsApp]
+}
+
 #[derive(Deserialize, Debug)]
 pub struct IncomingMessage {
-    phone: String,
+    #[serde(default = "default_channels")]
+    channels: Vec<Channel>,
+    phone: Option<String>,
+    email: Option<String>,
     body: String,
 }
 
+#[derive(Serialize, Debug)]
+pub struct ChannelResult {
+    channel: Channel,
+    sent: bool,
+    error: Option<String>,
+    /// WhatsApp message id (wamid), the web app matches delivery statuses with it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message_id: Option<String>,
+}
+
 #[derive(Serialize)]
 pub struct OutgoingMessage {
     req_id: String,
     msg: String,
+    results: Vec<ChannelResult>,
 }
 
-async fn send_msg(phone_number: &str, body: &str) -> Result<(), Error> {
+async fn send_msg(phone_number: &str, body: &str) -> Result<Option<String>, Error> {
     let response = reqwest::Client::new()
         .post(config::APP_CONFIG.whatsapp_send_msg_endpoint())
         .header("accept", "application/json")
@@ -41,30 +66,111 @@ async fn send_msg(phone_number: &str, body: &str) -> Result<(), Error> {
         .send()
         .await?;
 
-    if response.status().is_success() {
-        return Ok(());
-    }
-
+    let is_success = response.status().is_success();
     let response = response
         .json::<serde_json::Value>()
         .await
         .unwrap_or_default();
 
+    if is_success {
+        return Ok(response["messages"][0]["id"].as_str().map(String::from));
+    }
+
     Err(Box::new(simple_error::SimpleError::new(format!(
         "remainder did not send: {response}"
     ))))
 }
 
+async fn send_email(email: &str, body: &str) -> Result<(), Error> {
+    let (Some(endpoint), Some(token)) = (
+        config::APP_CONFIG.email_send_endpoint.as_ref(),
+        config::APP_CONFIG.email_api_token.as_ref(),
+    ) else {
+        return Err(Box::new(simple_error::SimpleError::new(
+            "email provider is not configured",
+        )));
+    };
+
+    let response = reqwest::Client::new()
+        .post(endpoint)
+        .header("accept", "application/json")
+        .header("content-type", "application/json")
+        .bearer_auth(token)
+        .json(&json!({
+            "from": config::APP_CONFIG.email_from,
+            "to": email,
+            "subject": "Recordatorio Pet-Info",
+            "text": body,
+        }))
+        .send()
+        .await?;
+
+    if response.status().is_success() {
+        return Ok(());
+    }
+
+    let response = response.text().await.unwrap_or_default();
+
+    Err(Box::new(simple_error::SimpleError::new(format!(
+        "email remainder did not send: {response}"
+    ))))
+}
+
+async fn send_to_channel(
+    channel: Channel,
+    payload: &IncomingMessage,
+) -> Result<Option<String>, Error> {
+    let contact = match channel {
+        Channel::WhatsApp => payload.phone.as_deref(),
+        Channel::Email => payload.email.as_deref(),
+    };
+    let Some(contact) = contact else {
+        return Err(Box::new(simple_error::SimpleError::new(format!(
+            "missing contact for channel {channel:?}"
+        ))));
+    };
+
+    match channel {
+        Channel::WhatsApp => send_msg(contact, &payload.body).await,
+        Channel::Email => send_email(contact, &payload.body).await.map(|_| None),
+    }
+}
+
 #[tracing::instrument()]
 pub async fn function_handler(
     event: LambdaEvent<IncomingMessage>,
 ) -> Result<OutgoingMessage, Error> {
     let payload = event.payload;
 
-    send_msg(&payload.phone, &payload.body).await?;
+    // a failing channel must not prevent sending the reminder through the others
+    let mut results = Vec::with_capacity(payload.channels.len());
+    for channel in payload.channels.iter().copied() {
+        let result = send_to_channel(channel, &payload).await;
+        if let Err(e) = &result {
+            tracing::error!("reminder not sent through {channel:?}: {e}");
+        }
+
+        let (sent, error, message_id) = match result {
+            Ok(message_id) => (true, None, message_id),
+            Err(e) => (false, Some(e.to_string()), None),
+        };
+        results.push(ChannelResult {
+            channel,
+            sent,
+            error,
+            message_id,
+        });
+    }
+
+    if !results.iter().any(|result| result.sent) {
+        return Err(Box::new(simple_error::SimpleError::new(format!(
+            "reminder was not sent through any channel: {results:?}"
+        ))));
+    }
 
     Ok(OutgoingMessage {
         req_id: event.context.request_id,
         msg: "reminder was sent".into(),
+        results,
     })
 }
@@ -18,6 +18,31 @@ locals {
       type        = "String"
       description = "CloudFront URL for serving images"
     }
+    PUBLIC_RATE_LIMIT_PER_MINUTE = {
+      value       = "60"
+      type        = "String"
+      description = "Max requests per minute per client IP on public profile routes"
+    }
+    MAX_PETS_PER_USER = {
+      value       = "20"
+      type        = "String"
+      description = "Hard limit of pets a single account can register"
+    }
+    PUBLIC_PROFILE_CACHE_TTL_SECS = {
+      value       = "60"
+      type        = "String"
+      description = "Seconds a public profile is served from the in-memory cache"
+    }
+    ALLOWED_ORIGINS = {
+      value       = "https://pet-info.link"
+      type        = "String"
+      description = "Comma separated origins allowed by CORS, besides the OAuth and payment providers"
+    }
+    STORAGE_BUCKET_NAME = {
+      value       = module.pet_info_bucket.info.name
+      type        = "String"
+      description = "S3 bucket storing the pet pictures"
+    }
   }
 
   # Merge default parameters with user-provided sensitive parameters
@@ -93,10 +118,12 @@ module "lambda_send_reminders" {
   env = {
     WHATSAPP_BUSINESS_PHONE_NUMBER_ID = var.sensitive_instance_envs["WHATSAPP_BUSINESS_PHONE_NUMBER_ID"].value
     WHATSAPP_BUSINESS_AUTH            = var.sensitive_instance_envs["WHATSAPP_BUSINESS_AUTH"].value
+    EMAIL_SEND_ENDPOINT               = var.sensitive_instance_envs["EMAIL_SEND_ENDPOINT"].value
+    EMAIL_API_TOKEN                   = var.sensitive_instance_envs["EMAIL_API_TOKEN"].value
   }
   lambda_details = {
     name         = "send_reminders"
-    desc         = "send a whats reminder to a user"
+    desc         = "send a whats or email reminder to a user"
     code_package = "lambda_package/send-reminders/out/bootstrap.zip"
   }
 }
@@ -152,6 +179,97 @@ module "send_reminders_step_function" {
   }
 }
 
+# Hourly call to the vaccine digest job, each owner gets it at the digest
+# time of their own timezone
+resource "aws_cloudwatch_event_connection" "scheduled_jobs" {
+  name               = "pet-info-scheduled-jobs"
+  authorization_type = "API_KEY"
+
+  auth_parameters {
+    api_key {
+      key   = "Authorization"
+      value = "Bearer ${var.sensitive_instance_envs["SCHEDULED_JOBS_TOKEN"].value}"
+    }
+  }
+}
+
+resource "aws_cloudwatch_event_api_destination" "vaccine_digest" {
+  name                             = "pet-info-vaccine-digest"
+  invocation_endpoint              = "https://pet-info.link/webhook/schedule/vaccine-digest"
+  http_method                      = "POST"
+  invocation_rate_limit_per_second = 1
+  connection_arn                   = aws_cloudwatch_event_connection.scheduled_jobs.arn
+}
+
+resource "aws_cloudwatch_event_rule" "vaccine_digest" {
+  name                = "pet-info-vaccine-digest"
+  description         = "send the weekly vaccine boosters digest"
+  schedule_expression = "cron(0 * * * ? *)"
+}
+
+resource "aws_iam_role" "scheduled_jobs" {
+  name = "pet-info-scheduled-jobs-role"
+
+  assume_role_policy = jsonencode({
+    Version = "2012-10-17"
+    Statement = [
+      {
+        Effect = "Allow"
+        Principal = {
+          Service = "events.amazonaws.com"
+        }
+        Action = "sts:AssumeRole"
+      }
+    ]
+  })
+}
+
+resource "aws_iam_role_policy" "scheduled_jobs" {
+  name = "pet-info-scheduled-jobs-policy"
+  role = aws_iam_role.scheduled_jobs.id
+
+  policy = jsonencode({
+    Version = "2012-10-17"
+    Statement = [
+      {
+        Effect   = "Allow"
+        Action   = "events:InvokeApiDestination"
+        Resource = [
+          aws_cloudwatch_event_api_destination.vaccine_digest.arn,
+          aws_cloudwatch_event_api_destination.purge_scans.arn,
+        ]
+      }
+    ]
+  })
+}
+
+resource "aws_cloudwatch_event_target" "vaccine_digest" {
+  rule     = aws_cloudwatch_event_rule.vaccine_digest.name
+  arn      = aws_cloudwatch_event_api_destination.vaccine_digest.arn
+  role_arn = aws_iam_role.scheduled_jobs.arn
+}
+
+# Daily purge of the public profile visits past their retention
+resource "aws_cloudwatch_event_api_destination" "purge_scans" {
+  name                             = "pet-info-purge-scans"
+  invocation_endpoint              = "https://pet-info.link/webhook/schedule/purge-scans"
+  http_method                      = "POST"
+  invocation_rate_limit_per_second = 1
+  connection_arn                   = aws_cloudwatch_event_connection.scheduled_jobs.arn
+}
+
+resource "aws_cloudwatch_event_rule" "purge_scans" {
+  name                = "pet-info-purge-scans"
+  description         = "delete the public profile visits past their retention"
+  schedule_expression = "cron(30 9 * * ? *)"
+}
+
+resource "aws_cloudwatch_event_target" "purge_scans" {
+  rule     = aws_cloudwatch_event_rule.purge_scans.name
+  arn      = aws_cloudwatch_event_api_destination.purge_scans.arn
+  role_arn = aws_iam_role.scheduled_jobs.arn
+}
+
 module "pet_info_role" {
   source = "./modules/role"
 
@@ -0,0 +1,37 @@
+//! # Admin API Module
+//!
+//! Usage metrics shown to admin accounts.
+
+use crate::repo;
+use serde::Serialize;
+
+/// Days back counted as recent payments
+const RECENT_PAYMENTS_DAYS: i64 = 30;
+
+/// Summary of the service usage
+#[derive(Serialize, Debug, PartialEq)]
+pub struct UsageMetrics {
+    pub total_users: i64,
+    pub subscribed_users: i64,
+    pub total_pets: i64,
+    /// Approved payments of the last [`RECENT_PAYMENTS_DAYS`] days
+    pub recent_payments: i64,
+}
+
+/// Collects the usage metrics from the repository aggregates.
+///
+/// # Arguments
+/// * `repo` - Repository instance for database operations
+///
+/// # Returns
+/// * `anyhow::Result<UsageMetrics>` - Current usage counts
+pub async fn get_usage_metrics(repo: &repo::ImplAppRepo) -> anyhow::Result<UsageMetrics> {
+    let since = chrono::Utc::now() - chrono::Duration::days(RECENT_PAYMENTS_DAYS);
+
+    Ok(UsageMetrics {
+        total_users: repo.count_users().await?,
+        subscribed_users: repo.count_subscribed_users().await?,
+        total_pets: repo.count_pets().await?,
+        recent_payments: repo.count_approved_payments_since(since).await?,
+    })
+}
@@ -0,0 +1,222 @@
+//! # Breed API Module
+//!
+//! This module provides breed lookups backed by the known breed list in
+//! [`consts::PET_BREEDS`]. The pet `breed` column is free text, these
+//! functions only help users pick a known breed and catch typos.
+
+use crate::consts;
+
+/// Max number of breeds returned by a search.
+const MAX_BREED_RESULTS: usize = 10;
+
+/// Known dog breeds of large or giant size, they age faster than smaller dogs.
+const LARGE_DOG_BREEDS: [&str; 14] = [
+    "Akita",
+    "Boxer",
+    "Cane Corso",
+    "Doberman",
+    "Dálmata",
+    "Gran Danés",
+    "Golden Retriever",
+    "Labrador Retriever",
+    "Malamute de Alaska",
+    "Pastor Alemán",
+    "Pastor Belga Malinois",
+    "Rottweiler",
+    "San Bernardo",
+    "Weimaraner",
+];
+
+/// Normalizes a breed name for comparison.
+///
+/// Lowercases the text, removes Spanish accents and trims whitespace.
+fn normalize(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'á' => 'a',
+            'é' => 'e',
+            'í' => 'i',
+            'ó' => 'o',
+            'ú' | 'ü' => 'u',
+            'ñ' => 'n',
+            c => c,
+        })
+        .collect()
+}
+
+/// Computes the Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b_chars.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b_chars.len() + 1];
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let cost = usize::from(a_char != *b_char);
+            current[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(current[j] + 1);
+        }
+        prev = current;
+    }
+
+    prev[b_chars.len()]
+}
+
+/// Returns the known breeds of a species, or of all species if `None`.
+fn known_breeds(species: Option<&str>) -> Vec<&'static String> {
+    match species {
+        Some(species) => consts::PET_BREEDS
+            .get(&normalize(species))
+            .map(|breeds| breeds.iter().collect())
+            .unwrap_or_default(),
+        None => consts::PET_BREEDS.values().flatten().collect(),
+    }
+}
+
+/// Guesses the species of a pet from its breed.
+///
+/// # Returns
+/// * `Option<&'static str>` - The species (`dog`, `cat`, `bird`) if the breed is
+///   known for only one of them
+pub fn species_of(breed: &str) -> Option<&'static str> {
+    let breed = normalize(breed);
+    let mut species = consts::PET_BREEDS
+        .iter()
+        .filter(|(_, breeds)| breeds.iter().any(|known| normalize(known) == breed))
+        .map(|(species, _)| species.as_str());
+
+    match (species.next(), species.next()) {
+        (Some(species), None) => Some(species),
+        _ => None,
+    }
+}
+
+/// Checks if the breed is a known large or giant dog breed.
+///
+/// The comparison ignores case and accents.
+pub fn is_large_dog_breed(breed: &str) -> bool {
+    let breed = normalize(breed);
+    LARGE_DOG_BREEDS
+        .iter()
+        .any(|known| normalize(known) == breed)
+}
+
+/// Gets the adult weight range of a breed from [`consts::BREED_WEIGHT_RANGES_KG`].
+///
+/// The comparison ignores case and accents.
+///
+/// # Returns
+/// * `Option<(f64, f64)>` - `(min, max)` in kilograms, `None` for unknown breeds
+pub fn standard_weight_range(breed: &str) -> Option<(f64, f64)> {
+    let breed = normalize(breed);
+    consts::BREED_WEIGHT_RANGES_KG
+        .iter()
+        .find(|(known, _)| normalize(known) == breed)
+        .map(|(_, range)| *range)
+}
+
+/// Searches known breeds containing the query text.
+///
+/// The comparison ignores case and accents.
+///
+/// # Arguments
+/// * `species` - Optional species (`dog`, `cat`, `bird`) to search in; all species if `None`
+/// * `query` - Text typed by the user
+///
+/// # Returns
+/// * `Vec<String>` - Matching breed names, sorted and deduplicated
+pub fn search_breeds(species: Option<&str>, query: &str) -> Vec<String> {
+    let query = normalize(query);
+
+    let mut breeds: Vec<String> = known_breeds(species)
+        .into_iter()
+        .filter(|breed| normalize(breed).contains(&query))
+        .cloned()
+        .collect();
+    breeds.sort();
+    breeds.dedup();
+    breeds.truncate(MAX_BREED_RESULTS);
+
+    breeds
+}
+
+/// Suggests the closest known breed for a breed typed by the user.
+///
+/// # Arguments
+/// * `species` - Optional species (`dog`, `cat`, `bird`) to search in; all species if `None`
+/// * `breed` - Breed typed by the user
+///
+/// # Returns
+/// * `Option<String>` - `None` if the breed is known or nothing is close enough,
+///   otherwise the closest known breed
+pub fn did_you_mean(species: Option<&str>, breed: &str) -> Option<String> {
+    let breed = normalize(breed);
+    if breed.is_empty() {
+        return None;
+    }
+
+    let max_distance = (breed.chars().count() / 4).max(2);
+
+    known_breeds(species)
+        .into_iter()
+        .map(|known| (edit_distance(&breed, &normalize(known)), known))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .filter(|(distance, _)| *distance > 0)
+        .map(|(_, known)| known.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_breeds() {
+        let breeds = search_breeds(Some("dog"), "lab");
+        assert_eq!(breeds, vec!["Labrador Retriever".to_string()]);
+
+        // accents and case are ignored
+        let breeds = search_breeds(Some("dog"), "PASTOR ALEMAN");
+        assert_eq!(breeds, vec!["Pastor Alemán".to_string()]);
+
+        assert!(search_breeds(Some("fish"), "lab").is_empty());
+        assert!(search_breeds(None, "siam").contains(&"Siamés".to_string()));
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        assert_eq!(
+            did_you_mean(Some("dog"), "Labrdor Retriver"),
+            Some("Labrador Retriever".to_string())
+        );
+        assert_eq!(did_you_mean(None, "chihuahua"), None);
+        assert_eq!(did_you_mean(None, "pastor aleman"), None);
+        assert_eq!(did_you_mean(None, "zzzzzzzz"), None);
+        assert_eq!(did_you_mean(None, ""), None);
+    }
+
+    #[test]
+    fn test_species_of() {
+        assert_eq!(species_of("pastor aleman"), Some("dog"));
+        assert_eq!(species_of("Siamés"), Some("cat"));
+        assert_eq!(species_of("periquito australiano"), Some("bird"));
+        // known for both species
+        assert_eq!(species_of("Mestizo"), None);
+        assert_eq!(species_of("Ornitorrinco"), None);
+    }
+
+    #[test]
+    fn test_standard_weight_range() {
+        assert_eq!(standard_weight_range("beagle"), Some((9.0, 14.0)));
+        assert_eq!(standard_weight_range(" DALMATA "), Some((20.0, 32.0)));
+        assert_eq!(standard_weight_range("Mestizo"), None);
+
+        // every range is usable
+        assert!(
+            consts::BREED_WEIGHT_RANGES_KG
+                .values()
+                .all(|(min, max)| 0.0 < *min && min < max)
+        );
+    }
+}
@@ -0,0 +1,92 @@
+//! # ISO 8601 Dates Module
+//!
+//! Serde `with` module giving every date of the API schemas the same RFC3339
+//! shape, `2024-01-15T09:30:00Z`, whether the field is a date or a naive
+//! datetime. Naive values are stored in UTC, so they get the `Z` offset.
+//!
+//! ```ignore
+//! #[derive(Serialize)]
+//! struct Schema {
+//!     #[serde(with = "api::fmt_iso8601")]
+//!     date: NaiveDateTime,
+//!     #[serde(with = "api::fmt_iso8601::option")]
+//!     last_date: Option<NaiveDate>,
+//! }
+//! ```
+
+use chrono::{NaiveDate, NaiveDateTime, SecondsFormat};
+use serde::Serializer;
+
+/// Dates and datetimes that can be written as an RFC3339 string
+pub trait ToIso8601 {
+    /// RFC3339 string in UTC with seconds precision
+    fn to_iso8601(&self) -> String;
+}
+
+impl ToIso8601 for NaiveDateTime {
+    fn to_iso8601(&self) -> String {
+        self.and_utc().to_rfc3339_opts(SecondsFormat::Secs, true)
+    }
+}
+
+impl ToIso8601 for NaiveDate {
+    fn to_iso8601(&self) -> String {
+        NaiveDateTime::from(*self).to_iso8601()
+    }
+}
+
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: ToIso8601,
+    S: Serializer,
+{
+    serializer.serialize_str(&value.to_iso8601())
+}
+
+/// Same format for optional fields, `None` stays `null`
+pub mod option {
+    use super::ToIso8601;
+    use serde::Serializer;
+
+    pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: ToIso8601,
+        S: Serializer,
+    {
+        match value {
+            Some(value) => super::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct Dates {
+        #[serde(with = "crate::api::fmt_iso8601")]
+        datetime: NaiveDateTime,
+        #[serde(with = "crate::api::fmt_iso8601::option")]
+        date: Option<NaiveDate>,
+        #[serde(with = "crate::api::fmt_iso8601::option")]
+        missing: Option<NaiveDate>,
+    }
+
+    #[test]
+    fn test_dates_serialize_as_rfc3339() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let dates = Dates {
+            datetime: date.and_hms_milli_opt(9, 30, 5, 250).unwrap(),
+            date: Some(date),
+            missing: None,
+        };
+
+        assert_eq!(
+            serde_json::to_string(&dates).unwrap(),
+            r#"{"datetime":"2024-01-15T09:30:05Z","date":"2024-01-15T00:00:00Z","missing":null}"#
+        );
+    }
+}
@@ -6,16 +6,28 @@
 //!
 //! ## Modules
 //!
+//! - [`admin`] - Usage metrics for admin accounts
+//! - [`breed`] - Breed lookup and suggestions for the pet form
+//! - [`fmt_iso8601`] - Common RFC3339 format of the serialized dates
 //! - [`passes`] - Apple Wallet pass generation and handling
 //! - [`payment`] - Payment processing and billing operations
 //! - [`pdf_handler`] - PDF generation and report handling
 //! - [`pet`] - Pet management, profiles, and health records
+//! - [`profile_cache`] - Cached public profiles of pets
 //! - [`reminder`] - Notification and reminder systems
+//! - [`thumbnail`] - Cached thumbnails of pet pictures
 //! - [`user`] - User management and authentication
+//! - [`verification_throttle`] - Limit of phone verification codes sent per user
 
+pub mod admin;
+pub mod breed;
+pub mod fmt_iso8601;
 pub mod passes;
 pub mod payment;
 pub mod pdf_handler;
 pub mod pet;
+pub mod profile_cache;
 pub mod reminder;
+pub mod thumbnail;
 pub mod user;
+pub mod verification_throttle;
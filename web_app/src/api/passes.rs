@@ -20,7 +20,12 @@
 //! - **Auxiliary Fields**: Spay/neuter status and sex
 //! - **Back Fields**: Pet ID and additional details
 //! - **QR Code**: Links to the pet's public profile
-//! - **Icon**: Pet photo (if available) or default icon
+//! - **Icon**: Pet photo (if available) or a default icon of its species
+//!
+//! ## Web Preview
+//!
+//! Owners without an Apple device can look at an HTML approximation of the
+//! pass front, built from the same [`PassFields`] and colors as the pass.
 //!
 //! ## Security & Certificates
 //!
@@ -37,11 +42,16 @@
 //! - Spanish to English text conversion for better compatibility
 //! - Unicode character sanitization
 
-use crate::{api::pet::PetPublicInfoSchema, consts, services};
-use anyhow::{Context, Result};
+use crate::{
+    api::{breed, pet::PetPublicInfoSchema, thumbnail::PassThumbnails},
+    models::user_app::WeightUnit,
+    qr,
+};
+use anyhow::Result;
+use base64::{Engine, prelude::BASE64_STANDARD};
 use chrono::{Duration, Utc};
-use image::ImageEncoder;
 use passes::{Package, resource, sign};
+use serde::Serialize;
 use std::io::Cursor;
 
 /// Configuration constants for Apple Wallet passes
@@ -70,6 +80,155 @@ mod pass_config {
     pub const LABEL_COLOR: &str = "rgb(245, 245, 245)";
 }
 
+/// A labeled value shown on the pass
+#[derive(Debug, Serialize)]
+pub struct PassField {
+    /// Unique key of the field within the pass
+    pub key: &'static str,
+    /// Label shown above the value
+    pub label: &'static str,
+    /// Displayed value
+    pub value: String,
+}
+
+/// Fields of the pass, grouped as in Apple's generic pass template.
+///
+/// Shared by the pass.json and the web preview so both show the same data.
+///
+/// ## Field Structure
+/// - **Primary Fields**: Most prominent display (pet name)
+/// - **Secondary Fields**: Secondary importance (breed, age, life stage)
+/// - **Auxiliary Fields**: Additional details (sex, weight, spay/neuter status)
+/// - **Back Fields**: Detailed information shown on pass back
+///
+/// ## Localization
+/// Currently uses Spanish labels to match the application's primary language.
+#[derive(Debug, Serialize)]
+pub struct PassFields {
+    pub primary: Vec<PassField>,
+    pub secondary: Vec<PassField>,
+    pub auxiliary: Vec<PassField>,
+    pub back: Vec<PassField>,
+}
+
+impl PassFields {
+    /// Builds the pass fields of a pet.
+    ///
+    /// ## Parameters
+    /// - `pet_info`: Pet information schema containing all displayable data
+    /// - `weight_unit`: Unit the weight is shown in
+    pub fn new(pet_info: &PetPublicInfoSchema, weight_unit: WeightUnit) -> Self {
+        Self {
+            primary: vec![PassField {
+                key: "name",
+                label: "Nombre",
+                value: pet_info.name.clone(),
+            }],
+            secondary: vec![
+                PassField {
+                    key: "breed",
+                    label: "Raza",
+                    value: pet_info.pet_breed.clone(),
+                },
+                PassField {
+                    key: "age",
+                    label: "Edad",
+                    value: pet_info.fmt_age.clone(),
+                },
+                PassField {
+                    key: "life_stage",
+                    label: "Etapa",
+                    value: pet_info.life_stage.to_string(),
+                },
+            ],
+            auxiliary: vec![
+                PassField {
+                    key: "sex",
+                    label: "Sexo",
+                    value: format_sex_spanish(&pet_info.sex),
+                },
+                PassField {
+                    key: "weight",
+                    label: "Peso",
+                    value: format_weight(&pet_info.last_weight, weight_unit),
+                },
+                PassField {
+                    key: "spayed",
+                    label: "Esterilizado/a",
+                    value: if pet_info.is_spaying_neutering {
+                        "Sí"
+                    } else {
+                        "No"
+                    }
+                    .to_string(),
+                },
+            ],
+            back: create_back_fields(pet_info),
+        }
+    }
+
+    /// Generic pass fields structure of the pass.json
+    ///
+    /// ## Returns
+    /// A `serde_json::Value` containing the complete generic field structure
+    fn to_generic_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "primaryFields": self.primary,
+            "secondaryFields": self.secondary,
+            "auxiliaryFields": self.auxiliary,
+            "backFields": self.back,
+            "headerFields": [] // Empty for generic passes
+        })
+    }
+}
+
+/// HTML approximation of the pass front for owners on non-Apple devices.
+#[derive(Debug, Serialize)]
+pub struct PassPreview {
+    /// Text next to the logo, as on the pass
+    pub logo_text: String,
+    pub foreground_color: &'static str,
+    pub background_color: &'static str,
+    pub label_color: &'static str,
+    pub fields: PassFields,
+    /// Public profile url encoded in the QR code
+    pub profile_url: String,
+    /// QR code as a PNG data URI
+    pub qr_code: String,
+    /// Pet photo thumbnail as a PNG data URI, `None` without a photo
+    pub thumbnail: Option<String>,
+}
+
+/// Builds the web preview of a pet pass.
+///
+/// ## Parameters
+/// - `pet_info`: Pet information schema containing all displayable data
+/// - `thumbnails`: Thumbnails of the pet photo, if any; the standard one is shown
+/// - `weight_unit`: Unit the weight is shown in
+///
+/// ## Errors
+/// Fails if the QR code can't be generated
+pub fn build_pass_preview(
+    pet_info: &PetPublicInfoSchema,
+    thumbnails: Option<&PassThumbnails>,
+    weight_unit: WeightUnit,
+) -> Result<PassPreview> {
+    let profile_url = profile_url(pet_info);
+    let png_data_uri =
+        |png: &[u8]| format!("data:image/png;base64,{}", BASE64_STANDARD.encode(png));
+
+    Ok(PassPreview {
+        logo_text: logo_text(pet_info),
+        foreground_color: pass_config::FOREGROUND_COLOR,
+        background_color: pass_config::BACKGROUND_COLOR,
+        label_color: pass_config::LABEL_COLOR,
+        fields: PassFields::new(pet_info, weight_unit),
+        qr_code: png_data_uri(&qr::get_qr_code(&profile_url)?),
+        thumbnail: thumbnails.map(|thumbnails| png_data_uri(&thumbnails.standard)),
+        profile_url,
+    })
+}
+
 /// Generates a properly signed Apple Wallet pass for a pet.
 ///
 /// This is the main entry point for creating Apple Wallet passes. It orchestrates
@@ -84,7 +243,8 @@ mod pass_config {
 ///
 /// ## Parameters
 /// - `pet_info`: Pet information schema containing all displayable data
-/// - `storage_service`: Service for retrieving pet photos and other assets
+/// - `thumbnails`: Standard and @2x PNG thumbnails of the pet photo, if any
+/// - `weight_unit`: Unit the weight is shown in
 ///
 /// ## Returns
 /// - `Ok(Vec<u8>)`: Binary .pkpass file data ready for download
@@ -96,16 +256,18 @@ mod pass_config {
 /// - Invalid pet information data
 /// - File system errors when loading resources
 /// - Pass signing/packaging failures
-pub async fn generate_pet_pass(
+pub fn generate_pet_pass(
     pet_info: &PetPublicInfoSchema,
-    storage_service: &services::ImplStorageService,
+    thumbnails: Option<&PassThumbnails>,
+    weight_unit: WeightUnit,
 ) -> Result<Vec<u8>> {
-    let pass_schema = create_pass_schema(pet_info);
+    let pass_schema = create_pass_schema(pet_info, weight_unit);
     let pass = passes::Pass::from_json(&pass_schema.to_string())?;
 
     let mut package = create_signed_package(pass)?;
 
-    add_pass_resources(&mut package, storage_service, &pet_info.pic_path).await?;
+    let species = breed::species_of(&pet_info.pet_breed);
+    add_pass_resources(&mut package, thumbnails, species)?;
 
     generate_pkpass_bytes(package)
 }
@@ -125,13 +287,18 @@ pub async fn generate_pet_pass(
 ///
 /// ## Parameters
 /// - `pet_info`: Pet information schema containing all data to display
+/// - `weight_unit`: Unit the weight is shown in
 ///
 /// ## Returns
 /// A `serde_json::Value` representing the complete pass.json structure
-fn create_pass_schema(pet_info: &PetPublicInfoSchema) -> serde_json::Value {
+fn create_pass_schema(
+    pet_info: &PetPublicInfoSchema,
+    weight_unit: WeightUnit,
+) -> serde_json::Value {
     let now = Utc::now();
     let expiration = now + Duration::days(365);
     let pet_name = pet_info.name.to_uppercase();
+    let fields = PassFields::new(pet_info, weight_unit);
 
     serde_json::json!({
         "formatVersion": 1,
@@ -140,7 +307,7 @@ fn create_pass_schema(pet_info: &PetPublicInfoSchema) -> serde_json::Value {
         "passTypeIdentifier": pass_config::PASS_TYPE_IDENTIFIER,
         "teamIdentifier": pass_config::TEAM_IDENTIFIER,
         "serialNumber": pet_info.external_id,
-        "logoText": format!("🐾 Pet-Info {}", &pet_name),
+        "logoText": logo_text(pet_info),
 
         // iOS 18.5 styling
         "labelColor": pass_config::LABEL_COLOR,
@@ -152,17 +319,27 @@ fn create_pass_schema(pet_info: &PetPublicInfoSchema) -> serde_json::Value {
         "expirationDate": expiration.format(pass_config::DATE_FORMAT).to_string(),
 
         "barcodes": [{
-            "message": format!("https://pet-info.link/info/{}", pet_info.external_id),
+            "message": profile_url(pet_info),
             "format": "PKBarcodeFormatQR",
             "altText": "Perfil público de la mascota",
             "messageEncoding": "iso-8859-1"
         }],
 
         // Pass content
-        "generic": create_generic_fields(pet_info)
+        "generic": fields.to_generic_json()
     })
 }
 
+/// Text shown next to the pass logo
+fn logo_text(pet_info: &PetPublicInfoSchema) -> String {
+    format!("🐾 Pet-Info {}", pet_info.name.to_uppercase())
+}
+
+/// Public profile url encoded in the pass QR code
+fn profile_url(pet_info: &PetPublicInfoSchema) -> String {
+    format!("https://pet-info.link/info/{}", pet_info.external_id)
+}
+
 /// Formats sex in Spanish for display on the pass.
 ///
 /// Converts the Sex enum to appropriate Spanish text.
@@ -186,12 +363,13 @@ fn format_sex_spanish(sex: &crate::api::pet::Sex) -> String {
 ///
 /// ## Parameters
 /// - `weight`: Optional weight value in kilograms
+/// - `weight_unit`: Unit the weight is shown in
 ///
 /// ## Returns
-/// Formatted weight string (e.g., "15.5 kg") or "No registrado"
-fn format_weight(weight: &Option<f64>) -> String {
+/// Formatted weight string (e.g., "15.50 kg") or "No registrado"
+fn format_weight(weight: &Option<f64>, weight_unit: WeightUnit) -> String {
     weight
-        .map(|w| format!("{:.1} kg", w))
+        .map(|w| crate::api::pet::format_weight_with_unit(w, weight_unit))
         .unwrap_or_else(|| "No registrado".to_string())
 }
 
@@ -210,97 +388,47 @@ fn convert_html_to_text(html_content: &str) -> String {
     html2text::from_read(html_content.as_bytes(), 80).unwrap_or_else(|_| html_content.to_string())
 }
 
-/// Creates the generic pass fields structure.
-///
-/// This function defines the layout and content of the pass using Apple's generic
-/// pass template. The generic template provides maximum flexibility and is suitable
-/// for displaying various types of information.
-///
-/// ## Field Structure
-/// - **Primary Fields**: Most prominent display (pet name)
-/// - **Secondary Fields**: Secondary importance (age, breed)  
-/// - **Auxiliary Fields**: Additional details (spay/neuter status, sex)
-/// - **Back Fields**: Detailed information shown on pass back
-/// - **Header Fields**: Top-level info (currently empty for generic passes)
-///
-/// ## Localization
-/// Currently uses Spanish labels to match the application's primary language.
-/// Can be easily modified to support multiple languages based on user preferences.
-///
-/// ## Parameters
-/// - `pet_info`: Pet information schema containing all displayable data
-///
-/// ## Returns
-/// A `serde_json::Value` containing the complete generic field structure
-fn create_generic_fields(pet_info: &PetPublicInfoSchema) -> serde_json::Value {
-    serde_json::json!({
-        "primaryFields": [
-            {
-                "key": "name",
-                "label": "Nombre",
-                "value": &pet_info.name,
-            },
-        ],
-        "secondaryFields": [
-            {
-                "key": "breed",
-                "label": "Raza",
-                "value": &pet_info.pet_breed
-            },
-            {
-                "key": "age",
-                "label": "Edad",
-                "value": &pet_info.fmt_age
-            },
-        ],
-        "auxiliaryFields": [
-            {
-                "key": "sex",
-                "label": "Sexo",
-                "value": format_sex_spanish(&pet_info.sex)
-            },
-            {
-                "key": "weight",
-                "label": "Peso",
-                "value": format_weight(&pet_info.last_weight)
-            },
-            {
-                "key": "spayed",
-                "label": "Esterilizado/a",
-                "value": if pet_info.is_spaying_neutering { "Sí" } else { "No" }
-            },
-        ],
-        "backFields": create_back_fields(pet_info),
-        "headerFields": [] // Empty for generic passes
-    })
-}
-
 /// Creates back fields with essential pet information.
 ///
 /// Back fields are displayed when the user flips the pass over in Apple Wallet.
 /// This is where detailed information is shown that doesn't fit on the front.
-/// Includes the pet's unique identifier and the about section (with HTML converted to text).
+/// Includes the pet's unique identifier, when the profile was last updated and
+/// the about section (with HTML converted to text).
 ///
 /// ## Parameters
 /// - `pet_info`: Pet information schema
 ///
 /// ## Returns
-/// A vector of `serde_json::Value` objects representing each back field
-fn create_back_fields(pet_info: &PetPublicInfoSchema) -> Vec<serde_json::Value> {
-    let mut fields = vec![serde_json::json!({
-        "key": "pet_id",
-        "label": "ID de Mascota",
-        "value": pet_info.external_id
-    })];
+/// A vector of [`PassField`] representing each back field
+fn create_back_fields(pet_info: &PetPublicInfoSchema) -> Vec<PassField> {
+    let mut fields = vec![
+        PassField {
+            key: "pet_id",
+            label: "ID de Mascota",
+            value: pet_info.external_id.clone(),
+        },
+        PassField {
+            key: "last_updated",
+            label: "Última actualización",
+            value: pet_info.fmt_last_updated.clone(),
+        },
+    ];
+
+    if let Some(microchip) = &pet_info.microchip {
+        fields.push(PassField {
+            key: "microchip",
+            label: "Microchip",
+            value: microchip.clone(),
+        });
+    }
 
     // Add about section if not empty
     if !pet_info.about_pet.is_empty() {
-        let about_text = convert_html_to_text(&pet_info.about_pet);
-        fields.push(serde_json::json!({
-            "key": "about",
-            "label": "Acerca de",
-            "value": about_text
-        }));
+        fields.push(PassField {
+            key: "about",
+            label: "Acerca de",
+            value: convert_html_to_text(&pet_info.about_pet),
+        });
     }
 
     fields
@@ -346,64 +474,76 @@ fn create_signed_package(pass: passes::Pass) -> Result<Package> {
     Ok(package)
 }
 
-/// Convert to PNG format (Apple Wallet requirement - all images must be PNG)
-/// Resize to optimal thumbnail dimensions for @2x Retina displays
-fn build_thumbnail(image_bytes: Vec<u8>) -> Result<Vec<u8>> {
-    let img = image::load_from_memory(&image_bytes).context("Failed to load pet image for pass")?;
-
-    // Resize to Apple Wallet thumbnail dimensions using Lanczos3 for high-quality downsampling
-    let resized = img.resize_to_fill(
-        consts::PKPASS_THUMBNAIL_SIZE_PX,
-        consts::PKPASS_THUMBNAIL_SIZE_PX,
-        image::imageops::FilterType::Lanczos3,
-    );
-
-    // Encode to PNG with maximum compression for smallest file size
-    let mut png_bytes = Vec::new();
-    let encoder = image::codecs::png::PngEncoder::new_with_quality(
-        Cursor::new(&mut png_bytes),
-        image::codecs::png::CompressionType::Best,
-        image::codecs::png::FilterType::Adaptive,
-    );
-    encoder
-        .write_image(
-            resized.as_bytes(),
-            resized.width(),
-            resized.height(),
-            resized.color().into(),
+/// Default icon bundled in `web/static/images`, as its file name and bytes.
+macro_rules! pass_icon {
+    ($file:literal) => {
+        (
+            $file,
+            include_bytes!(concat!("../../web/static/images/", $file)) as &[u8],
         )
-        .context("Failed to encode image as optimized PNG")?;
+    };
+}
 
-    Ok(png_bytes)
+/// App icon, used when the pet has a photo or its species is unknown.
+const GENERIC_PASS_ICON: (&str, &[u8]) = pass_icon!("maskable-512.png");
+
+/// Picks the default pass icon of a pet without photo.
+///
+/// ## Parameters
+/// - `species`: The pet's species (`dog`, `cat`, `bird`) if known
+///
+/// ## Returns
+/// The icon file name and bytes, the generic app icon for any other species
+fn default_pass_icon(species: Option<&str>) -> (&'static str, &'static [u8]) {
+    match species {
+        Some("dog") => pass_icon!("pass_icon_dog.png"),
+        Some("cat") => pass_icon!("pass_icon_cat.png"),
+        Some("bird") => pass_icon!("pass_icon_bird.png"),
+        _ => GENERIC_PASS_ICON,
+    }
 }
 
 /// Adds visual resources to the pass package.
 ///
 /// This function adds icons and images to make the pass visually appealing.
-/// Resources include a default icon and optionally the pet's photo if available.
-async fn add_pass_resources(
+/// Resources include an icon and optionally the pet's photo if available,
+/// as `thumbnail` and, when the photo is big enough, `thumbnail@2x`.
+/// Pets without photo get the default icon of their species, see
+/// [`default_pass_icon`].
+/// The photos must already be PNG thumbnails (Apple Wallet requirement).
+fn add_pass_resources(
     package: &mut Package,
-    storage_service: &services::ImplStorageService,
-    pic_path: &str,
+    thumbnails: Option<&PassThumbnails>,
+    species: Option<&str>,
 ) -> Result<()> {
-    let icon_data = include_bytes!("../../web/static/images/maskable-512.png");
+    let (_, icon_data) = match thumbnails {
+        Some(_) => GENERIC_PASS_ICON,
+        None => default_pass_icon(species),
+    };
     package
-        .add_resource(
-            resource::Type::Icon(resource::Version::Standard),
-            &icon_data[..],
-        )
+        .add_resource(resource::Type::Icon(resource::Version::Standard), icon_data)
         .map_err(|e| anyhow::anyhow!("Failed to add icon resource: {}", e))?;
 
-    let image_bytes = storage_service.get_pic_as_bytes(pic_path).await?;
-    let image_bytes = build_thumbnail(image_bytes)?;
+    let Some(thumbnails) = thumbnails else {
+        return Ok(());
+    };
 
     package
         .add_resource(
             resource::Type::Thumbnail(resource::Version::Standard),
-            &image_bytes[..],
+            &thumbnails.standard[..],
         )
         .map_err(|e| anyhow::anyhow!("Failed to add Thumbnail: {}", e))?;
 
+    if let Some(retina) = &thumbnails.retina {
+        package
+            .add_resource(
+                resource::Type::Thumbnail(resource::Version::Size2X),
+                &retina[..],
+            )
+            .map_err(|e| anyhow::anyhow!("Failed to add Thumbnail@2x: {}", e))?;
+    }
+
     Ok(())
 }
 
@@ -437,3 +577,77 @@ fn generate_pkpass_bytes(mut package: Package) -> Result<Vec<u8>> {
         .map_err(|e| anyhow::anyhow!("Failed to write package to buffer: {}", e))?;
     Ok(buffer)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{api::thumbnail::build_pass_thumbnails, front::templates, models};
+
+    fn render_preview(thumbnails: Option<&PassThumbnails>) -> String {
+        let pet = models::pet::Pet {
+            pet_name: "Firulais".to_string(),
+            breed: "Mestizo".to_string(),
+            last_weight: Some(12.5),
+            ..Default::default()
+        };
+        let pet_info = PetPublicInfoSchema::from_pet(pet, Utc::now().date_naive());
+        let preview = build_pass_preview(&pet_info, thumbnails, WeightUnit::default()).unwrap();
+
+        templates::WEB_TEMPLATES
+            .render(
+                "widgets/pet_pass_preview.html",
+                &tera::Context::from_value(serde_json::json!({ "preview": preview })).unwrap(),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_pass_preview_renders_with_and_without_photo() {
+        let img = image::RgbaImage::from_pixel(8, 8, image::Rgba([200, 120, 80, 255]));
+        let mut photo = Vec::new();
+        img.write_to(&mut Cursor::new(&mut photo), image::ImageFormat::Png)
+            .unwrap();
+        let thumbnails = build_pass_thumbnails(&photo).unwrap();
+
+        let with_photo = render_preview(Some(&thumbnails));
+        let without_photo = render_preview(None);
+
+        for html in [&with_photo, &without_photo] {
+            assert!(html.contains("🐾 Pet-Info FIRULAIS"));
+            assert!(html.contains("Mestizo"));
+            assert!(html.contains(pass_config::BACKGROUND_COLOR));
+            assert!(html.contains(r#"alt="Perfil público de la mascota""#));
+        }
+        assert!(with_photo.contains(r#"alt="Foto de la mascota""#));
+        assert!(!without_photo.contains(r#"alt="Foto de la mascota""#));
+    }
+
+    #[test]
+    fn test_default_pass_icon_by_species() {
+        let (dog, dog_icon) = default_pass_icon(Some("dog"));
+        let (cat, cat_icon) = default_pass_icon(Some("cat"));
+        let (bird, bird_icon) = default_pass_icon(Some("bird"));
+
+        assert_eq!(
+            [dog, cat, bird],
+            [
+                "pass_icon_dog.png",
+                "pass_icon_cat.png",
+                "pass_icon_bird.png"
+            ]
+        );
+        assert_ne!(dog_icon, cat_icon);
+        assert_ne!(cat_icon, bird_icon);
+        for icon in [dog_icon, cat_icon, bird_icon] {
+            assert_eq!(crate::utils::detect_image_format(icon), "png");
+        }
+
+        for other in [None, Some("fish")] {
+            assert_eq!(default_pass_icon(other).0, GENERIC_PASS_ICON.0);
+        }
+        assert_eq!(
+            default_pass_icon(breed::species_of("Canario")).0,
+            "pass_icon_bird.png"
+        );
+    }
+}
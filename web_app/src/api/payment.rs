@@ -48,7 +48,7 @@ pub async fn user_has_orphan_payment(
         .get_user_payments(user_id, Some(models::payment::PaymentStatus::Approved))
         .await?
         .len();
-    let pets = repo.get_all_pets_user_id(user_id).await?.len();
+    let pets = usize::try_from(repo.count_user_pets(user_id).await?).unwrap_or_default();
 
     Ok(user_approved_payments > pets)
 }
@@ -160,6 +160,7 @@ pub async fn create_subscription(
         user_id: payment_request.user_id,
         payment_idempotency_h,
         transaction_amount: payment_request.mp_paym_info.transaction_amount.to_string(),
+        currency: body_response.currency_id,
         installments: payment_request.mp_paym_info.installments,
         payment_method_id: payment_request.mp_paym_info.payment_method_id,
         issuer_id: payment_request.mp_paym_info.issuer_id,
@@ -179,6 +180,45 @@ pub async fn create_subscription(
     Ok((subs_payment.mp_paym_id, is_subscribed))
 }
 
+/// Formats a payment amount with its currency for display.
+///
+/// The amount is rounded to two decimals and grouped by thousands, e.g.
+/// `1234.5` in `MXN` is shown as `$1,234.50 MXN`.
+///
+/// # Arguments
+/// * `amount` - Raw amount as stored in the payment record
+/// * `currency` - ISO 4217 currency code
+///
+/// # Returns
+/// * `String` - Formatted amount, or the raw values if the amount is not a number
+pub fn format_money(amount: &str, currency: &str) -> String {
+    let currency = currency.trim().to_uppercase();
+    let Ok(amount) = amount.trim().parse::<Decimal>() else {
+        return format!("{amount} {currency}");
+    };
+
+    let symbol = match currency.as_str() {
+        "MXN" | "USD" => "$",
+        "EUR" => "€",
+        _ => "",
+    };
+
+    let amount = amount.round_dp(2);
+    let sign = if amount.is_sign_negative() { "-" } else { "" };
+    let digits = format!("{:.2}", amount.abs());
+    let (integer, fraction) = digits.split_once('.').unwrap_or((&digits, "00"));
+
+    let mut grouped = String::new();
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+
+    format!("{sign}{symbol}{grouped}.{fraction} {currency}")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -191,6 +231,7 @@ mod tests {
             mp_paym_id: 456,
             payment_idempotency_h: "test_idempotency".to_string(),
             transaction_amount: "29.99".to_string(),
+            currency: "MXN".to_string(),
             installments: 1,
             payment_method_id: "visa".to_string(),
             issuer_id: "test_issuer".to_string(),
@@ -200,6 +241,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_format_money() {
+        assert_eq!(format_money("100", "MXN"), "$100.00 MXN");
+        assert_eq!(format_money("1234.5", "mxn"), "$1,234.50 MXN");
+        assert_eq!(format_money("9.99", "USD"), "$9.99 USD");
+        assert_eq!(format_money("1000000", "USD"), "$1,000,000.00 USD");
+        assert_eq!(format_money("not-a-number", "USD"), "not-a-number USD");
+    }
+
     #[ntex::test]
     async fn test_user_has_orphan_payment_true() {
         let mut mock_repo = MockAppRepo::new();
@@ -217,10 +267,10 @@ mod tests {
             });
 
         mock_repo
-            .expect_get_all_pets_user_id()
+            .expect_count_user_pets()
             .with(eq(user_id))
             .times(1)
-            .returning(|_| Box::pin(async move { Ok(vec![models::pet::Pet::default()]) }));
+            .returning(|_| Box::pin(async move { Ok(1) }));
 
         let mock_repo: repo::ImplAppRepo = Box::new(mock_repo);
         let result = user_has_orphan_payment(&mock_repo, user_id).await;
@@ -244,17 +294,10 @@ mod tests {
             });
 
         mock_repo
-            .expect_get_all_pets_user_id()
+            .expect_count_user_pets()
             .with(eq(user_id))
             .times(1)
-            .returning(|_| {
-                Box::pin(async move {
-                    Ok(vec![
-                        models::pet::Pet::default(),
-                        models::pet::Pet::default(),
-                    ])
-                })
-            });
+            .returning(|_| Box::pin(async move { Ok(2) }));
 
         let mock_repo: repo::ImplAppRepo = Box::new(mock_repo);
         let result = user_has_orphan_payment(&mock_repo, user_id).await;
@@ -4,15 +4,25 @@
 //! health records, profiles, and public information handling. It serves as the
 //! core domain logic for pet operations in the application.
 
-use crate::{front, models, repo, services};
+use crate::{api, config, consts, front, models, repo, services};
 use anyhow::bail;
-use chrono::{NaiveDate, NaiveDateTime, Utc};
-use derive_more::Display;
+use chrono::{Datelike, Months, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use derive_more::{Display, Error};
 use serde::Serialize;
 use std::path::Path;
 use uuid::Uuid;
 
-/// Updates an existing pet or creates a new one based on the insert flag.
+/// What a submitted pet form does
+#[derive(Debug, Clone, Copy)]
+enum PetFormAction {
+    /// Adds a new pet, taking a paid pet from the balance if `charge_balance`
+    Create { charge_balance: bool },
+    /// Edits an existing pet
+    Update,
+}
+
+/// Updates an existing pet or creates a new one based on the form action.
 ///
 /// This internal function handles both pet creation and updates with a unified
 /// interface. It validates external IDs, processes pet information, and manages
@@ -21,7 +31,7 @@ use uuid::Uuid;
 /// # Arguments
 /// * `user_id` - ID of the user who owns the pet
 /// * `user_email` - Email of the user (used for file storage paths)
-/// * `insert` - True for creation, false for update
+/// * `action` - Whether the pet is created (and charged) or updated
 /// * `pet_info` - Pet form data including all pet details
 /// * `repo` - Repository instance for database operations
 /// * `storage_service` - Service for handling file uploads
@@ -32,9 +42,9 @@ use uuid::Uuid;
 /// # Process
 /// 1. Validate external ID if provided (for creation only)
 /// 2. Convert form data to pet model
-/// 3. Insert or update pet in database
+/// 3. Insert or update pet in database; on creation the balance charge and
+///    the user subscription status are updated in the same transaction
 /// 4. Handle pet picture upload if provided
-/// 5. Update user subscription status if creating
 ///
 /// # Errors
 /// Returns an error if:
@@ -43,12 +53,13 @@ use uuid::Uuid;
 /// - File upload fails
 async fn update_or_create_pet(
     user_id: i64,
-    insert: bool,
+    action: PetFormAction,
     pet_info: front::forms::pet::CreatePetForm,
     repo: &repo::ImplAppRepo,
     storage_service: &services::ImplStorageService,
 ) -> anyhow::Result<()> {
     let _span = logfire::span!("update_or_create_pet").entered();
+    let insert = matches!(action, PetFormAction::Create { .. });
 
     if let Some(external_id) = pet_info.pet_external_id {
         // Check if the external ID exists and is not already linked to a pet.
@@ -69,22 +80,53 @@ async fn update_or_create_pet(
         ..pet_info.clone().into()
     };
 
-    if insert {
-        repo.save_pet(&pet).await?;
-
-        repo.set_user_as_subscribed(user_id).await?;
-    } else {
-        // update
-        repo.update_pet(&pet).await?;
+    match action {
+        PetFormAction::Create { charge_balance } => {
+            repo.create_pet_and_charge(&pet, user_id, charge_balance)
+                .await?;
+        }
+        PetFormAction::Update => {
+            repo.update_pet(&pet).await?;
+        }
     }
 
     if let (Some(path), Some(pic_body)) = (&pet.pic, pet_info.pet_pic) {
-        storage_service.save_pic(path, pic_body).await?;
+        storage_service.upload_pic(path, pic_body).await?;
     }
 
     Ok(())
 }
 
+/// Checks if a user can register a new pet.
+///
+/// Uses the [capabilities](models::user_app::Capabilities) of the user role:
+/// the service access, the paid pets balance and the max number of pets.
+///
+/// # Arguments
+/// * `user` - User adding the pet
+/// * `pet_balance` - Paid pets the user can still add
+/// * `repo` - Repository instance for database operations
+///
+/// # Returns
+/// * `anyhow::Result<bool>` - True if a new pet is allowed
+pub async fn user_can_add_pet(
+    user: &models::user_app::User,
+    pet_balance: u32,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<bool> {
+    if !user.can_access_service() {
+        return Ok(false);
+    }
+
+    let capabilities = user.capabilities();
+    let current_pets = match capabilities.max_pets {
+        Some(_) => usize::try_from(repo.count_user_pets(user.id).await?).unwrap_or_default(),
+        None => 0,
+    };
+
+    Ok(capabilities.allows_new_pet(current_pets, pet_balance))
+}
+
 /// State information required for adding a new pet to a user.
 ///
 /// Contains user context and balance information needed to process
@@ -96,6 +138,19 @@ pub struct UserStateAddNewPet {
     pub user_email: String,
     /// Current pet balance available for creating new pets
     pub pet_balance: u32,
+    /// Hard limit of pets the account can register
+    pub max_pets: u64,
+}
+
+/// Errors raised when a new pet can't be added to an account
+#[derive(Debug, Display, Error)]
+pub enum AddPetError {
+    #[display("el limite de {max} mascotas por cuenta ha sido alcanzado")]
+    MaxPetsReached { max: u64 },
+    #[display("ya no tienes mascotas disponibles por agregar")]
+    NoPetBalance,
+    #[display("la placa pertenece a otra cuenta")]
+    ExternalIdTaken,
 }
 
 /// Adds a new pet to a user's account and decrements their pet balance.
@@ -111,30 +166,86 @@ pub struct UserStateAddNewPet {
 /// * `storage_service` - Service for handling file uploads
 ///
 /// # Returns
-/// * `anyhow::Result<()>` - Success confirmation or error details
+/// * `anyhow::Result<bool>` - `false` if the form was already submitted and
+///   no pet was added
 ///
 /// # Process
-/// 1. Create the pet using the internal update_or_create_pet function
-/// 2. Decrement user's pet balance by 1 if balance is available
+/// 1. Claim the form idempotency token, a token already claimed means a
+///    repeated submit of a form that already added the pet
+/// 2. Check the user hasn't reached the max number of pets per account
+/// 3. Create the pet using the internal update_or_create_pet function,
+///    taking one from the user's pet balance in the same transaction if
+///    balance is available
+///
+/// The token is released if the pet couldn't be added, so the form can be
+/// submitted again.
 ///
 /// # Errors
 /// Returns an error if:
+/// - The account already has `max_pets` pets ([`AddPetError::MaxPetsReached`])
+/// - The balance was spent meanwhile, e.g. from another tab ([`AddPetError::NoPetBalance`])
 /// - Pet creation fails (validation, database, file upload)
-/// - Balance update fails
 pub async fn add_new_pet_to_user(
     user_state: UserStateAddNewPet,
     pet_info: front::forms::pet::CreatePetForm,
     repo: &repo::ImplAppRepo,
     storage_service: &services::ImplStorageService,
-) -> anyhow::Result<()> {
-    update_or_create_pet(user_state.user_id, true, pet_info, repo, storage_service).await?;
+) -> anyhow::Result<bool> {
+    let user_id = user_state.user_id;
+    let idempotency_token = pet_info.idempotency_token;
 
-    if user_state.pet_balance > 0 {
-        repo.set_pet_balance(user_state.user_id, user_state.pet_balance - 1)
-            .await?;
+    if let Some(token) = idempotency_token
+        && !repo.claim_pet_creation_token(user_id, token).await?
+    {
+        logfire::info!(
+            "repeated create pet form submit ignored for user {user_id}",
+            user_id = user_id
+        );
+        return Ok(false);
     }
 
-    Ok(())
+    let result = add_pet_and_update_balance(user_state, pet_info, repo, storage_service).await;
+
+    if let (Err(_), Some(token)) = (&result, idempotency_token)
+        && let Err(e) = repo.release_pet_creation_token(user_id, token).await
+    {
+        logfire::error!(
+            "create pet form token could not be released: {error}",
+            error = e.to_string()
+        );
+    }
+
+    result.map(|_| true)
+}
+
+/// Adds the pet checking the max pets and decrements the user's pet balance
+async fn add_pet_and_update_balance(
+    user_state: UserStateAddNewPet,
+    pet_info: front::forms::pet::CreatePetForm,
+    repo: &repo::ImplAppRepo,
+    storage_service: &services::ImplStorageService,
+) -> anyhow::Result<()> {
+    // the balance is checked in the session, this guards crafted requests
+    let current_pets = repo.count_user_pets(user_state.user_id).await?;
+    if u64::try_from(current_pets).unwrap_or_default() >= user_state.max_pets {
+        return Err(AddPetError::MaxPetsReached {
+            max: user_state.max_pets,
+        }
+        .into());
+    }
+
+    // the paid pet is taken in the same transaction that adds the pet, so two
+    // tabs can't spend the same one and a failed insert doesn't charge it
+    let action = PetFormAction::Create {
+        charge_balance: user_state.pet_balance > 0,
+    };
+    update_or_create_pet(user_state.user_id, action, pet_info, repo, storage_service)
+        .await
+        .map_err(|e| match e.downcast_ref::<repo::errors::RepoError>() {
+            Some(repo::errors::RepoError::NotFound) => AddPetError::NoPetBalance.into(),
+            Some(repo::errors::RepoError::Conflict) => AddPetError::ExternalIdTaken.into(),
+            _ => e,
+        })
 }
 
 /// Updates an existing pet's information.
@@ -148,6 +259,8 @@ pub async fn add_new_pet_to_user(
 /// * `pet_info` - Updated pet form data
 /// * `repo` - Repository instance for database operations
 /// * `storage_service` - Service for handling file uploads
+/// * `thumbnail_cache` - Cache invalidated when the picture changes
+/// * `profile_cache` - Cache of public profiles, invalidated for the pet
 ///
 /// # Returns
 /// * `anyhow::Result<()>` - Success confirmation or error details
@@ -156,15 +269,81 @@ pub async fn update_pet_to_user(
     pet_info: front::forms::pet::CreatePetForm,
     repo: &repo::ImplAppRepo,
     storage_service: &services::ImplStorageService,
+    thumbnail_cache: &api::thumbnail::ThumbnailCache,
+    profile_cache: &api::profile_cache::PublicProfileCache,
 ) -> anyhow::Result<()> {
-    update_or_create_pet(user_id, false, pet_info, repo, storage_service).await
+    let pet_id = pet_info.id;
+    let pic_changed = pet_info.pet_pic.is_some();
+
+    update_or_create_pet(
+        user_id,
+        PetFormAction::Update,
+        pet_info,
+        repo,
+        storage_service,
+    )
+    .await?;
+    profile_cache.invalidate_pet(pet_id);
+
+    if pic_changed {
+        let pet = repo.get_pet_by_id(pet_id, user_id).await?;
+        thumbnail_cache.invalidate(pet.external_id);
+    }
+
+    Ok(())
+}
+
+/// Replaces the picture of a pet with a photo sent outside the pet form,
+/// e.g. through WhatsApp.
+///
+/// The photo goes through the same checks as the form upload, and like the
+/// pet form the owner and the collaborators who can edit the pet can change it.
+///
+/// # Arguments
+/// * `user_id` - ID of the user sending the photo
+/// * `pet_external_id` - Public UUID of the pet
+/// * `pic` - Raw image bytes
+/// * `repo` - Repository instance for database operations
+/// * `storage_service` - Service for handling file uploads
+/// * `thumbnail_cache` - Cache invalidated after the picture changes
+///
+/// # Returns
+/// * `anyhow::Result<models::pet::Pet>` - The pet with the new picture
+///
+/// # Errors
+/// Returns an error if the user can't edit the pet, the picture is not valid
+/// or it can't be stored.
+pub async fn replace_pet_pic(
+    user_id: i64,
+    pet_external_id: Uuid,
+    pic: models::Pic,
+    repo: &repo::ImplAppRepo,
+    storage_service: &services::ImplStorageService,
+    thumbnail_cache: &api::thumbnail::ThumbnailCache,
+) -> anyhow::Result<models::pet::Pet> {
+    let pet = repo
+        .get_pet_by_external_id_for_user(pet_external_id, user_id)
+        .await?;
+
+    let pic = front::utils::prepare_pet_pic(&pic)?;
+    let pic_path = format!("pics/{pet_external_id}");
+
+    // like the pet form, the edit access is checked before the picture is stored
+    repo.set_pet_pic(pet.id, user_id, &pic_path).await?;
+    storage_service.upload_pic(&pic_path, pic).await?;
+    thumbnail_cache.invalidate(pet_external_id);
+
+    Ok(models::pet::Pet {
+        pic: Some(pic_path),
+        ..pet
+    })
 }
 
 /// Pet sex/gender enumeration.
 ///
 /// Represents the biological sex of a pet with appropriate serialization
 /// for Spanish language display and male/female display formats.
-#[derive(Debug, Display, Default, Serialize)]
+#[derive(Debug, Display, Default, Serialize, Clone)]
 pub enum Sex {
     #[serde(rename(serialize = "macho"))]
     #[display("male")]
@@ -175,6 +354,104 @@ pub enum Sex {
     Female,
 }
 
+/// Species of a pet as far as its life stage goes, large dogs age faster.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Species {
+    Dog,
+    LargeDog,
+    Cat,
+}
+
+impl Species {
+    /// Guesses the species from the breed, a dog if the breed is unknown.
+    pub fn from_breed(breed: &str) -> Self {
+        match api::breed::species_of(breed) {
+            Some("cat") => Species::Cat,
+            _ if api::breed::is_large_dog_breed(breed) => Species::LargeDog,
+            _ => Species::Dog,
+        }
+    }
+}
+
+/// Life stage of a pet, its age thresholds depend on the [`Species`].
+#[derive(Debug, Display, Serialize, Clone, Copy, PartialEq)]
+pub enum LifeStage {
+    #[serde(rename(serialize = "cachorro"))]
+    #[display("cachorro")]
+    Puppy,
+    #[serde(rename(serialize = "gatito"))]
+    #[display("gatito")]
+    Kitten,
+    #[serde(rename(serialize = "adulto"))]
+    #[display("adulto")]
+    Adult,
+    #[serde(rename(serialize = "senior"))]
+    #[display("senior")]
+    Senior,
+}
+
+/// Computes the life stage of a pet on `today`.
+///
+/// Puppies and kittens are the pets under 1 year, large dogs under 18
+/// months. Large dogs are senior from 7 years, other dogs from 10 and
+/// cats from 11.
+///
+/// # Arguments
+/// * `birthday` - Birthday of the pet
+/// * `species` - Species of the pet, see [`Species::from_breed`]
+/// * `today` - Date the age is computed up to
+pub fn life_stage(birthday: NaiveDate, species: Species, today: NaiveDate) -> LifeStage {
+    let (young_months, senior_months) = match species {
+        Species::Dog => (12, 10 * 12),
+        Species::LargeDog => (18, 7 * 12),
+        Species::Cat => (12, 11 * 12),
+    };
+
+    let mut age_months =
+        (today.year() - birthday.year()) * 12 + today.month() as i32 - birthday.month() as i32;
+    if today.day() < birthday.day() {
+        age_months -= 1;
+    }
+
+    match age_months {
+        months if months < young_months && species == Species::Cat => LifeStage::Kitten,
+        months if months < young_months => LifeStage::Puppy,
+        months if months < senior_months => LifeStage::Adult,
+        _ => LifeStage::Senior,
+    }
+}
+
+/// Weight of a pet compared with the adult range of its breed, see [`weight_vs_standard`].
+#[derive(Debug, Display, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WeightStatus {
+    #[display("bajo peso para su raza")]
+    Under,
+    #[display("peso normal para su raza")]
+    Normal,
+    #[display("sobrepeso para su raza")]
+    Over,
+    /// The breed has no known range, e.g. mixed breeds
+    #[default]
+    #[display("")]
+    Unknown,
+}
+
+/// Compares a weight with the adult weight range of the breed, see
+/// [`standard_weight_range`](api::breed::standard_weight_range).
+///
+/// # Arguments
+/// * `weight` - Weight of the pet, in kilograms
+/// * `breed` - Breed of the pet, free text
+pub fn weight_vs_standard(weight: f64, breed: &str) -> WeightStatus {
+    match api::breed::standard_weight_range(breed) {
+        None => WeightStatus::Unknown,
+        Some((min, _)) if weight < min => WeightStatus::Under,
+        Some((_, max)) if weight > max => WeightStatus::Over,
+        Some(_) => WeightStatus::Normal,
+    }
+}
+
 /// Schema for displaying pets in a list format.
 ///
 /// Contains essential pet information optimized for list views,
@@ -193,27 +470,41 @@ pub struct PetListSchema {
     pub sex: Sex,
     /// Human-readable formatted age string
     pub fmt_age: String,
+    /// Number of vaccines recorded
+    pub vaccine_count: i64,
+    /// Number of weights recorded
+    pub weight_count: i64,
+    /// Date of the most recent vaccine, if any
+    #[serde(with = "api::fmt_iso8601::option")]
+    pub last_vaccine_date: Option<NaiveDate>,
+    /// Whether the pet is reported as lost
+    pub is_lost: bool,
+    /// Whether the pet is spayed/neutered
+    pub is_spaying_neutering: bool,
 }
 
-/// Converts a Pet model to PetListSchema for display.
-///
-/// Transforms database pet data into a format suitable for list views,
-/// including age calculation and sex conversion.
-impl From<models::pet::Pet> for PetListSchema {
-    fn from(val: models::pet::Pet) -> Self {
+impl PetListSchema {
+    /// Converts a pet with its record counts to PetListSchema for display.
+    ///
+    /// Transforms database pet data into a format suitable for list views,
+    /// including age calculation (up to the user's `today`) and sex conversion.
+    pub fn from_pet(val: models::pet::PetWithCounts, today: NaiveDate) -> Self {
+        let pet = val.pet;
         PetListSchema {
-            id: val.id,
-            external_id: val.external_id,
-            name: val.pet_name,
-            breed: val.breed,
-            sex: match val.is_female {
+            id: pet.id,
+            external_id: pet.external_id,
+            name: pet.pet_name,
+            breed: pet.breed,
+            sex: match pet.is_female {
                 true => Sex::Female,
                 false => Sex::Male,
             },
-            fmt_age: front::utils::fmt_dates_difference(
-                val.birthday,
-                front::utils::get_utc_now_with_default_time().date_naive(),
-            ),
+            fmt_age: front::utils::fmt_dates_difference(pet.birthday, today),
+            vaccine_count: val.vaccine_count,
+            weight_count: val.weight_count,
+            last_vaccine_date: val.last_vaccine_date.map(|date| date.date()),
+            is_lost: pet.is_lost,
+            is_spaying_neutering: pet.is_spaying_neutering,
         }
     }
 }
@@ -225,19 +516,23 @@ impl From<models::pet::Pet> for PetListSchema {
 ///
 /// # Arguments
 /// * `user_id` - ID of the user to get pets for
+/// * `user_timezone` - Timezone of the user, ages are computed up to its local today
 /// * `repo` - Repository instance for database operations
 ///
 /// # Returns
 /// * `anyhow::Result<Vec<PetListSchema>>` - List of pets in display format
 pub async fn get_user_pets_cards(
     user_id: i64,
+    user_timezone: Tz,
     repo: &repo::ImplAppRepo,
 ) -> anyhow::Result<Vec<PetListSchema>> {
+    let today = front::utils::get_now_date_in_tz(user_timezone);
+
     Ok(repo
-        .get_all_pets_user_id(user_id)
+        .get_pets_with_counts(user_id)
         .await?
         .into_iter()
-        .map(Into::into)
+        .map(|pet| PetListSchema::from_pet(pet, today))
         .collect())
 }
 
@@ -269,8 +564,10 @@ pub async fn get_pet_user_to_edit(
         is_spaying_neutering: pet.is_spaying_neutering,
         is_female: pet.is_female,
         about_pet: pet.about,
+        microchip: pet.microchip,
         pet_pic: pet.pic.map(|_| vec![]),
         pet_external_id: Some(pet.external_id),
+        idempotency_token: None,
     })
 }
 
@@ -278,7 +575,7 @@ pub async fn get_pet_user_to_edit(
 ///
 /// Contains comprehensive pet information suitable for public viewing,
 /// including health status and contact-enabling details.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Clone)]
 pub struct PetPublicInfoSchema {
     /// Public UUID as string for external references
     pub external_id: String,
@@ -292,27 +589,50 @@ pub struct PetPublicInfoSchema {
     pub last_weight: Option<f64>,
     /// Human-readable formatted age string
     pub fmt_age: String,
+    /// Life stage for the pet's age and species
+    pub life_stage: LifeStage,
+    /// Last weight compared with its breed, only for grown pets
+    pub weight_status: WeightStatus,
     /// Whether the pet is spayed/neutered
     pub is_spaying_neutering: bool,
+    /// Microchip number, helps vets and shelters to identify the pet
+    pub microchip: Option<String>,
     /// Whether the pet is currently lost
     pub is_lost: bool,
     /// Description and additional information about the pet
     pub about_pet: String,
     /// Whether the pet has a picture available or default one
     pub pic_path: String,
+    /// Human-readable time since the profile was last updated
+    pub fmt_last_updated: String,
 }
 
-/// Converts a Pet model to PetPublicInfoSchema for public display.
-///
-/// Transforms database pet data into a format suitable for public
-/// viewing, including all relevant information for found pet scenarios.
-impl From<models::pet::Pet> for PetPublicInfoSchema {
-    fn from(val: models::pet::Pet) -> Self {
+impl PetPublicInfoSchema {
+    /// Converts a Pet model to PetPublicInfoSchema for public display.
+    ///
+    /// Transforms database pet data into a format suitable for public
+    /// viewing, including all relevant information for found pet scenarios.
+    pub fn from_pet(val: models::pet::Pet, today: NaiveDate) -> Self {
         let mut pic_path = "pics/default".to_string();
         if let Some(path) = val.pic {
             pic_path = path;
         }
 
+        let last_updated = val.updated_at.date_naive();
+        let fmt_last_updated = match last_updated >= today {
+            true => "hoy".to_string(),
+            false => format!(
+                "hace {}",
+                front::utils::fmt_dates_difference(last_updated, today)
+            ),
+        };
+        let stage = life_stage(val.birthday, Species::from_breed(&val.breed), today);
+        // the breed ranges are for adults, puppies and kittens are still growing
+        let weight_status = match (stage, val.last_weight) {
+            (LifeStage::Adult | LifeStage::Senior, Some(kg)) => weight_vs_standard(kg, &val.breed),
+            _ => WeightStatus::Unknown,
+        };
+
         PetPublicInfoSchema {
             external_id: val.external_id.to_string(),
             name: val.pet_name,
@@ -323,13 +643,14 @@ impl From<models::pet::Pet> for PetPublicInfoSchema {
             pic_path,
             pet_breed: val.breed,
             last_weight: val.last_weight,
-            fmt_age: front::utils::fmt_dates_difference(
-                val.birthday,
-                front::utils::get_utc_now_with_default_time().date_naive(),
-            ),
+            fmt_age: front::utils::fmt_dates_difference(val.birthday, today),
+            life_stage: stage,
+            weight_status,
             is_spaying_neutering: val.is_spaying_neutering,
+            microchip: val.microchip,
             is_lost: val.is_lost,
             about_pet: val.about,
+            fmt_last_updated,
         }
     }
 }
@@ -338,19 +659,64 @@ impl From<models::pet::Pet> for PetPublicInfoSchema {
 ///
 /// Gets pet data using the public external ID and formats it
 /// for public display. Used for QR code scanning and public access.
+/// The profile is served from the cache while it's fresh.
 ///
 /// # Arguments
 /// * `pet_external_id` - Public UUID of the pet
+/// * `user_timezone` - Timezone of the visitor, the age is computed up to its local today
 /// * `repo` - Repository instance for database operations
+/// * `profile_cache` - Cache of recently built public profiles
 ///
 /// # Returns
 /// * `anyhow::Result<PetPublicInfoSchema>` - Pet information for public display
 pub async fn get_pet_public_info(
     pet_external_id: Uuid,
+    user_timezone: Tz,
     repo: &repo::ImplAppRepo,
+    profile_cache: &api::profile_cache::PublicProfileCache,
 ) -> anyhow::Result<PetPublicInfoSchema> {
+    let today = front::utils::get_now_date_in_tz(user_timezone);
+    if let Some(profile) = profile_cache.get(pet_external_id, today) {
+        return Ok(profile);
+    }
+
     let pet = repo.get_pet_by_external_id(pet_external_id).await?;
-    Ok(pet.into())
+    let pet_id = pet.id;
+    let profile = PetPublicInfoSchema::from_pet(pet, today);
+    profile_cache.insert(pet_external_id, today, pet_id, profile.clone());
+
+    Ok(profile)
+}
+
+/// Retrieves the public information of several pets at once.
+///
+/// Built straight from the repo in a single query, the profile cache is
+/// not used and no scan is recorded.
+///
+/// # Arguments
+/// * `pet_external_ids` - Public UUIDs of the pets, duplicates are ignored
+/// * `user_timezone` - Timezone of the visitor, the ages are computed up to its local today
+/// * `repo` - Repository instance for database operations
+///
+/// # Returns
+/// * `anyhow::Result<Vec<PetPublicInfoSchema>>` - Information of the pets
+///   found, unknown ids are omitted
+pub async fn get_pets_public_info(
+    pet_external_ids: &[Uuid],
+    user_timezone: Tz,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<Vec<PetPublicInfoSchema>> {
+    let mut pet_external_ids = pet_external_ids.to_vec();
+    pet_external_ids.sort_unstable();
+    pet_external_ids.dedup();
+
+    let today = front::utils::get_now_date_in_tz(user_timezone);
+    Ok(repo
+        .get_pets_by_external_ids(&pet_external_ids)
+        .await?
+        .into_iter()
+        .map(|pet| PetPublicInfoSchema::from_pet(pet, today))
+        .collect())
 }
 
 /// Retrieves metadata about a pet's external ID.
@@ -378,6 +744,150 @@ pub async fn get_pet_external_id_metadata(
     Ok(None)
 }
 
+/// External id (QR tag) of a user linked to one of their pets
+#[derive(Debug, Serialize, PartialEq)]
+pub struct LinkedExternalId {
+    pub external_id: Uuid,
+    pub pet_name: String,
+}
+
+/// External ids (QR tags) of a user split by whether they show a pet
+#[derive(Debug, Serialize, PartialEq)]
+pub struct UserExternalIds {
+    pub linked: Vec<LinkedExternalId>,
+    pub unlinked: Vec<Uuid>,
+}
+
+/// Lists the external ids (QR tags) of a user and whether they are linked.
+///
+/// Linked ones are the ids of the user's pets, unlinked ones were bought
+/// or gifted to the user but don't show a pet yet.
+///
+/// # Arguments
+/// * `user_id` - ID of the user
+/// * `repo` - Repository instance for database operations
+///
+/// # Returns
+/// * `anyhow::Result<UserExternalIds>` - Linked and unlinked external ids
+pub async fn get_user_external_ids(
+    user_id: i64,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<UserExternalIds> {
+    let linked = repo
+        .get_all_pets_user_id(user_id)
+        .await?
+        .into_iter()
+        .map(|pet| LinkedExternalId {
+            external_id: pet.external_id,
+            pet_name: pet.pet_name,
+        })
+        .collect();
+
+    Ok(UserExternalIds {
+        linked,
+        unlinked: repo.get_user_unlinked_external_ids(user_id).await?,
+    })
+}
+
+/// Records a visit to the public profile of a pet.
+///
+/// Views of the owner are not counted; the user agent is truncated to
+/// [PET_SCAN_USER_AGENT_MAX_LEN](consts::PET_SCAN_USER_AGENT_MAX_LEN) chars.
+///
+/// # Arguments
+/// * `pet_external_id` - Public UUID of the pet
+/// * `viewer_id` - Logged user viewing the profile, if any
+/// * `user_agent` - User agent header of the visitor
+/// * `repo` - Repository instance for database operations
+pub async fn record_pet_scan(
+    pet_external_id: Uuid,
+    viewer_id: Option<i64>,
+    user_agent: Option<&str>,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<()> {
+    let user_agent = user_agent.map(|user_agent| {
+        user_agent
+            .chars()
+            .take(consts::PET_SCAN_USER_AGENT_MAX_LEN)
+            .collect::<String>()
+    });
+
+    repo.insert_pet_scan(pet_external_id, viewer_id, user_agent)
+        .await
+}
+
+/// Retrieves how many times the public profile of a pet was visited.
+///
+/// # Arguments
+/// * `pet_external_id` - Public UUID of the pet
+/// * `user_id` - Owner ID (for authorization)
+/// * `repo` - Repository instance for database operations
+///
+/// # Returns
+/// * `anyhow::Result<models::pet::PetScanStats>` - Total and recent visits
+pub async fn get_pet_scan_stats(
+    pet_external_id: Uuid,
+    user_id: i64,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<models::pet::PetScanStats> {
+    repo.get_scan_stats(pet_external_id, user_id).await
+}
+
+/// Deletes the visits to public profiles older than
+/// [PET_SCAN_RETENTION_DAYS](consts::PET_SCAN_RETENTION_DAYS) days.
+///
+/// # Arguments
+/// * `repo` - Repository instance for database operations
+/// * `clock` - Source of the current time
+///
+/// # Returns
+/// * `anyhow::Result<u64>` - Number of deleted visits
+pub async fn purge_pet_scans(
+    repo: &repo::ImplAppRepo,
+    clock: &services::ImplClock,
+) -> anyhow::Result<u64> {
+    repo.delete_pet_scans_before(
+        clock.now() - chrono::Duration::days(consts::PET_SCAN_RETENTION_DAYS),
+    )
+    .await
+}
+
+/// Retrieves who changed what on a pet, latest changes first.
+///
+/// # Arguments
+/// * `pet_id` - ID of the pet
+/// * `owner_id` - Owner ID (for authorization)
+/// * `repo` - Repository instance for database operations
+///
+/// # Returns
+/// * `anyhow::Result<Vec<models::pet::PetAuditEntry>>` - Latest changes, empty
+///   if the pet doesn't belong to the owner
+pub async fn get_pet_audit(
+    pet_id: i64,
+    owner_id: i64,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<Vec<models::pet::PetAuditEntry>> {
+    repo.get_pet_audit(pet_id, owner_id).await
+}
+
+/// Retrieves the messages left by people who found the pet, latest first.
+///
+/// # Arguments
+/// * `pet_id` - ID of the pet
+/// * `owner_id` - Owner ID (for authorization)
+/// * `repo` - Repository instance for database operations
+///
+/// # Returns
+/// * `anyhow::Result<Vec<models::pet::FoundReport>>` - Latest reports, empty
+///   if the pet doesn't belong to the owner
+pub async fn get_pet_found_reports(
+    pet_id: i64,
+    owner_id: i64,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<Vec<models::pet::FoundReport>> {
+    repo.get_pet_found_reports(pet_id, owner_id).await
+}
+
 /// Structure for pet picture data with file extension.
 ///
 /// Contains the raw image bytes and file extension information
@@ -394,6 +904,7 @@ pub struct PetPublicPic {
 ///
 /// Gets the pet's picture file from storage using the external ID.
 /// Returns the image bytes and file extension for serving to clients.
+/// A picture path whose file is no longer stored is treated as no picture.
 ///
 /// # Arguments
 /// * `pet_external_id` - Public UUID of the pet
@@ -411,8 +922,17 @@ pub async fn get_public_pic(
         .get_pet_pic_path_by_external_id(pet_external_id)
         .await?
     {
+        let Some(body) = storage_service.get_pic_as_bytes(&pic_path).await? else {
+            logfire::warn!(
+                "pet {pet_external_id} picture {pic_path} is missing in storage",
+                pet_external_id = pet_external_id.to_string(),
+                pic_path = pic_path.to_string()
+            );
+            return Ok(None);
+        };
+
         return Ok(Some(PetPublicPic {
-            body: storage_service.get_pic_as_bytes(&pic_path).await?,
+            body,
             extension: Path::new(&pic_path)
                 .extension()
                 .and_then(|p| p.to_str())
@@ -424,6 +944,116 @@ pub async fn get_public_pic(
     Ok(None)
 }
 
+/// Version of a pet's public picture, used by clients to revalidate their
+/// cached copy without downloading it again.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PetPublicPicVersion {
+    /// Weak entity tag, the picture is re-encoded so bytes aren't compared
+    pub etag: String,
+    /// Last update of the pet, a new picture always updates it
+    pub last_modified: chrono::DateTime<Utc>,
+}
+
+impl From<chrono::DateTime<Utc>> for PetPublicPicVersion {
+    fn from(updated_at: chrono::DateTime<Utc>) -> Self {
+        PetPublicPicVersion {
+            etag: format!("W/\"{:x}\"", updated_at.timestamp_millis()),
+            last_modified: updated_at,
+        }
+    }
+}
+
+/// Retrieves the version of a pet's public picture without reading it from
+/// storage.
+///
+/// # Arguments
+/// * `pet_external_id` - Public UUID of the pet
+/// * `repo` - Repository instance for database operations
+///
+/// # Returns
+/// * `anyhow::Result<Option<PetPublicPicVersion>>` - The version if the pet
+///   has a picture
+pub async fn get_public_pic_version(
+    pet_external_id: Uuid,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<Option<PetPublicPicVersion>> {
+    Ok(repo
+        .get_pet_pic_updated_at_by_external_id(pet_external_id)
+        .await?
+        .map(PetPublicPicVersion::from))
+}
+
+/// Retrieves the name and breed drawn on the QR card of a pet, without
+/// loading the rest of its profile.
+///
+/// # Arguments
+/// * `pet_external_id` - Public UUID of the pet
+/// * `repo` - Repository instance for database operations
+///
+/// # Returns
+/// * `anyhow::Result<models::pet::PetQrCaption>` - [`RepoError::NotFound`](repo::errors::RepoError::NotFound)
+///   if the external id isn't linked to a pet
+pub async fn get_pet_qr_caption(
+    pet_external_id: Uuid,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<models::pet::PetQrCaption> {
+    repo.get_pet_qr_caption(pet_external_id)
+        .await?
+        .ok_or_else(|| repo::errors::RepoError::NotFound.into())
+}
+
+/// Builds the QR code of a pet's public profile.
+///
+/// Draws the QR card with the pet's avatar and name. The avatar comes from
+/// the thumbnail cache, so storage is only hit on a cache miss; pets without
+/// picture get a default avatar with their initials.
+///
+/// # Arguments
+/// * `pet_external_id` - Public UUID of the pet
+/// * `pet_name` - Caption drawn above the QR code
+/// * `pet_breed` - Breed of the pet, picks the default avatar colors
+/// * `info_url` - URL encoded in the QR code
+/// * `repo` - Repository instance for database operations
+/// * `storage_service` - Service for file retrieval
+/// * `thumbnail_cache` - Cache of already resized pet pictures
+///
+/// # Returns
+/// * `anyhow::Result<Vec<u8>>` - PNG image data
+pub async fn build_pet_qr_code(
+    pet_external_id: Uuid,
+    pet_name: &str,
+    pet_breed: &str,
+    info_url: &str,
+    repo: &repo::ImplAppRepo,
+    storage_service: &services::ImplStorageService,
+    thumbnail_cache: &api::thumbnail::ThumbnailCache,
+) -> anyhow::Result<Vec<u8>> {
+    let avatar = api::thumbnail::get_pet_thumbnail(
+        pet_external_id,
+        consts::QR_CARD_AVATAR_SIZE_PX,
+        repo,
+        storage_service,
+        thumbnail_cache,
+    )
+    .await
+    .ok()
+    .flatten();
+
+    let body = match avatar {
+        Some(body) => body,
+        None => crate::qr::generate_default_avatar(pet_name, api::breed::species_of(pet_breed))?,
+    };
+
+    crate::qr::build_qr_card_with_pic(
+        &PetPublicPic {
+            body,
+            extension: "png".to_string(),
+        },
+        pet_name,
+        info_url,
+    )
+}
+
 /// Unified structure for pet health records.
 ///
 /// Represents health records (weight, vaccines, deworms) in a consistent
@@ -435,22 +1065,32 @@ pub struct PetHealthRecord {
     /// Record value (weight amount, vaccine name, etc.)
     pub value: String,
     /// Date when the record was created
+    #[serde(with = "api::fmt_iso8601")]
     pub date: NaiveDateTime,
 }
 
-/// Converts a PetWeight model to PetHealthRecord.
-///
-/// Formats weight values to 2 decimal places for consistent display.
-impl From<models::pet::PetWeight> for PetHealthRecord {
-    fn from(val: models::pet::PetWeight) -> Self {
+impl PetHealthRecord {
+    /// Converts a PetWeight model (kilograms) to PetHealthRecord.
+    ///
+    /// The value is shown in `weight_unit` with 2 decimal places, without
+    /// the unit so it can be edited back.
+    pub fn from_weight(
+        val: models::pet::PetWeight,
+        weight_unit: models::user_app::WeightUnit,
+    ) -> Self {
         PetHealthRecord {
             id: val.id,
-            value: format!("{:.2}", val.value),
+            value: format!("{:.2}", weight_unit.from_kg(val.value)),
             date: val.created_at,
         }
     }
 }
 
+/// Formats a weight stored in kilograms in the given unit, e.g. `"22.05 lb"`.
+pub fn format_weight_with_unit(kg: f64, weight_unit: models::user_app::WeightUnit) -> String {
+    format!("{:.2} {weight_unit}", weight_unit.from_kg(kg))
+}
+
 /// Converts a PetHealth model to PetHealthRecord.
 ///
 /// Used for vaccine and deworming records.
@@ -473,6 +1113,7 @@ impl From<models::pet::PetHealth> for PetHealthRecord {
 /// * `pet_external_id` - Public UUID of the pet
 /// * `health_record` - Type of health record to retrieve
 /// * `user_id` - Optional user ID for ownership verification
+/// * `weight_unit` - Unit the weights are shown in
 /// * `repo` - Repository instance for database operations
 ///
 /// # Returns
@@ -481,6 +1122,7 @@ pub async fn get_pet_health_records(
     pet_external_id: Uuid,
     health_record: &models::pet::PetHealthType,
     user_id: Option<i64>,
+    weight_unit: models::user_app::WeightUnit,
     repo: &repo::ImplAppRepo,
 ) -> anyhow::Result<Vec<PetHealthRecord>> {
     match health_record {
@@ -488,7 +1130,7 @@ pub async fn get_pet_health_records(
             .get_pet_weights(pet_external_id, user_id)
             .await?
             .into_iter()
-            .map(Into::into)
+            .map(|weight| PetHealthRecord::from_weight(weight, weight_unit))
             .collect()),
         _ => Ok(repo
             .get_pet_health_records(pet_external_id, user_id, health_record.clone())
@@ -499,557 +1141,3644 @@ pub async fn get_pet_health_records(
     }
 }
 
-/// Deletes a pet and all associated information.
+/// Builds a CSV with the weight history of a pet.
 ///
-/// Removes the pet and all related data (health records, notes, etc.)
-/// from the database. This operation is irreversible.
+/// Rows are `date,weight_kg`, sorted by date ascending, with the weight
+/// formatted to 2 decimal places like the health records view.
 ///
 /// # Arguments
-/// * `pet_id` - ID of the pet to delete
+/// * `weights` - Weight records of the pet
+///
+/// # Returns
+/// * `String` - CSV content, only the header row if there are no weights
+pub fn weights_to_csv(weights: &[models::pet::PetWeight]) -> String {
+    let mut sorted_weights: Vec<&models::pet::PetWeight> = weights.iter().collect();
+    sorted_weights.sort_by_key(|w| w.created_at);
+
+    let mut csv = String::from("date,weight_kg\n");
+    for weight in sorted_weights {
+        csv.push_str(&format!(
+            "{},{:.2}\n",
+            weight.created_at.format("%Y-%m-%d"),
+            weight.value
+        ));
+    }
+
+    csv
+}
+
+/// Retrieves the weight history of a pet as CSV.
+///
+/// Verifies the pet belongs to the user before reading its weights.
+///
+/// # Arguments
+/// * `pet_id` - ID of the pet
 /// * `user_id` - ID of the user who owns the pet
 /// * `repo` - Repository instance for database operations
 ///
 /// # Returns
-/// * `anyhow::Result<()>` - Success confirmation or error details
-pub async fn delete_pet_and_its_info(
+/// * `anyhow::Result<String>` - CSV content with `date,weight_kg` rows
+pub async fn get_pet_weights_csv(
     pet_id: i64,
     user_id: i64,
     repo: &repo::ImplAppRepo,
-) -> anyhow::Result<()> {
-    repo.delete_pet(pet_id, user_id).await?;
-    Ok(())
+) -> anyhow::Result<String> {
+    let pet = repo.get_pet_by_id(pet_id, user_id).await?;
+    let weights = repo.get_pet_weights(pet.external_id, Some(user_id)).await?;
+
+    Ok(weights_to_csv(&weights))
 }
 
-/// Adds a new health record to a pet.
+/// Name of the note added to the photos ZIP listing the skipped photos.
+const PHOTOS_ZIP_MANIFEST_NAME: &str = "LEEME.txt";
+
+/// Builds a ZIP with all the photos of a pet.
 ///
-/// Creates a new health record (weight, vaccine, or deworm) for the specified
-/// pet with the provided information and date.
+/// Verifies the pet belongs to the user before reading its photos.
 ///
 /// # Arguments
-/// * `pet_external_id` - Public UUID of the pet
-/// * `health_record` - Type of health record to create
+/// * `pet_id` - ID of the pet
 /// * `user_id` - ID of the user who owns the pet
-/// * `desc` - Record description/value (weight amount, vaccine name, etc.)
-/// * `date` - Date for the health record
 /// * `repo` - Repository instance for database operations
+/// * `storage_service` - Storage the photos are read from
 ///
 /// # Returns
-/// * `anyhow::Result<PetHealthRecord>` - The created health record
-pub async fn insert_pet_health_record(
-    pet_external_id: Uuid,
-    health_record: &models::pet::PetHealthType,
+/// * `anyhow::Result<Vec<u8>>` - The ZIP file content, see [zip_pet_photos]
+pub async fn build_photos_zip(
+    pet_id: i64,
     user_id: i64,
-    desc: String,
-    date: NaiveDate,
     repo: &repo::ImplAppRepo,
-) -> anyhow::Result<PetHealthRecord> {
-    match health_record {
-        models::pet::PetHealthType::Weight => Ok(repo
-            .insert_pet_weight(
-                pet_external_id,
-                user_id,
-                desc.parse::<f64>().unwrap_or(0.0),
-                date,
-            )
-            .await?
-            .into()),
-        models::pet::PetHealthType::Vaccine => Ok(repo
-            .insert_vaccine_to(pet_external_id, user_id, desc, date)
-            .await?
-            .into()),
-        models::pet::PetHealthType::Deworm => Ok(repo
-            .insert_deworm_to(pet_external_id, user_id, desc, date)
-            .await?
-            .into()),
+    storage_service: &services::ImplStorageService,
+) -> anyhow::Result<Vec<u8>> {
+    let pet = repo.get_pet_by_id(pet_id, user_id).await?;
+    let photo_paths: Vec<String> = pet.pic.iter().cloned().collect();
+
+    zip_pet_photos(&pet.pet_name, &photo_paths, storage_service).await
+}
+
+/// Writes the given photos into an in-memory ZIP.
+///
+/// Entries are named `{pet_name}_{n}.{ext}`, the extension taken from the
+/// image content. Photos no longer in the storage are skipped and listed in
+/// a [manifest](PHOTOS_ZIP_MANIFEST_NAME) entry.
+///
+/// # Arguments
+/// * `pet_name` - Name of the pet, used for the entry names
+/// * `photo_paths` - Storage paths of the photos
+/// * `storage_service` - Storage the photos are read from
+///
+/// # Returns
+/// * `anyhow::Result<Vec<u8>>` - The ZIP file content
+pub async fn zip_pet_photos(
+    pet_name: &str,
+    photo_paths: &[String],
+    storage_service: &services::ImplStorageService,
+) -> anyhow::Result<Vec<u8>> {
+    use std::io::Write;
+
+    let file_stem: String = pet_name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    let options = zip::write::FileOptions::default();
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let mut missing = Vec::new();
+
+    for (n, path) in (1..).zip(photo_paths) {
+        let Some(body) = storage_service.get_pic_as_bytes(path).await? else {
+            missing.push(format!("foto {n}: no se encontró en el almacenamiento"));
+            continue;
+        };
+
+        let extension = image::guess_format(&body)
+            .ok()
+            .and_then(|format| format.extensions_str().first().copied())
+            .unwrap_or("bin");
+
+        zip.start_file(format!("{file_stem}_{n}.{extension}"), options)?;
+        zip.write_all(&body)?;
+    }
+
+    if !missing.is_empty() {
+        zip.start_file(PHOTOS_ZIP_MANIFEST_NAME, options)?;
+        zip.write_all(format!("Fotos omitidas:\n{}\n", missing.join("\n")).as_bytes())?;
     }
+
+    Ok(zip.finish()?.into_inner())
 }
 
-/// Deletes a specific health record from a pet.
+/// Deletes a pet and all associated information.
 ///
-/// Removes a health record (weight, vaccine, or deworm) from the pet's
-/// health history. Requires ownership verification.
+/// Removes the pet and all related data (health records, notes, etc.)
+/// from the database. This operation is irreversible.
+///
+/// Reminders linked to the pet are removed too and their scheduled
+/// notifications cancelled; reminders without a pet are kept.
 ///
 /// # Arguments
-/// * `record_id` - ID of the health record to delete
-/// * `pet_external_id` - Public UUID of the pet
+/// * `pet_id` - ID of the pet to delete
 /// * `user_id` - ID of the user who owns the pet
-/// * `health_record` - Type of health record being deleted
 /// * `repo` - Repository instance for database operations
+/// * `notification_service` - Service for cancelling the pet reminders
+/// * `profile_cache` - Cache of public profiles, invalidated for the pet
 ///
 /// # Returns
 /// * `anyhow::Result<()>` - Success confirmation or error details
-pub async fn delete_pet_health_record(
-    record_id: i64,
-    pet_external_id: Uuid,
+pub async fn delete_pet_and_its_info(
+    pet_id: i64,
     user_id: i64,
-    health_record: &models::pet::PetHealthType,
     repo: &repo::ImplAppRepo,
+    notification_service: &services::ImplNotificationService,
+    profile_cache: &api::profile_cache::PublicProfileCache,
 ) -> anyhow::Result<()> {
-    match health_record {
-        models::pet::PetHealthType::Weight => Ok(repo
-            .delete_pet_weight(pet_external_id, user_id, record_id)
-            .await?),
-        models::pet::PetHealthType::Vaccine => Ok(repo
-            .delete_vaccine(pet_external_id, user_id, record_id)
-            .await?),
-        models::pet::PetHealthType::Deworm => Ok(repo
-            .delete_deworm(pet_external_id, user_id, record_id)
-            .await?),
+    let execution_ids = repo.delete_reminders_for_pet(pet_id, user_id).await?;
+    repo.delete_pet(pet_id, user_id).await?;
+    profile_cache.invalidate_pet(pet_id);
+
+    // The pet is already gone, a reminder that can't be cancelled only
+    // sends one stale notification
+    if let Err(e) = notification_service
+        .cancel_reminder_executions(&execution_ids)
+        .await
+    {
+        logfire::warn!(
+            "pet reminders couldn't be cancelled: {error}",
+            error = e.to_string()
+        );
     }
+
+    Ok(())
 }
 
-/// Retrieves all notes for a specific pet.
+/// Replaces the public external ID of a pet.
 ///
-/// Gets all user-created notes associated with the pet.
-/// Notes are private to the pet owner.
+/// Used when a physical QR tag is lost or compromised: the old external ID
+/// stops resolving to the pet while its health records, weights and notes
+/// are kept.
 ///
 /// # Arguments
+/// * `pet_id` - ID of the pet to rotate the external ID for
 /// * `user_id` - ID of the user who owns the pet
-/// * `pet_id` - ID of the pet to get notes for
 /// * `repo` - Repository instance for database operations
+/// * `profile_cache` - Cache of public profiles, the old external ID is dropped from it
 ///
 /// # Returns
-/// * `anyhow::Result<Vec<models::pet::PetNote>>` - List of pet notes
-pub async fn get_pet_notes(
-    user_id: i64,
+/// * `anyhow::Result<Uuid>` - The new external ID of the pet
+pub async fn rotate_pet_external_id(
     pet_id: i64,
+    user_id: i64,
     repo: &repo::ImplAppRepo,
-) -> anyhow::Result<Vec<models::pet::PetNote>> {
-    repo.get_pet_notes(user_id, pet_id).await
-}
+    profile_cache: &api::profile_cache::PublicProfileCache,
+) -> anyhow::Result<Uuid> {
+    let new_external_id = repo.rotate_pet_external_id(pet_id, user_id).await?;
+    profile_cache.invalidate_pet(pet_id);
 
-/// Information for creating a new pet note.
-///
-/// Contains the title and content for a new note to be added to a pet.
-pub struct PetNoteInfo {
-    /// Title of the note
-    pub title: String,
-    /// Main content/body of the note
-    pub body: String,
+    Ok(new_external_id)
 }
 
-/// Converts a PetNoteForm to PetNoteInfo.
-///
-/// Transforms form data into the structure needed for note creation.
-impl From<front::forms::pet::PetNoteForm> for PetNoteInfo {
-    fn from(val: front::forms::pet::PetNoteForm) -> Self {
-        PetNoteInfo {
-            title: val.title,
-            body: val.body,
-        }
-    }
+/// Errors raised when two pets can't be merged
+#[derive(Debug, Display, Error)]
+pub enum MergePetsError {
+    #[display("no se puede fusionar una mascota consigo misma")]
+    SamePet,
 }
 
-/// Adds a new note to a pet.
+/// Merges a pet created twice into the one that is kept.
 ///
-/// Creates a new note with the provided title and content,
-/// associating it with the specified pet and user.
+/// The duplicate's records are moved to the primary pet, which also takes
+/// the duplicate's picture and QR tag when it has none; the duplicate is
+/// deleted afterwards, see [`AppRepo::merge_pets`](repo::AppRepo::merge_pets).
+/// The duplicate's picture that wasn't kept is removed from the storage.
 ///
 /// # Arguments
-/// * `user_id` - ID of the user creating the note
-/// * `pet_id` - ID of the pet to add the note to
-/// * `note_info` - Note title and content
+/// * `primary_id` - ID of the pet that is kept
+/// * `duplicate_id` - ID of the pet merged into the primary one
+/// * `user_id` - ID of the user who must own both pets
 /// * `repo` - Repository instance for database operations
+/// * `storage_service` - Service where the pictures are stored
+/// * `profile_cache` - Cache of public profiles, both pets are dropped from it
+/// * `thumbnail_cache` - Cache of thumbnails, both pets are dropped from it
 ///
-/// # Returns
-/// * `anyhow::Result<()>` - Success confirmation or error details
-pub async fn add_new_note(
+/// # Errors
+/// Returns [`MergePetsError::SamePet`] if both IDs are the same pet, or
+/// [`RepoError::NotFound`](repo::errors::RepoError::NotFound) if the user
+/// doesn't own both pets.
+pub async fn merge_pets(
+    primary_id: i64,
+    duplicate_id: i64,
     user_id: i64,
-    pet_id: i64,
-    note_info: PetNoteInfo,
     repo: &repo::ImplAppRepo,
+    storage_service: &services::ImplStorageService,
+    profile_cache: &api::profile_cache::PublicProfileCache,
+    thumbnail_cache: &api::thumbnail::ThumbnailCache,
 ) -> anyhow::Result<()> {
-    repo.insert_new_pet_note(
-        user_id,
-        &models::pet::PetNote {
-            id: 0,
-            pet_id,
-            title: note_info.title.to_string(),
-            content: note_info.body.to_string(),
-            created_at: Utc::now(),
-            updated_at: Utc::now(),
-        },
-    )
-    .await?;
+    if primary_id == duplicate_id {
+        return Err(MergePetsError::SamePet.into());
+    }
+
+    let primary = repo.get_pet_by_id(primary_id, user_id).await?;
+    let duplicate = repo.get_pet_by_id(duplicate_id, user_id).await?;
+
+    let pic_not_moved = repo.merge_pets(primary_id, duplicate_id, user_id).await?;
+    profile_cache.invalidate_pet(primary_id);
+    profile_cache.invalidate_pet(duplicate_id);
+    thumbnail_cache.invalidate(primary.external_id);
+    thumbnail_cache.invalidate(duplicate.external_id);
+
+    // the merge is done, a picture left behind is only wasted storage
+    if let Some(pic) = pic_not_moved
+        && let Err(e) = storage_service.delete_pic(&pic).await
+    {
+        logfire::warn!(
+            "picture {pic} of merged pet {duplicate_id} could not be deleted: {error}",
+            pic = pic,
+            duplicate_id = duplicate_id,
+            error = e.to_string()
+        );
+    }
+
     Ok(())
 }
 
-/// Deletes a specific note from a pet.
+/// Errors raised when the PIN of a public profile can't be set
+#[derive(Debug, Display, Error)]
+pub enum PublicPinError {
+    #[display(
+        "el PIN debe tener de {} a {} digitos",
+        consts::PET_PUBLIC_PIN_MIN_LEN,
+        consts::PET_PUBLIC_PIN_MAX_LEN
+    )]
+    InvalidPin,
+    #[display("demasiados intentos, vuelve a intentar mas tarde")]
+    TooManyAttempts,
+}
+
+/// Sets the PIN visitors must enter to see the owner contacts on the public profile.
 ///
-/// Removes a note from the pet's note collection. Requires ownership
-/// verification to ensure only the pet owner can delete notes.
+/// The PIN is stored hashed; an empty PIN removes it and the contacts are
+/// shown to every visitor again.
 ///
 /// # Arguments
-/// * `note_id` - ID of the note to delete
-/// * `pet_id` - ID of the pet the note belongs to
+/// * `pet_id` - ID of the pet to update
 /// * `user_id` - ID of the user who owns the pet
+/// * `pin` - Digits chosen by the owner, empty to remove the PIN
 /// * `repo` - Repository instance for database operations
 ///
-/// # Returns
-/// * `anyhow::Result<()>` - Success confirmation or error details
-pub async fn delete_note(
-    note_id: i64,
+/// # Errors
+/// Returns [`PublicPinError::InvalidPin`] if the PIN isn't made of 4 to 8 digits.
+pub async fn set_pet_public_pin(
     pet_id: i64,
     user_id: i64,
+    pin: &str,
     repo: &repo::ImplAppRepo,
 ) -> anyhow::Result<()> {
-    repo.delete_pet_note(pet_id, user_id, note_id).await?;
-    Ok(())
+    let pin = pin.trim();
+    if pin.is_empty() {
+        return repo.set_pet_public_pin(pet_id, user_id, None).await;
+    }
+
+    if !(consts::PET_PUBLIC_PIN_MIN_LEN..=consts::PET_PUBLIC_PIN_MAX_LEN).contains(&pin.len())
+        || !pin.chars().all(|c| c.is_ascii_digit())
+    {
+        return Err(PublicPinError::InvalidPin.into());
+    }
+
+    repo.set_pet_public_pin(pet_id, user_id, Some(crate::utils::hash_secret(pin)?))
+        .await
 }
 
-/// Complete pet information including all related data.
-///
-/// Aggregates all pet-related information including the pet details,
-/// health records, and notes for comprehensive display or export.
-pub struct PetFullInfo {
-    /// Core pet information
-    pub pet: models::pet::Pet,
-    /// All vaccine records for the pet
-    pub vaccines: Vec<models::pet::PetHealth>,
-    /// All deworming records for the pet
-    pub deworms: Vec<models::pet::PetHealth>,
-    /// All weight records for the pet
-    pub weights: Vec<models::pet::PetWeight>,
-    /// All notes associated with the pet
-    pub notes: Vec<models::pet::PetNote>,
+/// Checks if the owner contacts of a public profile are hidden behind a PIN.
+pub async fn are_owner_contacts_locked(
+    pet_external_id: Uuid,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<bool> {
+    Ok(repo.get_pet_public_pin(pet_external_id).await?.is_some())
 }
 
-/// Retrieves complete pet information with all related data.
+/// Checks the PIN typed by a visitor to see the owner contacts.
 ///
-/// Gets the pet details along with all health records, notes, and other
-/// associated information for comprehensive display or data export.
+/// Wrong PINs are counted per pet, after
+/// [`PET_PUBLIC_PIN_MAX_FAILED_ATTEMPTS`](consts::PET_PUBLIC_PIN_MAX_FAILED_ATTEMPTS)
+/// the PIN is blocked for a while, whatever the address of the visitor.
 ///
 /// # Arguments
-/// * `pet_id` - ID of the pet to get information for
-/// * `user_id` - ID of the user who owns the pet
-/// * `repo` - Repository instance for database operations
+/// * `clock` - Source of the current time, to start and end the block
 ///
 /// # Returns
-/// * `anyhow::Result<PetFullInfo>` - Complete pet information structure
-pub async fn get_full_info(
-    pet_id: i64,
-    user_id: i64,
+/// * `anyhow::Result<bool>` - Whether the contacts can be shown, always
+///   true for pets without PIN
+///
+/// # Errors
+/// Returns [`PublicPinError::TooManyAttempts`] while the PIN is blocked.
+pub async fn unlock_owner_contacts(
+    pet_external_id: Uuid,
+    pin: &str,
     repo: &repo::ImplAppRepo,
-) -> anyhow::Result<PetFullInfo> {
-    let _span = logfire::span!("get_pet_full_info").entered();
+    clock: &services::ImplClock,
+) -> anyhow::Result<bool> {
+    let Some(pin_hash) = repo.get_pet_public_pin(pet_external_id).await? else {
+        return Ok(true);
+    };
 
-    let pet = repo.get_pet_by_id(pet_id, user_id).await?;
-    let external_id = pet.external_id;
-    let pet_id = pet.id;
+    let now = clock.now();
+    if repo
+        .get_pet_pin_locked_until(pet_external_id)
+        .await?
+        .is_some_and(|locked_until| locked_until > now)
+    {
+        return Err(PublicPinError::TooManyAttempts.into());
+    }
 
-    Ok(PetFullInfo {
-        pet,
-        vaccines: repo
-            .get_pet_health_records(
-                external_id,
-                Some(user_id),
-                models::pet::PetHealthType::Vaccine,
-            )
-            .await?,
-        deworms: repo
-            .get_pet_health_records(
-                external_id,
-                Some(user_id),
-                models::pet::PetHealthType::Deworm,
-            )
-            .await?,
-        weights: repo.get_pet_weights(external_id, Some(user_id)).await?,
-        notes: repo.get_pet_notes(user_id, pet_id).await?,
-    })
-}
+    if crate::utils::verify_secret(pin.trim(), &pin_hash) {
+        repo.clear_failed_pet_pin_attempts(pet_external_id).await?;
+        return Ok(true);
+    }
 
-/// Weight measurement data for PDF report generation
-#[derive(Debug, Clone, Default, Serialize, PartialEq)]
-pub struct WeightReport {
-    /// Weight value in user's preferred units
-    pub value: f64,
-    /// Timestamp when the weight was recorded
-    pub created_at: NaiveDateTime,
-    /// Formatted age string (e.g., "3 months, 2 weeks")
-    pub fmt_age: String,
+    repo.record_failed_pet_pin_attempt(
+        pet_external_id,
+        consts::PET_PUBLIC_PIN_MAX_FAILED_ATTEMPTS,
+        now + chrono::Duration::minutes(consts::PET_PUBLIC_PIN_LOCKOUT_MINUTES),
+    )
+    .await?;
+
+    Ok(false)
 }
 
-/// Converts HTML content in pet notes to plain text
-fn convert_html_to_text(note: &models::pet::PetNote) -> models::pet::PetNote {
-    models::pet::PetNote {
-        content: html2text::from_read(note.content.as_bytes(), 20)
-            .unwrap_or(note.content.to_string()),
-        ..note.clone()
-    }
+/// Errors raised when a share link can't be created
+#[derive(Debug, Display, Error)]
+pub enum ShareLinkError {
+    #[display("el link debe durar de 1 a {} dias", consts::PET_SHARE_LINK_MAX_DAYS)]
+    InvalidDuration,
 }
 
-/// Generates PDF report bytes for a pet by external ID
+/// Creates a temporary link to the public profile of a pet.
 ///
-/// Creates a comprehensive PDF report containing all pet information including
-/// health records, weight history, and notes. This function is designed for
-/// public access (e.g., WhatsApp bot) and doesn't require authentication.
-pub async fn generate_pdf_report_bytes(
-    pet_id: i64,
-    user_id: i64,
+/// Unlike `/info/{external_id}`, the link stops working when it expires or
+/// the owner revokes it, e.g. after a pet sitter is done.
+///
+/// # Arguments
+/// * `pet_id` - ID of the pet to share
+/// * `user_id` - ID of the user who owns the pet
+/// * `days` - Days the link stays valid, typed by the owner
+/// * `repo` - Repository instance for database operations
+/// * `clock` - Source of the current time, the link expires `days` after it
+///
+/// # Errors
+/// Returns [`ShareLinkError::InvalidDuration`] if `days` isn't a number from 1 to
+/// [PET_SHARE_LINK_MAX_DAYS](consts::PET_SHARE_LINK_MAX_DAYS).
+pub async fn create_pet_share_link(
+    pet_id: i64,
+    user_id: i64,
+    days: &str,
     repo: &repo::ImplAppRepo,
-    storage_service: &services::ImplStorageService,
-) -> anyhow::Result<Vec<u8>> {
-    let _span = logfire::span!("generate_pdf_report_bytes").entered();
+    clock: &services::ImplClock,
+) -> anyhow::Result<models::pet::PetShareToken> {
+    let days = days
+        .trim()
+        .parse::<i64>()
+        .ok()
+        .filter(|days| (1..=consts::PET_SHARE_LINK_MAX_DAYS).contains(days))
+        .ok_or(ShareLinkError::InvalidDuration)?;
 
-    let pet_full_info = get_full_info(pet_id, user_id, repo).await?;
+    let share_token = models::pet::PetShareToken {
+        token: crate::utils::generate_opaque_token(),
+        pet_id,
+        expires_at: clock.now() + chrono::Duration::days(days),
+    };
+    repo.insert_pet_share_token(pet_id, user_id, &share_token.token, share_token.expires_at)
+        .await?;
 
-    let now = chrono::Utc::now().date_naive();
+    Ok(share_token)
+}
 
-    // Convert HTML notes to plain text
-    let notes = pet_full_info
-        .notes
-        .iter()
-        .map(convert_html_to_text)
-        .collect::<Vec<models::pet::PetNote>>();
+/// Revokes a share link of a pet, it stops working right away.
+pub async fn revoke_pet_share_link(
+    pet_id: i64,
+    user_id: i64,
+    token: &str,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<()> {
+    repo.delete_pet_share_token(pet_id, user_id, token).await
+}
 
-    // Calculate formatted age for each weight measurement
-    let weights: Vec<WeightReport> = pet_full_info
-        .weights
-        .iter()
-        .map(|w| WeightReport {
-            value: w.value,
-            created_at: w.created_at,
-            fmt_age: front::utils::fmt_dates_difference(
-                pet_full_info.pet.birthday,
-                w.created_at.into(),
-            ),
+/// Retrieves the share links of a pet that still work.
+pub async fn get_pet_share_links(
+    pet_id: i64,
+    user_id: i64,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<Vec<models::pet::PetShareToken>> {
+    repo.get_pet_share_tokens(pet_id, user_id).await
+}
+
+/// Resolves a share link to the external ID of the profile it shows.
+///
+/// # Returns
+/// * `anyhow::Result<Option<Uuid>>` - `None` for unknown, revoked or expired links
+pub async fn resolve_pet_share_link(
+    token: &str,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<Option<Uuid>> {
+    repo.get_pet_external_id_by_share_token(token).await
+}
+
+/// Marks a pet as lost or found without resending the whole pet form.
+///
+/// # Arguments
+/// * `pet_id` - ID of the pet to update
+/// * `user_id` - ID of the user who owns the pet
+/// * `is_lost` - Whether the pet is lost
+/// * `repo` - Repository instance for database operations
+/// * `profile_cache` - Cache of public profiles, invalidated so finders see the new status
+pub async fn set_pet_lost_status(
+    pet_id: i64,
+    user_id: i64,
+    is_lost: bool,
+    repo: &repo::ImplAppRepo,
+    profile_cache: &api::profile_cache::PublicProfileCache,
+) -> anyhow::Result<()> {
+    repo.set_pet_lost_status(pet_id, user_id, is_lost).await?;
+    profile_cache.invalidate_pet(pet_id);
+
+    Ok(())
+}
+
+/// Sets whether a pet is spayed/neutered without resending the whole pet form.
+///
+/// # Arguments
+/// * `pet_id` - ID of the pet to update
+/// * `user_id` - ID of the user who owns the pet
+/// * `is_spaying_neutering` - Whether the pet is spayed/neutered
+/// * `repo` - Repository instance for database operations
+/// * `profile_cache` - Cache of public profiles, invalidated for the pet
+pub async fn set_pet_spaying_neutering_status(
+    pet_id: i64,
+    user_id: i64,
+    is_spaying_neutering: bool,
+    repo: &repo::ImplAppRepo,
+    profile_cache: &api::profile_cache::PublicProfileCache,
+) -> anyhow::Result<()> {
+    repo.set_pet_spaying_neutering_status(pet_id, user_id, is_spaying_neutering)
+        .await?;
+    profile_cache.invalidate_pet(pet_id);
+
+    Ok(())
+}
+
+/// Errors raised when a pet can't be offered to another account
+#[derive(Debug, Display, Error)]
+pub enum TransferPetError {
+    #[display("el correo del nuevo dueño no es valido")]
+    InvalidEmail,
+    #[display("el correo no tiene una cuenta registrada")]
+    UnknownRecipient,
+}
+
+/// Offers a pet to the account of `to_user_email`.
+///
+/// The pet stays with its owner until the recipient accepts the offer with
+/// [`accept_pet_transfer`]. Only existing accounts can receive a pet.
+///
+/// # Arguments
+/// * `pet_id` - ID of the pet to transfer
+/// * `from_user_id` - ID of the user who currently owns the pet
+/// * `to_user_email` - Email of the new owner
+/// * `repo` - Repository instance for database operations
+///
+/// # Errors
+/// Returns an error if:
+/// - The email is not valid ([`TransferPetError::InvalidEmail`])
+/// - The email has no account or it's the owner's own ([`TransferPetError::UnknownRecipient`])
+/// - The pet doesn't belong to `from_user_id`
+pub async fn offer_pet_transfer(
+    pet_id: i64,
+    from_user_id: i64,
+    to_user_email: &str,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<()> {
+    let to_user_email = to_user_email.trim().to_lowercase();
+    if to_user_email.is_empty() || !to_user_email.contains('@') {
+        return Err(TransferPetError::InvalidEmail.into());
+    }
+
+    repo.offer_pet_transfer(pet_id, from_user_id, &to_user_email)
+        .await
+        .map_err(|e| match e {
+            repo::errors::RepoError::NotFound => TransferPetError::UnknownRecipient.into(),
+            e => e.into(),
         })
-        .collect();
+}
 
-    // Generate QR code from public link
-    let pet_link = format!(
-        "https://pet-info.link/info/{external_id}",
-        external_id = pet_full_info.pet.external_id
-    );
-    let qr_code_data = crate::qr::get_qr_code(&pet_link)?;
+/// Retrieves the pets offered to a user and waiting for an answer
+///
+/// # Arguments
+/// * `to_user_id` - ID of the recipient
+/// * `repo` - Repository instance for database operations
+pub async fn get_incoming_pet_transfers(
+    to_user_id: i64,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<Vec<models::pet::PetTransferOffer>> {
+    repo.get_incoming_pet_transfers(to_user_id).await
+}
 
-    let pet_pic_option = if pet_full_info.pet.pic.is_some() {
-        get_public_pic(pet_full_info.pet.external_id, repo, storage_service).await?
-    } else {
-        None
+/// Accepts a pet offered to the user, it becomes the owner with all the
+/// pet's records.
+///
+/// # Arguments
+/// * `pet_id` - ID of the offered pet
+/// * `to_user_id` - ID of the recipient
+/// * `max_pets` - Most pets an account can have
+/// * `repo` - Repository instance for database operations
+/// * `profile_cache` - Cache of public profiles, invalidated for the pet
+///
+/// # Errors
+/// Returns an error if:
+/// - The account already has `max_pets` pets ([`AddPetError::MaxPetsReached`])
+/// - There is no offer of the pet to the user ([`RepoError::NotFound`](repo::errors::RepoError::NotFound))
+pub async fn accept_pet_transfer(
+    pet_id: i64,
+    to_user_id: i64,
+    max_pets: u64,
+    repo: &repo::ImplAppRepo,
+    profile_cache: &api::profile_cache::PublicProfileCache,
+) -> anyhow::Result<()> {
+    let current_pets = repo.count_user_pets(to_user_id).await?;
+    if u64::try_from(current_pets).unwrap_or_default() >= max_pets {
+        return Err(AddPetError::MaxPetsReached { max: max_pets }.into());
+    }
+
+    repo.accept_pet_transfer(pet_id, to_user_id).await?;
+    profile_cache.invalidate_pet(pet_id);
+
+    Ok(())
+}
+
+/// Withdraws or declines the pending offer of a pet
+///
+/// # Arguments
+/// * `pet_id` - ID of the offered pet
+/// * `user_id` - ID of the owner or the recipient of the offer
+/// * `repo` - Repository instance for database operations
+pub async fn cancel_pet_transfer(
+    pet_id: i64,
+    user_id: i64,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<()> {
+    repo.cancel_pet_transfer(pet_id, user_id).await
+}
+
+/// Errors raised when a found report can't be sent to the pet owner
+#[derive(Debug, Display, Error)]
+pub enum FoundReportError {
+    #[display("escribe un mensaje para el dueño")]
+    EmptyMessage,
+    #[display("el mensaje es demasiado largo")]
+    MessageTooLong,
+    #[display("el contacto es demasiado largo")]
+    ContactTooLong,
+    #[display("la mascota no esta reportada como perdida")]
+    PetNotLost,
+}
+
+/// Relays the message of someone who found a lost pet to its owner.
+///
+/// The report is stored so the owner keeps a history, then sent right away
+/// through the owner's verified phone (WhatsApp) and email. The finder's
+/// contact is only shared with the owner, never shown on the public profile.
+///
+/// # Arguments
+/// * `pet_external_id` - Public UUID of the pet
+/// * `message` - Message left by the finder
+/// * `callback_contact` - Optional contact to reach the finder
+/// * `repo` - Repository instance for database operations
+/// * `notification_service` - Service used to deliver the message
+/// * `clock` - Source of the time the report is sent at
+///
+/// # Errors
+/// Returns an error if:
+/// - The message or the contact are not valid ([`FoundReportError`])
+/// - The pet is not reported as lost ([`FoundReportError::PetNotLost`])
+/// - The report couldn't be stored or sent
+pub async fn report_found_pet(
+    pet_external_id: Uuid,
+    message: &str,
+    callback_contact: Option<&str>,
+    repo: &repo::ImplAppRepo,
+    notification_service: &services::ImplNotificationService,
+    clock: &services::ImplClock,
+) -> anyhow::Result<()> {
+    use models::reminder::ReminderNotificationType;
+
+    let message = message.trim();
+    if message.is_empty() {
+        return Err(FoundReportError::EmptyMessage.into());
+    }
+    if message.chars().count() > consts::FOUND_REPORT_MAX_MESSAGE_LEN {
+        return Err(FoundReportError::MessageTooLong.into());
+    }
+    let callback_contact = callback_contact
+        .map(str::trim)
+        .filter(|contact| !contact.is_empty());
+    if callback_contact
+        .is_some_and(|contact| contact.chars().count() > consts::FOUND_REPORT_MAX_CONTACT_LEN)
+    {
+        return Err(FoundReportError::ContactTooLong.into());
+    }
+
+    let pet = repo.get_pet_by_external_id(pet_external_id).await?;
+    if !pet.is_lost {
+        return Err(FoundReportError::PetNotLost.into());
+    }
+    let Some(owner) = repo.get_pet_owner_by_external_id(pet_external_id).await? else {
+        bail!("pet external id {pet_external_id} has no owner");
     };
-    let image_filename = pet_pic_option
-        .as_ref()
-        .map(|pic| format!("pet.{}", pic.extension));
 
-    let content = front::templates::PDF_REPORT_TEMPLATES.render(
-        "pet_default.typ",
-        &tera::Context::from_value(serde_json::json!({
-            "pet_name": pet_full_info.pet.pet_name,
-            "birthday": pet_full_info.pet.birthday,
-            "age": front::utils::fmt_dates_difference(pet_full_info.pet.birthday, now),
-            "breed": pet_full_info.pet.breed,
-            "is_female": pet_full_info.pet.is_female,
-            "is_spaying_neutering": pet_full_info.pet.is_spaying_neutering,
-            "pet_link": pet_link,
-            "vaccines": pet_full_info.vaccines,
-            "deworms": pet_full_info.deworms,
-            "weights": weights,
-            "notes": notes,
-            "image_filename": image_filename.as_deref().unwrap_or("NO_PIC"),
-        }))
-        .unwrap_or_default(),
-    )?;
+    let now = clock.now();
+    repo.insert_found_report(
+        pet_external_id,
+        message.to_string(),
+        callback_contact.map(str::to_string),
+        now,
+    )
+    .await?;
+
+    let mut body = format!("¡Alguien encontró a {}! Mensaje: {message}", pet.pet_name);
+    if let Some(contact) = callback_contact {
+        body.push_str(&format!(" | Contacto: {contact}"));
+    }
+
+    let mut channels = vec![ReminderNotificationType::Email];
+    if owner.phone_reminder.is_some() {
+        channels.push(ReminderNotificationType::WhatsApp);
+    }
+
+    notification_service
+        .send_reminder_to_phone_number(&api::reminder::ScheduleReminderInfo {
+            user_id: owner.id,
+            channels: channels.into_iter().collect(),
+            phone_number: owner.phone_reminder,
+            email: Some(owner.email),
+            when: now.with_timezone(&front::utils::default_timezone()),
+            body,
+            pet_id: None,
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// Health record values submitted by the user.
+///
+/// Groups the record content with the optional booster interval used
+/// to auto-schedule a reminder when a vaccine is recorded.
+pub struct HealthRecordEntry {
+    /// Record description/value (weight amount, vaccine name, etc.)
+    pub desc: String,
+    /// Date for the health record
+    pub date: NaiveDate,
+    /// Months until the vaccine booster is due, only used for vaccines
+    pub booster_months: Option<u32>,
+    /// Timezone of the user, used to schedule the booster reminder
+    pub user_timezone: Tz,
+}
+
+/// Result of adding a new health record.
+///
+/// The record is always persisted; `warning` carries a soft, user facing
+/// message when the booster reminder couldn't be scheduled or the weight
+/// looks like a typo.
+pub struct HealthRecordInsertOutcome {
+    /// The created health record
+    pub record: PetHealthRecord,
+    /// Message to show to the user if the booster reminder was skipped or
+    /// the weight is far from the recent ones
+    pub warning: Option<String>,
+}
+
+/// Checks whether a new weight deviates from the recent ones beyond `factor`.
+///
+/// # Arguments
+/// * `new_kg` - The weight being added, in kilograms
+/// * `weights` - Weights already recorded for the pet
+/// * `factor` - Times above or below the average a weight is an outlier
+///
+/// # Returns
+/// * The average of the [latest weights](consts::WEIGHT_OUTLIER_RECENT_COUNT)
+///   if the new weight is an outlier, `None` otherwise or without history
+pub fn weight_outlier_average(
+    new_kg: f64,
+    weights: &[models::pet::PetWeight],
+    factor: f64,
+) -> Option<f64> {
+    let mut recent: Vec<&models::pet::PetWeight> = weights.iter().collect();
+    recent.sort_by_key(|w| std::cmp::Reverse((w.created_at, w.id)));
+    recent.truncate(consts::WEIGHT_OUTLIER_RECENT_COUNT);
+
+    if recent.is_empty() {
+        return None;
+    }
+
+    let average = recent.iter().map(|w| w.value).sum::<f64>() / recent.len() as f64;
+    if average <= 0.0 {
+        return None;
+    }
+
+    let is_outlier = new_kg > average * factor || new_kg < average / factor;
+
+    is_outlier.then_some(average)
+}
+
+/// Configured weight outlier factor, the const when the config isn't loaded
+fn weight_outlier_factor() -> f64 {
+    config::APP_CONFIG
+        .get()
+        .map(|app_config| app_config.weight_outlier_factor())
+        .unwrap_or(consts::WEIGHT_OUTLIER_FACTOR)
+}
+
+/// Hour of the day (user local time) when booster reminders are sent.
+const BOOSTER_REMINDER_HOUR: u32 = 9;
+
+/// Adds a new health record to a pet.
+///
+/// Creates a new health record (weight, vaccine, or deworm) for the specified
+/// pet with the provided information and date. When a vaccine is recorded with
+/// a booster interval, a reminder is scheduled for `date + booster_months`.
+///
+/// # Arguments
+/// * `pet_external_id` - Public UUID of the pet
+/// * `health_record` - Type of health record to create
+/// * `user` - User who owns the pet
+/// * `entry` - Record values and optional booster interval
+/// * `repo` - Repository instance for database operations
+/// * `notification_service` - Service for scheduling the booster reminder
+/// * `profile_cache` - Cache of public profiles, invalidated when a weight is added
+/// * `clock` - Source of the current time, a booster already due isn't scheduled
+///
+/// # Returns
+/// * `anyhow::Result<HealthRecordInsertOutcome>` - The created health record and an optional warning
+///
+/// # Errors
+/// Returns an error only if the health record can't be stored. Failing to
+/// schedule the booster reminder is reported through `warning`.
+#[allow(clippy::too_many_arguments)]
+pub async fn insert_pet_health_record(
+    pet_external_id: Uuid,
+    health_record: &models::pet::PetHealthType,
+    user: &models::user_app::User,
+    entry: HealthRecordEntry,
+    repo: &repo::ImplAppRepo,
+    notification_service: &services::ImplNotificationService,
+    profile_cache: &api::profile_cache::PublicProfileCache,
+    clock: &services::ImplClock,
+) -> anyhow::Result<HealthRecordInsertOutcome> {
+    let (pet_id, record): (i64, PetHealthRecord) = match health_record {
+        models::pet::PetHealthType::Weight => {
+            let new_kg = user
+                .weight_unit
+                .to_kg(entry.desc.parse::<f64>().unwrap_or(0.0));
+            // a history that can't be read just skips the outlier check
+            let previous_weights = repo
+                .get_pet_weights(pet_external_id, Some(user.id))
+                .await
+                .unwrap_or_default();
+
+            let weight = repo
+                .insert_pet_weight(pet_external_id, user.id, new_kg, entry.date)
+                .await?;
+            profile_cache.invalidate_pet(weight.pet_id);
+
+            let warning = weight_outlier_average(new_kg, &previous_weights, weight_outlier_factor()).map(|average| {
+                format!(
+                    "Peso guardado. ¿Seguro que es correcto? Es muy distinto al promedio reciente de {:.2} {}",
+                    user.weight_unit.from_kg(average),
+                    user.weight_unit
+                )
+            });
+            return Ok(HealthRecordInsertOutcome {
+                record: PetHealthRecord::from_weight(weight, user.weight_unit),
+                warning,
+            });
+        }
+        models::pet::PetHealthType::Vaccine => {
+            let vaccine = repo
+                .insert_vaccine_to(
+                    pet_external_id,
+                    user.id,
+                    entry.desc.to_string(),
+                    entry.date,
+                    entry.booster_months,
+                )
+                .await?;
+            (vaccine.pet_id, vaccine.into())
+        }
+        models::pet::PetHealthType::Deworm => {
+            let deworm = repo
+                .insert_deworm_to(pet_external_id, user.id, entry.desc.to_string(), entry.date)
+                .await?;
+            (deworm.pet_id, deworm.into())
+        }
+    };
+
+    let warning = match (health_record, entry.booster_months) {
+        (models::pet::PetHealthType::Vaccine, Some(booster_months)) if booster_months > 0 => {
+            schedule_vaccine_booster_reminder(
+                user,
+                pet_id,
+                &entry,
+                booster_months,
+                repo,
+                notification_service,
+                clock,
+            )
+            .await
+        }
+        _ => None,
+    };
+
+    Ok(HealthRecordInsertOutcome { record, warning })
+}
+
+/// Schedules a reminder for the booster of a recorded vaccine, through the
+/// channels of [automatic_reminder_channels](api::reminder::automatic_reminder_channels).
+///
+/// # Returns
+/// * `Option<String>` - A soft warning if the reminder couldn't be scheduled
+async fn schedule_vaccine_booster_reminder(
+    user: &models::user_app::User,
+    pet_id: i64,
+    entry: &HealthRecordEntry,
+    booster_months: u32,
+    repo: &repo::ImplAppRepo,
+    notification_service: &services::ImplNotificationService,
+    clock: &services::ImplClock,
+) -> Option<String> {
+    // the DST adjustment isn't shown, the booster hour is picked by the app
+    let Some(when) = entry
+        .date
+        .checked_add_months(Months::new(booster_months))
+        .and_then(|d| d.and_hms_opt(BOOSTER_REMINDER_HOUR, 0, 0))
+        .map(|dt| api::reminder::resolve_local_datetime(dt, entry.user_timezone).0)
+        .filter(|when| *when > clock.now())
+        .map(|when| when.with_timezone(&entry.user_timezone))
+    else {
+        return Some(
+            "Vacuna guardada. La fecha del refuerzo ya paso, no se programo recordatorio".into(),
+        );
+    };
+
+    let reminder_info = api::reminder::ScheduleReminderInfo {
+        user_id: user.id,
+        channels: api::reminder::automatic_reminder_channels(user),
+        phone_number: user.phone_reminder.clone(),
+        email: Some(user.email.clone()),
+        when,
+        body: format!("Refuerzo de vacuna: {}", entry.desc),
+        pet_id: Some(pet_id),
+    };
+
+    if let Err(e) =
+        api::reminder::schedule_reminder(reminder_info, repo, notification_service, clock).await
+    {
+        logfire::warn!(
+            "booster reminder couldn't be scheduled: {error}",
+            error = e.to_string()
+        );
+        return Some("Vacuna guardada. No se pudo programar el recordatorio del refuerzo".into());
+    }
+
+    None
+}
+
+/// Date formats accepted when importing health records.
+const IMPORT_DATE_FORMATS: [&str; 2] = ["%Y-%m-%d", "%d/%m/%Y"];
+
+/// Summary of a health records import.
+#[derive(Debug, Default, Serialize)]
+pub struct HealthRecordsImport {
+    /// Number of rows saved
+    pub imported: u64,
+    /// Number of rows that couldn't be parsed
+    pub failed: usize,
+    /// Reason of each failed row, with its line number
+    pub errors: Vec<String>,
+}
+
+/// Parses `date,description` rows of a health records CSV.
+///
+/// A header row starting with `date` is skipped, as well as blank lines.
+/// The description may contain commas and be wrapped in double quotes.
+///
+/// # Returns
+/// * `(Vec<(NaiveDate, String)>, Vec<String>)` - Valid records and the errors of invalid rows
+fn parse_health_records_csv(csv: &str) -> (Vec<(NaiveDate, String)>, Vec<String>) {
+    let mut records = Vec::new();
+    let mut errors = Vec::new();
+
+    for (i, line) in csv.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.trim();
+        if line.is_empty() || (i == 0 && line.to_lowercase().starts_with("date")) {
+            continue;
+        }
+
+        let Some((date, desc)) = line.split_once(',') else {
+            errors.push(format!(
+                "línea {line_number}: se esperaba fecha,descripcion"
+            ));
+            continue;
+        };
+
+        let date = date.trim().trim_matches('"');
+        let Some(date) = IMPORT_DATE_FORMATS
+            .iter()
+            .find_map(|fmt| NaiveDate::parse_from_str(date, fmt).ok())
+        else {
+            errors.push(format!("línea {line_number}: fecha inválida '{date}'"));
+            continue;
+        };
+
+        let desc = ammonia::clean(desc.trim().trim_matches('"').trim());
+        if desc.is_empty() {
+            errors.push(format!("línea {line_number}: descripción vacía"));
+            continue;
+        }
+
+        records.push((date, desc));
+    }
+
+    (records, errors)
+}
+
+/// Imports past health records of a pet from a CSV.
+///
+/// Rows are `date,description`. Invalid rows are reported in the summary
+/// instead of aborting the import, valid rows are saved in a single transaction.
+///
+/// # Arguments
+/// * `pet_external_id` - Public UUID of the pet
+/// * `user_id` - ID of the user who owns the pet
+/// * `health_type` - Type of the imported records (vaccine or deworm)
+/// * `csv` - CSV content
+/// * `repo` - Repository instance for database operations
+///
+/// # Returns
+/// * `anyhow::Result<HealthRecordsImport>` - Counts of imported and failed rows
+///
+/// # Errors
+/// Returns an error if the type is weight, the pet is not owned by the user
+/// or the database operation fails
+pub async fn import_health_records_csv(
+    pet_external_id: Uuid,
+    user_id: i64,
+    health_type: &models::pet::PetHealthType,
+    csv: &str,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<HealthRecordsImport> {
+    if health_type.eq(&models::pet::PetHealthType::Weight) {
+        bail!("weight records can't be imported as health records");
+    }
+
+    let (records, errors) = parse_health_records_csv(csv);
+    let mut summary = HealthRecordsImport {
+        failed: errors.len(),
+        errors,
+        ..Default::default()
+    };
+
+    if records.is_empty() {
+        return Ok(summary);
+    }
+
+    summary.imported = repo
+        .insert_health_records_batch(pet_external_id, user_id, health_type.clone(), records)
+        .await?;
+
+    Ok(summary)
+}
+
+/// Updates the value and date of a health record, keeping its id.
+///
+/// Fixes typos in a vaccine/deworm description or a mistyped weight
+/// without deleting and re-adding the record.
+///
+/// # Arguments
+/// * `record_id` - ID of the health record to update
+/// * `pet_external_id` - Public UUID of the pet
+/// * `user_id` - ID of the user who owns the pet
+/// * `health_record` - Type of health record being updated
+/// * `desc` - New record value, weights are parsed as `f64`
+/// * `weight_unit` - Unit a weight `desc` is typed in, it's stored in kilograms
+/// * `date` - New date of the record
+/// * `repo` - Repository instance for database operations
+/// * `profile_cache` - Cache of public profiles, invalidated when a weight changes
+///
+/// # Returns
+/// * `anyhow::Result<()>` - Success confirmation or error details
+#[allow(clippy::too_many_arguments)]
+pub async fn update_pet_health_record(
+    record_id: i64,
+    pet_external_id: Uuid,
+    user_id: i64,
+    health_record: &models::pet::PetHealthType,
+    desc: &str,
+    weight_unit: models::user_app::WeightUnit,
+    date: NaiveDate,
+    repo: &repo::ImplAppRepo,
+    profile_cache: &api::profile_cache::PublicProfileCache,
+) -> anyhow::Result<()> {
+    match health_record {
+        models::pet::PetHealthType::Weight => {
+            repo.update_pet_weight(
+                record_id,
+                pet_external_id,
+                user_id,
+                weight_unit.to_kg(desc.parse::<f64>().unwrap_or(0.0)),
+                date,
+            )
+            .await?;
+            profile_cache.invalidate(pet_external_id);
+            Ok(())
+        }
+        _ => {
+            repo.update_pet_health_record(
+                record_id,
+                pet_external_id,
+                user_id,
+                health_record.clone(),
+                desc.to_string(),
+                date,
+            )
+            .await
+        }
+    }
+}
+
+/// Deletes a specific health record from a pet.
+///
+/// Removes a health record (weight, vaccine, or deworm) from the pet's
+/// health history. Requires ownership verification.
+///
+/// # Arguments
+/// * `record_id` - ID of the health record to delete
+/// * `pet_external_id` - Public UUID of the pet
+/// * `user_id` - ID of the user who owns the pet
+/// * `health_record` - Type of health record being deleted
+/// * `repo` - Repository instance for database operations
+/// * `profile_cache` - Cache of public profiles, invalidated when a weight is deleted
+///
+/// # Returns
+/// * `anyhow::Result<()>` - Success confirmation or error details
+pub async fn delete_pet_health_record(
+    record_id: i64,
+    pet_external_id: Uuid,
+    user_id: i64,
+    health_record: &models::pet::PetHealthType,
+    repo: &repo::ImplAppRepo,
+    profile_cache: &api::profile_cache::PublicProfileCache,
+) -> anyhow::Result<()> {
+    match health_record {
+        models::pet::PetHealthType::Weight => {
+            repo.delete_pet_weight(pet_external_id, user_id, record_id)
+                .await?;
+            profile_cache.invalidate(pet_external_id);
+            Ok(())
+        }
+        models::pet::PetHealthType::Vaccine => Ok(repo
+            .delete_vaccine(pet_external_id, user_id, record_id)
+            .await?),
+        models::pet::PetHealthType::Deworm => Ok(repo
+            .delete_deworm(pet_external_id, user_id, record_id)
+            .await?),
+    }
+}
+
+/// Deletes every health record of a type from a pet.
+///
+/// Used to clear the records before importing them again. Requires
+/// ownership verification.
+///
+/// # Arguments
+/// * `pet_external_id` - Public UUID of the pet
+/// * `user_id` - ID of the user who owns the pet
+/// * `health_record` - Type of health records being deleted
+/// * `repo` - Repository instance for database operations
+/// * `profile_cache` - Cache of public profiles, invalidated when weights are deleted
+///
+/// # Returns
+/// * `anyhow::Result<u64>` - Number of deleted records
+pub async fn delete_all_pet_health_records(
+    pet_external_id: Uuid,
+    user_id: i64,
+    health_record: &models::pet::PetHealthType,
+    repo: &repo::ImplAppRepo,
+    profile_cache: &api::profile_cache::PublicProfileCache,
+) -> anyhow::Result<u64> {
+    match health_record {
+        models::pet::PetHealthType::Weight => {
+            let deleted = repo.delete_all_weights(pet_external_id, user_id).await?;
+            profile_cache.invalidate(pet_external_id);
+            Ok(deleted)
+        }
+        models::pet::PetHealthType::Vaccine | models::pet::PetHealthType::Deworm => Ok(repo
+            .delete_all_health_records(pet_external_id, user_id, health_record.clone())
+            .await?),
+    }
+}
+
+/// A page of the notes of a pet.
+#[derive(Debug, Serialize)]
+pub struct PetNotesPage {
+    /// Notes of the page, newest first
+    pub notes: Vec<models::pet::PetNote>,
+    /// Offset of the next page, `None` when this is the last one
+    pub next_offset: Option<i64>,
+}
+
+/// Retrieves a page of the notes of a pet.
+///
+/// Gets [`PET_NOTES_PAGE_SIZE`](consts::PET_NOTES_PAGE_SIZE) user-created
+/// notes associated with the pet, newest first.
+/// Notes are private to the pet owner.
+///
+/// # Arguments
+/// * `user_id` - ID of the user who owns the pet
+/// * `pet_id` - ID of the pet to get notes for
+/// * `offset` - Number of notes to skip
+/// * `repo` - Repository instance for database operations
+///
+/// # Returns
+/// * `anyhow::Result<PetNotesPage>` - Page of pet notes
+pub async fn get_pet_notes(
+    user_id: i64,
+    pet_id: i64,
+    offset: i64,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<PetNotesPage> {
+    let offset = offset.max(0);
+    let notes = repo
+        .get_pet_notes(user_id, pet_id, consts::PET_NOTES_PAGE_SIZE, offset)
+        .await?;
+    let total = repo.get_pet_notes_count(user_id, pet_id).await?;
+
+    let loaded = offset + i64::try_from(notes.len()).unwrap_or_default();
+    Ok(PetNotesPage {
+        notes,
+        next_offset: (loaded < total).then_some(loaded),
+    })
+}
+
+/// Searches the notes of a pet by title or content.
+///
+/// An empty query returns all the notes of the pet.
+///
+/// # Arguments
+/// * `user_id` - ID of the user who owns the pet
+/// * `pet_id` - ID of the pet to search notes for
+/// * `query` - Text typed by the user
+/// * `repo` - Repository instance for database operations
+///
+/// # Returns
+/// * `anyhow::Result<Vec<models::pet::PetNote>>` - Matching pet notes
+pub async fn search_pet_notes(
+    user_id: i64,
+    pet_id: i64,
+    query: &str,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<Vec<models::pet::PetNote>> {
+    let query = query.trim();
+    if query.is_empty() {
+        return repo.get_all_pet_notes(user_id, pet_id).await;
+    }
+
+    repo.search_pet_notes(user_id, pet_id, query.to_string())
+        .await
+}
+
+/// Information for creating a new pet note.
+///
+/// Contains the title and content for a new note to be added to a pet.
+pub struct PetNoteInfo {
+    /// Title of the note
+    pub title: String,
+    /// Main content/body of the note
+    pub body: String,
+}
+
+/// Converts a PetNoteForm to PetNoteInfo.
+///
+/// Transforms form data into the structure needed for note creation.
+impl From<front::forms::pet::PetNoteForm> for PetNoteInfo {
+    fn from(val: front::forms::pet::PetNoteForm) -> Self {
+        PetNoteInfo {
+            title: val.title,
+            body: val.body,
+        }
+    }
+}
+
+/// Errors raised when a pet note is not valid
+#[derive(Debug, Display, Error)]
+pub enum PetNoteError {
+    #[display("el titulo de la nota es demasiado largo")]
+    TitleTooLong,
+    #[display("el contenido de la nota es demasiado largo")]
+    BodyTooLong,
+}
+
+impl PetNoteInfo {
+    /// Strips unsafe HTML from the title and content and checks their
+    /// length, measured once sanitized.
+    fn sanitized(self) -> Result<Self, PetNoteError> {
+        let title = ammonia::clean(self.title.trim());
+        if title.chars().count() > consts::PET_NOTE_MAX_TITLE_LEN {
+            return Err(PetNoteError::TitleTooLong);
+        }
+
+        let body = front::sanitize::rich_text(&self.body);
+        if body.chars().count() > consts::PET_NOTE_MAX_BODY_LEN {
+            return Err(PetNoteError::BodyTooLong);
+        }
+
+        Ok(Self { title, body })
+    }
+}
+
+/// Adds a new note to a pet.
+///
+/// Creates a new note with the provided title and content,
+/// associating it with the specified pet and user. Both are sanitized
+/// before being stored.
+///
+/// # Arguments
+/// * `user_id` - ID of the user creating the note
+/// * `pet_id` - ID of the pet to add the note to
+/// * `note_info` - Note title and content
+/// * `repo` - Repository instance for database operations
+/// * `clock` - Source of the note creation time
+///
+/// # Returns
+/// * `anyhow::Result<()>` - Success confirmation or error details
+///
+/// # Errors
+/// Returns an error if:
+/// - The title or content are too long ([`PetNoteError`])
+/// - Database operation fails
+pub async fn add_new_note(
+    user_id: i64,
+    pet_id: i64,
+    note_info: PetNoteInfo,
+    repo: &repo::ImplAppRepo,
+    clock: &services::ImplClock,
+) -> anyhow::Result<()> {
+    let note_info = note_info.sanitized()?;
+    let now = clock.now();
+    repo.insert_new_pet_note(
+        user_id,
+        &models::pet::PetNote {
+            id: 0,
+            pet_id,
+            title: note_info.title.to_string(),
+            content: note_info.body.to_string(),
+            created_at: now,
+            updated_at: now,
+        },
+    )
+    .await?;
+    Ok(())
+}
+
+/// Edits the title and content of a pet note.
+///
+/// Requires ownership verification, the note's pet must belong to the user.
+/// The new title and content are sanitized like in [`add_new_note`].
+///
+/// # Arguments
+/// * `user_id` - ID of the user who owns the pet
+/// * `note_id` - ID of the note to edit
+/// * `note_info` - New note title and content
+/// * `repo` - Repository instance for database operations
+///
+/// # Returns
+/// * `anyhow::Result<()>` - Success confirmation or error details
+///
+/// # Errors
+/// Returns an error if:
+/// - The title or content are too long ([`PetNoteError`])
+/// - Database operation fails
+pub async fn edit_note(
+    user_id: i64,
+    note_id: i64,
+    note_info: PetNoteInfo,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<()> {
+    let note_info = note_info.sanitized()?;
+    repo.update_pet_note(user_id, note_id, note_info.title, note_info.body)
+        .await
+}
+
+/// Deletes a specific note from a pet.
+///
+/// Removes a note from the pet's note collection. Requires ownership
+/// verification to ensure only the pet owner can delete notes.
+///
+/// # Arguments
+/// * `note_id` - ID of the note to delete
+/// * `pet_id` - ID of the pet the note belongs to
+/// * `user_id` - ID of the user who owns the pet
+/// * `repo` - Repository instance for database operations
+///
+/// # Returns
+/// * `anyhow::Result<()>` - Success confirmation or error details
+pub async fn delete_note(
+    note_id: i64,
+    pet_id: i64,
+    user_id: i64,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<()> {
+    repo.delete_pet_note(pet_id, user_id, note_id).await?;
+    Ok(())
+}
+
+/// Complete pet information including all related data.
+///
+/// Aggregates all pet-related information including the pet details,
+/// health records, and notes for comprehensive display or export.
+pub struct PetFullInfo {
+    /// Core pet information
+    pub pet: models::pet::Pet,
+    /// All vaccine records for the pet
+    pub vaccines: Vec<models::pet::PetHealth>,
+    /// All deworming records for the pet
+    pub deworms: Vec<models::pet::PetHealth>,
+    /// All weight records for the pet
+    pub weights: Vec<models::pet::PetWeight>,
+    /// All notes associated with the pet
+    pub notes: Vec<models::pet::PetNote>,
+}
+
+/// Retrieves complete pet information with all related data.
+///
+/// Gets the pet details along with all health records, notes, and other
+/// associated information for comprehensive display or data export.
+///
+/// # Arguments
+/// * `pet_id` - ID of the pet to get information for
+/// * `user_id` - ID of the user who owns the pet
+/// * `repo` - Repository instance for database operations
+///
+/// # Returns
+/// * `anyhow::Result<PetFullInfo>` - Complete pet information structure
+pub async fn get_full_info(
+    pet_id: i64,
+    user_id: i64,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<PetFullInfo> {
+    let _span = logfire::span!("get_pet_full_info").entered();
+
+    let pet = repo.get_pet_by_id(pet_id, user_id).await?;
+    let external_id = pet.external_id;
+    let pet_id = pet.id;
+
+    Ok(PetFullInfo {
+        pet,
+        vaccines: repo
+            .get_pet_health_records(
+                external_id,
+                Some(user_id),
+                models::pet::PetHealthType::Vaccine,
+            )
+            .await?,
+        deworms: repo
+            .get_pet_health_records(
+                external_id,
+                Some(user_id),
+                models::pet::PetHealthType::Deworm,
+            )
+            .await?,
+        weights: repo.get_pet_weights(external_id, Some(user_id)).await?,
+        notes: repo.get_all_pet_notes(user_id, pet_id).await?,
+    })
+}
+
+/// Weight measurement data for PDF report generation
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct WeightReport {
+    /// Weight value in user's preferred units
+    pub value: f64,
+    /// Timestamp when the weight was recorded
+    pub created_at: NaiveDateTime,
+    /// Formatted age string (e.g., "3 months, 2 weeks")
+    pub fmt_age: String,
+}
+
+/// Converts HTML content in pet notes to plain text
+fn convert_html_to_text(note: &models::pet::PetNote) -> models::pet::PetNote {
+    models::pet::PetNote {
+        content: html2text::from_read(note.content.as_bytes(), 20)
+            .unwrap_or(note.content.to_string()),
+        ..note.clone()
+    }
+}
+
+/// Quality preset of the PDF report
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PdfQuality {
+    /// The pet picture is embedded as uploaded
+    #[default]
+    Print,
+    /// The pet picture is downscaled and compressed, for reports sent by
+    /// WhatsApp or downloaded on mobile data
+    Share,
+}
+
+/// How the pet picture is embedded in the PDF report
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PdfImageOptions {
+    /// Longest side in pixels, bigger pictures are downscaled
+    pub max_side_px: u32,
+    /// JPEG quality (1-100) the picture is re-encoded with
+    pub jpeg_quality: u8,
+}
+
+/// Sections to include in the PDF report and how its picture is compressed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PdfReportOptions {
+    pub include_weights: bool,
+    pub include_vaccines: bool,
+    pub include_deworms: bool,
+    pub include_notes: bool,
+    /// `None` embeds the pet picture as uploaded
+    pub image: Option<PdfImageOptions>,
+}
+
+impl Default for PdfReportOptions {
+    fn default() -> Self {
+        Self {
+            include_weights: true,
+            include_vaccines: true,
+            include_deworms: true,
+            include_notes: true,
+            image: None,
+        }
+    }
+}
+
+impl PdfReportOptions {
+    /// Builds the options from a comma separated list of sections
+    /// (e.g. `vaccines,weights`). Unknown sections are ignored and an
+    /// empty list keeps the full report.
+    pub fn from_sections(sections: &str) -> Self {
+        let sections: Vec<&str> = sections
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        if sections.is_empty() {
+            return Self::default();
+        }
+
+        let has = |name: &str| sections.iter().any(|s| s.eq_ignore_ascii_case(name));
+        Self {
+            include_weights: has("weights"),
+            include_vaccines: has("vaccines"),
+            include_deworms: has("deworms"),
+            include_notes: has("notes"),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the picture compression of a quality preset, keeping the sections
+    pub fn with_quality(self, quality: PdfQuality) -> Self {
+        let image = match quality {
+            PdfQuality::Print => None,
+            PdfQuality::Share => Some(PdfImageOptions {
+                max_side_px: consts::PDF_SHARE_IMAGE_MAX_PX,
+                jpeg_quality: consts::PDF_SHARE_IMAGE_JPEG_QUALITY,
+            }),
+        };
+
+        Self { image, ..self }
+    }
+}
+
+/// Downscales a picture and re-encodes it as JPEG to make the report lighter
+///
+/// Pictures already smaller than `max_side_px` keep their size and are only
+/// re-encoded; the transparency is dropped since JPEG has none.
+fn compress_report_image(body: &[u8], options: &PdfImageOptions) -> anyhow::Result<Vec<u8>> {
+    let img = image::load_from_memory(body)?;
+    let img = match img.width().max(img.height()) > options.max_side_px {
+        true => img.resize(
+            options.max_side_px,
+            options.max_side_px,
+            image::imageops::FilterType::Lanczos3,
+        ),
+        false => img,
+    };
+
+    let mut jpeg_bytes = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg_bytes, options.jpeg_quality)
+        .encode_image(&img.to_rgb8())?;
+
+    Ok(jpeg_bytes)
+}
+
+/// Generates PDF report bytes for a pet by external ID
+///
+/// Creates a PDF report containing the pet information plus the health
+/// records, weight history, and notes selected in `options`. This function
+/// is designed for public access (e.g., WhatsApp bot) and doesn't require
+/// authentication. Weights are shown in `weight_unit`.
+///
+/// The pet picture is downscaled and compressed as set in `options.image`;
+/// if it can't be decoded it's embedded as uploaded.
+pub async fn generate_pdf_report_bytes(
+    pet_id: i64,
+    user_id: i64,
+    user_timezone: Tz,
+    weight_unit: models::user_app::WeightUnit,
+    options: &PdfReportOptions,
+    repo: &repo::ImplAppRepo,
+    storage_service: &services::ImplStorageService,
+) -> anyhow::Result<Vec<u8>> {
+    let _span = logfire::span!("generate_pdf_report_bytes").entered();
+
+    let pet_full_info = get_full_info(pet_id, user_id, repo).await?;
+
+    let now = front::utils::get_now_date_in_tz(user_timezone);
+
+    // Convert HTML notes to plain text
+    let notes = pet_full_info
+        .notes
+        .iter()
+        .map(convert_html_to_text)
+        .collect::<Vec<models::pet::PetNote>>();
+
+    // Calculate formatted age for each weight measurement
+    let weights: Vec<WeightReport> = pet_full_info
+        .weights
+        .iter()
+        .map(|w| WeightReport {
+            value: (weight_unit.from_kg(w.value) * 100.0).round() / 100.0,
+            created_at: w.created_at,
+            fmt_age: front::utils::fmt_dates_difference(
+                pet_full_info.pet.birthday,
+                w.created_at.into(),
+            ),
+        })
+        .collect();
+
+    // Generate QR code from public link
+    let pet_link = format!(
+        "https://pet-info.link/info/{external_id}",
+        external_id = pet_full_info.pet.external_id
+    );
+    let qr_code_data = crate::qr::get_qr_code(&pet_link)?;
+
+    let mut pet_pic_option = if pet_full_info.pet.pic.is_some() {
+        get_public_pic(pet_full_info.pet.external_id, repo, storage_service).await?
+    } else {
+        None
+    };
+    if let (Some(pic), Some(image_options)) = (pet_pic_option.as_mut(), options.image.as_ref()) {
+        match compress_report_image(&pic.body, image_options) {
+            Ok(jpeg_bytes) => {
+                pic.body = jpeg_bytes;
+                pic.extension = "jpg".to_string();
+            }
+            Err(e) => logfire::warn!(
+                "pet picture could not be compressed for the report: {error}",
+                error = e.to_string()
+            ),
+        }
+    }
+    let image_filename = pet_pic_option
+        .as_ref()
+        .map(|pic| format!("pet.{}", pic.extension));
+
+    let content = front::templates::PDF_REPORT_TEMPLATES.render(
+        "pet_default.typ",
+        &tera::Context::from_value(serde_json::json!({
+            "pet_name": pet_full_info.pet.pet_name,
+            "birthday": pet_full_info.pet.birthday,
+            "age": front::utils::fmt_dates_difference(pet_full_info.pet.birthday, now),
+            "breed": pet_full_info.pet.breed,
+            "is_female": pet_full_info.pet.is_female,
+            "is_spaying_neutering": pet_full_info.pet.is_spaying_neutering,
+            "pet_link": pet_link,
+            "vaccines": pet_full_info.vaccines,
+            "deworms": pet_full_info.deworms,
+            "weights": weights,
+            "weight_unit": weight_unit,
+            "notes": notes,
+            "include_vaccines": options.include_vaccines,
+            "include_deworms": options.include_deworms,
+            "include_weights": options.include_weights,
+            "include_notes": options.include_notes,
+            "image_filename": image_filename.as_deref().unwrap_or("NO_PIC"),
+        }))
+        .unwrap_or_default(),
+    )?;
+
+    let mut images = vec![(qr_code_data, "qr.png")];
+    if let Some(pet_pic) = pet_pic_option
+        && let Some(ref filename) = image_filename
+    {
+        images.push((pet_pic.body, filename.as_str()));
+    }
+
+    crate::api::pdf_handler::create_pdf_bytes_with_images(&content, images)
+}
+
+/// Generates a one page vaccine certificate PDF for a pet
+///
+/// Lighter than the full report: only the pet identity and the vaccines,
+/// for vets and boarding facilities. The picture of the pet is shown when
+/// available, its public profile QR code otherwise.
+///
+/// # Arguments
+/// * `pet_id` - Internal database ID of the pet
+/// * `user_id` - ID of the user who owns the pet
+/// * `user_timezone` - Timezone of the user, the age and issue date are computed in it
+/// * `include_signature` - Whether to add a line for the vet signature
+/// * `repo` - Repository instance for database operations
+/// * `storage_service` - Storage service to read the pet picture
+///
+/// # Returns
+/// * `anyhow::Result<Vec<u8>>` - PDF document bytes
+pub async fn generate_vaccine_certificate_pdf(
+    pet_id: i64,
+    user_id: i64,
+    user_timezone: Tz,
+    include_signature: bool,
+    repo: &repo::ImplAppRepo,
+    storage_service: &services::ImplStorageService,
+) -> anyhow::Result<Vec<u8>> {
+    let _span = logfire::span!("generate_vaccine_certificate_pdf").entered();
+
+    let pet = repo.get_pet_by_id(pet_id, user_id).await?;
+    let vaccines = repo
+        .get_pet_health_records(
+            pet.external_id,
+            Some(user_id),
+            models::pet::PetHealthType::Vaccine,
+        )
+        .await?;
+
+    let now = front::utils::get_now_date_in_tz(user_timezone);
+    let pet_link = format!(
+        "https://pet-info.link/info/{external_id}",
+        external_id = pet.external_id
+    );
+
+    let pet_pic_option = if pet.pic.is_some() {
+        get_public_pic(pet.external_id, repo, storage_service).await?
+    } else {
+        None
+    };
+    let image_filename = pet_pic_option
+        .as_ref()
+        .map(|pic| format!("pet.{}", pic.extension));
+
+    let content = front::templates::PDF_REPORT_TEMPLATES.render(
+        "vaccine_certificate.typ",
+        &tera::Context::from_value(serde_json::json!({
+            "pet_name": pet.pet_name,
+            "birthday": pet.birthday,
+            "age": front::utils::fmt_dates_difference(pet.birthday, now),
+            "breed": pet.breed,
+            "is_female": pet.is_female,
+            "pet_link": pet_link,
+            "vaccines": vaccines,
+            "include_signature": include_signature,
+            "issued_at": now,
+            "image_filename": image_filename.as_deref().unwrap_or("NO_PIC"),
+        }))
+        .unwrap_or_default(),
+    )?;
+
+    let mut images = vec![(crate::qr::get_qr_code(&pet_link)?, "qr.png")];
+    if let Some(pet_pic) = pet_pic_option
+        && let Some(ref filename) = image_filename
+    {
+        images.push((pet_pic.body, filename.as_str()));
+    }
+
+    crate::api::pdf_handler::create_pdf_bytes_with_images(&content, images)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repo::{AppRepo, MockAppRepo};
+    use crate::services::{
+        NotificationService, StorageService,
+        test_utils::{FakeNotificationService, FakeStorageService},
+    };
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use mockall::predicate::*;
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    fn create_test_user(phone_reminder: Option<String>) -> models::user_app::User {
+        models::user_app::User {
+            id: 123,
+            phone_reminder,
+            is_subscribed: true,
+            ..models::user_app::User::create_default_from_email("test@example.com")
+        }
+    }
+
+    fn create_test_storage() -> FakeStorageService {
+        FakeStorageService::serving(vec![1, 2, 3, 4])
+    }
+
+    fn create_test_vaccine_entry(booster_months: Option<u32>) -> HealthRecordEntry {
+        HealthRecordEntry {
+            desc: "Rabia".to_string(),
+            date: Utc::now().date_naive(),
+            booster_months,
+            user_timezone: Tz::America__Mexico_City,
+        }
+    }
+
+    fn create_test_pet() -> models::pet::Pet {
+        models::pet::Pet {
+            id: 1,
+            external_id: Uuid::new_v4(),
+            user_app_id: 123,
+            pet_name: "Buddy".to_string(),
+            birthday: NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(),
+            breed: "Golden Retriever".to_string(),
+            about: "A friendly dog".to_string(),
+            is_female: false,
+            is_lost: false,
+            is_spaying_neutering: true,
+            microchip: None,
+            last_weight: Some(25.5),
+            pic: Some("test.jpg".to_string()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn create_test_clock() -> services::ImplClock {
+        Box::new(services::clock::SystemClock)
+    }
+
+    fn create_test_profile_cache() -> api::profile_cache::PublicProfileCache {
+        api::profile_cache::PublicProfileCache::new(Duration::from_secs(60))
+    }
+
+    /// Caches the public profile of `pet` as if it had been visited today
+    fn prefill_profile_cache(
+        profile_cache: &api::profile_cache::PublicProfileCache,
+        pet: models::pet::Pet,
+    ) {
+        let today = Utc::now().date_naive();
+        profile_cache.insert(
+            pet.external_id,
+            today,
+            pet.id,
+            PetPublicInfoSchema::from_pet(pet, today),
+        );
+    }
+
+    fn create_test_pet_form() -> front::forms::pet::CreatePetForm {
+        front::forms::pet::CreatePetForm {
+            id: 0,
+            pet_full_name: "Buddy".to_string(),
+            pet_birthday: NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(),
+            pet_breed: "Golden Retriever".to_string(),
+            is_lost: false,
+            is_spaying_neutering: true,
+            is_female: false,
+            about_pet: "A friendly dog".to_string(),
+            microchip: None,
+            pet_pic: None,
+            pet_external_id: None,
+            idempotency_token: None,
+        }
+    }
+
+    fn create_test_user_state() -> UserStateAddNewPet {
+        UserStateAddNewPet {
+            user_id: 123,
+            user_email: "test@example.com".to_string(),
+            pet_balance: 5,
+            max_pets: 3,
+        }
+    }
+
+    #[ntex::test]
+    async fn test_add_new_pet_to_user_success() {
+        let mut mock_repo = MockAppRepo::new();
+        let storage_service: Box<dyn StorageService> = Box::new(create_test_storage());
+        let user_state = create_test_user_state();
+        let pet_form = create_test_pet_form();
+
+        mock_repo
+            .expect_count_user_pets()
+            .with(eq(123))
+            .returning(|_| Box::pin(async move { Ok(2) }));
+
+        mock_repo
+            .expect_is_pet_external_id_linked()
+            .returning(|_| Box::pin(async move { Ok(None) }));
+
+        mock_repo
+            .expect_create_pet_and_charge()
+            .with(always(), eq(123), eq(true))
+            .times(1)
+            .returning(|_, _, _| Box::pin(async move { Ok(1) }));
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let result = add_new_pet_to_user(user_state, pet_form, &repo, &storage_service).await;
+
+        assert!(result.is_ok());
+    }
+
+    #[ntex::test]
+    async fn test_add_new_pet_to_user_repeated_token_adds_one_pet() {
+        let mut mock_repo = MockAppRepo::new();
+        let storage_service: Box<dyn StorageService> = Box::new(create_test_storage());
+        let claimed_tokens = std::sync::Arc::new(std::sync::Mutex::new(Vec::<Uuid>::new()));
+
+        mock_repo
+            .expect_claim_pet_creation_token()
+            .times(2)
+            .returning(move |_, token| {
+                let mut claimed_tokens = claimed_tokens.lock().unwrap();
+                let is_new = !claimed_tokens.contains(&token);
+                claimed_tokens.push(token);
+                Box::pin(async move { Ok(is_new) })
+            });
+        mock_repo
+            .expect_count_user_pets()
+            .returning(|_| Box::pin(async move { Ok(0) }));
+        mock_repo
+            .expect_create_pet_and_charge()
+            .times(1)
+            .returning(|_, _, _| Box::pin(async move { Ok(1) }));
+        mock_repo.expect_release_pet_creation_token().times(0);
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let pet_form = front::forms::pet::CreatePetForm {
+            idempotency_token: Some(Uuid::new_v4()),
+            ..create_test_pet_form()
+        };
+
+        let first = add_new_pet_to_user(
+            create_test_user_state(),
+            pet_form.clone(),
+            &repo,
+            &storage_service,
+        )
+        .await;
+        let second =
+            add_new_pet_to_user(create_test_user_state(), pet_form, &repo, &storage_service).await;
+
+        assert!(first.unwrap());
+        assert!(!second.unwrap());
+    }
+
+    #[ntex::test]
+    async fn test_add_new_pet_to_user_with_external_id_validation_error() {
+        let mut mock_repo = MockAppRepo::new();
+        let storage_service: Box<dyn StorageService> = Box::new(create_test_storage());
+        let user_state = create_test_user_state();
+        let mut pet_form = create_test_pet_form();
+        pet_form.pet_external_id = Some(Uuid::new_v4());
+
+        mock_repo
+            .expect_count_user_pets()
+            .returning(|_| Box::pin(async move { Ok(0) }));
+
+        mock_repo
+            .expect_is_pet_external_id_linked()
+            .returning(|_| Box::pin(async move { Ok(Some(true)) }));
+
+        // nothing is charged for a pet that isn't added
+        mock_repo.expect_create_pet_and_charge().times(0);
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let result = add_new_pet_to_user(user_state, pet_form, &repo, &storage_service).await;
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("invalid pet_external_id")
+        );
+    }
+
+    #[ntex::test]
+    async fn test_add_new_pet_to_user_with_tag_of_other_user_fails() {
+        let mut mock_repo = MockAppRepo::new();
+        let storage_service: Box<dyn StorageService> = Box::new(create_test_storage());
+        let mut pet_form = create_test_pet_form();
+        pet_form.pet_external_id = Some(Uuid::new_v4());
+
+        mock_repo
+            .expect_count_user_pets()
+            .returning(|_| Box::pin(async move { Ok(0) }));
+        mock_repo
+            .expect_is_pet_external_id_linked()
+            .returning(|_| Box::pin(async move { Ok(Some(false)) }));
+        mock_repo
+            .expect_create_pet_and_charge()
+            .times(1)
+            .returning(|_, _, _| {
+                Box::pin(async move { Err(repo::errors::RepoError::Conflict.into()) })
+            });
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let result =
+            add_new_pet_to_user(create_test_user_state(), pet_form, &repo, &storage_service).await;
+
+        assert!(result.is_err_and(|e| matches!(
+            e.downcast_ref::<AddPetError>(),
+            Some(AddPetError::ExternalIdTaken)
+        )));
+    }
+
+    #[ntex::test]
+    async fn test_add_new_pet_to_user_over_max_pets_fails() {
+        let mut mock_repo = MockAppRepo::new();
+        let storage_service: Box<dyn StorageService> = Box::new(create_test_storage());
+
+        // the account already has max_pets (3) pets
+        mock_repo
+            .expect_count_user_pets()
+            .with(eq(123))
+            .times(1)
+            .returning(|_| Box::pin(async move { Ok(3) }));
+        mock_repo.expect_create_pet_and_charge().times(0);
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let result = add_new_pet_to_user(
+            create_test_user_state(),
+            create_test_pet_form(),
+            &repo,
+            &storage_service,
+        )
+        .await;
+
+        assert!(result.is_err_and(|e| matches!(
+            e.downcast_ref::<AddPetError>(),
+            Some(AddPetError::MaxPetsReached { max: 3 })
+        )));
+    }
+
+    #[ntex::test]
+    async fn test_add_new_pet_to_user_without_balance_left_fails() {
+        let mut mock_repo = MockAppRepo::new();
+        let storage_service: Box<dyn StorageService> = Box::new(create_test_storage());
+
+        // the session still shows a balance another tab already spent
+        mock_repo
+            .expect_count_user_pets()
+            .returning(|_| Box::pin(async move { Ok(0) }));
+        mock_repo
+            .expect_create_pet_and_charge()
+            .with(always(), eq(123), eq(true))
+            .times(1)
+            .returning(|_, _, _| {
+                Box::pin(async move { Err(repo::errors::RepoError::NotFound.into()) })
+            });
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let result = add_new_pet_to_user(
+            create_test_user_state(),
+            create_test_pet_form(),
+            &repo,
+            &storage_service,
+        )
+        .await;
+
+        assert!(result.is_err_and(|e| matches!(
+            e.downcast_ref::<AddPetError>(),
+            Some(AddPetError::NoPetBalance)
+        )));
+    }
+
+    #[ntex::test]
+    async fn test_get_user_pets_cards_success() {
+        let mut mock_repo = MockAppRepo::new();
+        let user_id = 123;
+
+        mock_repo
+            .expect_get_pets_with_counts()
+            .with(eq(user_id))
+            .times(1)
+            .returning(move |_| {
+                let pet = models::pet::PetWithCounts {
+                    pet: create_test_pet(),
+                    vaccine_count: 2,
+                    weight_count: 3,
+                    last_vaccine_date: NaiveDate::from_ymd_opt(2024, 3, 1)
+                        .unwrap()
+                        .and_hms_opt(10, 0, 0),
+                };
+                Box::pin(async move { Ok(vec![pet]) })
+            });
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let result = get_user_pets_cards(user_id, Tz::America__Mexico_City, &repo).await;
+
+        assert!(result.is_ok_and(|pets| {
+            pets.len() == 1
+                && pets[0].name == "Buddy"
+                && pets[0].breed == "Golden Retriever"
+                && pets[0].vaccine_count == 2
+                && pets[0].weight_count == 3
+                && pets[0].last_vaccine_date == NaiveDate::from_ymd_opt(2024, 3, 1)
+        }));
+    }
+
+    #[ntex::test]
+    async fn test_get_pet_public_info_success() {
+        let mut mock_repo = MockAppRepo::new();
+        let external_id = Uuid::new_v4();
+
+        mock_repo
+            .expect_get_pet_by_external_id()
+            .with(eq(external_id))
+            .times(1)
+            .returning(move |_| {
+                let pet = create_test_pet();
+                Box::pin(async move { Ok(pet) })
+            });
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let profile_cache = create_test_profile_cache();
+        let result =
+            get_pet_public_info(external_id, Tz::Pacific__Kiritimati, &repo, &profile_cache).await;
+
+        assert!(result.is_ok_and(|pet_info| {
+            pet_info.name == "Buddy" && pet_info.pet_breed == "Golden Retriever"
+        }));
+
+        // the second visit is served from the cache, the repo expects a single call
+        let cached =
+            get_pet_public_info(external_id, Tz::Pacific__Kiritimati, &repo, &profile_cache).await;
+        assert!(cached.is_ok_and(|pet_info| pet_info.name == "Buddy"));
+    }
+
+    #[ntex::test]
+    async fn test_update_pet_to_user_invalidates_public_profile() {
+        let mut mock_repo = MockAppRepo::new();
+        let storage_service: Box<dyn StorageService> = Box::new(create_test_storage());
+        let profile_cache = create_test_profile_cache();
+        prefill_profile_cache(&profile_cache, create_test_pet());
+
+        mock_repo
+            .expect_update_pet()
+            .times(1)
+            .returning(|_| Box::pin(async { Ok(1) }));
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let result = update_pet_to_user(
+            123,
+            front::forms::pet::CreatePetForm {
+                id: 1,
+                ..create_test_pet_form()
+            },
+            &repo,
+            &storage_service,
+            &api::thumbnail::ThumbnailCache::new(1),
+            &profile_cache,
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert!(profile_cache.is_empty());
+    }
+
+    #[ntex::test]
+    async fn test_delete_pet_and_its_info_success() {
+        let mut mock_repo = MockAppRepo::new();
+        let pet_id = 1;
+        let user_id = 123;
+
+        mock_repo
+            .expect_delete_reminders_for_pet()
+            .with(eq(pet_id), eq(user_id))
+            .times(1)
+            .returning(|_, _| Box::pin(async move { Ok(vec![]) }));
+        mock_repo
+            .expect_delete_pet()
+            .with(eq(pet_id), eq(user_id))
+            .times(1)
+            .returning(|_, _| Box::pin(async move { Ok(()) }));
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let notification_service: Box<dyn NotificationService> =
+            Box::<FakeNotificationService>::default();
+        let result = delete_pet_and_its_info(
+            pet_id,
+            user_id,
+            &repo,
+            &notification_service,
+            &create_test_profile_cache(),
+        )
+        .await;
+
+        assert!(result.is_ok());
+    }
+
+    #[ntex::test]
+    async fn test_delete_pet_cancels_its_reminders() {
+        let mut mock_repo = MockAppRepo::new();
+
+        mock_repo
+            .expect_delete_reminders_for_pet()
+            .with(eq(1), eq(123))
+            .times(1)
+            .returning(|_, _| {
+                Box::pin(async move { Ok(vec!["execution-1".into(), "execution-2".into()]) })
+            });
+        mock_repo
+            .expect_delete_pet()
+            .times(1)
+            .returning(|_, _| Box::pin(async move { Ok(()) }));
+
+        let notification = FakeNotificationService::default();
+        let notification_service: Box<dyn NotificationService> = Box::new(notification.clone());
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+
+        let result = delete_pet_and_its_info(
+            1,
+            123,
+            &repo,
+            &notification_service,
+            &create_test_profile_cache(),
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(
+            notification.cancelled(),
+            vec!["execution-1".to_string(), "execution-2".to_string()]
+        );
+    }
+
+    #[ntex::test]
+    async fn test_insert_vaccine_with_booster_schedules_reminder() {
+        let mut mock_repo = MockAppRepo::new();
+        let notification_service: Box<dyn NotificationService> =
+            Box::<FakeNotificationService>::default();
+        let pet_external_id = Uuid::new_v4();
+
+        mock_repo
+            .expect_insert_vaccine_to()
+            .with(
+                eq(pet_external_id),
+                eq(123),
+                eq("Rabia".to_string()),
+                always(),
+                eq(Some(12)),
+            )
+            .times(1)
+            .returning(|_, _, desc, _, _| {
+                Box::pin(async move {
+                    Ok(models::pet::PetHealth {
+                        id: 1,
+                        pet_id: 1,
+                        health_record: models::pet::PetHealthType::Vaccine,
+                        description: desc,
+                        created_at: Utc::now().naive_utc(),
+                    })
+                })
+            });
+
+        mock_repo
+            .expect_insert_user_remider()
+            .withf(|reminder| reminder.body.contains("Rabia") && reminder.user_app_id == 123)
+            .times(1)
+            .returning(|_| Box::pin(async move { Ok(1) }));
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let result = insert_pet_health_record(
+            pet_external_id,
+            &models::pet::PetHealthType::Vaccine,
+            &create_test_user(Some("5215512345678".to_string())),
+            create_test_vaccine_entry(Some(12)),
+            &repo,
+            &notification_service,
+            &create_test_profile_cache(),
+            &create_test_clock(),
+        )
+        .await;
+
+        assert!(result.is_ok_and(|outcome| outcome.warning.is_none()));
+    }
+
+    #[ntex::test]
+    async fn test_insert_vaccine_with_booster_without_phone_schedules_email() {
+        let mut mock_repo = MockAppRepo::new();
+        let notification_service: Box<dyn NotificationService> =
+            Box::<FakeNotificationService>::default();
+
+        mock_repo
+            .expect_insert_vaccine_to()
+            .times(1)
+            .returning(|_, _, desc, _, _| {
+                Box::pin(async move {
+                    Ok(models::pet::PetHealth {
+                        id: 1,
+                        pet_id: 1,
+                        health_record: models::pet::PetHealthType::Vaccine,
+                        description: desc,
+                        created_at: Utc::now().naive_utc(),
+                    })
+                })
+            });
+        mock_repo
+            .expect_insert_user_remider()
+            .withf(|reminder| {
+                reminder.notification_type
+                    == [models::reminder::ReminderNotificationType::Email]
+                        .into_iter()
+                        .collect()
+            })
+            .times(1)
+            .returning(|_| Box::pin(async move { Ok(1) }));
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let result = insert_pet_health_record(
+            Uuid::new_v4(),
+            &models::pet::PetHealthType::Vaccine,
+            &create_test_user(None),
+            create_test_vaccine_entry(Some(12)),
+            &repo,
+            &notification_service,
+            &create_test_profile_cache(),
+            &create_test_clock(),
+        )
+        .await;
+
+        assert!(result.is_ok_and(|outcome| outcome.warning.is_none()));
+    }
+
+    fn create_test_weights(values: &[f64]) -> Vec<models::pet::PetWeight> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| models::pet::PetWeight {
+                id: i as i64 + 1,
+                pet_id: 1,
+                value: *value,
+                created_at: NaiveDate::from_ymd_opt(2024, 1, 1 + i as u32)
+                    .unwrap()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_weight_outlier_average_threshold() {
+        let weights = create_test_weights(&[24.0, 25.0, 26.0]);
+
+        assert_eq!(
+            weight_outlier_average(25.5, &weights, consts::WEIGHT_OUTLIER_FACTOR),
+            None
+        );
+        // exactly at the factor is still accepted
+        assert_eq!(
+            weight_outlier_average(50.0, &weights, consts::WEIGHT_OUTLIER_FACTOR),
+            None
+        );
+        assert_eq!(
+            weight_outlier_average(12.5, &weights, consts::WEIGHT_OUTLIER_FACTOR),
+            None
+        );
+        assert_eq!(
+            weight_outlier_average(250.0, &weights, consts::WEIGHT_OUTLIER_FACTOR),
+            Some(25.0)
+        );
+        assert_eq!(
+            weight_outlier_average(2.5, &weights, consts::WEIGHT_OUTLIER_FACTOR),
+            Some(25.0)
+        );
+        // without history there is nothing to compare with
+        assert_eq!(
+            weight_outlier_average(250.0, &[], consts::WEIGHT_OUTLIER_FACTOR),
+            None
+        );
+    }
+
+    #[test]
+    fn test_weight_outlier_average_uses_latest_weights() {
+        // a puppy that grew, only the latest weights count
+        let weights = create_test_weights(&[2.0, 3.0, 20.0, 20.0, 20.0, 20.0, 20.0]);
+
+        assert_eq!(
+            weight_outlier_average(21.0, &weights, consts::WEIGHT_OUTLIER_FACTOR),
+            None
+        );
+        assert_eq!(
+            weight_outlier_average(3.0, &weights, consts::WEIGHT_OUTLIER_FACTOR),
+            Some(20.0)
+        );
+    }
+
+    #[test]
+    fn test_weight_outlier_average_uses_given_factor() {
+        let weights = create_test_weights(&[24.0, 25.0, 26.0]);
+
+        assert_eq!(weight_outlier_average(40.0, &weights, 1.5), Some(25.0));
+        assert_eq!(weight_outlier_average(40.0, &weights, 2.0), None);
+        assert_eq!(weight_outlier_average(10.0, &weights, 3.0), None);
+    }
+
+    #[ntex::test]
+    async fn test_insert_weight_outlier_is_stored_with_warning() {
+        let mut mock_repo = MockAppRepo::new();
+        let notification_service: Box<dyn NotificationService> =
+            Box::<FakeNotificationService>::default();
+
+        mock_repo
+            .expect_get_pet_weights()
+            .times(1)
+            .returning(|_, _| Box::pin(async { Ok(create_test_weights(&[24.0, 25.0, 26.0])) }));
+        mock_repo
+            .expect_insert_pet_weight()
+            .with(always(), eq(123), eq(250.0), always())
+            .times(1)
+            .returning(|_, _, value, _| {
+                Box::pin(async move {
+                    Ok(models::pet::PetWeight {
+                        id: 4,
+                        pet_id: 1,
+                        value,
+                        created_at: Utc::now().naive_utc(),
+                    })
+                })
+            });
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let outcome = insert_pet_health_record(
+            Uuid::new_v4(),
+            &models::pet::PetHealthType::Weight,
+            &create_test_user(None),
+            HealthRecordEntry {
+                desc: "250".to_string(),
+                ..create_test_vaccine_entry(None)
+            },
+            &repo,
+            &notification_service,
+            &create_test_profile_cache(),
+            &create_test_clock(),
+        )
+        .await
+        .unwrap();
+
+        assert!(outcome.warning.is_some_and(|w| w.contains("25.00 kg")));
+    }
+
+    #[ntex::test]
+    async fn test_zip_pet_photos_skips_missing_with_manifest() {
+        let mut png = Vec::new();
+        image::RgbImage::new(2, 2)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let storage_service: Box<dyn StorageService> = Box::new(FakeStorageService {
+            pics: [
+                ("pics/1".to_string(), png),
+                ("pics/2".to_string(), vec![1, 2, 3]),
+            ]
+            .into(),
+            ..Default::default()
+        });
+
+        let paths = ["pics/1".to_string(), "pics/2".to_string()];
+        let body = zip_pet_photos("Max Power", &paths, &storage_service)
+            .await
+            .unwrap();
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(body)).unwrap();
+        assert_eq!(archive.len(), 2);
+        assert!(archive.by_name("Max_Power_1.png").is_ok());
+        assert!(archive.by_name("Max_Power_2.bin").is_ok());
+
+        let paths = [paths[0].clone(), "pics/gone".to_string()];
+        let body = zip_pet_photos("Max", &paths, &storage_service)
+            .await
+            .unwrap();
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(body)).unwrap();
+        assert_eq!(archive.len(), 2);
+        assert!(archive.by_name("Max_1.png").is_ok());
+        assert!(archive.by_name(PHOTOS_ZIP_MANIFEST_NAME).is_ok());
+    }
+
+    #[ntex::test]
+    async fn test_update_pet_health_record_weight_parses_value() {
+        let mut mock_repo = MockAppRepo::new();
+        let external_id = Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+
+        mock_repo
+            .expect_update_pet_weight()
+            .with(eq(7), eq(external_id), eq(123), eq(4.1), eq(date))
+            .times(1)
+            .returning(|_, _, _, _, _| Box::pin(async { Ok(()) }));
+        mock_repo.expect_update_pet_health_record().times(0);
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let profile_cache = create_test_profile_cache();
+        prefill_profile_cache(
+            &profile_cache,
+            models::pet::Pet {
+                external_id,
+                ..create_test_pet()
+            },
+        );
+        let result = update_pet_health_record(
+            7,
+            external_id,
+            123,
+            &models::pet::PetHealthType::Weight,
+            "4.1",
+            models::user_app::WeightUnit::Kg,
+            date,
+            &repo,
+            &profile_cache,
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert!(profile_cache.is_empty());
+    }
+
+    #[ntex::test]
+    async fn test_update_pet_health_record_vaccine() {
+        let mut mock_repo = MockAppRepo::new();
+        let external_id = Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+
+        mock_repo
+            .expect_update_pet_health_record()
+            .with(
+                eq(7),
+                eq(external_id),
+                eq(123),
+                eq(models::pet::PetHealthType::Vaccine),
+                eq("Rabia".to_string()),
+                eq(date),
+            )
+            .times(1)
+            .returning(|_, _, _, _, _, _| Box::pin(async { Ok(()) }));
+        mock_repo.expect_update_pet_weight().times(0);
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let result = update_pet_health_record(
+            7,
+            external_id,
+            123,
+            &models::pet::PetHealthType::Vaccine,
+            "Rabia",
+            models::user_app::WeightUnit::Kg,
+            date,
+            &repo,
+            &create_test_profile_cache(),
+        )
+        .await;
+
+        assert!(result.is_ok());
+    }
+
+    #[ntex::test]
+    async fn test_rotate_pet_external_id_success() {
+        let mut mock_repo = MockAppRepo::new();
+        let new_external_id = Uuid::new_v4();
+
+        mock_repo
+            .expect_rotate_pet_external_id()
+            .with(eq(1), eq(123))
+            .times(1)
+            .returning(move |_, _| Box::pin(async move { Ok(new_external_id) }));
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let result = rotate_pet_external_id(1, 123, &repo, &create_test_profile_cache()).await;
+
+        assert!(result.is_ok_and(|external_id| external_id == new_external_id));
+    }
+
+    #[ntex::test]
+    async fn test_merge_pets_success() {
+        let mut mock_repo = MockAppRepo::new();
+        let (primary_external_id, duplicate_external_id) = (Uuid::new_v4(), Uuid::new_v4());
+
+        mock_repo
+            .expect_get_pet_by_id()
+            .returning(move |pet_id, _| {
+                let pet = models::pet::Pet {
+                    id: pet_id,
+                    external_id: match pet_id {
+                        1 => primary_external_id,
+                        _ => duplicate_external_id,
+                    },
+                    ..create_test_pet()
+                };
+                Box::pin(async move { Ok(pet) })
+            });
+        mock_repo
+            .expect_merge_pets()
+            .with(eq(1), eq(2), eq(123))
+            .times(1)
+            .returning(|_, _, _| Box::pin(async move { Ok(Some("pics/duplicate".to_string())) }));
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let storage = FakeStorageService::serving(Vec::new());
+        let storage_service: services::ImplStorageService = Box::new(storage.clone());
+        let thumbnail_cache = api::thumbnail::ThumbnailCache::new(4);
+        thumbnail_cache.insert(primary_external_id, 64, vec![1]);
+        thumbnail_cache.insert(duplicate_external_id, 64, vec![2]);
+
+        let result = merge_pets(
+            1,
+            2,
+            123,
+            &repo,
+            &storage_service,
+            &create_test_profile_cache(),
+            &thumbnail_cache,
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert!(thumbnail_cache.is_empty());
+        assert_eq!(*storage.deleted.lock().unwrap(), vec!["pics/duplicate"]);
+    }
+
+    #[ntex::test]
+    async fn test_merge_pets_rejects_same_pet() {
+        let mut mock_repo = MockAppRepo::new();
+        mock_repo.expect_merge_pets().times(0);
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let storage_service: services::ImplStorageService = Box::new(create_test_storage());
+        let result = merge_pets(
+            1,
+            1,
+            123,
+            &repo,
+            &storage_service,
+            &create_test_profile_cache(),
+            &api::thumbnail::ThumbnailCache::new(1),
+        )
+        .await;
+
+        assert!(matches!(
+            result.unwrap_err().downcast_ref::<MergePetsError>(),
+            Some(MergePetsError::SamePet)
+        ));
+    }
+
+    #[test]
+    fn test_pet_public_info_from_pet() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let pet = models::pet::Pet {
+            updated_at: NaiveDate::from_ymd_opt(2024, 1, 15)
+                .unwrap()
+                .and_hms_opt(10, 0, 0)
+                .unwrap()
+                .and_utc(),
+            ..create_test_pet()
+        };
+        let external_id = pet.external_id.to_string();
+
+        let info = PetPublicInfoSchema::from_pet(pet, today);
+
+        assert_eq!(info.external_id, external_id);
+        assert_eq!(info.name, "Buddy");
+        assert!(matches!(info.sex, Sex::Male));
+        assert_eq!(info.pic_path, "test.jpg");
+        assert_eq!(info.fmt_age, "4 años 2 meses 1 días");
+        assert_eq!(info.life_stage, LifeStage::Adult);
+        assert_eq!(info.weight_status, WeightStatus::Normal);
+        assert_eq!(info.fmt_last_updated, "hace 1 meses 16 días");
+
+        let info = PetPublicInfoSchema::from_pet(
+            models::pet::Pet {
+                updated_at: today.and_hms_opt(8, 0, 0).unwrap().and_utc(),
+                ..create_test_pet()
+            },
+            today,
+        );
+        assert_eq!(info.fmt_last_updated, "hoy");
+    }
+
+    #[test]
+    fn test_life_stage_by_species() {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+        let two_months_old = NaiveDate::from_ymd_opt(2024, 4, 15).unwrap();
+        let nine_years_old = NaiveDate::from_ymd_opt(2015, 6, 1).unwrap();
+
+        assert_eq!(
+            life_stage(two_months_old, Species::Dog, today),
+            LifeStage::Puppy
+        );
+        assert_eq!(
+            life_stage(two_months_old, Species::Cat, today),
+            LifeStage::Kitten
+        );
+        assert_eq!(
+            life_stage(nine_years_old, Species::from_breed("Gran Danés"), today),
+            LifeStage::Senior
+        );
+        assert_eq!(
+            life_stage(nine_years_old, Species::from_breed("Chihuahua"), today),
+            LifeStage::Adult
+        );
+        assert_eq!(
+            life_stage(nine_years_old, Species::from_breed("Siamés"), today),
+            LifeStage::Adult
+        );
+
+        // large dogs stay puppies longer
+        let fifteen_months_old = NaiveDate::from_ymd_opt(2023, 3, 15).unwrap();
+        assert_eq!(
+            life_stage(fifteen_months_old, Species::LargeDog, today),
+            LifeStage::Puppy
+        );
+        assert_eq!(
+            life_stage(fifteen_months_old, Species::Dog, today),
+            LifeStage::Adult
+        );
+    }
+
+    #[test]
+    fn test_weight_vs_standard() {
+        // Golden Retriever: 25 - 34 kg
+        assert_eq!(
+            weight_vs_standard(30.0, "Golden Retriever"),
+            WeightStatus::Normal
+        );
+        assert_eq!(
+            weight_vs_standard(25.0, "golden retriever"),
+            WeightStatus::Normal
+        );
+        assert_eq!(
+            weight_vs_standard(19.5, "Golden Retriever"),
+            WeightStatus::Under
+        );
+        assert_eq!(
+            weight_vs_standard(41.0, "Golden Retriever"),
+            WeightStatus::Over
+        );
+
+        assert_eq!(weight_vs_standard(30.0, "Mestizo"), WeightStatus::Unknown);
+        assert_eq!(
+            weight_vs_standard(30.0, "Ornitorrinco"),
+            WeightStatus::Unknown
+        );
+    }
+
+    #[test]
+    fn test_pet_public_info_skips_weight_status_of_puppies() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let info = PetPublicInfoSchema::from_pet(
+            models::pet::Pet {
+                birthday: NaiveDate::from_ymd_opt(2023, 12, 1).unwrap(),
+                last_weight: Some(8.0),
+                ..create_test_pet()
+            },
+            today,
+        );
+
+        assert_eq!(info.life_stage, LifeStage::Puppy);
+        assert_eq!(info.weight_status, WeightStatus::Unknown);
+    }
+
+    #[ntex::test]
+    async fn test_report_found_pet_relays_message_to_owner() {
+        let mut mock_repo = MockAppRepo::new();
+        let pet_external_id = Uuid::new_v4();
+
+        mock_repo
+            .expect_get_pet_by_external_id()
+            .with(eq(pet_external_id))
+            .times(1)
+            .returning(|_| {
+                Box::pin(async {
+                    Ok(models::pet::Pet {
+                        is_lost: true,
+                        ..create_test_pet()
+                    })
+                })
+            });
+        mock_repo
+            .expect_get_pet_owner_by_external_id()
+            .with(eq(pet_external_id))
+            .times(1)
+            .returning(|_| {
+                Box::pin(async { Ok(Some(create_test_user(Some("5215512345678".into())))) })
+            });
+        mock_repo
+            .expect_insert_found_report()
+            .with(
+                eq(pet_external_id),
+                eq("Esta en el parque".to_string()),
+                eq(Some("5598765432".to_string())),
+                always(),
+            )
+            .times(1)
+            .returning(|_, _, _, _| Box::pin(async { Ok(1) }));
+
+        let notification = FakeNotificationService::default();
+        let notification_service: Box<dyn NotificationService> = Box::new(notification.clone());
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+
+        let result = report_found_pet(
+            pet_external_id,
+            "  Esta en el parque ",
+            Some(" 5598765432"),
+            &repo,
+            &notification_service,
+            &create_test_clock(),
+        )
+        .await;
+
+        assert!(result.is_ok());
+        let sent = notification.sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        let info = &sent[0];
+        assert!(
+            info.channels
+                .contains(models::reminder::ReminderNotificationType::WhatsApp)
+        );
+        assert!(
+            info.channels
+                .contains(models::reminder::ReminderNotificationType::Email)
+        );
+        assert_eq!(info.phone_number.as_deref(), Some("5215512345678"));
+        assert!(info.body.contains("Buddy") && info.body.contains("Esta en el parque"));
+        assert!(info.body.contains("5598765432"));
+    }
+
+    #[ntex::test]
+    async fn test_report_found_pet_rejects_pet_not_lost() {
+        let mut mock_repo = MockAppRepo::new();
+
+        mock_repo
+            .expect_get_pet_by_external_id()
+            .times(1)
+            .returning(|_| Box::pin(async { Ok(create_test_pet()) }));
+        mock_repo.expect_insert_found_report().times(0);
+
+        let notification_service: Box<dyn NotificationService> =
+            Box::<FakeNotificationService>::default();
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+
+        let result = report_found_pet(
+            Uuid::new_v4(),
+            "Esta en el parque",
+            None,
+            &repo,
+            &notification_service,
+            &create_test_clock(),
+        )
+        .await;
+
+        assert!(result.is_err_and(|e| matches!(
+            e.downcast_ref::<FoundReportError>(),
+            Some(FoundReportError::PetNotLost)
+        )));
+    }
+
+    #[ntex::test]
+    async fn test_build_pet_qr_code_fetches_picture_once() {
+        let mut mock_repo = MockAppRepo::new();
+        let external_id = Uuid::new_v4();
+
+        mock_repo
+            .expect_get_pet_pic_path_by_external_id()
+            .with(eq(external_id))
+            .times(1)
+            .returning(|_| Box::pin(async { Ok(Some("pets/pic.png".to_string())) }));
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let img = image::RgbaImage::from_pixel(32, 32, image::Rgba([200, 120, 80, 255]));
+        let mut pic = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut pic), image::ImageFormat::Png)
+            .unwrap();
+        let storage = FakeStorageService::serving(pic);
+        let storage_service: services::ImplStorageService = Box::new(storage.clone());
+        let thumbnail_cache = api::thumbnail::ThumbnailCache::new(4);
+
+        for _ in 0..2 {
+            let card = build_pet_qr_code(
+                external_id,
+                "Firulais",
+                "Beagle",
+                "https://example.com/info/123",
+                &repo,
+                &storage_service,
+                &thumbnail_cache,
+            )
+            .await
+            .unwrap();
+            assert_eq!(&card[0..8], &[137, 80, 78, 71, 13, 10, 26, 10]);
+        }
+
+        assert_eq!(storage.fetched.lock().unwrap().len(), 1);
+        assert_eq!(thumbnail_cache.len(), 1);
+    }
+
+    #[ntex::test]
+    async fn test_set_pet_lost_status_success() {
+        let mut mock_repo = MockAppRepo::new();
+
+        mock_repo
+            .expect_set_pet_lost_status()
+            .with(eq(1), eq(123), eq(true))
+            .times(1)
+            .returning(|_, _, _| Box::pin(async { Ok(()) }));
+        mock_repo.expect_update_pet().times(0);
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let profile_cache = create_test_profile_cache();
+        prefill_profile_cache(&profile_cache, create_test_pet());
+        let result = set_pet_lost_status(1, 123, true, &repo, &profile_cache).await;
+
+        assert!(result.is_ok());
+        assert!(profile_cache.is_empty());
+    }
+
+    #[ntex::test]
+    async fn test_offer_pet_transfer_normalizes_email() {
+        let mut mock_repo = MockAppRepo::new();
+
+        mock_repo
+            .expect_offer_pet_transfer()
+            .with(eq(1), eq(123), eq("new@example.com"))
+            .times(1)
+            .returning(|_, _, _| Box::pin(async { Ok(()) }));
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let result = offer_pet_transfer(1, 123, "  New@Example.com ", &repo).await;
+
+        assert!(result.is_ok());
+    }
+
+    #[ntex::test]
+    async fn test_offer_pet_transfer_invalid_email_fails() {
+        let mut mock_repo = MockAppRepo::new();
+        mock_repo.expect_offer_pet_transfer().times(0);
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let result = offer_pet_transfer(1, 123, "not-an-email", &repo).await;
+
+        assert!(result.is_err_and(|e| matches!(
+            e.downcast_ref::<TransferPetError>(),
+            Some(TransferPetError::InvalidEmail)
+        )));
+    }
+
+    #[ntex::test]
+    async fn test_offer_pet_transfer_unknown_recipient_fails() {
+        let mut mock_repo = MockAppRepo::new();
+        mock_repo
+            .expect_offer_pet_transfer()
+            .times(1)
+            .returning(|_, _, _| Box::pin(async { Err(repo::errors::RepoError::NotFound) }));
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let result = offer_pet_transfer(1, 123, "nobody@example.com", &repo).await;
+
+        assert!(result.is_err_and(|e| matches!(
+            e.downcast_ref::<TransferPetError>(),
+            Some(TransferPetError::UnknownRecipient)
+        )));
+    }
+
+    #[ntex::test]
+    async fn test_accept_pet_transfer_checks_max_pets() {
+        let mut mock_repo = MockAppRepo::new();
+        mock_repo
+            .expect_count_user_pets()
+            .with(eq(456))
+            .returning(|_| Box::pin(async { Ok(3) }));
+        mock_repo.expect_accept_pet_transfer().times(0);
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let result = accept_pet_transfer(1, 456, 3, &repo, &create_test_profile_cache()).await;
+
+        assert!(result.is_err_and(|e| matches!(
+            e.downcast_ref::<AddPetError>(),
+            Some(AddPetError::MaxPetsReached { max: 3 })
+        )));
+    }
+
+    #[ntex::test]
+    async fn test_import_health_records_csv() {
+        let mut mock_repo = MockAppRepo::new();
+        let pet_external_id = Uuid::new_v4();
+        let user_id = 123;
+
+        mock_repo
+            .expect_insert_health_records_batch()
+            .withf(move |ext_id, uid, health_type, records| {
+                *ext_id == pet_external_id
+                    && *uid == user_id
+                    && *health_type == models::pet::PetHealthType::Vaccine
+                    && records.len() == 2
+            })
+            .times(1)
+            .returning(|_, _, _, records| {
+                let inserted = records.len() as u64;
+                Box::pin(async move { Ok(inserted) })
+            });
+
+        let mock_repo: Box<dyn repo::AppRepo> = Box::new(mock_repo);
+        let summary = import_health_records_csv(
+            pet_external_id,
+            user_id,
+            &models::pet::PetHealthType::Vaccine,
+            "2024-01-15,Rabia\n2024-02-30,Parvovirus\n2024-03-01,Moquillo\n",
+            &mock_repo,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(summary.imported, 2);
+        assert_eq!(summary.failed, 1);
+
+        let result = import_health_records_csv(
+            pet_external_id,
+            user_id,
+            &models::pet::PetHealthType::Weight,
+            "2024-01-15,10",
+            &mock_repo,
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[ntex::test]
+    async fn test_import_health_records_csv_unknown_pet_is_not_found() {
+        let mut mock_repo = MockAppRepo::new();
+        mock_repo
+            .expect_insert_health_records_batch()
+            .times(1)
+            .returning(|_, _, _, _| {
+                Box::pin(async { Err(repo::errors::RepoError::NotFound.into()) })
+            });
 
-    let mut images = vec![(qr_code_data, "qr.png")];
-    if let Some(pet_pic) = pet_pic_option
-        && let Some(ref filename) = image_filename
-    {
-        images.push((pet_pic.body, filename.as_str()));
+        let mock_repo: Box<dyn repo::AppRepo> = Box::new(mock_repo);
+        let result = import_health_records_csv(
+            Uuid::new_v4(),
+            123,
+            &models::pet::PetHealthType::Vaccine,
+            "2024-01-15,Rabia\n",
+            &mock_repo,
+        )
+        .await;
+        assert!(result.is_err_and(|e| repo::errors::RepoError::is_not_found(&e)));
     }
 
-    crate::api::pdf_handler::create_pdf_bytes_with_images(&content, images)
-}
+    #[ntex::test]
+    async fn test_edit_note() {
+        let mut mock_repo = MockAppRepo::new();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::repo::{AppRepo, MockAppRepo};
-    use crate::services::StorageService;
-    use async_trait::async_trait;
-    use chrono::{NaiveDate, Utc};
-    use mockall::predicate::*;
-    use uuid::Uuid;
+        mock_repo
+            .expect_update_pet_note()
+            .with(
+                eq(123),
+                eq(7),
+                eq("Dieta".to_string()),
+                eq("<p>pollo</p>".to_string()),
+            )
+            .times(1)
+            .returning(|_, _, _, _| Box::pin(async move { Ok(()) }));
 
-    struct MockStorageService;
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let result = edit_note(
+            123,
+            7,
+            PetNoteInfo {
+                title: "Dieta".into(),
+                body: "<p>pollo</p>".into(),
+            },
+            &repo,
+        )
+        .await;
 
-    impl MockStorageService {
-        fn new() -> Self {
-            Self
-        }
+        assert!(result.is_ok());
     }
 
-    #[async_trait]
-    impl StorageService for MockStorageService {
-        async fn save_pic(&self, _path: &str, _body: Vec<u8>) -> anyhow::Result<()> {
-            Ok(())
-        }
+    #[ntex::test]
+    async fn test_add_new_note_strips_script_tag() {
+        let mut mock_repo = MockAppRepo::new();
 
-        async fn get_pic_as_bytes(&self, _file_name: &str) -> anyhow::Result<Vec<u8>> {
-            Ok(vec![1, 2, 3, 4])
-        }
-    }
+        let clock = services::clock::FixedClock::at("2024-06-15T12:00:00Z");
+        let now = clock.0;
+        mock_repo
+            .expect_insert_new_pet_note()
+            .withf(move |user_id, note| {
+                *user_id == 123
+                    && note.pet_id == 7
+                    && note.title == "Dieta"
+                    && note.content == "<p>pollo</p>"
+                    && note.created_at == now
+                    && note.updated_at == now
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(async move { Ok(1) }));
 
-    fn create_test_pet() -> models::pet::Pet {
-        models::pet::Pet {
-            id: 1,
-            external_id: Uuid::new_v4(),
-            user_app_id: 123,
-            pet_name: "Buddy".to_string(),
-            birthday: NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(),
-            breed: "Golden Retriever".to_string(),
-            about: "A friendly dog".to_string(),
-            is_female: false,
-            is_lost: false,
-            is_spaying_neutering: true,
-            last_weight: Some(25.5),
-            pic: Some("test.jpg".to_string()),
-            created_at: Utc::now(),
-            updated_at: Utc::now(),
-        }
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let result = add_new_note(
+            123,
+            7,
+            PetNoteInfo {
+                title: "<script>alert(1)</script>Dieta".into(),
+                body: "<p>pollo</p><script>alert(1)</script>".into(),
+            },
+            &repo,
+            &(Box::new(clock) as services::ImplClock),
+        )
+        .await;
+
+        assert!(result.is_ok());
     }
 
-    fn create_test_pet_form() -> front::forms::pet::CreatePetForm {
-        front::forms::pet::CreatePetForm {
-            id: 0,
-            pet_full_name: "Buddy".to_string(),
-            pet_birthday: NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(),
-            pet_breed: "Golden Retriever".to_string(),
-            is_lost: false,
-            is_spaying_neutering: true,
-            is_female: false,
-            about_pet: "A friendly dog".to_string(),
-            pet_pic: None,
-            pet_external_id: None,
-        }
+    #[ntex::test]
+    async fn test_edit_note_rejects_long_body() {
+        let mut mock_repo = MockAppRepo::new();
+        mock_repo.expect_update_pet_note().times(0);
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let result = edit_note(
+            123,
+            7,
+            PetNoteInfo {
+                title: "Dieta".into(),
+                body: "a".repeat(consts::PET_NOTE_MAX_BODY_LEN + 1),
+            },
+            &repo,
+        )
+        .await;
+
+        assert!(matches!(
+            result.unwrap_err().downcast_ref::<PetNoteError>(),
+            Some(PetNoteError::BodyTooLong)
+        ));
     }
 
-    fn create_test_user_state() -> UserStateAddNewPet {
-        UserStateAddNewPet {
-            user_id: 123,
-            user_email: "test@example.com".to_string(),
-            pet_balance: 5,
-        }
+    #[ntex::test]
+    async fn test_get_pet_notes_next_offset() {
+        let mut mock_repo = MockAppRepo::new();
+        let page_size = consts::PET_NOTES_PAGE_SIZE;
+
+        mock_repo
+            .expect_get_pet_notes()
+            .with(eq(123), eq(1), eq(page_size), eq(0))
+            .times(1)
+            .returning(move |_, _, limit, _| {
+                Box::pin(async move {
+                    Ok((0..limit)
+                        .map(|id| models::pet::PetNote {
+                            id,
+                            ..Default::default()
+                        })
+                        .collect())
+                })
+            });
+        mock_repo
+            .expect_get_pet_notes()
+            .with(eq(123), eq(1), eq(page_size), eq(page_size))
+            .times(1)
+            .returning(|_, _, _, _| {
+                Box::pin(async move { Ok(vec![models::pet::PetNote::default()]) })
+            });
+        mock_repo
+            .expect_get_pet_notes_count()
+            .with(eq(123), eq(1))
+            .returning(move |_, _| Box::pin(async move { Ok(page_size + 1) }));
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+
+        let first_page = get_pet_notes(123, 1, 0, &repo).await.unwrap();
+        assert_eq!(first_page.next_offset, Some(page_size));
+
+        let last_page = get_pet_notes(123, 1, page_size, &repo).await.unwrap();
+        assert_eq!(last_page.notes.len(), 1);
+        assert_eq!(last_page.next_offset, None);
     }
 
     #[ntex::test]
-    async fn test_add_new_pet_to_user_success() {
+    async fn test_search_pet_notes_empty_query_returns_all() {
         let mut mock_repo = MockAppRepo::new();
-        let storage_service: Box<dyn StorageService> = Box::new(MockStorageService::new());
-        let user_state = create_test_user_state();
-        let pet_form = create_test_pet_form();
 
         mock_repo
-            .expect_is_pet_external_id_linked()
-            .returning(|_| Box::pin(async move { Ok(None) }));
+            .expect_get_all_pet_notes()
+            .with(eq(123), eq(1))
+            .times(1)
+            .returning(|_, _| Box::pin(async move { Ok(vec![]) }));
+        mock_repo.expect_search_pet_notes().times(0);
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        assert!(search_pet_notes(123, 1, "   ", &repo).await.is_ok());
+    }
+
+    #[ntex::test]
+    async fn test_search_pet_notes_trims_query() {
+        let mut mock_repo = MockAppRepo::new();
 
         mock_repo
-            .expect_save_pet()
-            .with(always())
+            .expect_search_pet_notes()
+            .with(eq(123), eq(1), eq("pollo".to_string()))
             .times(1)
-            .returning(|_| Box::pin(async move { Ok(1) }));
+            .returning(|_, _, _| Box::pin(async move { Ok(vec![]) }));
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        assert!(search_pet_notes(123, 1, " pollo ", &repo).await.is_ok());
+    }
 
+    #[ntex::test]
+    async fn test_user_can_add_pet_by_role() {
+        let mut mock_repo = MockAppRepo::new();
         mock_repo
-            .expect_set_user_as_subscribed()
+            .expect_count_user_pets()
             .with(eq(123))
             .times(1)
-            .returning(|_| Box::pin(async move { Ok(()) }));
+            .returning(|_| Box::pin(async move { Ok(1) }));
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+
+        // users need a paid pet balance, pets are not counted
+        let user = create_test_user(None);
+        assert!(user_can_add_pet(&user, 1, &repo).await.unwrap());
+        assert!(!user_can_add_pet(&user, 0, &repo).await.unwrap());
+
+        // premium users don't need a balance, their pets are counted
+        let premium = models::user_app::User {
+            account_role: models::user_app::AccountRole::Premium,
+            ..create_test_user(None)
+        };
+        assert!(user_can_add_pet(&premium, 0, &repo).await.unwrap());
+
+        let unsubscribed = models::user_app::User {
+            is_subscribed: false,
+            ..create_test_user(None)
+        };
+        assert!(!user_can_add_pet(&unsubscribed, 1, &repo).await.unwrap());
+    }
+
+    #[test]
+    fn test_pdf_report_options_from_sections() {
+        assert_eq!(
+            PdfReportOptions::from_sections(""),
+            PdfReportOptions::default()
+        );
+        assert_eq!(
+            PdfReportOptions::from_sections("vaccines, Weights,unknown"),
+            PdfReportOptions {
+                include_weights: true,
+                include_vaccines: true,
+                include_deworms: false,
+                include_notes: false,
+                image: None,
+            }
+        );
+    }
 
+    #[ntex::test]
+    async fn test_generate_pdf_report_without_sections_is_smaller() {
+        let mut mock_repo = MockAppRepo::new();
+        mock_repo.expect_get_pet_by_id().returning(|_, _| {
+            let pet = models::pet::Pet {
+                pic: None,
+                ..create_test_pet()
+            };
+            Box::pin(async move { Ok(pet) })
+        });
         mock_repo
-            .expect_set_pet_balance()
-            .with(eq(123), eq(4))
-            .times(1)
-            .returning(|_, _| Box::pin(async move { Ok(()) }));
+            .expect_get_pet_health_records()
+            .returning(|_, _, health_type| {
+                Box::pin(async move {
+                    Ok(vec![models::pet::PetHealth {
+                        id: 1,
+                        pet_id: 1,
+                        health_record: health_type,
+                        description: "Rabia".to_string(),
+                        created_at: Utc::now().naive_utc(),
+                    }])
+                })
+            });
+        mock_repo.expect_get_pet_weights().returning(|_, _| {
+            Box::pin(async move {
+                Ok(vec![models::pet::PetWeight {
+                    id: 1,
+                    pet_id: 1,
+                    value: 25.5,
+                    created_at: Utc::now().naive_utc(),
+                }])
+            })
+        });
+        mock_repo.expect_get_all_pet_notes().returning(|_, _| {
+            Box::pin(async move {
+                Ok(vec![models::pet::PetNote {
+                    id: 1,
+                    pet_id: 1,
+                    title: "Dieta".to_string(),
+                    content: "<p>pollo con arroz</p>".to_string(),
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                }])
+            })
+        });
 
         let repo: Box<dyn AppRepo> = Box::new(mock_repo);
-        let result = add_new_pet_to_user(user_state, pet_form, &repo, &storage_service).await;
+        let storage_service: Box<dyn StorageService> = Box::new(create_test_storage());
 
-        assert!(result.is_ok());
+        let full = generate_pdf_report_bytes(
+            1,
+            123,
+            Tz::America__Mexico_City,
+            models::user_app::WeightUnit::Lb,
+            &PdfReportOptions::default(),
+            &repo,
+            &storage_service,
+        )
+        .await
+        .unwrap();
+        let vaccines_only = generate_pdf_report_bytes(
+            1,
+            123,
+            Tz::America__Mexico_City,
+            models::user_app::WeightUnit::Kg,
+            &PdfReportOptions::from_sections("vaccines"),
+            &repo,
+            &storage_service,
+        )
+        .await
+        .unwrap();
+
+        assert!(vaccines_only.len() < full.len());
     }
 
     #[ntex::test]
-    async fn test_add_new_pet_to_user_with_external_id_validation_error() {
+    async fn test_vaccine_certificate_without_vaccines_is_valid_pdf() {
         let mut mock_repo = MockAppRepo::new();
-        let storage_service: Box<dyn StorageService> = Box::new(MockStorageService::new());
-        let user_state = create_test_user_state();
-        let mut pet_form = create_test_pet_form();
-        pet_form.pet_external_id = Some(Uuid::new_v4());
+        mock_repo.expect_get_pet_by_id().times(1).returning(|_, _| {
+            let pet = models::pet::Pet {
+                pic: None,
+                ..create_test_pet()
+            };
+            Box::pin(async move { Ok(pet) })
+        });
+        mock_repo
+            .expect_get_pet_health_records()
+            .with(
+                always(),
+                eq(Some(123)),
+                eq(models::pet::PetHealthType::Vaccine),
+            )
+            .times(1)
+            .returning(|_, _, _| Box::pin(async move { Ok(vec![]) }));
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let storage_service: Box<dyn StorageService> = Box::new(create_test_storage());
+
+        let pdf = generate_vaccine_certificate_pdf(
+            1,
+            123,
+            Tz::America__Mexico_City,
+            true,
+            &repo,
+            &storage_service,
+        )
+        .await
+        .unwrap();
+
+        assert!(pdf.starts_with(b"%PDF"));
+    }
+
+    #[ntex::test]
+    async fn test_unlock_owner_contacts_checks_pin() {
+        let pin_hash = crate::utils::hash_secret("4321").unwrap();
 
+        let mut mock_repo = MockAppRepo::new();
+        mock_repo.expect_get_pet_public_pin().returning(move |_| {
+            let pin_hash = pin_hash.clone();
+            Box::pin(async move { Ok(Some(pin_hash)) })
+        });
         mock_repo
-            .expect_is_pet_external_id_linked()
-            .returning(|_| Box::pin(async move { Ok(Some(true)) }));
+            .expect_get_pet_pin_locked_until()
+            .returning(|_| Box::pin(async move { Ok(None) }));
+        mock_repo
+            .expect_clear_failed_pet_pin_attempts()
+            .times(1)
+            .returning(|_| Box::pin(async move { Ok(()) }));
+        mock_repo
+            .expect_record_failed_pet_pin_attempt()
+            .times(2)
+            .returning(|_, _, _| Box::pin(async move { Ok(()) }));
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let clock = create_test_clock();
+
+        let external_id = Uuid::new_v4();
+        assert!(are_owner_contacts_locked(external_id, &repo).await.unwrap());
+        assert!(
+            unlock_owner_contacts(external_id, " 4321 ", &repo, &clock)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !unlock_owner_contacts(external_id, "1234", &repo, &clock)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !unlock_owner_contacts(external_id, "", &repo, &clock)
+                .await
+                .unwrap()
+        );
 
+        // pets without PIN show their contacts to everyone
+        let mut mock_repo = MockAppRepo::new();
+        mock_repo
+            .expect_get_pet_public_pin()
+            .returning(|_| Box::pin(async move { Ok(None) }));
         let repo: Box<dyn AppRepo> = Box::new(mock_repo);
-        let result = add_new_pet_to_user(user_state, pet_form, &repo, &storage_service).await;
 
-        assert!(result.is_err());
+        assert!(!are_owner_contacts_locked(external_id, &repo).await.unwrap());
         assert!(
-            result
-                .unwrap_err()
-                .to_string()
-                .contains("invalid pet_external_id")
+            unlock_owner_contacts(external_id, "", &repo, &clock)
+                .await
+                .unwrap()
         );
     }
 
     #[ntex::test]
-    async fn test_get_user_pets_cards_success() {
+    async fn test_unlock_owner_contacts_blocked_after_failures() {
+        let pin_hash = crate::utils::hash_secret("4321").unwrap();
+        let clock = create_test_clock();
+        let locked_until = clock.now() + chrono::Duration::minutes(1);
+
         let mut mock_repo = MockAppRepo::new();
-        let user_id = 123;
+        mock_repo.expect_get_pet_public_pin().returning(move |_| {
+            let pin_hash = pin_hash.clone();
+            Box::pin(async move { Ok(Some(pin_hash)) })
+        });
+        mock_repo
+            .expect_get_pet_pin_locked_until()
+            .returning(move |_| Box::pin(async move { Ok(Some(locked_until)) }));
+        // the PIN isn't checked while blocked, not even the right one
+        mock_repo.expect_clear_failed_pet_pin_attempts().never();
+        mock_repo.expect_record_failed_pet_pin_attempt().never();
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+
+        let err = unlock_owner_contacts(Uuid::new_v4(), "4321", &repo, &clock)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<PublicPinError>(),
+            Some(PublicPinError::TooManyAttempts)
+        ));
+    }
 
+    #[ntex::test]
+    async fn test_set_pet_public_pin_stores_hash() {
+        let mut mock_repo = MockAppRepo::new();
         mock_repo
-            .expect_get_all_pets_user_id()
-            .with(eq(user_id))
+            .expect_set_pet_public_pin()
+            .withf(|pet_id, user_id, pin_hash| {
+                *pet_id == 1
+                    && *user_id == 123
+                    && pin_hash.as_deref().is_some_and(|hash| {
+                        hash != "2468" && crate::utils::verify_secret("2468", hash)
+                    })
+            })
             .times(1)
-            .returning(move |_| {
-                let pet = create_test_pet();
-                Box::pin(async move { Ok(vec![pet]) })
-            });
-
+            .returning(|_, _, _| Box::pin(async move { Ok(()) }));
+        mock_repo
+            .expect_set_pet_public_pin()
+            .withf(|_, _, pin_hash| pin_hash.is_none())
+            .times(1)
+            .returning(|_, _, _| Box::pin(async move { Ok(()) }));
         let repo: Box<dyn AppRepo> = Box::new(mock_repo);
-        let result = get_user_pets_cards(user_id, &repo).await;
 
-        assert!(result.is_ok_and(|pets| {
-            pets.len() == 1 && pets[0].name == "Buddy" && pets[0].breed == "Golden Retriever"
-        }));
+        set_pet_public_pin(1, 123, "2468", &repo).await.unwrap();
+        set_pet_public_pin(1, 123, "  ", &repo).await.unwrap();
+
+        for invalid_pin in ["123", "123456789", "12a4"] {
+            let err = set_pet_public_pin(1, 123, invalid_pin, &repo)
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err.downcast_ref::<PublicPinError>(),
+                Some(PublicPinError::InvalidPin)
+            ));
+        }
     }
 
     #[ntex::test]
-    async fn test_get_pet_public_info_success() {
+    async fn test_get_public_pic_missing_in_storage_returns_none() {
         let mut mock_repo = MockAppRepo::new();
         let external_id = Uuid::new_v4();
 
         mock_repo
-            .expect_get_pet_by_external_id()
+            .expect_get_pet_pic_path_by_external_id()
             .with(eq(external_id))
             .times(1)
-            .returning(move |_| {
-                let pet = create_test_pet();
-                Box::pin(async move { Ok(pet) })
-            });
+            .returning(|_| Box::pin(async { Ok(Some("pets/pic.png".to_string())) }));
 
         let repo: Box<dyn AppRepo> = Box::new(mock_repo);
-        let result = get_pet_public_info(external_id, &repo).await;
+        let storage_service: services::ImplStorageService = Box::<FakeStorageService>::default();
 
-        assert!(result.is_ok_and(|pet_info| {
-            pet_info.name == "Buddy" && pet_info.pet_breed == "Golden Retriever"
-        }));
+        let pic = get_public_pic(external_id, &repo, &storage_service).await;
+
+        assert!(pic.is_ok_and(|pic| pic.is_none()));
     }
 
     #[ntex::test]
-    async fn test_delete_pet_and_its_info_success() {
+    async fn test_create_pet_share_link_checks_days() {
+        let now = Utc.with_ymd_and_hms(2024, 6, 15, 12, 0, 0).unwrap();
+        let clock: services::ImplClock = Box::new(services::clock::FixedClock(now));
+
+        for days in ["0", "31", "-1", "dos", ""] {
+            let mut mock_repo = MockAppRepo::new();
+            mock_repo.expect_insert_pet_share_token().times(0);
+            let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+
+            let result = create_pet_share_link(1, 123, days, &repo, &clock).await;
+
+            assert!(result.is_err_and(|e| matches!(
+                e.downcast_ref::<ShareLinkError>(),
+                Some(ShareLinkError::InvalidDuration)
+            )));
+        }
+
         let mut mock_repo = MockAppRepo::new();
-        let pet_id = 1;
-        let user_id = 123;
+        mock_repo
+            .expect_insert_pet_share_token()
+            .withf(move |pet_id, user_id, token, expires_at| {
+                *pet_id == 1
+                    && *user_id == 123
+                    && token.len() == 43
+                    && *expires_at == Utc.with_ymd_and_hms(2024, 6, 22, 12, 0, 0).unwrap()
+            })
+            .times(1)
+            .returning(|_, _, _, _| Box::pin(async { Ok(()) }));
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+
+        let share_token = create_pet_share_link(1, 123, " 7 ", &repo, &clock)
+            .await
+            .unwrap();
+        assert_eq!(share_token.pet_id, 1);
+        assert_ne!(
+            share_token.token,
+            crate::utils::generate_opaque_token(),
+            "tokens are random"
+        );
+    }
+
+    #[test]
+    fn test_pet_health_record_serializes_date_as_rfc3339() {
+        let record = PetHealthRecord {
+            id: 7,
+            value: "Rabia".to_string(),
+            date: NaiveDate::from_ymd_opt(2024, 1, 15)
+                .unwrap()
+                .and_hms_opt(9, 30, 0)
+                .unwrap(),
+        };
 
+        assert_eq!(
+            serde_json::to_string(&record).unwrap(),
+            r#"{"id":7,"value":"Rabia","date":"2024-01-15T09:30:00Z"}"#
+        );
+    }
+
+    #[ntex::test]
+    async fn test_generate_pdf_report_share_quality_is_smaller() {
+        let mut mock_repo = MockAppRepo::new();
+        mock_repo.expect_get_pet_by_id().returning(|_, _| {
+            let pet = models::pet::Pet {
+                pic: Some("pics/buddy.png".to_string()),
+                ..create_test_pet()
+            };
+            Box::pin(async move { Ok(pet) })
+        });
         mock_repo
-            .expect_delete_pet()
-            .with(eq(pet_id), eq(user_id))
+            .expect_get_pet_pic_path_by_external_id()
+            .returning(|_| Box::pin(async move { Ok(Some("pics/buddy.png".to_string())) }));
+        mock_repo
+            .expect_get_pet_health_records()
+            .returning(|_, _, _| Box::pin(async move { Ok(vec![]) }));
+        mock_repo
+            .expect_get_pet_weights()
+            .returning(|_, _| Box::pin(async move { Ok(vec![]) }));
+        mock_repo
+            .expect_get_all_pet_notes()
+            .returning(|_, _| Box::pin(async move { Ok(vec![]) }));
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        // a big, detailed picture
+        let img = image::RgbImage::from_fn(1200, 900, |x, y| {
+            image::Rgb([(x * 7 + y * 3) as u8, (x ^ y) as u8, (x * y) as u8])
+        });
+        let mut pic = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut pic), image::ImageFormat::Png)
+            .unwrap();
+        let storage_service: Box<dyn StorageService> = Box::new(FakeStorageService::serving(pic));
+
+        let mut sizes = Vec::new();
+        for quality in [PdfQuality::Print, PdfQuality::Share] {
+            let pdf = generate_pdf_report_bytes(
+                1,
+                123,
+                Tz::America__Mexico_City,
+                models::user_app::WeightUnit::Kg,
+                &PdfReportOptions::default().with_quality(quality),
+                &repo,
+                &storage_service,
+            )
+            .await
+            .unwrap();
+            assert!(pdf.starts_with(b"%PDF"));
+            sizes.push(pdf.len());
+        }
+
+        assert!(
+            sizes[1] < sizes[0] / 2,
+            "share {} vs print {}",
+            sizes[1],
+            sizes[0]
+        );
+    }
+
+    #[test]
+    fn test_pdf_report_quality_keeps_sections() {
+        let options = PdfReportOptions::from_sections("vaccines").with_quality(PdfQuality::Share);
+
+        assert!(options.include_vaccines && !options.include_notes);
+        assert_eq!(
+            options.image,
+            Some(PdfImageOptions {
+                max_side_px: consts::PDF_SHARE_IMAGE_MAX_PX,
+                jpeg_quality: consts::PDF_SHARE_IMAGE_JPEG_QUALITY,
+            })
+        );
+        assert_eq!(options.with_quality(PdfQuality::Print).image, None);
+    }
+
+    #[ntex::test]
+    async fn test_viewing_unlinked_tag_keeps_its_owner() {
+        let tag = Uuid::new_v4();
+
+        let mut mock_repo = MockAppRepo::new();
+        mock_repo
+            .expect_is_pet_external_id_linked()
+            .with(eq(tag))
             .times(1)
-            .returning(|_, _| Box::pin(async move { Ok(()) }));
+            .returning(|_| Box::pin(async { Ok(Some(false)) }));
+        mock_repo.expect_assign_external_id_owner().times(0);
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+
+        assert_eq!(
+            get_pet_external_id_metadata(&tag, &repo)
+                .await
+                .unwrap()
+                .map(|metadata| metadata.is_linked),
+            Some(false)
+        );
+    }
+
+    #[ntex::test]
+    async fn test_get_user_external_ids_splits_linked_and_unlinked() {
+        let (linked_id, unlinked_id) = (Uuid::new_v4(), Uuid::new_v4());
 
+        let mut mock_repo = MockAppRepo::new();
+        mock_repo.expect_get_all_pets_user_id().returning(move |_| {
+            Box::pin(async move {
+                Ok(vec![models::pet::Pet {
+                    external_id: linked_id,
+                    pet_name: "Firulais".into(),
+                    ..Default::default()
+                }])
+            })
+        });
+        mock_repo
+            .expect_get_user_unlinked_external_ids()
+            .returning(move |_| Box::pin(async move { Ok(vec![unlinked_id]) }));
         let repo: Box<dyn AppRepo> = Box::new(mock_repo);
-        let result = delete_pet_and_its_info(pet_id, user_id, &repo).await;
 
-        assert!(result.is_ok());
+        assert_eq!(
+            get_user_external_ids(1, &repo).await.unwrap(),
+            UserExternalIds {
+                linked: vec![LinkedExternalId {
+                    external_id: linked_id,
+                    pet_name: "Firulais".into(),
+                }],
+                unlinked: vec![unlinked_id],
+            }
+        );
     }
 }
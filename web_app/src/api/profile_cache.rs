@@ -0,0 +1,161 @@
+//! # Public Profile Cache Module
+//!
+//! Public profiles are scanned over and over, e.g. the tag of a lost pet, so
+//! the [`PetPublicInfoSchema`] of a pet is kept in memory for a short TTL.
+//! Entries are keyed by the pet external id and the visitor's local date,
+//! since the age and the last update shown are relative to it.
+//! Changes to the pet invalidate its entries right away.
+
+use crate::api::pet::PetPublicInfoSchema;
+use chrono::NaiveDate;
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+use uuid::Uuid;
+
+/// Cache entry key: pet external id and visitor's local date
+type ProfileKey = (Uuid, NaiveDate);
+
+struct CachedProfile {
+    pet_id: i64,
+    cached_at: Instant,
+    profile: PetPublicInfoSchema,
+}
+
+/// TTL cache of public profiles.
+pub struct PublicProfileCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<ProfileKey, CachedProfile>>,
+}
+
+impl PublicProfileCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// A panic while holding the lock can't leave the entries inconsistent
+    fn lock_entries(&self) -> MutexGuard<'_, HashMap<ProfileKey, CachedProfile>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Returns the cached profile if it hasn't expired.
+    pub fn get(&self, pet_external_id: Uuid, today: NaiveDate) -> Option<PetPublicInfoSchema> {
+        let mut entries = self.lock_entries();
+        let key = (pet_external_id, today);
+
+        match entries.get(&key) {
+            Some(entry) if entry.cached_at.elapsed() < self.ttl => Some(entry.profile.clone()),
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Stores a profile, dropping the expired ones.
+    pub fn insert(
+        &self,
+        pet_external_id: Uuid,
+        today: NaiveDate,
+        pet_id: i64,
+        profile: PetPublicInfoSchema,
+    ) {
+        if self.ttl.is_zero() {
+            return;
+        }
+
+        let mut entries = self.lock_entries();
+        entries.retain(|_, entry| entry.cached_at.elapsed() < self.ttl);
+        entries.insert(
+            (pet_external_id, today),
+            CachedProfile {
+                pet_id,
+                cached_at: Instant::now(),
+                profile,
+            },
+        );
+    }
+
+    /// Removes the cached profiles of a pet, used when the pet changes.
+    pub fn invalidate(&self, pet_external_id: Uuid) {
+        self.lock_entries()
+            .retain(|(external_id, _), _| *external_id != pet_external_id);
+    }
+
+    /// Same as [`invalidate`](Self::invalidate) for callers only knowing the pet id.
+    pub fn invalidate_pet(&self, pet_id: i64) {
+        self.lock_entries()
+            .retain(|_, entry| entry.pet_id != pet_id);
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock_entries().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models;
+
+    fn profile(pet_id: i64, external_id: Uuid) -> PetPublicInfoSchema {
+        PetPublicInfoSchema::from_pet(
+            models::pet::Pet {
+                id: pet_id,
+                external_id,
+                ..Default::default()
+            },
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_profile_cache_expires_after_ttl() {
+        let cache = PublicProfileCache::new(Duration::from_millis(20));
+        let (external_id, today) = (Uuid::new_v4(), NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
+
+        cache.insert(external_id, today, 1, profile(1, external_id));
+        assert!(cache.get(external_id, today).is_some());
+        // another day has its own entry
+        assert!(cache.get(external_id, today.succ_opt().unwrap()).is_none());
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(cache.get(external_id, today).is_none());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_profile_cache_invalidate_removes_pet_entries() {
+        let cache = PublicProfileCache::new(Duration::from_secs(60));
+        let (external_id, other_external_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let today = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+
+        cache.insert(external_id, today, 1, profile(1, external_id));
+        cache.insert(
+            external_id,
+            today.succ_opt().unwrap(),
+            1,
+            profile(1, external_id),
+        );
+        cache.insert(other_external_id, today, 2, profile(2, other_external_id));
+
+        cache.invalidate_pet(1);
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get(other_external_id, today).is_some());
+
+        cache.invalidate(other_external_id);
+        assert!(cache.is_empty());
+    }
+}
@@ -4,11 +4,17 @@
 //! phone verification via WhatsApp, reminder scheduling, and notification
 //! delivery for pet health and care reminders.
 
-use crate::{metric, models, repo, services, utils, webhook};
-use anyhow::Context;
-use chrono::{DateTime, Utc};
+use crate::{api, config, consts, metric, models, repo, services, utils, webhook};
+use anyhow::{Context, bail};
+use chrono::{
+    DateTime, Datelike, Days, LocalResult, Months, NaiveDate, NaiveDateTime, TimeDelta, Timelike,
+    Utc,
+};
 use chrono_tz::Tz;
+use derive_more::{Display, Error};
+use serde::Serialize;
 use serde_json::json;
+use std::collections::BTreeMap;
 
 /// Sends a verification code to a phone number via WhatsApp.
 ///
@@ -16,16 +22,28 @@ use serde_json::json;
 /// number using WhatsApp Business API.
 ///
 /// # Arguments
-/// * `whatsapp_client` - Shared WhatsApp client instance
+/// * `user_id` - ID of the user verifying the phone
 /// * `phone_number` - Phone number to send verification to (international format)
+/// * `whatsapp_client` - Shared WhatsApp client instance
+/// * `throttle` - Limit of codes sent per user
+/// * `clock` - Source of the current time for the throttle window
 ///
 /// # Returns
 /// * `anyhow::Result<()>` - Success confirmation or error details
+///
+/// # Errors
+/// Returns [`TooManyVerificationCodes`](api::verification_throttle::TooManyVerificationCodes)
+/// if the user already got too many codes recently, nothing is sent then.
 pub async fn send_verification(
-    whatsapp_client: &webhook::whatsapp::client::WhatsAppClient,
+    user_id: i64,
     phone_number: &str,
+    whatsapp_client: &webhook::whatsapp::client::WhatsAppClient,
+    throttle: &api::verification_throttle::VerificationThrottle,
+    clock: &services::ImplClock,
 ) -> anyhow::Result<()> {
-    let otp = utils::TOTP_CLIENT.generate_current()?;
+    throttle.try_acquire(user_id, clock.now())?;
+
+    let otp = utils::totp_client()?.generate_current()?;
 
     let payload = create_whatsapp_verification_payload(phone_number, &otp);
 
@@ -71,7 +89,9 @@ fn create_whatsapp_verification_payload(phone_number: &str, otp: &str) -> serde_
 /// # Returns
 /// * `bool` - True if OTP is valid, false otherwise
 pub fn validate_otp(otp: &str) -> bool {
-    utils::TOTP_CLIENT.check_current(otp).unwrap_or(false)
+    utils::totp_client()
+        .and_then(|totp| Ok(totp.check_current(otp)?))
+        .unwrap_or(false)
 }
 
 /// Adds a verified phone number to a user's account.
@@ -96,68 +116,321 @@ pub async fn remove_verified_phone_to_user(
 ///
 /// Contains all the details needed to create and schedule a reminder,
 /// including user information, timing, and message content.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ScheduleReminderInfo {
     /// ID of the user to send the reminder to
     pub user_id: i64,
-    /// Phone number to send the reminder to
-    pub phone_number: String,
+    /// Channels to send the reminder through
+    pub channels: models::reminder::ReminderChannels,
+    /// Phone number to send the reminder to, required by the WhatsApp channel
+    pub phone_number: Option<String>,
+    /// Email to send the reminder to, required by the email channel
+    pub email: Option<String>,
     /// When to send the reminder (with timezone)
     pub when: DateTime<Tz>,
     /// Message content for the reminder
     pub body: String,
+    /// Pet the reminder is about, its reminders are removed when it is deleted
+    pub pet_id: Option<i64>,
+}
+
+/// Errors raised when the time of a reminder can't be scheduled
+#[derive(Debug, Display, Error, PartialEq)]
+pub enum ReminderTimeError {
+    #[display("la fecha del recordatorio ya paso")]
+    InPast,
+    #[display(
+        "el recordatorio debe programarse con al menos {min_minutes} minutos de anticipacion"
+    )]
+    TooSoon { min_minutes: i64 },
+    #[display("el recordatorio no puede programarse a mas de {max_days} dias")]
+    TooFar { max_days: i64 },
+}
+
+/// Checks a reminder time is inside the window that can be scheduled.
+///
+/// # Arguments
+/// * `when` - When the reminder should be sent
+/// * `now` - Current instant
+/// * `min_lead` - Shortest time ahead of `now` allowed
+/// * `max_horizon` - Longest time ahead of `now` allowed
+///
+/// # Returns
+/// * `Result<(), ReminderTimeError>` - Why the time can't be scheduled, if so
+pub fn validate_reminder_time(
+    when: DateTime<Utc>,
+    now: DateTime<Utc>,
+    min_lead: TimeDelta,
+    max_horizon: TimeDelta,
+) -> Result<(), ReminderTimeError> {
+    let ahead = when - now;
+
+    if ahead <= TimeDelta::zero() {
+        return Err(ReminderTimeError::InPast);
+    }
+    if ahead < min_lead {
+        return Err(ReminderTimeError::TooSoon {
+            min_minutes: min_lead.num_minutes(),
+        });
+    }
+    if ahead > max_horizon {
+        return Err(ReminderTimeError::TooFar {
+            max_days: max_horizon.num_days(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Configured scheduling window of the reminders, the consts when the
+/// config isn't loaded
+fn reminder_window() -> (TimeDelta, TimeDelta) {
+    config::APP_CONFIG
+        .get()
+        .map(|app_config| {
+            (
+                app_config.reminder_min_lead(),
+                app_config.reminder_max_horizon(),
+            )
+        })
+        .unwrap_or((
+            TimeDelta::minutes(consts::REMINDER_MIN_LEAD_MINUTES),
+            TimeDelta::days(consts::REMINDER_MAX_HORIZON_DAYS),
+        ))
+}
+
+/// Checks every channel of a reminder has its contact.
+fn check_reminder_contacts(reminder_info: &ScheduleReminderInfo) -> anyhow::Result<()> {
+    use models::reminder::ReminderNotificationType;
+
+    if reminder_info.channels.is_empty() {
+        bail!("reminder without notification channels");
+    }
+    if reminder_info
+        .channels
+        .contains(ReminderNotificationType::WhatsApp)
+        && reminder_info.phone_number.is_none()
+    {
+        bail!("whatsapp reminder without phone number");
+    }
+    if reminder_info
+        .channels
+        .contains(ReminderNotificationType::Email)
+        && reminder_info.email.is_none()
+    {
+        bail!("email reminder without email");
+    }
+
+    Ok(())
 }
 
 /// Schedules a reminder notification for future delivery.
+///
+/// The reminder is sent through every channel in `reminder_info.channels`,
+/// each channel needs its contact (phone number or email). The time must be
+/// inside the configured window, otherwise a [`ReminderTimeError`] is returned.
 pub async fn schedule_reminder(
     reminder_info: ScheduleReminderInfo,
     repo: &repo::ImplAppRepo,
     notification_service: &services::ImplNotificationService,
+    clock: &services::ImplClock,
 ) -> anyhow::Result<()> {
+    check_reminder_contacts(&reminder_info)?;
+
+    let now = clock.now();
+    let (min_lead, max_horizon) = reminder_window();
+    validate_reminder_time(reminder_info.when.to_utc(), now, min_lead, max_horizon)?;
+
     let execution_id = notification_service
         .send_reminder_to_phone_number(&reminder_info)
         .await?;
 
-    repo.insert_user_remider(&create_reminder_model(reminder_info, execution_id))
+    repo.insert_user_remider(&create_reminder_model(reminder_info, execution_id, now))
         .await?;
     metric::incr_reminder_action_statds("schedule");
 
     Ok(())
 }
 
+/// Adjustment applied to a local reminder time around a DST transition
+#[derive(Debug, Display, Clone, Copy, PartialEq)]
+pub enum DstAdjustment {
+    /// The local time fell in a DST gap and was moved to the next valid time
+    #[display(
+        "la hora {requested} no existe por el cambio de horario, se programo a las {shifted_to}"
+    )]
+    ShiftedForward {
+        requested: NaiveDateTime,
+        shifted_to: NaiveDateTime,
+    },
+    /// The local time happens twice (DST overlap) and the first one was used
+    #[display(
+        "la hora {requested} ocurre dos veces por el cambio de horario, se programo la primera"
+    )]
+    EarliestOfAmbiguous { requested: NaiveDateTime },
+}
+
+/// Resolves a local date and time of the user to an instant.
+///
+/// Around DST transitions a local time may not exist (gap) or happen twice
+/// (overlap). On a gap the time is shifted forward to the next valid minute,
+/// on an overlap the earlier offset is picked. The adjustment is returned so
+/// it can be shown to the user.
+///
+/// # Arguments
+/// * `naive` - Local date and time typed by the user
+/// * `tz` - Timezone of the user
+///
+/// # Returns
+/// * `(DateTime<Utc>, Option<DstAdjustment>)` - The instant and the applied
+///   adjustment, if any
+pub fn resolve_local_datetime(
+    naive: NaiveDateTime,
+    tz: Tz,
+) -> (DateTime<Utc>, Option<DstAdjustment>) {
+    match naive.and_local_timezone(tz) {
+        LocalResult::Single(dt) => (dt.to_utc(), None),
+        LocalResult::Ambiguous(earliest, _) => (
+            earliest.to_utc(),
+            Some(DstAdjustment::EarliestOfAmbiguous { requested: naive }),
+        ),
+        LocalResult::None => {
+            // gaps last at most a few hours, walk minute by minute to its end
+            let mut shifted_to = naive
+                .with_second(0)
+                .and_then(|dt| dt.with_nanosecond(0))
+                .unwrap_or(naive);
+            loop {
+                shifted_to += TimeDelta::minutes(1);
+
+                if let Some(dt) = shifted_to.and_local_timezone(tz).earliest() {
+                    return (
+                        dt.to_utc(),
+                        Some(DstAdjustment::ShiftedForward {
+                            requested: naive,
+                            shifted_to,
+                        }),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// When a reminder will be sent
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ReminderPreview {
+    /// Instant the reminder fires
+    pub send_at: DateTime<Utc>,
+    /// Human-readable time in the user's timezone
+    pub local: String,
+    /// Message explaining the DST adjustment applied to the time, if any
+    pub adjustment: Option<String>,
+}
+
+/// Previews when a reminder scheduled at `when` (user local time) fires.
+///
+/// Applies the same timezone conversion as the reminder creation
+/// ([`resolve_local_datetime`]). If `recurrence` is set and `when` is already
+/// past, the next occurrence (same local time every `recurrence` days) is
+/// returned.
+///
+/// # Arguments
+/// * `when` - Local date and time typed by the user
+/// * `tz` - Timezone of the user
+/// * `recurrence` - Optional days between occurrences
+/// * `clock` - Source of the current time
+///
+/// # Returns
+/// * `anyhow::Result<ReminderPreview>` - UTC instant and local string
+pub fn preview_next_occurrence(
+    when: NaiveDateTime,
+    tz: Tz,
+    recurrence: Option<Days>,
+    clock: &services::ImplClock,
+) -> anyhow::Result<ReminderPreview> {
+    next_occurrence_from(when, tz, recurrence, clock.now())
+}
+
+/// Same as [`preview_next_occurrence`] with an explicit `now`.
+fn next_occurrence_from(
+    when: NaiveDateTime,
+    tz: Tz,
+    recurrence: Option<Days>,
+    now: DateTime<Utc>,
+) -> anyhow::Result<ReminderPreview> {
+    let mut when = when;
+    let (mut send_at, mut adjustment) = resolve_local_datetime(when, tz);
+
+    if let Some(every) = recurrence.filter(|days| *days != Days::new(0))
+        && send_at < now
+    {
+        let add_days = |when: NaiveDateTime, days: Days| {
+            when.checked_add_days(days)
+                .context("reminder recurrence out of range")
+        };
+        let every_days = (add_days(when, every)? - when).num_days();
+
+        // jumps the whole periods already past in local time, the offset
+        // changes in between leave at most a couple of periods to step
+        let elapsed_days = (now.with_timezone(&tz).naive_local() - when).num_days();
+        let skipped_periods = u64::try_from(elapsed_days / every_days).unwrap_or_default();
+        when = add_days(when, Days::new(skipped_periods * every_days.unsigned_abs()))?;
+        (send_at, adjustment) = resolve_local_datetime(when, tz);
+
+        while send_at < now {
+            when = add_days(when, every)?;
+            (send_at, adjustment) = resolve_local_datetime(when, tz);
+        }
+    }
+
+    Ok(ReminderPreview {
+        send_at,
+        local: send_at
+            .with_timezone(&tz)
+            .format("%d/%m/%Y %H:%M %Z")
+            .to_string(),
+        adjustment: adjustment.map(|adjustment| adjustment.to_string()),
+    })
+}
+
 /// Creates a reminder model from the provided information.
 fn create_reminder_model(
     reminder_info: ScheduleReminderInfo,
     execution_id: String,
+    created_at: DateTime<Utc>,
 ) -> models::reminder::Reminder {
     models::reminder::Reminder {
         id: 0,
         user_app_id: reminder_info.user_id,
         body: reminder_info.body,
         execution_id,
-        notification_type: models::reminder::ReminderNotificationType::WhatsApp,
+        notification_type: reminder_info.channels,
         user_timezone: reminder_info.when.timezone().name().to_string(),
         send_at: reminder_info.when.to_utc(),
-        created_at: Utc::now(),
+        pet_id: reminder_info.pet_id,
+        created_at,
     }
 }
 
-/// Retrieves all scheduled reminders for a user.
+/// Retrieves the reminders of a user with their status.
 ///
-/// Fetches all active (non-sent, non-cancelled) reminders that are
-/// scheduled for the specified user.
+/// Lists the upcoming reminders first and, when `include_past` is set, the
+/// recently fired ones after them.
 ///
 /// # Arguments
 /// * `user_app_id` - ID of the user to get reminders for
+/// * `include_past` - Whether to list the recently fired reminders too
 /// * `repo` - Repository instance for database operations
 ///
 /// # Returns
-/// * `anyhow::Result<Vec<models::reminder::Reminder>>` - List of scheduled reminders
-pub async fn get_scheduled_reminders(
+/// * `anyhow::Result<Vec<models::reminder::ReminderListItem>>` - List of reminders
+pub async fn get_user_reminders(
     user_app_id: i64,
+    include_past: bool,
     repo: &repo::ImplAppRepo,
-) -> anyhow::Result<Vec<models::reminder::Reminder>> {
-    repo.get_active_user_remiders(user_app_id).await
+) -> anyhow::Result<Vec<models::reminder::ReminderListItem>> {
+    repo.get_all_user_reminders(user_app_id, include_past).await
 }
 
 /// Deletes a scheduled reminder and cancels its delivery.
@@ -202,3 +475,1450 @@ pub async fn delete_reminder(
 
     repo.delete_user_reminder(reminder_id, user_id).await
 }
+
+/// Moves a scheduled reminder to a new time, keeping its id and body.
+///
+/// The new execution is started before the old one is cancelled, and each
+/// step undoes the previous ones when it fails, so a failure never leaves
+/// both executions running: the reminder keeps its old time instead.
+///
+/// # Arguments
+/// * `reminder_id` - ID of the reminder to move
+/// * `user` - User who owns the reminder, its contacts receive the reminder
+/// * `new_when` - New local date and time typed by the user
+/// * `tz` - Timezone of the user
+/// * `repo` - Repository instance for database operations
+/// * `notification_service` - Service scheduling and cancelling the executions
+/// * `clock` - Source of the current time for the scheduling window
+///
+/// # Returns
+/// * `anyhow::Result<Option<DstAdjustment>>` - The DST adjustment applied to
+///   the new time, if any
+///
+/// # Process
+/// 1. Check the new time is inside the scheduling window
+/// 2. Start the execution at the new time
+/// 3. Save the new time and execution, cancelling the new one if it fails
+/// 4. Cancel the old execution; if it fails the new one is cancelled and the
+///    old time and execution are saved back
+///
+/// # Errors
+/// Returns [`ReminderTimeError`] if the new time can't be scheduled, or
+/// [`RepoError::NotFound`](repo::errors::RepoError::NotFound) if the user
+/// doesn't own the reminder.
+pub async fn reschedule_reminder(
+    reminder_id: i64,
+    user: &models::user_app::User,
+    new_when: NaiveDateTime,
+    tz: Tz,
+    repo: &repo::ImplAppRepo,
+    notification_service: &services::ImplNotificationService,
+    clock: &services::ImplClock,
+) -> anyhow::Result<Option<DstAdjustment>> {
+    let reminder = repo.get_user_reminder(reminder_id, user.id).await?;
+
+    let (send_at, adjustment) = resolve_local_datetime(new_when, tz);
+    let (min_lead, max_horizon) = reminder_window();
+    validate_reminder_time(send_at, clock.now(), min_lead, max_horizon)?;
+
+    let reminder_info = ScheduleReminderInfo {
+        user_id: user.id,
+        channels: reminder.notification_type,
+        phone_number: user.phone_reminder.clone(),
+        email: Some(user.email.clone()),
+        when: send_at.with_timezone(&tz),
+        body: reminder.body,
+        pet_id: reminder.pet_id,
+    };
+    check_reminder_contacts(&reminder_info)?;
+
+    let new_execution_id = notification_service
+        .send_reminder_to_phone_number(&reminder_info)
+        .await?;
+
+    if let Err(e) = repo
+        .update_reminder_schedule(reminder_id, user.id, send_at, tz.name(), &new_execution_id)
+        .await
+    {
+        cancel_orphan_execution(reminder_id, &new_execution_id, notification_service).await;
+        return Err(e);
+    }
+
+    if let Err(e) = notification_service
+        .cancel_reminder_to_phone_number(&reminder.execution_id)
+        .await
+    {
+        // the old execution may still run, so the reminder goes back to it
+        cancel_orphan_execution(reminder_id, &new_execution_id, notification_service).await;
+        repo.update_reminder_schedule(
+            reminder_id,
+            user.id,
+            reminder.send_at,
+            &reminder.user_timezone,
+            &reminder.execution_id,
+        )
+        .await
+        .context("the reminder couldn't be restored to its old execution")?;
+        return Err(e.context("the old reminder execution couldn't be cancelled"));
+    }
+
+    metric::incr_reminder_action_statds("reschedule");
+
+    Ok(adjustment)
+}
+
+/// Cancels an execution no reminder points to, only logging a failure since
+/// the error that made it an orphan is the one returned.
+async fn cancel_orphan_execution(
+    reminder_id: i64,
+    execution_id: &str,
+    notification_service: &services::ImplNotificationService,
+) {
+    if let Err(e) = notification_service
+        .cancel_reminder_to_phone_number(execution_id)
+        .await
+    {
+        logfire::error!(
+            "reminder {reminder_id} execution {execution_id} couldn't be cancelled: {error}",
+            reminder_id = reminder_id,
+            execution_id = execution_id.to_string(),
+            error = e.to_string()
+        );
+    }
+}
+
+/// Reminder template a user can pick when creating a reminder
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ReminderTemplateOption {
+    /// Key of an embedded default or id of a saved template
+    pub value: String,
+    pub name: String,
+    pub body: String,
+    /// Whether it is one of the embedded defaults, those can't be deleted
+    pub is_default: bool,
+}
+
+/// Errors raised when a reminder template can't be saved
+#[derive(Debug, Display, Error, PartialEq)]
+pub enum ReminderTemplateError {
+    #[display("la plantilla necesita un nombre y un texto")]
+    Empty,
+    #[display("el nombre de la plantilla es demasiado largo")]
+    NameTooLong,
+    #[display("el texto de la plantilla es demasiado largo")]
+    BodyTooLong,
+    #[display("la plantilla no existe")]
+    NotFound,
+}
+
+/// Fills the `{pet_name}` and `{date}` placeholders of a reminder template.
+///
+/// Placeholders without a value, like `{pet_name}` when no pet is given,
+/// and unknown ones are left as they are.
+///
+/// # Arguments
+/// * `template` - Template text
+/// * `pet` - Pet the reminder is about, if any
+/// * `date` - Date the reminder is about
+///
+/// # Returns
+/// * `String` - Reminder text
+pub fn render_template(template: &str, pet: Option<&models::pet::Pet>, date: NaiveDate) -> String {
+    let rendered = template.replace("{date}", &date.format("%d/%m/%Y").to_string());
+
+    match pet {
+        Some(pet) => rendered.replace("{pet_name}", &pet.pet_name),
+        None => rendered,
+    }
+}
+
+/// Gets the reminder templates a user can pick, the embedded defaults
+/// first and then the ones the user saved.
+///
+/// # Arguments
+/// * `user_id` - ID of the user
+/// * `repo` - Repository instance for database operations
+///
+/// # Returns
+/// * `anyhow::Result<Vec<ReminderTemplateOption>>` - Templates to pick from
+pub async fn get_reminder_templates(
+    user_id: i64,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<Vec<ReminderTemplateOption>> {
+    let defaults = consts::DEFAULT_REMINDER_TEMPLATES
+        .iter()
+        .map(|(key, name, body)| ReminderTemplateOption {
+            value: key.to_string(),
+            name: name.to_string(),
+            body: body.to_string(),
+            is_default: true,
+        });
+    let saved = repo
+        .get_reminder_templates(user_id)
+        .await?
+        .into_iter()
+        .map(|template| ReminderTemplateOption {
+            value: template.id.to_string(),
+            name: template.name,
+            body: template.body,
+            is_default: false,
+        });
+
+    Ok(defaults.chain(saved).collect())
+}
+
+/// Gets the text of the template picked in the reminder form.
+///
+/// # Arguments
+/// * `template` - Key of an embedded default or id of a template of the user
+/// * `user_id` - ID of the user
+/// * `repo` - Repository instance for database operations
+///
+/// # Returns
+/// * `anyhow::Result<Option<String>>` - `None` if the user has no such template
+pub async fn get_template_body(
+    template: &str,
+    user_id: i64,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<Option<String>> {
+    if let Some((_, _, body)) = consts::DEFAULT_REMINDER_TEMPLATES
+        .iter()
+        .find(|(key, _, _)| *key == template)
+    {
+        return Ok(Some(body.to_string()));
+    }
+
+    Ok(get_reminder_templates(user_id, repo)
+        .await?
+        .into_iter()
+        .find(|option| option.value == template)
+        .map(|option| option.body))
+}
+
+/// Saves a reminder template of a user.
+///
+/// # Arguments
+/// * `user_id` - ID of the user
+/// * `name` - Name the template is picked by
+/// * `body` - Template text, may have `{pet_name}` and `{date}` placeholders
+/// * `repo` - Repository instance for database operations
+///
+/// # Errors
+/// Returns [`ReminderTemplateError`] if the name or the text are empty or too long.
+pub async fn save_reminder_template(
+    user_id: i64,
+    name: &str,
+    body: &str,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<i64> {
+    let (name, body) = (name.trim(), body.trim());
+    if name.is_empty() || body.is_empty() {
+        bail!(ReminderTemplateError::Empty);
+    }
+    if name.chars().count() > consts::REMINDER_TEMPLATE_MAX_NAME_LEN {
+        bail!(ReminderTemplateError::NameTooLong);
+    }
+    if body.chars().count() > consts::REMINDER_TEMPLATE_MAX_BODY_LEN {
+        bail!(ReminderTemplateError::BodyTooLong);
+    }
+
+    repo.insert_reminder_template(user_id, name, body).await
+}
+
+/// Removes a reminder template saved by a user.
+pub async fn delete_reminder_template(
+    template_id: i64,
+    user_id: i64,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<()> {
+    repo.delete_reminder_template(template_id, user_id).await
+}
+
+/// Builds the body of a reminder created from the form.
+///
+/// The typed body is kept as is, when it's empty the picked template is
+/// rendered for the pet and the reminder date instead.
+///
+/// # Arguments
+/// * `body` - Body typed by the user
+/// * `template` - Picked template, empty if none
+/// * `pet_external_id` - Pet the reminder is about, if any
+/// * `date` - Date of the reminder
+/// * `user_id` - ID of the user creating the reminder
+/// * `repo` - Repository instance for database operations
+///
+/// # Returns
+/// * `anyhow::Result<(String, Option<i64>)>` - Reminder body and pet id
+///
+/// # Errors
+/// Returns [`ReminderTemplateError::NotFound`] if the user has no such
+/// template and [`RepoError::NotFound`](crate::repo::errors::RepoError::NotFound)
+/// if the pet isn't the user's.
+pub async fn build_reminder_body(
+    body: &str,
+    template: &str,
+    pet_external_id: Option<uuid::Uuid>,
+    date: NaiveDate,
+    user_id: i64,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<(String, Option<i64>)> {
+    let pet = match pet_external_id {
+        Some(pet_external_id) => Some(
+            repo.get_pet_by_external_id_for_user(pet_external_id, user_id)
+                .await?,
+        ),
+        None => None,
+    };
+    let pet_id = pet.as_ref().map(|pet| pet.id);
+
+    if !body.trim().is_empty() || template.is_empty() {
+        return Ok((body.to_string(), pet_id));
+    }
+
+    let template = get_template_body(template, user_id, repo)
+        .await?
+        .ok_or(ReminderTemplateError::NotFound)?;
+
+    Ok((render_template(&template, pet.as_ref(), date), pet_id))
+}
+
+/// Gets the delivery status of the WhatsApp message of a reminder.
+///
+/// The first time, the message id is taken from the reminder execution and
+/// linked to the reminder, the statuses received by the webhook are kept
+/// by message id.
+///
+/// # Arguments
+/// * `reminder_id` - ID of the reminder
+/// * `user_id` - ID of the user who owns the reminder
+/// * `repo` - Repository instance for database operations
+/// * `notification_service` - Service that sent the reminder
+///
+/// # Returns
+/// * `anyhow::Result<Option<models::reminder::DeliveryStatus>>` - `None` if
+///   the reminder wasn't sent through WhatsApp yet
+pub async fn get_reminder_delivery_status(
+    reminder_id: i64,
+    user_id: i64,
+    repo: &repo::ImplAppRepo,
+    notification_service: &services::ImplNotificationService,
+) -> anyhow::Result<Option<models::reminder::DeliveryStatus>> {
+    if let Some(status) = repo
+        .get_reminder_delivery_status(reminder_id, user_id)
+        .await?
+    {
+        return Ok(Some(status));
+    }
+
+    let Some(execution_id) = repo.get_reminder_execution_id(user_id, reminder_id).await? else {
+        return Ok(None);
+    };
+    let Some(wamid) = notification_service
+        .get_reminder_message_id(&execution_id)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    repo.link_message_delivery_to_reminder(&wamid, reminder_id)
+        .await?;
+    repo.get_reminder_delivery_status(reminder_id, user_id)
+        .await
+}
+
+/// Single message summarizing the vaccine boosters due soon for all the
+/// pets of a user. Empty when nothing is due.
+///
+/// It is kept in one line, WhatsApp template parameters can't hold line breaks.
+#[derive(Debug, Display, Default, PartialEq)]
+pub struct DigestText(String);
+
+impl DigestText {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Vaccine booster digest of a user along with the vaccine records it
+/// announces.
+#[derive(Debug, Default, PartialEq)]
+pub struct VaccineDigest {
+    pub text: DigestText,
+    /// IDs of the announced `pet_health` records with their booster state, see
+    /// [`AppRepo::record_vaccine_digest_sent`](repo::AppRepo::record_vaccine_digest_sent)
+    pub announced: Vec<(i64, models::pet::BoosterStatus)>,
+}
+
+/// Builds the vaccine booster digest of a user.
+///
+/// Scans the latest vaccine with a booster interval of each of the user's
+/// pets and lists, grouped by pet, the boosters due in the next
+/// `within_days` days along with the overdue ones. A booster already
+/// announced before it was due is left out until it is overdue.
+///
+/// # Arguments
+/// * `user_id` - ID of the owner of the pets
+/// * `within_days` - Days ahead to look for due boosters
+/// * `tz` - Timezone of the owner, the digest is as of their local date
+/// * `repo` - Repository instance for database operations
+/// * `clock` - Source of the current time
+///
+/// # Returns
+/// * `anyhow::Result<VaccineDigest>` - The digest, empty if no booster is due
+pub async fn build_vaccine_digest(
+    user_id: i64,
+    within_days: u32,
+    tz: Tz,
+    repo: &repo::ImplAppRepo,
+    clock: &services::ImplClock,
+) -> anyhow::Result<VaccineDigest> {
+    let vaccines = repo.get_all_user_pets_vaccines(user_id).await?;
+
+    Ok(compose_vaccine_digest(
+        &vaccines,
+        clock.today_in(tz),
+        within_days,
+    ))
+}
+
+/// Composes the digest of the given vaccines as of `today`.
+fn compose_vaccine_digest(
+    vaccines: &[models::pet::PetVaccineBooster],
+    today: NaiveDate,
+    within_days: u32,
+) -> VaccineDigest {
+    let Some(limit) = today.checked_add_days(Days::new(within_days.into())) else {
+        return VaccineDigest::default();
+    };
+
+    let mut announced = Vec::new();
+    let mut due_by_pet: BTreeMap<(&str, i64), Vec<(NaiveDate, &str)>> = BTreeMap::new();
+    for vaccine in vaccines {
+        let Some(due) = vaccine
+            .created_at
+            .date()
+            .checked_add_months(Months::new(vaccine.booster_months))
+        else {
+            continue;
+        };
+
+        let status = match due < today {
+            true => models::pet::BoosterStatus::Overdue,
+            false => models::pet::BoosterStatus::Upcoming,
+        };
+        if status == models::pet::BoosterStatus::Upcoming && vaccine.announced_upcoming {
+            continue;
+        }
+
+        if due <= limit {
+            announced.push((vaccine.id, status));
+            due_by_pet
+                .entry((vaccine.pet_name.as_str(), vaccine.pet_id))
+                .or_default()
+                .push((due, vaccine.description.as_str()));
+        }
+    }
+
+    if due_by_pet.is_empty() {
+        return VaccineDigest::default();
+    }
+
+    let pets: Vec<String> = due_by_pet
+        .into_iter()
+        .map(|((pet_name, _), mut due)| {
+            due.sort();
+            let vaccines: Vec<String> = due
+                .into_iter()
+                .map(|(date, desc)| {
+                    let formatted = date.format("%d/%m/%Y");
+                    if date < today {
+                        format!("{desc} (vencido desde {formatted})")
+                    } else {
+                        format!("{desc} ({formatted})")
+                    }
+                })
+                .collect();
+            format!("{pet_name}: {}", vaccines.join(", "))
+        })
+        .collect();
+
+    VaccineDigest {
+        text: DigestText(format!(
+            "Refuerzos de vacunas pendientes. {}",
+            pets.join("; ")
+        )),
+        announced,
+    }
+}
+
+/// Whether `now` falls in the hour the weekly digest is sent in `tz`,
+/// [`VACCINE_DIGEST_HOUR`](consts::VACCINE_DIGEST_HOUR) of
+/// [`VACCINE_DIGEST_WEEKDAY`](consts::VACCINE_DIGEST_WEEKDAY).
+fn is_vaccine_digest_time(now: DateTime<Utc>, tz: Tz) -> bool {
+    let local = now.with_timezone(&tz);
+    local.weekday() == consts::VACCINE_DIGEST_WEEKDAY && local.hour() == consts::VACCINE_DIGEST_HOUR
+}
+
+/// Keeps the channels the user can be notified through, WhatsApp is dropped
+/// when they can't use it or have no verified phone.
+fn usable_channels(
+    user: &models::user_app::User,
+    channels: models::reminder::ReminderChannels,
+) -> models::reminder::ReminderChannels {
+    use models::reminder::ReminderNotificationType;
+
+    let can_use_whatsapp = user.capabilities().can_use_whatsapp && user.phone_reminder.is_some();
+
+    channels
+        .0
+        .into_iter()
+        .filter(|channel| *channel != ReminderNotificationType::WhatsApp || can_use_whatsapp)
+        .collect()
+}
+
+/// Channels the digest of an owner goes through: the ones of their latest
+/// reminder, or email when they have none. WhatsApp is dropped when the
+/// owner can't use it or has no verified phone.
+fn vaccine_digest_channels(
+    recipient: &models::reminder::VaccineDigestRecipient,
+) -> models::reminder::ReminderChannels {
+    use models::reminder::ReminderNotificationType;
+
+    usable_channels(
+        &recipient.user,
+        recipient
+            .channels
+            .clone()
+            .unwrap_or_else(|| [ReminderNotificationType::Email].into_iter().collect()),
+    )
+}
+
+/// Channels of a reminder the app schedules on its own, e.g. a vaccine
+/// booster: WhatsApp when the user can be reached there, email otherwise.
+pub fn automatic_reminder_channels(
+    user: &models::user_app::User,
+) -> models::reminder::ReminderChannels {
+    use models::reminder::ReminderNotificationType;
+
+    let channels = usable_channels(user, models::reminder::ReminderChannels::default());
+    if channels.is_empty() {
+        return [ReminderNotificationType::Email].into_iter().collect();
+    }
+
+    channels
+}
+
+/// Sends the vaccine booster digest to the owners whose local time is the
+/// digest time, meant to be triggered every hour by the scheduler.
+///
+/// Each owner gets it in the timezone and through the channels of their
+/// latest reminder, `default_tz` and email when they have none. The digest
+/// is started right away through the notification service and isn't stored
+/// as a reminder of the user. The announced vaccines are recorded with the
+/// state of their booster, so a booster is announced once while upcoming
+/// and once more when it becomes overdue. A failure for one owner is logged
+/// and doesn't stop the rest.
+///
+/// # Returns
+/// * `anyhow::Result<usize>` - Number of digests sent
+pub async fn send_vaccine_digests(
+    repo: &repo::ImplAppRepo,
+    notification_service: &services::ImplNotificationService,
+    clock: &services::ImplClock,
+    default_tz: Tz,
+) -> anyhow::Result<usize> {
+    let now = clock.now();
+
+    let mut sent = 0;
+    for recipient in repo.get_vaccine_digest_recipients().await? {
+        let user = &recipient.user;
+        if !user.can_access_service() {
+            continue;
+        }
+
+        let tz = recipient
+            .user_timezone
+            .as_deref()
+            .and_then(|tz| tz.parse::<Tz>().ok())
+            .unwrap_or(default_tz);
+        if !is_vaccine_digest_time(now, tz) {
+            continue;
+        }
+
+        let channels = vaccine_digest_channels(&recipient);
+        if channels.is_empty() {
+            continue;
+        }
+
+        let digest = match build_vaccine_digest(
+            user.id,
+            consts::VACCINE_DIGEST_WITHIN_DAYS,
+            tz,
+            repo,
+            clock,
+        )
+        .await
+        {
+            Ok(digest) if !digest.text.is_empty() => digest,
+            Ok(_) => continue,
+            Err(e) => {
+                logfire::error!(
+                    "vaccine digest couldn't be built for user {user_id}: {error}",
+                    user_id = user.id,
+                    error = e.to_string()
+                );
+                continue;
+            }
+        };
+
+        let info = ScheduleReminderInfo {
+            user_id: user.id,
+            channels,
+            phone_number: user.phone_reminder.clone(),
+            email: Some(user.email.clone()),
+            when: now.with_timezone(&tz),
+            body: digest.text.to_string(),
+            pet_id: None,
+        };
+
+        if let Err(e) = notification_service
+            .send_reminder_to_phone_number(&info)
+            .await
+        {
+            logfire::error!(
+                "vaccine digest couldn't be sent to user {user_id}: {error}",
+                user_id = user.id,
+                error = e.to_string()
+            );
+            continue;
+        }
+
+        sent += 1;
+        metric::incr_reminder_action_statds("vaccine_digest");
+
+        if let Err(e) = repo
+            .record_vaccine_digest_sent(&digest.announced, now)
+            .await
+        {
+            logfire::error!(
+                "vaccine digest of user {user_id} couldn't be recorded: {error}",
+                user_id = user.id,
+                error = e.to_string()
+            );
+        }
+    }
+
+    Ok(sent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::pet::BoosterStatus,
+        repo::{AppRepo, MockAppRepo},
+        services::{clock::FixedClock, test_utils::FakeNotificationService},
+    };
+    use chrono::TimeZone;
+
+    fn naive(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, min, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_preview_single_occurrence() {
+        let preview = next_occurrence_from(
+            naive(2030, 1, 15, 9, 30),
+            Tz::America__Mexico_City,
+            None,
+            Utc::now(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            preview.send_at,
+            Utc.with_ymd_and_hms(2030, 1, 15, 15, 30, 0).unwrap()
+        );
+        assert_eq!(preview.local, "15/01/2030 09:30 CST");
+    }
+
+    #[test]
+    fn test_preview_next_occurrence_with_recurrence() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 10, 0, 0, 0).unwrap();
+
+        let preview = next_occurrence_from(
+            naive(2024, 1, 1, 9, 0),
+            Tz::America__Mexico_City,
+            Some(Days::new(7)),
+            now,
+        )
+        .unwrap();
+
+        assert_eq!(
+            preview.send_at,
+            Utc.with_ymd_and_hms(2024, 1, 15, 15, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_preview_next_occurrence_far_in_the_past() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 10, 16, 0, 0).unwrap();
+
+        // decades of daily occurrences are skipped at once
+        let daily = next_occurrence_from(
+            naive(1990, 1, 1, 9, 0),
+            Tz::America__Mexico_City,
+            Some(Days::new(1)),
+            now,
+        )
+        .unwrap();
+        assert_eq!(daily.local, "11/03/2024 09:00 CST");
+
+        // same day, still ahead of now
+        let ahead = next_occurrence_from(
+            naive(2024, 3, 3, 11, 0),
+            Tz::America__Mexico_City,
+            Some(Days::new(7)),
+            now,
+        )
+        .unwrap();
+        assert_eq!(ahead.local, "10/03/2024 11:00 CST");
+    }
+
+    #[test]
+    fn test_preview_dst_gap_and_overlap() {
+        // Mexico City still had DST in 2022: 2022-04-03 02:30 didn't exist
+        // and 2022-10-30 01:30 happened twice
+        let tz = Tz::America__Mexico_City;
+        let now = Utc.with_ymd_and_hms(2022, 1, 1, 0, 0, 0).unwrap();
+
+        let gap = next_occurrence_from(naive(2022, 4, 3, 2, 30), tz, None, now).unwrap();
+        assert_eq!(gap.local, "03/04/2022 03:00 CDT");
+        assert!(gap.adjustment.is_some());
+
+        let overlap = next_occurrence_from(naive(2022, 10, 30, 1, 30), tz, None, now).unwrap();
+        assert_eq!(overlap.local, "30/10/2022 01:30 CDT");
+        assert!(overlap.adjustment.is_some());
+    }
+
+    #[test]
+    fn test_resolve_local_datetime_single() {
+        let (send_at, adjustment) =
+            resolve_local_datetime(naive(2022, 6, 1, 9, 0), Tz::America__Mexico_City);
+
+        // summer time, UTC-5
+        assert_eq!(send_at, Utc.with_ymd_and_hms(2022, 6, 1, 14, 0, 0).unwrap());
+        assert_eq!(adjustment, None);
+    }
+
+    #[test]
+    fn test_resolve_local_datetime_gap_shifts_forward() {
+        // 2022-04-03 clocks jumped from 02:00 to 03:00 in Mexico City
+        let (send_at, adjustment) =
+            resolve_local_datetime(naive(2022, 4, 3, 2, 30), Tz::America__Mexico_City);
+
+        // 03:00 CDT (UTC-5)
+        assert_eq!(send_at, Utc.with_ymd_and_hms(2022, 4, 3, 8, 0, 0).unwrap());
+        assert_eq!(
+            adjustment,
+            Some(DstAdjustment::ShiftedForward {
+                requested: naive(2022, 4, 3, 2, 30),
+                shifted_to: naive(2022, 4, 3, 3, 0),
+            })
+        );
+    }
+
+    #[test]
+    fn test_resolve_local_datetime_overlap_picks_earlier() {
+        // 2022-10-30 clocks went back from 02:00 to 01:00 in Mexico City
+        let (send_at, adjustment) =
+            resolve_local_datetime(naive(2022, 10, 30, 1, 30), Tz::America__Mexico_City);
+
+        // first 01:30 is still CDT (UTC-5)
+        assert_eq!(
+            send_at,
+            Utc.with_ymd_and_hms(2022, 10, 30, 6, 30, 0).unwrap()
+        );
+        assert_eq!(
+            adjustment,
+            Some(DstAdjustment::EarliestOfAmbiguous {
+                requested: naive(2022, 10, 30, 1, 30),
+            })
+        );
+    }
+
+    fn vaccine(
+        id: i64,
+        pet_id: i64,
+        pet_name: &str,
+        desc: &str,
+        applied: NaiveDate,
+        booster_months: u32,
+    ) -> models::pet::PetVaccineBooster {
+        models::pet::PetVaccineBooster {
+            id,
+            pet_id,
+            pet_name: pet_name.to_string(),
+            description: desc.to_string(),
+            created_at: applied.and_time(chrono::NaiveTime::default()),
+            booster_months,
+            announced_upcoming: false,
+        }
+    }
+
+    fn repo_with_vaccines(vaccines: Vec<models::pet::PetVaccineBooster>) -> repo::ImplAppRepo {
+        let mut mock_repo = MockAppRepo::new();
+        mock_repo
+            .expect_get_all_user_pets_vaccines()
+            .withf(|user_id| *user_id == 7)
+            .times(1)
+            .returning(move |_| {
+                let vaccines = vaccines.clone();
+                Box::pin(async move { Ok(vaccines) })
+            });
+
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        repo
+    }
+
+    #[ntex::test]
+    async fn test_vaccine_digest_lists_multiple_pets() {
+        let clock: services::ImplClock = Box::new(FixedClock::at("2024-06-15T12:00:00Z"));
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        // due this month, overdue, due this month and not due yet
+        let repo = repo_with_vaccines(vec![
+            vaccine(10, 1, "Firulais", "Rabia", date(2023, 6, 25), 12),
+            vaccine(11, 1, "Firulais", "Parvovirus", date(2023, 11, 15), 6),
+            vaccine(12, 2, "Michi", "Triple felina", date(2023, 7, 5), 12),
+            vaccine(13, 2, "Michi", "Leucemia", date(2024, 5, 15), 12),
+        ]);
+
+        let digest = build_vaccine_digest(7, 30, Tz::America__Mexico_City, &repo, &clock)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            digest.announced,
+            vec![
+                (10, BoosterStatus::Upcoming),
+                (11, BoosterStatus::Overdue),
+                (12, BoosterStatus::Upcoming)
+            ]
+        );
+        assert_eq!(
+            digest.text.to_string(),
+            "Refuerzos de vacunas pendientes. Firulais: Parvovirus (vencido desde 15/05/2024), \
+             Rabia (25/06/2024); Michi: Triple felina (05/07/2024)"
+        );
+    }
+
+    #[ntex::test]
+    async fn test_vaccine_digest_empty_when_nothing_due() {
+        let clock: services::ImplClock = Box::new(FixedClock::at("2024-06-15T12:00:00Z"));
+        let repo = repo_with_vaccines(vec![vaccine(
+            10,
+            1,
+            "Firulais",
+            "Rabia",
+            NaiveDate::from_ymd_opt(2024, 4, 15).unwrap(),
+            12,
+        )]);
+
+        let tz = Tz::America__Mexico_City;
+        let digest = build_vaccine_digest(7, 30, tz, &repo, &clock)
+            .await
+            .unwrap();
+        assert!(digest.text.is_empty());
+        assert!(digest.announced.is_empty());
+
+        let digest = build_vaccine_digest(7, 30, tz, &repo_with_vaccines(vec![]), &clock)
+            .await
+            .unwrap();
+        assert!(digest.text.is_empty());
+    }
+
+    #[ntex::test]
+    async fn test_vaccine_digest_announces_overdue_booster_once_more() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let rabies = models::pet::PetVaccineBooster {
+            announced_upcoming: true,
+            ..vaccine(10, 1, "Firulais", "Rabia", date(2023, 6, 25), 12)
+        };
+
+        // already announced while upcoming
+        let clock: services::ImplClock = Box::new(FixedClock::at("2024-06-15T12:00:00Z"));
+        let repo = repo_with_vaccines(vec![rabies.clone()]);
+        let digest = build_vaccine_digest(7, 30, Tz::America__Mexico_City, &repo, &clock)
+            .await
+            .unwrap();
+        assert!(digest.text.is_empty());
+
+        // announced again once overdue
+        let clock: services::ImplClock = Box::new(FixedClock::at("2024-07-01T12:00:00Z"));
+        let repo = repo_with_vaccines(vec![rabies]);
+        let digest = build_vaccine_digest(7, 30, Tz::America__Mexico_City, &repo, &clock)
+            .await
+            .unwrap();
+        assert_eq!(digest.announced, vec![(10, BoosterStatus::Overdue)]);
+        assert_eq!(
+            digest.text.to_string(),
+            "Refuerzos de vacunas pendientes. Firulais: Rabia (vencido desde 25/06/2024)"
+        );
+    }
+
+    #[test]
+    fn test_is_vaccine_digest_time_uses_the_timezone() {
+        // Monday 2024-01-15 09:00 CST (UTC-6)
+        let now = Utc.with_ymd_and_hms(2024, 1, 15, 15, 30, 0).unwrap();
+        assert!(is_vaccine_digest_time(now, Tz::America__Mexico_City));
+        assert!(!is_vaccine_digest_time(now, Tz::Europe__Madrid));
+
+        // Monday 09:00 in Madrid (UTC+1)
+        let now = Utc.with_ymd_and_hms(2024, 1, 15, 8, 0, 0).unwrap();
+        assert!(is_vaccine_digest_time(now, Tz::Europe__Madrid));
+        assert!(!is_vaccine_digest_time(now, Tz::America__Mexico_City));
+    }
+
+    fn digest_recipient(
+        channels: Option<models::reminder::ReminderChannels>,
+        user_timezone: Option<&str>,
+    ) -> models::reminder::VaccineDigestRecipient {
+        models::reminder::VaccineDigestRecipient {
+            user: models::user_app::User {
+                id: 7,
+                ..models::user_app::User::create_default_from_email("user7@example.com")
+            },
+            channels,
+            user_timezone: user_timezone.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_vaccine_digest_channels_follow_latest_reminder() {
+        use models::reminder::ReminderNotificationType::{Email, WhatsApp};
+
+        // owners without reminders get it by email
+        let recipient = digest_recipient(None, None);
+        assert_eq!(
+            vaccine_digest_channels(&recipient),
+            [Email].into_iter().collect()
+        );
+
+        // WhatsApp needs a verified phone
+        let mut recipient = digest_recipient(Some([WhatsApp].into_iter().collect()), None);
+        assert!(vaccine_digest_channels(&recipient).is_empty());
+
+        recipient.user.phone_reminder = Some("+525512345678".into());
+        assert_eq!(
+            vaccine_digest_channels(&recipient),
+            [WhatsApp].into_iter().collect()
+        );
+    }
+
+    #[ntex::test]
+    async fn test_send_vaccine_digests_records_sent_vaccines() {
+        // Monday 2024-01-15 09:00 CST (UTC-6)
+        let clock: services::ImplClock = Box::new(FixedClock::at("2024-01-15T15:00:00Z"));
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        let mut mock_repo = MockAppRepo::new();
+        mock_repo
+            .expect_get_vaccine_digest_recipients()
+            .times(1)
+            .returning(|| {
+                Box::pin(async {
+                    Ok(vec![
+                        digest_recipient(None, Some("America/Mexico_City")),
+                        // not 09:00 in Madrid yet
+                        models::reminder::VaccineDigestRecipient {
+                            user: models::user_app::User {
+                                id: 8,
+                                ..digest_recipient(None, None).user
+                            },
+                            ..digest_recipient(None, Some("Europe/Madrid"))
+                        },
+                    ])
+                })
+            });
+        mock_repo
+            .expect_get_all_user_pets_vaccines()
+            .withf(|user_id| *user_id == 7)
+            .times(1)
+            .returning(move |_| {
+                let vaccines = vec![vaccine(10, 1, "Firulais", "Rabia", date(2023, 1, 10), 12)];
+                Box::pin(async move { Ok(vaccines) })
+            });
+        mock_repo
+            .expect_record_vaccine_digest_sent()
+            .withf(|announced, _| announced == [(10, BoosterStatus::Overdue)])
+            .times(1)
+            .returning(|_, _| Box::pin(async { Ok(()) }));
+        let repo: repo::ImplAppRepo = Box::new(mock_repo);
+
+        let notification = FakeNotificationService::default();
+        let notification_service: services::ImplNotificationService =
+            Box::new(notification.clone());
+
+        let sent = send_vaccine_digests(
+            &repo,
+            &notification_service,
+            &clock,
+            Tz::America__Mexico_City,
+        )
+        .await
+        .unwrap();
+        assert_eq!(sent, 1);
+
+        assert_eq!(
+            *notification.calls.lock().unwrap(),
+            vec![
+                "send Refuerzos de vacunas pendientes. Firulais: Rabia (vencido desde 10/01/2024)"
+                    .to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_reminder_time_boundaries() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 10, 12, 0, 0).unwrap();
+        let min_lead = TimeDelta::minutes(5);
+        let max_horizon = TimeDelta::days(365);
+        let validate = |when| validate_reminder_time(when, now, min_lead, max_horizon);
+
+        assert_eq!(
+            validate(now - TimeDelta::minutes(1)),
+            Err(ReminderTimeError::InPast)
+        );
+        assert_eq!(validate(now), Err(ReminderTimeError::InPast));
+        assert_eq!(
+            validate(now + TimeDelta::minutes(1)),
+            Err(ReminderTimeError::TooSoon { min_minutes: 5 })
+        );
+        assert_eq!(
+            validate(now + min_lead - TimeDelta::seconds(1)),
+            Err(ReminderTimeError::TooSoon { min_minutes: 5 })
+        );
+        assert_eq!(validate(now + min_lead), Ok(()));
+        assert_eq!(validate(now + max_horizon), Ok(()));
+        assert_eq!(
+            validate(now + max_horizon + TimeDelta::seconds(1)),
+            Err(ReminderTimeError::TooFar { max_days: 365 })
+        );
+    }
+
+    #[test]
+    fn test_default_reminder_window_fits_yearly_booster() {
+        let (min_lead, max_horizon) = reminder_window();
+        let now = Utc::now();
+        let booster = now.checked_add_months(Months::new(12)).unwrap() + TimeDelta::hours(23);
+
+        assert_eq!(
+            validate_reminder_time(booster, now, min_lead, max_horizon),
+            Ok(())
+        );
+    }
+
+    #[ntex::test]
+    async fn test_schedule_reminder_lead_time_with_fixed_clock() {
+        let now = Utc.with_ymd_and_hms(2024, 6, 15, 12, 0, 0).unwrap();
+        let clock: services::ImplClock = Box::new(FixedClock(now));
+        let notification_service: services::ImplNotificationService =
+            Box::<FakeNotificationService>::default();
+
+        let mut mock_repo = MockAppRepo::new();
+        mock_repo
+            .expect_insert_user_remider()
+            .withf(move |reminder| reminder.created_at == now)
+            .times(1)
+            .returning(|_| Box::pin(async { Ok(1) }));
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+
+        let reminder_at = |when: DateTime<Utc>| ScheduleReminderInfo {
+            user_id: 1,
+            channels: models::reminder::ReminderChannels::default(),
+            phone_number: Some("5512345678".to_string()),
+            email: Some("test@example.com".to_string()),
+            when: when.with_timezone(&Tz::America__Mexico_City),
+            body: "Paseo".to_string(),
+            pet_id: None,
+        };
+
+        // a minute after the clock is too soon, tomorrow is fine
+        let result = schedule_reminder(
+            reminder_at(now + TimeDelta::minutes(1)),
+            &repo,
+            &notification_service,
+            &clock,
+        )
+        .await;
+        assert!(result.is_err_and(|e| {
+            e.downcast_ref::<ReminderTimeError>()
+                == Some(&ReminderTimeError::TooSoon { min_minutes: 5 })
+        }));
+
+        schedule_reminder(
+            reminder_at(now + TimeDelta::days(1)),
+            &repo,
+            &notification_service,
+            &clock,
+        )
+        .await
+        .unwrap();
+    }
+
+    #[ntex::test]
+    async fn test_reminder_delivery_status_links_message_once() {
+        use models::reminder::DeliveryStatus;
+        use std::sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        };
+
+        let linked = Arc::new(AtomicBool::new(false));
+        let mut mock_repo = MockAppRepo::new();
+        let is_linked = linked.clone();
+        mock_repo
+            .expect_get_reminder_delivery_status()
+            .returning(move |_, _| {
+                let status = is_linked
+                    .load(Ordering::SeqCst)
+                    .then_some(DeliveryStatus::Delivered);
+                Box::pin(async move { Ok(status) })
+            });
+        mock_repo
+            .expect_get_reminder_execution_id()
+            .times(1)
+            .returning(|_, _| Box::pin(async { Ok(Some("execution-id".to_string())) }));
+        let set_linked = linked.clone();
+        mock_repo
+            .expect_link_message_delivery_to_reminder()
+            .withf(|wamid, reminder_id| wamid == "wamid.1" && *reminder_id == 4)
+            .times(1)
+            .returning(move |_, _| {
+                set_linked.store(true, Ordering::SeqCst);
+                Box::pin(async { Ok(()) })
+            });
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let notification_service: services::ImplNotificationService =
+            Box::new(FakeNotificationService {
+                message_id: Some("wamid.1"),
+                ..Default::default()
+            });
+
+        for _ in 0..2 {
+            assert_eq!(
+                get_reminder_delivery_status(4, 1, &repo, &notification_service)
+                    .await
+                    .unwrap(),
+                Some(DeliveryStatus::Delivered)
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_template_fills_placeholders() {
+        let pet = models::pet::Pet {
+            pet_name: "Firulais".into(),
+            ..Default::default()
+        };
+        let date = NaiveDate::from_ymd_opt(2030, 3, 9).unwrap();
+
+        assert_eq!(
+            render_template("Vacuna de {pet_name} el {date}", Some(&pet), date),
+            "Vacuna de Firulais el 09/03/2030"
+        );
+        assert_eq!(
+            render_template("{pet_name}: {date}, {pet_name}", Some(&pet), date),
+            "Firulais: 09/03/2030, Firulais"
+        );
+    }
+
+    #[test]
+    fn test_render_template_keeps_missing_placeholders() {
+        let date = NaiveDate::from_ymd_opt(2030, 3, 9).unwrap();
+
+        assert_eq!(
+            render_template("Vacuna de {pet_name} el {date}", None, date),
+            "Vacuna de {pet_name} el 09/03/2030"
+        );
+        assert_eq!(
+            render_template("Llevar a {vet} sin fecha", None, date),
+            "Llevar a {vet} sin fecha"
+        );
+    }
+
+    #[ntex::test]
+    async fn test_build_reminder_body_from_template() {
+        let mut mock_repo = MockAppRepo::new();
+        mock_repo
+            .expect_get_pet_by_external_id_for_user()
+            .returning(|_, _| {
+                Box::pin(async {
+                    Ok(models::pet::Pet {
+                        id: 7,
+                        pet_name: "Firulais".into(),
+                        ..Default::default()
+                    })
+                })
+            });
+        mock_repo.expect_get_reminder_templates().returning(|_| {
+            Box::pin(async {
+                Ok(vec![models::reminder::ReminderTemplate {
+                    id: 3,
+                    name: "baño".into(),
+                    body: "Bañar a {pet_name}".into(),
+                }])
+            })
+        });
+        let repo: Box<dyn AppRepo> = Box::new(mock_repo);
+        let date = NaiveDate::from_ymd_opt(2030, 3, 9).unwrap();
+        let pet_external_id = Some(uuid::Uuid::new_v4());
+
+        assert_eq!(
+            build_reminder_body("", "vaccine", pet_external_id, date, 1, &repo)
+                .await
+                .unwrap(),
+            (
+                "Toca la vacuna de Firulais el 09/03/2030".to_string(),
+                Some(7)
+            )
+        );
+        assert_eq!(
+            build_reminder_body("", "3", pet_external_id, date, 1, &repo)
+                .await
+                .unwrap(),
+            ("Bañar a Firulais".to_string(), Some(7))
+        );
+        // a typed body wins over the template
+        assert_eq!(
+            build_reminder_body("Comprar croquetas", "vaccine", None, date, 1, &repo)
+                .await
+                .unwrap(),
+            ("Comprar croquetas".to_string(), None)
+        );
+        assert!(
+            build_reminder_body("", "4", None, date, 1, &repo)
+                .await
+                .is_err_and(|e| e.downcast_ref::<ReminderTemplateError>()
+                    == Some(&ReminderTemplateError::NotFound))
+        );
+    }
+
+    fn reschedule_user() -> models::user_app::User {
+        models::user_app::User {
+            id: 7,
+            phone_reminder: Some("5512345678".to_string()),
+            ..models::user_app::User::create_default_from_email("test@example.com")
+        }
+    }
+
+    /// Notification service scheduling the rescheduled reminder as `new-execution`
+    fn reschedule_notification() -> FakeNotificationService {
+        FakeNotificationService {
+            execution_id: "new-execution",
+            ..Default::default()
+        }
+    }
+
+    /// Repo returning a reminder sent at 2024-06-16 and expecting
+    /// `updates` schedule updates, failing them if `fail_update`
+    fn reschedule_repo(updates: usize, fail_update: bool) -> repo::ImplAppRepo {
+        let mut mock_repo = MockAppRepo::new();
+        mock_repo
+            .expect_get_user_reminder()
+            .with(mockall::predicate::eq(1), mockall::predicate::eq(7))
+            .returning(|_, _| {
+                Box::pin(async {
+                    Ok(models::reminder::Reminder {
+                        id: 1,
+                        user_app_id: 7,
+                        body: "Vacuna de Firulais".to_string(),
+                        execution_id: "old-execution".to_string(),
+                        send_at: Utc.with_ymd_and_hms(2024, 6, 16, 15, 0, 0).unwrap(),
+                        user_timezone: "America/Mexico_City".to_string(),
+                        ..Default::default()
+                    })
+                })
+            });
+        mock_repo
+            .expect_update_reminder_schedule()
+            .times(updates)
+            .returning(move |_, _, _, _, _| {
+                Box::pin(async move {
+                    match fail_update {
+                        true => anyhow::bail!("database is locked"),
+                        false => Ok(()),
+                    }
+                })
+            });
+
+        Box::new(mock_repo)
+    }
+
+    async fn run_reschedule(
+        repo: &repo::ImplAppRepo,
+        notification_service: FakeNotificationService,
+        new_when: NaiveDateTime,
+    ) -> (anyhow::Result<Option<DstAdjustment>>, Vec<String>) {
+        let calls = notification_service.calls.clone();
+        let notification_service: services::ImplNotificationService =
+            Box::new(notification_service);
+        let clock: services::ImplClock = Box::new(FixedClock(
+            Utc.with_ymd_and_hms(2024, 6, 15, 12, 0, 0).unwrap(),
+        ));
+
+        let result = reschedule_reminder(
+            1,
+            &reschedule_user(),
+            new_when,
+            Tz::America__Mexico_City,
+            repo,
+            &notification_service,
+            &clock,
+        )
+        .await;
+
+        let calls = calls.lock().unwrap().clone();
+        (result, calls)
+    }
+
+    #[ntex::test]
+    async fn test_reschedule_reminder_keeps_id_and_body() {
+        let mut mock_repo = MockAppRepo::new();
+        let mut seq = mockall::Sequence::new();
+        mock_repo
+            .expect_get_user_reminder()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _| {
+                Box::pin(async {
+                    Ok(models::reminder::Reminder {
+                        id: 1,
+                        user_app_id: 7,
+                        body: "Vacuna de Firulais".to_string(),
+                        execution_id: "old-execution".to_string(),
+                        pet_id: Some(3),
+                        ..Default::default()
+                    })
+                })
+            });
+        mock_repo
+            .expect_update_reminder_schedule()
+            .withf(
+                |reminder_id, user_id, send_at, user_timezone, execution_id| {
+                    *reminder_id == 1
+                        && *user_id == 7
+                        && *send_at == Utc.with_ymd_and_hms(2024, 6, 20, 15, 0, 0).unwrap()
+                        && user_timezone == "America/Mexico_City"
+                        && execution_id == "new-execution"
+                },
+            )
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _, _, _, _| Box::pin(async { Ok(()) }));
+        let repo: repo::ImplAppRepo = Box::new(mock_repo);
+
+        let (result, calls) =
+            run_reschedule(&repo, reschedule_notification(), naive(2024, 6, 20, 9, 0)).await;
+
+        assert!(result.is_ok_and(|adjustment| adjustment.is_none()));
+        // the new execution exists before the old one is cancelled
+        assert_eq!(calls, ["send Vacuna de Firulais", "cancel old-execution"]);
+    }
+
+    #[ntex::test]
+    async fn test_reschedule_reminder_failed_schedule_keeps_old_execution() {
+        let repo = reschedule_repo(0, false);
+
+        let (result, calls) = run_reschedule(
+            &repo,
+            FakeNotificationService {
+                fail_send: true,
+                ..reschedule_notification()
+            },
+            naive(2024, 6, 20, 9, 0),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls, ["send Vacuna de Firulais"]);
+    }
+
+    #[ntex::test]
+    async fn test_reschedule_reminder_failed_update_cancels_new_execution() {
+        let repo = reschedule_repo(1, true);
+
+        let (result, calls) =
+            run_reschedule(&repo, reschedule_notification(), naive(2024, 6, 20, 9, 0)).await;
+
+        assert!(result.is_err());
+        assert_eq!(calls, ["send Vacuna de Firulais", "cancel new-execution"]);
+    }
+
+    #[ntex::test]
+    async fn test_reschedule_reminder_failed_cancel_restores_old_execution() {
+        let mut mock_repo = MockAppRepo::new();
+        let mut seq = mockall::Sequence::new();
+        let old_send_at = Utc.with_ymd_and_hms(2024, 6, 16, 15, 0, 0).unwrap();
+        mock_repo.expect_get_user_reminder().returning(move |_, _| {
+            Box::pin(async move {
+                Ok(models::reminder::Reminder {
+                    id: 1,
+                    user_app_id: 7,
+                    body: "Vacuna de Firulais".to_string(),
+                    execution_id: "old-execution".to_string(),
+                    send_at: old_send_at,
+                    user_timezone: "America/Mexico_City".to_string(),
+                    ..Default::default()
+                })
+            })
+        });
+        for expected_execution_id in ["new-execution", "old-execution"] {
+            mock_repo
+                .expect_update_reminder_schedule()
+                .withf(move |_, _, _, _, execution_id| execution_id == expected_execution_id)
+                .times(1)
+                .in_sequence(&mut seq)
+                .returning(|_, _, _, _, _| Box::pin(async { Ok(()) }));
+        }
+        let repo: repo::ImplAppRepo = Box::new(mock_repo);
+
+        let (result, calls) = run_reschedule(
+            &repo,
+            FakeNotificationService {
+                fail_cancel_of: Some("old-execution"),
+                ..reschedule_notification()
+            },
+            naive(2024, 6, 20, 9, 0),
+        )
+        .await;
+
+        assert!(result.is_err());
+        // only the old execution is left, as saved back
+        assert_eq!(
+            calls,
+            [
+                "send Vacuna de Firulais",
+                "cancel old-execution",
+                "cancel new-execution"
+            ]
+        );
+    }
+
+    #[ntex::test]
+    async fn test_reschedule_reminder_rejects_invalid_time_before_scheduling() {
+        let repo = reschedule_repo(0, false);
+
+        let (result, calls) =
+            run_reschedule(&repo, reschedule_notification(), naive(2024, 6, 1, 9, 0)).await;
+
+        assert!(result.is_err_and(|e| {
+            e.downcast_ref::<ReminderTimeError>() == Some(&ReminderTimeError::InPast)
+        }));
+        assert!(calls.is_empty());
+    }
+}
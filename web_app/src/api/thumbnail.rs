@@ -0,0 +1,294 @@
+//! # Pet Thumbnails Module
+//!
+//! QR cards and Apple Wallet passes only need a small PNG of the pet picture;
+//! passes also get an @2x variant for Retina displays.
+//! Resized thumbnails are kept in an in-memory LRU cache, keyed by the pet
+//! external id and the thumbnail size, so the full picture is fetched from
+//! storage and resized only on a cache miss.
+
+use crate::{consts, repo, services};
+use anyhow::Context;
+use image::ImageEncoder;
+use std::{
+    collections::VecDeque,
+    io::Cursor,
+    sync::{Mutex, MutexGuard},
+};
+use uuid::Uuid;
+
+/// Cache entry key: pet external id and thumbnail size in pixels
+type ThumbnailKey = (Uuid, u32);
+
+/// LRU cache of resized pet pictures encoded as PNG.
+///
+/// The most recently used entry is kept at the front; the entry at the back
+/// is evicted when the cache is full.
+pub struct ThumbnailCache {
+    capacity: usize,
+    entries: Mutex<VecDeque<(ThumbnailKey, Vec<u8>)>>,
+}
+
+impl ThumbnailCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// A panic while holding the lock can't leave the entries inconsistent
+    fn lock_entries(&self) -> MutexGuard<'_, VecDeque<(ThumbnailKey, Vec<u8>)>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Returns the cached thumbnail and marks it as the most recently used.
+    pub fn get(&self, pet_external_id: Uuid, size: u32) -> Option<Vec<u8>> {
+        let mut entries = self.lock_entries();
+        let position = entries
+            .iter()
+            .position(|(key, _)| *key == (pet_external_id, size))?;
+        let entry = entries.remove(position)?;
+        let thumbnail = entry.1.clone();
+        entries.push_front(entry);
+
+        Some(thumbnail)
+    }
+
+    /// Stores a thumbnail, evicting the least recently used one when full.
+    pub fn insert(&self, pet_external_id: Uuid, size: u32, thumbnail: Vec<u8>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.lock_entries();
+        entries.retain(|(key, _)| *key != (pet_external_id, size));
+        entries.truncate(self.capacity - 1);
+        entries.push_front(((pet_external_id, size), thumbnail));
+    }
+
+    /// Removes every cached size of a pet's thumbnail, used when its picture changes.
+    pub fn invalidate(&self, pet_external_id: Uuid) {
+        self.lock_entries()
+            .retain(|((external_id, _), _)| *external_id != pet_external_id);
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock_entries().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Resizes a picture to a square PNG of `size`x`size` pixels times `scale`,
+/// e.g. `scale` 2 builds the @2x variant used by Retina displays.
+///
+/// Uses Lanczos3 for high-quality downsampling and the best PNG compression
+/// for the smallest file size.
+pub fn build_png_thumbnail(image_bytes: &[u8], size: u32, scale: u32) -> anyhow::Result<Vec<u8>> {
+    let img = image::load_from_memory(image_bytes).context("Failed to load pet image")?;
+
+    resize_to_png(&img, size * scale)
+}
+
+/// Resizes an already decoded picture to a `size_px`x`size_px` square PNG.
+fn resize_to_png(img: &image::DynamicImage, size_px: u32) -> anyhow::Result<Vec<u8>> {
+    let resized = img.resize_to_fill(size_px, size_px, image::imageops::FilterType::Lanczos3);
+
+    let mut png_bytes = Vec::new();
+    let encoder = image::codecs::png::PngEncoder::new_with_quality(
+        Cursor::new(&mut png_bytes),
+        image::codecs::png::CompressionType::Best,
+        image::codecs::png::FilterType::Adaptive,
+    );
+    encoder
+        .write_image(
+            resized.as_bytes(),
+            resized.width(),
+            resized.height(),
+            resized.color().into(),
+        )
+        .context("Failed to encode image as optimized PNG")?;
+
+    Ok(png_bytes)
+}
+
+/// Apple Wallet thumbnails of a pet picture.
+pub struct PassThumbnails {
+    /// Thumbnail at [`PKPASS_THUMBNAIL_SIZE_PX`](consts::PKPASS_THUMBNAIL_SIZE_PX)
+    pub standard: Vec<u8>,
+    /// @2x thumbnail, `None` when the picture is too small to fill it
+    pub retina: Option<Vec<u8>>,
+}
+
+/// Builds the standard and @2x pass thumbnails of a picture.
+///
+/// The picture is decoded once and the @2x variant is skipped when the
+/// picture is smaller than its size, since upscaling only adds bytes;
+/// Wallet then falls back to the standard thumbnail.
+pub fn build_pass_thumbnails(image_bytes: &[u8]) -> anyhow::Result<PassThumbnails> {
+    let img = image::load_from_memory(image_bytes).context("Failed to load pet image")?;
+    let retina_size = consts::PKPASS_THUMBNAIL_SIZE_PX * consts::PKPASS_RETINA_SCALE;
+
+    Ok(PassThumbnails {
+        standard: resize_to_png(&img, consts::PKPASS_THUMBNAIL_SIZE_PX)?,
+        retina: (img.width().min(img.height()) >= retina_size)
+            .then(|| resize_to_png(&img, retina_size))
+            .transpose()?,
+    })
+}
+
+/// Retrieves the PNG thumbnail of a pet's picture, using the cache first.
+///
+/// # Arguments
+/// * `pet_external_id` - Public UUID of the pet
+/// * `size` - Width and height of the thumbnail in pixels
+/// * `repo` - Repository instance for database operations
+/// * `storage_service` - Service for file retrieval
+/// * `thumbnail_cache` - Cache of already resized thumbnails
+///
+/// # Returns
+/// * `anyhow::Result<Option<Vec<u8>>>` - PNG bytes if the pet has a picture
+pub async fn get_pet_thumbnail(
+    pet_external_id: Uuid,
+    size: u32,
+    repo: &repo::ImplAppRepo,
+    storage_service: &services::ImplStorageService,
+    thumbnail_cache: &ThumbnailCache,
+) -> anyhow::Result<Option<Vec<u8>>> {
+    if let Some(thumbnail) = thumbnail_cache.get(pet_external_id, size) {
+        return Ok(Some(thumbnail));
+    }
+
+    let Some(pic) = crate::api::pet::get_public_pic(pet_external_id, repo, storage_service).await?
+    else {
+        return Ok(None);
+    };
+
+    let thumbnail = build_png_thumbnail(&pic.body, size, 1)?;
+    thumbnail_cache.insert(pet_external_id, size, thumbnail.clone());
+
+    Ok(Some(thumbnail))
+}
+
+/// Retrieves the Apple Wallet thumbnails of a pet's picture, using the cache first.
+///
+/// Both sizes are cached; a picture too small for the @2x thumbnail is
+/// fetched again on each pass download.
+///
+/// # Returns
+/// * `anyhow::Result<Option<PassThumbnails>>` - Thumbnails if the pet has a picture
+pub async fn get_pet_pass_thumbnails(
+    pet_external_id: Uuid,
+    repo: &repo::ImplAppRepo,
+    storage_service: &services::ImplStorageService,
+    thumbnail_cache: &ThumbnailCache,
+) -> anyhow::Result<Option<PassThumbnails>> {
+    let standard_size = consts::PKPASS_THUMBNAIL_SIZE_PX;
+    let retina_size = standard_size * consts::PKPASS_RETINA_SCALE;
+
+    if let (Some(standard), Some(retina)) = (
+        thumbnail_cache.get(pet_external_id, standard_size),
+        thumbnail_cache.get(pet_external_id, retina_size),
+    ) {
+        return Ok(Some(PassThumbnails {
+            standard,
+            retina: Some(retina),
+        }));
+    }
+
+    let Some(pic) = crate::api::pet::get_public_pic(pet_external_id, repo, storage_service).await?
+    else {
+        return Ok(None);
+    };
+
+    let thumbnails = build_pass_thumbnails(&pic.body)?;
+    thumbnail_cache.insert(pet_external_id, standard_size, thumbnails.standard.clone());
+    if let Some(retina) = &thumbnails.retina {
+        thumbnail_cache.insert(pet_external_id, retina_size, retina.clone());
+    }
+
+    Ok(Some(thumbnails))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thumbnail_cache_evicts_least_recently_used() {
+        let cache = ThumbnailCache::new(2);
+        let (first, second, third) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        cache.insert(first, 160, vec![1]);
+        cache.insert(second, 160, vec![2]);
+        // first becomes the most recently used, second gets evicted
+        assert_eq!(cache.get(first, 160), Some(vec![1]));
+        cache.insert(third, 160, vec![3]);
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(second, 160), None);
+        assert_eq!(cache.get(first, 160), Some(vec![1]));
+        assert_eq!(cache.get(third, 160), Some(vec![3]));
+    }
+
+    #[test]
+    fn test_thumbnail_cache_invalidate_removes_all_sizes() {
+        let cache = ThumbnailCache::new(4);
+        let (pet, other_pet) = (Uuid::new_v4(), Uuid::new_v4());
+
+        cache.insert(pet, 160, vec![1]);
+        cache.insert(pet, 180, vec![2]);
+        cache.insert(other_pet, 160, vec![3]);
+        cache.invalidate(pet);
+
+        assert_eq!(cache.get(pet, 160), None);
+        assert_eq!(cache.get(pet, 180), None);
+        assert_eq!(cache.get(other_pet, 160), Some(vec![3]));
+    }
+
+    #[test]
+    fn test_build_png_thumbnail_resizes_to_square() {
+        let img = image::RgbaImage::from_pixel(64, 32, image::Rgba([200, 120, 80, 255]));
+        let mut body = Vec::new();
+        img.write_to(&mut Cursor::new(&mut body), image::ImageFormat::Png)
+            .unwrap();
+
+        let thumbnail =
+            image::load_from_memory(&build_png_thumbnail(&body, 16, 1).unwrap()).unwrap();
+
+        assert_eq!((thumbnail.width(), thumbnail.height()), (16, 16));
+    }
+
+    fn png_bytes(width: u32, height: u32) -> Vec<u8> {
+        let img = image::RgbaImage::from_pixel(width, height, image::Rgba([200, 120, 80, 255]));
+        let mut body = Vec::new();
+        img.write_to(&mut Cursor::new(&mut body), image::ImageFormat::Png)
+            .unwrap();
+        body
+    }
+
+    #[test]
+    fn test_build_pass_thumbnails_retina_doubles_size() {
+        let thumbnails = build_pass_thumbnails(&png_bytes(400, 300)).unwrap();
+
+        let standard = image::load_from_memory(&thumbnails.standard).unwrap();
+        let retina = image::load_from_memory(&thumbnails.retina.unwrap()).unwrap();
+
+        assert_eq!(standard.width(), consts::PKPASS_THUMBNAIL_SIZE_PX);
+        assert_eq!(retina.width(), standard.width() * 2);
+        assert_eq!(retina.height(), standard.height() * 2);
+    }
+
+    #[test]
+    fn test_build_pass_thumbnails_small_picture_has_single_size() {
+        let thumbnails = build_pass_thumbnails(&png_bytes(120, 120)).unwrap();
+
+        assert!(thumbnails.retina.is_none());
+        let standard = image::load_from_memory(&thumbnails.standard).unwrap();
+        assert_eq!(standard.width(), consts::PKPASS_THUMBNAIL_SIZE_PX);
+    }
+}
@@ -3,7 +3,7 @@
 //! This module handles user management operations including user creation,
 //! authentication, contact management, and user profile operations.
 
-use crate::{metric, models, repo};
+use crate::{consts, metric, models, repo};
 use uuid::Uuid;
 
 /// Gets an existing user by email or creates a new one if not found.
@@ -89,12 +89,70 @@ pub async fn get_owner_contacts(
 /// Contains the contact details submitted by users for display on their
 /// pet profiles. This information allows people who find a pet to contact
 /// the owner.
-#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize, Default)]
 pub struct OwnerContactRequest {
     /// Display name for the contact method (e.g., "Phone", "Email")
     pub contact_name: String,
     /// Contact value (e.g., phone number, email address)
     pub contact_value: String,
+    /// Type chosen by the user, guessed from the value if empty
+    #[serde(default, deserialize_with = "deserialize_optional_contact_type")]
+    pub contact_type: Option<models::user_app::ContactType>,
+    /// Optional home area shown on the public profile of lost pets
+    #[serde(default)]
+    pub address: Option<String>,
+    /// Optional coordinates of the home area, rounded before being stored
+    #[serde(default, deserialize_with = "deserialize_optional_f64")]
+    pub latitude: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_optional_f64")]
+    pub longitude: Option<f64>,
+}
+
+/// Deserializes an optional number of the form, an empty value is `None`.
+fn deserialize_optional_f64<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value: Option<String> = serde::Deserialize::deserialize(deserializer)?;
+    match value.as_deref().map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(value) => value.parse().map(Some).map_err(serde::de::Error::custom),
+    }
+}
+
+/// Rounds the home area coordinates to
+/// [HOME_AREA_COORDINATE_DECIMALS](consts::HOME_AREA_COORDINATE_DECIMALS), so
+/// they point to the neighborhood and not to the owner's door.
+///
+/// # Returns
+/// * `Option<(f64, f64)>` - The rounded latitude and longitude, `None` if
+///   any of them is missing or out of range
+pub fn coarse_coordinates(latitude: Option<f64>, longitude: Option<f64>) -> Option<(f64, f64)> {
+    let (latitude, longitude) = (latitude?, longitude?);
+    if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+        return None;
+    }
+
+    let scale = 10_f64.powi(consts::HOME_AREA_COORDINATE_DECIMALS);
+    let round = |value: f64| (value * scale).round() / scale;
+
+    Some((round(latitude), round(longitude)))
+}
+
+/// Deserializes the optional contact type of the form, an empty value is `None`.
+fn deserialize_optional_contact_type<'de, D>(
+    deserializer: D,
+) -> Result<Option<models::user_app::ContactType>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value: Option<String> = serde::Deserialize::deserialize(deserializer)?;
+    match value.as_deref().map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(value) => serde_json::from_value(serde_json::Value::String(value.to_string()))
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+    }
 }
 
 impl OwnerContactRequest {
@@ -134,15 +192,30 @@ impl OwnerContactRequest {
 ///
 /// # Validation
 /// The request should be validated using `fields_are_valid()` before calling this function.
+/// If no contact type was chosen, it is guessed from the contact value.
+/// The home area is optional, coordinates are stored rounded, see [coarse_coordinates].
 pub async fn add_owner_contact(
     user_app_id: i64,
     request: &OwnerContactRequest,
     repo: &repo::ImplAppRepo,
 ) -> anyhow::Result<models::user_app::OwnerContact> {
+    let address = request
+        .address
+        .as_deref()
+        .map(str::trim)
+        .filter(|address| !address.is_empty())
+        .map(str::to_string);
+
     repo.insert_owner_contact(
         user_app_id,
         request.contact_name.to_string(),
         request.contact_value.to_string(),
+        request
+            .contact_type
+            .clone()
+            .unwrap_or_else(|| models::user_app::ContactType::sniff(&request.contact_value)),
+        address,
+        coarse_coordinates(request.latitude, request.longitude),
     )
     .await
 }
@@ -167,6 +240,56 @@ pub async fn delete_owner_contact(
     repo.delete_owner_contact(user_app_id, contact_id).await
 }
 
+/// Marks a contact as the primary one, shown first on the public profile.
+///
+/// # Arguments
+/// * `user_app_id` - ID of the user who owns the contact
+/// * `contact_id` - ID of the new primary contact
+/// * `repo` - Repository instance for database operations
+///
+/// # Returns
+/// * `anyhow::Result<()>` - Success confirmation or error details
+pub async fn set_primary_contact(
+    user_app_id: i64,
+    contact_id: i64,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<()> {
+    repo.set_primary_contact(user_app_id, contact_id).await
+}
+
+/// Moves a contact one position up in the user's contact list.
+///
+/// The current order is taken from the listed contacts and stored again
+/// with the contact swapped with the previous one. Moving the first contact
+/// or an unknown contact keeps the order.
+///
+/// # Arguments
+/// * `user_app_id` - ID of the user who owns the contact
+/// * `contact_id` - ID of the contact to move
+/// * `repo` - Repository instance for database operations
+///
+/// # Returns
+/// * `anyhow::Result<()>` - Success confirmation or error details
+pub async fn move_contact_up(
+    user_app_id: i64,
+    contact_id: i64,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<()> {
+    let mut ordered_ids: Vec<i64> = repo
+        .get_owner_contacts(user_app_id)
+        .await?
+        .iter()
+        .map(|contact| contact.id)
+        .collect();
+
+    match ordered_ids.iter().position(|id| *id == contact_id) {
+        Some(position) if position > 0 => ordered_ids.swap(position - 1, position),
+        _ => return Ok(()),
+    }
+
+    repo.reorder_contacts(user_app_id, &ordered_ids).await
+}
+
 /// Retrieves payment history for a user.
 ///
 /// Gets all payment records associated with the user, including
@@ -203,6 +326,26 @@ pub async fn delete_user_data(user_app_id: i64, repo: &repo::ImplAppRepo) -> any
     repo.remove_user_app_data(user_app_id).await
 }
 
+/// Sets the unit the user sees the weights in.
+///
+/// Weights are always stored in kilograms, the unit is only used to show
+/// them and to read the weights typed by the user.
+///
+/// # Arguments
+/// * `user_app_id` - ID of the user
+/// * `weight_unit` - The preferred weight unit
+/// * `repo` - Repository instance for database operations
+///
+/// # Returns
+/// * `anyhow::Result<()>` - Success confirmation or error details
+pub async fn set_weight_unit(
+    user_app_id: i64,
+    weight_unit: models::user_app::WeightUnit,
+    repo: &repo::ImplAppRepo,
+) -> anyhow::Result<()> {
+    repo.set_user_weight_unit(user_app_id, weight_unit).await
+}
+
 /// Reactivates a deactivated user account.
 ///
 /// Sets the user status back to active, allowing them to use the
@@ -235,6 +378,7 @@ mod tests {
             account_role: models::user_app::AccountRole::User,
             is_subscribed: false,
             is_enabled: true,
+            weight_unit: models::user_app::WeightUnit::default(),
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
@@ -251,6 +395,12 @@ mod tests {
             user_app_id,
             full_name: name.to_string(),
             contact_value: value.to_string(),
+            contact_type: models::user_app::ContactType::sniff(value),
+            is_primary: false,
+            sort_order: 0,
+            address: None,
+            latitude: None,
+            longitude: None,
             created_at: Utc::now(),
         }
     }
@@ -261,6 +411,7 @@ mod tests {
             mp_paym_id: 123456,
             payment_idempotency_h: "test_hash".to_string(),
             transaction_amount: "9.99".to_string(),
+            currency: "USD".to_string(),
             installments: 1,
             payment_method_id: "visa".to_string(),
             issuer_id: "123".to_string(),
@@ -382,33 +533,148 @@ mod tests {
         assert!(result.is_ok_and(|contacts| contacts.len() == 2));
     }
 
+    #[ntex::test]
+    async fn test_move_contact_up_swaps_with_previous() {
+        let mut mock_repo = MockAppRepo::new();
+        let user_id = 1;
+        let contacts = vec![
+            create_test_owner_contact(1, user_id, "Phone", "555-1234"),
+            create_test_owner_contact(2, user_id, "Email", "test@example.com"),
+            create_test_owner_contact(3, user_id, "Vet", "555-9876"),
+        ];
+
+        mock_repo
+            .expect_get_owner_contacts()
+            .with(eq(user_id))
+            .times(2)
+            .returning(move |_| {
+                let contacts = contacts.clone();
+                Box::pin(async move { Ok(contacts) })
+            });
+        mock_repo
+            .expect_reorder_contacts()
+            .withf(|user_id, ordered_ids| *user_id == 1 && *ordered_ids == [1, 3, 2])
+            .times(1)
+            .returning(|_, _| Box::pin(async move { Ok(()) }));
+
+        let mock_repo: Box<dyn repo::AppRepo> = Box::new(mock_repo);
+
+        assert!(move_contact_up(user_id, 3, &mock_repo).await.is_ok());
+        // the first contact can't move up, nothing is stored
+        assert!(move_contact_up(user_id, 1, &mock_repo).await.is_ok());
+    }
+
     #[ntex::test]
     async fn test_owner_contact_request_fields_are_valid() {
         let valid_request = OwnerContactRequest {
             contact_name: "Phone".into(),
             contact_value: "555-1234".into(),
+            contact_type: None,
+            ..Default::default()
         };
         assert!(valid_request.fields_are_valid());
 
         let invalid_request_empty_name = OwnerContactRequest {
             contact_name: "".into(),
             contact_value: "555-1234".into(),
+            contact_type: None,
+            ..Default::default()
         };
         assert!(!invalid_request_empty_name.fields_are_valid());
 
         let invalid_request_whitespace = OwnerContactRequest {
             contact_name: "   ".into(),
             contact_value: "555-1234".into(),
+            contact_type: None,
+            ..Default::default()
         };
         assert!(!invalid_request_whitespace.fields_are_valid());
 
         let invalid_request_empty_value = OwnerContactRequest {
             contact_name: "Phone".into(),
             contact_value: "".into(),
+            contact_type: None,
+            ..Default::default()
         };
         assert!(!invalid_request_empty_value.fields_are_valid());
     }
 
+    #[test]
+    fn test_coarse_coordinates_rounding() {
+        assert_eq!(
+            coarse_coordinates(Some(19.432_608), Some(-99.133_209)),
+            Some((19.43, -99.13))
+        );
+        assert_eq!(
+            coarse_coordinates(Some(-33.868_8), Some(151.209_3)),
+            Some((-33.87, 151.21))
+        );
+        // both coordinates are needed
+        assert_eq!(coarse_coordinates(Some(19.43), None), None);
+        assert_eq!(coarse_coordinates(None, Some(-99.13)), None);
+        assert_eq!(coarse_coordinates(None, None), None);
+        // out of range values are dropped
+        assert_eq!(coarse_coordinates(Some(91.0), Some(0.0)), None);
+        assert_eq!(coarse_coordinates(Some(0.0), Some(-180.5)), None);
+    }
+
+    #[test]
+    fn test_owner_contact_request_empty_home_area_is_none() {
+        let request: OwnerContactRequest = serde_json::from_value(serde_json::json!({
+            "contact_name": "Casa",
+            "contact_value": "555-1234",
+            "latitude": "",
+            "longitude": " -99.133209 ",
+        }))
+        .unwrap();
+
+        assert_eq!(request.address, None);
+        assert_eq!(request.latitude, None);
+        assert_eq!(request.longitude, Some(-99.133_209));
+    }
+
+    #[ntex::test]
+    async fn test_add_owner_contact_rounds_home_area() {
+        let mut mock_repo = MockAppRepo::new();
+        let request = OwnerContactRequest {
+            contact_name: "Casa".to_string(),
+            contact_value: "555-1234".to_string(),
+            address: Some("  Col. Roma, CDMX ".to_string()),
+            latitude: Some(19.419_4),
+            longitude: Some(-99.162_7),
+            ..Default::default()
+        };
+
+        mock_repo
+            .expect_insert_owner_contact()
+            .with(
+                eq(1),
+                always(),
+                always(),
+                always(),
+                eq(Some("Col. Roma, CDMX".to_string())),
+                eq(Some((19.42, -99.16))),
+            )
+            .times(1)
+            .returning(|user_id, name, value, contact_type, address, coordinates| {
+                Box::pin(async move {
+                    Ok(models::user_app::OwnerContact {
+                        contact_type,
+                        address,
+                        latitude: coordinates.map(|(lat, _)| lat),
+                        longitude: coordinates.map(|(_, lng)| lng),
+                        ..create_test_owner_contact(1, user_id, &name, &value)
+                    })
+                })
+            });
+
+        let mock_repo: Box<dyn repo::AppRepo> = Box::new(mock_repo);
+        let contact = add_owner_contact(1, &request, &mock_repo).await.unwrap();
+
+        assert_eq!(contact.latitude, Some(19.42));
+        assert_eq!(contact.longitude, Some(-99.16));
+    }
+
     #[ntex::test]
     async fn test_add_owner_contact() {
         let mut mock_repo = MockAppRepo::new();
@@ -416,6 +682,8 @@ mod tests {
         let request = OwnerContactRequest {
             contact_name: "Phone".to_string(),
             contact_value: "555-1234".to_string(),
+            contact_type: None,
+            ..Default::default()
         };
         let expected_contact = create_test_owner_contact(1, user_id, "Phone", "555-1234");
 
@@ -425,9 +693,12 @@ mod tests {
                 eq(user_id),
                 eq("Phone".to_string()),
                 eq("555-1234".to_string()),
+                eq(models::user_app::ContactType::Phone),
+                eq(None),
+                eq(None),
             )
             .times(1)
-            .returning(move |_, _, _| {
+            .returning(move |_, _, _, _, _, _| {
                 let contact = expected_contact.clone();
                 Box::pin(async move { Ok(contact) })
             });
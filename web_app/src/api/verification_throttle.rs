@@ -0,0 +1,132 @@
+//! # Verification Code Throttle Module
+//!
+//! Each phone verification code is a WhatsApp template message, so sending
+//! them is limited to [`VERIFICATION_CODE_MAX_SENDS`] per user within a
+//! [`VERIFICATION_CODE_WINDOW_MINUTES`] sliding window. The send times are
+//! kept in memory and shared by the clones of the throttle, so every server
+//! worker counts the same sends; a restart just starts the windows over.
+//!
+//! [`VERIFICATION_CODE_MAX_SENDS`]: crate::consts::VERIFICATION_CODE_MAX_SENDS
+//! [`VERIFICATION_CODE_WINDOW_MINUTES`]: crate::consts::VERIFICATION_CODE_WINDOW_MINUTES
+
+use chrono::{DateTime, TimeDelta, Utc};
+use derive_more::{Display, Error};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+/// Raised when a user asks for too many verification codes
+#[derive(Debug, Display, Error, PartialEq)]
+#[display("demasiados codigos enviados, intentar de nuevo en {wait_secs} segundos")]
+pub struct TooManyVerificationCodes {
+    /// Seconds until a new code can be sent
+    pub wait_secs: i64,
+}
+
+/// Send times of the verification codes of each user, clones share them
+#[derive(Clone)]
+pub struct VerificationThrottle {
+    max_sends: usize,
+    window: TimeDelta,
+    sends: Arc<Mutex<HashMap<i64, Vec<DateTime<Utc>>>>>,
+}
+
+impl VerificationThrottle {
+    pub fn new(max_sends: usize, window: TimeDelta) -> Self {
+        Self {
+            max_sends,
+            window,
+            sends: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// A panic while holding the lock can't leave the send times inconsistent
+    fn lock_sends(&self) -> MutexGuard<'_, HashMap<i64, Vec<DateTime<Utc>>>> {
+        self.sends
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Records a code sent to the user at `now`, unless the user already got
+    /// the max codes within the window.
+    ///
+    /// # Returns
+    /// * [`TooManyVerificationCodes`] with the seconds until the oldest send
+    ///   in the window expires
+    pub fn try_acquire(
+        &self,
+        user_id: i64,
+        now: DateTime<Utc>,
+    ) -> Result<(), TooManyVerificationCodes> {
+        let mut sends = self.lock_sends();
+        // drops every user's expired sends, so the map doesn't keep growing
+        sends.retain(|_, times| {
+            times.retain(|sent_at| now - *sent_at < self.window);
+            !times.is_empty()
+        });
+
+        let times = sends.entry(user_id).or_default();
+        if times.len() >= self.max_sends {
+            let oldest = times.iter().min().copied().unwrap_or(now);
+            let wait = (oldest + self.window - now).num_seconds().max(1);
+            return Err(TooManyVerificationCodes { wait_secs: wait });
+        }
+
+        times.push(now);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle_blocks_until_the_window_resets() {
+        let throttle = VerificationThrottle::new(3, TimeDelta::minutes(10));
+        let start = DateTime::parse_from_rfc3339("2024-06-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        for minute in 0..3 {
+            assert!(
+                throttle
+                    .try_acquire(1, start + TimeDelta::minutes(minute))
+                    .is_ok()
+            );
+        }
+        assert_eq!(
+            throttle.try_acquire(1, start + TimeDelta::minutes(4)),
+            Err(TooManyVerificationCodes { wait_secs: 6 * 60 })
+        );
+        // other users have their own counter
+        assert!(
+            throttle
+                .try_acquire(2, start + TimeDelta::minutes(4))
+                .is_ok()
+        );
+
+        // the first send leaves the window, one more code is allowed
+        assert!(
+            throttle
+                .try_acquire(1, start + TimeDelta::minutes(10))
+                .is_ok()
+        );
+        assert!(
+            throttle
+                .try_acquire(1, start + TimeDelta::minutes(10))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_throttle_clones_share_the_sends() {
+        let throttle = VerificationThrottle::new(1, TimeDelta::minutes(10));
+        let worker_throttle = throttle.clone();
+        let now = Utc::now();
+
+        assert!(throttle.try_acquire(1, now).is_ok());
+        assert!(worker_throttle.try_acquire(1, now).is_err());
+    }
+}
@@ -37,6 +37,7 @@
 //! println!("Running in {} environment", app_config.env);
 //! ```
 
+use crate::consts;
 use anyhow::Context;
 use envconfig::Envconfig;
 use serde::{Deserialize, Deserializer};
@@ -62,6 +63,40 @@ where
     s.parse::<u64>().map_err(serde::de::Error::custom)
 }
 
+/// Same as [`deserialize_string_to_u64`] for the optional values, a missing
+/// parameter is `None` so the accessor falls back to its const.
+fn deserialize_optional_string_to_u64<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|s| s.parse::<u64>().map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+/// Same as [`deserialize_optional_string_to_u64`] for the optional decimal values
+fn deserialize_optional_string_to_f64<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|s| s.parse::<f64>().map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+/// Custom deserializer to convert string values ("true"/"false") to bool,
+/// SSM Parameter Store stores all values as strings.
+fn deserialize_string_to_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    s.trim()
+        .to_lowercase()
+        .parse::<bool>()
+        .map_err(serde::de::Error::custom)
+}
+
 /// Application configuration with security-aware field management.
 ///
 /// This struct contains all environment variables used to configure the application.
@@ -132,6 +167,10 @@ pub struct AppConfig {
     /// This token must match the value configured in WhatsApp Business API dashboard
     pub whatsapp_verify_token: String,
 
+    /// 🔒 SENSITIVE: WhatsApp (Meta) app secret
+    /// Security: Used to verify the `X-Hub-Signature-256` HMAC of webhook requests
+    pub whatsapp_app_secret: String,
+
     /// AWS Step Functions ARN for notifications (SEMI-SENSITIVE)
     /// Security: Contains account information, restrict access
     /// Example: "arn:aws:states:us-east-1:123456789012:stateMachine:notifications"
@@ -159,19 +198,423 @@ pub struct AppConfig {
 
     pub cloudfront_url: String,
 
+    /// S3 bucket storing the pet pictures (NON-SENSITIVE)
+    #[envconfig(default = "pet-info-app-storage")]
+    pub storage_bucket_name: String,
+
+    /// Prefix added to every S3 key so local/dev objects don't collide with prod (NON-SENSITIVE)
+    /// Empty in prod, the public pictures are served from `pics/` by CloudFront
+    #[envconfig(default = "")]
+    #[serde(default)]
+    pub storage_key_prefix: String,
+
+    /// Max requests per minute a client IP can make to public profile routes (NON-SENSITIVE)
+    #[envconfig(default = "60")]
+    #[serde(deserialize_with = "deserialize_string_to_u64")]
+    pub public_rate_limit_per_minute: u64,
+
+    /// Hard limit of pets a single account can register (NON-SENSITIVE)
+    #[envconfig(default = "20")]
+    #[serde(deserialize_with = "deserialize_string_to_u64")]
+    pub max_pets_per_user: u64,
+
+    /// Seconds a public profile is served from the cache, 0 disables it (NON-SENSITIVE)
+    #[envconfig(default = "60")]
+    #[serde(deserialize_with = "deserialize_string_to_u64")]
+    pub public_profile_cache_ttl_secs: u64,
+
+    /// Seconds the session cookie lives, must be positive (NON-SENSITIVE)
+    /// Default: [`MAX_AGE_COOKIES`](crate::consts::MAX_AGE_COOKIES)
+    #[serde(default, deserialize_with = "deserialize_optional_string_to_u64")]
+    pub session_max_age_secs: Option<u64>,
+
+    /// Seconds the identity (logged user) cookie lives, must be positive (NON-SENSITIVE)
+    /// Default: [`MAX_AGE_COOKIES`](crate::consts::MAX_AGE_COOKIES)
+    #[serde(default, deserialize_with = "deserialize_optional_string_to_u64")]
+    pub identity_max_age_secs: Option<u64>,
+
+    /// Minutes a reminder must be ahead of now to be scheduled (NON-SENSITIVE)
+    /// Default: [`REMINDER_MIN_LEAD_MINUTES`](crate::consts::REMINDER_MIN_LEAD_MINUTES)
+    #[serde(default, deserialize_with = "deserialize_optional_string_to_u64")]
+    pub reminder_min_lead_minutes: Option<u64>,
+
+    /// Days ahead a reminder can be scheduled at most, must be positive (NON-SENSITIVE)
+    /// Default: [`REMINDER_MAX_HORIZON_DAYS`](crate::consts::REMINDER_MAX_HORIZON_DAYS)
+    #[serde(default, deserialize_with = "deserialize_optional_string_to_u64")]
+    pub reminder_max_horizon_days: Option<u64>,
+
+    /// Times above or below the recent average a new weight is flagged as a
+    /// likely typo, must be greater than 1 (NON-SENSITIVE)
+    /// Default: [`WEIGHT_OUTLIER_FACTOR`](crate::consts::WEIGHT_OUTLIER_FACTOR)
+    #[serde(default, deserialize_with = "deserialize_optional_string_to_f64")]
+    pub weight_outlier_factor: Option<f64>,
+
+    /// Comma separated origins allowed by CORS (NON-SENSITIVE)
+    /// The OAuth and payment providers are always allowed on top of these,
+    /// see [`CORS_PROVIDER_ORIGINS`](crate::consts::CORS_PROVIDER_ORIGINS)
+    /// Example: "https://pet-info.link,https://staging.pet-info.link"
+    #[envconfig(default = "http://localhost:8080,https://pet-info.link")]
+    pub allowed_origins: String,
+
+    /// Hash algorithm of the phone verification (TOTP) codes (NON-SENSITIVE)
+    /// Values: "SHA1", "SHA256", "SHA512"
+    /// Default: [`TOTP_ALGORITHM`](crate::consts::TOTP_ALGORITHM)
+    #[serde(default)]
+    pub totp_algorithm: Option<String>,
+
+    /// Digits of the phone verification codes, 6 to 8 (NON-SENSITIVE)
+    /// Default: [`TOTP_DIGITS`](crate::consts::TOTP_DIGITS)
+    #[serde(default, deserialize_with = "deserialize_optional_string_to_u64")]
+    pub totp_digits: Option<u64>,
+
+    /// Seconds each phone verification code window lasts (NON-SENSITIVE)
+    /// Bounds: [`TOTP_TIME_STEP_SECONDS_RANGE`](crate::consts::TOTP_TIME_STEP_SECONDS_RANGE)
+    /// Default: [`TOTP_TIME_STEP_SECONDS`](crate::consts::TOTP_TIME_STEP_SECONDS)
+    #[serde(default, deserialize_with = "deserialize_optional_string_to_u64")]
+    pub totp_step_secs: Option<u64>,
+
+    /// Previous/next windows a phone verification code is still accepted (NON-SENSITIVE)
+    /// SMS friendly deployments can widen it when messages arrive late
+    /// Default: [`TOTP_SKEW`](crate::consts::TOTP_SKEW)
+    #[serde(default, deserialize_with = "deserialize_optional_string_to_u64")]
+    pub totp_skew: Option<u64>,
+
+    /// Skips the reminder executions and WhatsApp sends, logging them instead (NON-SENSITIVE)
+    /// Only for local/dev testing, the app refuses to start in prod with it enabled
+    #[envconfig(default = "false")]
+    #[serde(default, deserialize_with = "deserialize_string_to_bool")]
+    pub notifications_dry_run: bool,
+
+    /// Passes stopping the executions of cancelled reminders until none is running, 1 to 10 (NON-SENSITIVE)
+    /// A just started execution may not be visible yet to the first stop
+    /// Default: [`REMINDER_CANCEL_MAX_ATTEMPTS`](crate::consts::REMINDER_CANCEL_MAX_ATTEMPTS)
+    #[serde(default, deserialize_with = "deserialize_optional_string_to_u64")]
+    pub reminder_cancel_max_attempts: Option<u64>,
+
+    /// Biggest PDF report sent as a WhatsApp document, bigger ones are sent as a link (NON-SENSITIVE)
+    /// Must be positive and at most [`WHATSAPP_DOCUMENT_MAX_SIZE_BYTES`](crate::consts::WHATSAPP_DOCUMENT_MAX_SIZE_BYTES),
+    /// big uploads are slow and fail often well below that limit
+    /// Default: [`WHATSAPP_DOCUMENT_SEND_MAX_BYTES`](crate::consts::WHATSAPP_DOCUMENT_SEND_MAX_BYTES)
+    #[serde(default, deserialize_with = "deserialize_optional_string_to_u64")]
+    pub whatsapp_document_max_bytes: Option<u64>,
+
+    /// PBKDF2 iterations deriving the SQLCipher key (NON-SENSITIVE)
+    /// Default: [`DB_CIPHER_LEGACY_KDF_ITER`](crate::consts::DB_CIPHER_LEGACY_KDF_ITER),
+    /// the value existing databases were created with. The cipher settings
+    /// can't be changed on an existing database without rekeying it first:
+    /// `ATTACH DATABASE 'new.db' AS new KEY '<key>'`, set the new
+    /// `new.kdf_iter`/`new.cipher_page_size`/`new.cipher_hmac_algorithm`/
+    /// `new.cipher_kdf_algorithm` pragmas, `SELECT sqlcipher_export('new')`
+    /// and swap the files; otherwise the database can't be opened.
+    #[serde(default, deserialize_with = "deserialize_optional_string_to_u64")]
+    pub db_cipher_kdf_iter: Option<u64>,
+
+    /// SQLCipher page size in bytes, a power of two between 512 and 65536 (NON-SENSITIVE)
+    /// Default: [`DB_CIPHER_LEGACY_PAGE_SIZE`](crate::consts::DB_CIPHER_LEGACY_PAGE_SIZE),
+    /// changing it needs the rekey described in [`db_cipher_kdf_iter`](Self::db_cipher_kdf_iter)
+    #[serde(default, deserialize_with = "deserialize_optional_string_to_u64")]
+    pub db_cipher_page_size: Option<u64>,
+
+    /// HMAC and PBKDF2 hash of SQLCipher (NON-SENSITIVE)
+    /// Values: "SHA1", "SHA256", "SHA512"; "SHA1" only for existing databases,
+    /// changing it needs the rekey described in [`db_cipher_kdf_iter`](Self::db_cipher_kdf_iter)
+    /// Default: [`DB_CIPHER_LEGACY_HMAC_ALGORITHM`](crate::consts::DB_CIPHER_LEGACY_HMAC_ALGORITHM)
+    #[serde(default)]
+    pub db_cipher_hmac_algorithm: Option<String>,
+
     /// 🔒 SENSITIVE: Google OAuth client secret
     /// Security: Store in secure secret management system
     pub google_oauth_client_secret: String,
 
+    /// 🔒 SENSITIVE: Bearer token the scheduler sends to run the scheduled jobs
+    /// Security: Store in secure secret management system, the jobs endpoints
+    /// reject every request while it isn't set
+    #[serde(default)]
+    pub scheduled_jobs_token: Option<String>,
+
+    /// IANA timezone of the users whose timezone isn't known (NON-SENSITIVE)
+    /// Default: [`DEFAULT_TIMEZONE`](crate::consts::DEFAULT_TIMEZONE)
+    /// Example: "America/Mexico_City"
+    #[serde(default)]
+    pub default_timezone: Option<String>,
+
     pub logfire_token: String,
 }
 
 impl AppConfig {
+    /// Checks the values that parse fine but can't be used
+    fn validate(&self) -> anyhow::Result<()> {
+        for (name, max_age_secs) in [
+            ("SESSION_MAX_AGE_SECS", self.session_max_age_secs),
+            ("IDENTITY_MAX_AGE_SECS", self.identity_max_age_secs),
+        ] {
+            anyhow::ensure!(
+                max_age_secs.is_none_or(|secs| secs > 0 && i64::try_from(secs).is_ok()),
+                "{name} must be a positive number of seconds"
+            );
+        }
+
+        anyhow::ensure!(
+            self.reminder_max_horizon_days.is_none_or(|days| days > 0)
+                && self.reminder_max_horizon() > self.reminder_min_lead(),
+            "REMINDER_MAX_HORIZON_DAYS must be positive and longer than REMINDER_MIN_LEAD_MINUTES"
+        );
+
+        anyhow::ensure!(
+            self.weight_outlier_factor
+                .is_none_or(|factor| factor.is_finite() && factor > 1.0),
+            "WEIGHT_OUTLIER_FACTOR must be a number greater than 1"
+        );
+
+        anyhow::ensure!(
+            self.totp_hash_algorithm().is_some(),
+            "TOTP_ALGORITHM must be one of SHA1, SHA256 or SHA512"
+        );
+        anyhow::ensure!(
+            (6..=8).contains(&self.totp_digits()),
+            "TOTP_DIGITS must be between 6 and 8"
+        );
+        anyhow::ensure!(
+            consts::TOTP_TIME_STEP_SECONDS_RANGE.contains(&self.totp_step_secs()),
+            "TOTP_STEP_SECS must be between {} and {} seconds",
+            consts::TOTP_TIME_STEP_SECONDS_RANGE.start(),
+            consts::TOTP_TIME_STEP_SECONDS_RANGE.end()
+        );
+        anyhow::ensure!(
+            u8::try_from(self.totp_skew()).is_ok(),
+            "TOTP_SKEW must be a small number of windows"
+        );
+
+        anyhow::ensure!(
+            self.reminder_cancel_max_attempts
+                .is_none_or(|attempts| (1..=10).contains(&attempts)),
+            "REMINDER_CANCEL_MAX_ATTEMPTS must be between 1 and 10"
+        );
+
+        anyhow::ensure!(
+            self.whatsapp_document_max_bytes
+                .is_none_or(|bytes| (1..=consts::WHATSAPP_DOCUMENT_MAX_SIZE_BYTES).contains(&bytes)),
+            "WHATSAPP_DOCUMENT_MAX_BYTES must be between 1 and {} bytes",
+            consts::WHATSAPP_DOCUMENT_MAX_SIZE_BYTES
+        );
+
+        anyhow::ensure!(
+            self.db_cipher_hmac().is_some(),
+            "DB_CIPHER_HMAC_ALGORITHM must be one of SHA1, SHA256 or SHA512"
+        );
+        anyhow::ensure!(
+            self.db_cipher_page_size().is_power_of_two()
+                && (512..=65536).contains(&self.db_cipher_page_size()),
+            "DB_CIPHER_PAGE_SIZE must be a power of two between 512 and 65536"
+        );
+        anyhow::ensure!(
+            self.db_cipher_kdf_iter() > 0 && u32::try_from(self.db_cipher_kdf_iter()).is_ok(),
+            "DB_CIPHER_KDF_ITER must be a positive number of iterations"
+        );
+
+        anyhow::ensure!(
+            !(self.notifications_dry_run && self.is_prod()),
+            "NOTIFICATIONS_DRY_RUN can't be enabled in prod"
+        );
+
+        anyhow::ensure!(
+            self.default_timezone
+                .as_deref()
+                .is_none_or(|tz| tz.parse::<chrono_tz::Tz>().is_ok()),
+            "DEFAULT_TIMEZONE must be an IANA timezone like America/Mexico_City"
+        );
+
+        for origin in self.configured_origins() {
+            anyhow::ensure!(
+                reqwest::Url::parse(origin).is_ok_and(|url| {
+                    matches!(url.scheme(), "http" | "https")
+                        && url.origin().ascii_serialization() == origin
+                }),
+                "ALLOWED_ORIGINS entry {origin:?} isn't an origin like https://example.com"
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Non empty entries of [`allowed_origins`](Self::allowed_origins)
+    fn configured_origins(&self) -> impl Iterator<Item = &str> {
+        self.allowed_origins
+            .split(',')
+            .map(str::trim)
+            .filter(|origin| !origin.is_empty())
+    }
+
+    /// Origins allowed by CORS: the configured ones plus the providers
+    pub fn cors_allowed_origins(&self) -> Vec<String> {
+        let mut origins: Vec<String> = self
+            .configured_origins()
+            .chain(consts::CORS_PROVIDER_ORIGINS)
+            .map(str::to_string)
+            .collect();
+        origins.sort();
+        origins.dedup();
+        origins
+    }
+
+    /// Max age of the session cookie in seconds
+    pub fn session_max_age(&self) -> i64 {
+        self.session_max_age_secs
+            .and_then(|secs| i64::try_from(secs).ok())
+            .unwrap_or(consts::MAX_AGE_COOKIES)
+    }
+
+    /// Max age of the identity cookie in seconds
+    pub fn identity_max_age(&self) -> i64 {
+        self.identity_max_age_secs
+            .and_then(|secs| i64::try_from(secs).ok())
+            .unwrap_or(consts::MAX_AGE_COOKIES)
+    }
+
+    /// Timezone of the users whose timezone isn't known
+    pub fn default_timezone(&self) -> chrono_tz::Tz {
+        self.default_timezone
+            .as_deref()
+            .and_then(|tz| tz.parse().ok())
+            .unwrap_or(consts::DEFAULT_TIMEZONE)
+    }
+
+    /// Shortest time ahead a reminder can be scheduled
+    pub fn reminder_min_lead(&self) -> chrono::TimeDelta {
+        self.reminder_min_lead_minutes
+            .and_then(|minutes| i64::try_from(minutes).ok())
+            .and_then(chrono::TimeDelta::try_minutes)
+            .unwrap_or(chrono::TimeDelta::minutes(
+                consts::REMINDER_MIN_LEAD_MINUTES,
+            ))
+    }
+
+    /// Longest time ahead a reminder can be scheduled
+    pub fn reminder_max_horizon(&self) -> chrono::TimeDelta {
+        self.reminder_max_horizon_days
+            .and_then(|days| i64::try_from(days).ok())
+            .and_then(chrono::TimeDelta::try_days)
+            .unwrap_or(chrono::TimeDelta::days(consts::REMINDER_MAX_HORIZON_DAYS))
+    }
+
+    /// Times above or below the recent average a new weight is flagged
+    pub fn weight_outlier_factor(&self) -> f64 {
+        self.weight_outlier_factor
+            .unwrap_or(consts::WEIGHT_OUTLIER_FACTOR)
+    }
+
+    /// Hash algorithm of the phone verification codes, `None` if unknown
+    fn totp_hash_algorithm(&self) -> Option<totp_rs::Algorithm> {
+        let algorithm = self
+            .totp_algorithm
+            .as_deref()
+            .unwrap_or(consts::TOTP_ALGORITHM);
+        match algorithm.trim().to_uppercase().as_str() {
+            "SHA1" => Some(totp_rs::Algorithm::SHA1),
+            "SHA256" => Some(totp_rs::Algorithm::SHA256),
+            "SHA512" => Some(totp_rs::Algorithm::SHA512),
+            _ => None,
+        }
+    }
+
+    /// Digits of the phone verification codes
+    pub fn totp_digits(&self) -> u64 {
+        self.totp_digits.unwrap_or(consts::TOTP_DIGITS)
+    }
+
+    /// Seconds each phone verification code window lasts
+    pub fn totp_step_secs(&self) -> u64 {
+        self.totp_step_secs
+            .unwrap_or(consts::TOTP_TIME_STEP_SECONDS)
+    }
+
+    /// Previous/next windows a phone verification code is still accepted
+    pub fn totp_skew(&self) -> u64 {
+        self.totp_skew.unwrap_or(consts::TOTP_SKEW)
+    }
+
+    /// Builds the phone verification (TOTP) client with the configured parameters
+    ///
+    /// # Arguments
+    /// * `secret` - Secret the codes are derived from, at least 16 bytes
+    pub fn build_totp(&self, secret: Vec<u8>) -> anyhow::Result<totp_rs::TOTP> {
+        totp_rs::TOTP::new(
+            self.totp_hash_algorithm()
+                .context("TOTP_ALGORITHM must be one of SHA1, SHA256 or SHA512")?,
+            usize::try_from(self.totp_digits())?,
+            u8::try_from(self.totp_skew())?,
+            self.totp_step_secs(),
+            secret,
+        )
+        .map_err(|e| anyhow::anyhow!("invalid TOTP parameters: {e}"))
+    }
+
+    /// PBKDF2 iterations deriving the SQLCipher key
+    pub fn db_cipher_kdf_iter(&self) -> u64 {
+        self.db_cipher_kdf_iter
+            .unwrap_or(consts::DB_CIPHER_LEGACY_KDF_ITER)
+    }
+
+    /// SQLCipher page size in bytes
+    pub fn db_cipher_page_size(&self) -> u64 {
+        self.db_cipher_page_size
+            .unwrap_or(consts::DB_CIPHER_LEGACY_PAGE_SIZE)
+    }
+
+    /// Hash of the SQLCipher HMAC and PBKDF2, `None` if unknown
+    pub fn db_cipher_hmac(&self) -> Option<&'static str> {
+        let algorithm = self
+            .db_cipher_hmac_algorithm
+            .as_deref()
+            .unwrap_or(consts::DB_CIPHER_LEGACY_HMAC_ALGORITHM);
+        match algorithm.trim().to_uppercase().as_str() {
+            "SHA1" => Some("SHA1"),
+            "SHA256" => Some("SHA256"),
+            "SHA512" => Some("SHA512"),
+            _ => None,
+        }
+    }
+
+    /// SQLCipher settings below the recommended ones, they still work but
+    /// should be raised (rekeying the database) in prod
+    pub fn weak_db_cipher_settings(&self) -> Vec<String> {
+        let mut warnings = vec![];
+        if self.db_cipher_kdf_iter() < consts::DB_CIPHER_MIN_KDF_ITER {
+            warnings.push(format!(
+                "DB_CIPHER_KDF_ITER={} is below {}",
+                self.db_cipher_kdf_iter(),
+                consts::DB_CIPHER_MIN_KDF_ITER
+            ));
+        }
+        if self.db_cipher_hmac() == Some("SHA1") {
+            warnings.push("DB_CIPHER_HMAC_ALGORITHM=SHA1 is deprecated, use SHA512".to_string());
+        }
+        warnings
+    }
+
     /// Checks if running in production environment
     pub fn is_prod(&self) -> bool {
         self.env.to_lowercase() == "prod"
     }
 
+    /// Passes stopping the executions of cancelled reminders
+    pub fn reminder_cancel_max_attempts(&self) -> u32 {
+        self.reminder_cancel_max_attempts
+            .and_then(|attempts| u32::try_from(attempts).ok())
+            .unwrap_or(consts::REMINDER_CANCEL_MAX_ATTEMPTS)
+    }
+
+    /// Biggest PDF report sent as a WhatsApp document
+    pub fn whatsapp_document_max_bytes(&self) -> u64 {
+        self.whatsapp_document_max_bytes
+            .unwrap_or(consts::WHATSAPP_DOCUMENT_SEND_MAX_BYTES)
+    }
+
+    /// Whether notifications are only logged, never in prod
+    pub fn is_notifications_dry_run(&self) -> bool {
+        self.notifications_dry_run && !self.is_prod()
+    }
+
     /// Gets the server URL host with port for non-production environments
     pub fn url_host(&self) -> String {
         if self.is_prod() {
@@ -244,6 +687,7 @@ pub static APP_CONFIG: OnceCell<AppConfig> = OnceCell::const_new();
 pub async fn init_config() -> anyhow::Result<()> {
     let config = AppConfig::init_from_env()
         .context("Failed to load and validate application configuration. Check environment variables and security requirements.")?;
+    config.validate()?;
 
     APP_CONFIG
         .set(config)
@@ -289,6 +733,7 @@ pub async fn init_config() -> anyhow::Result<()> {
     let env_values = ssm_env::get_values().await?;
     let config = serde_json::from_value::<AppConfig>(env_values)
         .context("Failed to deserialize configuration from SSM parameters")?;
+    config.validate()?;
 
     APP_CONFIG
         .set(config)
@@ -420,3 +865,340 @@ mod ssm_env {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn required_env() -> HashMap<String, String> {
+        let uuid = uuid::Uuid::new_v4().to_string();
+        [
+            ("DB_HOST", "sqlite::memory:"),
+            ("DB_PASS_ENCRYPT", "pass"),
+            ("WEP_SERVER_HOST", "localhost"),
+            ("WEP_SERVER_PORT", "8080"),
+            ("CSRF_PASS", uuid.as_str()),
+            ("CSRF_SALT", uuid.as_str()),
+            ("MERCADO_PAGO_PUBLIC_KEY", "key"),
+            ("MERCADO_TOKEN", "token"),
+            ("WHATSAPP_BUSINESS_PHONE_NUMBER_ID", "1"),
+            ("WHATSAPP_BUSINESS_AUTH", "auth"),
+            ("WHATSAPP_VERIFY_TOKEN", "token"),
+            ("WHATSAPP_APP_SECRET", "secret"),
+            ("AWS_SFN_ARN_WB_NOTIFICATIONS", "arn"),
+            ("GOOGLE_OAUTH_CLIENT_ID", "id"),
+            ("GOOGLE_OAUTH_PROJECT_ID", "project"),
+            ("CLOUDFRONT_URL", "https://cdn.example.com"),
+            ("GOOGLE_OAUTH_CLIENT_SECRET", "secret"),
+            ("LOGFIRE_TOKEN", "token"),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+    }
+
+    #[test]
+    fn test_cookie_max_age_defaults_to_const() {
+        let config = AppConfig::init_from_hashmap(&required_env()).unwrap();
+
+        assert!(config.validate().is_ok());
+        assert_eq!(config.session_max_age(), consts::MAX_AGE_COOKIES);
+        assert_eq!(config.identity_max_age(), consts::MAX_AGE_COOKIES);
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_max_age() {
+        for key in ["SESSION_MAX_AGE_SECS", "IDENTITY_MAX_AGE_SECS"] {
+            let mut env = required_env();
+            env.insert(key.to_string(), "0".to_string());
+            let config = AppConfig::init_from_hashmap(&env).unwrap();
+
+            assert!(
+                config
+                    .validate()
+                    .is_err_and(|e| e.to_string().contains(key))
+            );
+        }
+    }
+
+    #[test]
+    fn test_reminder_cancel_max_attempts_defaults_to_const() {
+        let config = AppConfig::init_from_hashmap(&required_env()).unwrap();
+        assert_eq!(
+            config.reminder_cancel_max_attempts(),
+            consts::REMINDER_CANCEL_MAX_ATTEMPTS
+        );
+
+        let mut env = required_env();
+        env.insert("REMINDER_CANCEL_MAX_ATTEMPTS".to_string(), "5".to_string());
+        let config = AppConfig::init_from_hashmap(&env).unwrap();
+        assert_eq!(config.reminder_cancel_max_attempts(), 5);
+    }
+
+    #[test]
+    fn test_reminder_window_defaults_to_consts() {
+        let config = AppConfig::init_from_hashmap(&required_env()).unwrap();
+
+        assert_eq!(
+            config.reminder_min_lead(),
+            chrono::TimeDelta::minutes(consts::REMINDER_MIN_LEAD_MINUTES)
+        );
+        assert_eq!(
+            config.reminder_max_horizon(),
+            chrono::TimeDelta::days(consts::REMINDER_MAX_HORIZON_DAYS)
+        );
+    }
+
+    #[test]
+    fn test_weight_outlier_factor_defaults_to_const() {
+        let config = AppConfig::init_from_hashmap(&required_env()).unwrap();
+
+        assert_eq!(
+            config.weight_outlier_factor(),
+            consts::WEIGHT_OUTLIER_FACTOR
+        );
+
+        let mut env = required_env();
+        env.insert("WEIGHT_OUTLIER_FACTOR".to_string(), "3.5".to_string());
+        let config = AppConfig::init_from_hashmap(&env).unwrap();
+
+        assert!(config.validate().is_ok());
+        assert_eq!(config.weight_outlier_factor(), 3.5);
+    }
+
+    #[test]
+    fn test_validate_rejects_weight_outlier_factor_not_above_one() {
+        for factor in ["1", "0.5", "NaN"] {
+            let mut env = required_env();
+            env.insert("WEIGHT_OUTLIER_FACTOR".to_string(), factor.to_string());
+            let config = AppConfig::init_from_hashmap(&env).unwrap();
+
+            assert!(
+                config
+                    .validate()
+                    .is_err_and(|e| e.to_string().contains("WEIGHT_OUTLIER_FACTOR"))
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_reminder_horizon_shorter_than_lead() {
+        for (lead_minutes, horizon_days) in [("5", "0"), ("1440", "1")] {
+            let mut env = required_env();
+            env.insert(
+                "REMINDER_MIN_LEAD_MINUTES".to_string(),
+                lead_minutes.to_string(),
+            );
+            env.insert(
+                "REMINDER_MAX_HORIZON_DAYS".to_string(),
+                horizon_days.to_string(),
+            );
+            let config = AppConfig::init_from_hashmap(&env).unwrap();
+
+            assert!(
+                config
+                    .validate()
+                    .is_err_and(|e| e.to_string().contains("REMINDER_MAX_HORIZON_DAYS"))
+            );
+        }
+    }
+
+    #[test]
+    fn test_totp_defaults() {
+        let config = AppConfig::init_from_hashmap(&required_env()).unwrap();
+        let totp = config.build_totp(OTP_SECRET.as_bytes().to_vec()).unwrap();
+
+        assert_eq!(totp.algorithm, totp_rs::Algorithm::SHA512);
+        assert_eq!(totp.digits, 6);
+        assert_eq!(totp.step, 60);
+        assert_eq!(totp.skew, 1);
+    }
+
+    #[test]
+    fn test_totp_code_validates_within_configured_skew() {
+        let mut env = required_env();
+        env.insert("TOTP_ALGORITHM".to_string(), "sha256".to_string());
+        env.insert("TOTP_DIGITS".to_string(), "8".to_string());
+        env.insert("TOTP_STEP_SECS".to_string(), "30".to_string());
+        env.insert("TOTP_SKEW".to_string(), "2".to_string());
+        let config = AppConfig::init_from_hashmap(&env).unwrap();
+        assert!(config.validate().is_ok());
+
+        let totp = config.build_totp(OTP_SECRET.as_bytes().to_vec()).unwrap();
+        let now = 1_700_000_010;
+        let code = totp.generate(now);
+        assert_eq!(code.len(), 8);
+
+        // the code is accepted up to 2 windows of 30 seconds later
+        assert!(totp.check(&code, now));
+        assert!(totp.check(&code, now + 2 * 30));
+        assert!(!totp.check(&code, now + 3 * 30));
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_totp_params() {
+        for (key, value) in [
+            ("TOTP_ALGORITHM", "MD5"),
+            ("TOTP_DIGITS", "5"),
+            ("TOTP_DIGITS", "9"),
+            ("TOTP_STEP_SECS", "0"),
+            ("TOTP_STEP_SECS", "86400"),
+            ("TOTP_SKEW", "1000"),
+        ] {
+            let mut env = required_env();
+            env.insert(key.to_string(), value.to_string());
+            let config = AppConfig::init_from_hashmap(&env).unwrap();
+
+            assert!(
+                config
+                    .validate()
+                    .is_err_and(|e| e.to_string().contains(key))
+            );
+        }
+    }
+
+    #[test]
+    fn test_cors_allowed_origins_include_providers() {
+        let mut env = required_env();
+        env.insert(
+            "ALLOWED_ORIGINS".to_string(),
+            " https://staging.pet-info.link,,https://accounts.google.com ".to_string(),
+        );
+        let config = AppConfig::init_from_hashmap(&env).unwrap();
+        let origins = config.cors_allowed_origins();
+
+        assert!(config.validate().is_ok());
+        assert!(origins.contains(&"https://staging.pet-info.link".to_string()));
+        assert!(
+            consts::CORS_PROVIDER_ORIGINS
+                .iter()
+                .all(|origin| origins.contains(&origin.to_string()))
+        );
+        assert_eq!(origins.len(), consts::CORS_PROVIDER_ORIGINS.len() + 1);
+    }
+
+    #[test]
+    fn test_validate_rejects_malformed_origin() {
+        for origin in [
+            "pet-info.link",
+            "https://pet-info.link/",
+            "https://pet-info.link/path",
+            "ftp://pet-info.link",
+        ] {
+            let mut env = required_env();
+            env.insert("ALLOWED_ORIGINS".to_string(), origin.to_string());
+            let config = AppConfig::init_from_hashmap(&env).unwrap();
+
+            assert!(
+                config
+                    .validate()
+                    .is_err_and(|e| e.to_string().contains("ALLOWED_ORIGINS"))
+            );
+        }
+    }
+
+    #[test]
+    fn test_notifications_dry_run_is_rejected_in_prod() {
+        let mut env = required_env();
+        env.insert("NOTIFICATIONS_DRY_RUN".to_string(), "true".to_string());
+
+        let config = AppConfig::init_from_hashmap(&env).unwrap();
+        assert!(config.validate().is_ok());
+        assert!(config.is_notifications_dry_run());
+
+        env.insert("ENV".to_string(), "prod".to_string());
+        let config = AppConfig::init_from_hashmap(&env).unwrap();
+        assert!(config.validate().is_err());
+        assert!(!config.is_notifications_dry_run());
+
+        let config = AppConfig::init_from_hashmap(&required_env()).unwrap();
+        assert!(!config.notifications_dry_run);
+    }
+
+    #[test]
+    fn test_db_cipher_defaults_keep_legacy_settings() {
+        let config = AppConfig::init_from_hashmap(&required_env()).unwrap();
+
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            config.db_cipher_kdf_iter(),
+            consts::DB_CIPHER_LEGACY_KDF_ITER
+        );
+        assert_eq!(
+            config.db_cipher_page_size(),
+            consts::DB_CIPHER_LEGACY_PAGE_SIZE
+        );
+        assert_eq!(config.db_cipher_hmac(), Some("SHA1"));
+        assert_eq!(config.weak_db_cipher_settings().len(), 2);
+    }
+
+    #[test]
+    fn test_strong_db_cipher_settings_have_no_warnings() {
+        let mut env = required_env();
+        env.insert("DB_CIPHER_KDF_ITER".to_string(), "256000".to_string());
+        env.insert("DB_CIPHER_PAGE_SIZE".to_string(), "4096".to_string());
+        env.insert("DB_CIPHER_HMAC_ALGORITHM".to_string(), "sha512".to_string());
+        let config = AppConfig::init_from_hashmap(&env).unwrap();
+
+        assert!(config.validate().is_ok());
+        assert_eq!(config.db_cipher_hmac(), Some("SHA512"));
+        assert!(config.weak_db_cipher_settings().is_empty());
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_db_cipher_settings() {
+        for (key, value) in [
+            ("DB_CIPHER_HMAC_ALGORITHM", "MD5"),
+            ("DB_CIPHER_PAGE_SIZE", "1000"),
+            ("DB_CIPHER_PAGE_SIZE", "256"),
+            ("DB_CIPHER_KDF_ITER", "0"),
+        ] {
+            let mut env = required_env();
+            env.insert(key.to_string(), value.to_string());
+            let config = AppConfig::init_from_hashmap(&env).unwrap();
+
+            assert!(
+                config
+                    .validate()
+                    .is_err_and(|e| e.to_string().contains(key))
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_reminder_cancel_max_attempts() {
+        for attempts in ["0", "11"] {
+            let mut env = required_env();
+            env.insert(
+                "REMINDER_CANCEL_MAX_ATTEMPTS".to_string(),
+                attempts.to_string(),
+            );
+            let config = AppConfig::init_from_hashmap(&env).unwrap();
+
+            assert!(
+                config
+                    .validate()
+                    .is_err_and(|e| e.to_string().contains("REMINDER_CANCEL_MAX_ATTEMPTS"))
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_whatsapp_document_max_bytes() {
+        for max_bytes in ["0", "200000000"] {
+            let mut env = required_env();
+            env.insert(
+                "WHATSAPP_DOCUMENT_MAX_BYTES".to_string(),
+                max_bytes.to_string(),
+            );
+            let config = AppConfig::init_from_hashmap(&env).unwrap();
+
+            assert!(
+                config
+                    .validate()
+                    .is_err_and(|e| e.to_string().contains("WHATSAPP_DOCUMENT_MAX_BYTES"))
+            );
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use std::{collections::HashMap, sync::LazyLock};
 
 pub const CSRF_TOKEN_COOKIE_NAME: &str = "csrf_token";
 pub const CSRF_STATE_COOKIE_NAME: &str = "csrf_state";
@@ -8,17 +9,225 @@ pub const OTP_PHONE_COOKIE_NAME: &str = "otp_phone_value";
 pub const GOOGLE_ENDPOINT_USER_INFO: &str = "https://openidconnect.googleapis.com/v1/userinfo";
 pub const GOOGLE_ENDPOINT_REVOKE_TOKEN: &str = "https://oauth2.googleapis.com/revoke";
 pub const ADD_PET_PRICE: Decimal = dec!(100.00);
+/// Hours a used pet creation form token is kept to detect repeated submits.
+pub const PET_CREATION_TOKEN_TTL_HOURS: i64 = 24;
+pub const DEFAULT_CURRENCY: &str = "MXN";
 pub const PIC_PET_MAX_SIZE_BYTES: usize = 6_000_000;
+/// Pictures bigger than this are uploaded to S3 in parts instead of a single put.
+pub const PIC_MULTIPART_THRESHOLD_BYTES: usize = 5 * 1024 * 1024;
+/// Size of each part of a multipart upload, S3 rejects parts (but the last)
+/// smaller than 5 MiB.
+pub const PIC_MULTIPART_PART_SIZE_BYTES: usize = 5 * 1024 * 1024;
+/// Max width/height of an uploaded picture. A small, highly compressed file
+/// can decode to enormous dimensions and exhaust memory (decompression bomb).
+pub const PIC_PET_MAX_DIMENSION_PX: u32 = 10_000;
+/// Max pixels of an uploaded picture, about 160 MB once decoded as RGBA.
+pub const PIC_PET_MAX_PIXELS: u64 = 40_000_000;
+/// Default of [`AppConfig::weight_outlier_factor`](crate::config::AppConfig::weight_outlier_factor),
+/// a new weight this many times above or below the recent average is
+/// likely a typo (e.g. 250 instead of 25.0) and is flagged to the owner.
+pub const WEIGHT_OUTLIER_FACTOR: f64 = 2.0;
+/// Number of latest weights averaged to detect an outlier.
+pub const WEIGHT_OUTLIER_RECENT_COUNT: usize = 5;
+pub const HEALTH_RECORDS_CSV_MAX_SIZE_BYTES: usize = 1_000_000;
 
-/// Apple Wallet pass thumbnail dimensions for @2x Retina displays.
-/// Based on Apple's specification: 90x90 points = 180x180 pixels at @2x.
-/// This size provides optimal quality on most iOS devices while keeping file size minimal.
+/// Apple Wallet pass thumbnail dimensions at standard resolution.
+/// Based on Apple's specification: thumbnails are 90x90 points.
 /// Reference: https://developer.apple.com/library/archive/documentation/UserExperience/Conceptual/PassKit_PG/Creating.html
-pub const PKPASS_THUMBNAIL_SIZE_PX: u32 = 180;
+pub const PKPASS_THUMBNAIL_SIZE_PX: u32 = 90;
+
+/// Scale of the Retina (`thumbnail@2x`) pass thumbnail: 90x90 points = 180x180 pixels.
+pub const PKPASS_RETINA_SCALE: u32 = 2;
+
+/// Size of the circular pet avatar drawn on the QR card.
+pub const QR_CARD_AVATAR_SIZE_PX: u32 = 160;
+
+/// Longest side of the pet picture in a report at share quality; the report
+/// shows it 100pt tall, so this still looks sharp on screens.
+pub const PDF_SHARE_IMAGE_MAX_PX: u32 = 400;
+/// JPEG quality (1-100) of the pet picture in a report at share quality.
+pub const PDF_SHARE_IMAGE_JPEG_QUALITY: u8 = 60;
+
+/// Max number of resized pet pictures kept in memory for QR cards and passes.
+pub const THUMBNAIL_CACHE_CAPACITY: usize = 256;
+
+/// Origins always allowed by CORS: the OAuth and payment providers.
+/// The app's own origins come from [`AppConfig::allowed_origins`](crate::config::AppConfig::allowed_origins).
+pub const CORS_PROVIDER_ORIGINS: [&str; 6] = [
+    "https://openidconnect.googleapis.com",
+    "https://oauth2.googleapis.com",
+    "https://www.googleapis.com",
+    "https://accounts.google.com",
+    "https://graph.facebook.com",
+    "https://api.mercadopago.com",
+];
+
+/// Max length of the title of a pet note.
+pub const PET_NOTE_MAX_TITLE_LEN: usize = 100;
+/// Max length of the (HTML) content of a pet note.
+pub const PET_NOTE_MAX_BODY_LEN: usize = 10_000;
+
+/// Allowed length of the breed typed in the pet form.
+pub const PET_BREED_MIN_LEN: usize = 2;
+pub const PET_BREED_MAX_LEN: usize = 50;
+/// Oldest birthday accepted in the pet form, in years before today.
+pub const PET_MAX_AGE_YEARS: i32 = 40;
+
+/// Max length of the message a finder leaves on a lost pet profile.
+pub const FOUND_REPORT_MAX_MESSAGE_LEN: usize = 500;
+/// Max length of the optional contact a finder leaves to be called back.
+pub const FOUND_REPORT_MAX_CONTACT_LEN: usize = 100;
+/// Found reports allowed per client IP each minute, stricter than the
+/// public profile limit since every report notifies the owner.
+pub const FOUND_REPORT_RATE_LIMIT_PER_MINUTE: u64 = 3;
+
+/// Attempts made for a WhatsApp API request before giving up on transient errors.
+pub const WHATSAPP_MAX_REQUEST_ATTEMPTS: u32 = 3;
+/// Delay before the first WhatsApp retry, doubled on each following attempt.
+pub const WHATSAPP_RETRY_BASE_DELAY_MS: u64 = 500;
+/// Upper bound for a single WhatsApp retry delay, including `Retry-After`.
+pub const WHATSAPP_RETRY_MAX_DELAY_MS: u64 = 10_000;
+/// Delay between the passes stopping the executions of cancelled reminders.
+pub const REMINDER_CANCEL_RETRY_DELAY_MS: u64 = 500;
+/// Passes stopping the executions of cancelled reminders when not configured.
+pub const REMINDER_CANCEL_MAX_ATTEMPTS: u32 = 3;
+
+/// Hard limit of WhatsApp for an uploaded document.
+pub const WHATSAPP_DOCUMENT_MAX_SIZE_BYTES: u64 = 100_000_000;
+/// Biggest PDF report sent as a WhatsApp document when not configured,
+/// bigger ones are sent as a link.
+pub const WHATSAPP_DOCUMENT_SEND_MAX_BYTES: u64 = 16_000_000;
+/// Base URL of the WhatsApp Graph API, used for media uploads and downloads.
+pub const WHATSAPP_GRAPH_API_URL: &str = "https://graph.facebook.com/v22.0";
 
-pub const S3_MAIN_BUCKET_NAME: &str = "pet-info-app-storage";
 pub const DATETIME_LOCAL_INPUT_FORMAT: &str = "%Y-%m-%dT%H:%M";
 
-pub const ACCEPTED_IMAGE_EXTENSIONS: [&str; 4] = ["png", "jpeg", "jpg", "heic"];
+/// Reminder templates offered to every user as (key, name, text), the
+/// `{pet_name}` and `{date}` placeholders are filled when the reminder is created.
+pub const DEFAULT_REMINDER_TEMPLATES: [(&str, &str, &str); 4] = [
+    (
+        "vaccine",
+        "Vacuna",
+        "Toca la vacuna de {pet_name} el {date}",
+    ),
+    (
+        "deworm",
+        "Desparasitación",
+        "Toca desparasitar a {pet_name} el {date}",
+    ),
+    (
+        "vet_visit",
+        "Veterinario",
+        "Cita con el veterinario de {pet_name} el {date}",
+    ),
+    (
+        "grooming",
+        "Estética",
+        "Baño y estética de {pet_name} el {date}",
+    ),
+];
+/// Max length of the name of a reminder template saved by a user.
+pub const REMINDER_TEMPLATE_MAX_NAME_LEN: usize = 50;
+/// Max length of the text of a reminder template saved by a user.
+pub const REMINDER_TEMPLATE_MAX_BODY_LEN: usize = 500;
+
+pub const ACCEPTED_IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpeg", "jpg", "heic", "gif"];
+
+/// Notes loaded per page in the pet notes widget.
+pub const PET_NOTES_PAGE_SIZE: i64 = 10;
+
+/// Max external ids asked in a single public info batch request.
+pub const PET_PUBLIC_INFO_BATCH_MAX_IDS: usize = 50;
+
+/// Entries listed per page in the blog index.
+pub const BLOG_POSTS_PAGE_SIZE: usize = 10;
 
+/// Allowed digits of the PIN that unlocks the owner contacts on a public profile.
+pub const PET_PUBLIC_PIN_MIN_LEN: usize = 4;
+pub const PET_PUBLIC_PIN_MAX_LEN: usize = 8;
+/// Wrong PINs allowed on a public profile before it's blocked.
+pub const PET_PUBLIC_PIN_MAX_FAILED_ATTEMPTS: i64 = 5;
+/// Minutes the PIN of a public profile stays blocked after too many wrong PINs.
+pub const PET_PUBLIC_PIN_LOCKOUT_MINUTES: i64 = 60;
+
+/// Max days a temporary share link of a public profile stays valid.
+pub const PET_SHARE_LINK_MAX_DAYS: i64 = 30;
+/// Random bytes of a share link token, before base64 encoding.
+pub const PET_SHARE_TOKEN_BYTES: usize = 32;
+
+/// Days counted as recent in the public profile scan stats.
+pub const PET_SCAN_RECENT_DAYS: i64 = 7;
+
+/// Days the visits to a public profile are kept before being purged.
+pub const PET_SCAN_RETENTION_DAYS: i64 = 365;
+/// Max chars of the user agent kept per public profile scan.
+pub const PET_SCAN_USER_AGENT_MAX_LEN: usize = 255;
+
+/// Max changes listed in the audit log of a pet, latest first.
+pub const PET_AUDIT_LIST_LIMIT: i64 = 50;
+
+/// Max found reports listed to the owner of a pet, latest first.
+pub const PET_FOUND_REPORT_LIST_LIMIT: i64 = 50;
+
+/// Days fired reminders are still listed in the reminders table.
+pub const REMINDER_HISTORY_DAYS: i64 = 30;
+/// Default minutes a reminder must be ahead of now to be scheduled.
+pub const REMINDER_MIN_LEAD_MINUTES: i64 = 5;
+/// Default days ahead a reminder can be scheduled at most, a bit over a year
+/// so yearly vaccine boosters still fit.
+pub const REMINDER_MAX_HORIZON_DAYS: i64 = 400;
+
+/// Days ahead scanned for due boosters by the weekly vaccine digest.
+pub const VACCINE_DIGEST_WITHIN_DAYS: u32 = 30;
+/// Weekday and hour (owner's local time) the weekly vaccine digest is sent.
+pub const VACCINE_DIGEST_WEEKDAY: chrono::Weekday = chrono::Weekday::Mon;
+pub const VACCINE_DIGEST_HOUR: u32 = 9;
+/// Default timezone of the users whose timezone isn't known.
+pub const DEFAULT_TIMEZONE: chrono_tz::Tz = chrono_tz::Tz::America__Mexico_City;
+
+/// Max phone verification codes sent to a user within
+/// [VERIFICATION_CODE_WINDOW_MINUTES], each one is a WhatsApp message.
+pub const VERIFICATION_CODE_MAX_SENDS: usize = 3;
+pub const VERIFICATION_CODE_WINDOW_MINUTES: i64 = 10;
+/// Bounds of a usable phone verification (TOTP) code window, the code must be
+/// typed before it expires but shouldn't be valid for hours.
+pub const TOTP_TIME_STEP_SECONDS_RANGE: std::ops::RangeInclusive<u64> = 30..=600;
+/// Defaults of the phone verification (TOTP) codes, see [`AppConfig::build_totp`](crate::config::AppConfig::build_totp).
+pub const TOTP_ALGORITHM: &str = "SHA512";
+pub const TOTP_DIGITS: u64 = 6;
+pub const TOTP_TIME_STEP_SECONDS: u64 = 60;
+pub const TOTP_SKEW: u64 = 1;
+
+/// SQLCipher settings the existing databases were created with, still the
+/// defaults so they keep opening; see [`AppConfig::db_cipher_kdf_iter`](crate::config::AppConfig::db_cipher_kdf_iter).
+pub const DB_CIPHER_LEGACY_KDF_ITER: u64 = 64_000;
+pub const DB_CIPHER_LEGACY_PAGE_SIZE: u64 = 1024;
+pub const DB_CIPHER_LEGACY_HMAC_ALGORITHM: &str = "SHA1";
+/// KDF iterations of the SQLCipher 4 defaults, fewer are warned about in prod.
+pub const DB_CIPHER_MIN_KDF_ITER: u64 = 256_000;
+
+/// Decimals kept of the home area coordinates of a contact, 2 is about 1 km
+/// so the public profile never shows the exact home of the owner.
+pub const HOME_AREA_COORDINATE_DECIMALS: i32 = 2;
+/// Zoom of the map linked from the home area of a contact.
+pub const HOME_AREA_MAP_ZOOM: u8 = 14;
+
+/// Default max age of the session and identity cookies, also the CSRF token TTL.
 pub const MAX_AGE_COOKIES: i64 = chrono::TimeDelta::hours(4).num_seconds();
+
+/// Known breeds per species (`dog`, `cat`, `bird`), loaded once from `assets/breeds.json`.
+/// Used to suggest breeds in the pet form; the breed column stays free text.
+pub static PET_BREEDS: LazyLock<HashMap<String, Vec<String>>> = LazyLock::new(|| {
+    serde_json::from_str(include_str!("../assets/breeds.json"))
+        .expect("assets/breeds.json must be a valid species to breeds map")
+});
+
+/// Adult weight range in kilograms `(min, max)` of the known breeds, loaded
+/// once from `assets/breed_weights.json`. Mixed breeds have no range.
+pub static BREED_WEIGHT_RANGES_KG: LazyLock<HashMap<String, (f64, f64)>> = LazyLock::new(|| {
+    serde_json::from_str(include_str!("../assets/breed_weights.json"))
+        .expect("assets/breed_weights.json must be a valid breed to [min, max] map")
+});
+
+/// Prefix of the fake execution ids returned when notifications run in dry-run mode.
+pub const DRY_RUN_EXECUTION_ID_PREFIX: &str = "dry-run:";
@@ -0,0 +1,20 @@
+//! Handlers only available to admin accounts
+
+use crate::{
+    api,
+    front::{AppState, errors, middleware::logged_user::CheckUserIsAdmin},
+};
+use ntex::web;
+
+/// Returns the service usage metrics as JSON
+#[web::get("/metrics")]
+async fn get_usage_metrics(
+    _: CheckUserIsAdmin,
+    app_state: web::types::State<AppState>,
+) -> Result<impl web::Responder, web::Error> {
+    let metrics = api::admin::get_usage_metrics(&app_state.repo)
+        .await
+        .map_err(|e| errors::ServerError::InternalServerError(e.to_string()))?;
+
+    Ok(web::HttpResponse::Ok().json(&metrics))
+}
@@ -1,7 +1,11 @@
 use ntex::web;
 use serde_json::json;
+use std::sync::LazyLock;
 
-use crate::front::{errors, templates};
+use crate::{
+    consts,
+    front::{errors, templates},
+};
 use pulldown_cmark::{Options, Parser};
 
 #[derive(serde::Deserialize, serde::Serialize)]
@@ -10,6 +14,124 @@ struct Blog {
     content: String,
 }
 
+/// Blog entry listed in the blog index
+#[derive(Debug, Clone, serde::Serialize)]
+struct BlogPost {
+    /// Markdown file name without extension, used in the entry url
+    name: String,
+    /// First `# ` heading of the entry, its name if it has none
+    title: String,
+    /// Raw markdown, only used for searching
+    #[serde(skip)]
+    content: String,
+}
+
+/// A page of the blog index
+#[derive(Debug, serde::Serialize)]
+struct BlogPostsPage {
+    posts: Vec<BlogPost>,
+    page: usize,
+    /// Next page number, `None` when this is the last one
+    next_page: Option<usize>,
+}
+
+/// Index of the markdown entries in `web/blog/`, sorted by name.
+/// The entries are static files, so it is built once.
+static BLOG_INDEX: LazyLock<Vec<BlogPost>> = LazyLock::new(|| {
+    let mut names: Vec<&str> = templates::BLOG_TEMPLATES.get_template_names().collect();
+    names.sort_unstable();
+
+    names
+        .into_iter()
+        .filter_map(|template_name| {
+            let name = template_name.strip_suffix(".md")?;
+            let content = templates::BLOG_TEMPLATES
+                .render(template_name, &tera::Context::new())
+                .ok()?;
+
+            Some(blog_post(name, content))
+        })
+        .collect()
+});
+
+fn blog_post(name: &str, content: String) -> BlogPost {
+    let title = content
+        .lines()
+        .find_map(|line| line.strip_prefix("# "))
+        .map(|title| title.trim().to_string())
+        .unwrap_or_else(|| name.to_string());
+
+    BlogPost {
+        name: name.to_string(),
+        title,
+        content,
+    }
+}
+
+/// Gets a page of the blog entries whose title or content contain `query`,
+/// ignoring case. An empty query lists all the entries.
+///
+/// # Arguments
+/// * `posts` - Blog index to search
+/// * `page` - Page number, starting at 1
+/// * `query` - Text typed by the user
+fn get_blog_posts(posts: &[BlogPost], page: usize, query: &str) -> BlogPostsPage {
+    let page = page.max(1);
+    let query = query.trim().to_lowercase();
+
+    let mut matches = posts.iter().filter(|post| {
+        query.is_empty()
+            || post.title.to_lowercase().contains(&query)
+            || post.content.to_lowercase().contains(&query)
+    });
+
+    let posts: Vec<BlogPost> = matches
+        .by_ref()
+        .skip((page - 1) * consts::BLOG_POSTS_PAGE_SIZE)
+        .take(consts::BLOG_POSTS_PAGE_SIZE)
+        .cloned()
+        .collect();
+    let next_page = matches.next().map(|_| page + 1);
+
+    BlogPostsPage {
+        posts,
+        page,
+        next_page,
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct BlogIndexQuery {
+    #[serde(default)]
+    page: usize,
+    #[serde(default)]
+    q: String,
+}
+
+/// Lists the blog entries, `?q=` filters them by title or content
+#[web::get("")]
+async fn get_blog_index(
+    query: web::types::Query<BlogIndexQuery>,
+) -> Result<impl web::Responder, web::Error> {
+    let context = tera::Context::from_value(json!({
+        "blog_page": get_blog_posts(&BLOG_INDEX, query.page, &query.q),
+        "q": query.q,
+    }))
+    .unwrap_or_default();
+
+    let content = templates::WEB_TEMPLATES
+        .render("blog_index.html", &context)
+        .map_err(|e| {
+            errors::ServerError::TemplateError(format!(
+                "at /blog endpoint the template couldnt be rendered: {e}"
+            ))
+        })?;
+
+    Ok(web::HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(content))
+}
+
 #[web::get("{blog_name}")]
 async fn get_blog_entry(
     path: web::types::Path<(String,)>,
@@ -46,3 +168,71 @@ async fn get_blog_entry(
         .content_type("text/html; charset=utf-8")
         .body(content))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn posts(count: usize) -> Vec<BlogPost> {
+        (0..count)
+            .map(|i| blog_post(&format!("entry{i:02}"), format!("# Entrada {i}\n\ntexto")))
+            .collect()
+    }
+
+    #[test]
+    fn test_blog_post_title_from_heading() {
+        assert_eq!(
+            blog_post("about", "# Por que pet-info?\n".into()).title,
+            "Por que pet-info?"
+        );
+        assert_eq!(
+            blog_post("questions", "<details>".into()).title,
+            "questions"
+        );
+    }
+
+    #[test]
+    fn test_get_blog_posts_pagination() {
+        let posts = posts(consts::BLOG_POSTS_PAGE_SIZE + 3);
+
+        let first = get_blog_posts(&posts, 0, "");
+        assert_eq!(first.page, 1);
+        assert_eq!(first.posts.len(), consts::BLOG_POSTS_PAGE_SIZE);
+        assert_eq!(first.next_page, Some(2));
+
+        let second = get_blog_posts(&posts, 2, "");
+        assert_eq!(second.posts.len(), 3);
+        assert_eq!(
+            second.posts[0].name,
+            format!("entry{:02}", consts::BLOG_POSTS_PAGE_SIZE)
+        );
+        assert_eq!(second.next_page, None);
+
+        assert!(get_blog_posts(&posts, 3, "").posts.is_empty());
+    }
+
+    #[test]
+    fn test_get_blog_posts_search() {
+        let mut posts = posts(3);
+        posts.push(blog_post(
+            "privacy",
+            "# Política de privacidad\n\nGoogle Oauth".into(),
+        ));
+
+        let by_title = get_blog_posts(&posts, 1, "  PRIVACIDAD ");
+        assert_eq!(by_title.posts.len(), 1);
+        assert_eq!(by_title.posts[0].name, "privacy");
+
+        let by_content = get_blog_posts(&posts, 1, "oauth");
+        assert_eq!(by_content.posts.len(), 1);
+
+        assert_eq!(get_blog_posts(&posts, 1, "texto").posts.len(), 3);
+        assert!(get_blog_posts(&posts, 1, "perdida").posts.is_empty());
+    }
+
+    #[test]
+    fn test_blog_index_lists_markdown_entries() {
+        let names: Vec<&str> = BLOG_INDEX.iter().map(|post| post.name.as_str()).collect();
+        assert!(names.contains(&"about") && names.contains(&"terms"));
+    }
+}
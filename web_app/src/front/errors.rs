@@ -1,12 +1,13 @@
-use super::templates;
+use super::{middleware::request_id::RequestId, templates};
+use crate::repo::errors::RepoError;
 use derive_more::{Display, Error};
-use log::error;
 use ntex::{http, web};
 
 #[derive(Debug, Display, Error)]
 pub enum UserError {
     UrlNotFound,
     Unauthorized,
+    Forbidden,
     NeedSubscription,
     FormInputValueError(#[error(not(source))] String),
 }
@@ -24,6 +25,10 @@ impl web::error::WebResponseError for UserError {
                 context.insert("msg_details", "favor de iniciar sesion");
                 "errors/need_login.html"
             }
+            UserError::Forbidden => {
+                context.insert("msg_details", "no tiene permisos para ver este recurso");
+                "errors/url_not_found.html"
+            }
             UserError::NeedSubscription => {
                 context.insert("msg_details", "su perido de prueba a terminado");
                 "errors/need_subscription.html"
@@ -51,6 +56,7 @@ impl web::error::WebResponseError for UserError {
         match *self {
             UserError::UrlNotFound => http::StatusCode::NOT_FOUND,
             UserError::Unauthorized => http::StatusCode::UNAUTHORIZED,
+            UserError::Forbidden => http::StatusCode::FORBIDDEN,
             UserError::NeedSubscription => http::StatusCode::PAYMENT_REQUIRED,
             UserError::FormInputValueError(_) => http::StatusCode::BAD_REQUEST,
         }
@@ -79,8 +85,16 @@ impl ServerError {
 }
 
 impl web::error::WebResponseError for ServerError {
-    fn error_response(&self, _: &web::HttpRequest) -> web::HttpResponse {
-        logfire::error!("error={error}", error = self.get_error_message());
+    fn error_response(&self, req: &web::HttpRequest) -> web::HttpResponse {
+        let request_id = RequestId::of(req).unwrap_or_default();
+        logfire::error!(
+            "request_id={request_id} error={error}",
+            request_id = request_id.clone(),
+            error = self.get_error_message()
+        );
+
+        let mut context = tera::Context::new();
+        context.insert("request_id", &request_id);
 
         let template_name = match self {
             // will be a success status code cause it htmx should render something
@@ -92,7 +106,7 @@ impl web::error::WebResponseError for ServerError {
             .set_header("content-type", "text/html; charset=utf-8")
             .body(
                 templates::WEB_TEMPLATES
-                    .render(template_name, &tera::Context::new())
+                    .render(template_name, &context)
                     .unwrap_or(self.to_string()),
             )
     }
@@ -105,3 +119,34 @@ impl web::error::WebResponseError for ServerError {
         }
     }
 }
+
+/// Maps an error raised by the api layer to the response error:
+/// a missing record renders a 404, any other error is an internal error
+/// logged with `context`.
+pub fn not_found_or_internal(e: anyhow::Error, context: &str) -> web::Error {
+    if RepoError::is_not_found(&e) {
+        return UserError::UrlNotFound.into();
+    }
+
+    ServerError::InternalServerError(format!("{context}: {e}")).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_not_found_or_internal_status() {
+        let not_found = not_found_or_internal(RepoError::NotFound.into(), "getting the pet");
+        let internal = not_found_or_internal(anyhow::anyhow!("db is down"), "getting the pet");
+
+        assert_eq!(
+            not_found.as_response_error().status_code(),
+            http::StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            internal.as_response_error().status_code(),
+            http::StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
@@ -1,6 +1,7 @@
-use crate::models;
-use chrono::{NaiveDate, Utc};
+use crate::{consts, models};
+use chrono::{Datelike, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 use uuid::Uuid;
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
@@ -20,8 +21,164 @@ pub struct CreatePetForm {
     pub is_spaying_neutering: bool,
     pub is_female: bool,
     pub about_pet: String,
+    pub microchip: Option<String>,
     pub pet_pic: Option<crate::models::Pic>,
     pub pet_external_id: Option<Uuid>,
+    /// Token of the rendered create form, a repeated token is a double submit
+    pub idempotency_token: Option<Uuid>,
+}
+
+/// Sets a text field of the [CreatePetForm] from its multipart value
+type CreatePetFormSetter = fn(&mut CreatePetForm, String) -> anyhow::Result<()>;
+
+/// Text fields of the pet form by their exact multipart name
+const CREATE_PET_FORM_FIELDS: &[(&str, CreatePetFormSetter)] = &[
+    ("pet_full_name", |form, value| {
+        form.pet_full_name = value;
+        Ok(())
+    }),
+    ("pet_birthday", |form, value| {
+        form.pet_birthday = NaiveDate::parse_from_str(&value, "%Y-%m-%d")?;
+        Ok(())
+    }),
+    ("pet_breed", |form, value| {
+        form.pet_breed = value;
+        Ok(())
+    }),
+    ("is_lost", |form, value| {
+        form.is_lost = value.contains("on");
+        Ok(())
+    }),
+    ("is_spaying_neutering", |form, value| {
+        form.is_spaying_neutering = value.contains("on");
+        Ok(())
+    }),
+    ("is_female", |form, value| {
+        form.is_female = value.contains("on");
+        Ok(())
+    }),
+    ("about_pet", |form, value| {
+        form.about_pet = value;
+        Ok(())
+    }),
+    ("microchip", |form, value| {
+        form.microchip = parse_microchip(&value)?;
+        Ok(())
+    }),
+    ("pet_external_id", |form, value| {
+        form.pet_external_id = Some(Uuid::from_str(&value).unwrap_or(Uuid::new_v4()));
+        Ok(())
+    }),
+    ("idempotency_token", |form, value| {
+        form.idempotency_token = Uuid::from_str(&value).ok();
+        Ok(())
+    }),
+];
+
+/// Normalizes the microchip number typed in the pet form, spaces and dashes
+/// are ignored.
+///
+/// # Returns
+/// * `Ok(None)` - Empty value, the pet has no microchip
+/// * `Ok(Some(chip))` - A 15 digit ISO 11784/11785 chip or a 10 char legacy
+///   one (e.g. AVID), in uppercase
+/// * `Err` - Any other format
+pub fn parse_microchip(value: &str) -> anyhow::Result<Option<String>> {
+    let chip: String = value
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect::<String>()
+        .to_uppercase();
+
+    let is_iso = chip.len() == 15 && chip.chars().all(|c| c.is_ascii_digit());
+    let is_legacy = chip.len() == 10 && chip.chars().all(|c| c.is_ascii_alphanumeric());
+
+    match chip.is_empty() {
+        true => Ok(None),
+        false if is_iso || is_legacy => Ok(Some(chip)),
+        false => anyhow::bail!(
+            "microchip invalido, debe tener 15 digitos (ISO) o 10 caracteres (anterior)"
+        ),
+    }
+}
+
+/// Invalid value of a pet form field, the message is shown to the user
+#[derive(Debug, Clone, PartialEq, Eq, derive_more::Display)]
+#[display("{field}: {message}")]
+pub struct FieldError {
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl FieldError {
+    fn new(field: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            field,
+            message: message.into(),
+        }
+    }
+}
+
+impl CreatePetForm {
+    /// Checks the form before it is persisted
+    ///
+    /// # Returns
+    /// * `Ok(())` - Every field is valid
+    /// * `Err(Vec<FieldError>)` - One error per invalid field
+    pub fn validate(&self) -> Result<(), Vec<FieldError>> {
+        self.validate_at(Utc::now().date_naive())
+    }
+
+    fn validate_at(&self, today: NaiveDate) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+
+        if self.pet_full_name.trim().is_empty() {
+            errors.push(FieldError::new("pet_full_name", "el nombre es obligatorio"));
+        }
+
+        let oldest_birthday = today
+            .with_year(today.year() - consts::PET_MAX_AGE_YEARS)
+            .unwrap_or(NaiveDate::MIN);
+        if self.pet_birthday == NaiveDate::default() {
+            errors.push(FieldError::new(
+                "pet_birthday",
+                "la fecha de nacimiento es obligatoria",
+            ));
+        } else if self.pet_birthday > today || self.pet_birthday < oldest_birthday {
+            errors.push(FieldError::new(
+                "pet_birthday",
+                "la fecha de nacimiento no es valida",
+            ));
+        }
+
+        let breed_len = self.pet_breed.trim().chars().count();
+        if !(consts::PET_BREED_MIN_LEN..=consts::PET_BREED_MAX_LEN).contains(&breed_len) {
+            errors.push(FieldError::new(
+                "pet_breed",
+                format!(
+                    "la raza debe tener entre {} y {} caracteres",
+                    consts::PET_BREED_MIN_LEN,
+                    consts::PET_BREED_MAX_LEN
+                ),
+            ));
+        }
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+
+    /// Sets the text field named exactly `field_name`, unknown fields are ignored
+    pub fn set_field(&mut self, field_name: &str, value: String) -> anyhow::Result<()> {
+        match CREATE_PET_FORM_FIELDS
+            .iter()
+            .find(|(name, _)| *name == field_name)
+        {
+            Some((_, setter)) => setter(self, value),
+            None => Ok(()),
+        }
+    }
 }
 
 impl From<CreatePetForm> for models::pet::Pet {
@@ -36,6 +193,7 @@ impl From<CreatePetForm> for models::pet::Pet {
             is_female: val.is_female,
             is_lost: val.is_lost,
             is_spaying_neutering: val.is_spaying_neutering,
+            microchip: val.microchip,
             external_id: Uuid::new_v4(),
             created_at: now,
             updated_at: now,
@@ -56,8 +214,185 @@ pub struct PetNoteForm {
     pub body: String,
 }
 
+#[derive(Deserialize)]
+pub struct PetLostForm {
+    pub is_lost: bool,
+}
+
+#[derive(Deserialize)]
+pub struct PetSpayingNeuteringForm {
+    pub is_spaying_neutering: bool,
+}
+
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct HealthRecordForm {
     pub value: String,
     pub date: chrono::NaiveDate,
+    #[serde(default, deserialize_with = "deserialize_optional_months")]
+    pub booster_months: Option<u32>,
+}
+
+fn deserialize_optional_months<'de, D>(deserializer: D) -> Result<Option<u32>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let buf = String::deserialize(deserializer)?;
+    if buf.trim().is_empty() {
+        return Ok(None);
+    }
+
+    buf.trim()
+        .parse::<u32>()
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+/// PIN typed by a visitor to see the owner contacts
+#[derive(Deserialize)]
+pub struct UnlockContactsForm {
+    pub pin: String,
+}
+
+/// Message left on a lost pet profile by whoever found it
+#[derive(Debug, Deserialize)]
+pub struct FoundReportForm {
+    pub message: String,
+    #[serde(default)]
+    pub callback_contact: String,
+    /// Honeypot field hidden from people, bots filling it are ignored
+    #[serde(default)]
+    pub website: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_pet_form_set_field_exact_name() {
+        let mut form = CreatePetForm::default();
+
+        form.set_field("pet_full_name", "Firulais".into()).unwrap();
+        form.set_field("is_lost", "on".into()).unwrap();
+        form.set_field("pet_birthday", "2020-05-17".into()).unwrap();
+        // names containing a known field are not that field
+        form.set_field("pet_name", "Otro".into()).unwrap();
+        form.set_field("is_female_x", "on".into()).unwrap();
+
+        assert_eq!(form.pet_full_name, "Firulais");
+        assert!(form.is_lost);
+        assert!(!form.is_female);
+        assert_eq!(
+            form.pet_birthday,
+            NaiveDate::from_ymd_opt(2020, 5, 17).unwrap()
+        );
+        assert!(form.set_field("pet_birthday", "17/05/2020".into()).is_err());
+    }
+
+    #[test]
+    fn test_parse_microchip_formats() {
+        assert_eq!(
+            parse_microchip("982 000-123 456 789").unwrap().as_deref(),
+            Some("982000123456789")
+        );
+        assert_eq!(
+            parse_microchip("0a1b2c3d4e").unwrap().as_deref(),
+            Some("0A1B2C3D4E")
+        );
+        assert_eq!(parse_microchip("  ").unwrap(), None);
+
+        for invalid in [
+            "98200012345678",
+            "9820001234567890",
+            "98200012345678X",
+            "0A1B2C3D4",
+            "0A1B2C3D4É",
+        ] {
+            assert!(parse_microchip(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_create_pet_form_rejects_malformed_microchip() {
+        let mut form = CreatePetForm::default();
+
+        assert!(form.set_field("microchip", "12345".into()).is_err());
+        assert_eq!(form.microchip, None);
+
+        form.set_field("microchip", "982000123456789".into())
+            .unwrap();
+        assert_eq!(form.microchip.as_deref(), Some("982000123456789"));
+    }
+
+    fn valid_form() -> CreatePetForm {
+        CreatePetForm {
+            pet_full_name: "Firulais".into(),
+            pet_birthday: NaiveDate::from_ymd_opt(2020, 5, 17).unwrap(),
+            pet_breed: "Labrador".into(),
+            ..CreatePetForm::default()
+        }
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 1, 1).unwrap()
+    }
+
+    fn invalid_fields(form: &CreatePetForm) -> Vec<&'static str> {
+        form.validate_at(today())
+            .unwrap_err()
+            .iter()
+            .map(|e| e.field)
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_accepts_valid_form() {
+        assert!(valid_form().validate_at(today()).is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_blank_name() {
+        let form = CreatePetForm {
+            pet_full_name: "   ".into(),
+            ..valid_form()
+        };
+
+        assert_eq!(invalid_fields(&form), vec!["pet_full_name"]);
+    }
+
+    #[test]
+    fn test_validate_rejects_missing_or_invalid_birthday() {
+        for birthday in [
+            NaiveDate::default(),
+            NaiveDate::from_ymd_opt(2026, 1, 2).unwrap(),
+            NaiveDate::from_ymd_opt(1985, 12, 31).unwrap(),
+        ] {
+            let form = CreatePetForm {
+                pet_birthday: birthday,
+                ..valid_form()
+            };
+
+            assert_eq!(invalid_fields(&form), vec!["pet_birthday"], "{birthday}");
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_breed_out_of_bounds() {
+        for breed in ["", " a ", &"a".repeat(consts::PET_BREED_MAX_LEN + 1)] {
+            let form = CreatePetForm {
+                pet_breed: breed.to_string(),
+                ..valid_form()
+            };
+
+            assert_eq!(invalid_fields(&form), vec!["pet_breed"], "{breed}");
+        }
+    }
+
+    #[test]
+    fn test_validate_reports_every_invalid_field() {
+        assert_eq!(
+            invalid_fields(&CreatePetForm::default()),
+            vec!["pet_full_name", "pet_birthday", "pet_breed"]
+        );
+    }
 }
@@ -8,6 +8,65 @@ pub struct UserReminderForm {
     #[serde(deserialize_with = "deserialize_when_user_input")]
     pub when: NaiveDateTime,
     pub body: String,
+    #[serde(default)]
+    pub notify_whatsapp: bool,
+    #[serde(default)]
+    pub notify_email: bool,
+    /// Template picked to write the body when it's left empty
+    #[serde(default)]
+    pub template: String,
+    /// Pet the reminder is about, fills `{pet_name}` in the template
+    #[serde(default, deserialize_with = "deserialize_optional_uuid")]
+    pub pet_external_id: Option<uuid::Uuid>,
+}
+
+impl UserReminderForm {
+    /// Channels picked by the user, WhatsApp if none was picked
+    pub fn channels(&self) -> crate::models::reminder::ReminderChannels {
+        use crate::models::reminder::{ReminderChannels, ReminderNotificationType};
+
+        let channels: ReminderChannels = [
+            (self.notify_whatsapp, ReminderNotificationType::WhatsApp),
+            (self.notify_email, ReminderNotificationType::Email),
+        ]
+        .into_iter()
+        .filter_map(|(picked, channel)| picked.then_some(channel))
+        .collect();
+
+        if channels.is_empty() {
+            return ReminderChannels::default();
+        }
+        channels
+    }
+}
+
+/// New time of a scheduled reminder
+#[derive(serde::Deserialize, Debug)]
+pub struct ReminderRescheduleForm {
+    #[serde(deserialize_with = "deserialize_when_user_input")]
+    pub when: NaiveDateTime,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub struct ReminderPreviewQuery {
+    #[serde(deserialize_with = "deserialize_when_user_input")]
+    pub when: NaiveDateTime,
+    /// Optional days between occurrences
+    pub every_days: Option<u64>,
+}
+
+fn deserialize_optional_uuid<'de, D>(deserializer: D) -> Result<Option<uuid::Uuid>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let buf = String::deserialize(deserializer)?;
+    if buf.trim().is_empty() {
+        return Ok(None);
+    }
+
+    uuid::Uuid::parse_str(buf.trim())
+        .map(Some)
+        .map_err(serde::de::Error::custom)
 }
 
 fn deserialize_when_user_input<'de, D>(deserializer: D) -> Result<NaiveDateTime, D::Error>
@@ -20,6 +79,12 @@ where
         .map_err(serde::de::Error::custom)
 }
 
+#[derive(serde::Deserialize, Debug)]
+pub struct ReminderTemplateForm {
+    pub name: String,
+    pub body: String,
+}
+
 #[derive(serde::Deserialize, Debug)]
 pub struct ReminderPhoneToVerify {
     pub country_phone_code: u32,
@@ -30,3 +95,8 @@ pub struct ReminderPhoneToVerify {
 pub struct ReminderPhoneOtp {
     pub otp_value: String,
 }
+
+#[derive(serde::Deserialize, Debug)]
+pub struct WeightUnitForm {
+    pub weight_unit: crate::models::user_app::WeightUnit,
+}
@@ -0,0 +1,94 @@
+//! Liveness and readiness probes used by Nginx and the load balancer.
+//!
+//! ## Routes
+//! - `GET /healthz` - Always 200 while the process is up
+//! - `GET /readyz` - 200 when the config is loaded and the database answers, 503 otherwise
+
+use ntex::web;
+use sqlx::SqlitePool;
+
+use crate::config;
+
+/// Checks the app can serve requests: config loaded and database reachable.
+///
+/// # Returns
+/// * `Result<(), String>` - `Err` with the reason the app is not ready
+async fn check_readiness(db_pool: &SqlitePool, is_config_ready: bool) -> Result<(), String> {
+    if !is_config_ready {
+        return Err("app config is not initialized".into());
+    }
+
+    sqlx::query("SELECT 1;")
+        .execute(db_pool)
+        .await
+        .map_err(|e| format!("database is unreachable: {e}"))?;
+
+    Ok(())
+}
+
+/// Liveness probe, answers as long as the server is running
+#[web::get("/healthz")]
+async fn healthz() -> web::HttpResponse {
+    web::HttpResponse::Ok().body("ok")
+}
+
+/// Readiness probe, answers 503 when the database can't be queried
+///
+/// The reason is only logged, the route is public and the database errors
+/// must not reach the client.
+#[web::get("/readyz")]
+async fn readyz(db_pool: web::types::State<SqlitePool>) -> web::HttpResponse {
+    match check_readiness(&db_pool, config::APP_CONFIG.initialized()).await {
+        Ok(()) => web::HttpResponse::Ok().body("ready"),
+        Err(reason) => {
+            logfire::warn!("readiness check failed: {reason}", reason = reason);
+            web::HttpResponse::ServiceUnavailable().body("not ready")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ntex::{http::StatusCode, web::test};
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_pool() -> SqlitePool {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+    }
+
+    #[ntex::test]
+    async fn test_health_endpoints() {
+        let app = test::init_service(
+            web::App::new()
+                .state(setup_test_pool().await)
+                .service((healthz, readyz)),
+        )
+        .await;
+
+        let res =
+            test::call_service(&app, test::TestRequest::with_uri("/healthz").to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // APP_CONFIG is never initialized in tests
+        let res =
+            test::call_service(&app, test::TestRequest::with_uri("/readyz").to_request()).await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(test::read_body(res).await, "not ready");
+    }
+
+    #[ntex::test]
+    async fn test_check_readiness_queries_database() {
+        let db_pool = setup_test_pool().await;
+
+        assert!(check_readiness(&db_pool, true).await.is_ok());
+        assert!(check_readiness(&db_pool, false).await.is_err());
+
+        db_pool.close().await;
+        assert!(check_readiness(&db_pool, true).await.is_err());
+    }
+}
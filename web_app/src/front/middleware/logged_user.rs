@@ -7,7 +7,7 @@ use ntex::{
 };
 use ntex_identity::RequestIdentity;
 
-use crate::front;
+use crate::{front, models};
 
 /// Every logged request must have the serialized user [session](crate::front::session::WebAppSession)
 /// this block will extract the logged user session data from the [request](ntex::web::HttpRequest)
@@ -24,12 +24,43 @@ impl<Err> FromRequest<Err> for front::session::WebAppSession {
 }
 
 /// Checks if the request is made by a user with a valid membership
+///
+/// Whether a subscription is needed depends on the user role
+/// [capabilities](crate::models::user_app::Capabilities).
 pub struct CheckUserCanAccessService;
 
+/// Checks if the request is made by a user whose role can download reports and exports
+pub struct CheckUserCanExport;
+
+/// Checks if the request is made by a user whose role can use WhatsApp reminders
+pub struct CheckUserCanUseWhatsApp;
+
+/// Checks if the request is made by a user with the admin role
+pub struct CheckUserIsAdmin;
+
 /// Checks if the request is made by a user with
 /// valid conditions to edit its own data
 pub struct IsUserLoggedAndCanEdit(pub bool, pub Option<i64>);
 
+/// Unit the weights are shown in: the preference of the logged user,
+/// kilograms for visitors
+pub struct PreferredWeightUnit(pub models::user_app::WeightUnit);
+
+impl<Err> FromRequest<Err> for PreferredWeightUnit {
+    type Error = Error;
+
+    fn from_request(
+        req: &HttpRequest,
+        _: &mut Payload,
+    ) -> impl std::future::Future<Output = Result<Self, Self::Error>> {
+        let weight_unit = get_logged_user_session(req.get_identity())
+            .map(|session| session.user.weight_unit)
+            .unwrap_or_default();
+
+        futures::future::ready(Ok(Self(weight_unit)))
+    }
+}
+
 impl<Err> FromRequest<Err> for IsUserLoggedAndCanEdit {
     type Error = Error;
 
@@ -62,6 +93,74 @@ impl<Err> FromRequest<Err> for CheckUserCanAccessService {
     }
 }
 
+impl<Err> FromRequest<Err> for CheckUserCanExport {
+    type Error = Error;
+
+    fn from_request(
+        req: &HttpRequest,
+        _: &mut Payload,
+    ) -> impl std::future::Future<Output = Result<Self, Self::Error>> {
+        let identity_cookie = req.get_identity();
+        futures::future::ready(
+            check_capability(identity_cookie, |capabilities| capabilities.can_export).map(|_| Self),
+        )
+    }
+}
+
+impl<Err> FromRequest<Err> for CheckUserCanUseWhatsApp {
+    type Error = Error;
+
+    fn from_request(
+        req: &HttpRequest,
+        _: &mut Payload,
+    ) -> impl std::future::Future<Output = Result<Self, Self::Error>> {
+        let identity_cookie = req.get_identity();
+        futures::future::ready(
+            check_capability(identity_cookie, |capabilities| {
+                capabilities.can_use_whatsapp
+            })
+            .map(|_| Self),
+        )
+    }
+}
+
+impl<Err> FromRequest<Err> for CheckUserIsAdmin {
+    type Error = Error;
+
+    fn from_request(
+        req: &HttpRequest,
+        _: &mut Payload,
+    ) -> impl std::future::Future<Output = Result<Self, Self::Error>> {
+        let identity_cookie = req.get_identity();
+        futures::future::ready(check_is_admin(identity_cookie).map(|_| Self))
+    }
+}
+
+/// Checks the logged user has the admin role, any other logged user is forbidden
+fn check_is_admin(auth_cookie: Option<String>) -> Result<(), Error> {
+    let session = get_logged_user_session(auth_cookie)?;
+
+    if session.user.account_role == models::user_app::AccountRole::Admin {
+        Ok(())
+    } else {
+        Err(front::errors::UserError::Forbidden.into())
+    }
+}
+
+/// Checks the logged user role has the capability selected by `has_capability`
+fn check_capability(
+    auth_cookie: Option<String>,
+    has_capability: impl Fn(&crate::models::user_app::Capabilities) -> bool,
+) -> Result<(), Error> {
+    let session = get_logged_user_session(auth_cookie)?;
+
+    if has_capability(&session.user.capabilities()) {
+        Ok(())
+    } else {
+        Err(front::errors::UserError::NeedSubscription.into())
+    }
+}
+
 fn serialize_logged_user_session(str: &str) -> serde_json::Result<front::session::WebAppSession> {
     serde_json::from_str::<front::session::WebAppSession>(str)
 }
@@ -81,3 +180,40 @@ fn check_can_edit(auth_cookie: Option<String>) -> IsUserLoggedAndCanEdit {
         })
         .unwrap_or(IsUserLoggedAndCanEdit(false, None))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::user_app::{AccountRole, User};
+
+    fn session_cookie(account_role: AccountRole) -> Option<String> {
+        let mut user = User::create_default_from_email("test@example.com");
+        user.account_role = account_role;
+
+        serde_json::to_string(&front::session::WebAppSession {
+            user,
+            add_pet_balance: 0,
+        })
+        .ok()
+    }
+
+    #[test]
+    fn test_check_is_admin() {
+        assert!(check_is_admin(session_cookie(AccountRole::Admin)).is_ok());
+        assert_eq!(
+            check_is_admin(None)
+                .unwrap_err()
+                .as_response_error()
+                .status_code(),
+            ntex::http::StatusCode::UNAUTHORIZED
+        );
+
+        for account_role in [AccountRole::User, AccountRole::Premium, AccountRole::Staff] {
+            let err = check_is_admin(session_cookie(account_role)).unwrap_err();
+            assert_eq!(
+                err.as_response_error().status_code(),
+                ntex::http::StatusCode::FORBIDDEN
+            );
+        }
+    }
+}
@@ -1,2 +1,4 @@
 pub mod csrf_token;
 pub mod logged_user;
+pub mod rate_limit;
+pub mod request_id;
@@ -0,0 +1,242 @@
+//! Rate limiting middleware for unauthenticated routes.
+//!
+//! Requests are keyed by client IP, taken from the headers the Nginx reverse
+//! proxy sets, and limited with a token bucket that refills
+//! `requests_per_minute` tokens every minute.
+
+use ntex::{
+    http::HeaderMap,
+    service::{Middleware, Service, ServiceCtx},
+    web,
+};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Number of tracked clients before idle buckets are pruned
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Token bucket for a single client.
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: f64, now: Instant) -> Self {
+        Self {
+            tokens: capacity,
+            last_refill: now,
+        }
+    }
+
+    /// Adds the tokens earned since the last refill, capped at `capacity`.
+    fn refill(&mut self, now: Instant, capacity: f64, refill_per_sec: f64) {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * refill_per_sec).min(capacity);
+        self.last_refill = now;
+    }
+
+    /// Consumes a token if available.
+    ///
+    /// # Returns
+    /// * `Result<(), Duration>` - `Err` with the wait time until the next token
+    fn try_consume(
+        &mut self,
+        now: Instant,
+        capacity: f64,
+        refill_per_sec: f64,
+    ) -> Result<(), Duration> {
+        self.refill(now, capacity, refill_per_sec);
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+
+        Err(Duration::from_secs_f64(
+            (1.0 - self.tokens) / refill_per_sec,
+        ))
+    }
+}
+
+/// Middleware factory that limits requests per client IP.
+///
+/// Buckets are shared between clones so every server worker applies the
+/// same limit to a client.
+#[derive(Clone)]
+pub struct RateLimit {
+    capacity: f64,
+    refill_per_sec: f64,
+    buckets: Arc<Mutex<HashMap<String, TokenBucket>>>,
+}
+
+impl RateLimit {
+    /// Creates a limiter allowing `requests_per_minute` requests per client.
+    pub fn new(requests_per_minute: u64) -> Self {
+        let capacity = requests_per_minute.max(1) as f64;
+        Self {
+            capacity,
+            refill_per_sec: capacity / 60.0,
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Checks the bucket of `client_key` at `now`.
+    ///
+    /// # Returns
+    /// * `Result<(), Duration>` - `Err` with the time to wait if the limit was exceeded
+    fn check(&self, client_key: &str, now: Instant) -> Result<(), Duration> {
+        let Ok(mut buckets) = self.buckets.lock() else {
+            return Ok(());
+        };
+
+        if buckets.len() >= MAX_TRACKED_CLIENTS {
+            buckets.retain(|_, bucket| {
+                bucket.refill(now, self.capacity, self.refill_per_sec);
+                bucket.tokens < self.capacity
+            });
+        }
+
+        buckets
+            .entry(client_key.to_string())
+            .or_insert_with(|| TokenBucket::new(self.capacity, now))
+            .try_consume(now, self.capacity, self.refill_per_sec)
+    }
+}
+
+/// Extracts the client IP the Nginx proxy saw: its `X-Real-IP`, else the
+/// last `X-Forwarded-For` entry, the one it appended. Earlier entries are sent
+/// by the client and can't be trusted. Falls back to the peer address.
+fn client_ip(headers: &HeaderMap, peer_addr: Option<SocketAddr>) -> String {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+
+    header("x-real-ip")
+        .or_else(|| header("x-forwarded-for").and_then(|value| value.rsplit(',').next()))
+        .map(|ip| ip.trim().to_string())
+        .filter(|ip| !ip.is_empty())
+        .or_else(|| peer_addr.map(|addr| addr.ip().to_string()))
+        .unwrap_or_default()
+}
+
+impl<S> Middleware<S> for RateLimit {
+    type Service = RateLimitMiddleware<S>;
+
+    fn create(&self, service: S) -> Self::Service {
+        RateLimitMiddleware {
+            service,
+            limiter: self.clone(),
+        }
+    }
+}
+
+pub struct RateLimitMiddleware<S> {
+    service: S,
+    limiter: RateLimit,
+}
+
+impl<S, Err> Service<web::WebRequest<Err>> for RateLimitMiddleware<S>
+where
+    S: Service<web::WebRequest<Err>, Response = web::WebResponse, Error = web::Error>,
+    Err: web::ErrorRenderer,
+{
+    type Response = web::WebResponse;
+    type Error = web::Error;
+
+    ntex::forward_ready!(service);
+
+    async fn call(
+        &self,
+        req: web::WebRequest<Err>,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        if let Err(retry_after) = self
+            .limiter
+            .check(&client_ip(req.headers(), req.peer_addr()), Instant::now())
+        {
+            return Ok(req.into_response(
+                web::HttpResponse::TooManyRequests()
+                    .set_header(
+                        "Retry-After",
+                        (retry_after.as_secs_f64().ceil() as u64).max(1).to_string(),
+                    )
+                    .finish(),
+            ));
+        }
+
+        ctx.call(&self.service, req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket_refill() {
+        let start = Instant::now();
+        let capacity = 2.0;
+        let refill_per_sec = capacity / 60.0;
+        let mut bucket = TokenBucket::new(capacity, start);
+
+        assert!(bucket.try_consume(start, capacity, refill_per_sec).is_ok());
+        assert!(bucket.try_consume(start, capacity, refill_per_sec).is_ok());
+
+        let retry_after = bucket
+            .try_consume(start, capacity, refill_per_sec)
+            .unwrap_err();
+        assert_eq!(retry_after.as_secs(), 30);
+
+        // Half a minute later a single token has been refilled
+        let later = start + Duration::from_secs(30);
+        assert!(bucket.try_consume(later, capacity, refill_per_sec).is_ok());
+        assert!(bucket.try_consume(later, capacity, refill_per_sec).is_err());
+
+        // Refill never exceeds the bucket capacity
+        let much_later = later + Duration::from_secs(600);
+        bucket.refill(much_later, capacity, refill_per_sec);
+        assert_eq!(bucket.tokens, capacity);
+    }
+
+    #[test]
+    fn test_rate_limit_is_per_client() {
+        let limiter = RateLimit::new(1);
+        let now = Instant::now();
+
+        assert!(limiter.check("10.0.0.1", now).is_ok());
+        assert!(limiter.check("10.0.0.1", now).is_err());
+        assert!(limiter.check("10.0.0.2", now).is_ok());
+    }
+
+    #[test]
+    fn test_client_ip_ignores_spoofed_forwarded_for() {
+        let peer_addr = Some(SocketAddr::from(([10, 0, 0, 9], 4321)));
+        let headers = |pairs: &[(&'static str, &'static str)]| {
+            let mut headers = HeaderMap::new();
+            for (name, value) in pairs {
+                headers.insert(
+                    ntex::http::header::HeaderName::from_static(name),
+                    ntex::http::header::HeaderValue::from_static(value),
+                );
+            }
+            headers
+        };
+
+        // the client prepends whatever it wants, the proxy appends the real one
+        for spoofed in ["1.1.1.1, 203.0.113.7", "2.2.2.2, 203.0.113.7"] {
+            let forwarded = headers(&[("x-forwarded-for", spoofed)]);
+            assert_eq!(client_ip(&forwarded, peer_addr), "203.0.113.7");
+
+            let forwarded = headers(&[("x-forwarded-for", spoofed), ("x-real-ip", "203.0.113.8")]);
+            assert_eq!(client_ip(&forwarded, peer_addr), "203.0.113.8");
+        }
+
+        assert_eq!(client_ip(&headers(&[]), peer_addr), "10.0.0.9");
+    }
+}
@@ -0,0 +1,157 @@
+//! Request id middleware.
+//!
+//! Every request gets an id, taken from the incoming `X-Request-Id` header
+//! when it's valid or generated otherwise. The id is stored in the request
+//! extensions, recorded in a span covering the whole request and returned
+//! in the `X-Request-Id` response header, so logged errors can be matched
+//! with the request that raised them.
+
+use ntex::{
+    http::header::{HeaderName, HeaderValue},
+    service::{Middleware, Service, ServiceCtx},
+    web,
+};
+use tracing::Instrument;
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Max length accepted for an inbound request id
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Id of the current request, available in the request extensions
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    /// Reuses `inbound` when it's a short printable ASCII value, so ids set by
+    /// the reverse proxy are kept, otherwise a new id is generated.
+    fn from_inbound(inbound: Option<&str>) -> Self {
+        match inbound.map(str::trim) {
+            Some(id)
+                if !id.is_empty()
+                    && id.len() <= MAX_REQUEST_ID_LEN
+                    && id.chars().all(|c| c.is_ascii_graphic()) =>
+            {
+                Self(id.to_string())
+            }
+            _ => Self(Uuid::new_v4().to_string()),
+        }
+    }
+
+    /// Id of the request, if it went through [`AssignRequestId`]
+    pub fn of(req: &web::HttpRequest) -> Option<String> {
+        req.extensions().get::<Self>().map(|id| id.0.clone())
+    }
+}
+
+/// Middleware factory that assigns a [`RequestId`] to every request.
+#[derive(Clone, Default)]
+pub struct AssignRequestId;
+
+impl<S> Middleware<S> for AssignRequestId {
+    type Service = AssignRequestIdMiddleware<S>;
+
+    fn create(&self, service: S) -> Self::Service {
+        AssignRequestIdMiddleware { service }
+    }
+}
+
+pub struct AssignRequestIdMiddleware<S> {
+    service: S,
+}
+
+impl<S, Err> Service<web::WebRequest<Err>> for AssignRequestIdMiddleware<S>
+where
+    S: Service<web::WebRequest<Err>, Response = web::WebResponse, Error = web::Error>,
+    Err: web::ErrorRenderer,
+{
+    type Response = web::WebResponse;
+    type Error = web::Error;
+
+    ntex::forward_ready!(service);
+
+    async fn call(
+        &self,
+        req: web::WebRequest<Err>,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let request_id = RequestId::from_inbound(
+            req.headers()
+                .get(REQUEST_ID_HEADER)
+                .and_then(|value| value.to_str().ok()),
+        );
+        req.extensions_mut().insert(request_id.clone());
+
+        let span = logfire::span!(
+            "{method} {path}",
+            method = req.method().to_string(),
+            path = req.path().to_string(),
+            request_id = request_id.0.clone()
+        );
+
+        let mut res = ctx.call(&self.service, req).instrument(span).await?;
+
+        if let Ok(value) = HeaderValue::from_str(&request_id.0) {
+            res.headers_mut()
+                .insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+        }
+
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ntex::web::test;
+
+    async fn call_with_request_id(inbound: Option<&str>) -> Option<String> {
+        let app = test::init_service(web::App::new().wrap(AssignRequestId).route(
+            "/",
+            web::get().to(|req: web::HttpRequest| async move {
+                web::HttpResponse::Ok().body(RequestId::of(&req).unwrap_or_default())
+            }),
+        ))
+        .await;
+
+        let mut req = test::TestRequest::default();
+        if let Some(inbound) = inbound {
+            req = req.header(REQUEST_ID_HEADER, inbound);
+        }
+        let res = test::call_service(&app, req.to_request()).await;
+
+        let header = res
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .map(|value| value.to_str().unwrap().to_string());
+        let body = test::read_body(res).await;
+        assert_eq!(header.as_deref().map(str::as_bytes), Some(body.as_ref()));
+
+        header
+    }
+
+    #[ntex::test]
+    async fn test_request_id_header_is_generated() {
+        let request_id = call_with_request_id(None).await.unwrap();
+
+        assert!(Uuid::parse_str(&request_id).is_ok());
+    }
+
+    #[ntex::test]
+    async fn test_request_id_reuses_inbound_id() {
+        assert_eq!(
+            call_with_request_id(Some("abc-123")).await.as_deref(),
+            Some("abc-123")
+        );
+    }
+
+    #[test]
+    fn test_request_id_rejects_invalid_inbound_id() {
+        let too_long = "a".repeat(MAX_REQUEST_ID_LEN + 1);
+
+        for inbound in ["", "   ", "con espacio", too_long.as_str()] {
+            assert_ne!(RequestId::from_inbound(Some(inbound)).0, inbound);
+        }
+    }
+}
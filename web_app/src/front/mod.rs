@@ -1,23 +1,30 @@
+pub mod admin;
 pub mod auth;
 pub mod blog;
 pub mod checkout;
 pub mod errors;
 pub mod forms;
+pub mod health;
 pub mod middleware;
 pub mod oauth;
 pub mod pet;
+pub mod pet_audit;
+pub mod pet_found_report;
 pub mod pet_health;
 pub mod pet_note;
 pub mod pet_public;
+pub mod pet_share;
+pub mod pet_transfer;
 pub mod profile;
 pub mod reminder;
 pub mod routes;
+pub mod sanitize;
 pub mod server;
 pub mod session;
 pub mod templates;
 pub mod utils;
 
-use crate::{repo, services, webhook};
+use crate::{api, repo, services, webhook};
 use csrf::AesGcmCsrfProtection;
 
 pub struct AppState {
@@ -26,4 +33,39 @@ pub struct AppState {
     pub storage_service: services::ImplStorageService,
     pub notification_service: services::ImplNotificationService,
     pub whatsapp_client: webhook::whatsapp::client::WhatsAppClient,
+    pub thumbnail_cache: api::thumbnail::ThumbnailCache,
+    pub profile_cache: api::profile_cache::PublicProfileCache,
+    pub clock: services::ImplClock,
+    pub verification_throttle: api::verification_throttle::VerificationThrottle,
+}
+
+/// App state shared by the handler tests
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use super::*;
+    use crate::{
+        repo::MockAppRepo,
+        services::test_utils::{FakeNotificationService, FakeStorageService},
+        webhook::whatsapp::client::test_utils::test_client,
+    };
+
+    /// App state around `mock_repo`, with fakes of the other services
+    pub fn app_state(mock_repo: MockAppRepo) -> AppState {
+        AppState {
+            csrf_protec: AesGcmCsrfProtection::from_key([0; 32]),
+            repo: Box::new(mock_repo),
+            storage_service: Box::<FakeStorageService>::default(),
+            notification_service: Box::<FakeNotificationService>::default(),
+            whatsapp_client: test_client("http://127.0.0.1:1"),
+            thumbnail_cache: api::thumbnail::ThumbnailCache::new(1),
+            profile_cache: api::profile_cache::PublicProfileCache::new(
+                std::time::Duration::from_secs(1),
+            ),
+            clock: Box::new(services::clock::SystemClock),
+            verification_throttle: api::verification_throttle::VerificationThrottle::new(
+                1,
+                chrono::TimeDelta::minutes(1),
+            ),
+        }
+    }
 }
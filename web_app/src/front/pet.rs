@@ -7,68 +7,68 @@
 //! - `GET /pet` - Pet dashboard showing all user's pets
 //! - `GET /pet/list` - HTMX endpoint for pets list widget
 //! - `GET /pet/new` - Form for creating new pets
+//! - `GET /pet/breeds` - Autocomplete of known breeds
+//! - `GET /pet/external-id/{uuid}/status` - Whether an external id exists and is linked
+//! - `GET /pet/scans/{pet_external_id}` - Public profile visits widget
 //! - `POST /pet/new` - Handle pet creation
 //! - `DELETE /pet/delete/{pet_id}` - Delete a pet
+//! - `POST /pet/rotate-id/{pet_id}` - Replace the pet's public external id
+//! - `POST /pet/public-pin/{pet_id}` - Set the PIN that hides the owner contacts
+//! - `POST /pet/lost/{pet_id}` - Mark a pet as lost or found
+//! - `POST /pet/spaying/{pet_id}` - Set the spaying/neutering status of a pet
 //! - `GET /pet/details/{pet_id}` - Form for editing pet details
 //! - `POST /pet/details/{pet_id}` - Handle pet updates
 //! - `GET /pet/qr_code/{pet_external_id}` - Generate QR code for pet profile
-//! - `GET /pet/pdf_report/{pet_id}` - Generate PDF report
+//! - `GET /pet/pdf_report/{pet_id}?sections=&quality=` - Generate PDF report
+//! - `GET /pet/vaccine_cert/{pet_id}?signature=` - Generate vaccine certificate PDF
+//! - `GET /pet/weights.csv/{pet_id}` - Download weight history as CSV
 //! - `GET /pet/public_pic/{pet_external_id}` - Serve public pet pictures
 //! - `GET /pet/pass/{pet_external_id}` - Generate Apple Wallet pass
+//! - `GET /pet/pass_preview/{pet_external_id}` - Preview of the Apple Wallet pass
 //!
 //! # Security
 //! Most routes require authentication and user ownership validation.
 //! CSRF protection is enabled for state-changing operations.
 
-use anyhow::{Context, bail};
+use anyhow::Context;
 use futures::{TryStreamExt, future::ok, stream::once};
-use ntex::{util::Bytes, web};
+use ntex::{http::header, util::Bytes, web};
 use serde_json::json;
-use std::str::FromStr;
 use uuid::Uuid;
 
 use crate::{
     api, config, consts,
-    front::{AppState, errors, forms, middleware, session, templates, utils},
+    front::{AppState, errors, forms, middleware, sanitize, session, templates, utils},
 };
 
-/// Safely extracts header value as string from HTTP headers
-///
-/// # Arguments
-/// * `headers` - HTTP headers map
-/// * `key` - Header key to extract
-///
-/// # Returns
-/// String value of the header or empty string if not found/invalid
-fn get_header_str_value(headers: &ntex::http::HeaderMap, key: &str) -> String {
-    let default_header_value = ntex::http::header::HeaderValue::from_static("");
-
-    headers
-        .get(key)
-        .unwrap_or(&default_header_value)
-        .to_str()
-        .unwrap_or_default()
-        .to_string()
+/// Checks if the field contains an image for pet picture upload
+fn is_image_field(field: &ntex_multipart::Field, field_name: &str) -> bool {
+    field.content_type().essence_str().contains("image") && field_name == "pet_pic"
 }
 
-/// Checks if the field contains an image for pet picture upload
-fn is_image_field(field: &ntex_multipart::Field, content_disposition: &str) -> bool {
-    field.content_type().essence_str().contains("image") && content_disposition.contains("pet_pic")
+/// Checks the picture format, detected from its magic bytes, is one of
+/// [`consts::ACCEPTED_IMAGE_EXTENSIONS`]. The declared content type is not trusted.
+fn check_accepted_image_format(body: &[u8]) -> anyhow::Result<()> {
+    match crate::utils::sniff_image_format(body) {
+        Some(format) if consts::ACCEPTED_IMAGE_EXTENSIONS.contains(&format) => Ok(()),
+        format => anyhow::bail!(
+            "formato de imagen no soportado ({}), extensiones validas: {}",
+            format.unwrap_or("desconocido"),
+            consts::ACCEPTED_IMAGE_EXTENSIONS.join(", ")
+        ),
+    }
 }
 
-/// Processes an image field, validating size and extracting file data
+/// Processes an image field, validating format and size and extracting file data
+///
+/// HEIC pictures (iPhone uploads) are converted to PNG so they can be cropped,
+/// animated GIFs keep only their first frame, other formats are rotated
+/// according to their EXIF orientation.
 async fn process_image_field(field: ntex_multipart::Field) -> anyhow::Result<crate::models::Pic> {
     let body = utils::get_bytes_value(field).await;
+    check_accepted_image_format(&body)?;
 
-    // Validate image size
-    if body.len() > consts::PIC_PET_MAX_SIZE_BYTES {
-        bail!(
-            "Image is too big. Maximum size: {} bytes",
-            consts::PIC_PET_MAX_SIZE_BYTES
-        );
-    }
-
-    Ok(body)
+    utils::prepare_pet_pic(&body)
 }
 
 /// Deserializes multipart form data into a pet creation form
@@ -85,8 +85,10 @@ async fn process_image_field(field: ntex_multipart::Field) -> anyhow::Result<cra
 ///
 /// # Image Processing
 /// - Validates image size against `PIC_PET_MAX_SIZE_BYTES` limit
+/// - Validates image dimensions against `PIC_PET_MAX_DIMENSION_PX` limit
 /// - Applies circular cropping if cropper coordinates are provided
-/// - Sanitizes all text inputs with ammonia
+/// - Sanitizes all text inputs with ammonia, the `about` with the
+///   [`sanitize::rich_text`] allowlist
 async fn deserialize_pet_form(
     mut payload: ntex_multipart::Multipart,
 ) -> anyhow::Result<super::forms::pet::CreatePetForm> {
@@ -97,35 +99,23 @@ async fn deserialize_pet_form(
     let mut pet_pic: Option<crate::models::Pic> = None;
 
     while let Ok(Some(field)) = payload.try_next().await {
-        let headers = field.headers();
-
-        let content_disposition = get_header_str_value(headers, "content-disposition");
+        let field_name = utils::get_multipart_field_name(&field);
 
-        if is_image_field(&field, &content_disposition) {
+        if is_image_field(&field, &field_name) {
             pet_pic = Some(process_image_field(field).await?);
             continue;
         }
 
-        let field_value = ammonia::clean(&utils::get_field_value(field).await);
-
-        if content_disposition.contains("pet_full_name") {
-            form.pet_full_name = field_value;
-        } else if content_disposition.contains("pet_birthday") {
-            form.pet_birthday = chrono::NaiveDate::parse_from_str(&field_value, "%Y-%m-%d")?;
-        } else if content_disposition.contains("pet_breed") {
-            form.pet_breed = field_value;
-        } else if content_disposition.contains("is_lost") {
-            form.is_lost = field_value.contains("on");
-        } else if content_disposition.contains("is_spaying_neutering") {
-            form.is_spaying_neutering = field_value.contains("on");
-        } else if content_disposition.contains("is_female") {
-            form.is_female = field_value.contains("on");
-        } else if content_disposition.contains("about_pet") {
-            form.about_pet = field_value;
-        } else if content_disposition.contains("pet_external_id") {
-            form.pet_external_id = Some(Uuid::from_str(&field_value).unwrap_or(Uuid::new_v4()));
-        } else if content_disposition.contains("cropper_box") {
+        let field_value = utils::get_field_value(field).await;
+        let field_value = match field_name.as_str() {
+            "about_pet" => sanitize::rich_text(&field_value),
+            _ => ammonia::clean(&field_value),
+        };
+
+        if field_name == "cropper_box" {
             cropper_box = serde_json::from_str(&field_value)?;
+        } else {
+            form.set_field(&field_name, field_value)?;
         }
     }
 
@@ -157,10 +147,13 @@ async fn deserialize_pet_form(
 #[web::get("")]
 async fn get_pet_view(
     session::WebAppSession { user, .. }: session::WebAppSession,
+    r: web::HttpRequest,
     app_state: web::types::State<AppState>,
 ) -> Result<impl web::Responder, web::Error> {
+    let user_timezone = utils::user_timezone(r.headers());
+
     let context = tera::Context::from_value(json!({
-        "pets": api::pet::get_user_pets_cards(user.id, &app_state.repo)
+        "pets": api::pet::get_user_pets_cards(user.id, user_timezone, &app_state.repo)
         .await
         .map_err(|e| {
             errors::ServerError::InternalServerError(format!(
@@ -197,10 +190,13 @@ async fn get_pet_view(
 #[web::get("/list")]
 async fn user_pets_list(
     session::WebAppSession { user, .. }: session::WebAppSession,
+    r: web::HttpRequest,
     app_state: web::types::State<AppState>,
 ) -> Result<impl web::Responder, web::Error> {
+    let user_timezone = utils::user_timezone(r.headers());
+
     let context = tera::Context::from_value(json!({
-        "pets": api::pet::get_user_pets_cards(user.id, &app_state.repo)
+        "pets": api::pet::get_user_pets_cards(user.id, user_timezone, &app_state.repo)
         .await
         .map_err(|e| {
             errors::ServerError::InternalServerError(format!(
@@ -253,6 +249,7 @@ async fn render_pet_details_form(
                 "PIC_PET_MAX_SIZE_BYTES": consts::PIC_PET_MAX_SIZE_BYTES,
                 "ACCEPTED_IMAGE_EXTENSIONS": consts::ACCEPTED_IMAGE_EXTENSIONS,
                 "pet_external_id": q.pet_external_id,
+                "idempotency_token": Uuid::new_v4(),
             }))
             .unwrap_or_default(),
         )
@@ -267,6 +264,23 @@ async fn render_pet_details_form(
         .body(content))
 }
 
+/// Rejects a pet form with invalid fields before it reaches persistence
+///
+/// # Returns
+/// * `Err(web::Error)` - User error listing every invalid field
+fn validate_pet_form(form: &forms::pet::CreatePetForm) -> Result<(), web::Error> {
+    form.validate().map_err(|field_errors| {
+        errors::UserError::FormInputValueError(
+            field_errors
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
+        .into()
+    })
+}
+
 /// Handles pet creation form submission
 ///
 /// Creates a new pet if the user has sufficient balance or is linking
@@ -299,27 +313,55 @@ async fn create_pet_request(
     let pet_form = deserialize_pet_form(payload)
         .await
         .map_err(|e| errors::UserError::FormInputValueError(e.to_string()))?;
+    validate_pet_form(&pet_form)?;
 
     let request_has_pet_external_id = pet_form.pet_external_id.is_some();
+    let app_config = config::APP_CONFIG
+        .get()
+        .context("failed to get app config")
+        .map_err(web::error::ErrorInternalServerError)?;
 
-    if !(request_has_pet_external_id
-        || user_session.has_pet_balance() && user_session.user.can_access_service())
+    if !request_has_pet_external_id
+        && !api::pet::user_can_add_pet(
+            &user_session.user,
+            user_session.add_pet_balance,
+            &app_state.repo,
+        )
+        .await
+        .map_err(|e| {
+            errors::ServerError::InternalServerError(format!(
+                "function user_can_add_pet raised an error: {e}"
+            ))
+        })?
     {
         return Err(errors::UserError::NeedSubscription.into());
     }
 
-    api::pet::add_new_pet_to_user(
+    let pet_created = api::pet::add_new_pet_to_user(
         api::pet::UserStateAddNewPet {
             user_id: user_session.user.id,
             user_email: user_session.user.email.to_string(),
             pet_balance: user_session.add_pet_balance,
+            max_pets: app_config.max_pets_per_user,
         },
         pet_form,
         &app_state.repo,
         &app_state.storage_service,
     )
     .await
-    .map_err(|e| errors::ServerError::InternalServerError(e.to_string()))?;
+    .map_err(|e| -> web::Error {
+        match e.downcast_ref::<api::pet::AddPetError>() {
+            Some(add_pet_error) => {
+                errors::UserError::FormInputValueError(add_pet_error.to_string()).into()
+            }
+            None => errors::ServerError::InternalServerError(e.to_string()).into(),
+        }
+    })?;
+
+    // a repeated submit keeps the session updated by the first one
+    if !pet_created {
+        return utils::redirect_to("/pet");
+    }
 
     user_session.add_pet_balance -=
         u32::from(!request_has_pet_external_id && user_session.add_pet_balance > 0);
@@ -330,6 +372,111 @@ async fn create_pet_request(
     utils::redirect_to("/pet")
 }
 
+#[derive(serde::Deserialize)]
+struct BreedQuery {
+    species: Option<String>,
+    #[serde(default)]
+    q: String,
+}
+
+/// Autocomplete of known breeds for the pet form
+///
+/// # Query Parameters
+/// * `species` - Optional species (`dog`, `cat`, `bird`), all species if missing
+/// * `q` - Text typed by the user
+///
+/// # Returns
+/// JSON with the matching `breeds` and a `did_you_mean` suggestion when
+/// the typed text looks like a misspelled known breed
+#[web::get("/breeds")]
+async fn search_breeds(
+    _: session::WebAppSession,
+    query: web::types::Query<BreedQuery>,
+) -> Result<impl web::Responder, web::Error> {
+    let species = query.species.as_deref();
+
+    Ok(web::HttpResponse::Ok().json(&json!({
+        "breeds": api::breed::search_breeds(species, &query.q),
+        "did_you_mean": api::breed::did_you_mean(species, &query.q),
+    })))
+}
+
+/// Status of an external id, used by the pet form to validate it live
+/// before submitting
+///
+/// It reveals no more than the public profile `/info/{uuid}` already does,
+/// so no session is required
+///
+/// # Returns
+/// * `Ok(HttpResponse)` - JSON with `exists` and `is_linked`; 400 when the
+///   id is not a valid UUID
+/// * `Err(web::Error)` - Server error if the lookup fails
+#[web::get("/external-id/{pet_external_id}/status")]
+async fn get_pet_external_id_status(
+    app_state: web::types::State<AppState>,
+    path: web::types::Path<(String,)>,
+) -> Result<impl web::Responder, web::Error> {
+    let Ok(pet_external_id) = Uuid::parse_str(&path.0) else {
+        return Ok(web::HttpResponse::BadRequest().json(&json!({
+            "error": "identificador invalido",
+        })));
+    };
+
+    let external_id_metadata =
+        api::pet::get_pet_external_id_metadata(&pet_external_id, &app_state.repo)
+            .await
+            .map_err(|e| {
+                errors::ServerError::InternalServerError(format!(
+                    "function get_pet_external_id_metadata raised an error: {e}"
+                ))
+            })?;
+
+    Ok(web::HttpResponse::Ok().json(&json!({
+        "exists": external_id_metadata.is_some(),
+        "is_linked": external_id_metadata.is_some_and(|m| m.is_linked),
+    })))
+}
+
+/// HTMX endpoint returning how many times the public profile of a pet
+/// was visited, the owner's own views excluded
+///
+/// # Authentication
+/// Requires valid user session, only the owner gets the pet visits
+///
+/// # Returns
+/// * `Ok(HttpResponse)` - HTML widget with the total and recent visits
+/// * `Err(web::Error)` - Server error if database operation fails
+#[web::get("/scans/{pet_external_id}")]
+async fn get_pet_scan_stats(
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    path: web::types::Path<(Uuid,)>,
+    app_state: web::types::State<AppState>,
+) -> Result<impl web::Responder, web::Error> {
+    let context = tera::Context::from_value(json!({
+        "stats": api::pet::get_pet_scan_stats(path.0, user.id, &app_state.repo)
+            .await
+            .map_err(|e| {
+                errors::ServerError::InternalServerError(format!(
+                    "function get_pet_scan_stats raised an error: {e}"
+                ))
+            })?,
+        "recent_days": consts::PET_SCAN_RECENT_DAYS,
+    }))
+    .unwrap_or_default();
+
+    let content = templates::WEB_TEMPLATES
+        .render("widgets/pet_scan_stats.html", &context)
+        .map_err(|e| {
+            errors::ServerError::TemplateError(format!(
+                "at /pet/scans endpoint the template couldnt be rendered: {e}"
+            ))
+        })?;
+
+    Ok(web::HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(content))
+}
+
 /// Deletes a pet and all associated data
 ///
 /// Removes the pet and cascades deletion to all related records:
@@ -358,9 +505,176 @@ async fn delete_pet(
     path: web::types::Path<(i64,)>,
 ) -> Result<impl web::Responder, web::Error> {
     let pet_id = path.0;
-    api::pet::delete_pet_and_its_info(pet_id, user.id, &app_state.repo)
+    api::pet::delete_pet_and_its_info(
+        pet_id,
+        user.id,
+        &app_state.repo,
+        &app_state.notification_service,
+        &app_state.profile_cache,
+    )
+    .await
+    .map_err(|e| errors::ServerError::InternalServerError(e.to_string()))?;
+
+    Ok(web::HttpResponse::Ok()
+        .set_header("HX-Trigger", "petRecordUpdated")
+        .finish())
+}
+
+/// Replaces the public external id of a pet
+///
+/// Used when a physical QR tag is lost or compromised. The old external id
+/// stops resolving to the pet; health records, weights and notes are kept.
+///
+/// # Security
+/// - Requires service access (subscription)
+/// - Requires CSRF token
+/// - Validates user ownership
+///
+/// # Returns
+/// * `Ok(HttpResponse)` - Success response with HTMX trigger
+/// * `Err(web::Error)` - Server error if the rotation fails
+#[web::post("/rotate-id/{pet_id}")]
+async fn rotate_pet_external_id(
+    _: middleware::logged_user::CheckUserCanAccessService,
+    _: middleware::csrf_token::CsrfToken,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    app_state: web::types::State<AppState>,
+    path: web::types::Path<(i64,)>,
+) -> Result<impl web::Responder, web::Error> {
+    api::pet::rotate_pet_external_id(path.0, user.id, &app_state.repo, &app_state.profile_cache)
+        .await
+        .map_err(|e| {
+            errors::ServerError::InternalServerError(format!(
+                "function rotate_pet_external_id raised an error: {e}"
+            ))
+        })?;
+
+    Ok(web::HttpResponse::Ok()
+        .set_header("HX-Trigger", "petRecordUpdated")
+        .finish())
+}
+
+/// Marks a pet as lost or found in one click
+///
+/// Only the lost status is written, the rest of the pet is left untouched.
+///
+/// # Security
+/// - Requires service access (subscription)
+/// - Requires CSRF token
+/// - Validates user ownership
+///
+/// # Returns
+/// * `Ok(HttpResponse)` - Success response with HTMX trigger
+/// * `Err(web::Error)` - Server error if the update fails
+#[web::post("/lost/{pet_id}")]
+async fn set_pet_lost_status(
+    _: middleware::logged_user::CheckUserCanAccessService,
+    _: middleware::csrf_token::CsrfToken,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    app_state: web::types::State<AppState>,
+    path: web::types::Path<(i64,)>,
+    form: web::types::Form<forms::pet::PetLostForm>,
+) -> Result<impl web::Responder, web::Error> {
+    api::pet::set_pet_lost_status(
+        path.0,
+        user.id,
+        form.is_lost,
+        &app_state.repo,
+        &app_state.profile_cache,
+    )
+    .await
+    .map_err(|e| {
+        errors::ServerError::InternalServerError(format!(
+            "function set_pet_lost_status raised an error: {e}"
+        ))
+    })?;
+
+    Ok(web::HttpResponse::Ok()
+        .set_header("HX-Trigger", "petRecordUpdated")
+        .finish())
+}
+
+/// Sets the spaying/neutering status of a pet in one click
+///
+/// Only the spaying/neutering status is written, the rest of the pet is left untouched.
+///
+/// # Security
+/// - Requires service access (subscription)
+/// - Requires CSRF token
+/// - Validates user ownership
+///
+/// # Returns
+/// * `Ok(HttpResponse)` - Success response with HTMX trigger
+/// * `Err(web::Error)` - Server error if the update fails
+#[web::post("/spaying/{pet_id}")]
+async fn set_pet_spaying_neutering_status(
+    _: middleware::logged_user::CheckUserCanAccessService,
+    _: middleware::csrf_token::CsrfToken,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    app_state: web::types::State<AppState>,
+    path: web::types::Path<(i64,)>,
+    form: web::types::Form<forms::pet::PetSpayingNeuteringForm>,
+) -> Result<impl web::Responder, web::Error> {
+    api::pet::set_pet_spaying_neutering_status(
+        path.0,
+        user.id,
+        form.is_spaying_neutering,
+        &app_state.repo,
+        &app_state.profile_cache,
+    )
+    .await
+    .map_err(|e| {
+        errors::ServerError::InternalServerError(format!(
+            "function set_pet_spaying_neutering_status raised an error: {e}"
+        ))
+    })?;
+
+    Ok(web::HttpResponse::Ok()
+        .set_header("HX-Trigger", "petRecordUpdated")
+        .finish())
+}
+
+/// Sets the PIN visitors must enter to see the owner contacts on the
+/// public profile, an empty PIN removes it
+///
+/// The PIN arrives in the `HX-Prompt` header.
+///
+/// # Security
+/// - Requires service access (subscription)
+/// - Requires CSRF token
+/// - Only the owner can change it
+///
+/// # Returns
+/// * `Ok(HttpResponse)` - Success response with HTMX trigger
+/// * `Err(web::Error)` - Form error for an invalid PIN, server error otherwise
+#[web::post("/public-pin/{pet_id}")]
+async fn set_pet_public_pin(
+    _: middleware::logged_user::CheckUserCanAccessService,
+    _: middleware::csrf_token::CsrfToken,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    app_state: web::types::State<AppState>,
+    path: web::types::Path<(i64,)>,
+    r: web::HttpRequest,
+) -> Result<impl web::Responder, web::Error> {
+    let pin = r
+        .headers()
+        .get("HX-Prompt")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    api::pet::set_pet_public_pin(path.0, user.id, pin, &app_state.repo)
         .await
-        .map_err(|e| errors::ServerError::InternalServerError(e.to_string()))?;
+        .map_err(|e| -> web::Error {
+            match e.downcast_ref::<api::pet::PublicPinError>() {
+                Some(pin_error) => {
+                    errors::UserError::FormInputValueError(pin_error.to_string()).into()
+                }
+                None => errors::ServerError::InternalServerError(format!(
+                    "function set_pet_public_pin raised an error: {e}"
+                ))
+                .into(),
+            }
+        })?;
 
     Ok(web::HttpResponse::Ok()
         .set_header("HX-Trigger", "petRecordUpdated")
@@ -370,7 +684,7 @@ async fn delete_pet(
 /// Generates and streams QR code card for pet's public profile
 ///
 /// Creates a beautiful QR code card with the pet's picture, QR code, and branding.
-/// Falls back to simple QR code if pet picture is not available.
+/// Pets without picture get a default avatar with their initials.
 ///
 /// # Path Parameters
 /// * `pet_external_id` - UUID of the pet's external identifier
@@ -401,19 +715,21 @@ async fn get_profile_qr_code(
         external_id = pet_external_id
     );
 
-    // Try to get pet picture
-    let pet_pic =
-        api::pet::get_public_pic(pet_external_id, &app_state.repo, &app_state.storage_service)
-            .await
-            .ok()
-            .flatten();
-
-    // Generate QR code card with picture if available, otherwise simple QR code
-    let qr_code = if let Some(ref pic) = pet_pic {
-        crate::qr::build_qr_card_with_pic(pic, &url)
-    } else {
-        crate::qr::get_qr_code(&url)
-    }
+    let caption = api::pet::get_pet_qr_caption(pet_external_id, &app_state.repo)
+        .await
+        .unwrap_or_default();
+
+    // QR code card with the picture, or a default avatar if there is none
+    let qr_code = api::pet::build_pet_qr_code(
+        pet_external_id,
+        &caption.pet_name,
+        &caption.breed,
+        &url,
+        &app_state.repo,
+        &app_state.storage_service,
+        &app_state.thumbnail_cache,
+    )
+    .await
     .map_err(|e| {
         errors::ServerError::InternalServerError(format!("qr_code could not be generated: {}", e))
     })?;
@@ -426,6 +742,17 @@ async fn get_profile_qr_code(
         .streaming(body))
 }
 
+/// Query parameters for the PDF report
+#[derive(serde::Deserialize)]
+struct PdfReportQuery {
+    /// Comma separated sections to include (`vaccines,deworms,weights,notes`)
+    #[serde(default)]
+    sections: String,
+    /// `print` (default) or `share` for a lighter file with a compressed picture
+    #[serde(default)]
+    quality: api::pet::PdfQuality,
+}
+
 /// Generates and streams a comprehensive PDF report for a pet
 ///
 /// Creates a formatted PDF document containing:
@@ -438,6 +765,11 @@ async fn get_profile_qr_code(
 /// # Path Parameters
 /// * `pet_id` - Internal database ID of the pet
 ///
+/// # Query Parameters
+/// * `sections` - Optional comma separated sections to include, the full
+///   report is generated if missing
+/// * `quality` - Optional `print` or `share` preset, `print` if missing
+///
 /// # Security
 /// - Requires service access (subscription)
 /// - Validates user ownership of the pet
@@ -451,13 +783,22 @@ async fn get_profile_qr_code(
 #[web::get("pdf_report/{pet_id}")]
 async fn get_pdf_report(
     _: middleware::logged_user::CheckUserCanAccessService,
+    _: middleware::logged_user::CheckUserCanExport,
     path: web::types::Path<(i64,)>,
+    query: web::types::Query<PdfReportQuery>,
     session::WebAppSession { user, .. }: session::WebAppSession,
+    r: web::HttpRequest,
     app_state: web::types::State<AppState>,
 ) -> Result<impl web::Responder, web::Error> {
+    let user_timezone = utils::user_timezone(r.headers());
+
     let content = crate::api::pet::generate_pdf_report_bytes(
         path.0,
         user.id,
+        user_timezone,
+        user.weight_unit,
+        &crate::api::pet::PdfReportOptions::from_sections(&query.sections)
+            .with_quality(query.quality),
         &app_state.repo,
         &app_state.storage_service,
     )
@@ -476,6 +817,133 @@ async fn get_pdf_report(
         .streaming(body))
 }
 
+#[derive(serde::Deserialize)]
+struct VaccineCertificateQuery {
+    /// Whether to add a line for the vet signature, added by default
+    signature: Option<bool>,
+}
+
+/// Generates a one page vaccine certificate PDF for a pet
+///
+/// # Path Parameters
+/// * `pet_id` - Internal database ID of the pet
+///
+/// # Query Parameters
+/// * `signature` - `false` to leave out the vet signature line
+///
+/// # Security
+/// - Requires service access (subscription)
+/// - Validates user ownership of the pet
+///
+/// # Returns
+/// * `Ok(HttpResponse)` - PDF document stream
+/// * `Err(web::Error)` - Server error if PDF generation fails
+#[web::get("vaccine_cert/{pet_id}")]
+async fn get_vaccine_certificate(
+    _: middleware::logged_user::CheckUserCanAccessService,
+    _: middleware::logged_user::CheckUserCanExport,
+    path: web::types::Path<(i64,)>,
+    query: web::types::Query<VaccineCertificateQuery>,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    r: web::HttpRequest,
+    app_state: web::types::State<AppState>,
+) -> Result<impl web::Responder, web::Error> {
+    let user_timezone = utils::user_timezone(r.headers());
+
+    let content = crate::api::pet::generate_vaccine_certificate_pdf(
+        path.0,
+        user.id,
+        user_timezone,
+        query.signature.unwrap_or(true),
+        &app_state.repo,
+        &app_state.storage_service,
+    )
+    .await
+    .map_err(|e| {
+        errors::ServerError::InternalServerError(format!(
+            "get_vaccine_certificate could not generate the file: {e}"
+        ))
+    })?;
+
+    let body = once(ok::<_, web::Error>(Bytes::from_iter(&content)));
+
+    Ok(web::HttpResponse::Ok()
+        .content_type("application/pdf")
+        .set_header("HX-Trigger", "download-complete")
+        .streaming(body))
+}
+
+/// Downloads the pet's weight history as CSV
+///
+/// # Path Parameters
+/// * `pet_id` - Internal database ID of the pet
+///
+/// # Security
+/// - Requires service access (subscription)
+/// - Validates user ownership of the pet
+///
+/// # Returns
+/// * `Ok(HttpResponse)` - CSV attachment with `date,weight_kg` rows
+/// * `Err(web::Error)` - Server error if the pet or its weights can't be read
+#[web::get("weights.csv/{pet_id}")]
+async fn get_pet_weights_csv(
+    _: middleware::logged_user::CheckUserCanAccessService,
+    _: middleware::logged_user::CheckUserCanExport,
+    path: web::types::Path<(i64,)>,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    app_state: web::types::State<AppState>,
+) -> Result<impl web::Responder, web::Error> {
+    let csv = api::pet::get_pet_weights_csv(path.0, user.id, &app_state.repo)
+        .await
+        .map_err(|e| {
+            errors::not_found_or_internal(e, "function get_pet_weights_csv raised an error")
+        })?;
+
+    Ok(web::HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .set_header(
+            "Content-Disposition",
+            "attachment; filename=\"pet_weights.csv\"",
+        )
+        .body(csv))
+}
+
+/// Downloads all the pet's photos as a ZIP
+///
+/// # Path Parameters
+/// * `pet_id` - Internal database ID of the pet
+///
+/// # Security
+/// - Requires service access (subscription)
+/// - Validates user ownership of the pet
+///
+/// # Returns
+/// * `Ok(HttpResponse)` - ZIP attachment with the photos
+/// * `Err(web::Error)` - Server error if the pet or its photos can't be read
+#[web::get("photos.zip/{pet_id}")]
+async fn get_pet_photos_zip(
+    _: middleware::logged_user::CheckUserCanAccessService,
+    _: middleware::logged_user::CheckUserCanExport,
+    path: web::types::Path<(i64,)>,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    app_state: web::types::State<AppState>,
+) -> Result<impl web::Responder, web::Error> {
+    let zip =
+        api::pet::build_photos_zip(path.0, user.id, &app_state.repo, &app_state.storage_service)
+            .await
+            .map_err(|e| {
+                errors::not_found_or_internal(e, "function build_photos_zip raised an error")
+            })?;
+
+    Ok(web::HttpResponse::Ok()
+        .content_type("application/zip")
+        .set_header(
+            "Content-Disposition",
+            "attachment; filename=\"pet_photos.zip\"",
+        )
+        .body(zip))
+}
+
 /// Serves the pet's public profile picture
 ///
 /// Returns the pet's image from S3 storage for display on public profiles.
@@ -485,7 +953,10 @@ async fn get_pdf_report(
 /// * `pet_external_id` - UUID of the pet's external identifier
 ///
 /// # Returns
-/// * `Ok(HttpResponse)` - Image stream with appropriate content-type
+/// * `Ok(HttpResponse)` - Image stream with appropriate content-type, and
+///   its `ETag`/`Last-Modified` validators
+/// * `Ok(NotModified)` - If the client sent validators matching the picture,
+///   storage isn't read then
 /// * `Ok(NoContent)` - If no image is available for the pet
 /// * `Err(web::Error)` - Server error if storage access fails
 ///
@@ -493,10 +964,35 @@ async fn get_pdf_report(
 /// Supports various image formats based on stored file extension
 #[web::get("public_pic/{pet_external_id}")]
 async fn get_pet_public_pic(
+    req: web::HttpRequest,
     path: web::types::Path<(Uuid,)>,
     app_state: web::types::State<AppState>,
 ) -> Result<impl web::Responder, web::Error> {
-    let pet_external_id = path.0;
+    serve_public_pic(&req, path.0, &app_state).await
+}
+
+/// Answers the public picture of a pet, or `304` when the client already has it
+pub(super) async fn serve_public_pic(
+    req: &web::HttpRequest,
+    pet_external_id: Uuid,
+    app_state: &AppState,
+) -> Result<web::HttpResponse, web::Error> {
+    let pic_version = api::pet::get_public_pic_version(pet_external_id, &app_state.repo)
+        .await
+        .map_err(|e| {
+            errors::ServerError::InternalServerError(format!(
+                "pet_public_pic version could not be read: {e}"
+            ))
+        })?;
+
+    if let Some(version) = &pic_version
+        && utils::is_not_modified(req.headers(), &version.etag, version.last_modified)
+    {
+        return Ok(web::HttpResponse::NotModified()
+            .set_header(header::ETAG, version.etag.as_str())
+            .finish());
+    }
+
     let pet_pic =
         api::pet::get_public_pic(pet_external_id, &app_state.repo, &app_state.storage_service)
             .await
@@ -509,9 +1005,19 @@ async fn get_pet_public_pic(
     if let Some(pet_pic) = pet_pic {
         let body = once(ok::<_, web::Error>(Bytes::from_iter(&pet_pic.body)));
 
-        return Ok(web::HttpResponse::Ok()
+        let mut response = web::HttpResponse::Ok();
+        response
             .content_type(format!("image/{}", pet_pic.extension))
-            .streaming(body));
+            // cached, but revalidated so a new picture shows up right away
+            .set_header(header::CACHE_CONTROL, "public, no-cache");
+        if let Some(version) = pic_version {
+            response.set_header(header::ETAG, version.etag).set_header(
+                header::LAST_MODIFIED,
+                utils::fmt_http_date(version.last_modified),
+            );
+        }
+
+        return Ok(response.streaming(body));
     }
 
     Ok(web::HttpResponse::NoContent().into())
@@ -562,21 +1068,37 @@ async fn serve_webmanifest(
 #[web::get("pass/{pet_external_id}")]
 async fn download_pet_pass(
     _: middleware::logged_user::CheckUserCanAccessService,
+    middleware::logged_user::PreferredWeightUnit(weight_unit): middleware::logged_user::PreferredWeightUnit,
     path: web::types::Path<(Uuid,)>,
+    r: web::HttpRequest,
     app_state: web::types::State<AppState>,
 ) -> Result<impl web::Responder, web::Error> {
     let pet_external_id = path.0;
+    let user_timezone = utils::user_timezone(r.headers());
 
     // Get pet public information
-    let pet_info = api::pet::get_pet_public_info(pet_external_id, &app_state.repo)
-        .await
-        .map_err(|e| {
-            errors::ServerError::InternalServerError(format!("Failed to get pet info: {e}"))
-        })?;
+    let pet_info = api::pet::get_pet_public_info(
+        pet_external_id,
+        user_timezone,
+        &app_state.repo,
+        &app_state.profile_cache,
+    )
+    .await
+    .map_err(|e| errors::not_found_or_internal(e, "Failed to get pet info"))?;
+
+    let thumbnails = api::thumbnail::get_pet_pass_thumbnails(
+        pet_external_id,
+        &app_state.repo,
+        &app_state.storage_service,
+        &app_state.thumbnail_cache,
+    )
+    .await
+    .map_err(|e| {
+        errors::ServerError::InternalServerError(format!("Failed to get pet thumbnail: {e}"))
+    })?;
 
     // Generate the pass
-    let pass_data = api::passes::generate_pet_pass(&pet_info, &app_state.storage_service)
-        .await
+    let pass_data = api::passes::generate_pet_pass(&pet_info, thumbnails.as_ref(), weight_unit)
         .map_err(|e| {
             errors::ServerError::InternalServerError(format!("Failed to generate pass: {e}"))
         })?;
@@ -593,6 +1115,72 @@ async fn download_pet_pass(
         .streaming(body))
 }
 
+/// Renders an HTML preview of the Apple Wallet pass
+///
+/// Shows the pass front with the same fields, colors, photo and QR code
+/// as the .pkpass file, so owners on non-Apple devices can see it.
+///
+/// # Path Parameters
+/// * `pet_external_id` - UUID of the pet's external identifier
+///
+/// # Security
+/// Requires service access (subscription)
+///
+/// # Returns
+/// * `Ok(HttpResponse)` - HTML widget with the pass preview
+/// * `Err(web::Error)` - Server error if the preview can't be built
+#[web::get("pass_preview/{pet_external_id}")]
+async fn get_pet_pass_preview(
+    _: middleware::logged_user::CheckUserCanAccessService,
+    middleware::logged_user::PreferredWeightUnit(weight_unit): middleware::logged_user::PreferredWeightUnit,
+    path: web::types::Path<(Uuid,)>,
+    r: web::HttpRequest,
+    app_state: web::types::State<AppState>,
+) -> Result<impl web::Responder, web::Error> {
+    let pet_external_id = path.0;
+    let user_timezone = utils::user_timezone(r.headers());
+
+    let pet_info = api::pet::get_pet_public_info(
+        pet_external_id,
+        user_timezone,
+        &app_state.repo,
+        &app_state.profile_cache,
+    )
+    .await
+    .map_err(|e| errors::not_found_or_internal(e, "Failed to get pet info"))?;
+
+    let thumbnails = api::thumbnail::get_pet_pass_thumbnails(
+        pet_external_id,
+        &app_state.repo,
+        &app_state.storage_service,
+        &app_state.thumbnail_cache,
+    )
+    .await
+    .map_err(|e| {
+        errors::ServerError::InternalServerError(format!("Failed to get pet thumbnail: {e}"))
+    })?;
+
+    let preview = api::passes::build_pass_preview(&pet_info, thumbnails.as_ref(), weight_unit)
+        .map_err(|e| {
+            errors::ServerError::InternalServerError(format!("Failed to build pass preview: {e}"))
+        })?;
+
+    let content = templates::WEB_TEMPLATES
+        .render(
+            "widgets/pet_pass_preview.html",
+            &tera::Context::from_value(json!({ "preview": preview })).unwrap_or_default(),
+        )
+        .map_err(|e| {
+            errors::ServerError::TemplateError(format!(
+                "at /pet/pass_preview endpoint the template couldnt be rendered: {e}"
+            ))
+        })?;
+
+    Ok(web::HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(content))
+}
+
 /// Renders the pet editing form with existing data
 ///
 /// Displays a form pre-populated with the pet's current information
@@ -627,9 +1215,10 @@ async fn get_pet_details_form(
         "pet": api::pet::get_pet_user_to_edit(pet_id, user.id,&app_state.repo)
         .await
         .map_err(|e| {
-            errors::ServerError::InternalServerError(format!(
-                "at /pet/details/pet_id endpoint pet info [get_pet_user_to_edit] couldnt be retrieved: {e}"
-            ))
+            errors::not_found_or_internal(
+                e,
+                "at /pet/details/pet_id endpoint pet info [get_pet_user_to_edit] couldnt be retrieved",
+            )
         })?,
         "PIC_PET_MAX_SIZE_BYTES": consts::PIC_PET_MAX_SIZE_BYTES,
         "ACCEPTED_IMAGE_EXTENSIONS": consts::ACCEPTED_IMAGE_EXTENSIONS,
@@ -692,15 +1281,124 @@ async fn edit_pet_details(
             .await
             .map_err(|e| errors::UserError::FormInputValueError(e.to_string()))?
     };
+    validate_pet_form(&pet_form)?;
 
     api::pet::update_pet_to_user(
         user.id,
         pet_form,
         &app_state.repo,
         &app_state.storage_service,
+        &app_state.thumbnail_cache,
+        &app_state.profile_cache,
     )
     .await
     .map_err(|e| errors::ServerError::InternalServerError(e.to_string()))?;
 
     utils::redirect_to("/pet")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{front::test_utils::app_state, repo::MockAppRepo};
+    use ntex::{http::StatusCode, web::test};
+
+    #[test]
+    fn test_check_accepted_image_format() {
+        let png = [0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+        assert!(check_accepted_image_format(&png).is_ok());
+
+        // TIFF (little endian) isn't recognized, so it's not taken for a jpg
+        let tiff = [0x49, 0x49, 0x2A, 0x00, 0x08, 0x00, 0x00, 0x00];
+        assert!(check_accepted_image_format(&tiff).is_err());
+
+        // recognized but not accepted
+        assert!(check_accepted_image_format(&[0x42, 0x4D, 0x00, 0x00]).is_err());
+    }
+
+    #[ntex::test]
+    async fn test_get_pet_external_id_status() {
+        let linked_id = Uuid::new_v4();
+        let free_id = Uuid::new_v4();
+
+        let mut mock_repo = MockAppRepo::new();
+        mock_repo
+            .expect_is_pet_external_id_linked()
+            .times(3)
+            .returning(move |external_id| {
+                let status = match *external_id {
+                    id if id == linked_id => Some(true),
+                    id if id == free_id => Some(false),
+                    _ => None,
+                };
+                Box::pin(async move { Ok(status) })
+            });
+
+        let app = test::init_service(
+            web::App::new()
+                .state(app_state(mock_repo))
+                .service(web::scope("/pet").service(get_pet_external_id_status)),
+        )
+        .await;
+
+        for (external_id, exists, is_linked) in [
+            (linked_id, true, true),
+            (free_id, true, false),
+            (Uuid::new_v4(), false, false),
+        ] {
+            let req =
+                test::TestRequest::with_uri(&format!("/pet/external-id/{external_id}/status"))
+                    .to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::OK);
+
+            let body: serde_json::Value =
+                serde_json::from_slice(&test::read_body(res).await).unwrap();
+            assert_eq!(body, json!({"exists": exists, "is_linked": is_linked}));
+        }
+
+        let req = test::TestRequest::with_uri("/pet/external-id/not-a-uuid/status").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[ntex::test]
+    async fn test_get_pet_public_pic_not_modified() {
+        let pet_external_id = Uuid::new_v4();
+        let updated_at = chrono::DateTime::parse_from_rfc3339("2024-06-15T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let etag = api::pet::PetPublicPicVersion::from(updated_at).etag;
+
+        let mut mock_repo = MockAppRepo::new();
+        mock_repo
+            .expect_get_pet_pic_updated_at_by_external_id()
+            .times(2)
+            .returning(move |_| Box::pin(async move { Ok(Some(updated_at)) }));
+        // the cached copy is still fresh, storage is never read
+        mock_repo.expect_get_pet_pic_path_by_external_id().times(0);
+
+        let app = test::init_service(
+            web::App::new()
+                .state(app_state(mock_repo))
+                .service(web::scope("/pet").service(get_pet_public_pic)),
+        )
+        .await;
+
+        for (name, value) in [
+            (header::IF_NONE_MATCH, etag.clone()),
+            (
+                header::IF_MODIFIED_SINCE,
+                "Sat, 15 Jun 2024 12:00:00 GMT".to_string(),
+            ),
+        ] {
+            let req = test::TestRequest::with_uri(&format!("/pet/public_pic/{pet_external_id}"))
+                .header(name, value)
+                .to_request();
+            let res = test::call_service(&app, req).await;
+
+            assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+            assert_eq!(res.headers().get(header::ETAG).unwrap(), etag.as_str());
+        }
+    }
+}
@@ -0,0 +1,44 @@
+//! Handlers related to the /pet/audit url, the history of changes on a pet
+//!
+//! ## Routes
+//! - `GET /pet/audit/{pet_id}` - Latest changes widget, only for the owner
+
+use ntex::web;
+use serde_json::json;
+
+use crate::{
+    api,
+    front::{AppState, errors, middleware, session, templates},
+};
+
+/// Renders who changed the profile, health records and notes of a pet
+#[web::get("{pet_id}")]
+async fn get_pet_audit(
+    _: middleware::logged_user::CheckUserCanAccessService,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    path: web::types::Path<(i64,)>,
+    app_state: web::types::State<AppState>,
+) -> Result<impl web::Responder, web::Error> {
+    let context = tera::Context::from_value(json!({
+        "entries": api::pet::get_pet_audit(path.0, user.id, &app_state.repo)
+            .await
+            .map_err(|e| {
+                errors::ServerError::InternalServerError(format!(
+                    "function get_pet_audit raised an error: {e}"
+                ))
+            })?,
+    }))
+    .unwrap_or_default();
+
+    let content = templates::WEB_TEMPLATES
+        .render("widgets/pet_audit.html", &context)
+        .map_err(|e| {
+            errors::ServerError::TemplateError(format!(
+                "at /pet/audit endpoint the template couldnt be rendered: {e}"
+            ))
+        })?;
+
+    Ok(web::HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(content))
+}
@@ -0,0 +1,45 @@
+//! Handlers related to the /pet/found-report url, the messages left by
+//! people who found a lost pet
+//!
+//! ## Routes
+//! - `GET /pet/found-report/{pet_id}` - Found reports widget, only for the owner
+
+use ntex::web;
+use serde_json::json;
+
+use crate::{
+    api,
+    front::{AppState, errors, middleware, session, templates},
+};
+
+/// Renders the messages left on the public profile of a pet
+#[web::get("{pet_id}")]
+async fn get_pet_found_reports(
+    _: middleware::logged_user::CheckUserCanAccessService,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    path: web::types::Path<(i64,)>,
+    app_state: web::types::State<AppState>,
+) -> Result<impl web::Responder, web::Error> {
+    let context = tera::Context::from_value(json!({
+        "reports": api::pet::get_pet_found_reports(path.0, user.id, &app_state.repo)
+            .await
+            .map_err(|e| {
+                errors::ServerError::InternalServerError(format!(
+                    "function get_pet_found_reports raised an error: {e}"
+                ))
+            })?,
+    }))
+    .unwrap_or_default();
+
+    let content = templates::WEB_TEMPLATES
+        .render("widgets/pet_found_reports.html", &context)
+        .map_err(|e| {
+            errors::ServerError::TemplateError(format!(
+                "at /pet/found-report endpoint the template couldnt be rendered: {e}"
+            ))
+        })?;
+
+    Ok(web::HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(content))
+}
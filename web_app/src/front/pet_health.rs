@@ -1,14 +1,19 @@
 //! Handlers related to the /pet/health url
 
 use crate::{
-    api,
+    api, consts,
     front::{
         AppState, errors, forms,
-        middleware::{self, logged_user::IsUserLoggedAndCanEdit},
-        session, templates,
+        middleware::{
+            self,
+            logged_user::{IsUserLoggedAndCanEdit, PreferredWeightUnit},
+        },
+        session, templates, utils,
     },
     models,
 };
+use anyhow::{Context, bail};
+use futures::TryStreamExt;
 use ntex::web;
 use serde_json::json;
 
@@ -21,6 +26,7 @@ struct HealthPath {
 #[web::get("{pet_external_id}/{record_type}")]
 async fn get_pet_health_view(
     IsUserLoggedAndCanEdit(can_edit, user_id): IsUserLoggedAndCanEdit,
+    PreferredWeightUnit(weight_unit): PreferredWeightUnit,
     app_state: web::types::State<AppState>,
     path: web::types::Path<HealthPath>,
 ) -> Result<impl web::Responder, web::Error> {
@@ -28,10 +34,12 @@ async fn get_pet_health_view(
         "can_edit": &can_edit,
         "record_type": &path.record_type,
         "pet_external_id": &path.pet_external_id,
+        "weight_unit": weight_unit,
         "health_records": api::pet::get_pet_health_records(
             path.pet_external_id,
             &path.record_type,
             user_id,
+            weight_unit,
             &app_state.repo,
         )
         .await
@@ -60,6 +68,7 @@ async fn get_pet_health_view(
 #[web::get("{pet_external_id}/{record_type}/tbody")]
 async fn pet_health_records(
     IsUserLoggedAndCanEdit(can_edit, user_id): IsUserLoggedAndCanEdit,
+    PreferredWeightUnit(weight_unit): PreferredWeightUnit,
     app_state: web::types::State<AppState>,
     path: web::types::Path<HealthPath>,
 ) -> Result<impl web::Responder, web::Error> {
@@ -67,10 +76,12 @@ async fn pet_health_records(
         "can_edit": &can_edit,
         "record_type": &path.record_type,
         "pet_external_id": &path.pet_external_id,
+        "weight_unit": weight_unit,
         "health_records": api::pet::get_pet_health_records(
             path.pet_external_id,
             &path.record_type,
             user_id,
+            weight_unit,
             &app_state.repo,
         )
         .await
@@ -101,6 +112,7 @@ async fn pet_health_records(
 async fn add_health_record(
     _: middleware::logged_user::CheckUserCanAccessService,
     session::WebAppSession { user, .. }: session::WebAppSession,
+    r: web::HttpRequest,
     path: web::types::Path<HealthPath>,
     form: web::types::Form<forms::pet::HealthRecordForm>,
     app_state: web::types::State<AppState>,
@@ -109,6 +121,7 @@ async fn add_health_record(
     let form = forms::pet::HealthRecordForm {
         value: ammonia::clean(&form.value),
         date: form.date,
+        booster_months: form.booster_months,
     };
 
     let desc = form.value.to_string();
@@ -117,13 +130,29 @@ async fn add_health_record(
             .map_err(|_| errors::UserError::FormInputValueError("peso no es numerico".into()))?;
     }
 
-    api::pet::insert_pet_health_record(
+    let user_timezone = utils::user_timezone(r.headers());
+
+    if form.date > utils::get_now_date_in_tz(user_timezone) {
+        return Err(errors::UserError::FormInputValueError(
+            "la fecha no puede ser posterior a hoy".into(),
+        )
+        .into());
+    }
+
+    let outcome = api::pet::insert_pet_health_record(
         path.pet_external_id,
         &path.record_type,
-        user.id,
-        desc,
-        form.date,
+        &user,
+        api::pet::HealthRecordEntry {
+            desc,
+            date: form.date,
+            booster_months: form.booster_months,
+            user_timezone,
+        },
         &app_state.repo,
+        &app_state.notification_service,
+        &app_state.profile_cache,
+        &app_state.clock,
     )
     .await
     .map_err(|e| {
@@ -135,7 +164,104 @@ async fn add_health_record(
     Ok(web::HttpResponse::Created()
         .set_header("HX-Trigger", "healthRecordUpdated")
         .content_type("text/html; charset=utf-8")
-        .finish())
+        .body(outcome.warning.unwrap_or_default()))
+}
+
+/// Health records CSV upload sent to the import route
+struct HealthImportForm {
+    pet_external_id: uuid::Uuid,
+    record_type: models::pet::PetHealthType,
+    csv: String,
+}
+
+/// Deserializes the multipart form of a health records CSV import
+///
+/// # Returns
+/// * `Ok(HealthImportForm)` - Pet, record type and CSV content
+/// * `Err(anyhow::Error)` - If a field is missing, invalid or the file is too big
+async fn deserialize_health_import_form(
+    mut payload: ntex_multipart::Multipart,
+) -> anyhow::Result<HealthImportForm> {
+    let mut pet_external_id = None;
+    let mut record_type = None;
+    let mut csv = None;
+
+    while let Ok(Some(field)) = payload.try_next().await {
+        let field_name = utils::get_multipart_field_name(&field);
+
+        if field_name == "csv_file" {
+            let body = utils::get_bytes_value(field).await;
+            if body.len() > consts::HEALTH_RECORDS_CSV_MAX_SIZE_BYTES {
+                bail!(
+                    "el archivo es muy grande, maximo {} bytes",
+                    consts::HEALTH_RECORDS_CSV_MAX_SIZE_BYTES
+                );
+            }
+            csv = Some(String::from_utf8(body).context("el archivo no es un csv valido")?);
+            continue;
+        }
+
+        let field_value = utils::get_field_value(field).await;
+        match field_name.as_str() {
+            "pet_external_id" => {
+                pet_external_id = Some(uuid::Uuid::parse_str(field_value.trim())?);
+            }
+            "record_type" => {
+                record_type = Some(serde_json::from_value(serde_json::Value::String(
+                    field_value.trim().to_string(),
+                ))?);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(HealthImportForm {
+        pet_external_id: pet_external_id.context("falta la mascota")?,
+        record_type: record_type.context("falta el tipo de registro")?,
+        csv: csv.context("falta el archivo csv")?,
+    })
+}
+
+/// Handles the upload of a CSV with past health records of a pet
+///
+/// Rows are `date,description`. Responds with a summary of the imported
+/// and failed rows to show to the user.
+#[web::post("import")]
+async fn import_health_records(
+    _: middleware::logged_user::CheckUserCanAccessService,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    payload: ntex_multipart::Multipart,
+    app_state: web::types::State<AppState>,
+    _: middleware::csrf_token::CsrfToken,
+) -> Result<impl web::Responder, web::Error> {
+    let form = deserialize_health_import_form(payload)
+        .await
+        .map_err(|e| errors::UserError::FormInputValueError(e.to_string()))?;
+
+    let summary = api::pet::import_health_records_csv(
+        form.pet_external_id,
+        user.id,
+        &form.record_type,
+        &form.csv,
+        &app_state.repo,
+    )
+    .await
+    .map_err(|e| {
+        errors::not_found_or_internal(e, "function import_health_records_csv raised an error")
+    })?;
+
+    let mut message = format!(
+        "Registros importados: {}. Filas con error: {}",
+        summary.imported, summary.failed
+    );
+    for error in &summary.errors {
+        message.push_str(&format!("\n{error}"));
+    }
+
+    Ok(web::HttpResponse::Created()
+        .set_header("HX-Trigger", "healthRecordUpdated")
+        .content_type("text/plain; charset=utf-8")
+        .body(message))
 }
 
 #[derive(serde::Deserialize)]
@@ -145,6 +271,55 @@ struct HealthDeletePath {
     record_type: models::pet::PetHealthType,
 }
 
+/// Handles the request to update the value and date of a health record
+#[web::put("{pet_external_id}/{record_type}/{record_id}")]
+async fn update_health_record(
+    _: middleware::logged_user::CheckUserCanAccessService,
+    path: web::types::Path<HealthDeletePath>,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    r: web::HttpRequest,
+    form: web::types::Form<forms::pet::HealthRecordForm>,
+    app_state: web::types::State<AppState>,
+    _: middleware::csrf_token::CsrfToken,
+) -> Result<impl web::Responder, web::Error> {
+    let desc = ammonia::clean(&form.value);
+    if path.record_type.eq(&models::pet::PetHealthType::Weight) {
+        desc.parse::<f64>()
+            .map_err(|_| errors::UserError::FormInputValueError("peso no es numerico".into()))?;
+    }
+
+    let user_timezone = utils::user_timezone(r.headers());
+
+    if form.date > utils::get_now_date_in_tz(user_timezone) {
+        return Err(errors::UserError::FormInputValueError(
+            "la fecha no puede ser posterior a hoy".into(),
+        )
+        .into());
+    }
+
+    api::pet::update_pet_health_record(
+        path.record_id,
+        path.pet_external_id,
+        user.id,
+        &path.record_type,
+        &desc,
+        user.weight_unit,
+        form.date,
+        &app_state.repo,
+        &app_state.profile_cache,
+    )
+    .await
+    .map_err(|e| {
+        errors::ServerError::InternalServerError(format!(
+            "function update_pet_health_record raised an error: {e}"
+        ))
+    })?;
+
+    Ok(web::HttpResponse::Ok()
+        .set_header("HX-Trigger", "healthRecordUpdated")
+        .finish())
+}
+
 /// Handles the request to delete a health record to a pet
 #[web::delete("{pet_external_id}/{record_type}/{record_id}")]
 async fn delete_health_record(
@@ -160,6 +335,7 @@ async fn delete_health_record(
         user.id,
         &path.record_type,
         &app_state.repo,
+        &app_state.profile_cache,
     )
     .await
     .map_err(|e| {
@@ -173,3 +349,32 @@ async fn delete_health_record(
         .content_type("text/html; charset=utf-8")
         .finish())
 }
+
+/// Handles the request to delete every health record of a type from a pet
+#[web::delete("{pet_external_id}/{record_type}")]
+async fn delete_all_health_records(
+    _: middleware::logged_user::CheckUserCanAccessService,
+    path: web::types::Path<HealthPath>,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    app_state: web::types::State<AppState>,
+    _: middleware::csrf_token::CsrfToken,
+) -> Result<impl web::Responder, web::Error> {
+    let deleted = api::pet::delete_all_pet_health_records(
+        path.pet_external_id,
+        user.id,
+        &path.record_type,
+        &app_state.repo,
+        &app_state.profile_cache,
+    )
+    .await
+    .map_err(|e| {
+        errors::ServerError::InternalServerError(format!(
+            "function delete_all_pet_health_records raised an error: {e}"
+        ))
+    })?;
+
+    Ok(web::HttpResponse::Ok()
+        .set_header("HX-Trigger", "healthRecordUpdated")
+        .content_type("text/html; charset=utf-8")
+        .body(format!("{deleted} registros eliminados")))
+}
@@ -16,19 +16,24 @@ async fn get_pet_notes_view(
     app_state: web::types::State<AppState>,
 ) -> Result<impl web::Responder, web::Error> {
     let pet_id = params.0;
-    let context = tera::Context::from_value(json!({
-        "can_edit": can_edit,
-        "pet_id": pet_id,
-        "notes": if let Some(user_id) = user_id {
-            api::pet::get_pet_notes(user_id, pet_id, &app_state.repo).await
+    let notes_page = match user_id {
+        Some(user_id) => api::pet::get_pet_notes(user_id, pet_id, 0, &app_state.repo)
+            .await
             .map_err(|e| {
                 errors::ServerError::InternalServerError(format!(
                     "function get_pet_notes raised an error: {e}"
                 ))
-            })?
-        } else {
-            vec![]
+            })?,
+        None => api::pet::PetNotesPage {
+            notes: vec![],
+            next_offset: None,
         },
+    };
+    let context = tera::Context::from_value(json!({
+        "can_edit": can_edit,
+        "pet_id": pet_id,
+        "notes": notes_page.notes,
+        "next_offset": notes_page.next_offset,
     }))
     .unwrap_or_default();
 
@@ -56,49 +61,152 @@ async fn new_pet_note(
     _: middleware::csrf_token::CsrfToken,
 ) -> Result<impl web::Responder, web::Error> {
     let pet_id = params.0;
-    let form = forms::pet::PetNoteForm {
-        title: ammonia::clean(&form.title),
-        body: ammonia::clean(&form.body),
-    };
-
-    api::pet::add_new_note(user.id, pet_id, form.into(), &app_state.repo)
-        .await
-        .map_err(|e| {
-            errors::ServerError::InternalServerError(format!(
+    api::pet::add_new_note(
+        user.id,
+        pet_id,
+        form.into_inner().into(),
+        &app_state.repo,
+        &app_state.clock,
+    )
+    .await
+    .map_err(|e| -> web::Error {
+        match e.downcast_ref::<api::pet::PetNoteError>() {
+            Some(note_error) => {
+                errors::UserError::FormInputValueError(note_error.to_string()).into()
+            }
+            None => errors::ServerError::InternalServerError(format!(
                 "function add_new_note raised an error: {e}"
             ))
-        })?;
+            .into(),
+        }
+    })?;
 
     Ok(web::HttpResponse::Created()
         .set_header("HX-Trigger", "petNoteRecordUpdated")
         .finish())
 }
 
+/// Handles the request to edit the title and content of a pet note
+#[web::post("edit/{note_id}")]
+async fn edit_pet_note(
+    _: middleware::logged_user::CheckUserCanAccessService,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    params: web::types::Path<(i64,)>,
+    form: web::types::Form<forms::pet::PetNoteForm>,
+    app_state: web::types::State<AppState>,
+    _: middleware::csrf_token::CsrfToken,
+) -> Result<impl web::Responder, web::Error> {
+    let note_id = params.0;
+    api::pet::edit_note(user.id, note_id, form.into_inner().into(), &app_state.repo)
+        .await
+        .map_err(|e| -> web::Error {
+            match e.downcast_ref::<api::pet::PetNoteError>() {
+                Some(note_error) => {
+                    errors::UserError::FormInputValueError(note_error.to_string()).into()
+                }
+                None => errors::ServerError::InternalServerError(format!(
+                    "function edit_note raised an error: {e}"
+                ))
+                .into(),
+            }
+        })?;
+
+    Ok(web::HttpResponse::Ok()
+        .set_header("HX-Trigger", "petNoteRecordUpdated")
+        .finish())
+}
+
+#[derive(serde::Deserialize)]
+struct NotesPageQuery {
+    #[serde(default)]
+    offset: i64,
+}
+
+/// Renders a page of the notes of a pet, `offset` comes from the "load more" button
 #[web::get("{pet_id}/list")]
 async fn get_pet_notes(
     middleware::logged_user::IsUserLoggedAndCanEdit(can_edit, user_id): middleware::logged_user::IsUserLoggedAndCanEdit,
     params: web::types::Path<(i64,)>,
+    query: web::types::Query<NotesPageQuery>,
     app_state: web::types::State<AppState>,
 ) -> Result<impl web::Responder, web::Error> {
-    if !can_edit {
+    let (true, Some(user_id)) = (can_edit, user_id) else {
         return Ok(web::HttpResponse::PaymentRequired()
             .content_type("text/html; charset=utf-8")
             .finish());
-    }
+    };
+
+    render_notes_page(user_id, params.0, query.offset, &app_state).await
+}
+
+async fn render_notes_page(
+    user_id: i64,
+    pet_id: i64,
+    offset: i64,
+    app_state: &AppState,
+) -> Result<web::HttpResponse, web::Error> {
+    let notes_page = api::pet::get_pet_notes(user_id, pet_id, offset, &app_state.repo)
+        .await
+        .map_err(|e| {
+            errors::ServerError::InternalServerError(format!(
+                "function get_pet_notes raised an error: {e}"
+            ))
+        })?;
+    let context = tera::Context::from_value(json!({
+        "pet_id": pet_id,
+        "notes": notes_page.notes,
+        "next_offset": notes_page.next_offset,
+    }))
+    .unwrap_or_default();
+
+    let content = templates::WEB_TEMPLATES
+        .render("widgets/pet_notes.html", &context)
+        .map_err(|e| {
+            errors::ServerError::TemplateError(format!(
+                "at /pet/note/<pet_id>/list endpoint the template couldnt be rendered: {e}",
+            ))
+        })?;
+
+    Ok(web::HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(content))
+}
+
+#[derive(serde::Deserialize)]
+struct NoteSearchQuery {
+    #[serde(default)]
+    q: String,
+}
+
+/// Renders the notes of a pet filtered by title or content
+#[web::get("search/{pet_id}")]
+async fn search_pet_notes(
+    middleware::logged_user::IsUserLoggedAndCanEdit(can_edit, user_id): middleware::logged_user::IsUserLoggedAndCanEdit,
+    params: web::types::Path<(i64,)>,
+    query: web::types::Query<NoteSearchQuery>,
+    app_state: web::types::State<AppState>,
+) -> Result<impl web::Responder, web::Error> {
+    let (true, Some(user_id)) = (can_edit, user_id) else {
+        return Ok(web::HttpResponse::PaymentRequired()
+            .content_type("text/html; charset=utf-8")
+            .finish());
+    };
 
     let pet_id = params.0;
+    // a cleared search goes back to the paginated list
+    if query.q.trim().is_empty() {
+        return render_notes_page(user_id, pet_id, 0, &app_state).await;
+    }
+
     let context = tera::Context::from_value(json!({
         "pet_id": pet_id,
-        "notes": if let Some(user_id) = user_id {
-            api::pet::get_pet_notes(user_id, pet_id, &app_state.repo).await
+        "notes": api::pet::search_pet_notes(user_id, pet_id, &query.q, &app_state.repo)
+            .await
             .map_err(|e| {
                 errors::ServerError::InternalServerError(format!(
-                    "function get_pet_notes raised an error: {e}"
+                    "function search_pet_notes raised an error: {e}"
                 ))
-            })?
-        } else {
-            vec![]
-        },
+            })?,
     }))
     .unwrap_or_default();
 
@@ -106,7 +214,7 @@ async fn get_pet_notes(
         .render("widgets/pet_notes.html", &context)
         .map_err(|e| {
             errors::ServerError::TemplateError(format!(
-                "at /pet/note/<pet_id>/list endpoint the template couldnt be rendered: {e}",
+                "at /pet/note/search/<pet_id> endpoint the template couldnt be rendered: {e}",
             ))
         })?;
 
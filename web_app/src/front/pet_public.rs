@@ -1,22 +1,40 @@
 //! Handlers related to the /info/profile/{external-id} url
+//!
+//! ## Routes
+//! - `GET /info/{pet_external_id}` - Pet public profile, JSON when the `Accept` header asks for it
+//! - `POST /info/batch` - Public profiles of several pets as JSON
+//! - `POST /info/{pet_external_id}/found` - Send a found report to the pet owner
+//! - `POST /info/{pet_external_id}/unlock` - Show the owner contacts hidden behind a PIN
+//! - `GET /share/{token}` - Pet public profile through a temporary share link
+//! - `POST /share/{token}/found` - Send a found report through a temporary share link
+//! - `POST /share/{token}/unlock` - Show the owner contacts through a temporary share link
+//! - `GET /share/{token}/pic` - Pet picture through a temporary share link
 
 use anyhow::Context;
-use ntex::web;
+use csrf::CsrfProtection;
+use ntex::{http::header, web};
 use serde_json::json;
 use uuid::Uuid;
 
 use crate::{
     api, consts,
-    front::{AppState, errors, oauth, templates},
+    front::{AppState, errors, forms, middleware, oauth, templates, utils},
+    models, services,
 };
 
 /// Renders a pet public info based on its `external_id`
 /// If the pet_external_id is not linked to a pet; steps to link the
 /// external id will be shown
+///
+/// Apps scanning the QR can ask for `application/json` in the `Accept`
+/// header to get the profile and the visible owner contacts as JSON.
 #[web::get("/{pet_external_id}")]
 async fn get_pet_info_view(
+    middleware::logged_user::PreferredWeightUnit(weight_unit): middleware::logged_user::PreferredWeightUnit,
+    middleware::logged_user::IsUserLoggedAndCanEdit(_, viewer_id): middleware::logged_user::IsUserLoggedAndCanEdit,
     app_state: web::types::State<AppState>,
     path: web::types::Path<(Uuid,)>,
+    r: web::HttpRequest,
     cookie: ntex_session::Session,
 ) -> Result<impl web::Responder, web::Error> {
     let pet_external_id = path.0;
@@ -43,13 +61,173 @@ async fn get_pet_info_view(
         return empty_tag_view(cookie, &pet_external_id);
     }
 
-    let pet = api::pet::get_pet_public_info(pet_external_id, &app_state.repo)
+    render_pet_public_profile(
+        pet_external_id,
+        None,
+        viewer_id,
+        weight_unit,
+        &app_state,
+        &r,
+        &cookie,
+    )
+    .await
+}
+
+/// Returns the public profiles of the pets of a JSON array of external ids,
+/// for integrators scanning several tags at once
+///
+/// At most [`PET_PUBLIC_INFO_BATCH_MAX_IDS`](consts::PET_PUBLIC_INFO_BATCH_MAX_IDS)
+/// ids are accepted, unknown ids are omitted from the response.
+#[web::post("/batch")]
+async fn get_pets_info_batch(
+    app_state: web::types::State<AppState>,
+    body: web::types::Json<Vec<Uuid>>,
+    r: web::HttpRequest,
+) -> Result<impl web::Responder, web::Error> {
+    if body.len() > consts::PET_PUBLIC_INFO_BATCH_MAX_IDS {
+        return Ok(web::HttpResponse::BadRequest().json(&json!({
+            "error": format!(
+                "at most {} external ids per request",
+                consts::PET_PUBLIC_INFO_BATCH_MAX_IDS
+            )
+        })));
+    }
+
+    let user_timezone = utils::user_timezone(r.headers());
+    let pets = api::pet::get_pets_public_info(&body, user_timezone, &app_state.repo)
+        .await
+        .map_err(|e| {
+            errors::ServerError::InternalServerError(format!(
+                "function get_pets_public_info raised an error: {e}"
+            ))
+        })?;
+
+    Ok(web::HttpResponse::Ok().json(&pets))
+}
+
+/// Renders the public profile of a pet through a temporary share link
+///
+/// The link only works until it expires or the owner revokes it, after that
+/// it answers as an unknown url. The page never shows the external id, so
+/// the permanent profile url isn't leaked with the link.
+#[web::get("/{token}")]
+async fn get_shared_pet_info_view(
+    middleware::logged_user::PreferredWeightUnit(weight_unit): middleware::logged_user::PreferredWeightUnit,
+    middleware::logged_user::IsUserLoggedAndCanEdit(_, viewer_id): middleware::logged_user::IsUserLoggedAndCanEdit,
+    app_state: web::types::State<AppState>,
+    path: web::types::Path<(String,)>,
+    r: web::HttpRequest,
+    cookie: ntex_session::Session,
+) -> Result<impl web::Responder, web::Error> {
+    let pet_external_id = resolve_share_link(&path.0, &app_state).await?;
+
+    render_pet_public_profile(
+        pet_external_id,
+        Some(&path.0),
+        viewer_id,
+        weight_unit,
+        &app_state,
+        &r,
+        &cookie,
+    )
+    .await
+}
+
+/// Serves the picture of the profile seen through a share link, its storage
+/// key holds the external id of the pet
+#[web::get("/{token}/pic")]
+async fn get_shared_pet_pic(
+    app_state: web::types::State<AppState>,
+    path: web::types::Path<(String,)>,
+    r: web::HttpRequest,
+) -> Result<impl web::Responder, web::Error> {
+    let pet_external_id = resolve_share_link(&path.0, &app_state).await?;
+
+    super::pet::serve_public_pic(&r, pet_external_id, &app_state).await
+}
+
+/// Resolves a share link to the external id of its pet, unknown, revoked and
+/// expired links answer as an unknown url
+async fn resolve_share_link(token: &str, app_state: &AppState) -> Result<Uuid, web::Error> {
+    Ok(api::pet::resolve_pet_share_link(token, &app_state.repo)
+        .await
+        .map_err(|e| {
+            errors::ServerError::InternalServerError(format!(
+                "function resolve_pet_share_link raised an error: {e}"
+            ))
+        })?
+        .ok_or(errors::UserError::UrlNotFound)?)
+}
+
+/// Renders the public profile of a linked pet and records the visit, as JSON
+/// when the request [prefers it](utils::prefers_json)
+///
+/// Seen through a share link (`share_token`), the profile doesn't include
+/// the external id of the pet.
+async fn render_pet_public_profile(
+    pet_external_id: Uuid,
+    share_token: Option<&str>,
+    viewer_id: Option<i64>,
+    weight_unit: models::user_app::WeightUnit,
+    app_state: &AppState,
+    r: &web::HttpRequest,
+    cookie: &ntex_session::Session,
+) -> Result<web::HttpResponse, web::Error> {
+    let user_timezone = utils::user_timezone(r.headers());
+    let pet = api::pet::get_pet_public_info(
+        pet_external_id,
+        user_timezone,
+        &app_state.repo,
+        &app_state.profile_cache,
+    )
+    .await
+    .map_err(|e| {
+        errors::not_found_or_internal(
+            e,
+            "at /pet/external_id endpoint pet info couldnt be retrieved",
+        )
+    })?;
+
+    // a failed scan record must not break the public profile
+    if let Err(e) = api::pet::record_pet_scan(
+        pet_external_id,
+        viewer_id,
+        r.headers()
+            .get(ntex::http::header::USER_AGENT)
+            .and_then(|user_agent| user_agent.to_str().ok()),
+        &app_state.repo,
+    )
+    .await
+    {
+        logfire::warn!(
+            "pet scan couldnt be recorded: {error}",
+            error = e.to_string()
+        );
+    }
+
+    let contacts_locked = api::pet::are_owner_contacts_locked(pet_external_id, &app_state.repo)
         .await
         .map_err(|e| {
             errors::ServerError::InternalServerError(format!(
-                "at /pet/external_id endpoint pet info couldnt be retrieved: {e}"
+                "function are_owner_contacts_locked raised an error: {e}"
             ))
         })?;
+    let owner_contacts = match contacts_locked {
+        true => vec![],
+        false => get_public_owner_contacts(pet_external_id, &app_state.repo).await?,
+    };
+
+    if utils::prefers_json(r.headers()) {
+        let mut profile = public_profile_value(&pet, share_token);
+        profile["contacts_locked"] = json!(contacts_locked);
+        profile["owner_contacts"] = json!(owner_contacts);
+
+        return Ok(web::HttpResponse::Ok()
+            .set_header(header::VARY, "Accept")
+            .json(&profile));
+    }
+
+    ensure_csrf_token(app_state, cookie)?;
 
     let app_config = crate::config::APP_CONFIG
         .get()
@@ -58,19 +236,101 @@ async fn get_pet_info_view(
             errors::ServerError::InternalServerError(format!("failed to get app config: {e}"))
         })?;
 
-    let context = tera::Context::from_value(json!({
-        "pet": pet,
-        "owner_contacts": api::user::get_owner_contacts(0, Some(pet_external_id), &app_state.repo)
-        .await
-        .map_err(|e| {
-            errors::ServerError::InternalServerError(format!(
-                "function get_owner_contacts raised an error: {e}"
-            ))
-        })?,
-        "pet_pic_url": format!("{}/{}",
+    let pet_pic_url = match share_token {
+        Some(token) => format!("{}/share/{token}/pic", app_config.base_url()),
+        None => format!(
+            "{}/{}",
             app_config.cloudfront_url,
-            pet.pic_path,
+            services::storage::build_object_key(&app_config.storage_key_prefix, &pet.pic_path),
         ),
+    };
+    let last_weight = pet
+        .last_weight
+        .map(|kg| api::pet::format_weight_with_unit(kg, weight_unit));
+
+    let content = render_public_profile_page(
+        &pet,
+        share_token,
+        contacts_locked,
+        last_weight,
+        &owner_contacts,
+        &pet_pic_url,
+        &app_config.base_url(),
+    )?;
+
+    Ok(web::HttpResponse::Ok()
+        .set_header(header::VARY, "Accept")
+        .content_type("text/html; charset=utf-8")
+        .body(content))
+}
+
+/// Open Graph and Twitter card values so the shared profile link is shown
+/// as a rich preview; lost pets get an alert in the title
+fn open_graph_tags(
+    pet: &api::pet::PetPublicInfoSchema,
+    pet_pic_url: &str,
+    profile_url: &str,
+) -> serde_json::Value {
+    let title = match pet.is_lost {
+        true => format!("¡Mascota perdida! {}", pet.name),
+        false => pet.name.clone(),
+    };
+
+    json!({
+        "title": title,
+        "description": format!("{} • {}", pet.pet_breed, pet.fmt_age),
+        "image": pet_pic_url,
+        "url": profile_url,
+    })
+}
+
+/// Path of the public profile, the share link one when it's seen through it
+fn public_profile_path(pet: &api::pet::PetPublicInfoSchema, share_token: Option<&str>) -> String {
+    match share_token {
+        Some(token) => format!("/share/{token}"),
+        None => format!("/info/{}", pet.external_id),
+    }
+}
+
+/// Public profile as JSON, without the external id, nor the picture key that
+/// holds it, when it's seen through a share link
+fn public_profile_value(
+    pet: &api::pet::PetPublicInfoSchema,
+    share_token: Option<&str>,
+) -> serde_json::Value {
+    let mut profile = json!(pet);
+    if share_token.is_some()
+        && let Some(fields) = profile.as_object_mut()
+    {
+        fields.remove("external_id");
+        fields.remove("pic_path");
+    }
+
+    profile
+}
+
+/// Renders the public profile html of an already retrieved pet
+fn render_public_profile_page(
+    pet: &api::pet::PetPublicInfoSchema,
+    share_token: Option<&str>,
+    contacts_locked: bool,
+    last_weight: Option<String>,
+    owner_contacts: &[serde_json::Value],
+    pet_pic_url: &str,
+    base_url: &str,
+) -> Result<String, web::Error> {
+    let profile_path = public_profile_path(pet, share_token);
+    let profile_url = format!("{base_url}{profile_path}");
+
+    let context = tera::Context::from_value(json!({
+        "pet": public_profile_value(pet, share_token),
+        "is_shared": share_token.is_some(),
+        "profile_path": profile_path,
+        "contacts_locked": contacts_locked,
+        "last_weight": last_weight,
+        "owner_contacts": owner_contacts,
+        "pet_pic_url": pet_pic_url,
+        "og": open_graph_tags(pet, pet_pic_url, &profile_url),
     }))
     .unwrap_or_default();
 
@@ -82,11 +342,210 @@ async fn get_pet_info_view(
             ))
         })?;
 
+    Ok(content)
+}
+
+/// Sends the message of someone who found a lost pet to its owner
+///
+/// The finder doesn't need an account, so the route is protected by the
+/// CSRF token set when the public profile is rendered, a stricter rate
+/// limit and a honeypot field that only bots fill.
+///
+/// # Returns
+/// * `Ok(HttpResponse)` - Confirmation fragment for HTMX
+/// * `Err(web::Error)` - Form error for an invalid report, server error otherwise
+#[web::post("")]
+async fn report_found_pet(
+    _: middleware::csrf_token::CsrfToken,
+    app_state: web::types::State<AppState>,
+    path: web::types::Path<(Uuid,)>,
+    web::types::Form(form): web::types::Form<forms::pet::FoundReportForm>,
+) -> Result<impl web::Responder, web::Error> {
+    send_found_report(path.0, &form, &app_state).await
+}
+
+/// Sends a found report from the profile seen through a share link, see
+/// [`report_found_pet`]
+#[web::post("")]
+async fn report_found_shared_pet(
+    _: middleware::csrf_token::CsrfToken,
+    app_state: web::types::State<AppState>,
+    path: web::types::Path<(String,)>,
+    web::types::Form(form): web::types::Form<forms::pet::FoundReportForm>,
+) -> Result<impl web::Responder, web::Error> {
+    let pet_external_id = resolve_share_link(&path.0, &app_state).await?;
+
+    send_found_report(pet_external_id, &form, &app_state).await
+}
+
+/// Sends the found report unless the honeypot field was filled
+async fn send_found_report(
+    pet_external_id: Uuid,
+    form: &forms::pet::FoundReportForm,
+    app_state: &AppState,
+) -> Result<web::HttpResponse, web::Error> {
+    let sent_response = web::HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body("<p>Mensaje enviado al dueño, ¡gracias por ayudar!</p>");
+
+    if !form.website.is_empty() {
+        return Ok(sent_response);
+    }
+
+    api::pet::report_found_pet(
+        pet_external_id,
+        &form.message,
+        Some(form.callback_contact.as_str()),
+        &app_state.repo,
+        &app_state.notification_service,
+        &app_state.clock,
+    )
+    .await
+    .map_err(|e| -> web::Error {
+        match e.downcast_ref::<api::pet::FoundReportError>() {
+            Some(report_error) => {
+                errors::UserError::FormInputValueError(report_error.to_string()).into()
+            }
+            None => errors::ServerError::InternalServerError(format!(
+                "function report_found_pet raised an error: {e}"
+            ))
+            .into(),
+        }
+    })?;
+
+    Ok(sent_response)
+}
+
+/// Shows the owner contacts hidden behind a PIN when the visitor types it right
+///
+/// The route shares the stricter rate limit of the found reports and the
+/// PIN is blocked per pet after repeated failures, so it can't be guessed
+/// by brute force.
+///
+/// # Returns
+/// * `Ok(HttpResponse)` - Contact list fragment for HTMX
+/// * `Err(web::Error)` - Form error for a wrong PIN, server error otherwise
+#[web::post("")]
+async fn unlock_owner_contacts(
+    _: middleware::csrf_token::CsrfToken,
+    app_state: web::types::State<AppState>,
+    path: web::types::Path<(Uuid,)>,
+    web::types::Form(form): web::types::Form<forms::pet::UnlockContactsForm>,
+) -> Result<impl web::Responder, web::Error> {
+    render_unlocked_owner_contacts(path.0, &form.pin, &app_state).await
+}
+
+/// Shows the owner contacts of the profile seen through a share link, see
+/// [`unlock_owner_contacts`]
+#[web::post("")]
+async fn unlock_shared_owner_contacts(
+    _: middleware::csrf_token::CsrfToken,
+    app_state: web::types::State<AppState>,
+    path: web::types::Path<(String,)>,
+    web::types::Form(form): web::types::Form<forms::pet::UnlockContactsForm>,
+) -> Result<impl web::Responder, web::Error> {
+    let pet_external_id = resolve_share_link(&path.0, &app_state).await?;
+
+    render_unlocked_owner_contacts(pet_external_id, &form.pin, &app_state).await
+}
+
+/// Renders the owner contacts list when the PIN is right
+async fn render_unlocked_owner_contacts(
+    pet_external_id: Uuid,
+    pin: &str,
+    app_state: &AppState,
+) -> Result<web::HttpResponse, web::Error> {
+    let is_unlocked =
+        api::pet::unlock_owner_contacts(pet_external_id, pin, &app_state.repo, &app_state.clock)
+            .await
+            .map_err(|e| -> web::Error {
+                match e.downcast_ref::<api::pet::PublicPinError>() {
+                    Some(pin_error) => {
+                        errors::UserError::FormInputValueError(pin_error.to_string()).into()
+                    }
+                    None => errors::ServerError::InternalServerError(format!(
+                        "function unlock_owner_contacts raised an error: {e}"
+                    ))
+                    .into(),
+                }
+            })?;
+    if !is_unlocked {
+        return Err(errors::UserError::FormInputValueError("PIN incorrecto".into()).into());
+    }
+
+    let context = tera::Context::from_value(json!({
+        "owner_contacts": get_public_owner_contacts(pet_external_id, &app_state.repo).await?,
+    }))
+    .unwrap_or_default();
+
+    let content = templates::WEB_TEMPLATES
+        .render("widgets/owner_contact_list.html", &context)
+        .map_err(|e| {
+            errors::ServerError::TemplateError(format!(
+                "at /info/external_id/unlock endpoint the template couldnt be rendered: {e}"
+            ))
+        })?;
+
     Ok(web::HttpResponse::Ok()
         .content_type("text/html; charset=utf-8")
         .body(content))
 }
 
+/// Owner contacts of a pet ready for the public profile, with their link
+async fn get_public_owner_contacts(
+    pet_external_id: Uuid,
+    repo: &crate::repo::ImplAppRepo,
+) -> Result<Vec<serde_json::Value>, web::Error> {
+    Ok(
+        api::user::get_owner_contacts(0, Some(pet_external_id), repo)
+            .await
+            .map_err(|e| {
+                errors::ServerError::InternalServerError(format!(
+                    "function get_owner_contacts raised an error: {e}"
+                ))
+            })?
+            .iter()
+            .map(|contact| {
+                let mut value = json!(contact);
+                value["href"] = json!(contact.href());
+                value["map_url"] = json!(contact.map_url());
+                value
+            })
+            .collect(),
+    )
+}
+
+/// Stores a CSRF token in the session of visitors that don't have one yet,
+/// so anonymous finders can send a found report.
+fn ensure_csrf_token(
+    app_state: &AppState,
+    cookie: &ntex_session::Session,
+) -> Result<(), web::Error> {
+    if matches!(
+        cookie.get::<String>(consts::CSRF_TOKEN_COOKIE_NAME),
+        Ok(Some(_))
+    ) {
+        return Ok(());
+    }
+
+    let (csrf_token, csrf_cookie) = app_state
+        .csrf_protec
+        .generate_token_pair(None, consts::MAX_AGE_COOKIES)
+        .map_err(|e| {
+            errors::ServerError::InternalServerError(format!("cant set token csrf protection: {e}"))
+        })?;
+
+    cookie.set(
+        consts::CSRF_TOKEN_COOKIE_NAME,
+        serde_json::to_string(&middleware::csrf_token::CsrfToken {
+            token_base64: csrf_token.b64_string(),
+            cookie_base64: csrf_cookie.b64_string(),
+        })?,
+    )?;
+
+    Ok(())
+}
+
 fn empty_tag_view(
     cookie: ntex_session::Session,
     pet_external_id: &Uuid,
@@ -130,3 +589,173 @@ fn empty_tag_view(
                 })?,
         ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{front::test_utils::app_state, repo::MockAppRepo};
+    use ntex::{http::StatusCode, web::test};
+
+    #[ntex::test]
+    async fn test_get_pet_info_view_negotiates_content_type() {
+        let pet_external_id = Uuid::new_v4();
+
+        let mut mock_repo = MockAppRepo::new();
+        mock_repo
+            .expect_is_pet_external_id_linked()
+            .returning(|_| Box::pin(async { Ok(Some(true)) }));
+        mock_repo.expect_get_pet_by_external_id().returning(|id| {
+            Box::pin(async move {
+                Ok(models::pet::Pet {
+                    id: 1,
+                    external_id: id,
+                    pet_name: "Firulais".into(),
+                    ..Default::default()
+                })
+            })
+        });
+        mock_repo
+            .expect_insert_pet_scan()
+            .returning(|_, _, _| Box::pin(async { Ok(()) }));
+        mock_repo
+            .expect_get_pet_public_pin()
+            .returning(|_| Box::pin(async { Ok(None) }));
+        mock_repo.expect_get_pet_owner_contacts().returning(|_| {
+            Box::pin(async {
+                Ok(vec![models::user_app::OwnerContact {
+                    id: 1,
+                    user_app_id: 1,
+                    full_name: "Ana".into(),
+                    contact_value: "5512345678".into(),
+                    contact_type: models::user_app::ContactType::Phone,
+                    is_primary: true,
+                    sort_order: 0,
+                    address: None,
+                    latitude: None,
+                    longitude: None,
+                    created_at: chrono::Utc::now(),
+                }])
+            })
+        });
+
+        let app = test::init_service(
+            web::App::new()
+                .state(app_state(mock_repo))
+                .service(web::scope("/info").service(get_pet_info_view)),
+        )
+        .await;
+
+        let req = test::TestRequest::with_uri(&format!("/info/{pet_external_id}"))
+            .header(header::ACCEPT, "application/json")
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+
+        let body: serde_json::Value = serde_json::from_slice(&test::read_body(res).await).unwrap();
+        assert_eq!(body["name"], "Firulais");
+        assert_eq!(body["external_id"], pet_external_id.to_string());
+        assert_eq!(body["contacts_locked"], false);
+        assert_eq!(body["owner_contacts"][0]["contact_value"], "5512345678");
+
+        // APP_CONFIG is never initialized in tests, so the page is the error
+        // one, but it's still answered as HTML
+        let req = test::TestRequest::with_uri(&format!("/info/{pet_external_id}"))
+            .header(header::ACCEPT, "text/html,application/xhtml+xml,*/*;q=0.8")
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert!(
+            res.headers()
+                .get(header::CONTENT_TYPE)
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .starts_with("text/html")
+        );
+    }
+
+    #[test]
+    fn test_public_profile_page_has_open_graph_tags() {
+        let pet = api::pet::PetPublicInfoSchema::from_pet(
+            models::pet::Pet {
+                id: 1,
+                external_id: Uuid::new_v4(),
+                pet_name: "Firulais".into(),
+                breed: "labrador".into(),
+                is_lost: true,
+                ..Default::default()
+            },
+            chrono::Utc::now().date_naive(),
+        );
+        let pet_pic_url = "https://cdn.example.com/pics/firulais";
+        let content = render_public_profile_page(
+            &pet,
+            None,
+            false,
+            None,
+            &[],
+            pet_pic_url,
+            "https://pet-info.link",
+        )
+        .unwrap();
+
+        // tera escapes the slashes of the url inside the attribute
+        assert!(content.contains(&format!(
+            r#"<meta property="og:image" content="{}"/>"#,
+            tera::escape_html(pet_pic_url)
+        )));
+        assert!(
+            content.contains(r#"<meta property="og:title" content="¡Mascota perdida! Firulais"/>"#)
+        );
+        assert!(content.contains(r#"<meta name="twitter:card" content="summary_large_image"/>"#));
+    }
+
+    #[test]
+    fn test_shared_profile_page_hides_external_id() {
+        let external_id = Uuid::new_v4();
+        let pet = api::pet::PetPublicInfoSchema::from_pet(
+            models::pet::Pet {
+                id: 1,
+                external_id,
+                pet_name: "Firulais".into(),
+                is_lost: true,
+                ..Default::default()
+            },
+            chrono::Utc::now().date_naive(),
+        );
+
+        let content = render_public_profile_page(
+            &pet,
+            Some("token123"),
+            true,
+            None,
+            &[],
+            "https://pet-info.link/share/token123/pic",
+            "https://pet-info.link",
+        )
+        .unwrap();
+
+        assert!(!content.contains(&external_id.to_string()));
+        assert!(content.contains(&format!(
+            r#"<meta property="og:url" content="{}"/>"#,
+            tera::escape_html("https://pet-info.link/share/token123")
+        )));
+        for action in ["unlock", "found"] {
+            assert!(content.contains(&format!(
+                r#"hx-post="{}/{action}""#,
+                tera::escape_html("/share/token123")
+            )));
+        }
+
+        let profile = public_profile_value(&pet, Some("token123"));
+        assert!(profile.get("external_id").is_none());
+        assert!(profile.get("pic_path").is_none());
+        assert_eq!(
+            public_profile_value(&pet, None)["external_id"],
+            external_id.to_string()
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! Handlers related to the /pet/share url, temporary links to a pet public profile
+//!
+//! ## Routes
+//! - `GET /pet/share/{pet_id}` - Active share links widget
+//! - `POST /pet/share/{pet_id}` - Create a share link, the days come in the `HX-Prompt` header
+//! - `DELETE /pet/share/{pet_id}/{token}` - Revoke a share link
+
+use anyhow::Context;
+use ntex::web;
+use serde_json::json;
+
+use crate::{
+    api, consts,
+    front::{AppState, errors, middleware, session, templates},
+};
+
+/// Renders the active share links of a pet
+#[web::get("{pet_id}")]
+async fn get_pet_share_links(
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    path: web::types::Path<(i64,)>,
+    app_state: web::types::State<AppState>,
+) -> Result<impl web::Responder, web::Error> {
+    render_pet_share_links(path.0, user.id, &app_state).await
+}
+
+/// Creates a share link that expires after the days typed by the owner
+#[web::post("{pet_id}")]
+async fn create_pet_share_link(
+    _: middleware::logged_user::CheckUserCanAccessService,
+    _: middleware::csrf_token::CsrfToken,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    path: web::types::Path<(i64,)>,
+    app_state: web::types::State<AppState>,
+    r: web::HttpRequest,
+) -> Result<impl web::Responder, web::Error> {
+    let days = r
+        .headers()
+        .get("HX-Prompt")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    api::pet::create_pet_share_link(path.0, user.id, days, &app_state.repo, &app_state.clock)
+        .await
+        .map_err(|e| -> web::Error {
+            match e.downcast_ref::<api::pet::ShareLinkError>() {
+                Some(share_error) => {
+                    errors::UserError::FormInputValueError(share_error.to_string()).into()
+                }
+                None => errors::ServerError::InternalServerError(format!(
+                    "function create_pet_share_link raised an error: {e}"
+                ))
+                .into(),
+            }
+        })?;
+
+    render_pet_share_links(path.0, user.id, &app_state).await
+}
+
+/// Revokes a share link, it stops working right away
+#[web::delete("{pet_id}/{token}")]
+async fn revoke_pet_share_link(
+    _: middleware::logged_user::CheckUserCanAccessService,
+    _: middleware::csrf_token::CsrfToken,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    path: web::types::Path<(i64, String)>,
+    app_state: web::types::State<AppState>,
+) -> Result<impl web::Responder, web::Error> {
+    let (pet_id, token) = path.into_inner();
+
+    api::pet::revoke_pet_share_link(pet_id, user.id, &token, &app_state.repo)
+        .await
+        .map_err(|e| {
+            errors::ServerError::InternalServerError(format!(
+                "function revoke_pet_share_link raised an error: {e}"
+            ))
+        })?;
+
+    render_pet_share_links(pet_id, user.id, &app_state).await
+}
+
+/// Renders the share links widget of a pet
+async fn render_pet_share_links(
+    pet_id: i64,
+    user_id: i64,
+    app_state: &AppState,
+) -> Result<web::HttpResponse, web::Error> {
+    let app_config = crate::config::APP_CONFIG
+        .get()
+        .context("failed to get app config")
+        .map_err(|e| {
+            errors::ServerError::InternalServerError(format!("failed to get app config: {e}"))
+        })?;
+
+    let context = tera::Context::from_value(json!({
+        "pet_id": pet_id,
+        "base_url": app_config.base_url(),
+        "max_days": consts::PET_SHARE_LINK_MAX_DAYS,
+        "share_links": api::pet::get_pet_share_links(pet_id, user_id, &app_state.repo)
+            .await
+            .map_err(|e| {
+                errors::ServerError::InternalServerError(format!(
+                    "function get_pet_share_links raised an error: {e}"
+                ))
+            })?,
+    }))
+    .unwrap_or_default();
+
+    let content = templates::WEB_TEMPLATES
+        .render("widgets/pet_share_links.html", &context)
+        .map_err(|e| {
+            errors::ServerError::TemplateError(format!(
+                "at /pet/share endpoint the template couldnt be rendered: {e}"
+            ))
+        })?;
+
+    Ok(web::HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(content))
+}
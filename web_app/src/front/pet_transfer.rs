@@ -0,0 +1,150 @@
+//! Handlers related to the /pet/transfer url, offers of a pet to another account
+//!
+//! A transfer is an offer: the pet stays with its owner until the recipient
+//! accepts it from its own account.
+//!
+//! ## Routes
+//! - `GET /pet/transfer` - Pets offered to the user widget
+//! - `POST /pet/transfer/{pet_id}` - Offer a pet, the recipient email comes in the `HX-Prompt` header
+//! - `POST /pet/transfer/{pet_id}/accept` - Accept an offered pet
+//! - `DELETE /pet/transfer/{pet_id}` - Decline or withdraw the offer of a pet
+
+use anyhow::Context;
+use ntex::web;
+use serde_json::json;
+
+use crate::{
+    api, config,
+    front::{AppState, errors, middleware, session, templates},
+};
+
+/// Renders the pets offered to the user
+#[web::get("")]
+async fn get_incoming_pet_transfers(
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    app_state: web::types::State<AppState>,
+) -> Result<impl web::Responder, web::Error> {
+    render_incoming_pet_transfers(user.id, &app_state).await
+}
+
+/// Offers a pet to the account of the email typed in the htmx prompt
+///
+/// Nothing changes until the recipient accepts the offer.
+#[web::post("{pet_id}")]
+async fn offer_pet_transfer(
+    _: middleware::logged_user::CheckUserCanAccessService,
+    _: middleware::csrf_token::CsrfToken,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    app_state: web::types::State<AppState>,
+    path: web::types::Path<(i64,)>,
+    r: web::HttpRequest,
+) -> Result<impl web::Responder, web::Error> {
+    let to_user_email = r
+        .headers()
+        .get("HX-Prompt")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    api::pet::offer_pet_transfer(path.0, user.id, to_user_email, &app_state.repo)
+        .await
+        .map_err(|e| -> web::Error {
+            match e.downcast_ref::<api::pet::TransferPetError>() {
+                Some(transfer_error) => {
+                    errors::UserError::FormInputValueError(transfer_error.to_string()).into()
+                }
+                None => errors::ServerError::InternalServerError(format!(
+                    "function offer_pet_transfer raised an error: {e}"
+                ))
+                .into(),
+            }
+        })?;
+
+    Ok(web::HttpResponse::Ok().finish())
+}
+
+/// Accepts an offered pet, it joins the user's pets list
+#[web::post("{pet_id}/accept")]
+async fn accept_pet_transfer(
+    _: middleware::logged_user::CheckUserCanAccessService,
+    _: middleware::csrf_token::CsrfToken,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    app_state: web::types::State<AppState>,
+    path: web::types::Path<(i64,)>,
+) -> Result<impl web::Responder, web::Error> {
+    let app_config = config::APP_CONFIG
+        .get()
+        .context("failed to get app config")
+        .map_err(web::error::ErrorInternalServerError)?;
+
+    api::pet::accept_pet_transfer(
+        path.0,
+        user.id,
+        app_config.max_pets_per_user,
+        &app_state.repo,
+        &app_state.profile_cache,
+    )
+    .await
+    .map_err(|e| -> web::Error {
+        match e.downcast_ref::<api::pet::AddPetError>() {
+            Some(add_pet_error) => {
+                errors::UserError::FormInputValueError(add_pet_error.to_string()).into()
+            }
+            None => {
+                errors::not_found_or_internal(e, "function accept_pet_transfer raised an error")
+            }
+        }
+    })?;
+
+    // refreshes the pets list and this widget
+    Ok(web::HttpResponse::Ok()
+        .set_header("HX-Trigger", "petRecordUpdated")
+        .finish())
+}
+
+/// Declines (recipient) or withdraws (owner) the offer of a pet
+#[web::delete("{pet_id}")]
+async fn cancel_pet_transfer(
+    _: middleware::csrf_token::CsrfToken,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    app_state: web::types::State<AppState>,
+    path: web::types::Path<(i64,)>,
+) -> Result<impl web::Responder, web::Error> {
+    api::pet::cancel_pet_transfer(path.0, user.id, &app_state.repo)
+        .await
+        .map_err(|e| {
+            errors::ServerError::InternalServerError(format!(
+                "function cancel_pet_transfer raised an error: {e}"
+            ))
+        })?;
+
+    render_incoming_pet_transfers(user.id, &app_state).await
+}
+
+/// Renders the widget of the pets offered to the user
+async fn render_incoming_pet_transfers(
+    user_id: i64,
+    app_state: &AppState,
+) -> Result<web::HttpResponse, web::Error> {
+    let context = tera::Context::from_value(json!({
+        "transfers": api::pet::get_incoming_pet_transfers(user_id, &app_state.repo)
+            .await
+            .map_err(|e| {
+                errors::ServerError::InternalServerError(format!(
+                    "function get_incoming_pet_transfers raised an error: {e}"
+                ))
+            })?,
+    }))
+    .unwrap_or_default();
+
+    let content = templates::WEB_TEMPLATES
+        .render("widgets/pet_transfers.html", &context)
+        .map_err(|e| {
+            errors::ServerError::TemplateError(format!(
+                "at /pet/transfer endpoint the template couldnt be rendered: {e}"
+            ))
+        })?;
+
+    Ok(web::HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(content))
+}
@@ -1,7 +1,7 @@
 use crate::{
     api, consts,
     front::{
-        AppState, errors,
+        AppState, errors, forms,
         middleware::{self, logged_user::IsUserLoggedAndCanEdit},
         session, templates,
     },
@@ -33,11 +33,19 @@ async fn get_profile_view(
             errors::ServerError::InternalServerError(format!(
                 "function get_payments raised an error: {e}"
             ))
-        })?,
+        })?
+        .iter()
+        .map(|payment| json!({
+            "created_at": payment.created_at,
+            "status": payment.status,
+            "amount": api::payment::format_money(&payment.transaction_amount, &payment.currency),
+        }))
+        .collect::<Vec<_>>(),
         "otp_step": if user.phone_reminder.is_some() {"OTP_SUCCESS"} else {"OTP_START"},
         "phone_reminder": user.phone_reminder,
         "service_price": &format!("{:.2}", consts::ADD_PET_PRICE),
         "can_access_service": user.can_access_service(),
+        "weight_unit": user.weight_unit,
     }))
     .unwrap_or_default();
 
@@ -66,6 +74,10 @@ async fn add_new_owner_contact(
     let form_request = api::user::OwnerContactRequest {
         contact_name: ammonia::clean(&form.contact_name),
         contact_value: ammonia::clean(&form.contact_value),
+        contact_type: form.contact_type.clone(),
+        address: form.address.as_deref().map(ammonia::clean),
+        latitude: form.latitude,
+        longitude: form.longitude,
     };
 
     if !form_request.fields_are_valid() {
@@ -142,6 +154,93 @@ async fn delete_owner_contact(
         .finish())
 }
 
+/// Handles the request to show a contact first on the public profile
+#[web::post("contact/{contact_id}/primary")]
+async fn set_primary_owner_contact(
+    _: middleware::logged_user::CheckUserCanAccessService,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    path: web::types::Path<(i64,)>,
+    app_state: web::types::State<AppState>,
+    _: middleware::csrf_token::CsrfToken,
+) -> Result<impl web::Responder, web::Error> {
+    api::user::set_primary_contact(user.id, path.0, &app_state.repo)
+        .await
+        .map_err(|e| {
+            errors::ServerError::WidgetTemplateError(format!(
+                "function set_primary_contact raised an error: {e}"
+            ))
+        })?;
+
+    Ok(web::HttpResponse::Ok()
+        .set_header("HX-Trigger", "ownerContactRecordUpdated")
+        .finish())
+}
+
+/// Handles the request to move a contact one position up
+#[web::post("contact/{contact_id}/move-up")]
+async fn move_owner_contact_up(
+    _: middleware::logged_user::CheckUserCanAccessService,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    path: web::types::Path<(i64,)>,
+    app_state: web::types::State<AppState>,
+    _: middleware::csrf_token::CsrfToken,
+) -> Result<impl web::Responder, web::Error> {
+    api::user::move_contact_up(user.id, path.0, &app_state.repo)
+        .await
+        .map_err(|e| {
+            errors::ServerError::WidgetTemplateError(format!(
+                "function move_contact_up raised an error: {e}"
+            ))
+        })?;
+
+    Ok(web::HttpResponse::Ok()
+        .set_header("HX-Trigger", "ownerContactRecordUpdated")
+        .finish())
+}
+
+/// Lists the external ids (QR tags) of the user split by link status
+#[web::get("external-ids")]
+async fn get_user_external_ids(
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    _: IsUserLoggedAndCanEdit,
+    app_state: web::types::State<AppState>,
+) -> Result<impl web::Responder, web::Error> {
+    let external_ids = api::pet::get_user_external_ids(user.id, &app_state.repo)
+        .await
+        .map_err(|e| {
+            errors::ServerError::InternalServerError(format!(
+                "function get_user_external_ids raised an error: {e}"
+            ))
+        })?;
+
+    Ok(web::HttpResponse::Ok().json(&external_ids))
+}
+
+/// Handles the request to change the unit the weights are shown in
+#[web::post("weight-unit")]
+async fn set_weight_unit(
+    mut user_session: session::WebAppSession,
+    form: web::types::Form<forms::user::WeightUnitForm>,
+    app_state: web::types::State<AppState>,
+    identity: Identity,
+    _: middleware::csrf_token::CsrfToken,
+) -> Result<impl web::Responder, web::Error> {
+    api::user::set_weight_unit(user_session.user.id, form.weight_unit, &app_state.repo)
+        .await
+        .map_err(|e| {
+            errors::ServerError::InternalServerError(format!(
+                "function set_weight_unit raised an error: {e}"
+            ))
+        })?;
+
+    user_session.user.weight_unit = form.weight_unit;
+    identity.remember(
+        serde_json::to_string(&user_session).unwrap(), //unwrap cause its safe, it comes internally
+    );
+
+    Ok(web::HttpResponse::Ok().finish())
+}
+
 /// Deletes all data filled by the user app
 #[web::post("/delete-data")]
 async fn delete_user_data(
@@ -4,12 +4,14 @@ use crate::{
         AppState, errors, forms,
         middleware::{
             csrf_token::CsrfToken,
-            logged_user::{CheckUserCanAccessService, IsUserLoggedAndCanEdit},
+            logged_user::{
+                CheckUserCanAccessService, CheckUserCanUseWhatsApp, IsUserLoggedAndCanEdit,
+            },
         },
         session, templates, utils,
     },
+    models,
 };
-use chrono_tz::Tz;
 use ntex::web;
 use ntex_identity::Identity;
 use ntex_session::Session;
@@ -20,11 +22,15 @@ use serde_json::json;
 async fn get_reminder_view(
     _: IsUserLoggedAndCanEdit,
     session::WebAppSession { user, .. }: session::WebAppSession,
+    r: ntex::web::HttpRequest,
     app_state: web::types::State<AppState>,
 ) -> Result<impl web::Responder, web::Error> {
+    let user_timezone = utils::user_timezone(r.headers());
     let context = tera::Context::from_value(json!({
-        "reminders": api::reminder::get_scheduled_reminders(user.id, &app_state.repo).await.unwrap_or_default(),
+        "reminders": api::reminder::get_user_reminders(user.id, true, &app_state.repo).await.unwrap_or_default(),
         "can_schedule_reminder": user.phone_reminder.is_some(),
+        "templates": api::reminder::get_reminder_templates(user.id, &app_state.repo).await.unwrap_or_default(),
+        "pets": api::pet::get_user_pets_cards(user.id, user_timezone, &app_state.repo).await.unwrap_or_default(),
     })).unwrap_or_default();
 
     let content = templates::WEB_TEMPLATES
@@ -49,7 +55,7 @@ async fn get_reminder_records(
     app_state: web::types::State<AppState>,
 ) -> Result<impl web::Responder, web::Error> {
     let context = tera::Context::from_value(json!({
-        "reminders": api::reminder::get_scheduled_reminders(user.id, &app_state.repo).await.unwrap_or_default(),
+        "reminders": api::reminder::get_user_reminders(user.id, true, &app_state.repo).await.unwrap_or_default(),
     })).unwrap_or_default();
 
     let content = templates::WEB_TEMPLATES
@@ -86,6 +92,8 @@ async fn start_verification_code_to_reminder_phone(
 #[web::post("/send-verification-code")]
 async fn send_verification_code_to_reminder_phone(
     _: CheckUserCanAccessService,
+    _: CheckUserCanUseWhatsApp,
+    session::WebAppSession { user, .. }: session::WebAppSession,
     form: web::types::Form<forms::user::ReminderPhoneToVerify>,
     cookie: Session,
     app_state: web::types::State<AppState>,
@@ -102,11 +110,39 @@ async fn send_verification_code_to_reminder_phone(
         phone = form.reminders_phone
     );
 
-    api::reminder::send_verification(&app_state.whatsapp_client, &phone_number)
-        .await
-        .map_err(|e| {
-            errors::ServerError::WidgetTemplateError(format!("otp-send-verification-template: {e}"))
-        })?;
+    let sent = api::reminder::send_verification(
+        user.id,
+        &phone_number,
+        &app_state.whatsapp_client,
+        &app_state.verification_throttle,
+        &app_state.clock,
+    )
+    .await;
+    if let Err(e) = sent {
+        let Some(throttled) =
+            e.downcast_ref::<api::verification_throttle::TooManyVerificationCodes>()
+        else {
+            return Err(errors::ServerError::WidgetTemplateError(format!(
+                "otp-send-verification-template: {e}"
+            ))
+            .into());
+        };
+
+        let context = tera::Context::from_value(json!({
+            "otp_step": "OTP_THROTTLED",
+            "wait_secs": throttled.wait_secs,
+        }))
+        .unwrap_or_default();
+        let content = templates::WEB_TEMPLATES
+            .render("widgets/otp.html", &context)
+            .unwrap_or(throttled.to_string());
+
+        // ok status so htmx swaps the message in
+        return Ok(web::HttpResponse::Ok()
+            .set_header("Retry-After", throttled.wait_secs.to_string())
+            .content_type("text/html; charset=utf-8")
+            .body(content));
+    }
 
     cookie
         .set::<String>(consts::OTP_PHONE_COOKIE_NAME, phone_number.to_string())
@@ -232,46 +268,264 @@ async fn delete_reminder(
         .finish())
 }
 
-/// Handles the request to create a reminder
-#[web::post("")]
-async fn create_reminder(
+/// Handles the request to move a scheduled reminder to a new time
+///
+/// # Returns
+/// * `Ok(HttpResponse)` - The DST adjustment applied to the time, if any;
+///   400 with the reason when the time is outside the scheduling window
+/// * `Err(web::Error)` - Not found if the reminder isn't the user's, server
+///   error otherwise
+#[web::post("/{reminder_id}/reschedule")]
+async fn reschedule_reminder(
     _: IsUserLoggedAndCanEdit,
     session::WebAppSession { user, .. }: session::WebAppSession,
     r: ntex::web::HttpRequest,
-    form: web::types::Form<forms::user::UserReminderForm>,
+    params: web::types::Path<(i64,)>,
+    form: web::types::Form<forms::user::ReminderRescheduleForm>,
     app_state: web::types::State<AppState>,
     _: CsrfToken,
 ) -> Result<impl web::Responder, web::Error> {
-    let user_timezone: Tz =
-        utils::extract_usertimezone(r.headers()).unwrap_or(Tz::America__Mexico_City);
+    let user_timezone = utils::user_timezone(r.headers());
 
-    if user.phone_reminder.is_none() {
-        return Ok(web::HttpResponse::BadRequest()
-            .content_type("text/html; charset=utf-8")
-            .finish());
+    let adjustment = match api::reminder::reschedule_reminder(
+        params.0,
+        &user,
+        form.when,
+        user_timezone,
+        &app_state.repo,
+        &app_state.notification_service,
+        &app_state.clock,
+    )
+    .await
+    {
+        Ok(adjustment) => adjustment,
+        Err(e) => {
+            return match e.downcast_ref::<api::reminder::ReminderTimeError>() {
+                Some(time_error) => Ok(web::HttpResponse::BadRequest()
+                    .content_type("text/html; charset=utf-8")
+                    .body(time_error.to_string())),
+                None => Err(errors::not_found_or_internal(
+                    e,
+                    "function reschedule_reminder raised an error",
+                )),
+            };
+        }
+    };
+
+    Ok(web::HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .set_header("HX-Trigger", "reminderRecordUpdated")
+        .body(
+            adjustment
+                .map(|adjustment| adjustment.to_string())
+                .unwrap_or_default(),
+        ))
+}
+
+/// Saves a reminder template of the user
+#[web::post("/template")]
+async fn create_reminder_template(
+    _: IsUserLoggedAndCanEdit,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    form: web::types::Form<forms::user::ReminderTemplateForm>,
+    app_state: web::types::State<AppState>,
+    _: CsrfToken,
+) -> Result<impl web::Responder, web::Error> {
+    if let Err(e) =
+        api::reminder::save_reminder_template(user.id, &form.name, &form.body, &app_state.repo)
+            .await
+    {
+        return match e.downcast_ref::<api::reminder::ReminderTemplateError>() {
+            Some(template_error) => Ok(web::HttpResponse::BadRequest()
+                .content_type("text/html; charset=utf-8")
+                .body(template_error.to_string())),
+            None => Err(errors::ServerError::InternalServerError(format!(
+                "function save_reminder_template raised an error: {e}"
+            ))
+            .into()),
+        };
     }
 
-    if let Some(user_dt) = form.when.and_local_timezone(user_timezone).single() {
-        api::reminder::schedule_reminder(
-            api::reminder::ScheduleReminderInfo {
-                user_id: user.id,
-                phone_number: user.phone_reminder.unwrap(),
-                when: user_dt,
-                body: form.body.to_string(),
-            },
-            &app_state.repo,
-            &app_state.notification_service,
-        )
+    Ok(web::HttpResponse::Created()
+        .set_header("HX-Refresh", "true")
+        .finish())
+}
+
+/// Removes a reminder template of the user
+#[web::delete("/template/{template_id}")]
+async fn delete_reminder_template(
+    _: IsUserLoggedAndCanEdit,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    params: web::types::Path<(i64,)>,
+    app_state: web::types::State<AppState>,
+    _: CsrfToken,
+) -> Result<impl web::Responder, web::Error> {
+    api::reminder::delete_reminder_template(params.0, user.id, &app_state.repo)
         .await
         .map_err(|e| {
             errors::ServerError::InternalServerError(format!(
-                "function schedule_reminder raised an error: {e}"
+                "function delete_reminder_template raised an error: {e}"
             ))
         })?;
+
+    Ok(web::HttpResponse::Ok()
+        .set_header("HX-Refresh", "true")
+        .finish())
+}
+
+/// Shows whether the WhatsApp message of a sent reminder was delivered
+#[web::get("/delivery/{reminder_id}")]
+async fn get_reminder_delivery_status(
+    _: IsUserLoggedAndCanEdit,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    params: web::types::Path<(i64,)>,
+    app_state: web::types::State<AppState>,
+) -> Result<impl web::Responder, web::Error> {
+    let status = api::reminder::get_reminder_delivery_status(
+        params.0,
+        user.id,
+        &app_state.repo,
+        &app_state.notification_service,
+    )
+    .await
+    .map_err(|e| {
+        errors::ServerError::ExternalServiceError(format!(
+            "function get_reminder_delivery_status raised an error: {e}"
+        ))
+    })?;
+
+    let label = match status {
+        None | Some(models::reminder::DeliveryStatus::Sent) => "Enviado",
+        Some(models::reminder::DeliveryStatus::Delivered) => "Entregado",
+        Some(models::reminder::DeliveryStatus::Read) => "Leído",
+        Some(models::reminder::DeliveryStatus::Failed) => "No entregado",
+    };
+
+    Ok(web::HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(format!("<small>{label}</small>")))
+}
+
+/// Previews when a reminder fires in the user's timezone
+///
+/// # Query Parameters
+/// * `when` - Local date and time of the reminder (`datetime-local` input format)
+/// * `every_days` - Optional days between occurrences
+///
+/// # Returns
+/// JSON with the UTC `send_at` instant, the `local` formatted time and the
+/// DST `adjustment` applied to the time, if any
+#[web::get("/preview")]
+async fn preview_reminder(
+    _: IsUserLoggedAndCanEdit,
+    r: ntex::web::HttpRequest,
+    query: web::types::Query<forms::user::ReminderPreviewQuery>,
+    app_state: web::types::State<AppState>,
+) -> Result<impl web::Responder, web::Error> {
+    let user_timezone = utils::user_timezone(r.headers());
+
+    match api::reminder::preview_next_occurrence(
+        query.when,
+        user_timezone,
+        query.every_days.map(chrono::Days::new),
+        &app_state.clock,
+    ) {
+        Ok(preview) => Ok(web::HttpResponse::Ok().json(&preview)),
+        Err(e) => Ok(web::HttpResponse::BadRequest().json(&json!({
+            "error": e.to_string(),
+        }))),
+    }
+}
+
+/// Handles the request to create a reminder
+///
+/// Only the WhatsApp channel needs the role to allow it, email reminders
+/// are open to every user with access to the service.
+#[web::post("")]
+async fn create_reminder(
+    _: IsUserLoggedAndCanEdit,
+    _: CheckUserCanAccessService,
+    session::WebAppSession { user, .. }: session::WebAppSession,
+    r: ntex::web::HttpRequest,
+    form: web::types::Form<forms::user::UserReminderForm>,
+    app_state: web::types::State<AppState>,
+    _: CsrfToken,
+) -> Result<impl web::Responder, web::Error> {
+    let user_timezone = utils::user_timezone(r.headers());
+
+    let channels = form.channels();
+    if channels.contains(models::reminder::ReminderNotificationType::WhatsApp) {
+        if !user.capabilities().can_use_whatsapp {
+            return Err(errors::UserError::NeedSubscription.into());
+        }
+        if user.phone_reminder.is_none() {
+            return Ok(web::HttpResponse::BadRequest()
+                .content_type("text/html; charset=utf-8")
+                .finish());
+        }
+    }
+
+    let (body, pet_id) = match api::reminder::build_reminder_body(
+        &form.body,
+        &form.template,
+        form.pet_external_id,
+        form.when.date(),
+        user.id,
+        &app_state.repo,
+    )
+    .await
+    {
+        Ok(body_and_pet) => body_and_pet,
+        Err(e) => {
+            return match e.downcast_ref::<api::reminder::ReminderTemplateError>() {
+                Some(template_error) => Ok(web::HttpResponse::BadRequest()
+                    .content_type("text/html; charset=utf-8")
+                    .body(template_error.to_string())),
+                None => Err(errors::not_found_or_internal(
+                    e,
+                    "function build_reminder_body raised an error",
+                )),
+            };
+        }
+    };
+
+    let (send_at, adjustment) = api::reminder::resolve_local_datetime(form.when, user_timezone);
+
+    let scheduled = api::reminder::schedule_reminder(
+        api::reminder::ScheduleReminderInfo {
+            user_id: user.id,
+            channels,
+            phone_number: user.phone_reminder,
+            email: Some(user.email),
+            when: send_at.with_timezone(&user_timezone),
+            body,
+            pet_id,
+        },
+        &app_state.repo,
+        &app_state.notification_service,
+        &app_state.clock,
+    )
+    .await;
+
+    if let Err(e) = scheduled {
+        // the form shows the reason when the time is outside the window
+        return match e.downcast_ref::<api::reminder::ReminderTimeError>() {
+            Some(time_error) => Ok(web::HttpResponse::BadRequest()
+                .content_type("text/html; charset=utf-8")
+                .body(time_error.to_string())),
+            None => Err(errors::ServerError::InternalServerError(format!(
+                "function schedule_reminder raised an error: {e}"
+            ))
+            .into()),
+        };
     }
 
     Ok(web::HttpResponse::Created()
         .content_type("text/html; charset=utf-8")
         .set_header("HX-Trigger", "reminderRecordUpdated")
-        .finish())
+        .body(
+            adjustment
+                .map(|adjustment| adjustment.to_string())
+                .unwrap_or_default(),
+        ))
 }
@@ -4,7 +4,10 @@
 //! Routes are grouped by functionality into logical scopes for better organization
 //! and maintainability.
 
-use super::{blog, checkout, pet, pet_health, pet_note, pet_public, profile, reminder};
+use super::{
+    admin, blog, checkout, middleware, pet, pet_audit, pet_found_report, pet_health, pet_note,
+    pet_public, pet_share, pet_transfer, profile, reminder,
+};
 use ntex::web;
 
 /// Configures public pet profile routes.
@@ -13,10 +16,60 @@ use ntex::web;
 /// These routes are typically used for public pet profiles that can be accessed
 /// via QR codes or direct links.
 ///
+/// Requests are rate limited per client IP since these routes don't
+/// require authentication; found reports and PIN attempts have their own,
+/// stricter limit.
+///
 /// # Routes
-/// - `GET /info/{pet_external_id}` - View public pet information
-pub fn pet_public_profile(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::scope("/info").service((pet_public::get_pet_info_view,)));
+/// - `GET /info/{pet_external_id}` - View public pet information, JSON with `Accept: application/json`
+/// - `POST /info/batch` - Public information of up to 50 pets, from a JSON array of external ids
+/// - `POST /info/{pet_external_id}/found` - Send a found report to the owner
+/// - `POST /info/{pet_external_id}/unlock` - Show the owner contacts hidden behind a PIN
+/// - `GET /share/{token}` - View public pet information through a temporary share link
+/// - `POST /share/{token}/found` - Send a found report through a temporary share link
+/// - `POST /share/{token}/unlock` - Show the owner contacts through a temporary share link
+/// - `GET /share/{token}/pic` - Pet picture through a temporary share link
+pub fn pet_public_profile(
+    cfg: &mut web::ServiceConfig,
+    rate_limit: middleware::rate_limit::RateLimit,
+    found_report_rate_limit: middleware::rate_limit::RateLimit,
+) {
+    cfg.service(
+        web::scope("/share")
+            .wrap(rate_limit.clone())
+            .service(
+                web::scope("/{token}/found")
+                    .wrap(found_report_rate_limit.clone())
+                    .service((pet_public::report_found_shared_pet,)),
+            )
+            .service(
+                web::scope("/{token}/unlock")
+                    .wrap(found_report_rate_limit.clone())
+                    .service((pet_public::unlock_shared_owner_contacts,)),
+            )
+            .service((
+                pet_public::get_shared_pet_pic,
+                pet_public::get_shared_pet_info_view,
+            )),
+    );
+    cfg.service(
+        web::scope("/info")
+            .wrap(rate_limit)
+            .service(
+                web::scope("/{pet_external_id}/found")
+                    .wrap(found_report_rate_limit.clone())
+                    .service((pet_public::report_found_pet,)),
+            )
+            .service(
+                web::scope("/{pet_external_id}/unlock")
+                    .wrap(found_report_rate_limit)
+                    .service((pet_public::unlock_owner_contacts,)),
+            )
+            .service((
+                pet_public::get_pets_info_batch,
+                pet_public::get_pet_info_view,
+            )),
+    );
 }
 
 /// Configures pet management routes.
@@ -29,51 +82,121 @@ pub fn pet_public_profile(cfg: &mut web::ServiceConfig) {
 /// - `GET /pet` - Pet management dashboard
 /// - `GET /pet/list` - List user's pets
 /// - `GET /pet/details/{pet_id}` - Pet details form
+/// - `GET /pet/breeds` - Known breeds autocomplete (JSON)
+/// - `GET /pet/external-id/{uuid}/status` - External id existence and link status (JSON)
+/// - `GET /pet/scans/{pet_external_id}` - Public profile visits widget
 /// - `POST /pet/create` - Create new pet
 /// - `PUT /pet/edit/{pet_id}` - Update pet details
 /// - `DELETE /pet/delete/{pet_id}` - Delete pet
+/// - `POST /pet/rotate-id/{pet_id}` - Replace pet external id
+/// - `POST /pet/public-pin/{pet_id}` - Set the PIN that hides the owner contacts
+/// - `POST /pet/lost/{pet_id}` - Mark pet as lost or found
+/// - `POST /pet/spaying/{pet_id}` - Set pet spaying/neutering status
 /// - `GET /pet/qr_code/{pet_external_id}` - Generate QR code
-/// - `GET /pet/pdf_report/{pet_id}` - Generate PDF report
+/// - `GET /pet/pdf_report/{pet_id}?sections=&quality=` - Generate PDF report
+/// - `GET /pet/vaccine_cert/{pet_id}?signature=` - Generate vaccine certificate PDF
+/// - `GET /pet/weights.csv/{pet_id}` - Download weight history CSV
+/// - `GET /pet/photos.zip/{pet_id}` - Download all the pet photos as a ZIP
 /// - `GET /pet/public_pic/{pet_external_id}` - Get pet picture
 /// - `GET /pet/pass/{pet_external_id}` - Download Apple Wallet pass
+/// - `GET /pet/pass_preview/{pet_external_id}` - Preview of the Apple Wallet pass
 ///
 /// # Health Sub-routes (/pet/health)
 /// - `GET /pet/health/{pet_external_id}/{health_type}` - Health records view
 /// - `POST /pet/health/add` - Add health record
+/// - `POST /pet/health/import` - Import health records from a CSV
+/// - `PUT /pet/health/{pet_external_id}/{health_type}/{record_id}` - Update health record
 /// - `DELETE /pet/health/delete` - Delete health record
+/// - `DELETE /pet/health/{pet_external_id}/{health_type}` - Delete all health records of a type
 ///
 /// # Notes Sub-routes (/pet/note)
 /// - `GET /pet/note/{pet_id}` - Pet notes view
 /// - `POST /pet/note/new` - Create new note
+/// - `POST /pet/note/edit/{note_id}` - Edit note
 /// - `GET /pet/note/list/{pet_id}` - Get pet notes
+/// - `GET /pet/note/search/{pet_id}?q=` - Search pet notes by title or content
 /// - `DELETE /pet/note/delete` - Delete note
+///
+/// # Share Links Sub-routes (/pet/share)
+/// - `GET /pet/share/{pet_id}` - Active temporary share links widget
+/// - `POST /pet/share/{pet_id}` - Create temporary share link
+/// - `DELETE /pet/share/{pet_id}/{token}` - Revoke share link
+///
+/// # Transfer Sub-routes (/pet/transfer)
+/// - `GET /pet/transfer` - Pets offered to the user widget
+/// - `POST /pet/transfer/{pet_id}` - Offer a pet to another account
+/// - `POST /pet/transfer/{pet_id}/accept` - Accept an offered pet
+/// - `DELETE /pet/transfer/{pet_id}` - Decline or withdraw an offer
+///
+/// # Audit Sub-routes (/pet/audit)
+/// - `GET /pet/audit/{pet_id}` - History of changes widget
+///
+/// # Found Report Sub-routes (/pet/found-report)
+/// - `GET /pet/found-report/{pet_id}` - Messages left by people who found the pet
 pub fn pet(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::scope("/pet").service((
-        pet::get_pet_view,
-        pet::user_pets_list,
-        pet::render_pet_details_form,
-        pet::create_pet_request,
-        pet::get_profile_qr_code,
-        pet::get_pdf_report,
-        pet::get_pet_public_pic,
-        pet::serve_webmanifest,
-        pet::download_pet_pass,
-        pet::delete_pet,
-        pet::get_pet_details_form,
-        pet::edit_pet_details,
-        web::scope("/health").service((
-            pet_health::get_pet_health_view,
-            pet_health::pet_health_records,
-            pet_health::add_health_record,
-            pet_health::delete_health_record,
-        )),
-        web::scope("/note").service((
-            pet_note::get_pet_notes_view,
-            pet_note::new_pet_note,
-            pet_note::get_pet_notes,
-            pet_note::delete_pet_note,
-        )),
-    )));
+    cfg.service(
+        web::scope("/pet")
+            // handlers split in groups of 12, the largest tuple of services ntex accepts
+            .service((
+                pet::get_pet_view,
+                pet::user_pets_list,
+                pet::render_pet_details_form,
+                pet::search_breeds,
+                pet::get_pet_external_id_status,
+                pet::get_pet_scan_stats,
+                pet::create_pet_request,
+                pet::get_profile_qr_code,
+                pet::get_pdf_report,
+                pet::get_vaccine_certificate,
+                pet::get_pet_weights_csv,
+                pet::get_pet_photos_zip,
+            ))
+            .service((
+                pet::get_pet_public_pic,
+                pet::serve_webmanifest,
+                pet::download_pet_pass,
+                pet::get_pet_pass_preview,
+                pet::delete_pet,
+                pet::rotate_pet_external_id,
+                pet::set_pet_public_pin,
+                pet::set_pet_lost_status,
+                pet::set_pet_spaying_neutering_status,
+                pet::get_pet_details_form,
+                pet::edit_pet_details,
+            ))
+            .service((
+                web::scope("/health").service((
+                    pet_health::get_pet_health_view,
+                    pet_health::pet_health_records,
+                    pet_health::add_health_record,
+                    pet_health::import_health_records,
+                    pet_health::update_health_record,
+                    pet_health::delete_health_record,
+                    pet_health::delete_all_health_records,
+                )),
+                web::scope("/note").service((
+                    pet_note::get_pet_notes_view,
+                    pet_note::new_pet_note,
+                    pet_note::edit_pet_note,
+                    pet_note::get_pet_notes,
+                    pet_note::search_pet_notes,
+                    pet_note::delete_pet_note,
+                )),
+                web::scope("/share").service((
+                    pet_share::get_pet_share_links,
+                    pet_share::create_pet_share_link,
+                    pet_share::revoke_pet_share_link,
+                )),
+                web::scope("/transfer").service((
+                    pet_transfer::get_incoming_pet_transfers,
+                    pet_transfer::offer_pet_transfer,
+                    pet_transfer::accept_pet_transfer,
+                    pet_transfer::cancel_pet_transfer,
+                )),
+                web::scope("/audit").service(pet_audit::get_pet_audit),
+                web::scope("/found-report").service(pet_found_report::get_pet_found_reports),
+            )),
+    );
 }
 
 /// Configures reminder management routes.
@@ -85,23 +208,37 @@ pub fn pet(cfg: &mut web::ServiceConfig) {
 /// # Routes
 /// - `GET /reminder` - Reminders management view
 /// - `GET /reminder/list` - Get user's reminders
+/// - `GET /reminder/preview` - Preview when a reminder fires
 /// - `POST /reminder/create` - Create new reminder
 /// - `DELETE /reminder/delete/{reminder_id}` - Delete reminder
+/// - `POST /reminder/{reminder_id}/reschedule` - Move a reminder to a new time
+/// - `GET /reminder/delivery/{reminder_id}` - WhatsApp delivery status of a sent reminder
+/// - `POST /reminder/template` - Save a reminder template
+/// - `DELETE /reminder/template/{template_id}` - Delete a saved reminder template
 /// - `POST /reminder/phone/start-verification` - Start phone verification
 /// - `POST /reminder/phone/send-code` - Send verification code
 /// - `POST /reminder/phone/verify` - Verify phone number
 /// - `DELETE /reminder/phone/remove` - Remove verified phone
 pub fn reminders(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::scope("/reminder").service((
-        reminder::get_reminder_view,
-        reminder::get_reminder_records,
-        reminder::send_verification_code_to_reminder_phone,
-        reminder::verify_reminder_phone,
-        reminder::start_verification_code_to_reminder_phone,
-        reminder::remove_verified_phone,
-        reminder::create_reminder,
-        reminder::delete_reminder,
-    )));
+    cfg.service(
+        web::scope("/reminder")
+            // handlers split in groups of 12, the largest tuple of services ntex accepts
+            .service((
+                reminder::get_reminder_view,
+                reminder::get_reminder_records,
+                reminder::preview_reminder,
+                reminder::send_verification_code_to_reminder_phone,
+                reminder::verify_reminder_phone,
+                reminder::start_verification_code_to_reminder_phone,
+                reminder::remove_verified_phone,
+                reminder::create_reminder,
+                reminder::delete_reminder,
+                reminder::get_reminder_delivery_status,
+                reminder::create_reminder_template,
+                reminder::delete_reminder_template,
+            ))
+            .service(reminder::reschedule_reminder),
+    );
 }
 
 /// Configures user profile management routes.
@@ -114,6 +251,10 @@ pub fn reminders(cfg: &mut web::ServiceConfig) {
 /// - `POST /profile/contact/add` - Add new owner contact
 /// - `GET /profile/contact/list` - Get owner contacts
 /// - `DELETE /profile/contact/delete/{contact_id}` - Delete owner contact
+/// - `POST /profile/contact/{contact_id}/primary` - Show owner contact first
+/// - `POST /profile/contact/{contact_id}/move-up` - Move owner contact one position up
+/// - `POST /profile/weight-unit` - Change the unit the weights are shown in
+/// - `GET /profile/external-ids` - QR tags of the user, linked and unlinked
 /// - `DELETE /profile/delete-data` - Delete all user data
 /// - `POST /profile/logout` - Close user session
 pub fn user_profile(cfg: &mut web::ServiceConfig) {
@@ -122,6 +263,10 @@ pub fn user_profile(cfg: &mut web::ServiceConfig) {
         profile::add_new_owner_contact,
         profile::get_owner_contacts,
         profile::delete_owner_contact,
+        profile::set_primary_owner_contact,
+        profile::move_owner_contact_up,
+        profile::set_weight_unit,
+        profile::get_user_external_ids,
         profile::delete_user_data,
         profile::close_session,
     )));
@@ -147,7 +292,16 @@ pub fn checkout(cfg: &mut web::ServiceConfig) {
 /// pages. These routes are typically public and don't require authentication.
 ///
 /// # Routes
+/// - `GET /blog?page=&q=` - Blog entries, paginated and filtered by title or content
 /// - `GET /blog/{entry_id}` - View blog entry
 pub fn blog(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::scope("/blog").service((blog::get_blog_entry,)));
+    cfg.service(web::scope("/blog").service((blog::get_blog_index, blog::get_blog_entry)));
+}
+
+/// Configures routes only available to admin accounts.
+///
+/// # Routes
+/// - `GET /admin/metrics` - Usage metrics as JSON
+pub fn admin(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::scope("/admin").service((admin::get_usage_metrics,)));
 }
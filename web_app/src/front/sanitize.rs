@@ -0,0 +1,109 @@
+//! HTML sanitization of the rich text typed by the owners
+//!
+//! The pet `about` and the pet notes are written with the Quill editor and
+//! rendered as HTML, so they are cleaned with the allowlist below and nothing
+//! else. Any tag, attribute or class outside of it is dropped.
+
+use std::sync::LazyLock;
+
+/// Tags produced by the editor toolbar (bold, italic, underline, blockquote,
+/// lists) plus paragraphs and line breaks.
+pub const RICH_TEXT_TAGS: &[&str] = &[
+    "p",
+    "br",
+    "strong",
+    "em",
+    "u",
+    "s",
+    "blockquote",
+    "ul",
+    "ol",
+    "li",
+];
+
+/// Classes the editor sets on indented paragraphs and list items.
+pub const RICH_TEXT_INDENT_CLASSES: &[&str] = &[
+    "ql-indent-1",
+    "ql-indent-2",
+    "ql-indent-3",
+    "ql-indent-4",
+    "ql-indent-5",
+    "ql-indent-6",
+    "ql-indent-7",
+    "ql-indent-8",
+];
+
+/// Tags removed along with their content instead of being unwrapped.
+const REMOVED_CONTENT_TAGS: &[&str] = &["script", "style"];
+
+static RICH_TEXT_CLEANER: LazyLock<ammonia::Builder<'static>> = LazyLock::new(|| {
+    let mut builder = ammonia::Builder::empty();
+    builder
+        .tags(RICH_TEXT_TAGS.iter().copied().collect())
+        .clean_content_tags(REMOVED_CONTENT_TAGS.iter().copied().collect())
+        // the editor marks bullet items of an `ol` with it
+        .add_tag_attributes("li", &["data-list"])
+        .add_allowed_classes("p", RICH_TEXT_INDENT_CLASSES)
+        .add_allowed_classes("li", RICH_TEXT_INDENT_CLASSES);
+    builder
+});
+
+/// Cleans the rich text of a pet `about` or a pet note.
+///
+/// # Arguments
+/// * `html` - HTML sent by the editor
+///
+/// # Returns
+/// The HTML keeping only the [`RICH_TEXT_TAGS`] allowlist
+pub fn rich_text(html: &str) -> String {
+    RICH_TEXT_CLEANER.clean(html).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rich_text_keeps_allowed_tags() {
+        let html = concat!(
+            "<p>Le gusta <strong>correr</strong>, <em>nadar</em> y <u>dormir</u><br></p>",
+            "<blockquote>Muy <s>travieso</s></blockquote>",
+            "<ol><li data-list=\"bullet\">Croquetas</li>",
+            "<li data-list=\"bullet\" class=\"ql-indent-1\">Pollo</li></ol>",
+            "<ul><li>Juguetes</li></ul>",
+            "<p class=\"ql-indent-2\">Fin</p>",
+        );
+
+        assert_eq!(rich_text(html), html);
+    }
+
+    #[test]
+    fn test_rich_text_strips_disallowed_tags() {
+        let cases = [
+            ("<script>alert(1)</script><p>Hola</p>", "<p>Hola</p>"),
+            ("<style>p{}</style>Hola", "Hola"),
+            ("<img src=x onerror=alert(1)>Hola", "Hola"),
+            ("<a href=\"https://spam.example\">Hola</a>", "Hola"),
+            ("<h1>Hola</h1>", "Hola"),
+            ("<iframe src=\"https://spam.example\"></iframe>Hola", "Hola"),
+        ];
+
+        for (html, expected) in cases {
+            assert_eq!(rich_text(html), expected, "{html}");
+        }
+    }
+
+    #[test]
+    fn test_rich_text_strips_disallowed_attributes() {
+        assert_eq!(
+            rich_text(
+                "<p style=\"color:red\" onclick=\"alert(1)\" class=\"ql-indent-1 evil\">Hola</p>"
+            ),
+            "<p class=\"ql-indent-1\">Hola</p>"
+        );
+        assert_eq!(
+            rich_text("<strong class=\"ql-indent-1\" data-list=\"bullet\">Hola</strong>"),
+            "<strong>Hola</strong>"
+        );
+    }
+}
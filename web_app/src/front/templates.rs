@@ -58,6 +58,7 @@ mod tests {
 
         // Verify expected report template exists
         assert!(templates.get_template("pet_default.typ").is_ok());
+        assert!(templates.get_template("vaccine_certificate.typ").is_ok());
     }
 
     #[test]
@@ -86,5 +87,27 @@ mod tests {
         assert!(templates.get_template("errors/url_not_found.html").is_ok());
         assert!(templates.get_template("widgets/add_pet_form.html").is_ok());
         assert!(templates.get_template("widgets/pets.html").is_ok());
+        assert!(
+            templates
+                .get_template("widgets/pet_scan_stats.html")
+                .is_ok()
+        );
+        assert!(
+            templates
+                .get_template("widgets/pet_share_links.html")
+                .is_ok()
+        );
+        assert!(templates.get_template("widgets/pet_transfers.html").is_ok());
+        assert!(
+            templates
+                .get_template("widgets/pet_pass_preview.html")
+                .is_ok()
+        );
+        assert!(templates.get_template("widgets/pet_audit.html").is_ok());
+        assert!(
+            templates
+                .get_template("widgets/pet_found_reports.html")
+                .is_ok()
+        );
     }
 }
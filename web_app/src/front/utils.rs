@@ -2,6 +2,7 @@
 //!
 //! Here are functions needed in all the front end app
 
+use crate::{config, consts};
 use anyhow::Context;
 use chrono::NaiveDate;
 use chrono_tz::Tz;
@@ -68,6 +69,36 @@ async fn get_bytes_as_str(
     None
 }
 
+/// Extracts the exact name of a multipart field from its `content-disposition` header.
+///
+/// Only the `name` parameter is read, so `filename` or other fields containing
+/// the searched name can't be mistaken for it.
+///
+/// # Arguments
+/// * `content_disposition` - Header value, e.g. `form-data; name="pet_breed"`
+///
+/// # Returns
+/// * `Option<&str>` - The unquoted field name, None if the header has no name
+pub fn get_field_name(content_disposition: &str) -> Option<&str> {
+    content_disposition.split(';').skip(1).find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case("name")
+            .then(|| value.trim().trim_matches('"'))
+    })
+}
+
+/// Name of a multipart field, empty if its `content-disposition` header has none.
+pub fn get_multipart_field_name(field: &ntex_multipart::Field) -> String {
+    field
+        .headers()
+        .get("content-disposition")
+        .and_then(|value| value.to_str().ok())
+        .and_then(get_field_name)
+        .unwrap_or_default()
+        .to_string()
+}
+
 /// Extracts and concatenates all UTF-8 string values from a multipart field.
 ///
 /// This function processes a multipart field stream and attempts to convert
@@ -129,6 +160,85 @@ pub fn extract_usertimezone(request_headers: &ntex::http::HeaderMap) -> anyhow::
         .with_context(|| format!("Invalid timezone: '{}'", timezone_str))
 }
 
+/// Timezone of the app for users whose timezone isn't known, the
+/// configured `DEFAULT_TIMEZONE` or
+/// [DEFAULT_TIMEZONE](consts::DEFAULT_TIMEZONE) before the config is loaded.
+pub fn default_timezone() -> Tz {
+    config::APP_CONFIG.get().map_or(
+        consts::DEFAULT_TIMEZONE,
+        config::AppConfig::default_timezone,
+    )
+}
+
+/// Timezone of the user from the request headers, see
+/// [extract_usertimezone], falling back to [default_timezone] when the
+/// header is missing or invalid.
+pub fn user_timezone(request_headers: &ntex::http::HeaderMap) -> Tz {
+    extract_usertimezone(request_headers).unwrap_or_else(|_| default_timezone())
+}
+
+/// Checks if the request asks for JSON rather than HTML.
+///
+/// The first of `application/json` or `text/html` listed in the `Accept`
+/// header wins; browsers and requests without the header get HTML.
+///
+/// # Arguments
+/// * `request_headers` - HTTP headers from the incoming request
+///
+/// # Returns
+/// * `bool` - `true` if the response should be JSON
+pub fn prefers_json(request_headers: &ntex::http::HeaderMap) -> bool {
+    request_headers
+        .get(ntex::http::header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .into_iter()
+        .flat_map(|accept| accept.split(','))
+        .map(|media_range| media_range.split(';').next().unwrap_or_default().trim())
+        .find(|media_type| matches!(*media_type, "application/json" | "text/html"))
+        == Some("application/json")
+}
+
+/// Formats a date as an HTTP date (RFC 7231), e.g. for `Last-Modified`.
+pub fn fmt_http_date(date: chrono::DateTime<chrono::Utc>) -> String {
+    date.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Checks if the client's cached copy of a resource is still fresh.
+///
+/// `If-None-Match` takes precedence over `If-Modified-Since`, as in
+/// RFC 7232; entity tags are compared weakly.
+///
+/// # Arguments
+/// * `request_headers` - HTTP headers from the incoming request
+/// * `etag` - Current entity tag of the resource
+/// * `last_modified` - Current last modification of the resource
+///
+/// # Returns
+/// * `bool` - `true` if a `304 Not Modified` can be answered
+pub fn is_not_modified(
+    request_headers: &ntex::http::HeaderMap,
+    etag: &str,
+    last_modified: chrono::DateTime<chrono::Utc>,
+) -> bool {
+    let opaque_tag = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+
+    if let Some(if_none_match) = request_headers.get(ntex::http::header::IF_NONE_MATCH) {
+        let etag = opaque_tag(etag);
+        return if_none_match
+            .to_str()
+            .unwrap_or_default()
+            .split(',')
+            .any(|tag| tag.trim() == "*" || opaque_tag(tag) == etag);
+    }
+
+    request_headers
+        .get(ntex::http::header::IF_MODIFIED_SINCE)
+        .and_then(|since| since.to_str().ok())
+        .and_then(|since| chrono::DateTime::parse_from_rfc2822(since).ok())
+        // HTTP dates have no fraction of second
+        .is_some_and(|since| last_modified.timestamp() <= since.timestamp())
+}
+
 /// Formats the difference between two dates in a human-readable format.
 ///
 /// Calculates the time span between two dates and returns a localized string
@@ -192,25 +302,29 @@ pub fn fmt_dates_difference(start_date: NaiveDate, end_date: NaiveDate) -> Strin
     }
 }
 
-/// Gets the current UTC datetime with time set to 00:00:00.
+/// Gets the current date in the given timezone.
+///
+/// "Today" depends on where the user is: while it is still the 14th in
+/// Mexico City it can already be the 15th in UTC. Use this instead of the
+/// UTC date for ages and health record dates.
 ///
-/// Returns the current UTC date with the time component reset to midnight.
-/// Useful for date-only comparisons or when you need a consistent time
-/// for date-based operations.
+/// # Arguments
+/// * `tz` - Timezone of the user
 ///
 /// # Returns
-/// * `chrono::DateTime<chrono::Utc>` - Current UTC date at 00:00:00
+/// * `NaiveDate` - Current local date of the user
 ///
 /// # Example
 /// ```rust
-/// let today_start = get_utc_now_with_default_time();
-/// // 2023-06-15T00:00:00Z
+/// let today = get_now_date_in_tz(Tz::America__Mexico_City);
 /// ```
-pub fn get_utc_now_with_default_time() -> chrono::DateTime<chrono::Utc> {
-    chrono::Utc::now()
-        .with_time(chrono::NaiveTime::default())
-        .single()
-        .unwrap()
+pub fn get_now_date_in_tz(tz: Tz) -> NaiveDate {
+    date_in_tz(chrono::Utc::now(), tz)
+}
+
+/// Converts a UTC datetime to the local date of a timezone.
+fn date_in_tz(datetime: chrono::DateTime<chrono::Utc>, tz: Tz) -> NaiveDate {
+    datetime.with_timezone(&tz).date_naive()
 }
 
 /// Filters a string to contain only alphanumeric characters.
@@ -274,7 +388,7 @@ pub fn crop_circle(
     y: u32,
     diameter: u32,
 ) -> anyhow::Result<Vec<u8>> {
-    let original_img = image::load_from_memory(pic)?;
+    let original_img = load_first_frame(pic)?;
 
     let radius = (diameter / 2) as i32;
     let radius_squared = radius * radius;
@@ -319,6 +433,244 @@ pub fn crop_circle(
     Ok(result)
 }
 
+/// Validates an uploaded pet picture and prepares it to be stored.
+///
+/// Rejects pictures over [`consts::PIC_PET_MAX_SIZE_BYTES`], converts HEIC
+/// pictures to PNG and re-encodes other formats without their metadata,
+/// after checking their dimensions. Every path re-encodes the pixels, so no
+/// uploaded metadata reaches the storage.
+///
+/// # Arguments
+/// * `pic` - Raw image bytes as uploaded
+///
+/// # Returns
+/// * `anyhow::Result<Vec<u8>>` - Image bytes ready to be stored
+pub fn prepare_pet_pic(pic: &crate::models::Pic) -> anyhow::Result<Vec<u8>> {
+    if pic.len() > consts::PIC_PET_MAX_SIZE_BYTES {
+        anyhow::bail!(
+            "Image is too big. Maximum size: {} bytes",
+            consts::PIC_PET_MAX_SIZE_BYTES
+        );
+    }
+
+    if crate::utils::detect_image_format(pic) == "heic" {
+        return convert_heic_to_png(pic);
+    }
+
+    check_image_dimensions(pic)?;
+
+    if is_animated_gif(pic) {
+        return convert_first_frame_to_png(pic);
+    }
+
+    reencode_without_metadata(pic)
+}
+
+/// Decodes a picture, only the first frame of a GIF.
+///
+/// Decoding a GIF as a single image leaves the frame choice to the decoder,
+/// the first frame is picked explicitly so the avatar is always the same.
+///
+/// # Arguments
+/// * `pic` - Raw image bytes
+///
+/// # Returns
+/// * `anyhow::Result<image::DynamicImage>` - The decoded image or GIF first frame
+pub fn load_first_frame(pic: &[u8]) -> anyhow::Result<image::DynamicImage> {
+    use image::AnimationDecoder;
+
+    if crate::utils::detect_image_format(pic) != "gif" {
+        return Ok(image::load_from_memory(pic)?);
+    }
+
+    let first_frame = image::codecs::gif::GifDecoder::new(std::io::Cursor::new(pic))?
+        .into_frames()
+        .next()
+        .context("the GIF has no frames")??;
+
+    Ok(image::DynamicImage::ImageRgba8(first_frame.into_buffer()))
+}
+
+/// Checks if a picture is a GIF with more than one frame.
+pub fn is_animated_gif(pic: &[u8]) -> bool {
+    use image::AnimationDecoder;
+
+    crate::utils::detect_image_format(pic) == "gif"
+        && image::codecs::gif::GifDecoder::new(std::io::Cursor::new(pic))
+            .map(|decoder| decoder.into_frames().take(2).count() > 1)
+            .unwrap_or_default()
+}
+
+/// Keeps only the first frame of an animated picture, as PNG.
+///
+/// The pet picture is shown as a static avatar, the stored picture is the
+/// first frame so every view (profile, QR card, wallet pass) shows the same.
+///
+/// # Arguments
+/// * `pic` - Raw animated GIF bytes
+///
+/// # Returns
+/// * `anyhow::Result<Vec<u8>>` - PNG image data of the first frame
+fn convert_first_frame_to_png(pic: &crate::models::Pic) -> anyhow::Result<Vec<u8>> {
+    let mut result = Vec::new();
+    load_first_frame(pic)?.write_to(
+        &mut std::io::Cursor::new(&mut result),
+        image::ImageFormat::Png,
+    )?;
+
+    Ok(result)
+}
+
+/// Re-encodes a picture without its metadata, oriented by its EXIF tag.
+///
+/// Uploaded photos may carry GPS coordinates, device data, XMP or PNG text
+/// chunks that would leak the owner location, so every stored picture is
+/// decoded and re-encoded from its pixels only. Phone cameras store the
+/// picture as captured and save the rotation in the EXIF metadata, so the
+/// orientation is applied before dropping it. JPEG pictures stay JPEG, any
+/// other format is stored as PNG.
+///
+/// # Arguments
+/// * `pic` - Raw image bytes (JPEG, PNG, ...)
+///
+/// # Returns
+/// * `anyhow::Result<Vec<u8>>` - Oriented image bytes without metadata
+///
+/// # Errors
+/// Returns an error if the format is unknown or the image can't be decoded
+/// or re-encoded.
+pub fn reencode_without_metadata(pic: &crate::models::Pic) -> anyhow::Result<Vec<u8>> {
+    use image::ImageDecoder;
+
+    let mut reader = image::ImageReader::new(std::io::Cursor::new(pic)).with_guessed_format()?;
+    reader.limits(pet_pic_limits());
+    let format = match reader.format() {
+        Some(image::ImageFormat::Jpeg) => image::ImageFormat::Jpeg,
+        Some(_) => image::ImageFormat::Png,
+        None => anyhow::bail!("unknown image format"),
+    };
+
+    let mut decoder = reader.into_decoder()?;
+    let orientation = decoder.orientation()?;
+    let mut img = image::DynamicImage::from_decoder(decoder)?;
+    img.apply_orientation(orientation);
+
+    let mut result = Vec::with_capacity(pic.len());
+    img.write_to(&mut std::io::Cursor::new(&mut result), format)?;
+
+    Ok(result)
+}
+
+/// Rejects pictures wider or taller than [`consts::PIC_PET_MAX_DIMENSION_PX`]
+/// or with more than [`consts::PIC_PET_MAX_PIXELS`] pixels.
+///
+/// Only the image header is read, so a decompression bomb is rejected
+/// before its pixels are decoded.
+///
+/// # Arguments
+/// * `pic` - Raw image bytes (JPEG, PNG, ...)
+///
+/// # Errors
+/// Returns an error if the format is unknown, the header can't be read or
+/// the picture is too big.
+pub fn check_image_dimensions(pic: &crate::models::Pic) -> anyhow::Result<()> {
+    let (width, height) = image::ImageReader::new(std::io::Cursor::new(pic))
+        .with_guessed_format()?
+        .into_dimensions()
+        .context("Image dimensions could not be read")?;
+
+    ensure_max_dimensions(width, height)
+}
+
+fn ensure_max_dimensions(width: u32, height: u32) -> anyhow::Result<()> {
+    if width > consts::PIC_PET_MAX_DIMENSION_PX || height > consts::PIC_PET_MAX_DIMENSION_PX {
+        anyhow::bail!(
+            "Image is too big ({width}x{height} px). Maximum dimensions: {max}x{max} px",
+            max = consts::PIC_PET_MAX_DIMENSION_PX
+        );
+    }
+    if u64::from(width) * u64::from(height) > consts::PIC_PET_MAX_PIXELS {
+        anyhow::bail!(
+            "Image is too big ({width}x{height} px). Maximum pixels: {}",
+            consts::PIC_PET_MAX_PIXELS
+        );
+    }
+
+    Ok(())
+}
+
+/// Decoding limits of the uploaded pictures, the decoder stops before
+/// allocating the pixels of a picture past them.
+fn pet_pic_limits() -> image::Limits {
+    let mut limits = image::Limits::default();
+    limits.max_image_width = Some(consts::PIC_PET_MAX_DIMENSION_PX);
+    limits.max_image_height = Some(consts::PIC_PET_MAX_DIMENSION_PX);
+    limits.max_alloc = Some(consts::PIC_PET_MAX_PIXELS * 4);
+    limits
+}
+
+/// Message shown to the user when a HEIC picture can't be decoded.
+pub const HEIC_NOT_SUPPORTED_MSG: &str =
+    "las imagenes HEIC no son soportadas, por favor sube la foto en formato JPEG o PNG";
+
+/// Converts HEIC image bytes (iPhone photos) into PNG bytes.
+///
+/// The `image` crate can't decode HEIC, so the primary image is decoded
+/// to RGBA with libheif and re-encoded as PNG before any other processing.
+///
+/// # Arguments
+/// * `pic` - Raw HEIC image bytes
+///
+/// # Returns
+/// * `anyhow::Result<Vec<u8>>` - PNG image data
+///
+/// # Errors
+/// Returns an error if the HEIC data can't be decoded or the PNG encoding fails.
+#[cfg(feature = "heic")]
+pub fn convert_heic_to_png(pic: &crate::models::Pic) -> anyhow::Result<Vec<u8>> {
+    use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
+
+    let lib_heif = LibHeif::new();
+    let heif_ctx = HeifContext::read_from_bytes(pic).context(HEIC_NOT_SUPPORTED_MSG)?;
+    let handle = heif_ctx
+        .primary_image_handle()
+        .context(HEIC_NOT_SUPPORTED_MSG)?;
+    ensure_max_dimensions(handle.width(), handle.height())?;
+    let decoded = lib_heif
+        .decode(&handle, ColorSpace::Rgb(RgbChroma::Rgba), None)
+        .context(HEIC_NOT_SUPPORTED_MSG)?;
+
+    let planes = decoded.planes();
+    let interleaved = planes.interleaved.context(HEIC_NOT_SUPPORTED_MSG)?;
+
+    // Rows can be padded, copy them without the stride padding
+    let row_len = interleaved.width as usize * 4;
+    let mut rgba = Vec::with_capacity(row_len * interleaved.height as usize);
+    for row in interleaved.data.chunks(interleaved.stride) {
+        rgba.extend_from_slice(&row[..row_len]);
+    }
+
+    let img = image::RgbaImage::from_raw(interleaved.width, interleaved.height, rgba)
+        .context(HEIC_NOT_SUPPORTED_MSG)?;
+
+    let mut result = Vec::new();
+    img.write_to(
+        &mut std::io::Cursor::new(&mut result),
+        image::ImageFormat::Png,
+    )?;
+
+    Ok(result)
+}
+
+/// Fallback when the app is built without the `heic` feature.
+///
+/// # Errors
+/// Always returns an error asking the user to upload JPEG/PNG instead.
+#[cfg(not(feature = "heic"))]
+pub fn convert_heic_to_png(_pic: &crate::models::Pic) -> anyhow::Result<Vec<u8>> {
+    anyhow::bail!(HEIC_NOT_SUPPORTED_MSG)
+}
+
 #[cfg(test)]
 /// Test module for utility functions.
 ///
@@ -342,6 +694,94 @@ mod tests {
         Ok(())
     }
 
+    /// Tests the cached copy is fresh when its ETag or Last-Modified date
+    /// still matches, with the date compared at whole seconds.
+    #[test]
+    fn test_is_not_modified() {
+        use ntex::http::header::{self, HeaderMap, HeaderValue};
+
+        let last_modified = chrono::DateTime::parse_from_rfc3339("2024-06-15T12:00:00.250Z")
+            .unwrap()
+            .to_utc();
+        let headers = |name, value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(name, HeaderValue::from_str(value).unwrap());
+            headers
+        };
+        assert_eq!(
+            fmt_http_date(last_modified),
+            "Sat, 15 Jun 2024 12:00:00 GMT"
+        );
+
+        for (request_headers, expected) in [
+            (HeaderMap::new(), false),
+            (headers(header::IF_NONE_MATCH, "W/\"abc\""), true),
+            (headers(header::IF_NONE_MATCH, "\"xyz\", \"abc\""), true),
+            (headers(header::IF_NONE_MATCH, "*"), true),
+            (headers(header::IF_NONE_MATCH, "W/\"xyz\""), false),
+            (
+                headers(header::IF_MODIFIED_SINCE, "Sat, 15 Jun 2024 12:00:00 GMT"),
+                true,
+            ),
+            (
+                headers(header::IF_MODIFIED_SINCE, "Sat, 15 Jun 2024 11:59:59 GMT"),
+                false,
+            ),
+            (headers(header::IF_MODIFIED_SINCE, "not a date"), false),
+        ] {
+            assert_eq!(
+                is_not_modified(&request_headers, "W/\"abc\"", last_modified),
+                expected,
+                "{request_headers:?}"
+            );
+        }
+    }
+
+    /// Tests JSON is only chosen when asked before HTML.
+    #[test]
+    fn test_prefers_json() {
+        for (accept, expected) in [
+            ("application/json", true),
+            ("application/json; charset=utf-8, text/html", true),
+            ("text/html,application/xhtml+xml,*/*;q=0.8", false),
+            ("text/html, application/json", false),
+            ("*/*", false),
+        ] {
+            let map = ntex::http::HeaderMap::from_iter(vec![("Accept", accept)]);
+            assert_eq!(prefers_json(&map), expected, "{accept}");
+        }
+        assert!(!prefers_json(&ntex::http::HeaderMap::new()));
+    }
+
+    /// Tests only the exact `name` parameter is taken as the field name.
+    #[test]
+    fn test_get_field_name_matches_exact_name() {
+        assert_eq!(
+            get_field_name(r#"form-data; name="is_lost""#),
+            Some("is_lost")
+        );
+        assert_eq!(
+            get_field_name(r#"form-data; name="pet_pic"; filename="is_lost.png""#),
+            Some("pet_pic")
+        );
+        assert_eq!(
+            get_field_name(r#"form-data; filename="pet_pic.png"; name="pet_full_name""#),
+            Some("pet_full_name")
+        );
+        assert_eq!(
+            get_field_name("form-data; NAME=pet_breed"),
+            Some("pet_breed")
+        );
+        // a name containing another field name is not that field
+        assert_ne!(
+            get_field_name(r#"form-data; name="is_lost_reason""#),
+            Some("is_lost")
+        );
+        assert_eq!(get_field_name(r#"form-data; filename="is_lost""#), None);
+        assert_eq!(get_field_name(r#"name="is_lost""#), None);
+        assert_eq!(get_field_name(""), None);
+    }
+
     /// Tests redirect response creation with various URLs.
     #[test]
     fn test_redirect_to() {
@@ -389,11 +829,49 @@ mod tests {
         assert_eq!(result1, result2);
     }
 
-    /// Tests UTC datetime with default time functionality.
+    /// Tests the local date on both sides of the date line.
+    #[test]
+    fn test_date_in_tz_boundary() {
+        // 2024-01-01 11:00 UTC is already 2024-01-02 01:00 in Kiritimati (UTC+14)
+        let datetime = chrono::NaiveDate::from_ymd_opt(2024, 1, 1)
+            .unwrap()
+            .and_hms_opt(11, 0, 0)
+            .unwrap()
+            .and_utc();
+
+        assert_eq!(
+            date_in_tz(datetime, Tz::Pacific__Kiritimati),
+            NaiveDate::from_ymd_opt(2024, 1, 2).unwrap()
+        );
+        assert_eq!(
+            date_in_tz(datetime, Tz::UTC),
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
+        );
+
+        // 2024-01-01 03:00 UTC is still 2023-12-31 in Mexico City (UTC-6)
+        let datetime = chrono::NaiveDate::from_ymd_opt(2024, 1, 1)
+            .unwrap()
+            .and_hms_opt(3, 0, 0)
+            .unwrap()
+            .and_utc();
+
+        assert_eq!(
+            date_in_tz(datetime, Tz::America__Mexico_City),
+            NaiveDate::from_ymd_opt(2023, 12, 31).unwrap()
+        );
+        assert_eq!(
+            date_in_tz(datetime, Tz::Pacific__Kiritimati),
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
+        );
+    }
+
+    /// Tests the current local date is never more than a day away from UTC.
     #[test]
-    fn test_get_utc_now_with_default_time() {
-        let dt = get_utc_now_with_default_time();
-        assert_eq!(dt.time(), chrono::NaiveTime::from_hms_opt(0, 0, 0).unwrap());
+    fn test_get_now_date_in_tz() {
+        let utc_today = chrono::Utc::now().date_naive();
+        let kiritimati_today = get_now_date_in_tz(Tz::Pacific__Kiritimati);
+
+        assert!((kiritimati_today - utc_today).num_days().abs() <= 1);
     }
 
     // Tests alphanumeric character filtering.
@@ -454,6 +932,21 @@ mod tests {
         assert!(result.is_err());
     }
 
+    /// Tests the user timezone falls back to the default one.
+    #[test]
+    fn test_user_timezone_falls_back_to_default() {
+        let map = ntex::http::HeaderMap::from_iter(vec![("timezone", "Europe/Madrid")]);
+        assert_eq!(user_timezone(&map), Tz::Europe__Madrid);
+
+        let map = ntex::http::HeaderMap::from_iter(vec![("timezone", "Invalid/Timezone_Name")]);
+        assert_eq!(user_timezone(&map), consts::DEFAULT_TIMEZONE);
+
+        assert_eq!(
+            user_timezone(&ntex::http::HeaderMap::new()),
+            consts::DEFAULT_TIMEZONE
+        );
+    }
+
     /// Tests crop circle with mock image data.
     #[test]
     fn test_crop_circle_basic() {
@@ -476,6 +969,46 @@ mod tests {
         assert_eq!(&cropped_data[0..8], &[137, 80, 78, 71, 13, 10, 26, 10]);
     }
 
+    /// Builds a 10x10 GIF with a red first frame and a blue second frame.
+    fn create_two_frame_gif() -> Vec<u8> {
+        let mut gif = Vec::new();
+        {
+            let mut encoder = image::codecs::gif::GifEncoder::new(&mut gif);
+            encoder
+                .encode_frames([
+                    image::Frame::new(image::RgbaImage::from_pixel(
+                        10,
+                        10,
+                        image::Rgba([255, 0, 0, 255]),
+                    )),
+                    image::Frame::new(image::RgbaImage::from_pixel(
+                        10,
+                        10,
+                        image::Rgba([0, 0, 255, 255]),
+                    )),
+                ])
+                .unwrap();
+        }
+        gif
+    }
+
+    /// Tests an animated GIF avatar is cropped and stored from its first frame.
+    #[test]
+    fn test_animated_gif_uses_first_frame() {
+        let gif = create_two_frame_gif();
+        assert!(is_animated_gif(&gif));
+
+        let cropped = image::load_from_memory(&crop_circle(&gif, 5, 5, 6).unwrap())
+            .unwrap()
+            .to_rgba8();
+        assert_eq!(cropped[(3, 3)], image::Rgba([255, 0, 0, 255]));
+
+        let prepared = prepare_pet_pic(&gif).unwrap();
+        assert_eq!(crate::utils::detect_image_format(&prepared), "png");
+        let prepared = image::load_from_memory(&prepared).unwrap().to_rgba8();
+        assert_eq!(prepared[(5, 5)], image::Rgba([255, 0, 0, 255]));
+    }
+
     /// Tests crop circle with invalid image format.
     #[test]
     fn test_crop_circle_invalid_format() {
@@ -494,6 +1027,162 @@ mod tests {
         assert!(result.is_err());
     }
 
+    /// Builds a JPEG picture of the given size carrying an EXIF orientation tag.
+    fn create_jpeg_with_orientation(width: u32, height: u32, orientation: u8) -> Vec<u8> {
+        let mut jpeg = Vec::new();
+        image::RgbImage::from_pixel(width, height, image::Rgb([255, 0, 0]))
+            .write_to(
+                &mut std::io::Cursor::new(&mut jpeg),
+                image::ImageFormat::Jpeg,
+            )
+            .unwrap();
+
+        // Big endian TIFF header with a single IFD entry: orientation (0x0112), SHORT
+        let mut exif = b"Exif\0\0MM\0\x2a\0\0\0\x08\0\x01\x01\x12\0\x03\0\0\0\x01\0".to_vec();
+        exif.extend_from_slice(&[orientation, 0, 0, 0, 0, 0, 0]);
+
+        let mut app1 = vec![0xFF, 0xE1];
+        app1.extend_from_slice(&((exif.len() + 2) as u16).to_be_bytes());
+        app1.extend_from_slice(&exif);
+
+        // APP1 segment goes right after the SOI marker
+        jpeg.splice(2..2, app1);
+        jpeg
+    }
+
+    /// Builds a JPEG picture of the given size carrying a GPS EXIF tag.
+    fn create_jpeg_with_gps(width: u32, height: u32) -> Vec<u8> {
+        let mut jpeg = Vec::new();
+        image::RgbImage::from_pixel(width, height, image::Rgb([255, 0, 0]))
+            .write_to(
+                &mut std::io::Cursor::new(&mut jpeg),
+                image::ImageFormat::Jpeg,
+            )
+            .unwrap();
+
+        // Big endian TIFF header, IFD0 with a GPSInfo (0x8825) pointer to the
+        // GPS IFD at offset 26, which holds GPSLatitudeRef (0x0001) = "N"
+        let mut exif = b"Exif\0\0MM\0\x2a\0\0\0\x08".to_vec();
+        exif.extend_from_slice(b"\0\x01\x88\x25\0\x04\0\0\0\x01\0\0\0\x1a\0\0\0\0");
+        exif.extend_from_slice(b"\0\x01\0\x01\0\x02\0\0\0\x02N\0\0\0\0\0\0\0");
+
+        let mut app1 = vec![0xFF, 0xE1];
+        app1.extend_from_slice(&((exif.len() + 2) as u16).to_be_bytes());
+        app1.extend_from_slice(&exif);
+
+        jpeg.splice(2..2, app1);
+        assert!(jpeg.windows(4).any(|w| w == b"Exif"));
+        jpeg
+    }
+
+    /// Tests that EXIF orientation is applied and the metadata stripped.
+    #[test]
+    fn test_reencode_without_metadata_rotates_and_strips_exif() {
+        // orientation 6: the picture must be rotated 90° clockwise
+        let pic = create_jpeg_with_orientation(4, 2, 6);
+        assert!(pic.windows(4).any(|w| w == b"Exif"));
+
+        let result = reencode_without_metadata(&pic).unwrap();
+
+        let img = image::load_from_memory(&result).unwrap();
+        assert_eq!((img.width(), img.height()), (2, 4));
+        assert!(!result.windows(4).any(|w| w == b"Exif"));
+    }
+
+    /// Tests that a JPEG carrying GPS EXIF is stored without that metadata.
+    #[test]
+    fn test_prepare_pet_pic_strips_gps_exif() {
+        use image::ImageDecoder;
+
+        let pic = create_jpeg_with_gps(4, 2);
+
+        let result = prepare_pet_pic(&pic).unwrap();
+
+        assert_eq!(crate::utils::detect_image_format(&result), "jpg");
+        assert!(!result.windows(4).any(|w| w == b"Exif"));
+        let mut decoder = image::ImageReader::new(std::io::Cursor::new(&result))
+            .with_guessed_format()
+            .unwrap()
+            .into_decoder()
+            .unwrap();
+        assert_eq!(decoder.exif_metadata().unwrap(), None);
+    }
+
+    /// Tests that pictures without EXIF are re-encoded too, dropping other
+    /// metadata such as PNG text chunks.
+    #[test]
+    fn test_prepare_pet_pic_strips_png_text_chunks() {
+        let mut pic = Vec::new();
+        image::RgbImage::from_pixel(4, 2, image::Rgb([255, 0, 0]))
+            .write_to(&mut std::io::Cursor::new(&mut pic), image::ImageFormat::Png)
+            .unwrap();
+
+        // tEXt chunk right after IHDR (signature 8 bytes + IHDR 25 bytes)
+        let data = b"tEXtLocation\x0019.4326,-99.1332";
+        let mut chunk = ((data.len() - 4) as u32).to_be_bytes().to_vec();
+        chunk.extend_from_slice(data);
+        chunk.extend_from_slice(&crc32(data).to_be_bytes());
+        pic.splice(33..33, chunk);
+        assert!(image::load_from_memory(&pic).is_ok());
+
+        let result = prepare_pet_pic(&pic).unwrap();
+
+        assert_eq!(crate::utils::detect_image_format(&result), "png");
+        assert!(!result.windows(8).any(|w| w == b"Location"));
+        let img = image::load_from_memory(&result).unwrap().to_rgb8();
+        assert_eq!(img[(0, 0)], image::Rgb([255, 0, 0]));
+    }
+
+    /// CRC32 (ISO-HDLC) used by PNG chunks.
+    fn crc32(bytes: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for byte in bytes {
+            crc ^= u32::from(*byte);
+            for _ in 0..8 {
+                crc = if crc & 1 == 1 {
+                    (crc >> 1) ^ 0xEDB8_8320
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+        !crc
+    }
+
+    /// A few bytes PNG whose header claims `width`x`height` pixels.
+    fn create_png_with_dimensions(width: u32, height: u32) -> Vec<u8> {
+        let mut png = Vec::new();
+        image::RgbImage::from_pixel(1, 1, image::Rgb([255, 0, 0]))
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+
+        // IHDR chunk: length(8..12) type(12..16) width(16..20) height(20..24) crc(29..33)
+        png[16..20].copy_from_slice(&width.to_be_bytes());
+        png[20..24].copy_from_slice(&height.to_be_bytes());
+        let crc = crc32(&png[12..29]);
+        png[29..33].copy_from_slice(&crc.to_be_bytes());
+        png
+    }
+
+    /// Tests that a small file decoding to huge dimensions is rejected.
+    #[test]
+    fn test_check_image_dimensions_rejects_decompression_bomb() {
+        let pic = create_png_with_dimensions(50_000, 50_000);
+        assert!(pic.len() < 100);
+
+        assert!(check_image_dimensions(&pic).is_err());
+        // within the max width and height, but too many pixels in total
+        assert!(check_image_dimensions(&create_png_with_dimensions(9_000, 9_000)).is_err());
+        assert!(reencode_without_metadata(&create_png_with_dimensions(50_000, 50_000)).is_err());
+    }
+
+    /// Tests that regular pictures pass the dimensions check.
+    #[test]
+    fn test_check_image_dimensions_accepts_regular_picture() {
+        assert!(check_image_dimensions(&create_png_with_dimensions(4032, 3024)).is_ok());
+        assert!(check_image_dimensions(&create_jpeg_with_orientation(4, 2, 1)).is_ok());
+    }
+
     /// Tests get_bytes_as_str helper function.
     #[ntex::test]
     async fn test_get_bytes_as_str() {
@@ -525,10 +1214,15 @@ mod tests {
         assert_eq!(result, Some("".to_string()));
     }
 
-    /// Tests get_utc_now_with_default_time consistency.
+    /// Tests that HEIC pictures return a clear message when decoding isn't available.
+    #[cfg(not(feature = "heic"))]
     #[test]
-    fn test_get_utc_now_time_zone_is_utc() {
-        let dt = get_utc_now_with_default_time();
-        assert_eq!(dt.timezone(), chrono::offset::Utc)
+    fn test_convert_heic_to_png_without_feature() {
+        let mut heic_data = vec![0u8; 12];
+        heic_data[4..12].copy_from_slice(b"ftypheic");
+
+        let result = convert_heic_to_png(&heic_data);
+
+        assert!(result.is_err_and(|e| e.to_string() == HEIC_NOT_SUPPORTED_MSG));
     }
 }
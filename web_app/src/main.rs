@@ -25,6 +25,7 @@ use ntex::web;
 use ntex_cors::Cors;
 use ntex_identity::{CookieIdentityPolicy, IdentityService};
 use ntex_session::CookieSession;
+use std::time::Duration;
 
 #[ntex::main]
 async fn main() -> anyhow::Result<()> {
@@ -34,6 +35,7 @@ async fn main() -> anyhow::Result<()> {
     let app_config = config::APP_CONFIG
         .get()
         .context("failed to get app config")?;
+    utils::init_totp_client(app_config)?;
 
     // Initialize logging and metrics
     let shutdown_handler = logfire::configure()
@@ -43,6 +45,16 @@ async fn main() -> anyhow::Result<()> {
         .with_token(&app_config.logfire_token)
         .finish()?;
 
+    // Warn about outdated database encryption, raising it needs a rekey
+    if app_config.is_prod() {
+        for warning in app_config.weak_db_cipher_settings() {
+            logfire::warn!(
+                "weak database cipher settings: {warning}",
+                warning = warning
+            );
+        }
+    }
+
     // Initialize database connection pool
     let sqlite_repo = repo::sqlite::SqlxSqliteRepo {
         db_pool: utils::setup_sqlite_db_pool(app_config.is_prod()).await?,
@@ -56,9 +68,13 @@ async fn main() -> anyhow::Result<()> {
 
     let storage_service = services::storage::StorageHandler {
         client: aws_sdk_s3::Client::new(&aws_config),
+        bucket_name: app_config.storage_bucket_name.clone(),
+        key_prefix: app_config.storage_key_prefix.clone(),
     };
     let notification_service = services::notification::NotificationHandler {
         client: aws_sdk_sfn::Client::new(&aws_config),
+        dry_run: app_config.is_notifications_dry_run(),
+        cancel_max_attempts: app_config.reminder_cancel_max_attempts(),
     };
 
     // Generate cryptographically secure keys for application security
@@ -89,8 +105,15 @@ fn create_app_state(
     sqlite_repo: repo::sqlite::SqlxSqliteRepo,
     storage_service: services::storage::StorageHandler,
     notification_service: services::notification::NotificationHandler,
+    verification_throttle: api::verification_throttle::VerificationThrottle,
 ) -> anyhow::Result<front::AppState> {
     let whatsapp_client = webhook::whatsapp::client::WhatsAppClient::new()?;
+    let profile_cache_ttl = Duration::from_secs(
+        config::APP_CONFIG
+            .get()
+            .context("failed to get app config")?
+            .public_profile_cache_ttl_secs,
+    );
 
     Ok(front::AppState {
         csrf_protec: AesGcmCsrfProtection::from_key(csrf_key),
@@ -98,6 +121,10 @@ fn create_app_state(
         storage_service: Box::new(storage_service),
         notification_service: Box::new(notification_service),
         whatsapp_client,
+        thumbnail_cache: api::thumbnail::ThumbnailCache::new(consts::THUMBNAIL_CACHE_CAPACITY),
+        profile_cache: api::profile_cache::PublicProfileCache::new(profile_cache_ttl),
+        clock: Box::new(services::clock::SystemClock),
+        verification_throttle,
     })
 }
 
@@ -120,36 +147,47 @@ async fn configure_and_run_server(
     // Nginx reverse proxy handles HTTPS/TLS/mTLS on port 443
     let server_addr = ("127.0.0.1", 8080);
 
+    // Shared between workers so every worker applies the same per-client limit
+    let public_rate_limit =
+        front::middleware::rate_limit::RateLimit::new(app_config.public_rate_limit_per_minute);
+    let found_report_rate_limit =
+        front::middleware::rate_limit::RateLimit::new(consts::FOUND_REPORT_RATE_LIMIT_PER_MINUTE);
+    let verification_throttle = api::verification_throttle::VerificationThrottle::new(
+        consts::VERIFICATION_CODE_MAX_SENDS,
+        chrono::TimeDelta::minutes(consts::VERIFICATION_CODE_WINDOW_MINUTES),
+    );
+
+    let cors_allowed_origins = app_config.cors_allowed_origins();
+
     let server = web::server(move || {
         web::App::new()
             .wrap(
-                Cors::new()
-                    .allowed_methods(vec![
-                        "GET", "HEAD", "POST", "OPTIONS", "PUT", "PATCH", "DELETE",
-                    ])
-                    .allowed_origin("http://localhost:8080")
-                    .allowed_origin("https://openidconnect.googleapis.com")
-                    .allowed_origin("https://pet-info.link")
-                    .allowed_origin("https://oauth2.googleapis.com")
-                    .allowed_origin("https://www.googleapis.com")
-                    .allowed_origin("https://accounts.google.com")
-                    .allowed_origin("https://graph.facebook.com")
-                    .allowed_origin("https://api.mercadopago.com")
+                cors_allowed_origins
+                    .iter()
+                    .fold(
+                        Cors::new().allowed_methods(vec![
+                            "GET", "HEAD", "POST", "OPTIONS", "PUT", "PATCH", "DELETE",
+                        ]),
+                        |cors, origin| cors.allowed_origin(origin),
+                    )
                     .finish(),
             )
             .wrap(
                 CookieSession::private(&session_key)
                     .secure(app_config.is_prod())
-                    .max_age(consts::MAX_AGE_COOKIES)
+                    .max_age(app_config.session_max_age())
                     .name("pet-info-session"),
             )
             .wrap(IdentityService::new(
                 CookieIdentityPolicy::new(&identity_key)
                     .name("user_id")
-                    .max_age(consts::MAX_AGE_COOKIES)
+                    .max_age(app_config.identity_max_age())
                     .secure(app_config.is_prod()),
             ))
-            .wrap(web::middleware::Logger::default())
+            .wrap(front::middleware::request_id::AssignRequestId)
+            .wrap(web::middleware::Logger::new(
+                "%a \"%r\" %s %b \"%{Referer}i\" \"%{User-Agent}i\" %T %{x-request-id}o",
+            ))
             .wrap(web::middleware::Compress::default())
             .state(
                 create_app_state(
@@ -157,16 +195,27 @@ async fn configure_and_run_server(
                     sqlite_repo.clone(),
                     storage_service.clone(),
                     notification_service.clone(),
+                    verification_throttle.clone(),
                 )
                 .expect("Failed to create app state"),
             )
-            .configure(front::routes::pet_public_profile)
+            .state(sqlite_repo.db_pool.clone())
+            .configure(|cfg| {
+                front::routes::pet_public_profile(
+                    cfg,
+                    public_rate_limit.clone(),
+                    found_report_rate_limit.clone(),
+                )
+            })
             .configure(front::routes::pet)
             .configure(front::routes::user_profile)
             .configure(front::routes::checkout)
             .configure(front::routes::blog)
+            .configure(front::routes::admin)
             .configure(front::routes::reminders)
             .configure(webhook::routes::whatsapp)
+            .configure(webhook::routes::schedule)
+            .service((front::health::healthz, front::health::readyz))
             .service((
                 ntex_files::Files::new("/static", "web/static/"),
                 front::server::serve_favicon,
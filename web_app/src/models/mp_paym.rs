@@ -25,4 +25,10 @@ pub fn default_sub_paym_desc() -> String {
 pub struct PaymentResponse {
     pub id: usize,
     pub status: super::payment::PaymentStatus,
+    #[serde(default = "default_currency_id")]
+    pub currency_id: String,
+}
+
+pub fn default_currency_id() -> String {
+    crate::consts::DEFAULT_CURRENCY.into()
 }
@@ -35,6 +35,7 @@ pub struct Payment {
     pub mp_paym_id: usize,
     pub payment_idempotency_h: String,
     pub transaction_amount: String,
+    pub currency: String,
     pub installments: u32,
     pub payment_method_id: String,
     pub issuer_id: String,
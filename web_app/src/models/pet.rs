@@ -15,6 +15,8 @@ pub struct Pet {
     pub is_female: bool,
     pub is_lost: bool,
     pub is_spaying_neutering: bool,
+    /// Normalized microchip number, see [parse_microchip](crate::front::forms::pet::parse_microchip)
+    pub microchip: Option<String>,
     pub last_weight: Option<f64>,
     pub pic: Option<String>,
     pub created_at: DateTime<Utc>,
@@ -35,6 +37,78 @@ pub enum PetHealthType {
     Weight,
 }
 
+/// Pet with a summary of how complete its health records are
+#[derive(Default, Clone)]
+pub struct PetWithCounts {
+    pub pet: Pet,
+    pub vaccine_count: i64,
+    pub weight_count: i64,
+    pub last_vaccine_date: Option<NaiveDateTime>,
+}
+
+/// Access granted on a pet to an account other than its owner
+#[derive(Debug, Display, Clone, Default, Deserialize, Serialize, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+pub enum PetAccessRole {
+    /// Can see and update the pet and its records
+    #[display("editor")]
+    #[serde(alias = "editor", rename(serialize = "editor"))]
+    Editor,
+    /// Can only see the pet and its records
+    #[default]
+    #[display("viewer")]
+    #[serde(alias = "viewer", rename(serialize = "viewer"))]
+    Viewer,
+}
+
+/// Account sharing a pet with its owner
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PetCollaborator {
+    pub user_id: i64,
+    pub email: String,
+    pub role: PetAccessRole,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Pending offer to become the owner of a pet, waiting for the recipient
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PetTransferOffer {
+    pub pet_id: i64,
+    pub pet_name: String,
+    pub from_email: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Kind of change recorded in the audit log of a pet
+#[derive(Debug, Display, Clone, PartialEq, Serialize, sqlx::Type)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum PetAuditAction {
+    #[display("insert")]
+    Insert,
+    #[display("update")]
+    Update,
+    #[display("delete")]
+    Delete,
+}
+
+/// Change made on a pet, its profile or its records
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PetAuditEntry {
+    pub email: String,
+    pub action: PetAuditAction,
+    pub field: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Message left by someone who found a lost pet
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct FoundReport {
+    pub message: String,
+    pub callback_contact: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
 pub struct ExternalIdMetadata {
     pub external_id: Uuid,
     pub is_linked: bool,
@@ -57,6 +131,54 @@ pub struct PetHealth {
     pub created_at: NaiveDateTime,
 }
 
+/// Latest vaccine of a pet that has a booster interval.
+#[derive(Debug, Clone, Default, PartialEq, sqlx::FromRow)]
+pub struct PetVaccineBooster {
+    /// ID of the `pet_health` record
+    pub id: i64,
+    pub pet_id: i64,
+    pub pet_name: String,
+    pub description: String,
+    pub created_at: NaiveDateTime,
+    pub booster_months: u32,
+    /// Whether a digest already announced the booster before it was due
+    pub announced_upcoming: bool,
+}
+
+/// State of a booster when a digest announced it, a booster is announced
+/// once in each state
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+pub enum BoosterStatus {
+    #[display("upcoming")]
+    Upcoming,
+    #[display("overdue")]
+    Overdue,
+}
+
+/// What the QR card of a pet shows besides the code: the name caption and
+/// the breed that picks the default avatar
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+pub struct PetQrCaption {
+    pub pet_name: String,
+    pub breed: String,
+}
+
+/// Visits to the public profile of a pet, the owner's own views excluded.
+#[derive(Debug, Clone, Default, Serialize, PartialEq, sqlx::FromRow)]
+pub struct PetScanStats {
+    pub total: i64,
+    pub last_7_days: i64,
+}
+
+/// Temporary link to the public profile of a pet, revoked by deleting it.
+#[derive(Debug, Clone, Serialize, PartialEq, sqlx::FromRow)]
+pub struct PetShareToken {
+    pub token: String,
+    pub pet_id: i64,
+    pub expires_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, sqlx::FromRow)]
 pub struct PetNote {
     pub id: i64,
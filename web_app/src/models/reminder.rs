@@ -1,24 +1,175 @@
 use chrono::{DateTime, Utc};
 use derive_more::Display;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 
-#[derive(Debug, Display, Clone, Default, Deserialize, Serialize, PartialEq, sqlx::Type)]
+#[derive(
+    Debug,
+    Display,
+    Clone,
+    Copy,
+    Default,
+    Deserialize,
+    Serialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    sqlx::Type,
+)]
 #[sqlx(type_name = "varchar", rename_all = "lowercase")]
 pub enum ReminderNotificationType {
     #[default]
     #[display("whatsapp")]
     #[serde(alias = "whatsapp", rename(serialize = "whatsapp"))]
     WhatsApp,
+    #[display("email")]
+    #[serde(alias = "email", rename(serialize = "email"))]
+    Email,
 }
 
-#[derive(Default, Serialize, sqlx::FromRow)]
+/// Channels a reminder is sent through, stored as a JSON array
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(transparent)]
+pub struct ReminderChannels(pub BTreeSet<ReminderNotificationType>);
+
+impl Default for ReminderChannels {
+    fn default() -> Self {
+        Self(BTreeSet::from([ReminderNotificationType::WhatsApp]))
+    }
+}
+
+impl ReminderChannels {
+    pub fn contains(&self, channel: ReminderNotificationType) -> bool {
+        self.0.contains(&channel)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl FromIterator<ReminderNotificationType> for ReminderChannels {
+    fn from_iter<T: IntoIterator<Item = ReminderNotificationType>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+#[derive(Default, Serialize)]
 pub struct Reminder {
     pub id: i64,
     pub user_app_id: i64,
     pub body: String,
     pub execution_id: String,
-    pub notification_type: ReminderNotificationType,
+    pub notification_type: ReminderChannels,
     pub send_at: DateTime<Utc>,
     pub user_timezone: String,
+    /// Pet the reminder is about, `None` for general reminders
+    pub pet_id: Option<i64>,
     pub created_at: DateTime<Utc>,
 }
+
+/// Reminder message saved by a user to reuse it, see
+/// [`render_template`](crate::api::reminder::render_template)
+#[derive(Debug, Clone, Default, Serialize, PartialEq, sqlx::FromRow)]
+pub struct ReminderTemplate {
+    pub id: i64,
+    pub name: String,
+    pub body: String,
+}
+
+/// Whether a reminder is still waiting to be sent, derived from its `send_at`
+#[derive(
+    Debug, Display, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq, sqlx::Type,
+)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+pub enum ReminderStatus {
+    #[default]
+    #[display("scheduled")]
+    #[serde(alias = "scheduled", rename(serialize = "scheduled"))]
+    Scheduled,
+    #[display("sent")]
+    #[serde(alias = "sent", rename(serialize = "sent"))]
+    Sent,
+}
+
+/// Owner to send the vaccine digest to, through the channels and in the
+/// timezone of their latest reminder
+#[derive(Clone)]
+pub struct VaccineDigestRecipient {
+    pub user: super::user_app::User,
+    /// `None` for owners without reminders
+    pub channels: Option<ReminderChannels>,
+    /// `None` for owners without reminders
+    pub user_timezone: Option<String>,
+}
+
+/// Reminder listed to its owner, with its status at the time of the query
+#[derive(Default, Serialize)]
+pub struct ReminderListItem {
+    #[serde(flatten)]
+    pub reminder: Reminder,
+    pub status: ReminderStatus,
+}
+
+/// Delivery status of a WhatsApp message, as reported by the WhatsApp webhook
+#[derive(
+    Debug, Display, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq, sqlx::Type,
+)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    #[default]
+    #[display("sent")]
+    Sent,
+    #[display("delivered")]
+    Delivered,
+    #[display("read")]
+    Read,
+    #[display("failed")]
+    Failed,
+}
+
+impl DeliveryStatus {
+    /// Parses the `status` of a webhook status update, `None` for the ones
+    /// that are not tracked (e.g. `deleted`)
+    pub fn from_webhook(status: &str) -> Option<Self> {
+        match status {
+            "sent" => Some(Self::Sent),
+            "delivered" => Some(Self::Delivered),
+            "read" => Some(Self::Read),
+            "failed" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reminder_channels_json_round_trip() {
+        let channels: ReminderChannels = [
+            ReminderNotificationType::Email,
+            ReminderNotificationType::WhatsApp,
+            ReminderNotificationType::Email,
+        ]
+        .into_iter()
+        .collect();
+
+        let json = serde_json::to_string(&channels).unwrap();
+        assert_eq!(json, r#"["whatsapp","email"]"#);
+
+        let parsed: ReminderChannels = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, channels);
+    }
+
+    #[test]
+    fn test_reminder_channels_default_is_whatsapp() {
+        let parsed: ReminderChannels = serde_json::from_str(r#"["whatsapp"]"#).unwrap();
+
+        assert_eq!(parsed, ReminderChannels::default());
+        assert!(!parsed.contains(ReminderNotificationType::Email));
+    }
+}
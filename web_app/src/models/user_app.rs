@@ -10,12 +10,81 @@ pub enum AccountRole {
     #[serde(rename = "staff")]
     #[display("staff")]
     Staff,
+    #[serde(rename = "premium")]
+    #[display("premium")]
+    Premium,
+    #[serde(rename = "admin")]
+    #[display("admin")]
+    Admin,
     #[default]
     #[serde(rename = "unknown")]
     #[display("unknown")]
     Unknown,
 }
 
+/// Max number of pets of a premium account
+const PREMIUM_MAX_PETS: usize = 20;
+
+/// Features and limits allowed to an account role.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Max number of pets, `None` if there is no limit
+    pub max_pets: Option<usize>,
+    /// Whether each new pet must be paid (add pet balance)
+    pub requires_pet_balance: bool,
+    /// Whether an active subscription is needed to use the service
+    pub requires_subscription: bool,
+    /// Whether WhatsApp reminders can be used
+    pub can_use_whatsapp: bool,
+    /// Whether reports and data exports can be downloaded
+    pub can_export: bool,
+}
+
+impl Capabilities {
+    /// Checks if a new pet can be added by an account with
+    /// `current_pets` pets and `pet_balance` paid pets left.
+    pub fn allows_new_pet(&self, current_pets: usize, pet_balance: u32) -> bool {
+        self.max_pets.is_none_or(|max_pets| current_pets < max_pets)
+            && (!self.requires_pet_balance || pet_balance > 0)
+    }
+}
+
+impl AccountRole {
+    /// Returns the features and limits of the role.
+    pub fn capabilities(&self) -> Capabilities {
+        match self {
+            AccountRole::User => Capabilities {
+                max_pets: None,
+                requires_pet_balance: true,
+                requires_subscription: true,
+                can_use_whatsapp: true,
+                can_export: true,
+            },
+            AccountRole::Premium => Capabilities {
+                max_pets: Some(PREMIUM_MAX_PETS),
+                requires_pet_balance: false,
+                requires_subscription: true,
+                can_use_whatsapp: true,
+                can_export: true,
+            },
+            AccountRole::Staff | AccountRole::Admin => Capabilities {
+                max_pets: None,
+                requires_pet_balance: false,
+                requires_subscription: false,
+                can_use_whatsapp: true,
+                can_export: true,
+            },
+            AccountRole::Unknown => Capabilities {
+                max_pets: Some(0),
+                requires_pet_balance: true,
+                requires_subscription: true,
+                can_use_whatsapp: false,
+                can_export: false,
+            },
+        }
+    }
+}
+
 #[derive(Serialize, Debug, Deserialize, Clone, PartialEq)]
 pub struct User {
     pub id: i64,
@@ -24,13 +93,21 @@ pub struct User {
     pub account_role: AccountRole,
     pub is_subscribed: bool,
     pub is_enabled: bool,
+    /// Sessions serialized before the preference existed show kilograms
+    #[serde(default)]
+    pub weight_unit: WeightUnit,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
 impl User {
     pub fn can_access_service(&self) -> bool {
-        self.is_subscribed && self.is_enabled
+        self.is_enabled
+            && (self.is_subscribed || !self.account_role.capabilities().requires_subscription)
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        self.account_role.capabilities()
     }
 
     pub fn create_default_from_email(email: &str) -> Self {
@@ -42,17 +119,262 @@ impl User {
             account_role: AccountRole::User,
             is_subscribed: false,
             is_enabled: true,
+            weight_unit: WeightUnit::default(),
             created_at: now,
             updated_at: now,
         }
     }
 }
 
+/// Pounds in a kilogram
+const LB_PER_KG: f64 = 2.204_622_621_8;
+
+/// Unit the weights are shown in, they are always stored in kilograms
+#[derive(
+    Debug, Display, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq, sqlx::Type,
+)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+pub enum WeightUnit {
+    #[default]
+    #[display("kg")]
+    #[serde(alias = "kg", rename(serialize = "kg"))]
+    Kg,
+    #[display("lb")]
+    #[serde(alias = "lb", rename(serialize = "lb"))]
+    Lb,
+}
+
+impl WeightUnit {
+    /// Converts a weight in kilograms to this unit
+    pub fn from_kg(self, kg: f64) -> f64 {
+        match self {
+            WeightUnit::Kg => kg,
+            WeightUnit::Lb => kg * LB_PER_KG,
+        }
+    }
+
+    /// Converts a weight in this unit to kilograms
+    pub fn to_kg(self, value: f64) -> f64 {
+        match self {
+            WeightUnit::Kg => value,
+            WeightUnit::Lb => value / LB_PER_KG,
+        }
+    }
+}
+
+/// Country code added to 10 digit (Mexican) phones in WhatsApp links
+const DEFAULT_PHONE_COUNTRY_CODE: &str = "52";
+
+#[derive(Debug, Display, Clone, Default, Deserialize, Serialize, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+pub enum ContactType {
+    #[display("phone")]
+    #[serde(alias = "phone", rename(serialize = "phone"))]
+    Phone,
+    #[display("email")]
+    #[serde(alias = "email", rename(serialize = "email"))]
+    Email,
+    #[display("whatsapp")]
+    #[serde(alias = "whatsapp", rename(serialize = "whatsapp"))]
+    WhatsApp,
+    #[default]
+    #[display("other")]
+    #[serde(alias = "other", rename(serialize = "other"))]
+    Other,
+}
+
+impl ContactType {
+    /// Guesses the contact type from the value typed by the user.
+    ///
+    /// Phones are detected as [`ContactType::Phone`], WhatsApp has to be
+    /// chosen by the user.
+    pub fn sniff(contact_value: &str) -> Self {
+        let value = contact_value.trim();
+
+        if let Some((user, domain)) = value.split_once('@')
+            && !user.is_empty()
+            && domain.contains('.')
+            && !value.contains(char::is_whitespace)
+        {
+            return Self::Email;
+        }
+
+        let digits = value.chars().filter(char::is_ascii_digit).count();
+        if (7..=15).contains(&digits)
+            && value
+                .chars()
+                .all(|c| c.is_ascii_digit() || "+-(). ".contains(c))
+        {
+            return Self::Phone;
+        }
+
+        Self::Other
+    }
+}
+
 #[derive(serde::Serialize, sqlx::FromRow, Clone)]
 pub struct OwnerContact {
     pub id: i64,
     pub user_app_id: i64,
     pub full_name: String,
     pub contact_value: String,
+    pub contact_type: ContactType,
+    /// Shown first on the public profile, a user has one primary contact at most
+    pub is_primary: bool,
+    /// Position chosen by the owner, lower first
+    pub sort_order: i64,
+    /// Rough home area, helps to return a pet found near home
+    pub address: Option<String>,
+    /// Coordinates of the home area, stored already rounded
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
     pub created_at: DateTime<Utc>,
 }
+
+impl OwnerContact {
+    /// Builds the click-to-action link of the contact.
+    ///
+    /// # Returns
+    /// * `Option<String>` - `tel:`, `mailto:` or WhatsApp link, `None` for other contacts
+    pub fn href(&self) -> Option<String> {
+        let value = self.contact_value.trim();
+        let digits: String = value.chars().filter(char::is_ascii_digit).collect();
+
+        match self.contact_type {
+            ContactType::Phone if !digits.is_empty() => {
+                let prefix = if value.starts_with('+') { "+" } else { "" };
+                Some(format!("tel:{prefix}{digits}"))
+            }
+            ContactType::WhatsApp if !digits.is_empty() => {
+                let country_code = if digits.len() == 10 {
+                    DEFAULT_PHONE_COUNTRY_CODE
+                } else {
+                    ""
+                };
+                Some(format!("https://wa.me/{country_code}{digits}"))
+            }
+            ContactType::Email if !value.is_empty() => Some(format!("mailto:{value}")),
+            _ => None,
+        }
+    }
+
+    /// Builds the link to a map centered on the home area of the contact.
+    ///
+    /// # Returns
+    /// * `Option<String>` - OpenStreetMap link, `None` without coordinates
+    pub fn map_url(&self) -> Option<String> {
+        let (lat, lng) = (self.latitude?, self.longitude?);
+
+        Some(format!(
+            "https://www.openstreetmap.org/?mlat={lat}&mlon={lng}#map={zoom}/{lat}/{lng}",
+            zoom = crate::consts::HOME_AREA_MAP_ZOOM
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contact(contact_value: &str, contact_type: ContactType) -> OwnerContact {
+        OwnerContact {
+            id: 1,
+            user_app_id: 1,
+            full_name: "Juanito".into(),
+            contact_value: contact_value.into(),
+            contact_type,
+            is_primary: false,
+            sort_order: 0,
+            address: None,
+            latitude: None,
+            longitude: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_user_vs_premium_capabilities() {
+        let user = AccountRole::User.capabilities();
+        let premium = AccountRole::Premium.capabilities();
+
+        // users pay each pet, without a pet limit
+        assert!(user.allows_new_pet(100, 1));
+        assert!(!user.allows_new_pet(0, 0));
+
+        // premium accounts don't pay each pet, up to their limit
+        assert!(premium.allows_new_pet(0, 0));
+        assert!(premium.allows_new_pet(PREMIUM_MAX_PETS - 1, 0));
+        assert!(!premium.allows_new_pet(PREMIUM_MAX_PETS, 5));
+
+        assert!(!AccountRole::Unknown.capabilities().allows_new_pet(0, 1));
+    }
+
+    #[test]
+    fn test_can_access_service_by_role() {
+        let mut user = User::create_default_from_email("user@example.com");
+        assert!(!user.can_access_service());
+
+        user.is_subscribed = true;
+        assert!(user.can_access_service());
+
+        user.is_enabled = false;
+        assert!(!user.can_access_service());
+
+        let mut staff = User::create_default_from_email("staff@example.com");
+        staff.account_role = AccountRole::Staff;
+        assert!(staff.can_access_service());
+
+        let mut premium = User::create_default_from_email("premium@example.com");
+        premium.account_role = AccountRole::Premium;
+        assert!(!premium.can_access_service());
+    }
+
+    #[test]
+    fn test_contact_type_sniff() {
+        assert_eq!(
+            ContactType::sniff("juanito@example.com"),
+            ContactType::Email
+        );
+        assert_eq!(ContactType::sniff("55 2345 6789"), ContactType::Phone);
+        assert_eq!(ContactType::sniff("+52 (55) 2345-6789"), ContactType::Phone);
+        assert_eq!(ContactType::sniff("@juanito"), ContactType::Other);
+        assert_eq!(ContactType::sniff("123"), ContactType::Other);
+        assert_eq!(ContactType::sniff("calle 5 #23"), ContactType::Other);
+    }
+
+    #[test]
+    fn test_owner_contact_href() {
+        assert_eq!(
+            contact("+52 (55) 2345-6789", ContactType::Phone).href(),
+            Some("tel:+525523456789".into())
+        );
+        assert_eq!(
+            contact("55 2345 6789", ContactType::WhatsApp).href(),
+            Some("https://wa.me/525523456789".into())
+        );
+        assert_eq!(
+            contact("+1 415 555 0100", ContactType::WhatsApp).href(),
+            Some("https://wa.me/14155550100".into())
+        );
+        assert_eq!(
+            contact("juanito@example.com", ContactType::Email).href(),
+            Some("mailto:juanito@example.com".into())
+        );
+        assert_eq!(contact("calle 5", ContactType::Other).href(), None);
+    }
+
+    #[test]
+    fn test_owner_contact_map_url_needs_both_coordinates() {
+        let mut home = contact("calle 5", ContactType::Other);
+        assert_eq!(home.map_url(), None);
+
+        home.latitude = Some(19.43);
+        assert_eq!(home.map_url(), None);
+
+        home.longitude = Some(-99.13);
+        assert_eq!(
+            home.map_url().as_deref(),
+            Some("https://www.openstreetmap.org/?mlat=19.43&mlon=-99.13#map=14/19.43/-99.13")
+        );
+    }
+}
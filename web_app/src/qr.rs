@@ -4,6 +4,7 @@
 //! It provides functions to create beautiful QR codes with custom liquid-effect styling
 //! and QR cards with pet pictures.
 
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont};
 use anyhow::Context;
 use qrcode::{EcLevel, QrCode};
 use tiny_skia::{Color, FillRule, Paint, PathBuilder, Pixmap, Transform};
@@ -232,17 +233,215 @@ fn draw_finder_patterns<F>(
     draw_eye(0, width - 7); // Bottom-Left
 }
 
+const CARD_FONT: &[u8] = include_bytes!("../assets/fonts/DynaPuff.ttf");
+const CAPTION_FONT_SIZE: f32 = 40.0;
+const FOOTER_FONT_SIZE: f32 = 24.0;
+
+/// Measures the horizontal advance of `text` rendered at `scale`.
+fn text_width(font: &FontRef, scale: PxScale, text: &str) -> f32 {
+    let scaled_font = font.as_scaled(scale);
+    text.chars()
+        .map(|c| scaled_font.h_advance(scaled_font.glyph_id(c)))
+        .sum()
+}
+
+/// Fits a caption into `max_width`.
+///
+/// The font size is reduced down to `min_size` first; if the text still
+/// doesn't fit, it is truncated and an ellipsis is appended.
+///
+/// # Returns
+/// * `(String, PxScale)` - The text to render and the scale to render it with
+fn fit_caption(
+    font: &FontRef,
+    text: &str,
+    max_size: f32,
+    min_size: f32,
+    max_width: f32,
+) -> (String, PxScale) {
+    let width = text_width(font, PxScale::from(max_size), text);
+    if width <= max_width {
+        return (text.to_string(), PxScale::from(max_size));
+    }
+
+    let size = (max_size * max_width / width).max(min_size);
+    let scale = PxScale::from(size);
+    if text_width(font, scale, text) <= max_width {
+        return (text.to_string(), scale);
+    }
+
+    let mut truncated: String = text.chars().collect();
+    while !truncated.is_empty() && text_width(font, scale, &format!("{truncated}…")) > max_width {
+        truncated.pop();
+    }
+
+    (format!("{}…", truncated.trim_end()), scale)
+}
+
+/// Renders `text` horizontally centered on the pixmap with its baseline at `y`.
+///
+/// Glyphs are alpha blended with the existing background.
+fn draw_centered_text(
+    pixmap: &mut Pixmap,
+    font: &FontRef,
+    scale: PxScale,
+    text: &str,
+    y: f32,
+    text_color: tiny_skia::ColorU8,
+) {
+    let canvas_width = pixmap.width();
+    let canvas_height = pixmap.height();
+    let scaled_font = font.as_scaled(scale);
+
+    let text_x = ((canvas_width as f32 - text_width(font, scale, text)) / 2.0).max(0.0);
+
+    let mut x_offset = text_x;
+    for ch in text.chars() {
+        let glyph_id = scaled_font.glyph_id(ch);
+        let glyph = glyph_id.with_scale_and_position(scale, ab_glyph::point(x_offset, y));
+
+        if let Some(outlined) = scaled_font.outline_glyph(glyph) {
+            let bounds = outlined.px_bounds();
+            outlined.draw(|gx, gy, coverage| {
+                if coverage > 0.0 {
+                    let px = (bounds.min.x as i32 + gx as i32) as u32;
+                    let py = (bounds.min.y as i32 + gy as i32) as u32;
+
+                    if px < canvas_width && py < canvas_height {
+                        let idx = (py * canvas_width + px) as usize;
+                        let alpha = (coverage.min(1.0) * 255.0) as u8;
+
+                        // Alpha blend text with background
+                        let bg = pixmap.pixels()[idx].demultiply();
+                        let blended = tiny_skia::ColorU8::from_rgba(
+                            ((text_color.red() as u16 * alpha as u16
+                                + bg.red() as u16 * (255 - alpha) as u16)
+                                / 255) as u8,
+                            ((text_color.green() as u16 * alpha as u16
+                                + bg.green() as u16 * (255 - alpha) as u16)
+                                / 255) as u8,
+                            ((text_color.blue() as u16 * alpha as u16
+                                + bg.blue() as u16 * (255 - alpha) as u16)
+                                / 255) as u8,
+                            255,
+                        );
+                        pixmap.pixels_mut()[idx] = blended.premultiply();
+                    }
+                }
+            });
+        }
+
+        x_offset += scaled_font.h_advance(glyph_id);
+    }
+}
+
+/// Avatar colors of dogs, warm tones
+const DOG_AVATAR_COLORS: [(u8, u8, u8); 4] =
+    [(234, 88, 12), (217, 119, 6), (190, 18, 60), (161, 98, 7)];
+/// Avatar colors of cats, cool tones
+const CAT_AVATAR_COLORS: [(u8, u8, u8); 4] =
+    [(37, 99, 235), (79, 70, 229), (124, 58, 237), (8, 145, 178)];
+/// Avatar colors when the species is unknown
+const DEFAULT_AVATAR_COLORS: [(u8, u8, u8); 4] =
+    [(5, 150, 105), (13, 148, 136), (101, 163, 13), (71, 85, 105)];
+
+/// FNV-1a hash of the name, stable across builds unlike `DefaultHasher`
+/// so a pet keeps its avatar color between deploys.
+fn name_hash(name: &str) -> u64 {
+    name.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Uppercased initials of the first two words of the name, `?` if it has none.
+fn name_initials(name: &str) -> String {
+    let initials: String = name
+        .split_whitespace()
+        .filter_map(|word| word.chars().find(|c| c.is_alphanumeric()))
+        .take(2)
+        .flat_map(char::to_uppercase)
+        .collect();
+
+    if initials.is_empty() {
+        "?".to_string()
+    } else {
+        initials
+    }
+}
+
+/// Renders the avatar of a pet without picture: its initials on a colored circle.
+///
+/// The palette depends on the species (`dog`, `cat`) and the color within it
+/// on the name, so the same pet always gets the same avatar.
+///
+/// # Arguments
+/// * `pet_name` - The pet's name, its initials are drawn on the avatar
+/// * `species` - The pet's species if known
+///
+/// # Returns
+/// * `anyhow::Result<Vec<u8>>` - PNG of [`QR_CARD_AVATAR_SIZE_PX`](crate::consts::QR_CARD_AVATAR_SIZE_PX) square
+pub fn generate_default_avatar(pet_name: &str, species: Option<&str>) -> anyhow::Result<Vec<u8>> {
+    const AVATAR_SIZE: u32 = crate::consts::QR_CARD_AVATAR_SIZE_PX;
+
+    let palette = match species {
+        Some("dog") => &DOG_AVATAR_COLORS,
+        Some("cat") => &CAT_AVATAR_COLORS,
+        _ => &DEFAULT_AVATAR_COLORS,
+    };
+    let pet_name = pet_name.trim();
+    let (r, g, b) = palette[(name_hash(pet_name) % palette.len() as u64) as usize];
+
+    let mut pixmap = Pixmap::new(AVATAR_SIZE, AVATAR_SIZE).context("Failed to create pixmap")?;
+    let center = AVATAR_SIZE as f32 / 2.0;
+    let circle = PathBuilder::from_circle(center, center, center)
+        .context("Failed to build the avatar circle")?;
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(r, g, b, 255);
+    paint.anti_alias = true;
+    pixmap.fill_path(
+        &circle,
+        &paint,
+        FillRule::Winding,
+        Transform::default(),
+        None,
+    );
+
+    let font = FontRef::try_from_slice(CARD_FONT).context("Failed to load font")?;
+    let (initials, scale) = fit_caption(
+        &font,
+        &name_initials(pet_name),
+        AVATAR_SIZE as f32 * 0.45,
+        AVATAR_SIZE as f32 * 0.25,
+        AVATAR_SIZE as f32 * 0.7,
+    );
+    // Baseline that centers the glyphs between ascent and descent
+    let scaled_font = font.as_scaled(scale);
+    let baseline_y = center + (scaled_font.ascent() + scaled_font.descent()) / 2.0;
+    draw_centered_text(
+        &mut pixmap,
+        &font,
+        scale,
+        &initials,
+        baseline_y,
+        tiny_skia::ColorU8::from_rgba(255, 255, 255, 255),
+    );
+
+    Ok(pixmap.encode_png()?)
+}
+
 /// Builds a styled QR card with pet picture.
 ///
 /// Creates a beautiful card design with:
 /// - Gradient background
 /// - White rounded card container
 /// - Floating circular pet avatar (50% inside card, 50% outside)
+/// - Pet name caption (scaled down or truncated to fit the card)
 /// - Centered QR code
 /// - Footer text "by pet-info.link"
 ///
 /// # Arguments
 /// * `pet_pic` - The pet's picture data (body and extension)
+/// * `pet_name` - The pet's name, rendered as a caption above the QR code
 /// * `info_url` - The URL to encode in the QR code
 ///
 /// # Returns
@@ -255,12 +454,13 @@ fn draw_finder_patterns<F>(
 /// - Image composition fails
 pub fn build_qr_card_with_pic(
     pet_pic: &crate::api::pet::PetPublicPic,
+    pet_name: &str,
     info_url: &str,
 ) -> anyhow::Result<Vec<u8>> {
     // Card dimensions
     const CARD_WIDTH: u32 = 600;
     const CARD_HEIGHT: u32 = 720; // Reduced by 20%
-    const AVATAR_SIZE: u32 = 160;
+    const AVATAR_SIZE: u32 = crate::consts::QR_CARD_AVATAR_SIZE_PX;
     const CARD_RADIUS: f32 = 40.0;
     const AVATAR_RADIUS: u32 = AVATAR_SIZE / 2; // Avatar radius (80px)
 
@@ -330,7 +530,7 @@ pub fn build_qr_card_with_pic(
     // Position QR code in center of card, below avatar
     let qr_size = qr_img.width().min(qr_img.height());
     let qr_x = (CANVAS_WIDTH.saturating_sub(qr_size)) / 2;
-    let qr_y = card_y as u32 + AVATAR_RADIUS + 60; // Below avatar with spacing
+    let qr_y = card_y as u32 + AVATAR_RADIUS + 80; // Below avatar and name caption
 
     // Overlay QR code
     for (x, y, pixel) in qr_img.enumerate_pixels() {
@@ -352,20 +552,24 @@ pub fn build_qr_card_with_pic(
     let pic_format =
         image::ImageFormat::from_extension(&pet_pic.extension).unwrap_or(image::ImageFormat::Jpeg);
 
-    let pet_img = image::load_from_memory_with_format(&pet_pic.body, pic_format)
-        .with_context(|| {
-            format!(
-                "Failed to load pet picture (stored ext: {}, size: {} bytes)",
-                pet_pic.extension,
-                pet_pic.body.len()
-            )
-        })?
-        .resize_to_fill(
-            AVATAR_SIZE,
-            AVATAR_SIZE,
-            image::imageops::FilterType::Lanczos3,
+    // animated GIFs keep only their first frame
+    let pet_img = match pic_format {
+        image::ImageFormat::Gif => crate::front::utils::load_first_frame(&pet_pic.body),
+        _ => image::load_from_memory_with_format(&pet_pic.body, pic_format).map_err(Into::into),
+    }
+    .with_context(|| {
+        format!(
+            "Failed to load pet picture (stored ext: {}, size: {} bytes)",
+            pet_pic.extension,
+            pet_pic.body.len()
         )
-        .to_rgba8();
+    })?
+    .resize_to_fill(
+        AVATAR_SIZE,
+        AVATAR_SIZE,
+        image::imageops::FilterType::Lanczos3,
+    )
+    .to_rgba8();
 
     // Create circular mask and overlay avatar on canvas
     // Avatar center (avatar_x, avatar_y) should align with card's horizontal center and top edge
@@ -402,67 +606,37 @@ pub fn build_qr_card_with_pic(
         }
     }
 
-    // Draw footer text "by pet-info.link"
-    use ab_glyph::{Font, FontRef, PxScale, ScaleFont};
-
-    let font_data = include_bytes!("../assets/fonts/DynaPuff.ttf");
-    let font = FontRef::try_from_slice(font_data).context("Failed to load font")?;
-
-    const FONT_SIZE: f32 = 24.0;
-    let text = "by pet-info.link";
-    let scale = PxScale::from(FONT_SIZE);
-    let scaled_font = font.as_scaled(scale);
-
-    // Calculate text width for centering
-    let text_width: f32 = text
-        .chars()
-        .map(|c| scaled_font.h_advance(scaled_font.glyph_id(c)))
-        .sum();
-
-    let text_x = ((CANVAS_WIDTH as f32 - text_width) / 2.0).max(0.0);
-    let text_y = qr_y + qr_size + 60;
+    let font = FontRef::try_from_slice(CARD_FONT).context("Failed to load font")?;
     let text_color = tiny_skia::ColorU8::from_rgba(15, 23, 42, 255); // Matching QR color
 
-    // Render text
-    let mut x_offset = text_x;
-    for ch in text.chars() {
-        let glyph_id = scaled_font.glyph_id(ch);
-        let glyph =
-            glyph_id.with_scale_and_position(scale, ab_glyph::point(x_offset, text_y as f32));
-
-        if let Some(outlined) = scaled_font.outline_glyph(glyph) {
-            let bounds = outlined.px_bounds();
-            outlined.draw(|gx, gy, coverage| {
-                if coverage > 0.0 {
-                    let px = (bounds.min.x as i32 + gx as i32) as u32;
-                    let py = (bounds.min.y as i32 + gy as i32) as u32;
-
-                    if px < CANVAS_WIDTH && py < CANVAS_HEIGHT {
-                        let idx = (py * CANVAS_WIDTH + px) as usize;
-                        let alpha = (coverage * 255.0) as u8;
-
-                        // Alpha blend text with background
-                        let bg = pixmap.pixels()[idx].demultiply();
-                        let blended = tiny_skia::ColorU8::from_rgba(
-                            ((text_color.red() as u16 * alpha as u16
-                                + bg.red() as u16 * (255 - alpha) as u16)
-                                / 255) as u8,
-                            ((text_color.green() as u16 * alpha as u16
-                                + bg.green() as u16 * (255 - alpha) as u16)
-                                / 255) as u8,
-                            ((text_color.blue() as u16 * alpha as u16
-                                + bg.blue() as u16 * (255 - alpha) as u16)
-                                / 255) as u8,
-                            255,
-                        );
-                        pixmap.pixels_mut()[idx] = blended.premultiply();
-                    }
-                }
-            });
-        }
+    // Draw pet name caption between avatar and QR code
+    let (caption, caption_scale) = fit_caption(
+        &font,
+        pet_name.trim(),
+        CAPTION_FONT_SIZE,
+        FOOTER_FONT_SIZE,
+        CARD_WIDTH as f32 - 80.0,
+    );
+    let caption_y = card_y + AVATAR_RADIUS as f32 + 55.0;
+    draw_centered_text(
+        &mut pixmap,
+        &font,
+        caption_scale,
+        &caption,
+        caption_y,
+        text_color,
+    );
 
-        x_offset += scaled_font.h_advance(glyph_id);
-    }
+    // Draw footer text "by pet-info.link"
+    let text_y = (qr_y + qr_size + 60) as f32;
+    draw_centered_text(
+        &mut pixmap,
+        &font,
+        PxScale::from(FOOTER_FONT_SIZE),
+        "by pet-info.link",
+        text_y,
+        text_color,
+    );
 
     Ok(pixmap.encode_png()?)
 }
@@ -471,6 +645,21 @@ pub fn build_qr_card_with_pic(
 mod tests {
     use super::*;
 
+    fn create_test_pet_pic() -> crate::api::pet::PetPublicPic {
+        let img = image::RgbaImage::from_pixel(32, 32, image::Rgba([200, 120, 80, 255]));
+        let mut body = Vec::new();
+        img.write_to(
+            &mut std::io::Cursor::new(&mut body),
+            image::ImageFormat::Png,
+        )
+        .unwrap();
+
+        crate::api::pet::PetPublicPic {
+            body,
+            extension: "png".to_string(),
+        }
+    }
+
     /// Tests QR code generation with various inputs.
     #[test]
     fn test_get_qr_code() {
@@ -490,4 +679,46 @@ mod tests {
         let result = get_qr_code(long_url);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_build_qr_card_with_long_unicode_name() {
+        let pet_pic = create_test_pet_pic();
+        let long_name = "Señor Bigotes Ñandú 🐾 de la Montaña Encantada y Más Allá del Arcoíris ✨";
+
+        let result = build_qr_card_with_pic(&pet_pic, long_name, "https://example.com/info/123");
+        assert!(result.is_ok_and(|card| card[0..8] == [137, 80, 78, 71, 13, 10, 26, 10]));
+    }
+
+    #[test]
+    fn test_generate_default_avatar_is_png_of_avatar_size() {
+        let avatar = generate_default_avatar("Señor Bigotes", Some("cat")).unwrap();
+
+        assert_eq!(&avatar[0..8], &[137, 80, 78, 71, 13, 10, 26, 10]);
+        let img = image::load_from_memory_with_format(&avatar, image::ImageFormat::Png).unwrap();
+        assert_eq!(
+            (img.width(), img.height()),
+            (
+                crate::consts::QR_CARD_AVATAR_SIZE_PX,
+                crate::consts::QR_CARD_AVATAR_SIZE_PX
+            )
+        );
+    }
+
+    #[test]
+    fn test_name_initials() {
+        assert_eq!(name_initials("señor bigotes de la montaña"), "SB");
+        assert_eq!(name_initials("  Firulais "), "F");
+        assert_eq!(name_initials("🐾"), "?");
+    }
+
+    #[test]
+    fn test_fit_caption_truncates_to_max_width() {
+        let font = FontRef::try_from_slice(CARD_FONT).unwrap();
+        let max_width = 200.0;
+
+        let (caption, scale) = fit_caption(&font, &"Firulais ".repeat(20), 40.0, 24.0, max_width);
+
+        assert!(caption.ends_with('…'));
+        assert!(text_width(&font, scale, &caption) <= max_width);
+    }
 }
@@ -0,0 +1,67 @@
+//! Errors of the repository layer.
+//!
+//! Most repository methods return `anyhow::Result`; the ones whose callers
+//! need to tell a missing record apart from a database failure return
+//! [`RepoError`].
+
+use derive_more::{Display, Error};
+
+#[derive(Debug, Display, Error)]
+pub enum RepoError {
+    /// The record doesn't exist or doesn't belong to the user
+    #[display("record not found")]
+    NotFound,
+    /// The write conflicts with an existing record
+    #[display("record already exists")]
+    Conflict,
+    /// Any other database error
+    #[display("database error: {_0}")]
+    Db(sqlx::Error),
+}
+
+impl From<sqlx::Error> for RepoError {
+    fn from(e: sqlx::Error) -> Self {
+        match e {
+            sqlx::Error::RowNotFound => Self::NotFound,
+            sqlx::Error::Database(ref db_error) if db_error.is_unique_violation() => Self::Conflict,
+            e => Self::Db(e),
+        }
+    }
+}
+
+impl RepoError {
+    /// Whether an error raised by the api layer is a [`RepoError::NotFound`],
+    /// even if context was added to it.
+    pub fn is_not_found(e: &anyhow::Error) -> bool {
+        matches!(e.downcast_ref::<RepoError>(), Some(RepoError::NotFound))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_row_not_found_maps_to_not_found() {
+        assert!(matches!(
+            RepoError::from(sqlx::Error::RowNotFound),
+            RepoError::NotFound
+        ));
+        assert!(matches!(
+            RepoError::from(sqlx::Error::PoolTimedOut),
+            RepoError::Db(_)
+        ));
+    }
+
+    #[test]
+    fn test_is_not_found_through_anyhow_context() {
+        let not_found: anyhow::Result<()> = Err(RepoError::NotFound).context("getting the pet");
+        let db_error: anyhow::Result<()> =
+            Err(RepoError::from(sqlx::Error::PoolClosed)).context("getting the pet");
+
+        assert!(RepoError::is_not_found(&not_found.unwrap_err()));
+        assert!(!RepoError::is_not_found(&db_error.unwrap_err()));
+        assert!(!RepoError::is_not_found(&anyhow::anyhow!("other error")));
+    }
+}
@@ -4,6 +4,7 @@
 //! the `AppRepo` trait that abstracts database operations for pets, users,
 //! payments, and other application entities.
 
+pub mod errors;
 pub mod sqlite;
 pub mod sqlite_queries;
 
@@ -17,8 +18,10 @@ use uuid::Uuid;
 /// different implementations (currently SQLite) while maintaining a consistent
 /// interface for the application logic.
 ///
-/// All methods return `anyhow::Result` for error handling and are async to
-/// support non-blocking database operations.
+/// Methods return `anyhow::Result` for error handling, except the getters
+/// whose callers must tell a missing record from a failure, which return
+/// [`errors::RepoError`]. All methods are async to support non-blocking
+/// database operations.
 #[async_trait]
 #[cfg_attr(test, mockall::automock)]
 pub trait AppRepo {
@@ -77,6 +80,17 @@ pub trait AppRepo {
     /// * `user_app_id` - The user's unique identifier
     async fn set_to_null_verified_phone(&self, user_app_id: i64) -> anyhow::Result<()>;
 
+    /// Sets the unit the user sees the weights in.
+    ///
+    /// # Arguments
+    /// * `user_app_id` - The user's unique identifier
+    /// * `weight_unit` - The preferred weight unit
+    async fn set_user_weight_unit(
+        &self,
+        user_app_id: i64,
+        weight_unit: models::user_app::WeightUnit,
+    ) -> anyhow::Result<()>;
+
     /// Creates a new user in the system.
     ///
     /// # Arguments
@@ -121,6 +135,16 @@ pub trait AppRepo {
     /// * `balance` - The new balance amount
     async fn set_pet_balance(&self, user_id: i64, balance: u32) -> anyhow::Result<()>;
 
+    /// Takes one paid pet from the balance of a user in a single update, so
+    /// concurrent requests can't spend the same one nor leave it negative.
+    ///
+    /// # Arguments
+    /// * `user_id` - The user's unique identifier
+    ///
+    /// # Returns
+    /// * The balance left, [`errors::RepoError::NotFound`] if it was already zero
+    async fn decrement_pet_balance(&self, user_id: i64) -> anyhow::Result<u32>;
+
     /// Retrieves the current pet balance for a user.
     ///
     /// # Arguments
@@ -156,7 +180,38 @@ pub trait AppRepo {
     /// * The newly created pet's ID
     async fn save_pet(&self, pet: &models::pet::Pet) -> anyhow::Result<i64>;
 
-    /// Updates an existing pet record.
+    /// Creates a new pet and charges it to its owner in a single transaction:
+    /// the paid pet is taken from the balance, the pet is inserted and linked
+    /// to its external id and the owner is marked as subscribed. Nothing is
+    /// kept if any step fails.
+    ///
+    /// # Arguments
+    /// * `pet` - The pet data to create
+    /// * `user_id` - The owner's unique identifier
+    /// * `charge_balance` - Whether a paid pet is taken from the balance,
+    ///   roles without a balance add pets for free
+    ///
+    /// # Returns
+    /// * The newly created pet's ID, [`errors::RepoError::NotFound`] if the
+    ///   balance was already zero
+    async fn create_pet_and_charge(
+        &self,
+        pet: &models::pet::Pet,
+        user_id: i64,
+        charge_balance: bool,
+    ) -> anyhow::Result<i64>;
+
+    /// Counts the pets registered by a user.
+    ///
+    /// # Arguments
+    /// * `user_id` - The user's unique identifier
+    ///
+    /// # Returns
+    /// * Number of pets owned by the user
+    async fn count_user_pets(&self, user_id: i64) -> anyhow::Result<i64>;
+
+    /// Updates an existing pet record, recording the changed fields in its
+    /// audit log.
     ///
     /// # Arguments
     /// * `pet` - The updated pet data
@@ -165,13 +220,273 @@ pub trait AppRepo {
     /// * The updated pet's ID
     async fn update_pet(&self, pet: &models::pet::Pet) -> anyhow::Result<i64>;
 
+    /// Sets only the lost status of a pet, leaving the other fields untouched.
+    ///
+    /// # Arguments
+    /// * `pet_id` - The pet's unique identifier
+    /// * `user_id` - The owner's user ID (for authorization)
+    /// * `is_lost` - Whether the pet is lost
+    async fn set_pet_lost_status(
+        &self,
+        pet_id: i64,
+        user_id: i64,
+        is_lost: bool,
+    ) -> anyhow::Result<()>;
+
+    /// Sets or removes the PIN that unlocks the owner contacts on the public profile.
+    ///
+    /// Only the owner can change it, collaborators can't.
+    ///
+    /// # Arguments
+    /// * `pet_id` - The pet's unique identifier
+    /// * `user_id` - The owner's user ID (for authorization)
+    /// * `pin_hash` - Argon2 hash of the PIN, `None` removes it
+    async fn set_pet_public_pin(
+        &self,
+        pet_id: i64,
+        user_id: i64,
+        pin_hash: Option<String>,
+    ) -> anyhow::Result<()>;
+
+    /// Retrieves the hashed PIN that unlocks the owner contacts on the public profile.
+    ///
+    /// # Arguments
+    /// * `pet_external_id` - The pet's external UUID
+    ///
+    /// # Returns
+    /// * `Some(hash)` if the pet has a PIN, `None` otherwise
+    async fn get_pet_public_pin(&self, pet_external_id: Uuid) -> anyhow::Result<Option<String>>;
+
+    /// Retrieves until when the PIN of a pet public profile is blocked after too many failures.
+    ///
+    /// # Arguments
+    /// * `pet_external_id` - The pet's external UUID
+    ///
+    /// # Returns
+    /// * `Some(datetime)` if the pet was ever blocked, `None` otherwise
+    async fn get_pet_pin_locked_until(
+        &self,
+        pet_external_id: Uuid,
+    ) -> anyhow::Result<Option<chrono::DateTime<chrono::Utc>>>;
+
+    /// Counts a wrong PIN typed on a pet public profile.
+    ///
+    /// When the count reaches `max_attempts` the PIN is blocked until `locked_until`
+    /// and the count starts over.
+    ///
+    /// # Arguments
+    /// * `pet_external_id` - The pet's external UUID
+    /// * `max_attempts` - Failures allowed before blocking the PIN
+    /// * `locked_until` - End of the block if this failure triggers it
+    async fn record_failed_pet_pin_attempt(
+        &self,
+        pet_external_id: Uuid,
+        max_attempts: i64,
+        locked_until: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<()>;
+
+    /// Forgets the wrong PINs counted for a pet public profile.
+    ///
+    /// # Arguments
+    /// * `pet_external_id` - The pet's external UUID
+    async fn clear_failed_pet_pin_attempts(&self, pet_external_id: Uuid) -> anyhow::Result<()>;
+
+    /// Sets only the spaying/neutering status of a pet, leaving the other fields untouched.
+    ///
+    /// # Arguments
+    /// * `pet_id` - The pet's unique identifier
+    /// * `user_id` - The owner's user ID (for authorization)
+    /// * `is_spaying_neutering` - Whether the pet is spayed/neutered
+    async fn set_pet_spaying_neutering_status(
+        &self,
+        pet_id: i64,
+        user_id: i64,
+        is_spaying_neutering: bool,
+    ) -> anyhow::Result<()>;
+
+    /// Registers the token of a submitted create pet form.
+    ///
+    /// Tokens older than [`PET_CREATION_TOKEN_TTL_HOURS`](crate::consts::PET_CREATION_TOKEN_TTL_HOURS)
+    /// are removed first.
+    ///
+    /// # Arguments
+    /// * `user_id` - The user submitting the form
+    /// * `token` - Token rendered in the form
+    ///
+    /// # Returns
+    /// * `false` if the token was already registered, i.e. the form was submitted twice
+    async fn claim_pet_creation_token(&self, user_id: i64, token: Uuid) -> anyhow::Result<bool>;
+
+    /// Removes a create pet form token, so the form can be submitted again.
+    ///
+    /// # Arguments
+    /// * `user_id` - The user who submitted the form
+    /// * `token` - Token rendered in the form
+    async fn release_pet_creation_token(&self, user_id: i64, token: Uuid) -> anyhow::Result<()>;
+
+    /// Sets only the storage path of a pet's picture, leaving the other fields untouched.
+    ///
+    /// # Arguments
+    /// * `pet_id` - The pet's unique identifier
+    /// * `user_id` - The owner's or an editor's user ID (for authorization)
+    /// * `pic_path` - Storage path of the new picture
+    async fn set_pet_pic(&self, pet_id: i64, user_id: i64, pic_path: &str) -> anyhow::Result<()>;
+
     /// Deletes a pet belonging to a specific user.
     ///
+    /// Only the owner can delete the pet, collaborators are ignored.
+    ///
     /// # Arguments
     /// * `pet_id` - The pet's unique identifier
     /// * `user_id` - The owner's user ID (for authorization)
     async fn delete_pet(&self, pet_id: i64, user_id: i64) -> anyhow::Result<()>;
 
+    /// Replaces the external UUID of a pet belonging to a specific user.
+    ///
+    /// The previous external ID is removed so it no longer resolves to the pet.
+    /// Records linked by the internal pet ID (health, weights, notes) are kept.
+    ///
+    /// # Arguments
+    /// * `pet_id` - The pet's unique identifier
+    /// * `user_id` - The owner's user ID (for authorization)
+    ///
+    /// # Returns
+    /// * The new external UUID of the pet
+    async fn rotate_pet_external_id(&self, pet_id: i64, user_id: i64) -> anyhow::Result<Uuid>;
+
+    /// Merges a duplicate pet into another pet of the same owner.
+    ///
+    /// In a single transaction the duplicate's health records, weights,
+    /// notes, reminders, found reports, accesses, share links and audit log
+    /// are moved to the primary pet, which also takes the duplicate's picture
+    /// and QR tag when it has none. A QR tag the primary can't take is
+    /// removed, as on [`AppRepo::rotate_pet_external_id`]. The duplicate is
+    /// deleted afterwards.
+    ///
+    /// # Arguments
+    /// * `primary_id` - The pet that is kept
+    /// * `duplicate_id` - The pet whose records are moved, then deleted
+    /// * `user_id` - The owner's user ID (for authorization), must own both pets
+    ///
+    /// # Returns
+    /// * The stored picture of the duplicate that was not kept, if any
+    ///
+    /// # Errors
+    /// [`RepoError::NotFound`](errors::RepoError::NotFound) if any of the pets
+    /// doesn't belong to the user, nothing is changed then.
+    async fn merge_pets(
+        &self,
+        primary_id: i64,
+        duplicate_id: i64,
+        user_id: i64,
+    ) -> anyhow::Result<Option<String>>;
+
+    /// Offers a pet to another registered account. Nothing moves until the
+    /// recipient accepts it with [`AppRepo::accept_pet_transfer`].
+    ///
+    /// A pet has at most one pending offer, offering it again replaces the
+    /// recipient.
+    ///
+    /// # Arguments
+    /// * `pet_id` - The pet's unique identifier
+    /// * `from_user_id` - The current owner's user ID (for authorization)
+    /// * `to_user_email` - Email of the account the pet is offered to
+    ///
+    /// # Errors
+    /// [`errors::RepoError::NotFound`] if the pet doesn't belong to the owner,
+    /// the email has no account or it's the owner's own email
+    async fn offer_pet_transfer(
+        &self,
+        pet_id: i64,
+        from_user_id: i64,
+        to_user_email: &str,
+    ) -> Result<(), errors::RepoError>;
+
+    /// Retrieves the pets offered to a user and still waiting for an answer.
+    ///
+    /// # Arguments
+    /// * `to_user_id` - The recipient's user ID
+    async fn get_incoming_pet_transfers(
+        &self,
+        to_user_id: i64,
+    ) -> anyhow::Result<Vec<models::pet::PetTransferOffer>>;
+
+    /// Accepts a pending offer: the pet, with all its records, becomes the
+    /// recipient's. The offer is consumed.
+    ///
+    /// # Arguments
+    /// * `pet_id` - The pet's unique identifier
+    /// * `to_user_id` - The recipient's user ID (for authorization)
+    ///
+    /// # Errors
+    /// [`errors::RepoError::NotFound`] if there is no offer of the pet to the
+    /// user or the pet changed of owner since it was offered
+    async fn accept_pet_transfer(
+        &self,
+        pet_id: i64,
+        to_user_id: i64,
+    ) -> Result<(), errors::RepoError>;
+
+    /// Withdraws (owner) or declines (recipient) the pending offer of a pet.
+    ///
+    /// # Arguments
+    /// * `pet_id` - The pet's unique identifier
+    /// * `user_id` - The owner or the recipient of the offer
+    async fn cancel_pet_transfer(&self, pet_id: i64, user_id: i64) -> anyhow::Result<()>;
+
+    /// Shares a pet with another registered account, or changes its role
+    /// if the pet is already shared with it.
+    ///
+    /// Collaborators can read the pet and, as [`Editor`](models::pet::PetAccessRole::Editor),
+    /// update it; deleting or transferring the pet is kept to its owner.
+    ///
+    /// # Arguments
+    /// * `pet_id` - The pet's unique identifier
+    /// * `owner_id` - The owner's user ID (for authorization)
+    /// * `target_email` - Email of the account to share the pet with
+    /// * `role` - Access granted to the account
+    ///
+    /// # Errors
+    /// [`errors::RepoError::NotFound`] if the pet doesn't belong to the owner,
+    /// the email has no account or it's the owner's own email
+    async fn grant_pet_access(
+        &self,
+        pet_id: i64,
+        owner_id: i64,
+        target_email: &str,
+        role: models::pet::PetAccessRole,
+    ) -> Result<(), errors::RepoError>;
+
+    /// Retrieves the accounts a pet is shared with, oldest first.
+    ///
+    /// # Arguments
+    /// * `pet_id` - The pet's unique identifier
+    /// * `owner_id` - The owner's user ID (for authorization)
+    ///
+    /// # Returns
+    /// * Vector of collaborators, empty if the pet doesn't belong to the owner
+    async fn list_pet_collaborators(
+        &self,
+        pet_id: i64,
+        owner_id: i64,
+    ) -> anyhow::Result<Vec<models::pet::PetCollaborator>>;
+
+    /// Retrieves the latest changes made on a pet, its health records and
+    /// notes, newest first.
+    ///
+    /// # Arguments
+    /// * `pet_id` - The pet's unique identifier
+    /// * `owner_id` - The owner's user ID (for authorization)
+    ///
+    /// # Returns
+    /// * Up to [PET_AUDIT_LIST_LIMIT](crate::consts::PET_AUDIT_LIST_LIMIT) changes,
+    ///   empty if the pet doesn't belong to the owner
+    async fn get_pet_audit(
+        &self,
+        pet_id: i64,
+        owner_id: i64,
+    ) -> anyhow::Result<Vec<models::pet::PetAuditEntry>>;
+
     /// Retrieves all pets belonging to a user.
     ///
     /// # Arguments
@@ -181,17 +496,84 @@ pub trait AppRepo {
     /// * Vector of all pets owned by the user
     async fn get_all_pets_user_id(&self, user_id: i64) -> anyhow::Result<Vec<models::pet::Pet>>;
 
+    /// Hands an external id (QR tag) to a user, at purchase or gift time.
+    ///
+    /// The external id is created when it doesn't exist; tags already owned
+    /// by someone else are kept.
+    ///
+    /// # Arguments
+    /// * `pet_external_id` - The tag's external UUID
+    /// * `user_id` - The user who bought or was gifted the tag
+    ///
+    /// # Returns
+    /// * `true` if the tag belongs to the user now
+    async fn assign_external_id_owner(
+        &self,
+        pet_external_id: Uuid,
+        user_id: i64,
+    ) -> anyhow::Result<bool>;
+
+    /// Retrieves the external ids (QR tags) a user owns that aren't linked to a pet.
+    ///
+    /// # Arguments
+    /// * `user_id` - The user's unique identifier
+    ///
+    /// # Returns
+    /// * The unlinked external ids, oldest first
+    async fn get_user_unlinked_external_ids(&self, user_id: i64) -> anyhow::Result<Vec<Uuid>>;
+
+    /// Retrieves all pets belonging to a user with their health record counts.
+    ///
+    /// # Arguments
+    /// * `user_id` - The user's unique identifier
+    ///
+    /// # Returns
+    /// * Vector of pets with their vaccine/weight counts and last vaccine date
+    async fn get_pets_with_counts(
+        &self,
+        user_id: i64,
+    ) -> anyhow::Result<Vec<models::pet::PetWithCounts>>;
+
     /// Retrieves a pet by its external UUID (public identifier).
     ///
     /// # Arguments
     /// * `pet_external_id` - The pet's external UUID
     ///
     /// # Returns
-    /// * The pet data
+    /// * The pet data, [`errors::RepoError::NotFound`] if there is no such pet
     async fn get_pet_by_external_id(
         &self,
         pet_external_id: Uuid,
-    ) -> anyhow::Result<models::pet::Pet>;
+    ) -> Result<models::pet::Pet, errors::RepoError>;
+
+    /// Retrieves the pets linked to any of the external UUIDs, in a single query.
+    ///
+    /// # Arguments
+    /// * `pet_external_ids` - The pets' external UUIDs
+    ///
+    /// # Returns
+    /// * The pets found, unknown or unlinked ids are left out
+    async fn get_pets_by_external_ids(
+        &self,
+        pet_external_ids: &[Uuid],
+    ) -> anyhow::Result<Vec<models::pet::Pet>>;
+
+    /// Retrieves a pet by its external UUID, ensuring the user can access it.
+    ///
+    /// Unlike [`get_pet_by_external_id`](Self::get_pet_by_external_id), meant
+    /// for authenticated flows that only know the public identifier.
+    ///
+    /// # Arguments
+    /// * `pet_external_id` - The pet's external UUID
+    /// * `user_id` - The owner's or a collaborator's user ID (for authorization)
+    ///
+    /// # Returns
+    /// * The pet data if owned by or shared with the user, [`errors::RepoError::NotFound`] otherwise
+    async fn get_pet_by_external_id_for_user(
+        &self,
+        pet_external_id: Uuid,
+        user_id: i64,
+    ) -> Result<models::pet::Pet, errors::RepoError>;
 
     /// Retrieves the file path for a pet's picture.
     ///
@@ -205,15 +587,44 @@ pub trait AppRepo {
         pet_external_id: Uuid,
     ) -> anyhow::Result<Option<String>>;
 
-    /// Retrieves a pet by its internal ID, ensuring user ownership.
+    /// Retrieves only the name and breed of a pet, what its QR card shows.
+    ///
+    /// # Arguments
+    /// * `pet_external_id` - The pet's external UUID
+    ///
+    /// # Returns
+    /// * `None` if the external id isn't linked to a pet
+    async fn get_pet_qr_caption(
+        &self,
+        pet_external_id: Uuid,
+    ) -> anyhow::Result<Option<models::pet::PetQrCaption>>;
+
+    /// Retrieves the last update of a pet with picture, a new picture
+    /// always updates it.
+    ///
+    /// # Arguments
+    /// * `pet_external_id` - The pet's external UUID
+    ///
+    /// # Returns
+    /// * `Some(updated_at)` if the pet has a picture, `None` otherwise
+    async fn get_pet_pic_updated_at_by_external_id(
+        &self,
+        pet_external_id: Uuid,
+    ) -> anyhow::Result<Option<chrono::DateTime<chrono::Utc>>>;
+
+    /// Retrieves a pet by its internal ID, ensuring the user can access it.
     ///
     /// # Arguments
     /// * `pet_id` - The pet's internal ID
-    /// * `user_id` - The owner's user ID (for authorization)
+    /// * `user_id` - The owner's or a collaborator's user ID (for authorization)
     ///
     /// # Returns
-    /// * The pet data if owned by the user
-    async fn get_pet_by_id(&self, pet_id: i64, user_id: i64) -> anyhow::Result<models::pet::Pet>;
+    /// * The pet data if owned by or shared with the user, [`errors::RepoError::NotFound`] otherwise
+    async fn get_pet_by_id(
+        &self,
+        pet_id: i64,
+        user_id: i64,
+    ) -> Result<models::pet::Pet, errors::RepoError>;
 
     // Pet Weights Management
 
@@ -256,6 +667,7 @@ pub trait AppRepo {
     /// * `user_id` - The owner's user ID (for authorization)
     /// * `desc` - Description of the vaccine (name, type, etc.)
     /// * `date` - Date when the vaccination was administered
+    /// * `booster_months` - Months until the booster is due, if any
     ///
     /// # Returns
     /// * The newly created vaccination health record
@@ -265,8 +677,49 @@ pub trait AppRepo {
         user_id: i64,
         desc: String,
         date: chrono::NaiveDate,
+        booster_months: Option<u32>,
     ) -> anyhow::Result<models::pet::PetHealth>;
 
+    /// Retrieves the latest vaccine with a booster interval of each of the
+    /// user's pets.
+    ///
+    /// Older applications of the same vaccine are left out, the newest one
+    /// is the one that sets the next booster. Vaccines already announced as
+    /// overdue in a digest are left out too, see
+    /// [`Self::record_vaccine_digest_sent`].
+    ///
+    /// # Arguments
+    /// * `user_id` - The owner's user ID
+    ///
+    /// # Returns
+    /// * Vector of vaccines with their pet name and booster interval
+    async fn get_all_user_pets_vaccines(
+        &self,
+        user_id: i64,
+    ) -> anyhow::Result<Vec<models::pet::PetVaccineBooster>>;
+
+    /// Retrieves the enabled users having at least one vaccine with a
+    /// booster interval not announced as overdue yet, along with the
+    /// channels and timezone of their latest reminder.
+    ///
+    /// # Returns
+    /// * Vector of users to consider for the vaccine digest
+    async fn get_vaccine_digest_recipients(
+        &self,
+    ) -> anyhow::Result<Vec<models::reminder::VaccineDigestRecipient>>;
+
+    /// Marks vaccines as announced in a digest with the state their booster
+    /// was in, the next digests don't announce them again in that state.
+    ///
+    /// # Arguments
+    /// * `announced` - IDs of the announced vaccine records with their booster state
+    /// * `sent_at` - When the digest was sent
+    async fn record_vaccine_digest_sent(
+        &self,
+        announced: &[(i64, models::pet::BoosterStatus)],
+        sent_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<()>;
+
     /// Removes a vaccination record from a pet.
     ///
     /// # Arguments
@@ -311,6 +764,65 @@ pub trait AppRepo {
         deworm_id: i64,
     ) -> anyhow::Result<()>;
 
+    /// Removes every vaccine or deworming record of a pet.
+    ///
+    /// # Arguments
+    /// * `pet_external_id` - The pet's external UUID
+    /// * `user_id` - The owner's user ID (for authorization)
+    /// * `health_type` - Type of the records to delete, weights live in their
+    ///   own table, see [`delete_all_weights`](Self::delete_all_weights)
+    ///
+    /// # Returns
+    /// * Number of deleted records
+    async fn delete_all_health_records(
+        &self,
+        pet_external_id: Uuid,
+        user_id: i64,
+        health_type: models::pet::PetHealthType,
+    ) -> anyhow::Result<u64>;
+
+    /// Updates the description and date of a vaccine or deworming record.
+    ///
+    /// # Arguments
+    /// * `record_id` - The unique identifier of the health record to update
+    /// * `pet_external_id` - The pet's external UUID
+    /// * `user_id` - The owner's user ID (for authorization)
+    /// * `health_type` - The type of the record (vaccines, deworming)
+    /// * `desc` - New description of the record
+    /// * `date` - New date of the record
+    ///
+    /// # Errors
+    /// Fails for weights (see `update_pet_weight`) or if the record is not
+    /// found for the pet and owner
+    async fn update_pet_health_record(
+        &self,
+        record_id: i64,
+        pet_external_id: Uuid,
+        user_id: i64,
+        health_type: models::pet::PetHealthType,
+        desc: String,
+        date: chrono::NaiveDate,
+    ) -> anyhow::Result<()>;
+
+    /// Adds several health records of the same type to a pet in a single transaction.
+    ///
+    /// # Arguments
+    /// * `pet_external_id` - The pet's external UUID
+    /// * `user_id` - The owner's user ID (for authorization)
+    /// * `health_type` - The type of the records (vaccines, deworming)
+    /// * `records` - Date and description of each record
+    ///
+    /// # Returns
+    /// * Number of inserted records, [`errors::RepoError::NotFound`] if the
+    ///   pet is not owned by the user
+    async fn insert_health_records_batch(
+        &self,
+        pet_external_id: Uuid,
+        user_id: i64,
+        health_type: models::pet::PetHealthType,
+        records: Vec<(chrono::NaiveDate, String)>,
+    ) -> anyhow::Result<u64>;
+
     /// Records a new weight measurement for a pet.
     ///
     /// # Arguments
@@ -329,6 +841,26 @@ pub trait AppRepo {
         date: chrono::NaiveDate,
     ) -> anyhow::Result<models::pet::PetWeight>;
 
+    /// Updates the value and date of a weight record.
+    ///
+    /// # Arguments
+    /// * `weight_id` - The unique identifier of the weight record to update
+    /// * `pet_external_id` - The pet's external UUID
+    /// * `user_id` - The owner's user ID (for authorization)
+    /// * `weight` - New weight measurement
+    /// * `date` - New date of the measurement
+    ///
+    /// # Errors
+    /// Fails if the record is not found for the pet and owner
+    async fn update_pet_weight(
+        &self,
+        weight_id: i64,
+        pet_external_id: Uuid,
+        user_id: i64,
+        weight: f64,
+        date: chrono::NaiveDate,
+    ) -> anyhow::Result<()>;
+
     /// Removes a weight record from a pet.
     ///
     /// # Arguments
@@ -342,6 +874,16 @@ pub trait AppRepo {
         weight_id: i64,
     ) -> anyhow::Result<()>;
 
+    /// Removes every weight record of a pet.
+    ///
+    /// # Arguments
+    /// * `pet_external_id` - The pet's external UUID
+    /// * `user_id` - The owner's user ID (for authorization)
+    ///
+    /// # Returns
+    /// * Number of deleted records
+    async fn delete_all_weights(&self, pet_external_id: Uuid, user_id: i64) -> anyhow::Result<u64>;
+
     // Owner Contacts Management
 
     /// Retrieves contact information for a pet's owner.
@@ -374,6 +916,9 @@ pub trait AppRepo {
     /// * `user_id` - The user's unique identifier
     /// * `desc` - Description of the contact (e.g., "Primary Vet", "Emergency Contact")
     /// * `contact` - The contact information (phone, email, address, etc.)
+    /// * `contact_type` - Type of the contact, used to build its link
+    /// * `address` - Optional home area of the contact
+    /// * `coordinates` - Optional latitude and longitude of the home area, already rounded
     ///
     /// # Returns
     /// * The newly created owner contact record
@@ -382,6 +927,9 @@ pub trait AppRepo {
         user_id: i64,
         desc: String,
         contact: String,
+        contact_type: models::user_app::ContactType,
+        address: Option<String>,
+        coordinates: Option<(f64, f64)>,
     ) -> anyhow::Result<models::user_app::OwnerContact>;
 
     /// Removes a contact entry from a user's contact list.
@@ -391,6 +939,167 @@ pub trait AppRepo {
     /// * `contact_id` - The unique identifier of the contact to delete
     async fn delete_owner_contact(&self, user_id: i64, contact_id: i64) -> anyhow::Result<()>;
 
+    /// Marks a contact as the user's primary contact, unsetting any other.
+    ///
+    /// # Arguments
+    /// * `user_id` - The unique identifier of the user who owns the contact
+    /// * `contact_id` - The contact to show first on the public profile
+    ///
+    /// # Errors
+    /// Fails when the contact doesn't belong to the user, leaving the previous primary
+    async fn set_primary_contact(&self, user_id: i64, contact_id: i64) -> anyhow::Result<()>;
+
+    /// Sets the position of the user's contacts.
+    ///
+    /// # Arguments
+    /// * `user_id` - The unique identifier of the user who owns the contacts
+    /// * `ordered_ids` - Contact ids in the order to show them, ids of other users are ignored
+    async fn reorder_contacts(&self, user_id: i64, ordered_ids: &[i64]) -> anyhow::Result<()>;
+
+    /// Retrieves the owner of the pet linked to an external id.
+    ///
+    /// # Arguments
+    /// * `pet_external_id` - The pet's external UUID
+    ///
+    /// # Returns
+    /// * `Some(User)` if the external id is linked to a pet, `None` otherwise
+    async fn get_pet_owner_by_external_id(
+        &self,
+        pet_external_id: Uuid,
+    ) -> anyhow::Result<Option<models::user_app::User>>;
+
+    // Found Reports Management
+
+    /// Stores a report sent by someone who found a pet.
+    ///
+    /// # Arguments
+    /// * `pet_external_id` - The pet's external UUID
+    /// * `message` - Message left by the finder
+    /// * `callback_contact` - Optional contact to reach the finder
+    /// * `created_at` - When the report was sent
+    ///
+    /// # Returns
+    /// * The ID of the newly created report
+    async fn insert_found_report(
+        &self,
+        pet_external_id: Uuid,
+        message: String,
+        callback_contact: Option<String>,
+        created_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<i64>;
+
+    /// Retrieves the messages left by people who found a pet, newest first.
+    ///
+    /// # Arguments
+    /// * `pet_id` - The pet's unique identifier
+    /// * `owner_id` - The owner's user ID (for authorization)
+    ///
+    /// # Returns
+    /// * Up to [PET_FOUND_REPORT_LIST_LIMIT](crate::consts::PET_FOUND_REPORT_LIST_LIMIT)
+    ///   reports, empty if the pet doesn't belong to the owner
+    async fn get_pet_found_reports(
+        &self,
+        pet_id: i64,
+        owner_id: i64,
+    ) -> anyhow::Result<Vec<models::pet::FoundReport>>;
+
+    // Public Profile Scans
+
+    /// Records a visit to the public profile of a linked pet.
+    ///
+    /// # Arguments
+    /// * `pet_external_id` - The pet's external UUID
+    /// * `viewer_id` - Logged user viewing the profile, the owner's views are not recorded
+    /// * `user_agent` - Optional user agent of the visitor
+    async fn insert_pet_scan(
+        &self,
+        pet_external_id: Uuid,
+        viewer_id: Option<i64>,
+        user_agent: Option<String>,
+    ) -> anyhow::Result<()>;
+
+    /// Counts the visits to the public profile of a pet.
+    ///
+    /// # Arguments
+    /// * `pet_external_id` - The pet's external UUID
+    /// * `owner_id` - The owner's user ID (for authorization)
+    ///
+    /// # Returns
+    /// * Total visits and the ones in the last [PET_SCAN_RECENT_DAYS](crate::consts::PET_SCAN_RECENT_DAYS) days
+    async fn get_scan_stats(
+        &self,
+        pet_external_id: Uuid,
+        owner_id: i64,
+    ) -> anyhow::Result<models::pet::PetScanStats>;
+
+    /// Deletes the visits to public profiles made before a date.
+    ///
+    /// # Arguments
+    /// * `before` - Visits older than this are deleted
+    ///
+    /// # Returns
+    /// * The number of deleted visits
+    async fn delete_pet_scans_before(
+        &self,
+        before: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<u64>;
+
+    // Public Profile Share Links
+
+    /// Stores a temporary share link of a pet's public profile.
+    ///
+    /// Only the owner can create share links.
+    ///
+    /// # Arguments
+    /// * `pet_id` - The unique identifier of the pet
+    /// * `user_id` - The owner's user ID (for authorization)
+    /// * `token` - Random token of the link
+    /// * `expires_at` - When the link stops working
+    async fn insert_pet_share_token(
+        &self,
+        pet_id: i64,
+        user_id: i64,
+        token: &str,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<()>;
+
+    /// Revokes a share link of a pet, an unknown token is ignored.
+    ///
+    /// # Arguments
+    /// * `pet_id` - The unique identifier of the pet
+    /// * `user_id` - The owner's user ID (for authorization)
+    /// * `token` - Token of the link to revoke
+    async fn delete_pet_share_token(
+        &self,
+        pet_id: i64,
+        user_id: i64,
+        token: &str,
+    ) -> anyhow::Result<()>;
+
+    /// Retrieves the share links of a pet that haven't expired.
+    ///
+    /// # Arguments
+    /// * `pet_id` - The unique identifier of the pet
+    /// * `user_id` - The owner's user ID (for authorization)
+    ///
+    /// # Returns
+    /// * Active share links, the ones expiring first first
+    async fn get_pet_share_tokens(
+        &self,
+        pet_id: i64,
+        user_id: i64,
+    ) -> anyhow::Result<Vec<models::pet::PetShareToken>>;
+
+    /// Resolves a share link to the current external ID of its pet.
+    ///
+    /// # Arguments
+    /// * `token` - Token of the link
+    ///
+    /// # Returns
+    /// * The pet's external UUID, `None` if the token is unknown, revoked or expired
+    async fn get_pet_external_id_by_share_token(&self, token: &str)
+    -> anyhow::Result<Option<Uuid>>;
+
     // Pet Notes Management
 
     /// Creates a new note for a pet.
@@ -407,20 +1116,84 @@ pub trait AppRepo {
         note: &models::pet::PetNote,
     ) -> anyhow::Result<i64>;
 
-    /// Retrieves all notes for a specific pet.
+    /// Retrieves all notes for a specific pet, used by the PDF report and exports.
     ///
     /// # Arguments
     /// * `user_id` - The owner's user ID (for authorization)
     /// * `pet_id` - The pet's unique identifier
     ///
     /// # Returns
-    /// * Vector of all notes associated with the pet
+    /// * Vector of all notes associated with the pet, newest first
+    async fn get_all_pet_notes(
+        &self,
+        user_id: i64,
+        pet_id: i64,
+    ) -> anyhow::Result<Vec<models::pet::PetNote>>;
+
+    /// Retrieves a page of the notes of a pet.
+    ///
+    /// # Arguments
+    /// * `user_id` - The owner's user ID (for authorization)
+    /// * `pet_id` - The pet's unique identifier
+    /// * `limit` - Max number of notes to return
+    /// * `offset` - Number of notes to skip
+    ///
+    /// # Returns
+    /// * Vector of notes associated with the pet, newest first
     async fn get_pet_notes(
         &self,
         user_id: i64,
         pet_id: i64,
+        limit: i64,
+        offset: i64,
+    ) -> anyhow::Result<Vec<models::pet::PetNote>>;
+
+    /// Counts the notes of a pet.
+    ///
+    /// # Arguments
+    /// * `user_id` - The owner's user ID (for authorization)
+    /// * `pet_id` - The pet's unique identifier
+    ///
+    /// # Returns
+    /// * Number of notes associated with the pet
+    async fn get_pet_notes_count(&self, user_id: i64, pet_id: i64) -> anyhow::Result<i64>;
+
+    /// Searches the notes of a pet whose title or content contains the query.
+    ///
+    /// The match is case-insensitive (ASCII letters, like SQLite `LIKE`).
+    ///
+    /// # Arguments
+    /// * `user_id` - The owner's user ID (for authorization)
+    /// * `pet_id` - The pet's unique identifier
+    /// * `query` - Text to search for, matched literally
+    ///
+    /// # Returns
+    /// * Vector of matching notes, newest first
+    async fn search_pet_notes(
+        &self,
+        user_id: i64,
+        pet_id: i64,
+        query: String,
     ) -> anyhow::Result<Vec<models::pet::PetNote>>;
 
+    /// Updates the title and content of a pet note.
+    ///
+    /// # Arguments
+    /// * `user_id` - The owner's user ID (for authorization)
+    /// * `note_id` - The unique identifier of the note to update
+    /// * `title` - New title of the note
+    /// * `content` - New content of the note
+    ///
+    /// # Errors
+    /// Returns an error if the note doesn't exist or its pet is not owned by the user
+    async fn update_pet_note(
+        &self,
+        user_id: i64,
+        note_id: i64,
+        title: String,
+        content: String,
+    ) -> anyhow::Result<()>;
+
     /// Removes a note from a pet.
     ///
     /// # Arguments
@@ -459,6 +1232,24 @@ pub trait AppRepo {
         user_id: i64,
     ) -> anyhow::Result<Vec<models::reminder::Reminder>>;
 
+    /// Retrieves the reminders of a user with their status.
+    ///
+    /// Upcoming reminders come first, soonest first, followed by the fired
+    /// ones, latest first.
+    ///
+    /// # Arguments
+    /// * `user_id` - The user's unique identifier
+    /// * `include_past` - Whether to list the reminders fired in the last
+    ///   [`REMINDER_HISTORY_DAYS`](crate::consts::REMINDER_HISTORY_DAYS)
+    ///
+    /// # Returns
+    /// * Vector of the user's reminders
+    async fn get_all_user_reminders(
+        &self,
+        user_id: i64,
+        include_past: bool,
+    ) -> anyhow::Result<Vec<models::reminder::ReminderListItem>>;
+
     /// Creates a new reminder for a user.
     ///
     /// # Arguments
@@ -471,12 +1262,161 @@ pub trait AppRepo {
         reminder: &models::reminder::Reminder,
     ) -> anyhow::Result<i64>;
 
+    /// Retrieves a reminder of a user.
+    ///
+    /// # Arguments
+    /// * `reminder_id` - The reminder's unique identifier
+    /// * `user_id` - The user's unique identifier (for authorization)
+    ///
+    /// # Returns
+    /// * The reminder if the user owns it, [`errors::RepoError::NotFound`] otherwise
+    async fn get_user_reminder(
+        &self,
+        reminder_id: i64,
+        user_id: i64,
+    ) -> Result<models::reminder::Reminder, errors::RepoError>;
+
+    /// Moves a reminder to a new time, keeping its id and body.
+    ///
+    /// # Arguments
+    /// * `reminder_id` - The reminder's unique identifier
+    /// * `user_id` - The user's unique identifier (for authorization)
+    /// * `send_at` - New instant the reminder is sent
+    /// * `user_timezone` - Timezone the new time was picked in
+    /// * `execution_id` - Execution scheduled for the new time
+    ///
+    /// # Errors
+    /// [`errors::RepoError::NotFound`] if the user doesn't own the reminder
+    async fn update_reminder_schedule(
+        &self,
+        reminder_id: i64,
+        user_id: i64,
+        send_at: chrono::DateTime<chrono::Utc>,
+        user_timezone: &str,
+        execution_id: &str,
+    ) -> anyhow::Result<()>;
+
     /// Removes a reminder from a user's reminder list.
     ///
     /// # Arguments
     /// * `reminder_id` - The unique identifier of the reminder to delete
     /// * `user_id` - The user's unique identifier (for authorization)
     async fn delete_user_reminder(&self, reminder_id: i64, user_id: i64) -> anyhow::Result<()>;
+
+    /// Removes every reminder linked to a pet.
+    ///
+    /// Reminders without a pet are kept.
+    ///
+    /// # Arguments
+    /// * `pet_id` - The pet's unique identifier
+    /// * `user_id` - The owner's user ID (for authorization)
+    ///
+    /// # Returns
+    /// * Execution IDs of the deleted reminders, to cancel their notifications
+    async fn delete_reminders_for_pet(
+        &self,
+        pet_id: i64,
+        user_id: i64,
+    ) -> anyhow::Result<Vec<String>>;
+
+    // Reminder Templates
+
+    /// Saves a reminder template of a user.
+    ///
+    /// # Arguments
+    /// * `user_id` - The user's unique identifier
+    /// * `name` - Name the template is picked by
+    /// * `body` - Template text, may have `{pet_name}` and `{date}` placeholders
+    ///
+    /// # Returns
+    /// * The newly created template's ID
+    async fn insert_reminder_template(
+        &self,
+        user_id: i64,
+        name: &str,
+        body: &str,
+    ) -> anyhow::Result<i64>;
+
+    /// Retrieves the reminder templates saved by a user, sorted by name.
+    ///
+    /// # Arguments
+    /// * `user_id` - The user's unique identifier
+    async fn get_reminder_templates(
+        &self,
+        user_id: i64,
+    ) -> anyhow::Result<Vec<models::reminder::ReminderTemplate>>;
+
+    /// Removes a reminder template of a user.
+    ///
+    /// # Arguments
+    /// * `template_id` - The template's unique identifier
+    /// * `user_id` - The user's unique identifier (for authorization)
+    async fn delete_reminder_template(&self, template_id: i64, user_id: i64) -> anyhow::Result<()>;
+
+    // Message Delivery
+
+    /// Saves the delivery status of a WhatsApp message.
+    ///
+    /// The status is only replaced by a more recent one.
+    ///
+    /// # Arguments
+    /// * `wamid` - The WhatsApp message id
+    /// * `status` - The reported delivery status
+    /// * `at` - When WhatsApp reported the status
+    async fn upsert_message_delivery_status(
+        &self,
+        wamid: &str,
+        status: models::reminder::DeliveryStatus,
+        at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<()>;
+
+    /// Links a WhatsApp message to the reminder it delivered.
+    ///
+    /// Statuses already received for the message are kept, otherwise it
+    /// starts as sent.
+    ///
+    /// # Arguments
+    /// * `wamid` - The WhatsApp message id
+    /// * `reminder_id` - The reminder's unique identifier
+    async fn link_message_delivery_to_reminder(
+        &self,
+        wamid: &str,
+        reminder_id: i64,
+    ) -> anyhow::Result<()>;
+
+    /// Retrieves the delivery status of the WhatsApp message of a reminder.
+    ///
+    /// # Arguments
+    /// * `reminder_id` - The reminder's unique identifier
+    /// * `user_id` - The user's unique identifier (for authorization)
+    ///
+    /// # Returns
+    /// * `Some(status)` if the reminder message is tracked, `None` otherwise
+    async fn get_reminder_delivery_status(
+        &self,
+        reminder_id: i64,
+        user_id: i64,
+    ) -> anyhow::Result<Option<models::reminder::DeliveryStatus>>;
+
+    // Usage Metrics
+
+    /// Counts every registered user.
+    async fn count_users(&self) -> anyhow::Result<i64>;
+
+    /// Counts users with an active subscription.
+    async fn count_subscribed_users(&self) -> anyhow::Result<i64>;
+
+    /// Counts every pet of every user.
+    async fn count_pets(&self) -> anyhow::Result<i64>;
+
+    /// Counts approved payments created from `since` on.
+    ///
+    /// # Arguments
+    /// * `since` - Start of the period to count
+    async fn count_approved_payments_since(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<i64>;
 }
 
 /// Type alias for a boxed implementation of the AppRepo trait.
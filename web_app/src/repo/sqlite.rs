@@ -1,11 +1,189 @@
-use crate::models;
+use crate::{consts, models};
 use async_trait::async_trait;
 use chrono::Utc;
 use serde_json::from_str;
 use sqlx::{FromRow, Row, SqlitePool, sqlite::SqliteRow};
 use uuid::Uuid;
 
-use super::{AppRepo, sqlite_queries};
+use super::{AppRepo, errors::RepoError, sqlite_queries};
+
+/// Max number of health records inserted per statement in a batch insert
+const HEALTH_RECORDS_BATCH_SIZE: usize = 500;
+
+/// Builds a `LIKE` pattern matching `text` anywhere, escaping its wildcards.
+fn contains_like_pattern(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{escaped}%")
+}
+
+/// Pet whose change goes to the audit log, by the id the caller has at hand
+enum AuditedPet {
+    Id(i64),
+    ExternalId(Uuid),
+    NoteId(i64),
+}
+
+/// Records a change made by `user_id` in the audit log of a pet. The change
+/// is already done, so a failure here is only logged.
+async fn record_pet_audit(
+    db_pool: &SqlitePool,
+    pet: AuditedPet,
+    user_id: i64,
+    action: models::pet::PetAuditAction,
+    field: &str,
+) {
+    let query = match pet {
+        AuditedPet::Id(pet_id) => sqlx::query(sqlite_queries::QUERY_INSERT_PET_AUDIT).bind(pet_id),
+        AuditedPet::ExternalId(external_id) => {
+            sqlx::query(sqlite_queries::QUERY_INSERT_PET_AUDIT_BY_EXTERNAL_ID)
+                .bind(external_id.to_string())
+        }
+        AuditedPet::NoteId(note_id) => {
+            sqlx::query(sqlite_queries::QUERY_INSERT_PET_AUDIT_BY_NOTE_ID).bind(note_id)
+        }
+    };
+
+    if let Err(e) = query
+        .bind(user_id)
+        .bind(action.to_string())
+        .bind(field)
+        .execute(db_pool)
+        .await
+    {
+        logfire::warn!(
+            "pet {action} of {field} couldn't be audited: {error}",
+            action = action.to_string(),
+            field = field.to_string(),
+            error = e.to_string()
+        );
+    }
+}
+
+/// Records the deletion of health records of a pet, if any was deleted.
+async fn record_health_delete(
+    db_pool: &SqlitePool,
+    deleted: u64,
+    pet_external_id: Uuid,
+    user_id: i64,
+    health_type: models::pet::PetHealthType,
+) {
+    if deleted > 0 {
+        record_pet_audit(
+            db_pool,
+            AuditedPet::ExternalId(pet_external_id),
+            user_id,
+            models::pet::PetAuditAction::Delete,
+            &health_type.to_string(),
+        )
+        .await;
+    }
+}
+
+/// Names of the profile fields that differ between `old` and `new`. The
+/// picture only counts when `new` brings one.
+fn changed_pet_fields(old: &models::pet::Pet, new: &models::pet::Pet) -> Vec<&'static str> {
+    [
+        ("pet_name", old.pet_name != new.pet_name),
+        ("birthday", old.birthday != new.birthday),
+        ("breed", old.breed != new.breed),
+        ("about", old.about != new.about),
+        ("is_female", old.is_female != new.is_female),
+        ("is_lost", old.is_lost != new.is_lost),
+        (
+            "is_spaying_neutering",
+            old.is_spaying_neutering != new.is_spaying_neutering,
+        ),
+        ("microchip", old.microchip != new.microchip),
+        ("pic", new.pic.is_some() && old.pic != new.pic),
+    ]
+    .into_iter()
+    .filter_map(|(field, changed)| changed.then_some(field))
+    .collect()
+}
+
+/// Takes one paid pet from the balance of a user in a single update, fails
+/// with [`RepoError::NotFound`] when it was already zero.
+async fn take_pet_balance<'e>(
+    executor: impl sqlx::Executor<'e, Database = sqlx::Sqlite>,
+    user_id: i64,
+) -> Result<u32, RepoError> {
+    sqlx::query_scalar::<_, u32>(
+        "UPDATE add_pet_balance SET balance=balance-1 WHERE user_id = $1 AND balance > 0 RETURNING balance;",
+    )
+    .bind(user_id)
+    .fetch_optional(executor)
+    .await?
+    .ok_or(RepoError::NotFound)
+}
+
+/// Inserts a pet in `transaction` and links it to its external id, creating
+/// the external id when it's new.
+///
+/// Fails with [`RepoError::Conflict`] when the external id was handed to
+/// another user.
+async fn insert_pet_with_external_id(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    pet: &models::pet::Pet,
+) -> anyhow::Result<i64> {
+    let id_external_id = if let Some((id, owner_user_id)) =
+        sqlx::query_as::<_, (i64, Option<i64>)>(
+            "SELECT peid.id, peid.owner_user_id FROM pet_external_id AS peid WHERE peid.external_id = $1;",
+        )
+        .bind(pet.external_id.to_string())
+        .fetch_optional(&mut **transaction)
+        .await?
+    {
+        if owner_user_id.is_some_and(|owner_user_id| owner_user_id != pet.user_app_id) {
+            return Err(RepoError::Conflict.into());
+        }
+
+        // a tag nobody got yet belongs to whoever links it
+        sqlx::query(
+            "UPDATE pet_external_id SET owner_user_id = $2 WHERE id = $1 AND owner_user_id IS NULL;",
+        )
+        .bind(id)
+        .bind(pet.user_app_id)
+        .execute(&mut **transaction)
+        .await?;
+        id
+    } else {
+        sqlx::query(sqlite_queries::QUERY_INSERT_PET_EXTERNAL_ID)
+            .bind(pet.external_id.to_string())
+            .bind(chrono::Utc::now())
+            .bind(pet.user_app_id)
+            .execute(&mut **transaction)
+            .await?
+            .last_insert_rowid()
+    };
+
+    let pet_id = sqlx::query(sqlite_queries::QUERY_INSERT_PET)
+        .bind(pet.user_app_id)
+        .bind(&pet.pet_name)
+        .bind(pet.birthday)
+        .bind(&pet.breed)
+        .bind(&pet.about)
+        .bind(pet.is_female)
+        .bind(pet.is_lost)
+        .bind(pet.is_spaying_neutering)
+        .bind(&pet.pic)
+        .bind(pet.created_at)
+        .bind(pet.updated_at)
+        .bind(&pet.microchip)
+        .execute(&mut **transaction)
+        .await?
+        .last_insert_rowid();
+
+    sqlx::query(sqlite_queries::QUERY_LINK_PET_WITH_EXTERNAL_ID)
+        .bind(pet_id)
+        .bind(id_external_id)
+        .execute(&mut **transaction)
+        .await?;
+
+    Ok(pet_id)
+}
 
 #[derive(Clone)]
 pub struct SqlxSqliteRepo {
@@ -27,6 +205,7 @@ impl FromRow<'_, SqliteRow> for models::pet::Pet {
             is_female: row.try_get("is_female")?,
             is_lost: row.try_get("is_lost")?,
             is_spaying_neutering: row.try_get("is_spaying_neutering")?,
+            microchip: row.try_get("microchip")?,
             last_weight: row.try_get("last_weight")?,
             pic: row.try_get("pic")?,
             created_at: row.try_get("created_at")?,
@@ -35,6 +214,69 @@ impl FromRow<'_, SqliteRow> for models::pet::Pet {
     }
 }
 
+impl FromRow<'_, SqliteRow> for models::pet::PetWithCounts {
+    fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            pet: models::pet::Pet::from_row(row)?,
+            vaccine_count: row.try_get("vaccine_count")?,
+            weight_count: row.try_get("weight_count")?,
+            last_vaccine_date: row.try_get("last_vaccine_date")?,
+        })
+    }
+}
+
+impl FromRow<'_, SqliteRow> for models::reminder::Reminder {
+    fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
+        let notification_type =
+            from_str(row.try_get::<&str, &str>("notification_type")?).map_err(|e| {
+                sqlx::Error::ColumnDecode {
+                    index: "notification_type".to_string(),
+                    source: Box::new(e),
+                }
+            })?;
+
+        Ok(Self {
+            id: row.try_get("id")?,
+            user_app_id: row.try_get("user_app_id")?,
+            body: row.try_get("body")?,
+            execution_id: row.try_get("execution_id")?,
+            notification_type,
+            send_at: row.try_get("send_at")?,
+            user_timezone: row.try_get("user_timezone")?,
+            pet_id: row.try_get("pet_id")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+impl FromRow<'_, SqliteRow> for models::reminder::VaccineDigestRecipient {
+    fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
+        let channels = row
+            .try_get::<Option<&str>, &str>("notification_type")?
+            .map(from_str)
+            .transpose()
+            .map_err(|e| sqlx::Error::ColumnDecode {
+                index: "notification_type".to_string(),
+                source: Box::new(e),
+            })?;
+
+        Ok(Self {
+            user: models::user_app::User::from_row(row)?,
+            channels,
+            user_timezone: row.try_get("user_timezone")?,
+        })
+    }
+}
+
+impl FromRow<'_, SqliteRow> for models::reminder::ReminderListItem {
+    fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            reminder: models::reminder::Reminder::from_row(row)?,
+            status: row.try_get("status")?,
+        })
+    }
+}
+
 impl FromRow<'_, SqliteRow> for models::payment::Payment {
     fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
         Ok(Self {
@@ -47,6 +289,7 @@ impl FromRow<'_, SqliteRow> for models::payment::Payment {
             )?,
             payment_idempotency_h: row.try_get("payment_idempotency_h")?,
             transaction_amount: row.try_get("transaction_amount")?,
+            currency: row.try_get("currency")?,
             installments: row.try_get("installments")?,
             payment_method_id: row.try_get("payment_method_id")?,
             issuer_id: row.try_get("issuer_id")?,
@@ -80,6 +323,7 @@ impl FromRow<'_, SqliteRow> for models::user_app::User {
             account_role,
             is_subscribed: row.try_get("is_subscribed")?,
             is_enabled: row.try_get("is_enabled")?,
+            weight_unit: row.try_get("weight_unit")?,
             created_at: row.try_get("created_at")?,
             updated_at: row.try_get("updated_at")?,
         })
@@ -155,6 +399,22 @@ impl AppRepo for SqlxSqliteRepo {
         )
     }
 
+    async fn set_user_weight_unit(
+        &self,
+        user_app_id: i64,
+        weight_unit: models::user_app::WeightUnit,
+    ) -> anyhow::Result<()> {
+        Ok(
+            sqlx::query("UPDATE user_app SET weight_unit=$1, updated_at=$2 WHERE id=$3;")
+                .bind(weight_unit)
+                .bind(Utc::now())
+                .bind(user_app_id)
+                .execute(&self.db_pool)
+                .await
+                .map(|_| ())?,
+        )
+    }
+
     async fn insert_user_app(&self, app_user: &models::user_app::User) -> anyhow::Result<i64> {
         let mut transaction = self.db_pool.begin().await?;
 
@@ -190,6 +450,10 @@ impl AppRepo for SqlxSqliteRepo {
         )
     }
 
+    async fn decrement_pet_balance(&self, user_id: i64) -> anyhow::Result<u32> {
+        Ok(take_pet_balance(&self.db_pool, user_id).await?)
+    }
+
     async fn get_pet_balance(&self, user_id: i64) -> anyhow::Result<u32> {
         Ok(
             sqlx::query_scalar("SELECT balance FROM add_pet_balance WHERE user_id = $1;")
@@ -234,6 +498,7 @@ impl AppRepo for SqlxSqliteRepo {
             .bind(payment.mp_paym_id.to_string())
             .bind(&payment.payment_idempotency_h)
             .bind(&payment.transaction_amount)
+            .bind(&payment.currency)
             .bind(payment.installments)
             .bind(&payment.payment_method_id)
             .bind(&payment.issuer_id)
@@ -259,52 +524,52 @@ impl AppRepo for SqlxSqliteRepo {
     async fn save_pet(&self, pet: &models::pet::Pet) -> anyhow::Result<i64> {
         let mut transaction = self.db_pool.begin().await?;
 
-        let id_external_id = if let Some(id) = sqlx::query_scalar::<_, i64>(
-            "SELECT peid.id FROM pet_external_id AS peid WHERE peid.external_id = $1;",
-        )
-        .bind(pet.external_id.to_string())
-        .fetch_optional(&mut *transaction)
-        .await?
-        {
-            id
-        } else {
-            sqlx::query(sqlite_queries::QUERY_INSERT_PET_EXTERNAL_ID)
-                .bind(pet.external_id.to_string())
-                .bind(chrono::Utc::now())
-                .execute(&mut *transaction)
-                .await?
-                .last_insert_rowid()
-        };
+        let pet_id = insert_pet_with_external_id(&mut transaction, pet).await?;
 
-        let pet_id = sqlx::query(sqlite_queries::QUERY_INSERT_PET)
-            .bind(pet.user_app_id)
-            .bind(&pet.pet_name)
-            .bind(pet.birthday)
-            .bind(&pet.breed)
-            .bind(&pet.about)
-            .bind(pet.is_female)
-            .bind(pet.is_lost)
-            .bind(pet.is_spaying_neutering)
-            .bind(&pet.pic)
-            .bind(pet.created_at)
-            .bind(pet.updated_at)
-            .execute(&mut *transaction)
-            .await?
-            .last_insert_rowid();
+        transaction.commit().await?;
 
-        sqlx::query(sqlite_queries::QUERY_LINK_PET_WITH_EXTERNAL_ID)
-            .bind(pet_id)
-            .bind(id_external_id)
-            .execute(&mut *transaction)
-            .await?;
+        return Ok(pet_id);
+    }
+
+    async fn create_pet_and_charge(
+        &self,
+        pet: &models::pet::Pet,
+        user_id: i64,
+        charge_balance: bool,
+    ) -> anyhow::Result<i64> {
+        let mut transaction = self.db_pool.begin().await?;
+
+        // dropping the transaction on any early return rolls it back
+        if charge_balance {
+            take_pet_balance(&mut *transaction, user_id).await?;
+        }
+
+        let pet_id = insert_pet_with_external_id(&mut transaction, pet).await?;
+
+        sqlx::query(
+            "UPDATE user_app SET is_subscribed=1, updated_at=$1 WHERE id = $2 AND is_subscribed=0;",
+        )
+        .bind(Utc::now())
+        .bind(user_id)
+        .execute(&mut *transaction)
+        .await?;
 
         transaction.commit().await?;
 
-        return Ok(pet_id);
+        Ok(pet_id)
     }
 
     async fn update_pet(&self, pet: &models::pet::Pet) -> anyhow::Result<i64> {
-        sqlx::query(sqlite_queries::QUERY_UPDATE_PET)
+        // Only used to audit the changed fields, it can't make the update fail
+        let old_pet = sqlx::query_as::<_, models::pet::Pet>(sqlite_queries::QUERY_GET_PET_BY_ID)
+            .bind(pet.id)
+            .bind(pet.user_app_id)
+            .fetch_optional(&self.db_pool)
+            .await
+            .ok()
+            .flatten();
+
+        let updated = sqlx::query(sqlite_queries::QUERY_UPDATE_PET)
             .bind(pet.id)
             .bind(pet.user_app_id)
             .bind(&pet.pet_name)
@@ -314,20 +579,33 @@ impl AppRepo for SqlxSqliteRepo {
             .bind(pet.is_female)
             .bind(pet.is_lost)
             .bind(pet.is_spaying_neutering)
+            .bind(&pet.microchip)
             .bind(Utc::now())
             .execute(&self.db_pool)
-            .await?;
+            .await?
+            .rows_affected();
 
         if let Some(pic_path) = &pet.pic {
-            sqlx::query(
-                "UPDATE pet SET pic=$3, updated_at = $4 WHERE id = $1 AND user_app_id = $2;",
-            )
-            .bind(pet.id)
-            .bind(pet.user_app_id)
-            .bind(pic_path)
-            .bind(Utc::now())
-            .execute(&self.db_pool)
-            .await?;
+            sqlx::query(sqlite_queries::QUERY_SET_PET_PIC)
+                .bind(pet.id)
+                .bind(pet.user_app_id)
+                .bind(pic_path)
+                .bind(Utc::now())
+                .execute(&self.db_pool)
+                .await?;
+        }
+
+        if let Some(old_pet) = old_pet.filter(|_| updated > 0) {
+            for field in changed_pet_fields(&old_pet, pet) {
+                record_pet_audit(
+                    &self.db_pool,
+                    AuditedPet::Id(pet.id),
+                    pet.user_app_id,
+                    models::pet::PetAuditAction::Update,
+                    field,
+                )
+                .await;
+            }
         }
 
         Ok(pet.id)
@@ -342,388 +620,3931 @@ impl AppRepo for SqlxSqliteRepo {
             .map(|_| ())?)
     }
 
-    async fn get_all_pets_user_id(&self, user_id: i64) -> anyhow::Result<Vec<models::pet::Pet>> {
-        Ok(
-            sqlx::query_as::<_, models::pet::Pet>(sqlite_queries::QUERY_GET_ALL_PETS_USER_ID)
-                .bind(user_id)
-                .fetch_all(&self.db_pool)
-                .await?,
-        )
-    }
+    async fn rotate_pet_external_id(&self, pet_id: i64, user_id: i64) -> anyhow::Result<Uuid> {
+        let mut transaction = self.db_pool.begin().await?;
 
-    async fn get_pet_by_external_id(
-        &self,
-        pet_external_id: Uuid,
-    ) -> anyhow::Result<models::pet::Pet> {
-        Ok(
-            sqlx::query_as::<_, models::pet::Pet>(sqlite_queries::QUERY_GET_PET_BY_EXTERNAL_ID)
-                .bind(pet_external_id.to_string())
-                .fetch_one(&self.db_pool)
-                .await?,
+        sqlx::query_scalar::<_, i64>(
+            "SELECT p.id FROM pet AS p WHERE p.id = $1 AND p.user_app_id = $2;",
         )
+        .bind(pet_id)
+        .bind(user_id)
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        sqlx::query(sqlite_queries::QUERY_DELETE_PET_EXTERNAL_IDS_BY_PET_ID)
+            .bind(pet_id)
+            .execute(&mut *transaction)
+            .await?;
+
+        let new_external_id = Uuid::new_v4();
+        let id_external_id = sqlx::query(sqlite_queries::QUERY_INSERT_PET_EXTERNAL_ID)
+            .bind(new_external_id.to_string())
+            .bind(chrono::Utc::now())
+            .bind(user_id)
+            .execute(&mut *transaction)
+            .await?
+            .last_insert_rowid();
+
+        sqlx::query(sqlite_queries::QUERY_LINK_PET_WITH_EXTERNAL_ID)
+            .bind(pet_id)
+            .bind(id_external_id)
+            .execute(&mut *transaction)
+            .await?;
+
+        transaction.commit().await?;
+
+        Ok(new_external_id)
     }
 
-    async fn get_pet_pic_path_by_external_id(
+    async fn merge_pets(
         &self,
-        pet_external_id: Uuid,
+        primary_id: i64,
+        duplicate_id: i64,
+        user_id: i64,
     ) -> anyhow::Result<Option<String>> {
-        Ok(
-            sqlx::query_scalar::<_, String>(
-                sqlite_queries::QUERY_GET_PET_PUBLIC_PIC_BY_EXTERNAL_ID,
-            )
-            .bind(pet_external_id.to_string())
-            .fetch_optional(&self.db_pool)
-            .await?,
-        )
+        let mut transaction = self.db_pool.begin().await?;
+
+        // dropping the transaction on any early return rolls it back
+        let owned_pets = sqlx::query_scalar::<_, i64>(sqlite_queries::QUERY_COUNT_USER_PETS_BY_IDS)
+            .bind(primary_id)
+            .bind(duplicate_id)
+            .bind(user_id)
+            .fetch_one(&mut *transaction)
+            .await?;
+        if primary_id == duplicate_id || owned_pets != 2 {
+            return Err(RepoError::NotFound.into());
+        }
+
+        let pic_not_moved =
+            sqlx::query_scalar::<_, String>(sqlite_queries::QUERY_GET_PET_PIC_NOT_MOVED)
+                .bind(primary_id)
+                .bind(duplicate_id)
+                .fetch_optional(&mut *transaction)
+                .await?;
+
+        for query in sqlite_queries::QUERIES_MOVE_PET_RECORDS.iter().chain([
+            &sqlite_queries::QUERY_MOVE_PET_LINK_IF_UNLINKED,
+            &sqlite_queries::QUERY_MOVE_PET_PIC_IF_MISSING,
+        ]) {
+            sqlx::query(query)
+                .bind(primary_id)
+                .bind(duplicate_id)
+                .execute(&mut *transaction)
+                .await?;
+        }
+
+        // the tag the primary pet couldn't take is removed, as when it's
+        // rotated, so it can't be claimed by whoever scans it
+        sqlx::query(sqlite_queries::QUERY_DELETE_PET_EXTERNAL_IDS_BY_PET_ID)
+            .bind(duplicate_id)
+            .execute(&mut *transaction)
+            .await?;
+
+        sqlx::query(sqlite_queries::QUERY_DELETE_PET)
+            .bind(duplicate_id)
+            .bind(user_id)
+            .execute(&mut *transaction)
+            .await?;
+
+        transaction.commit().await?;
+
+        Ok(pic_not_moved)
     }
 
-    async fn get_pet_by_id(&self, pet_id: i64, user_id: i64) -> anyhow::Result<models::pet::Pet> {
+    async fn count_user_pets(&self, user_id: i64) -> anyhow::Result<i64> {
         Ok(
-            sqlx::query_as::<_, models::pet::Pet>(sqlite_queries::QUERY_GET_PET_BY_ID)
-                .bind(pet_id)
+            sqlx::query_scalar::<_, i64>(sqlite_queries::QUERY_COUNT_USER_PETS)
                 .bind(user_id)
                 .fetch_one(&self.db_pool)
                 .await?,
         )
     }
 
-    async fn get_pet_weights(
+    async fn set_pet_lost_status(
         &self,
-        pet_external_id: Uuid,
-        user_id: Option<i64>,
-    ) -> anyhow::Result<Vec<models::pet::PetWeight>> {
-        let pet_external_id = pet_external_id.to_string();
-        let query = if let Some(user_id) = user_id {
-            sqlx::query_as::<_, models::pet::PetWeight>(
-                sqlite_queries::QUERY_GET_PET_WEIGHTS_BY_EXTERNAL_AND_USER_ID,
-            )
-            .bind(pet_external_id)
+        pet_id: i64,
+        user_id: i64,
+        is_lost: bool,
+    ) -> anyhow::Result<()> {
+        let updated = sqlx::query(sqlite_queries::QUERY_SET_PET_LOST_STATUS)
+            .bind(pet_id)
             .bind(user_id)
-        } else {
-            sqlx::query_as::<_, models::pet::PetWeight>(
-                sqlite_queries::QUERY_GET_PET_WEIGHTS_BY_EXTERNAL_ID,
-            )
-            .bind(pet_external_id)
-        };
+            .bind(is_lost)
+            .bind(Utc::now())
+            .execute(&self.db_pool)
+            .await?
+            .rows_affected();
 
-        Ok(query.fetch_all(&self.db_pool).await?)
+        if updated == 0 {
+            anyhow::bail!("pet {pet_id} not found for user {user_id}");
+        }
+
+        Ok(())
     }
 
-    async fn get_pet_health_records(
+    async fn set_pet_public_pin(
         &self,
-        pet_external_id: Uuid,
-        user_id: Option<i64>,
-        health_type: models::pet::PetHealthType,
-    ) -> anyhow::Result<Vec<models::pet::PetHealth>> {
-        let pet_external_id = pet_external_id.to_string();
-        let query = if let Some(user_id) = user_id {
-            sqlx::query(sqlite_queries::QUERY_GET_PET_HEALTH_RECORD)
-                .bind(pet_external_id)
-                .bind(user_id)
-                .bind(health_type.to_string())
-        } else {
-            sqlx::query(sqlite_queries::QUERY_GET_PET_PUBLIC_HEALTH_RECORD)
-                .bind(pet_external_id)
-                .bind(health_type.to_string())
-        };
-
-        return Ok(query
-            .try_map(
-                |row: sqlx::sqlite::SqliteRow| -> sqlx::Result<models::pet::PetHealth> {
-                    Ok(models::pet::PetHealth {
-                        id: row.try_get("id")?,
-                        pet_id: row.try_get("pet_id")?,
-                        health_record: health_type.clone(),
-                        description: row.try_get("description")?,
-                        created_at: row.try_get("created_at")?,
-                    })
-                },
-            )
-            .fetch_all(&self.db_pool)
-            .await?);
-    }
-
-    async fn insert_vaccine_to(
-        &self,
-        pet_external_id: Uuid,
+        pet_id: i64,
         user_id: i64,
-        desc: String,
-        date: chrono::NaiveDate,
-    ) -> anyhow::Result<models::pet::PetHealth> {
-        let date = date.and_time(chrono::NaiveTime::default());
-        let health_record = models::pet::PetHealthType::Vaccine;
-
-        let record = sqlx::query(sqlite_queries::QUERY_INSERT_PET_HEALTH_RECORD)
-            .bind(pet_external_id.to_string())
+        pin_hash: Option<String>,
+    ) -> anyhow::Result<()> {
+        let updated = sqlx::query(sqlite_queries::QUERY_SET_PET_PUBLIC_PIN)
+            .bind(pet_id)
             .bind(user_id)
-            .bind(health_record.to_string())
-            .bind(&desc)
-            .bind(date)
-            .try_map(
-                |row: sqlx::sqlite::SqliteRow| -> sqlx::Result<models::pet::PetHealth> {
-                    Ok(models::pet::PetHealth {
-                        id: row.try_get("id")?,
-                        pet_id: row.try_get("pet_id")?,
-                        health_record: health_record.clone(),
-                        description: desc.to_string(),
-                        created_at: date,
-                    })
-                },
-            )
-            .fetch_one(&self.db_pool)
-            .await?;
-
-        return Ok(record);
-    }
+            .bind(pin_hash)
+            .bind(Utc::now())
+            .execute(&self.db_pool)
+            .await?
+            .rows_affected();
 
-    async fn insert_deworm_to(
-        &self,
-        pet_external_id: Uuid,
-        user_id: i64,
-        desc: String,
-        date: chrono::NaiveDate,
-    ) -> anyhow::Result<models::pet::PetHealth> {
-        let health_record = models::pet::PetHealthType::Deworm;
-        let date = date.and_time(chrono::NaiveTime::default());
+        if updated == 0 {
+            anyhow::bail!("pet {pet_id} not found for user {user_id}");
+        }
 
-        let record = sqlx::query(sqlite_queries::QUERY_INSERT_PET_HEALTH_RECORD)
-            .bind(pet_external_id.to_string())
-            .bind(user_id)
-            .bind(health_record.to_string())
-            .bind(&desc)
-            .bind(date)
-            .try_map(
-                |row: sqlx::sqlite::SqliteRow| -> sqlx::Result<models::pet::PetHealth> {
-                    Ok(models::pet::PetHealth {
-                        id: row.try_get("id")?,
-                        pet_id: row.try_get("pet_id")?,
-                        health_record: health_record.clone(),
-                        description: desc.to_string(),
-                        created_at: date,
-                    })
-                },
-            )
-            .fetch_one(&self.db_pool)
-            .await?;
+        Ok(())
+    }
 
-        return Ok(record);
+    async fn get_pet_public_pin(&self, pet_external_id: Uuid) -> anyhow::Result<Option<String>> {
+        Ok(
+            sqlx::query_scalar::<_, Option<String>>(sqlite_queries::QUERY_GET_PET_PUBLIC_PIN)
+                .bind(pet_external_id.to_string())
+                .fetch_optional(&self.db_pool)
+                .await?
+                .flatten(),
+        )
     }
 
-    async fn insert_pet_weight(
+    async fn get_pet_pin_locked_until(
         &self,
         pet_external_id: Uuid,
-        user_id: i64,
-        weight: f64,
-        date: chrono::NaiveDate,
-    ) -> anyhow::Result<models::pet::PetWeight> {
-        let date = date.and_time(chrono::NaiveTime::default());
-
-        Ok(
-            sqlx::query_as::<_, models::pet::PetWeight>(sqlite_queries::QUERY_INSERT_PET_WEIGHT)
-                .bind(pet_external_id.to_string())
-                .bind(user_id)
-                .bind(weight)
-                .bind(date)
-                .fetch_one(&self.db_pool)
-                .await?,
+    ) -> anyhow::Result<Option<chrono::DateTime<Utc>>> {
+        Ok(sqlx::query_scalar::<_, Option<chrono::DateTime<Utc>>>(
+            sqlite_queries::QUERY_GET_PET_PIN_LOCKED_UNTIL,
         )
+        .bind(pet_external_id.to_string())
+        .fetch_optional(&self.db_pool)
+        .await?
+        .flatten())
     }
 
-    async fn delete_pet_weight(
+    async fn record_failed_pet_pin_attempt(
         &self,
         pet_external_id: Uuid,
-        user_id: i64,
-        weight_id: i64,
+        max_attempts: i64,
+        locked_until: chrono::DateTime<Utc>,
     ) -> anyhow::Result<()> {
-        sqlx::query(sqlite_queries::QUERY_DELETE_PET_WEIGHT)
-            .bind(weight_id)
+        sqlx::query(sqlite_queries::QUERY_RECORD_FAILED_PET_PIN_ATTEMPT)
             .bind(pet_external_id.to_string())
-            .bind(user_id)
+            .bind(max_attempts)
+            .bind(locked_until)
             .execute(&self.db_pool)
             .await?;
+
         Ok(())
     }
 
-    async fn delete_deworm(
-        &self,
-        pet_external_id: Uuid,
-        user_id: i64,
-        deworm_id: i64,
-    ) -> anyhow::Result<()> {
-        sqlx::query(sqlite_queries::QUERY_DELETE_PET_HEALTH_RECORD)
-            .bind(deworm_id)
-            .bind(models::pet::PetHealthType::Deworm.to_string())
+    async fn clear_failed_pet_pin_attempts(&self, pet_external_id: Uuid) -> anyhow::Result<()> {
+        sqlx::query(sqlite_queries::QUERY_CLEAR_FAILED_PET_PIN_ATTEMPTS)
             .bind(pet_external_id.to_string())
-            .bind(user_id)
             .execute(&self.db_pool)
             .await?;
+
         Ok(())
     }
 
-    async fn delete_vaccine(
+    async fn set_pet_spaying_neutering_status(
         &self,
-        pet_external_id: Uuid,
+        pet_id: i64,
         user_id: i64,
-        vaccine_id: i64,
+        is_spaying_neutering: bool,
     ) -> anyhow::Result<()> {
-        sqlx::query(sqlite_queries::QUERY_DELETE_PET_HEALTH_RECORD)
-            .bind(vaccine_id)
-            .bind(models::pet::PetHealthType::Vaccine.to_string())
-            .bind(pet_external_id.to_string())
+        let updated = sqlx::query(sqlite_queries::QUERY_SET_PET_SPAYING_NEUTERING_STATUS)
+            .bind(pet_id)
             .bind(user_id)
+            .bind(is_spaying_neutering)
+            .bind(Utc::now())
             .execute(&self.db_pool)
-            .await?;
-        Ok(())
-    }
+            .await?
+            .rows_affected();
 
-    async fn get_pet_owner_contacts(
-        &self,
-        pet_external_id: Uuid,
-    ) -> anyhow::Result<Vec<models::user_app::OwnerContact>> {
-        Ok(sqlx::query_as::<_, models::user_app::OwnerContact>(
-            sqlite_queries::QUERY_GET_PET_OWNER_CONTACTS,
-        )
-        .bind(pet_external_id.to_string())
-        .fetch_all(&self.db_pool)
-        .await?)
-    }
+        if updated == 0 {
+            anyhow::bail!("pet {pet_id} not found for user {user_id}");
+        }
 
-    async fn get_owner_contacts(
-        &self,
-        user_id: i64,
-    ) -> anyhow::Result<Vec<models::user_app::OwnerContact>> {
-        Ok(sqlx::query_as::<_, models::user_app::OwnerContact>(
-            sqlite_queries::QUERY_GET_OWNER_CONTACTS,
-        )
-        .bind(user_id)
-        .fetch_all(&self.db_pool)
-        .await?)
+        Ok(())
     }
 
-    async fn insert_owner_contact(
-        &self,
-        user_id: i64,
-        desc: String,
-        contact: String,
-    ) -> anyhow::Result<models::user_app::OwnerContact> {
+    async fn claim_pet_creation_token(&self, user_id: i64, token: Uuid) -> anyhow::Result<bool> {
         let now = Utc::now();
-        let id = sqlx::query(sqlite_queries::QUERY_INSERT_NEW_OWNER_CONTACT)
+
+        sqlx::query(sqlite_queries::QUERY_DELETE_EXPIRED_PET_CREATION_TOKENS)
+            .bind(now - chrono::Duration::hours(consts::PET_CREATION_TOKEN_TTL_HOURS))
+            .execute(&self.db_pool)
+            .await?;
+
+        let inserted = sqlx::query(sqlite_queries::QUERY_INSERT_PET_CREATION_TOKEN)
+            .bind(token.to_string())
             .bind(user_id)
-            .bind(&desc)
-            .bind(&contact)
             .bind(now)
             .execute(&self.db_pool)
             .await?
-            .last_insert_rowid();
-        Ok(models::user_app::OwnerContact {
-            id,
-            user_app_id: user_id,
-            full_name: desc,
-            contact_value: contact,
-            created_at: now,
-        })
+            .rows_affected();
+
+        Ok(inserted == 1)
     }
 
-    async fn delete_owner_contact(&self, user_id: i64, contact_id: i64) -> anyhow::Result<()> {
-        sqlx::query(sqlite_queries::QUERY_DELETE_OWNER_CONTACT)
-            .bind(contact_id)
+    async fn release_pet_creation_token(&self, user_id: i64, token: Uuid) -> anyhow::Result<()> {
+        sqlx::query(sqlite_queries::QUERY_DELETE_PET_CREATION_TOKEN)
+            .bind(token.to_string())
             .bind(user_id)
             .execute(&self.db_pool)
             .await?;
+
         Ok(())
     }
 
-    async fn insert_new_pet_note(
-        &self,
-        user_id: i64,
-        note: &models::pet::PetNote,
-    ) -> anyhow::Result<i64> {
-        Ok(sqlx::query(sqlite_queries::QUERY_INSERT_PET_NOTE)
-            .bind(note.pet_id)
+    async fn set_pet_pic(&self, pet_id: i64, user_id: i64, pic_path: &str) -> anyhow::Result<()> {
+        let updated = sqlx::query(sqlite_queries::QUERY_SET_PET_PIC)
+            .bind(pet_id)
             .bind(user_id)
-            .bind(&note.title)
-            .bind(&note.content)
-            .bind(note.created_at)
+            .bind(pic_path)
+            .bind(Utc::now())
             .execute(&self.db_pool)
             .await?
-            .last_insert_rowid())
+            .rows_affected();
+
+        if updated == 0 {
+            anyhow::bail!("pet {pet_id} not found for user {user_id}");
+        }
+
+        Ok(())
     }
 
-    async fn get_pet_notes(
+    async fn offer_pet_transfer(
         &self,
-        user_id: i64,
         pet_id: i64,
-    ) -> anyhow::Result<Vec<models::pet::PetNote>> {
-        Ok(
-            sqlx::query_as::<_, models::pet::PetNote>(sqlite_queries::QUERY_GET_PET_NOTES)
-                .bind(pet_id)
-                .bind(user_id)
-                .fetch_all(&self.db_pool)
-                .await?,
+        from_user_id: i64,
+        to_user_email: &str,
+    ) -> Result<(), RepoError> {
+        let offered = sqlx::query(sqlite_queries::QUERY_OFFER_PET_TRANSFER)
+            .bind(pet_id)
+            .bind(from_user_id)
+            .bind(to_user_email)
+            .bind(Utc::now())
+            .execute(&self.db_pool)
+            .await?
+            .rows_affected();
+
+        if offered == 0 {
+            return Err(RepoError::NotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn get_incoming_pet_transfers(
+        &self,
+        to_user_id: i64,
+    ) -> anyhow::Result<Vec<models::pet::PetTransferOffer>> {
+        Ok(sqlx::query_as::<_, models::pet::PetTransferOffer>(
+            sqlite_queries::QUERY_GET_INCOMING_PET_TRANSFERS,
         )
+        .bind(to_user_id)
+        .fetch_all(&self.db_pool)
+        .await?)
     }
 
-    async fn delete_pet_note(&self, pet_id: i64, user_id: i64, note_id: i64) -> anyhow::Result<()> {
-        sqlx::query(sqlite_queries::QUERY_DELETE_PET_NOTE)
-            .bind(note_id)
+    async fn accept_pet_transfer(&self, pet_id: i64, to_user_id: i64) -> Result<(), RepoError> {
+        let mut transaction = self.db_pool.begin().await?;
+
+        let from_user_id = sqlx::query_scalar::<_, i64>(sqlite_queries::QUERY_TAKE_PET_TRANSFER)
+            .bind(pet_id)
+            .bind(to_user_id)
+            .fetch_optional(&mut *transaction)
+            .await?
+            .ok_or(RepoError::NotFound)?;
+
+        let transferred = sqlx::query(sqlite_queries::QUERY_TRANSFER_PET)
+            .bind(pet_id)
+            .bind(from_user_id)
+            .bind(to_user_id)
+            .bind(Utc::now())
+            .execute(&mut *transaction)
+            .await?
+            .rows_affected();
+        if transferred == 0 {
+            return Err(RepoError::NotFound);
+        }
+
+        // the tags of the pet go with it
+        sqlx::query(sqlite_queries::QUERY_TRANSFER_PET_EXTERNAL_IDS)
+            .bind(pet_id)
+            .bind(to_user_id)
+            .execute(&mut *transaction)
+            .await?;
+
+        // the new owner no longer needs to be a collaborator of the pet
+        sqlx::query(sqlite_queries::QUERY_DELETE_PET_ACCESS_OF_USER)
+            .bind(pet_id)
+            .bind(to_user_id)
+            .execute(&mut *transaction)
+            .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    async fn cancel_pet_transfer(&self, pet_id: i64, user_id: i64) -> anyhow::Result<()> {
+        sqlx::query(sqlite_queries::QUERY_CANCEL_PET_TRANSFER)
             .bind(pet_id)
             .bind(user_id)
             .execute(&self.db_pool)
             .await?;
+
         Ok(())
     }
 
-    async fn get_active_user_remiders(
-        &self,
-        user_id: i64,
-    ) -> anyhow::Result<Vec<models::reminder::Reminder>> {
+    async fn get_all_pets_user_id(&self, user_id: i64) -> anyhow::Result<Vec<models::pet::Pet>> {
         Ok(
-            sqlx::query_as(sqlite_queries::QUERY_GET_USER_ACTIVE_REMINDERS)
+            sqlx::query_as::<_, models::pet::Pet>(sqlite_queries::QUERY_GET_ALL_PETS_USER_ID)
                 .bind(user_id)
-                .bind(Utc::now())
                 .fetch_all(&self.db_pool)
                 .await?,
         )
     }
 
-    async fn get_reminder_execution_id(
+    async fn get_pets_with_counts(
         &self,
         user_id: i64,
-        reminder_id: i64,
-    ) -> anyhow::Result<Option<String>> {
-        Ok(sqlx::query_scalar::<_, String>(
-            "SELECT execution_id FROM reminder WHERE id=$1 AND user_app_id=$2 LIMIT 1;",
+    ) -> anyhow::Result<Vec<models::pet::PetWithCounts>> {
+        Ok(sqlx::query_as::<_, models::pet::PetWithCounts>(
+            sqlite_queries::QUERY_GET_PETS_WITH_COUNTS_USER_ID,
         )
-        .bind(reminder_id)
         .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?)
+    }
+
+    async fn get_pet_by_external_id(
+        &self,
+        pet_external_id: Uuid,
+    ) -> Result<models::pet::Pet, RepoError> {
+        Ok(
+            sqlx::query_as::<_, models::pet::Pet>(sqlite_queries::QUERY_GET_PET_BY_EXTERNAL_ID)
+                .bind(pet_external_id.to_string())
+                .fetch_one(&self.db_pool)
+                .await?,
+        )
+    }
+
+    async fn get_pets_by_external_ids(
+        &self,
+        pet_external_ids: &[Uuid],
+    ) -> anyhow::Result<Vec<models::pet::Pet>> {
+        if pet_external_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut query =
+            sqlx::QueryBuilder::<sqlx::Sqlite>::new(sqlite_queries::QUERY_GET_PETS_BY_EXTERNAL_IDS);
+        let mut ids = query.separated(",");
+        for pet_external_id in pet_external_ids {
+            ids.push_bind(pet_external_id.to_string());
+        }
+        query.push(");");
+
+        Ok(query
+            .build_query_as::<models::pet::Pet>()
+            .fetch_all(&self.db_pool)
+            .await?)
+    }
+
+    async fn assign_external_id_owner(
+        &self,
+        pet_external_id: Uuid,
+        user_id: i64,
+    ) -> anyhow::Result<bool> {
+        let mut transaction = self.db_pool.begin().await?;
+
+        let owner_user_id = sqlx::query_scalar::<_, Option<i64>>(
+            "SELECT peid.owner_user_id FROM pet_external_id AS peid WHERE peid.external_id = $1;",
+        )
+        .bind(pet_external_id.to_string())
+        .fetch_optional(&mut *transaction)
+        .await?;
+
+        let is_owner = match owner_user_id {
+            None => {
+                sqlx::query(sqlite_queries::QUERY_INSERT_PET_EXTERNAL_ID)
+                    .bind(pet_external_id.to_string())
+                    .bind(Utc::now())
+                    .bind(user_id)
+                    .execute(&mut *transaction)
+                    .await?;
+                true
+            }
+            Some(None) => {
+                sqlx::query(
+                    "UPDATE pet_external_id SET owner_user_id = $2 WHERE external_id = $1;",
+                )
+                .bind(pet_external_id.to_string())
+                .bind(user_id)
+                .execute(&mut *transaction)
+                .await?;
+                true
+            }
+            Some(Some(owner_user_id)) => owner_user_id == user_id,
+        };
+
+        transaction.commit().await?;
+
+        Ok(is_owner)
+    }
+
+    async fn get_user_unlinked_external_ids(&self, user_id: i64) -> anyhow::Result<Vec<Uuid>> {
+        Ok(sqlx::query_scalar::<_, uuid::fmt::Hyphenated>(
+            sqlite_queries::QUERY_GET_USER_UNLINKED_EXTERNAL_IDS,
+        )
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?
+        .into_iter()
+        .map(uuid::fmt::Hyphenated::into_uuid)
+        .collect())
+    }
+
+    async fn get_pet_by_external_id_for_user(
+        &self,
+        pet_external_id: Uuid,
+        user_id: i64,
+    ) -> Result<models::pet::Pet, RepoError> {
+        Ok(sqlx::query_as::<_, models::pet::Pet>(
+            sqlite_queries::QUERY_GET_PET_BY_EXTERNAL_AND_USER_ID,
+        )
+        .bind(pet_external_id.to_string())
+        .bind(user_id)
+        .fetch_one(&self.db_pool)
+        .await?)
+    }
+
+    async fn get_pet_pic_updated_at_by_external_id(
+        &self,
+        pet_external_id: Uuid,
+    ) -> anyhow::Result<Option<chrono::DateTime<Utc>>> {
+        Ok(sqlx::query_scalar::<_, chrono::DateTime<Utc>>(
+            sqlite_queries::QUERY_GET_PET_PUBLIC_PIC_UPDATED_AT_BY_EXTERNAL_ID,
+        )
+        .bind(pet_external_id.to_string())
         .fetch_optional(&self.db_pool)
         .await?)
     }
 
-    async fn insert_user_remider(
+    async fn get_pet_pic_path_by_external_id(
         &self,
-        reminder: &models::reminder::Reminder,
-    ) -> anyhow::Result<i64> {
-        Ok(sqlx::query(sqlite_queries::QUERY_INSERT_USER_REMINDER)
-            .bind(reminder.user_app_id)
-            .bind(reminder.body.to_string())
-            .bind(reminder.execution_id.to_string())
-            .bind(reminder.notification_type.to_string())
-            .bind(reminder.send_at)
-            .bind(reminder.user_timezone.to_string())
-            .bind(reminder.created_at)
+        pet_external_id: Uuid,
+    ) -> anyhow::Result<Option<String>> {
+        Ok(
+            sqlx::query_scalar::<_, String>(
+                sqlite_queries::QUERY_GET_PET_PUBLIC_PIC_BY_EXTERNAL_ID,
+            )
+            .bind(pet_external_id.to_string())
+            .fetch_optional(&self.db_pool)
+            .await?,
+        )
+    }
+
+    async fn get_pet_qr_caption(
+        &self,
+        pet_external_id: Uuid,
+    ) -> anyhow::Result<Option<models::pet::PetQrCaption>> {
+        Ok(sqlx::query_as::<_, models::pet::PetQrCaption>(
+            sqlite_queries::QUERY_GET_PET_QR_CAPTION_BY_EXTERNAL_ID,
+        )
+        .bind(pet_external_id.to_string())
+        .fetch_optional(&self.db_pool)
+        .await?)
+    }
+
+    async fn grant_pet_access(
+        &self,
+        pet_id: i64,
+        owner_id: i64,
+        target_email: &str,
+        role: models::pet::PetAccessRole,
+    ) -> Result<(), RepoError> {
+        let granted = sqlx::query(sqlite_queries::QUERY_GRANT_PET_ACCESS)
+            .bind(pet_id)
+            .bind(owner_id)
+            .bind(target_email)
+            .bind(role)
+            .bind(Utc::now())
             .execute(&self.db_pool)
             .await?
-            .last_insert_rowid())
+            .rows_affected();
+
+        if granted == 0 {
+            return Err(RepoError::NotFound);
+        }
+
+        Ok(())
     }
 
-    async fn delete_user_reminder(&self, reminder_id: i64, user_id: i64) -> anyhow::Result<()> {
-        sqlx::query("DELETE FROM reminder WHERE id=$1 AND user_app_id=$2")
-            .bind(reminder_id)
+    async fn list_pet_collaborators(
+        &self,
+        pet_id: i64,
+        owner_id: i64,
+    ) -> anyhow::Result<Vec<models::pet::PetCollaborator>> {
+        Ok(sqlx::query_as::<_, models::pet::PetCollaborator>(
+            sqlite_queries::QUERY_GET_PET_COLLABORATORS,
+        )
+        .bind(pet_id)
+        .bind(owner_id)
+        .fetch_all(&self.db_pool)
+        .await?)
+    }
+
+    async fn get_pet_audit(
+        &self,
+        pet_id: i64,
+        owner_id: i64,
+    ) -> anyhow::Result<Vec<models::pet::PetAuditEntry>> {
+        Ok(
+            sqlx::query_as::<_, models::pet::PetAuditEntry>(sqlite_queries::QUERY_GET_PET_AUDIT)
+                .bind(pet_id)
+                .bind(owner_id)
+                .bind(consts::PET_AUDIT_LIST_LIMIT)
+                .fetch_all(&self.db_pool)
+                .await?,
+        )
+    }
+
+    async fn get_pet_by_id(
+        &self,
+        pet_id: i64,
+        user_id: i64,
+    ) -> Result<models::pet::Pet, RepoError> {
+        Ok(
+            sqlx::query_as::<_, models::pet::Pet>(sqlite_queries::QUERY_GET_PET_BY_ID)
+                .bind(pet_id)
+                .bind(user_id)
+                .fetch_one(&self.db_pool)
+                .await?,
+        )
+    }
+
+    async fn get_pet_weights(
+        &self,
+        pet_external_id: Uuid,
+        user_id: Option<i64>,
+    ) -> anyhow::Result<Vec<models::pet::PetWeight>> {
+        let pet_external_id = pet_external_id.to_string();
+        let query = if let Some(user_id) = user_id {
+            sqlx::query_as::<_, models::pet::PetWeight>(
+                sqlite_queries::QUERY_GET_PET_WEIGHTS_BY_EXTERNAL_AND_USER_ID,
+            )
+            .bind(pet_external_id)
             .bind(user_id)
-            .execute(&self.db_pool)
-            .await?;
+        } else {
+            sqlx::query_as::<_, models::pet::PetWeight>(
+                sqlite_queries::QUERY_GET_PET_WEIGHTS_BY_EXTERNAL_ID,
+            )
+            .bind(pet_external_id)
+        };
+
+        Ok(query.fetch_all(&self.db_pool).await?)
+    }
+
+    async fn get_pet_health_records(
+        &self,
+        pet_external_id: Uuid,
+        user_id: Option<i64>,
+        health_type: models::pet::PetHealthType,
+    ) -> anyhow::Result<Vec<models::pet::PetHealth>> {
+        let pet_external_id = pet_external_id.to_string();
+        let query = if let Some(user_id) = user_id {
+            sqlx::query(sqlite_queries::QUERY_GET_PET_HEALTH_RECORD)
+                .bind(pet_external_id)
+                .bind(user_id)
+                .bind(health_type.to_string())
+        } else {
+            sqlx::query(sqlite_queries::QUERY_GET_PET_PUBLIC_HEALTH_RECORD)
+                .bind(pet_external_id)
+                .bind(health_type.to_string())
+        };
+
+        return Ok(query
+            .try_map(
+                |row: sqlx::sqlite::SqliteRow| -> sqlx::Result<models::pet::PetHealth> {
+                    Ok(models::pet::PetHealth {
+                        id: row.try_get("id")?,
+                        pet_id: row.try_get("pet_id")?,
+                        health_record: health_type.clone(),
+                        description: row.try_get("description")?,
+                        created_at: row.try_get("created_at")?,
+                    })
+                },
+            )
+            .fetch_all(&self.db_pool)
+            .await?);
+    }
+
+    async fn get_all_user_pets_vaccines(
+        &self,
+        user_id: i64,
+    ) -> anyhow::Result<Vec<models::pet::PetVaccineBooster>> {
+        Ok(
+            sqlx::query_as(sqlite_queries::QUERY_GET_USER_PETS_VACCINE_BOOSTERS)
+                .bind(user_id)
+                .fetch_all(&self.db_pool)
+                .await?,
+        )
+    }
+
+    async fn get_vaccine_digest_recipients(
+        &self,
+    ) -> anyhow::Result<Vec<models::reminder::VaccineDigestRecipient>> {
+        Ok(
+            sqlx::query_as(sqlite_queries::QUERY_GET_VACCINE_DIGEST_RECIPIENTS)
+                .fetch_all(&self.db_pool)
+                .await?,
+        )
+    }
+
+    async fn record_vaccine_digest_sent(
+        &self,
+        announced: &[(i64, models::pet::BoosterStatus)],
+        sent_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.db_pool.begin().await?;
+        for (pet_health_id, status) in announced {
+            sqlx::query(sqlite_queries::QUERY_INSERT_VACCINE_DIGEST_SENT)
+                .bind(pet_health_id)
+                .bind(status)
+                .bind(sent_at)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
 
         Ok(())
     }
+
+    async fn insert_vaccine_to(
+        &self,
+        pet_external_id: Uuid,
+        user_id: i64,
+        desc: String,
+        date: chrono::NaiveDate,
+        booster_months: Option<u32>,
+    ) -> anyhow::Result<models::pet::PetHealth> {
+        let date = date.and_time(chrono::NaiveTime::default());
+        let health_record = models::pet::PetHealthType::Vaccine;
+
+        let record = sqlx::query(sqlite_queries::QUERY_INSERT_PET_HEALTH_RECORD)
+            .bind(pet_external_id.to_string())
+            .bind(user_id)
+            .bind(health_record.to_string())
+            .bind(&desc)
+            .bind(date)
+            .bind(booster_months)
+            .try_map(
+                |row: sqlx::sqlite::SqliteRow| -> sqlx::Result<models::pet::PetHealth> {
+                    Ok(models::pet::PetHealth {
+                        id: row.try_get("id")?,
+                        pet_id: row.try_get("pet_id")?,
+                        health_record: health_record.clone(),
+                        description: desc.to_string(),
+                        created_at: date,
+                    })
+                },
+            )
+            .fetch_one(&self.db_pool)
+            .await?;
+
+        record_pet_audit(
+            &self.db_pool,
+            AuditedPet::Id(record.pet_id),
+            user_id,
+            models::pet::PetAuditAction::Insert,
+            &health_record.to_string(),
+        )
+        .await;
+
+        return Ok(record);
+    }
+
+    async fn insert_deworm_to(
+        &self,
+        pet_external_id: Uuid,
+        user_id: i64,
+        desc: String,
+        date: chrono::NaiveDate,
+    ) -> anyhow::Result<models::pet::PetHealth> {
+        let health_record = models::pet::PetHealthType::Deworm;
+        let date = date.and_time(chrono::NaiveTime::default());
+
+        let record = sqlx::query(sqlite_queries::QUERY_INSERT_PET_HEALTH_RECORD)
+            .bind(pet_external_id.to_string())
+            .bind(user_id)
+            .bind(health_record.to_string())
+            .bind(&desc)
+            .bind(date)
+            .bind(None::<u32>)
+            .try_map(
+                |row: sqlx::sqlite::SqliteRow| -> sqlx::Result<models::pet::PetHealth> {
+                    Ok(models::pet::PetHealth {
+                        id: row.try_get("id")?,
+                        pet_id: row.try_get("pet_id")?,
+                        health_record: health_record.clone(),
+                        description: desc.to_string(),
+                        created_at: date,
+                    })
+                },
+            )
+            .fetch_one(&self.db_pool)
+            .await?;
+
+        record_pet_audit(
+            &self.db_pool,
+            AuditedPet::Id(record.pet_id),
+            user_id,
+            models::pet::PetAuditAction::Insert,
+            &health_record.to_string(),
+        )
+        .await;
+
+        return Ok(record);
+    }
+
+    async fn insert_health_records_batch(
+        &self,
+        pet_external_id: Uuid,
+        user_id: i64,
+        health_type: models::pet::PetHealthType,
+        records: Vec<(chrono::NaiveDate, String)>,
+    ) -> anyhow::Result<u64> {
+        let mut transaction = self.db_pool.begin().await?;
+
+        let Some(pet_id) =
+            sqlx::query_scalar::<_, i64>(sqlite_queries::QUERY_GET_PET_ID_BY_EXTERNAL_AND_USER_ID)
+                .bind(pet_external_id.to_string())
+                .bind(user_id)
+                .fetch_optional(&mut *transaction)
+                .await?
+        else {
+            return Err(RepoError::NotFound.into());
+        };
+
+        let mut inserted = 0;
+        // Keeps each statement far below the SQLite bound parameters limit
+        for chunk in records.chunks(HEALTH_RECORDS_BATCH_SIZE) {
+            let mut query = sqlx::QueryBuilder::<sqlx::Sqlite>::new(
+                "INSERT INTO pet_health (pet_id,health_record,description,created_at) ",
+            );
+            query.push_values(chunk, |mut row, (date, desc)| {
+                row.push_bind(pet_id)
+                    .push_bind(health_type.to_string())
+                    .push_bind(desc)
+                    .push_bind(date.and_time(chrono::NaiveTime::default()));
+            });
+
+            inserted += query
+                .build()
+                .execute(&mut *transaction)
+                .await?
+                .rows_affected();
+        }
+
+        transaction.commit().await?;
+
+        if inserted > 0 {
+            record_pet_audit(
+                &self.db_pool,
+                AuditedPet::Id(pet_id),
+                user_id,
+                models::pet::PetAuditAction::Insert,
+                &health_type.to_string(),
+            )
+            .await;
+        }
+
+        Ok(inserted)
+    }
+
+    async fn insert_pet_weight(
+        &self,
+        pet_external_id: Uuid,
+        user_id: i64,
+        weight: f64,
+        date: chrono::NaiveDate,
+    ) -> anyhow::Result<models::pet::PetWeight> {
+        let date = date.and_time(chrono::NaiveTime::default());
+
+        let weight =
+            sqlx::query_as::<_, models::pet::PetWeight>(sqlite_queries::QUERY_INSERT_PET_WEIGHT)
+                .bind(pet_external_id.to_string())
+                .bind(user_id)
+                .bind(weight)
+                .bind(date)
+                .fetch_one(&self.db_pool)
+                .await?;
+
+        record_pet_audit(
+            &self.db_pool,
+            AuditedPet::Id(weight.pet_id),
+            user_id,
+            models::pet::PetAuditAction::Insert,
+            &models::pet::PetHealthType::Weight.to_string(),
+        )
+        .await;
+
+        Ok(weight)
+    }
+
+    async fn update_pet_weight(
+        &self,
+        weight_id: i64,
+        pet_external_id: Uuid,
+        user_id: i64,
+        weight: f64,
+        date: chrono::NaiveDate,
+    ) -> anyhow::Result<()> {
+        let updated = sqlx::query(sqlite_queries::QUERY_UPDATE_PET_WEIGHT)
+            .bind(weight)
+            .bind(date.and_time(chrono::NaiveTime::default()))
+            .bind(weight_id)
+            .bind(pet_external_id.to_string())
+            .bind(user_id)
+            .execute(&self.db_pool)
+            .await?
+            .rows_affected();
+
+        if updated == 0 {
+            anyhow::bail!("weight {weight_id} not found for pet {pet_external_id}");
+        }
+
+        record_pet_audit(
+            &self.db_pool,
+            AuditedPet::ExternalId(pet_external_id),
+            user_id,
+            models::pet::PetAuditAction::Update,
+            &models::pet::PetHealthType::Weight.to_string(),
+        )
+        .await;
+
+        Ok(())
+    }
+
+    async fn update_pet_health_record(
+        &self,
+        record_id: i64,
+        pet_external_id: Uuid,
+        user_id: i64,
+        health_type: models::pet::PetHealthType,
+        desc: String,
+        date: chrono::NaiveDate,
+    ) -> anyhow::Result<()> {
+        if health_type == models::pet::PetHealthType::Weight {
+            anyhow::bail!("weight records are updated with update_pet_weight");
+        }
+
+        let updated = sqlx::query(sqlite_queries::QUERY_UPDATE_PET_HEALTH_RECORD)
+            .bind(&desc)
+            .bind(date.and_time(chrono::NaiveTime::default()))
+            .bind(record_id)
+            .bind(health_type.to_string())
+            .bind(pet_external_id.to_string())
+            .bind(user_id)
+            .execute(&self.db_pool)
+            .await?
+            .rows_affected();
+
+        if updated == 0 {
+            anyhow::bail!("{health_type} {record_id} not found for pet {pet_external_id}");
+        }
+
+        record_pet_audit(
+            &self.db_pool,
+            AuditedPet::ExternalId(pet_external_id),
+            user_id,
+            models::pet::PetAuditAction::Update,
+            &health_type.to_string(),
+        )
+        .await;
+
+        Ok(())
+    }
+
+    async fn delete_pet_weight(
+        &self,
+        pet_external_id: Uuid,
+        user_id: i64,
+        weight_id: i64,
+    ) -> anyhow::Result<()> {
+        let deleted = sqlx::query(sqlite_queries::QUERY_DELETE_PET_WEIGHT)
+            .bind(weight_id)
+            .bind(pet_external_id.to_string())
+            .bind(user_id)
+            .execute(&self.db_pool)
+            .await?
+            .rows_affected();
+
+        record_health_delete(
+            &self.db_pool,
+            deleted,
+            pet_external_id,
+            user_id,
+            models::pet::PetHealthType::Weight,
+        )
+        .await;
+        Ok(())
+    }
+
+    async fn delete_all_weights(&self, pet_external_id: Uuid, user_id: i64) -> anyhow::Result<u64> {
+        let deleted = sqlx::query(sqlite_queries::QUERY_DELETE_ALL_PET_WEIGHTS)
+            .bind(pet_external_id.to_string())
+            .bind(user_id)
+            .execute(&self.db_pool)
+            .await?
+            .rows_affected();
+
+        record_health_delete(
+            &self.db_pool,
+            deleted,
+            pet_external_id,
+            user_id,
+            models::pet::PetHealthType::Weight,
+        )
+        .await;
+        Ok(deleted)
+    }
+
+    async fn delete_deworm(
+        &self,
+        pet_external_id: Uuid,
+        user_id: i64,
+        deworm_id: i64,
+    ) -> anyhow::Result<()> {
+        let deleted = sqlx::query(sqlite_queries::QUERY_DELETE_PET_HEALTH_RECORD)
+            .bind(deworm_id)
+            .bind(models::pet::PetHealthType::Deworm.to_string())
+            .bind(pet_external_id.to_string())
+            .bind(user_id)
+            .execute(&self.db_pool)
+            .await?
+            .rows_affected();
+
+        record_health_delete(
+            &self.db_pool,
+            deleted,
+            pet_external_id,
+            user_id,
+            models::pet::PetHealthType::Deworm,
+        )
+        .await;
+        Ok(())
+    }
+
+    async fn delete_vaccine(
+        &self,
+        pet_external_id: Uuid,
+        user_id: i64,
+        vaccine_id: i64,
+    ) -> anyhow::Result<()> {
+        let deleted = sqlx::query(sqlite_queries::QUERY_DELETE_PET_HEALTH_RECORD)
+            .bind(vaccine_id)
+            .bind(models::pet::PetHealthType::Vaccine.to_string())
+            .bind(pet_external_id.to_string())
+            .bind(user_id)
+            .execute(&self.db_pool)
+            .await?
+            .rows_affected();
+
+        record_health_delete(
+            &self.db_pool,
+            deleted,
+            pet_external_id,
+            user_id,
+            models::pet::PetHealthType::Vaccine,
+        )
+        .await;
+        Ok(())
+    }
+
+    async fn delete_all_health_records(
+        &self,
+        pet_external_id: Uuid,
+        user_id: i64,
+        health_type: models::pet::PetHealthType,
+    ) -> anyhow::Result<u64> {
+        let deleted = sqlx::query(sqlite_queries::QUERY_DELETE_ALL_PET_HEALTH_RECORDS)
+            .bind(health_type.to_string())
+            .bind(pet_external_id.to_string())
+            .bind(user_id)
+            .execute(&self.db_pool)
+            .await?
+            .rows_affected();
+
+        record_health_delete(
+            &self.db_pool,
+            deleted,
+            pet_external_id,
+            user_id,
+            health_type,
+        )
+        .await;
+        Ok(deleted)
+    }
+
+    async fn get_pet_owner_contacts(
+        &self,
+        pet_external_id: Uuid,
+    ) -> anyhow::Result<Vec<models::user_app::OwnerContact>> {
+        Ok(sqlx::query_as::<_, models::user_app::OwnerContact>(
+            sqlite_queries::QUERY_GET_PET_OWNER_CONTACTS,
+        )
+        .bind(pet_external_id.to_string())
+        .fetch_all(&self.db_pool)
+        .await?)
+    }
+
+    async fn get_owner_contacts(
+        &self,
+        user_id: i64,
+    ) -> anyhow::Result<Vec<models::user_app::OwnerContact>> {
+        Ok(sqlx::query_as::<_, models::user_app::OwnerContact>(
+            sqlite_queries::QUERY_GET_OWNER_CONTACTS,
+        )
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?)
+    }
+
+    async fn insert_owner_contact(
+        &self,
+        user_id: i64,
+        desc: String,
+        contact: String,
+        contact_type: models::user_app::ContactType,
+        address: Option<String>,
+        coordinates: Option<(f64, f64)>,
+    ) -> anyhow::Result<models::user_app::OwnerContact> {
+        let (latitude, longitude) = coordinates.unzip();
+        let inserted = sqlx::query_as::<_, models::user_app::OwnerContact>(
+            sqlite_queries::QUERY_INSERT_NEW_OWNER_CONTACT,
+        )
+        .bind(user_id)
+        .bind(&desc)
+        .bind(&contact)
+        .bind(&contact_type)
+        .bind(Utc::now())
+        .bind(&address)
+        .bind(latitude)
+        .bind(longitude)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        if let Some(contact) = inserted {
+            return Ok(contact);
+        }
+
+        // a repeated submit, the contact the user already has is returned
+        Ok(sqlx::query_as::<_, models::user_app::OwnerContact>(
+            sqlite_queries::QUERY_GET_OWNER_CONTACT_BY_VALUE,
+        )
+        .bind(user_id)
+        .bind(&contact)
+        .fetch_one(&self.db_pool)
+        .await?)
+    }
+
+    async fn delete_owner_contact(&self, user_id: i64, contact_id: i64) -> anyhow::Result<()> {
+        sqlx::query(sqlite_queries::QUERY_DELETE_OWNER_CONTACT)
+            .bind(contact_id)
+            .bind(user_id)
+            .execute(&self.db_pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn set_primary_contact(&self, user_id: i64, contact_id: i64) -> anyhow::Result<()> {
+        let mut transaction = self.db_pool.begin().await?;
+
+        sqlx::query(sqlite_queries::QUERY_UNSET_PRIMARY_OWNER_CONTACTS)
+            .bind(user_id)
+            .execute(&mut *transaction)
+            .await?;
+
+        let updated = sqlx::query(sqlite_queries::QUERY_SET_PRIMARY_OWNER_CONTACT)
+            .bind(contact_id)
+            .bind(user_id)
+            .execute(&mut *transaction)
+            .await?
+            .rows_affected();
+
+        if updated == 0 {
+            anyhow::bail!("contact {contact_id} not found for user {user_id}");
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    async fn reorder_contacts(&self, user_id: i64, ordered_ids: &[i64]) -> anyhow::Result<()> {
+        let mut transaction = self.db_pool.begin().await?;
+
+        for (sort_order, contact_id) in ordered_ids.iter().enumerate() {
+            sqlx::query(sqlite_queries::QUERY_UPDATE_OWNER_CONTACT_SORT_ORDER)
+                .bind(sort_order as i64)
+                .bind(contact_id)
+                .bind(user_id)
+                .execute(&mut *transaction)
+                .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    async fn get_pet_owner_by_external_id(
+        &self,
+        pet_external_id: Uuid,
+    ) -> anyhow::Result<Option<models::user_app::User>> {
+        Ok(
+            sqlx::query_as(sqlite_queries::QUERY_GET_PET_OWNER_BY_EXTERNAL_ID)
+                .bind(pet_external_id.to_string())
+                .fetch_optional(&self.db_pool)
+                .await?,
+        )
+    }
+
+    async fn insert_found_report(
+        &self,
+        pet_external_id: Uuid,
+        message: String,
+        callback_contact: Option<String>,
+        created_at: chrono::DateTime<Utc>,
+    ) -> anyhow::Result<i64> {
+        let result = sqlx::query(sqlite_queries::QUERY_INSERT_FOUND_REPORT)
+            .bind(pet_external_id.to_string())
+            .bind(message)
+            .bind(callback_contact)
+            .bind(created_at)
+            .execute(&self.db_pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            anyhow::bail!("pet external id {pet_external_id} is not linked to a pet");
+        }
+
+        Ok(result.last_insert_rowid())
+    }
+
+    async fn get_pet_found_reports(
+        &self,
+        pet_id: i64,
+        owner_id: i64,
+    ) -> anyhow::Result<Vec<models::pet::FoundReport>> {
+        Ok(sqlx::query_as::<_, models::pet::FoundReport>(
+            sqlite_queries::QUERY_GET_PET_FOUND_REPORTS,
+        )
+        .bind(pet_id)
+        .bind(owner_id)
+        .bind(consts::PET_FOUND_REPORT_LIST_LIMIT)
+        .fetch_all(&self.db_pool)
+        .await?)
+    }
+
+    async fn insert_pet_scan(
+        &self,
+        pet_external_id: Uuid,
+        viewer_id: Option<i64>,
+        user_agent: Option<String>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(sqlite_queries::QUERY_INSERT_PET_SCAN)
+            .bind(pet_external_id.to_string())
+            .bind(user_agent)
+            .bind(Utc::now())
+            .bind(viewer_id)
+            .execute(&self.db_pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_scan_stats(
+        &self,
+        pet_external_id: Uuid,
+        owner_id: i64,
+    ) -> anyhow::Result<models::pet::PetScanStats> {
+        Ok(sqlx::query_as(sqlite_queries::QUERY_GET_PET_SCAN_STATS)
+            .bind(pet_external_id.to_string())
+            .bind(owner_id)
+            .bind(Utc::now() - chrono::Duration::days(consts::PET_SCAN_RECENT_DAYS))
+            .fetch_one(&self.db_pool)
+            .await?)
+    }
+
+    async fn delete_pet_scans_before(&self, before: chrono::DateTime<Utc>) -> anyhow::Result<u64> {
+        Ok(sqlx::query(sqlite_queries::QUERY_DELETE_PET_SCANS_BEFORE)
+            .bind(before)
+            .execute(&self.db_pool)
+            .await?
+            .rows_affected())
+    }
+
+    async fn insert_pet_share_token(
+        &self,
+        pet_id: i64,
+        user_id: i64,
+        token: &str,
+        expires_at: chrono::DateTime<Utc>,
+    ) -> anyhow::Result<()> {
+        let inserted = sqlx::query(sqlite_queries::QUERY_INSERT_PET_SHARE_TOKEN)
+            .bind(pet_id)
+            .bind(user_id)
+            .bind(token)
+            .bind(expires_at)
+            .bind(Utc::now())
+            .execute(&self.db_pool)
+            .await?
+            .rows_affected();
+
+        if inserted == 0 {
+            anyhow::bail!("pet {pet_id} not found for user {user_id}");
+        }
+
+        Ok(())
+    }
+
+    async fn delete_pet_share_token(
+        &self,
+        pet_id: i64,
+        user_id: i64,
+        token: &str,
+    ) -> anyhow::Result<()> {
+        sqlx::query(sqlite_queries::QUERY_DELETE_PET_SHARE_TOKEN)
+            .bind(pet_id)
+            .bind(user_id)
+            .bind(token)
+            .execute(&self.db_pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_pet_share_tokens(
+        &self,
+        pet_id: i64,
+        user_id: i64,
+    ) -> anyhow::Result<Vec<models::pet::PetShareToken>> {
+        Ok(sqlx::query_as(sqlite_queries::QUERY_GET_PET_SHARE_TOKENS)
+            .bind(pet_id)
+            .bind(user_id)
+            .bind(Utc::now())
+            .fetch_all(&self.db_pool)
+            .await?)
+    }
+
+    async fn get_pet_external_id_by_share_token(
+        &self,
+        token: &str,
+    ) -> anyhow::Result<Option<Uuid>> {
+        Ok(sqlx::query_scalar::<_, uuid::fmt::Hyphenated>(
+            sqlite_queries::QUERY_GET_PET_EXTERNAL_ID_BY_SHARE_TOKEN,
+        )
+        .bind(token)
+        .bind(Utc::now())
+        .fetch_optional(&self.db_pool)
+        .await?
+        .map(uuid::fmt::Hyphenated::into_uuid))
+    }
+
+    async fn insert_new_pet_note(
+        &self,
+        user_id: i64,
+        note: &models::pet::PetNote,
+    ) -> anyhow::Result<i64> {
+        let note_id = sqlx::query(sqlite_queries::QUERY_INSERT_PET_NOTE)
+            .bind(note.pet_id)
+            .bind(user_id)
+            .bind(&note.title)
+            .bind(&note.content)
+            .bind(note.created_at)
+            .execute(&self.db_pool)
+            .await?
+            .last_insert_rowid();
+
+        record_pet_audit(
+            &self.db_pool,
+            AuditedPet::Id(note.pet_id),
+            user_id,
+            models::pet::PetAuditAction::Insert,
+            "note",
+        )
+        .await;
+
+        Ok(note_id)
+    }
+
+    async fn get_all_pet_notes(
+        &self,
+        user_id: i64,
+        pet_id: i64,
+    ) -> anyhow::Result<Vec<models::pet::PetNote>> {
+        Ok(
+            sqlx::query_as::<_, models::pet::PetNote>(sqlite_queries::QUERY_GET_ALL_PET_NOTES)
+                .bind(pet_id)
+                .bind(user_id)
+                .fetch_all(&self.db_pool)
+                .await?,
+        )
+    }
+
+    async fn get_pet_notes(
+        &self,
+        user_id: i64,
+        pet_id: i64,
+        limit: i64,
+        offset: i64,
+    ) -> anyhow::Result<Vec<models::pet::PetNote>> {
+        Ok(
+            sqlx::query_as::<_, models::pet::PetNote>(sqlite_queries::QUERY_GET_PET_NOTES)
+                .bind(pet_id)
+                .bind(user_id)
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(&self.db_pool)
+                .await?,
+        )
+    }
+
+    async fn get_pet_notes_count(&self, user_id: i64, pet_id: i64) -> anyhow::Result<i64> {
+        Ok(
+            sqlx::query_scalar::<_, i64>(sqlite_queries::QUERY_COUNT_PET_NOTES)
+                .bind(pet_id)
+                .bind(user_id)
+                .fetch_one(&self.db_pool)
+                .await?,
+        )
+    }
+
+    async fn search_pet_notes(
+        &self,
+        user_id: i64,
+        pet_id: i64,
+        query: String,
+    ) -> anyhow::Result<Vec<models::pet::PetNote>> {
+        Ok(
+            sqlx::query_as::<_, models::pet::PetNote>(sqlite_queries::QUERY_SEARCH_PET_NOTES)
+                .bind(pet_id)
+                .bind(user_id)
+                .bind(contains_like_pattern(&query))
+                .fetch_all(&self.db_pool)
+                .await?,
+        )
+    }
+
+    async fn update_pet_note(
+        &self,
+        user_id: i64,
+        note_id: i64,
+        title: String,
+        content: String,
+    ) -> anyhow::Result<()> {
+        let updated = sqlx::query(sqlite_queries::QUERY_UPDATE_PET_NOTE)
+            .bind(&title)
+            .bind(&content)
+            .bind(Utc::now())
+            .bind(note_id)
+            .bind(user_id)
+            .execute(&self.db_pool)
+            .await?
+            .rows_affected();
+
+        if updated == 0 {
+            anyhow::bail!("note {note_id} not found for user {user_id}");
+        }
+
+        record_pet_audit(
+            &self.db_pool,
+            AuditedPet::NoteId(note_id),
+            user_id,
+            models::pet::PetAuditAction::Update,
+            "note",
+        )
+        .await;
+
+        Ok(())
+    }
+
+    async fn delete_pet_note(&self, pet_id: i64, user_id: i64, note_id: i64) -> anyhow::Result<()> {
+        let deleted = sqlx::query(sqlite_queries::QUERY_DELETE_PET_NOTE)
+            .bind(note_id)
+            .bind(pet_id)
+            .bind(user_id)
+            .execute(&self.db_pool)
+            .await?
+            .rows_affected();
+
+        if deleted > 0 {
+            record_pet_audit(
+                &self.db_pool,
+                AuditedPet::Id(pet_id),
+                user_id,
+                models::pet::PetAuditAction::Delete,
+                "note",
+            )
+            .await;
+        }
+        Ok(())
+    }
+
+    async fn get_active_user_remiders(
+        &self,
+        user_id: i64,
+    ) -> anyhow::Result<Vec<models::reminder::Reminder>> {
+        Ok(
+            sqlx::query_as(sqlite_queries::QUERY_GET_USER_ACTIVE_REMINDERS)
+                .bind(user_id)
+                .bind(Utc::now())
+                .fetch_all(&self.db_pool)
+                .await?,
+        )
+    }
+
+    async fn get_all_user_reminders(
+        &self,
+        user_id: i64,
+        include_past: bool,
+    ) -> anyhow::Result<Vec<models::reminder::ReminderListItem>> {
+        let now = Utc::now();
+        let since = if include_past {
+            now - chrono::TimeDelta::days(consts::REMINDER_HISTORY_DAYS)
+        } else {
+            now
+        };
+
+        Ok(sqlx::query_as(sqlite_queries::QUERY_GET_USER_REMINDERS)
+            .bind(user_id)
+            .bind(now)
+            .bind(since)
+            .fetch_all(&self.db_pool)
+            .await?)
+    }
+
+    async fn get_reminder_execution_id(
+        &self,
+        user_id: i64,
+        reminder_id: i64,
+    ) -> anyhow::Result<Option<String>> {
+        Ok(sqlx::query_scalar::<_, String>(
+            "SELECT execution_id FROM reminder WHERE id=$1 AND user_app_id=$2 LIMIT 1;",
+        )
+        .bind(reminder_id)
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?)
+    }
+
+    async fn insert_user_remider(
+        &self,
+        reminder: &models::reminder::Reminder,
+    ) -> anyhow::Result<i64> {
+        Ok(sqlx::query(sqlite_queries::QUERY_INSERT_USER_REMINDER)
+            .bind(reminder.user_app_id)
+            .bind(reminder.body.to_string())
+            .bind(reminder.execution_id.to_string())
+            .bind(serde_json::to_string(&reminder.notification_type)?)
+            .bind(reminder.send_at)
+            .bind(reminder.user_timezone.to_string())
+            .bind(reminder.pet_id)
+            .bind(reminder.created_at)
+            .execute(&self.db_pool)
+            .await?
+            .last_insert_rowid())
+    }
+
+    async fn get_user_reminder(
+        &self,
+        reminder_id: i64,
+        user_id: i64,
+    ) -> Result<models::reminder::Reminder, RepoError> {
+        Ok(
+            sqlx::query_as::<_, models::reminder::Reminder>(
+                sqlite_queries::QUERY_GET_USER_REMINDER,
+            )
+            .bind(reminder_id)
+            .bind(user_id)
+            .fetch_one(&self.db_pool)
+            .await?,
+        )
+    }
+
+    async fn update_reminder_schedule(
+        &self,
+        reminder_id: i64,
+        user_id: i64,
+        send_at: chrono::DateTime<Utc>,
+        user_timezone: &str,
+        execution_id: &str,
+    ) -> anyhow::Result<()> {
+        let updated = sqlx::query(sqlite_queries::QUERY_UPDATE_REMINDER_SCHEDULE)
+            .bind(reminder_id)
+            .bind(user_id)
+            .bind(send_at)
+            .bind(user_timezone)
+            .bind(execution_id)
+            .execute(&self.db_pool)
+            .await?
+            .rows_affected();
+
+        if updated == 0 {
+            return Err(RepoError::NotFound.into());
+        }
+
+        Ok(())
+    }
+
+    async fn delete_user_reminder(&self, reminder_id: i64, user_id: i64) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM reminder WHERE id=$1 AND user_app_id=$2")
+            .bind(reminder_id)
+            .bind(user_id)
+            .execute(&self.db_pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete_reminders_for_pet(
+        &self,
+        pet_id: i64,
+        user_id: i64,
+    ) -> anyhow::Result<Vec<String>> {
+        Ok(
+            sqlx::query_scalar::<_, String>(sqlite_queries::QUERY_DELETE_PET_REMINDERS)
+                .bind(pet_id)
+                .bind(user_id)
+                .fetch_all(&self.db_pool)
+                .await?,
+        )
+    }
+
+    async fn insert_reminder_template(
+        &self,
+        user_id: i64,
+        name: &str,
+        body: &str,
+    ) -> anyhow::Result<i64> {
+        Ok(sqlx::query(sqlite_queries::QUERY_INSERT_REMINDER_TEMPLATE)
+            .bind(user_id)
+            .bind(name)
+            .bind(body)
+            .bind(Utc::now())
+            .execute(&self.db_pool)
+            .await?
+            .last_insert_rowid())
+    }
+
+    async fn get_reminder_templates(
+        &self,
+        user_id: i64,
+    ) -> anyhow::Result<Vec<models::reminder::ReminderTemplate>> {
+        Ok(sqlx::query_as::<_, models::reminder::ReminderTemplate>(
+            sqlite_queries::QUERY_GET_REMINDER_TEMPLATES,
+        )
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?)
+    }
+
+    async fn delete_reminder_template(&self, template_id: i64, user_id: i64) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM reminder_template WHERE id=$1 AND user_app_id=$2")
+            .bind(template_id)
+            .bind(user_id)
+            .execute(&self.db_pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn upsert_message_delivery_status(
+        &self,
+        wamid: &str,
+        status: models::reminder::DeliveryStatus,
+        at: chrono::DateTime<Utc>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(sqlite_queries::QUERY_UPSERT_MESSAGE_DELIVERY_STATUS)
+            .bind(wamid)
+            .bind(status)
+            .bind(at)
+            .execute(&self.db_pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn link_message_delivery_to_reminder(
+        &self,
+        wamid: &str,
+        reminder_id: i64,
+    ) -> anyhow::Result<()> {
+        sqlx::query(sqlite_queries::QUERY_LINK_MESSAGE_DELIVERY_TO_REMINDER)
+            .bind(wamid)
+            .bind(reminder_id)
+            .execute(&self.db_pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_reminder_delivery_status(
+        &self,
+        reminder_id: i64,
+        user_id: i64,
+    ) -> anyhow::Result<Option<models::reminder::DeliveryStatus>> {
+        Ok(sqlx::query_scalar::<_, models::reminder::DeliveryStatus>(
+            sqlite_queries::QUERY_GET_REMINDER_DELIVERY_STATUS,
+        )
+        .bind(reminder_id)
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?)
+    }
+
+    async fn count_users(&self) -> anyhow::Result<i64> {
+        Ok(sqlx::query_scalar("SELECT COUNT(*) FROM user_app;")
+            .fetch_one(&self.db_pool)
+            .await?)
+    }
+
+    async fn count_subscribed_users(&self) -> anyhow::Result<i64> {
+        Ok(
+            sqlx::query_scalar("SELECT COUNT(*) FROM user_app WHERE is_subscribed=1;")
+                .fetch_one(&self.db_pool)
+                .await?,
+        )
+    }
+
+    async fn count_pets(&self) -> anyhow::Result<i64> {
+        Ok(sqlx::query_scalar("SELECT COUNT(*) FROM pet;")
+            .fetch_one(&self.db_pool)
+            .await?)
+    }
+
+    async fn count_approved_payments_since(
+        &self,
+        since: chrono::DateTime<Utc>,
+    ) -> anyhow::Result<i64> {
+        Ok(
+            sqlx::query_scalar(sqlite_queries::QUERY_COUNT_APPROVED_PAYMENTS_SINCE)
+                .bind(models::payment::PaymentStatus::Approved.to_string())
+                .bind(since)
+                .fetch_one(&self.db_pool)
+                .await?,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    /// Creates a repo over an in-memory database with the app schema.
+    async fn setup_test_repo() -> SqlxSqliteRepo {
+        let db_pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        sqlx::raw_sql(include_str!("../../../migrations/create_tables.sql"))
+            .execute(&db_pool)
+            .await
+            .unwrap();
+
+        SqlxSqliteRepo { db_pool }
+    }
+
+    /// Inserts a user with a pet, returning the pet id.
+    async fn insert_user_with_pet(repo: &SqlxSqliteRepo, user_id: i64) -> i64 {
+        sqlx::query("INSERT INTO user_app(id,email) VALUES($1,$2);")
+            .bind(user_id)
+            .bind(format!("user{user_id}@example.com"))
+            .execute(&repo.db_pool)
+            .await
+            .unwrap();
+
+        sqlx::query(
+            "INSERT INTO pet(user_app_id,pet_name,birthday,breed,about,is_female,is_lost,is_spaying_neutering)
+            VALUES($1,'Buddy','2020-01-01','Mestizo','',0,0,0);",
+        )
+        .bind(user_id)
+        .execute(&repo.db_pool)
+        .await
+        .unwrap()
+        .last_insert_rowid()
+    }
+
+    async fn link_pet_external_id(repo: &SqlxSqliteRepo, pet_id: i64) -> Uuid {
+        let external_id = Uuid::new_v4();
+        let id_external_id = sqlx::query("INSERT INTO pet_external_id(external_id) VALUES($1);")
+            .bind(external_id.to_string())
+            .execute(&repo.db_pool)
+            .await
+            .unwrap()
+            .last_insert_rowid();
+        sqlx::query("INSERT INTO pet_linked(pet_id,id_pet_external_id) VALUES($1,$2);")
+            .bind(pet_id)
+            .bind(id_external_id)
+            .execute(&repo.db_pool)
+            .await
+            .unwrap();
+
+        external_id
+    }
+
+    #[ntex::test]
+    async fn test_collaborator_can_read_but_not_delete_pet() {
+        let repo = setup_test_repo().await;
+        let (owner_id, collaborator_id) = (1, 2);
+        let pet_id = insert_user_with_pet(&repo, owner_id).await;
+        link_pet_external_id(&repo, pet_id).await;
+        insert_user_with_pet(&repo, collaborator_id).await;
+
+        assert!(matches!(
+            repo.get_pet_by_id(pet_id, collaborator_id).await,
+            Err(RepoError::NotFound)
+        ));
+
+        repo.grant_pet_access(
+            pet_id,
+            owner_id,
+            "user2@example.com",
+            models::pet::PetAccessRole::Viewer,
+        )
+        .await
+        .unwrap();
+
+        assert!(repo.get_pet_by_id(pet_id, collaborator_id).await.is_ok());
+        // viewers can't update the pet
+        assert!(
+            repo.set_pet_lost_status(pet_id, collaborator_id, true)
+                .await
+                .is_err()
+        );
+
+        repo.delete_pet(pet_id, collaborator_id).await.unwrap();
+        assert!(repo.get_pet_by_id(pet_id, owner_id).await.is_ok());
+
+        // granting again changes the role
+        repo.grant_pet_access(
+            pet_id,
+            owner_id,
+            "user2@example.com",
+            models::pet::PetAccessRole::Editor,
+        )
+        .await
+        .unwrap();
+        repo.set_pet_lost_status(pet_id, collaborator_id, true)
+            .await
+            .unwrap();
+        assert!(repo.get_pet_by_id(pet_id, owner_id).await.unwrap().is_lost);
+
+        repo.delete_pet(pet_id, collaborator_id).await.unwrap();
+        assert!(repo.get_pet_by_id(pet_id, owner_id).await.is_ok());
+
+        let collaborators = repo.list_pet_collaborators(pet_id, owner_id).await.unwrap();
+        assert_eq!(collaborators.len(), 1);
+        assert_eq!(collaborators[0].user_id, collaborator_id);
+        assert_eq!(collaborators[0].role, models::pet::PetAccessRole::Editor);
+        // only the owner lists the collaborators
+        assert!(
+            repo.list_pet_collaborators(pet_id, collaborator_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[ntex::test]
+    async fn test_editor_can_update_pet_and_its_picture() {
+        let repo = setup_test_repo().await;
+        let (owner_id, editor_id) = (1, 2);
+        let pet_id = insert_user_with_pet(&repo, owner_id).await;
+        link_pet_external_id(&repo, pet_id).await;
+        insert_user_with_pet(&repo, editor_id).await;
+        repo.grant_pet_access(
+            pet_id,
+            owner_id,
+            "user2@example.com",
+            models::pet::PetAccessRole::Editor,
+        )
+        .await
+        .unwrap();
+
+        let pet = repo.get_pet_by_id(pet_id, owner_id).await.unwrap();
+        repo.update_pet(&models::pet::Pet {
+            user_app_id: editor_id,
+            pet_name: "Firulais".into(),
+            pic: Some("pics/new".into()),
+            ..pet
+        })
+        .await
+        .unwrap();
+
+        let pet = repo.get_pet_by_id(pet_id, owner_id).await.unwrap();
+        assert_eq!(pet.pet_name, "Firulais");
+        assert_eq!(pet.pic.as_deref(), Some("pics/new"));
+    }
+
+    #[ntex::test]
+    async fn test_grant_pet_access_checks_owner_and_target() {
+        let repo = setup_test_repo().await;
+        let pet_id = insert_user_with_pet(&repo, 1).await;
+        insert_user_with_pet(&repo, 2).await;
+
+        for (owner_id, target_email) in [
+            (1, "unknown@example.com"),
+            (1, "user1@example.com"),
+            (2, "user2@example.com"),
+        ] {
+            assert!(matches!(
+                repo.grant_pet_access(
+                    pet_id,
+                    owner_id,
+                    target_email,
+                    models::pet::PetAccessRole::Editor
+                )
+                .await,
+                Err(RepoError::NotFound)
+            ));
+        }
+    }
+
+    #[ntex::test]
+    async fn test_update_pet_note_checks_ownership() {
+        let repo = setup_test_repo().await;
+        let owner_id = 1;
+        let other_user_id = 2;
+        let pet_id = insert_user_with_pet(&repo, owner_id).await;
+        insert_user_with_pet(&repo, other_user_id).await;
+
+        let note_id = repo
+            .insert_new_pet_note(
+                owner_id,
+                &models::pet::PetNote {
+                    id: 0,
+                    pet_id,
+                    title: "Dieta".into(),
+                    content: "croquetas".into(),
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                },
+            )
+            .await
+            .unwrap();
+
+        let result = repo
+            .update_pet_note(other_user_id, note_id, "Hack".into(), "hack".into())
+            .await;
+        assert!(result.is_err());
+
+        let notes = repo.get_all_pet_notes(owner_id, pet_id).await.unwrap();
+        assert_eq!(notes[0].title, "Dieta");
+        assert_eq!(notes[0].content, "croquetas");
+
+        repo.update_pet_note(owner_id, note_id, "Dieta nueva".into(), "pollo".into())
+            .await
+            .unwrap();
+
+        let notes = repo.get_all_pet_notes(owner_id, pet_id).await.unwrap();
+        assert_eq!(notes[0].title, "Dieta nueva");
+        assert_eq!(notes[0].content, "pollo");
+        assert!(notes[0].updated_at >= notes[0].created_at);
+    }
+
+    #[ntex::test]
+    async fn test_get_pet_notes_pagination_boundaries() {
+        let repo = setup_test_repo().await;
+        let owner_id = 1;
+        let pet_id = insert_user_with_pet(&repo, owner_id).await;
+        let created_at = Utc::now();
+
+        for i in 0..5 {
+            repo.insert_new_pet_note(
+                owner_id,
+                &models::pet::PetNote {
+                    id: 0,
+                    pet_id,
+                    title: format!("nota {i}"),
+                    content: String::new(),
+                    created_at: created_at + chrono::TimeDelta::minutes(i),
+                    updated_at: created_at,
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        assert_eq!(repo.get_pet_notes_count(owner_id, pet_id).await.unwrap(), 5);
+        assert_eq!(repo.get_pet_notes_count(2, pet_id).await.unwrap(), 0);
+
+        // newest first
+        let titles = |notes: Vec<models::pet::PetNote>| {
+            notes.into_iter().map(|note| note.title).collect::<Vec<_>>()
+        };
+        let first_page = repo.get_pet_notes(owner_id, pet_id, 2, 0).await.unwrap();
+        assert_eq!(titles(first_page), ["nota 4", "nota 3"]);
+
+        let last_page = repo.get_pet_notes(owner_id, pet_id, 2, 4).await.unwrap();
+        assert_eq!(titles(last_page), ["nota 0"]);
+
+        let past_the_end = repo.get_pet_notes(owner_id, pet_id, 2, 5).await.unwrap();
+        assert!(past_the_end.is_empty());
+
+        let all_notes = repo.get_all_pet_notes(owner_id, pet_id).await.unwrap();
+        assert_eq!(all_notes.len(), 5);
+    }
+
+    #[ntex::test]
+    async fn test_search_pet_notes_title_and_content() {
+        let repo = setup_test_repo().await;
+        let owner_id = 1;
+        let other_user_id = 2;
+        let pet_id = insert_user_with_pet(&repo, owner_id).await;
+        let other_pet_id = insert_user_with_pet(&repo, other_user_id).await;
+
+        for (user_id, pet_id, title, content) in [
+            (owner_id, pet_id, "Dieta", "<p>croquetas</p>"),
+            (owner_id, pet_id, "Paseo", "<p>Come POLLO los domingos</p>"),
+            (other_user_id, other_pet_id, "Pollo", "<p>pollo</p>"),
+        ] {
+            repo.insert_new_pet_note(
+                user_id,
+                &models::pet::PetNote {
+                    id: 0,
+                    pet_id,
+                    title: title.into(),
+                    content: content.into(),
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        // title match, case insensitive
+        let notes = repo
+            .search_pet_notes(owner_id, pet_id, "DIETA".into())
+            .await
+            .unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].title, "Dieta");
+
+        // content match, other users' notes are excluded
+        let notes = repo
+            .search_pet_notes(owner_id, pet_id, "pollo".into())
+            .await
+            .unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].title, "Paseo");
+
+        // wildcards are matched literally
+        let notes = repo
+            .search_pet_notes(owner_id, pet_id, "%".into())
+            .await
+            .unwrap();
+        assert!(notes.is_empty());
+    }
+
+    #[ntex::test]
+    async fn test_get_pets_with_counts_mixed_records() {
+        let repo = setup_test_repo().await;
+        let user_id = 1;
+        let pet_id = insert_user_with_pet(&repo, user_id).await;
+        link_pet_external_id(&repo, pet_id).await;
+
+        for (health_record, created_at) in [
+            ("vaccine", "2024-01-15 00:00:00"),
+            ("vaccine", "2024-03-01 00:00:00"),
+            ("deworm", "2024-05-01 00:00:00"),
+        ] {
+            sqlx::query(
+                "INSERT INTO pet_health(pet_id,health_record,description,created_at) VALUES($1,$2,'',$3);",
+            )
+            .bind(pet_id)
+            .bind(health_record)
+            .bind(created_at)
+            .execute(&repo.db_pool)
+            .await
+            .unwrap();
+        }
+        for weight in [10.0, 12.5, 13.0] {
+            sqlx::query("INSERT INTO pet_weight(pet_id,weight) VALUES($1,$2);")
+                .bind(pet_id)
+                .bind(weight)
+                .execute(&repo.db_pool)
+                .await
+                .unwrap();
+        }
+
+        let pets = repo.get_pets_with_counts(user_id).await.unwrap();
+
+        assert_eq!(pets.len(), 1);
+        assert_eq!(pets[0].pet.id, pet_id);
+        assert_eq!(pets[0].vaccine_count, 2);
+        assert_eq!(pets[0].weight_count, 3);
+        assert_eq!(
+            pets[0].last_vaccine_date,
+            chrono::NaiveDate::from_ymd_opt(2024, 3, 1)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+        );
+    }
+
+    #[ntex::test]
+    async fn test_reminder_channels_round_trip() {
+        let repo = setup_test_repo().await;
+        let user_id = 1;
+        insert_user_with_pet(&repo, user_id).await;
+
+        let channels: models::reminder::ReminderChannels = [
+            models::reminder::ReminderNotificationType::WhatsApp,
+            models::reminder::ReminderNotificationType::Email,
+        ]
+        .into_iter()
+        .collect();
+
+        repo.insert_user_remider(&models::reminder::Reminder {
+            user_app_id: user_id,
+            body: "Desparasitar".into(),
+            execution_id: "execution-id".into(),
+            notification_type: channels.clone(),
+            send_at: Utc::now() + chrono::TimeDelta::days(1),
+            user_timezone: "America/Mexico_City".into(),
+            created_at: Utc::now(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        let stored: String = sqlx::query_scalar("SELECT notification_type FROM reminder;")
+            .fetch_one(&repo.db_pool)
+            .await
+            .unwrap();
+        assert_eq!(stored, r#"["whatsapp","email"]"#);
+
+        let reminders = repo.get_active_user_remiders(user_id).await.unwrap();
+        assert_eq!(reminders.len(), 1);
+        assert_eq!(reminders[0].notification_type, channels);
+    }
+
+    #[ntex::test]
+    async fn test_get_all_user_reminders_splits_past_and_upcoming() {
+        use models::reminder::ReminderStatus;
+
+        let repo = setup_test_repo().await;
+        let user_id = 1;
+        insert_user_with_pet(&repo, user_id).await;
+
+        let now = Utc::now();
+        for (execution_id, send_at) in [
+            ("fired", now - chrono::TimeDelta::days(2)),
+            ("later", now + chrono::TimeDelta::days(5)),
+            (
+                "too-old",
+                now - chrono::TimeDelta::days(consts::REMINDER_HISTORY_DAYS + 1),
+            ),
+            ("sooner", now + chrono::TimeDelta::days(1)),
+            ("fired-recently", now - chrono::TimeDelta::hours(1)),
+        ] {
+            repo.insert_user_remider(&models::reminder::Reminder {
+                user_app_id: user_id,
+                body: execution_id.into(),
+                execution_id: execution_id.into(),
+                send_at,
+                user_timezone: "America/Mexico_City".into(),
+                created_at: now,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        }
+
+        let listed = |reminders: Vec<models::reminder::ReminderListItem>| {
+            reminders
+                .into_iter()
+                .map(|item| (item.reminder.execution_id, item.status))
+                .collect::<Vec<_>>()
+        };
+        let expected = |items: &[(&str, ReminderStatus)]| {
+            items
+                .iter()
+                .map(|(execution_id, status)| (execution_id.to_string(), *status))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            listed(repo.get_all_user_reminders(user_id, false).await.unwrap()),
+            expected(&[
+                ("sooner", ReminderStatus::Scheduled),
+                ("later", ReminderStatus::Scheduled),
+            ])
+        );
+        assert_eq!(
+            listed(repo.get_all_user_reminders(user_id, true).await.unwrap()),
+            expected(&[
+                ("sooner", ReminderStatus::Scheduled),
+                ("later", ReminderStatus::Scheduled),
+                ("fired-recently", ReminderStatus::Sent),
+                ("fired", ReminderStatus::Sent),
+            ])
+        );
+    }
+
+    async fn get_pet_flags(repo: &SqlxSqliteRepo, pet_id: i64) -> (String, String, bool, bool) {
+        sqlx::query_as(
+            "SELECT pet_name, breed, is_lost, is_spaying_neutering FROM pet WHERE id = $1;",
+        )
+        .bind(pet_id)
+        .fetch_one(&repo.db_pool)
+        .await
+        .unwrap()
+    }
+
+    #[ntex::test]
+    async fn test_set_pet_lost_status_keeps_other_fields() {
+        let repo = setup_test_repo().await;
+        let owner_id = 1;
+        let pet_id = insert_user_with_pet(&repo, owner_id).await;
+
+        repo.set_pet_lost_status(pet_id, owner_id, true)
+            .await
+            .unwrap();
+        assert_eq!(
+            get_pet_flags(&repo, pet_id).await,
+            ("Buddy".into(), "Mestizo".into(), true, false)
+        );
+
+        repo.set_pet_lost_status(pet_id, owner_id, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            get_pet_flags(&repo, pet_id).await,
+            ("Buddy".into(), "Mestizo".into(), false, false)
+        );
+    }
+
+    #[ntex::test]
+    async fn test_set_pet_status_checks_ownership() {
+        let repo = setup_test_repo().await;
+        let owner_id = 1;
+        let other_user_id = 2;
+        let pet_id = insert_user_with_pet(&repo, owner_id).await;
+        insert_user_with_pet(&repo, other_user_id).await;
+
+        assert!(
+            repo.set_pet_lost_status(pet_id, other_user_id, true)
+                .await
+                .is_err()
+        );
+        assert!(
+            repo.set_pet_spaying_neutering_status(pet_id, other_user_id, true)
+                .await
+                .is_err()
+        );
+
+        repo.set_pet_spaying_neutering_status(pet_id, owner_id, true)
+            .await
+            .unwrap();
+        assert_eq!(
+            get_pet_flags(&repo, pet_id).await,
+            ("Buddy".into(), "Mestizo".into(), false, true)
+        );
+    }
+
+    #[ntex::test]
+    async fn test_update_pet_weight_keeps_record_id() {
+        let repo = setup_test_repo().await;
+        let owner_id = 1;
+        let other_user_id = 2;
+        let pet_id = insert_user_with_pet(&repo, owner_id).await;
+        insert_user_with_pet(&repo, other_user_id).await;
+        let external_id = link_pet_external_id(&repo, pet_id).await;
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+
+        let weight = repo
+            .insert_pet_weight(external_id, owner_id, 41.0, date)
+            .await
+            .unwrap();
+
+        let new_date = chrono::NaiveDate::from_ymd_opt(2024, 1, 16).unwrap();
+        assert!(
+            repo.update_pet_weight(weight.id, external_id, other_user_id, 4.1, new_date)
+                .await
+                .is_err()
+        );
+        repo.update_pet_weight(weight.id, external_id, owner_id, 4.1, new_date)
+            .await
+            .unwrap();
+
+        let weights = repo
+            .get_pet_weights(external_id, Some(owner_id))
+            .await
+            .unwrap();
+        assert_eq!(weights.len(), 1);
+        assert_eq!(weights[0].id, weight.id);
+        assert_eq!(weights[0].value, 4.1);
+        assert_eq!(weights[0].created_at.date(), new_date);
+    }
+
+    #[ntex::test]
+    async fn test_get_all_user_pets_vaccines_keeps_latest_with_booster() {
+        let repo = setup_test_repo().await;
+        let owner_id = 1;
+        let pet_id = insert_user_with_pet(&repo, owner_id).await;
+        let external_id = link_pet_external_id(&repo, pet_id).await;
+        let date = |m| chrono::NaiveDate::from_ymd_opt(2024, m, 15).unwrap();
+
+        for (desc, month, booster_months) in [
+            ("Rabia", 1, Some(12)),
+            ("Rabia", 3, Some(12)),
+            ("Parvovirus", 2, None),
+        ] {
+            repo.insert_vaccine_to(
+                external_id,
+                owner_id,
+                desc.into(),
+                date(month),
+                booster_months,
+            )
+            .await
+            .unwrap();
+        }
+
+        let vaccines = repo.get_all_user_pets_vaccines(owner_id).await.unwrap();
+        assert_eq!(vaccines.len(), 1);
+        assert_eq!(vaccines[0].pet_id, pet_id);
+        assert_eq!(vaccines[0].description, "Rabia");
+        assert_eq!(vaccines[0].created_at.date(), date(3));
+        assert_eq!(vaccines[0].booster_months, 12);
+        assert!(!vaccines[0].announced_upcoming);
+
+        let recipients = repo.get_vaccine_digest_recipients().await.unwrap();
+        assert_eq!(recipients.len(), 1);
+        assert_eq!(recipients[0].user.id, owner_id);
+        assert!(recipients[0].channels.is_none());
+        assert!(recipients[0].user_timezone.is_none());
+
+        // a booster announced while upcoming is still listed to announce it overdue
+        repo.record_vaccine_digest_sent(
+            &[(vaccines[0].id, models::pet::BoosterStatus::Upcoming)],
+            Utc::now(),
+        )
+        .await
+        .unwrap();
+        let vaccines = repo.get_all_user_pets_vaccines(owner_id).await.unwrap();
+        assert_eq!(vaccines.len(), 1);
+        assert!(vaccines[0].announced_upcoming);
+        assert_eq!(repo.get_vaccine_digest_recipients().await.unwrap().len(), 1);
+
+        // but not once announced overdue
+        repo.record_vaccine_digest_sent(
+            &[(vaccines[0].id, models::pet::BoosterStatus::Overdue)],
+            Utc::now(),
+        )
+        .await
+        .unwrap();
+        assert!(
+            repo.get_all_user_pets_vaccines(owner_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            repo.get_vaccine_digest_recipients()
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[ntex::test]
+    async fn test_update_pet_health_record_vaccine() {
+        let repo = setup_test_repo().await;
+        let owner_id = 1;
+        let pet_id = insert_user_with_pet(&repo, owner_id).await;
+        let external_id = link_pet_external_id(&repo, pet_id).await;
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+
+        let vaccine = repo
+            .insert_vaccine_to(external_id, owner_id, "Rabai".into(), date, None)
+            .await
+            .unwrap();
+
+        // the record type is part of the match, a vaccine isn't a deworm
+        assert!(
+            repo.update_pet_health_record(
+                vaccine.id,
+                external_id,
+                owner_id,
+                models::pet::PetHealthType::Deworm,
+                "Rabia".into(),
+                date,
+            )
+            .await
+            .is_err()
+        );
+        repo.update_pet_health_record(
+            vaccine.id,
+            external_id,
+            owner_id,
+            models::pet::PetHealthType::Vaccine,
+            "Rabia".into(),
+            date,
+        )
+        .await
+        .unwrap();
+
+        let vaccines = repo
+            .get_pet_health_records(
+                external_id,
+                Some(owner_id),
+                models::pet::PetHealthType::Vaccine,
+            )
+            .await
+            .unwrap();
+        assert_eq!(vaccines.len(), 1);
+        assert_eq!(vaccines[0].id, vaccine.id);
+        assert_eq!(vaccines[0].description, "Rabia");
+    }
+
+    #[ntex::test]
+    async fn test_offer_pet_transfer_checks_ownership_and_recipient() {
+        let repo = setup_test_repo().await;
+        let (owner_id, other_user_id) = (1, 2);
+        let pet_id = insert_user_with_pet(&repo, owner_id).await;
+        insert_user_with_pet(&repo, other_user_id).await;
+
+        // only the owner can offer the pet
+        let result = repo
+            .offer_pet_transfer(pet_id, other_user_id, "user2@example.com")
+            .await;
+        assert!(matches!(result, Err(RepoError::NotFound)));
+
+        // the pet can't be offered to its owner
+        let result = repo
+            .offer_pet_transfer(pet_id, owner_id, "user1@example.com")
+            .await;
+        assert!(matches!(result, Err(RepoError::NotFound)));
+
+        // neither to an email without account, no account is created for it
+        let result = repo
+            .offer_pet_transfer(pet_id, owner_id, "new@example.com")
+            .await;
+        assert!(matches!(result, Err(RepoError::NotFound)));
+        assert!(
+            repo.get_user_app_by_email("new@example.com")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[ntex::test]
+    async fn test_pet_transfer_moves_pet_only_once_accepted() {
+        let repo = setup_test_repo().await;
+        let (owner_id, new_owner_id) = (1, 2);
+        let pet_id = insert_user_with_pet(&repo, owner_id).await;
+        insert_user_with_pet(&repo, new_owner_id).await;
+        let external_id = link_pet_external_id(&repo, pet_id).await;
+        sqlx::query("UPDATE pet_external_id SET owner_user_id = $2 WHERE external_id = $1;")
+            .bind(external_id.to_string())
+            .bind(owner_id)
+            .execute(&repo.db_pool)
+            .await
+            .unwrap();
+        repo.insert_new_pet_note(
+            owner_id,
+            &models::pet::PetNote {
+                id: 0,
+                pet_id,
+                title: "Dieta".into(),
+                content: "croquetas".into(),
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            },
+        )
+        .await
+        .unwrap();
+
+        repo.offer_pet_transfer(pet_id, owner_id, "user2@example.com")
+            .await
+            .unwrap();
+
+        // the offer alone changes nothing
+        assert_eq!(repo.count_user_pets(owner_id).await.unwrap(), 1);
+        let offers = repo.get_incoming_pet_transfers(new_owner_id).await.unwrap();
+        assert_eq!(offers.len(), 1);
+        assert_eq!(offers[0].pet_id, pet_id);
+        assert_eq!(offers[0].from_email, "user1@example.com");
+        assert!(
+            repo.get_incoming_pet_transfers(owner_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // only the recipient can accept it
+        assert!(matches!(
+            repo.accept_pet_transfer(pet_id, owner_id).await,
+            Err(RepoError::NotFound)
+        ));
+
+        repo.accept_pet_transfer(pet_id, new_owner_id)
+            .await
+            .unwrap();
+
+        assert_eq!(repo.count_user_pets(owner_id).await.unwrap(), 0);
+        assert_eq!(repo.count_user_pets(new_owner_id).await.unwrap(), 2);
+        assert_eq!(
+            repo.get_pet_notes(new_owner_id, pet_id, 10, 0)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        let new_owner = repo
+            .get_user_app_by_email("user2@example.com")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!new_owner.is_subscribed);
+
+        // the tag of the pet goes with it
+        let tag_owner_id = sqlx::query_scalar::<_, Option<i64>>(
+            "SELECT owner_user_id FROM pet_external_id WHERE external_id = $1;",
+        )
+        .bind(external_id.to_string())
+        .fetch_one(&repo.db_pool)
+        .await
+        .unwrap();
+        assert_eq!(tag_owner_id, Some(new_owner_id));
+
+        // the offer is consumed
+        assert!(
+            repo.get_incoming_pet_transfers(new_owner_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(matches!(
+            repo.accept_pet_transfer(pet_id, new_owner_id).await,
+            Err(RepoError::NotFound)
+        ));
+    }
+
+    #[ntex::test]
+    async fn test_cancel_pet_transfer() {
+        let repo = setup_test_repo().await;
+        let (owner_id, new_owner_id) = (1, 2);
+        let pet_id = insert_user_with_pet(&repo, owner_id).await;
+        insert_user_with_pet(&repo, new_owner_id).await;
+
+        // declined by the recipient
+        repo.offer_pet_transfer(pet_id, owner_id, "user2@example.com")
+            .await
+            .unwrap();
+        repo.cancel_pet_transfer(pet_id, new_owner_id)
+            .await
+            .unwrap();
+        assert!(matches!(
+            repo.accept_pet_transfer(pet_id, new_owner_id).await,
+            Err(RepoError::NotFound)
+        ));
+
+        // withdrawn by the owner
+        repo.offer_pet_transfer(pet_id, owner_id, "user2@example.com")
+            .await
+            .unwrap();
+        repo.cancel_pet_transfer(pet_id, owner_id).await.unwrap();
+        assert!(
+            repo.get_incoming_pet_transfers(new_owner_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(repo.count_user_pets(owner_id).await.unwrap(), 1);
+    }
+
+    #[test]
+    fn test_contains_like_pattern() {
+        assert_eq!(contains_like_pattern("pollo"), "%pollo%");
+        assert_eq!(contains_like_pattern("50%_off"), "%50\\%\\_off%");
+    }
+
+    #[ntex::test]
+    async fn test_insert_found_report_for_linked_pet() {
+        let repo = setup_test_repo().await;
+        let owner_id = 1;
+        let pet_id = insert_user_with_pet(&repo, owner_id).await;
+        let external_id = link_pet_external_id(&repo, pet_id).await;
+
+        repo.insert_found_report(
+            external_id,
+            "Esta en el parque".into(),
+            Some("5512345678".into()),
+            Utc::now(),
+        )
+        .await
+        .unwrap();
+        assert!(
+            repo.insert_found_report(Uuid::new_v4(), "Esta en el parque".into(), None, Utc::now())
+                .await
+                .is_err()
+        );
+
+        let reports: Vec<(i64, String, Option<String>)> =
+            sqlx::query_as("SELECT pet_id,message,callback_contact FROM found_report;")
+                .fetch_all(&repo.db_pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            reports,
+            vec![(
+                pet_id,
+                "Esta en el parque".into(),
+                Some("5512345678".into())
+            )]
+        );
+
+        let owner = repo
+            .get_pet_owner_by_external_id(external_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(owner.id, owner_id);
+
+        let found_reports = repo.get_pet_found_reports(pet_id, owner_id).await.unwrap();
+        assert_eq!(found_reports.len(), 1);
+        assert_eq!(found_reports[0].message, "Esta en el parque");
+        assert!(
+            repo.get_pet_found_reports(pet_id, 2)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[ntex::test]
+    async fn test_delete_reminders_for_pet_keeps_other_reminders() {
+        let repo = setup_test_repo().await;
+        let owner_id = 1;
+        let other_user_id = 2;
+        let pet_id = insert_user_with_pet(&repo, owner_id).await;
+        insert_user_with_pet(&repo, other_user_id).await;
+
+        for (execution_id, reminder_pet_id) in [("pet-reminder", Some(pet_id)), ("legacy", None)] {
+            repo.insert_user_remider(&models::reminder::Reminder {
+                user_app_id: owner_id,
+                body: "Refuerzo de vacuna".into(),
+                execution_id: execution_id.into(),
+                send_at: Utc::now() + chrono::TimeDelta::days(1),
+                user_timezone: "America/Mexico_City".into(),
+                pet_id: reminder_pet_id,
+                created_at: Utc::now(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        }
+
+        assert!(
+            repo.delete_reminders_for_pet(pet_id, other_user_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            repo.delete_reminders_for_pet(pet_id, owner_id)
+                .await
+                .unwrap(),
+            vec!["pet-reminder".to_string()]
+        );
+
+        let remaining: Vec<String> = repo
+            .get_active_user_remiders(owner_id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|reminder| reminder.execution_id)
+            .collect();
+        assert_eq!(remaining, vec!["legacy".to_string()]);
+    }
+
+    #[ntex::test]
+    async fn test_user_unlinked_external_ids() {
+        let repo = setup_test_repo().await;
+        let (owner_id, other_user_id) = (1, 2);
+        let pet_id = insert_user_with_pet(&repo, owner_id).await;
+        link_pet_external_id(&repo, pet_id).await;
+        insert_user_with_pet(&repo, other_user_id).await;
+
+        let (first_tag, second_tag, other_tag) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        for tag in [first_tag, second_tag] {
+            assert!(repo.assign_external_id_owner(tag, owner_id).await.unwrap());
+        }
+        assert!(
+            repo.assign_external_id_owner(other_tag, other_user_id)
+                .await
+                .unwrap()
+        );
+        // a tag handed out already can't be taken by someone else
+        assert!(
+            !repo
+                .assign_external_id_owner(other_tag, owner_id)
+                .await
+                .unwrap()
+        );
+
+        assert_eq!(
+            repo.get_user_unlinked_external_ids(owner_id).await.unwrap(),
+            vec![first_tag, second_tag]
+        );
+
+        // nor linked to a pet of someone else
+        let err = repo
+            .save_pet(&models::pet::Pet {
+                external_id: other_tag,
+                user_app_id: owner_id,
+                pet_name: "Intruso".into(),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<RepoError>(),
+            Some(RepoError::Conflict)
+        ));
+        assert_eq!(
+            repo.get_user_unlinked_external_ids(other_user_id)
+                .await
+                .unwrap(),
+            vec![other_tag]
+        );
+
+        // linking a pet to a tag moves it out of the unlinked ones
+        repo.save_pet(&models::pet::Pet {
+            external_id: first_tag,
+            user_app_id: owner_id,
+            pet_name: "Firulais".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            repo.get_user_unlinked_external_ids(owner_id).await.unwrap(),
+            vec![second_tag]
+        );
+        assert_eq!(
+            repo.get_user_unlinked_external_ids(other_user_id)
+                .await
+                .unwrap(),
+            vec![other_tag]
+        );
+    }
+
+    #[ntex::test]
+    async fn test_reminder_templates_are_per_user() {
+        let repo = setup_test_repo().await;
+        let (owner_id, other_user_id) = (1, 2);
+        insert_user_with_pet(&repo, owner_id).await;
+        insert_user_with_pet(&repo, other_user_id).await;
+
+        let vaccine_id = repo
+            .insert_reminder_template(owner_id, "vacuna", "Vacuna de {pet_name} el {date}")
+            .await
+            .unwrap();
+        repo.insert_reminder_template(owner_id, "baño", "Bañar a {pet_name}")
+            .await
+            .unwrap();
+
+        let templates = repo.get_reminder_templates(owner_id).await.unwrap();
+        assert_eq!(
+            templates
+                .iter()
+                .map(|template| template.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["baño", "vacuna"]
+        );
+        assert!(
+            repo.get_reminder_templates(other_user_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // other users can't delete it
+        repo.delete_reminder_template(vaccine_id, other_user_id)
+            .await
+            .unwrap();
+        assert_eq!(
+            repo.get_reminder_templates(owner_id).await.unwrap().len(),
+            2
+        );
+
+        repo.delete_reminder_template(vaccine_id, owner_id)
+            .await
+            .unwrap();
+        assert_eq!(
+            repo.get_reminder_templates(owner_id).await.unwrap().len(),
+            1
+        );
+    }
+
+    #[ntex::test]
+    async fn test_reminder_delivery_status_keeps_latest_update() {
+        use models::reminder::DeliveryStatus;
+
+        let repo = setup_test_repo().await;
+        let (owner_id, other_user_id) = (1, 2);
+        insert_user_with_pet(&repo, owner_id).await;
+        insert_user_with_pet(&repo, other_user_id).await;
+
+        let reminder_id = repo
+            .insert_user_remider(&models::reminder::Reminder {
+                user_app_id: owner_id,
+                body: "Desparasitar".into(),
+                execution_id: "execution-id".into(),
+                send_at: Utc::now(),
+                user_timezone: "America/Mexico_City".into(),
+                created_at: Utc::now(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let sent_at = Utc::now();
+
+        // the webhook may report the message before it is linked to the reminder
+        repo.upsert_message_delivery_status(
+            "wamid.1",
+            DeliveryStatus::Delivered,
+            sent_at + chrono::TimeDelta::seconds(2),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            repo.get_reminder_delivery_status(reminder_id, owner_id)
+                .await
+                .unwrap(),
+            None
+        );
+
+        repo.link_message_delivery_to_reminder("wamid.1", reminder_id)
+            .await
+            .unwrap();
+        assert_eq!(
+            repo.get_reminder_delivery_status(reminder_id, owner_id)
+                .await
+                .unwrap(),
+            Some(DeliveryStatus::Delivered)
+        );
+
+        // a late "sent" status doesn't overwrite the newer one
+        repo.upsert_message_delivery_status("wamid.1", DeliveryStatus::Sent, sent_at)
+            .await
+            .unwrap();
+        repo.upsert_message_delivery_status(
+            "wamid.1",
+            DeliveryStatus::Read,
+            sent_at + chrono::TimeDelta::seconds(5),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            repo.get_reminder_delivery_status(reminder_id, owner_id)
+                .await
+                .unwrap(),
+            Some(DeliveryStatus::Read)
+        );
+        assert_eq!(
+            repo.get_reminder_delivery_status(reminder_id, other_user_id)
+                .await
+                .unwrap(),
+            None
+        );
+
+        // linked before any status arrives
+        repo.link_message_delivery_to_reminder("wamid.2", reminder_id)
+            .await
+            .unwrap();
+        repo.upsert_message_delivery_status("wamid.2", DeliveryStatus::Failed, sent_at)
+            .await
+            .unwrap();
+        let statuses: Vec<DeliveryStatus> = sqlx::query_scalar(
+            "SELECT status FROM message_delivery WHERE reminder_id=$1 ORDER BY wamid;",
+        )
+        .bind(reminder_id)
+        .fetch_all(&repo.db_pool)
+        .await
+        .unwrap();
+        assert_eq!(statuses, vec![DeliveryStatus::Read, DeliveryStatus::Failed]);
+    }
+
+    #[ntex::test]
+    async fn test_count_users_and_subscribed_users() {
+        let repo = setup_test_repo().await;
+        insert_user_with_pet(&repo, 1).await;
+        insert_user_with_pet(&repo, 2).await;
+        repo.set_user_as_subscribed(2).await.unwrap();
+
+        assert_eq!(repo.count_users().await.unwrap(), 2);
+        assert_eq!(repo.count_subscribed_users().await.unwrap(), 1);
+    }
+
+    #[ntex::test]
+    async fn test_count_pets() {
+        let repo = setup_test_repo().await;
+        assert_eq!(repo.count_pets().await.unwrap(), 0);
+
+        insert_user_with_pet(&repo, 1).await;
+        insert_user_with_pet(&repo, 2).await;
+
+        assert_eq!(repo.count_pets().await.unwrap(), 2);
+    }
+
+    #[ntex::test]
+    async fn test_count_approved_payments_since() {
+        let repo = setup_test_repo().await;
+        insert_user_with_pet(&repo, 1).await;
+        let now = Utc::now();
+
+        let payments = [
+            (1, models::payment::PaymentStatus::Approved, now),
+            (2, models::payment::PaymentStatus::Initiated, now),
+            (
+                3,
+                models::payment::PaymentStatus::Approved,
+                now - chrono::TimeDelta::days(45),
+            ),
+        ];
+        for (mp_paym_id, status, created_at) in payments {
+            repo.save_subs_payment(&models::payment::Payment {
+                user_id: 1,
+                mp_paym_id,
+                payment_idempotency_h: format!("hash-{mp_paym_id}"),
+                transaction_amount: "100.00".into(),
+                currency: "MXN".into(),
+                installments: 1,
+                payment_method_id: "visa".into(),
+                issuer_id: "1".into(),
+                status,
+                created_at,
+                updated_at: created_at,
+            })
+            .await
+            .unwrap();
+        }
+
+        assert_eq!(
+            repo.count_approved_payments_since(now - chrono::TimeDelta::days(30))
+                .await
+                .unwrap(),
+            1
+        );
+    }
+
+    async fn insert_test_contacts(repo: &SqlxSqliteRepo, user_id: i64, count: usize) -> Vec<i64> {
+        let mut ids = Vec::new();
+        for i in 0..count {
+            let contact = repo
+                .insert_owner_contact(
+                    user_id,
+                    format!("Contacto {i}"),
+                    format!("55512340{user_id}{i}"),
+                    models::user_app::ContactType::Phone,
+                    None,
+                    None,
+                )
+                .await
+                .unwrap();
+            ids.push(contact.id);
+        }
+        ids
+    }
+
+    #[ntex::test]
+    async fn test_owner_contact_home_area_is_optional() {
+        let repo = setup_test_repo().await;
+        insert_user_with_pet(&repo, 1).await;
+        insert_test_contacts(&repo, 1, 1).await;
+        repo.insert_owner_contact(
+            1,
+            "Casa".into(),
+            "5551234567".into(),
+            models::user_app::ContactType::Phone,
+            Some("Col. Roma, CDMX".into()),
+            Some((19.42, -99.16)),
+        )
+        .await
+        .unwrap();
+
+        let contacts = repo.get_owner_contacts(1).await.unwrap();
+        let home = contacts.iter().find(|c| c.full_name == "Casa").unwrap();
+        assert_eq!(home.address.as_deref(), Some("Col. Roma, CDMX"));
+        assert_eq!((home.latitude, home.longitude), (Some(19.42), Some(-99.16)));
+
+        let other = contacts.iter().find(|c| c.full_name != "Casa").unwrap();
+        assert_eq!((other.address.as_deref(), other.latitude), (None, None));
+    }
+
+    #[ntex::test]
+    async fn test_insert_owner_contact_twice_keeps_one_row() {
+        let repo = setup_test_repo().await;
+        insert_user_with_pet(&repo, 1).await;
+
+        let insert = |value: &str| {
+            repo.insert_owner_contact(
+                1,
+                "Casa".into(),
+                value.into(),
+                models::user_app::ContactType::Phone,
+                None,
+                None,
+            )
+        };
+        let first = insert("55 5123-4567").await.unwrap();
+        let repeated = insert("(55)51234567").await.unwrap();
+
+        assert_eq!(repeated.id, first.id);
+        assert_eq!(insert("55 5123-4567").await.unwrap().id, first.id);
+        assert_eq!(repeated.contact_value, "55 5123-4567");
+        assert_eq!(repo.get_owner_contacts(1).await.unwrap().len(), 1);
+
+        // emails ignore case
+        let email = insert("Owner@Example.com").await.unwrap();
+        assert_eq!(insert("owner@example.com ").await.unwrap().id, email.id);
+        assert_eq!(repo.get_owner_contacts(1).await.unwrap().len(), 2);
+    }
+
+    #[ntex::test]
+    async fn test_set_primary_contact_keeps_a_single_primary() {
+        let repo = setup_test_repo().await;
+        insert_user_with_pet(&repo, 1).await;
+        insert_user_with_pet(&repo, 2).await;
+        let ids = insert_test_contacts(&repo, 1, 3).await;
+        let other_user_ids = insert_test_contacts(&repo, 2, 1).await;
+        repo.set_primary_contact(2, other_user_ids[0])
+            .await
+            .unwrap();
+
+        for contact_id in [ids[1], ids[2], ids[0], ids[2]] {
+            repo.set_primary_contact(1, contact_id).await.unwrap();
+        }
+        // contacts of other users can't be set, the current primary is kept
+        assert!(
+            repo.set_primary_contact(1, other_user_ids[0])
+                .await
+                .is_err()
+        );
+
+        let contacts = repo.get_owner_contacts(1).await.unwrap();
+        let primary_ids: Vec<i64> = contacts
+            .iter()
+            .filter(|contact| contact.is_primary)
+            .map(|contact| contact.id)
+            .collect();
+        assert_eq!(primary_ids, vec![ids[2]]);
+        assert_eq!(contacts[0].id, ids[2]);
+
+        let other_user_contacts = repo.get_owner_contacts(2).await.unwrap();
+        assert!(other_user_contacts[0].is_primary);
+    }
+
+    #[ntex::test]
+    async fn test_reorder_contacts_lists_primary_first() {
+        let repo = setup_test_repo().await;
+        let pet_id = insert_user_with_pet(&repo, 1).await;
+        let pet_external_id = link_pet_external_id(&repo, pet_id).await;
+        let ids = insert_test_contacts(&repo, 1, 3).await;
+
+        repo.reorder_contacts(1, &[ids[2], ids[0], ids[1]])
+            .await
+            .unwrap();
+        repo.set_primary_contact(1, ids[1]).await.unwrap();
+
+        let public_ids: Vec<i64> = repo
+            .get_pet_owner_contacts(pet_external_id)
+            .await
+            .unwrap()
+            .iter()
+            .map(|contact| contact.id)
+            .collect();
+        assert_eq!(public_ids, vec![ids[1], ids[2], ids[0]]);
+    }
+
+    #[ntex::test]
+    async fn test_claim_pet_creation_token_only_once() {
+        let repo = setup_test_repo().await;
+        insert_user_with_pet(&repo, 1).await;
+        let token = Uuid::new_v4();
+
+        assert!(repo.claim_pet_creation_token(1, token).await.unwrap());
+        assert!(!repo.claim_pet_creation_token(1, token).await.unwrap());
+
+        // a released token can be claimed again
+        repo.release_pet_creation_token(1, token).await.unwrap();
+        assert!(repo.claim_pet_creation_token(1, token).await.unwrap());
+    }
+
+    #[ntex::test]
+    async fn test_get_pet_missing_is_not_found() {
+        let repo = setup_test_repo().await;
+        let pet_id = insert_user_with_pet(&repo, 1).await;
+        link_pet_external_id(&repo, pet_id).await;
+        insert_user_with_pet(&repo, 2).await;
+
+        assert!(repo.get_pet_by_id(pet_id, 1).await.is_ok());
+        // pets of other users are not found either
+        assert!(matches!(
+            repo.get_pet_by_id(pet_id, 2).await,
+            Err(RepoError::NotFound)
+        ));
+        assert!(matches!(
+            repo.get_pet_by_external_id(Uuid::new_v4()).await,
+            Err(RepoError::NotFound)
+        ));
+    }
+
+    #[ntex::test]
+    async fn test_get_pets_by_external_ids_omits_unknown_ids() {
+        let repo = setup_test_repo().await;
+        let first_pet_id = insert_user_with_pet(&repo, 1).await;
+        let second_pet_id = insert_user_with_pet(&repo, 2).await;
+        let first_external_id = link_pet_external_id(&repo, first_pet_id).await;
+        let second_external_id = link_pet_external_id(&repo, second_pet_id).await;
+        repo.insert_pet_weight(
+            first_external_id,
+            1,
+            12.5,
+            chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        )
+        .await
+        .unwrap();
+
+        // exists but isn't linked to a pet
+        let unlinked_external_id = Uuid::new_v4();
+        sqlx::query("INSERT INTO pet_external_id(external_id) VALUES($1);")
+            .bind(unlinked_external_id.to_string())
+            .execute(&repo.db_pool)
+            .await
+            .unwrap();
+
+        let mut pets = repo
+            .get_pets_by_external_ids(&[
+                first_external_id,
+                Uuid::new_v4(),
+                unlinked_external_id,
+                second_external_id,
+            ])
+            .await
+            .unwrap();
+        pets.sort_by_key(|pet| pet.id);
+
+        assert_eq!(pets.len(), 2);
+        assert_eq!(pets[0].id, first_pet_id);
+        assert_eq!(pets[0].external_id, first_external_id);
+        assert_eq!(pets[0].last_weight, Some(12.5));
+        assert_eq!(pets[1].external_id, second_external_id);
+
+        assert!(repo.get_pets_by_external_ids(&[]).await.unwrap().is_empty());
+    }
+
+    #[ntex::test]
+    async fn test_get_pet_by_external_id_for_user_enforces_access() {
+        let repo = setup_test_repo().await;
+        let (owner_id, collaborator_id, stranger_id) = (1, 2, 3);
+        let pet_id = insert_user_with_pet(&repo, owner_id).await;
+        insert_user_with_pet(&repo, collaborator_id).await;
+        insert_user_with_pet(&repo, stranger_id).await;
+        let external_id = link_pet_external_id(&repo, pet_id).await;
+
+        let pet = repo
+            .get_pet_by_external_id_for_user(external_id, owner_id)
+            .await
+            .unwrap();
+        assert_eq!(pet.id, pet_id);
+
+        assert!(matches!(
+            repo.get_pet_by_external_id_for_user(external_id, stranger_id)
+                .await,
+            Err(RepoError::NotFound)
+        ));
+        assert!(matches!(
+            repo.get_pet_by_external_id_for_user(Uuid::new_v4(), owner_id)
+                .await,
+            Err(RepoError::NotFound)
+        ));
+
+        repo.grant_pet_access(
+            pet_id,
+            owner_id,
+            "user2@example.com",
+            models::pet::PetAccessRole::Viewer,
+        )
+        .await
+        .unwrap();
+        assert!(
+            repo.get_pet_by_external_id_for_user(external_id, collaborator_id)
+                .await
+                .is_ok()
+        );
+        assert!(
+            repo.get_pet_by_external_id_for_user(external_id, stranger_id)
+                .await
+                .is_err()
+        );
+    }
+
+    #[ntex::test]
+    async fn test_get_all_pets_user_id_breaks_created_at_ties_by_id() {
+        let repo = setup_test_repo().await;
+        let first_pet_id = insert_user_with_pet(&repo, 1).await;
+        let second_pet_id = sqlx::query(
+            "INSERT INTO pet(user_app_id,pet_name,birthday,breed,about,is_female,is_lost,is_spaying_neutering)
+            VALUES(1,'Max','2021-01-01','Mestizo','',0,0,0);",
+        )
+        .execute(&repo.db_pool)
+        .await
+        .unwrap()
+        .last_insert_rowid();
+        link_pet_external_id(&repo, first_pet_id).await;
+        link_pet_external_id(&repo, second_pet_id).await;
+
+        // pets created in the same second, e.g. by a bulk import
+        sqlx::query("UPDATE pet SET created_at='2024-01-01 10:00:00' WHERE user_app_id=1;")
+            .execute(&repo.db_pool)
+            .await
+            .unwrap();
+
+        for _ in 0..3 {
+            let pet_ids: Vec<i64> = repo
+                .get_all_pets_user_id(1)
+                .await
+                .unwrap()
+                .iter()
+                .map(|pet| pet.id)
+                .collect();
+            assert_eq!(pet_ids, vec![second_pet_id, first_pet_id]);
+        }
+    }
+
+    #[ntex::test]
+    async fn test_get_scan_stats_excludes_owner_views() {
+        let repo = setup_test_repo().await;
+        let pet_id = insert_user_with_pet(&repo, 1).await;
+        insert_user_with_pet(&repo, 2).await;
+        let external_id = link_pet_external_id(&repo, pet_id).await;
+
+        repo.insert_pet_scan(external_id, None, Some("Mozilla/5.0".into()))
+            .await
+            .unwrap();
+        repo.insert_pet_scan(external_id, None, None).await.unwrap();
+        repo.insert_pet_scan(external_id, Some(2), None)
+            .await
+            .unwrap();
+        // the owner's own views are not counted
+        repo.insert_pet_scan(external_id, Some(1), None)
+            .await
+            .unwrap();
+        // unlinked external ids are not recorded
+        repo.insert_pet_scan(Uuid::new_v4(), None, None)
+            .await
+            .unwrap();
+
+        sqlx::query("INSERT INTO pet_scan(external_id,scanned_at) VALUES($1,$2);")
+            .bind(external_id.to_string())
+            .bind(Utc::now() - chrono::Duration::days(consts::PET_SCAN_RECENT_DAYS + 3))
+            .execute(&repo.db_pool)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            repo.get_scan_stats(external_id, 1).await.unwrap(),
+            models::pet::PetScanStats {
+                total: 4,
+                last_7_days: 3,
+            }
+        );
+        // only the owner gets the stats
+        assert_eq!(
+            repo.get_scan_stats(external_id, 2).await.unwrap(),
+            models::pet::PetScanStats::default()
+        );
+
+        let total_rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pet_scan;")
+            .fetch_one(&repo.db_pool)
+            .await
+            .unwrap();
+        assert_eq!(total_rows, 4);
+    }
+
+    #[ntex::test]
+    async fn test_get_pet_qr_caption() {
+        let repo = setup_test_repo().await;
+        let pet_id = insert_user_with_pet(&repo, 1).await;
+        let external_id = link_pet_external_id(&repo, pet_id).await;
+
+        let caption = repo.get_pet_qr_caption(external_id).await.unwrap().unwrap();
+        assert_eq!(caption.pet_name, "Buddy");
+        assert_eq!(caption.breed, "Mestizo");
+        assert!(
+            repo.get_pet_qr_caption(Uuid::new_v4())
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[ntex::test]
+    async fn test_pet_scans_are_purged_by_age_and_with_the_owner() {
+        let repo = setup_test_repo().await;
+        let pet_id = insert_user_with_pet(&repo, 1).await;
+        let external_id = link_pet_external_id(&repo, pet_id).await;
+        let other_pet_id = insert_user_with_pet(&repo, 2).await;
+        let other_external_id = link_pet_external_id(&repo, other_pet_id).await;
+
+        for (external_id, days_ago) in [
+            (external_id, 1),
+            (external_id, consts::PET_SCAN_RETENTION_DAYS + 1),
+            (other_external_id, 1),
+        ] {
+            sqlx::query("INSERT INTO pet_scan(external_id,scanned_at) VALUES($1,$2);")
+                .bind(external_id.to_string())
+                .bind(Utc::now() - chrono::Duration::days(days_ago))
+                .execute(&repo.db_pool)
+                .await
+                .unwrap();
+        }
+
+        let deleted = repo
+            .delete_pet_scans_before(
+                Utc::now() - chrono::Duration::days(consts::PET_SCAN_RETENTION_DAYS),
+            )
+            .await
+            .unwrap();
+        assert_eq!(deleted, 1);
+
+        // deleting an account takes the visits to its pets along
+        repo.remove_user_app_data(1).await.unwrap();
+        let scanned: Vec<String> = sqlx::query_scalar("SELECT external_id FROM pet_scan;")
+            .fetch_all(&repo.db_pool)
+            .await
+            .unwrap();
+        assert_eq!(scanned, vec![other_external_id.to_string()]);
+    }
+
+    #[ntex::test]
+    async fn test_set_pet_public_pin_only_by_owner() {
+        let repo = setup_test_repo().await;
+        let pet_id = insert_user_with_pet(&repo, 1).await;
+        insert_user_with_pet(&repo, 2).await;
+        let external_id = link_pet_external_id(&repo, pet_id).await;
+
+        assert_eq!(repo.get_pet_public_pin(external_id).await.unwrap(), None);
+
+        repo.set_pet_public_pin(pet_id, 1, Some("hash".into()))
+            .await
+            .unwrap();
+        assert_eq!(
+            repo.get_pet_public_pin(external_id).await.unwrap(),
+            Some("hash".to_string())
+        );
+
+        // other users can't change it
+        assert!(repo.set_pet_public_pin(pet_id, 2, None).await.is_err());
+
+        repo.set_pet_public_pin(pet_id, 1, None).await.unwrap();
+        assert_eq!(repo.get_pet_public_pin(external_id).await.unwrap(), None);
+        assert_eq!(repo.get_pet_public_pin(Uuid::new_v4()).await.unwrap(), None);
+    }
+
+    #[ntex::test]
+    async fn test_failed_pet_pin_attempts_block_after_max() {
+        let repo = setup_test_repo().await;
+        let pet_id = insert_user_with_pet(&repo, 1).await;
+        let other_pet_id = insert_user_with_pet(&repo, 2).await;
+        let external_id = link_pet_external_id(&repo, pet_id).await;
+        let other_external_id = link_pet_external_id(&repo, other_pet_id).await;
+        let locked_until = chrono::DateTime::parse_from_rfc3339("2024-06-15T13:00:00Z")
+            .unwrap()
+            .to_utc();
+
+        assert_eq!(
+            repo.get_pet_pin_locked_until(external_id).await.unwrap(),
+            None
+        );
+
+        for _ in 0..2 {
+            repo.record_failed_pet_pin_attempt(external_id, 3, locked_until)
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            repo.get_pet_pin_locked_until(external_id).await.unwrap(),
+            None
+        );
+
+        // a right PIN starts the count over
+        repo.clear_failed_pet_pin_attempts(external_id)
+            .await
+            .unwrap();
+        for _ in 0..2 {
+            repo.record_failed_pet_pin_attempt(external_id, 3, locked_until)
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            repo.get_pet_pin_locked_until(external_id).await.unwrap(),
+            None
+        );
+
+        repo.record_failed_pet_pin_attempt(external_id, 3, locked_until)
+            .await
+            .unwrap();
+        assert_eq!(
+            repo.get_pet_pin_locked_until(external_id).await.unwrap(),
+            Some(locked_until)
+        );
+        // the count is per pet
+        assert_eq!(
+            repo.get_pet_pin_locked_until(other_external_id)
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[ntex::test]
+    async fn test_delete_all_health_records_is_scoped_to_pet_and_type() {
+        let repo = setup_test_repo().await;
+        let (owner_id, other_user_id) = (1, 2);
+        let pet_id = insert_user_with_pet(&repo, owner_id).await;
+        let other_pet_id = insert_user_with_pet(&repo, other_user_id).await;
+        let external_id = link_pet_external_id(&repo, pet_id).await;
+        let other_external_id = link_pet_external_id(&repo, other_pet_id).await;
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+
+        for (ext_id, user_id) in [(external_id, owner_id), (other_external_id, other_user_id)] {
+            for desc in ["Rabia", "Parvovirus"] {
+                repo.insert_vaccine_to(ext_id, user_id, desc.into(), date, None)
+                    .await
+                    .unwrap();
+            }
+            repo.insert_deworm_to(ext_id, user_id, "Drontal".into(), date)
+                .await
+                .unwrap();
+            repo.insert_pet_weight(ext_id, user_id, 4.1, date)
+                .await
+                .unwrap();
+        }
+
+        // someone else's pet is left untouched
+        assert_eq!(
+            repo.delete_all_health_records(
+                external_id,
+                other_user_id,
+                models::pet::PetHealthType::Vaccine
+            )
+            .await
+            .unwrap(),
+            0
+        );
+        assert_eq!(
+            repo.delete_all_weights(external_id, other_user_id)
+                .await
+                .unwrap(),
+            0
+        );
+
+        assert_eq!(
+            repo.delete_all_health_records(
+                external_id,
+                owner_id,
+                models::pet::PetHealthType::Vaccine
+            )
+            .await
+            .unwrap(),
+            2
+        );
+
+        assert!(
+            repo.get_pet_health_records(
+                external_id,
+                Some(owner_id),
+                models::pet::PetHealthType::Vaccine
+            )
+            .await
+            .unwrap()
+            .is_empty()
+        );
+        assert_eq!(
+            repo.get_pet_health_records(
+                external_id,
+                Some(owner_id),
+                models::pet::PetHealthType::Deworm
+            )
+            .await
+            .unwrap()
+            .len(),
+            1
+        );
+        assert_eq!(
+            repo.get_pet_health_records(
+                other_external_id,
+                Some(other_user_id),
+                models::pet::PetHealthType::Vaccine
+            )
+            .await
+            .unwrap()
+            .len(),
+            2
+        );
+
+        assert_eq!(
+            repo.delete_all_weights(external_id, owner_id)
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(
+            repo.get_pet_weights(external_id, Some(owner_id))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            repo.get_pet_weights(other_external_id, Some(other_user_id))
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[ntex::test]
+    async fn test_pet_share_token_expiry_and_revocation() {
+        let repo = setup_test_repo().await;
+        let (owner_id, other_user_id) = (1, 2);
+        let pet_id = insert_user_with_pet(&repo, owner_id).await;
+        insert_user_with_pet(&repo, other_user_id).await;
+        let external_id = link_pet_external_id(&repo, pet_id).await;
+        let tomorrow = Utc::now() + chrono::Duration::days(1);
+
+        // only the owner can share the pet
+        assert!(
+            repo.insert_pet_share_token(pet_id, other_user_id, "stolen", tomorrow)
+                .await
+                .is_err()
+        );
+
+        repo.insert_pet_share_token(pet_id, owner_id, "active", tomorrow)
+            .await
+            .unwrap();
+        repo.insert_pet_share_token(
+            pet_id,
+            owner_id,
+            "expired",
+            Utc::now() - chrono::Duration::minutes(1),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            repo.get_pet_external_id_by_share_token("active")
+                .await
+                .unwrap(),
+            Some(external_id)
+        );
+        assert_eq!(
+            repo.get_pet_external_id_by_share_token("expired")
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            repo.get_pet_external_id_by_share_token("stolen")
+                .await
+                .unwrap(),
+            None
+        );
+
+        let share_tokens = repo.get_pet_share_tokens(pet_id, owner_id).await.unwrap();
+        assert_eq!(share_tokens.len(), 1);
+        assert_eq!(share_tokens[0].token, "active");
+        assert!(
+            repo.get_pet_share_tokens(pet_id, other_user_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // someone else can't revoke the link
+        repo.delete_pet_share_token(pet_id, other_user_id, "active")
+            .await
+            .unwrap();
+        assert!(
+            repo.get_pet_external_id_by_share_token("active")
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        repo.delete_pet_share_token(pet_id, owner_id, "active")
+            .await
+            .unwrap();
+        assert_eq!(
+            repo.get_pet_external_id_by_share_token("active")
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[ntex::test]
+    async fn test_concurrent_pet_balance_decrements_stop_at_zero() {
+        let repo = setup_test_repo().await;
+        let user_id = 1;
+        insert_user_with_pet(&repo, user_id).await;
+        sqlx::query("INSERT INTO add_pet_balance(user_id, balance) VALUES($1, 3);")
+            .bind(user_id)
+            .execute(&repo.db_pool)
+            .await
+            .unwrap();
+
+        let results =
+            futures::future::join_all((0..10).map(|_| repo.decrement_pet_balance(user_id))).await;
+
+        let mut balances_left = results
+            .iter()
+            .filter_map(|result| result.as_ref().ok().copied())
+            .collect::<Vec<_>>();
+        balances_left.sort();
+        assert_eq!(balances_left, vec![0, 1, 2]);
+        assert!(
+            results
+                .iter()
+                .filter_map(|result| result.as_ref().err())
+                .all(RepoError::is_not_found)
+        );
+        assert_eq!(repo.get_pet_balance(user_id).await.unwrap(), 0);
+    }
+
+    #[ntex::test]
+    async fn test_create_pet_and_charge_rolls_back_on_failure() {
+        let repo = setup_test_repo().await;
+        let user_id = 1;
+        sqlx::query("INSERT INTO user_app(id,email) VALUES($1,'a@b.com');")
+            .bind(user_id)
+            .execute(&repo.db_pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO add_pet_balance(user_id, balance) VALUES($1, 1);")
+            .bind(user_id)
+            .execute(&repo.db_pool)
+            .await
+            .unwrap();
+        let pet = models::pet::Pet {
+            external_id: Uuid::new_v4(),
+            user_app_id: user_id,
+            pet_name: "Buddy".to_string(),
+            ..Default::default()
+        };
+
+        // the last step (subscription flag) fails after the others ran
+        sqlx::query(
+            "CREATE TRIGGER fail_subscription BEFORE UPDATE OF is_subscribed ON user_app
+            BEGIN SELECT RAISE(ABORT, 'injected failure'); END;",
+        )
+        .execute(&repo.db_pool)
+        .await
+        .unwrap();
+
+        assert!(
+            repo.create_pet_and_charge(&pet, user_id, true)
+                .await
+                .is_err()
+        );
+        assert_eq!(repo.get_pet_balance(user_id).await.unwrap(), 1);
+        assert_eq!(repo.count_user_pets(user_id).await.unwrap(), 0);
+        assert_eq!(
+            repo.is_pet_external_id_linked(&pet.external_id)
+                .await
+                .unwrap(),
+            None
+        );
+
+        sqlx::query("DROP TRIGGER fail_subscription;")
+            .execute(&repo.db_pool)
+            .await
+            .unwrap();
+
+        let pet_id = repo
+            .create_pet_and_charge(&pet, user_id, true)
+            .await
+            .unwrap();
+        assert_eq!(repo.get_pet_balance(user_id).await.unwrap(), 0);
+        assert_eq!(
+            repo.is_pet_external_id_linked(&pet.external_id)
+                .await
+                .unwrap(),
+            Some(true)
+        );
+        assert!(repo.get_pet_by_id(pet_id, user_id).await.is_ok());
+
+        // with the balance spent nothing else is created
+        let other_pet = models::pet::Pet {
+            external_id: Uuid::new_v4(),
+            ..pet
+        };
+        let result = repo.create_pet_and_charge(&other_pet, user_id, true).await;
+        assert!(result.is_err_and(|e| RepoError::is_not_found(&e)));
+        assert_eq!(repo.count_user_pets(user_id).await.unwrap(), 1);
+    }
+
+    #[ntex::test]
+    async fn test_update_pet_writes_audit_rows() {
+        let repo = setup_test_repo().await;
+        let owner_id = 1;
+        let pet_id = insert_user_with_pet(&repo, owner_id).await;
+        link_pet_external_id(&repo, pet_id).await;
+
+        let mut pet = repo.get_pet_by_id(pet_id, owner_id).await.unwrap();
+        pet.pet_name = "Max".into();
+        repo.update_pet(&pet).await.unwrap();
+
+        let audit = repo.get_pet_audit(pet_id, owner_id).await.unwrap();
+        assert_eq!(audit.len(), 1);
+        assert_eq!(audit[0].field, "pet_name");
+        assert_eq!(audit[0].action, models::pet::PetAuditAction::Update);
+        assert_eq!(audit[0].email, "user1@example.com");
+
+        // Saving without changes adds nothing, and only the owner sees the log
+        repo.update_pet(&pet).await.unwrap();
+        assert_eq!(repo.get_pet_audit(pet_id, owner_id).await.unwrap().len(), 1);
+        assert!(repo.get_pet_audit(pet_id, 2).await.unwrap().is_empty());
+    }
+
+    #[ntex::test]
+    async fn test_merge_pets_moves_records_and_deletes_duplicate() {
+        let repo = setup_test_repo().await;
+        let owner_id = 1;
+        let primary_id = insert_user_with_pet(&repo, owner_id).await;
+        let duplicate_id = sqlx::query(
+            "INSERT INTO pet(user_app_id,pet_name,birthday,breed,about,is_female,is_lost,is_spaying_neutering,pic)
+            VALUES($1,'Buddy','2020-01-01','Mestizo','',0,0,0,'pics/buddy');",
+        )
+        .bind(owner_id)
+        .execute(&repo.db_pool)
+        .await
+        .unwrap()
+        .last_insert_rowid();
+        let external_id = link_pet_external_id(&repo, duplicate_id).await;
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+
+        repo.insert_vaccine_to(external_id, owner_id, "Rabia".into(), date, None)
+            .await
+            .unwrap();
+        repo.insert_pet_weight(external_id, owner_id, 4.1, date)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO pet_note(pet_id,title,content) VALUES($1,'Dieta','pollo');")
+            .bind(duplicate_id)
+            .execute(&repo.db_pool)
+            .await
+            .unwrap();
+
+        let pic_not_moved = repo
+            .merge_pets(primary_id, duplicate_id, owner_id)
+            .await
+            .unwrap();
+        assert_eq!(pic_not_moved, None);
+
+        // the QR tag now resolves to the primary pet, with every record
+        let pet = repo.get_pet_by_id(primary_id, owner_id).await.unwrap();
+        assert_eq!(pet.external_id, external_id);
+        assert_eq!(pet.pic.as_deref(), Some("pics/buddy"));
+        assert_eq!(
+            repo.get_pet_health_records(
+                external_id,
+                Some(owner_id),
+                models::pet::PetHealthType::Vaccine
+            )
+            .await
+            .unwrap()
+            .len(),
+            1
+        );
+        assert_eq!(
+            repo.get_pet_weights(external_id, Some(owner_id))
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(matches!(
+            repo.get_pet_by_id(duplicate_id, owner_id).await,
+            Err(RepoError::NotFound)
+        ));
+
+        for table in ["pet_health", "pet_weight", "pet_note", "pet_linked"] {
+            let orphans = sqlx::query_scalar::<_, i64>(&format!(
+                "SELECT COUNT(*) FROM {table} WHERE pet_id IS NULL OR pet_id NOT IN (SELECT id FROM pet);"
+            ))
+            .fetch_one(&repo.db_pool)
+            .await
+            .unwrap();
+            assert_eq!(orphans, 0, "{table}");
+
+            let moved = sqlx::query_scalar::<_, i64>(&format!(
+                "SELECT COUNT(*) FROM {table} WHERE pet_id = $1;"
+            ))
+            .bind(primary_id)
+            .fetch_one(&repo.db_pool)
+            .await
+            .unwrap();
+            assert_eq!(moved, 1, "{table}");
+        }
+    }
+
+    #[ntex::test]
+    async fn test_merge_linked_pets_blocks_duplicate_tag() {
+        let repo = setup_test_repo().await;
+        let (owner_id, collaborator_id) = (1, 2);
+        let primary_id = insert_user_with_pet(&repo, owner_id).await;
+        insert_user_with_pet(&repo, collaborator_id).await;
+        let duplicate_id = sqlx::query(
+            "INSERT INTO pet(user_app_id,pet_name,birthday,breed,about,is_female,is_lost,is_spaying_neutering,pic)
+            VALUES($1,'Buddy','2020-01-01','Mestizo','',0,0,0,'pics/duplicate');",
+        )
+        .bind(owner_id)
+        .execute(&repo.db_pool)
+        .await
+        .unwrap()
+        .last_insert_rowid();
+        repo.set_pet_pic(primary_id, owner_id, "pics/primary")
+            .await
+            .unwrap();
+        let primary_external_id = link_pet_external_id(&repo, primary_id).await;
+        let duplicate_external_id = link_pet_external_id(&repo, duplicate_id).await;
+
+        for query in [
+            "INSERT INTO pet_access(pet_id,user_id,role) VALUES($1,$2,'viewer');",
+            "INSERT INTO pet_audit(pet_id,user_id,action,field) VALUES($1,$2,'update','name');",
+            "INSERT INTO pet_share_token(token,pet_id,expires_at,created_by) VALUES('token',$1,'2099-01-01',$2);",
+        ] {
+            sqlx::query(query)
+                .bind(duplicate_id)
+                .bind(collaborator_id)
+                .execute(&repo.db_pool)
+                .await
+                .unwrap();
+        }
+
+        let pic_not_moved = repo
+            .merge_pets(primary_id, duplicate_id, owner_id)
+            .await
+            .unwrap();
+
+        // the primary pet keeps its tag and picture, the duplicate's picture
+        // is handed back to be removed from the storage
+        assert_eq!(pic_not_moved.as_deref(), Some("pics/duplicate"));
+        let pet = repo.get_pet_by_id(primary_id, owner_id).await.unwrap();
+        assert_eq!(pet.external_id, primary_external_id);
+        assert_eq!(pet.pic.as_deref(), Some("pics/primary"));
+
+        // the duplicate's tag is gone, not left as an unlinked tag anyone could claim
+        assert_eq!(
+            repo.is_pet_external_id_linked(&duplicate_external_id)
+                .await
+                .unwrap(),
+            None
+        );
+
+        for table in ["pet_access", "pet_audit", "pet_share_token"] {
+            let moved = sqlx::query_scalar::<_, i64>(&format!(
+                "SELECT COUNT(*) FROM {table} WHERE pet_id = $1;"
+            ))
+            .bind(primary_id)
+            .fetch_one(&repo.db_pool)
+            .await
+            .unwrap();
+            assert_eq!(moved, 1, "{table}");
+        }
+    }
+
+    #[ntex::test]
+    async fn test_merge_pets_requires_owning_both_pets() {
+        let repo = setup_test_repo().await;
+        let (owner_id, other_user_id) = (1, 2);
+        let primary_id = insert_user_with_pet(&repo, owner_id).await;
+        let other_pet_id = insert_user_with_pet(&repo, other_user_id).await;
+        link_pet_external_id(&repo, other_pet_id).await;
+        sqlx::query("INSERT INTO pet_note(pet_id,title,content) VALUES($1,'Dieta','pollo');")
+            .bind(other_pet_id)
+            .execute(&repo.db_pool)
+            .await
+            .unwrap();
+
+        for (primary, duplicate) in [
+            (primary_id, other_pet_id),
+            (other_pet_id, primary_id),
+            (primary_id, primary_id),
+        ] {
+            assert!(matches!(
+                repo.merge_pets(primary, duplicate, owner_id)
+                    .await
+                    .unwrap_err()
+                    .downcast_ref::<RepoError>(),
+                Some(RepoError::NotFound)
+            ));
+        }
+
+        assert!(
+            repo.get_pet_by_id(other_pet_id, other_user_id)
+                .await
+                .is_ok()
+        );
+        let notes =
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM pet_note WHERE pet_id = $1;")
+                .bind(other_pet_id)
+                .fetch_one(&repo.db_pool)
+                .await
+                .unwrap();
+        assert_eq!(notes, 1);
+    }
+
+    #[ntex::test]
+    async fn test_update_reminder_schedule_keeps_id_and_body() {
+        let repo = setup_test_repo().await;
+        let (owner_id, other_user_id) = (1, 2);
+        insert_user_with_pet(&repo, owner_id).await;
+        insert_user_with_pet(&repo, other_user_id).await;
+
+        let reminder_id = repo
+            .insert_user_remider(&models::reminder::Reminder {
+                user_app_id: owner_id,
+                body: "Desparasitar".into(),
+                execution_id: "old-execution".into(),
+                send_at: Utc::now(),
+                user_timezone: "America/Mexico_City".into(),
+                created_at: Utc::now(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let new_send_at = Utc::now() + chrono::TimeDelta::days(2);
+
+        // someone else's reminder is not found
+        assert!(matches!(
+            repo.update_reminder_schedule(
+                reminder_id,
+                other_user_id,
+                new_send_at,
+                "America/Tijuana",
+                "new-execution"
+            )
+            .await
+            .unwrap_err()
+            .downcast_ref::<RepoError>(),
+            Some(RepoError::NotFound)
+        ));
+        assert!(matches!(
+            repo.get_user_reminder(reminder_id, other_user_id).await,
+            Err(RepoError::NotFound)
+        ));
+
+        repo.update_reminder_schedule(
+            reminder_id,
+            owner_id,
+            new_send_at,
+            "America/Tijuana",
+            "new-execution",
+        )
+        .await
+        .unwrap();
+
+        let reminder = repo.get_user_reminder(reminder_id, owner_id).await.unwrap();
+        assert_eq!(reminder.id, reminder_id);
+        assert_eq!(reminder.body, "Desparasitar");
+        assert_eq!(reminder.execution_id, "new-execution");
+        assert_eq!(reminder.user_timezone, "America/Tijuana");
+        assert_eq!(reminder.send_at, new_send_at);
+    }
 }
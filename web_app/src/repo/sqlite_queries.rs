@@ -1,23 +1,31 @@
 pub const QUERY_GET_USER_APP_BY_EMAIL: &str = r#"
 SELECT
-    id,email,phone_reminder,account_role,is_subscribed,is_enabled,created_at,updated_at
+    id,email,phone_reminder,account_role,is_subscribed,is_enabled,weight_unit,
+    created_at,updated_at
 FROM user_app
 WHERE email=$1;
 "#;
 
 pub const QUERY_GET_USER_APP_BY_PHONE: &str = r#"
 SELECT
-    id,email,phone_reminder,account_role,is_subscribed,is_enabled,created_at,updated_at
+    id,email,phone_reminder,account_role,is_subscribed,is_enabled,weight_unit,
+    created_at,updated_at
 FROM user_app
 WHERE phone_reminder=$1;
 "#;
 
+pub const QUERY_COUNT_APPROVED_PAYMENTS_SINCE: &str = r#"
+SELECT COUNT(*) FROM user_sub_payment
+WHERE status=$1 AND created_at>=$2;
+"#;
+
 pub const QUERY_GET_USER_PAYM_SUBS: &str = r#"
 SELECT
     user_id,
     mp_paym_id,
     payment_idempotency_h,
     transaction_amount,
+    currency,
     installments,
     payment_method_id,
     issuer_id,
@@ -33,26 +41,34 @@ ORDER BY created_at DESC;
 
 pub const QUERY_INSERT_NEW_SUB_PAYM: &str = r#"
 INSERT INTO user_sub_payment(
-    user_id,mp_paym_id,payment_idempotency_h,transaction_amount,
+    user_id,mp_paym_id,payment_idempotency_h,transaction_amount,currency,
     installments,payment_method_id,issuer_id,status,created_at,updated_at
-) VALUES($1,$2,$3,$4,$5,$6,$7,$8,$9,$10);
+) VALUES($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11);
 "#;
 
 pub const QUERY_INSERT_PET: &str = r#"
 INSERT INTO pet (
     user_app_id,pet_name,birthday,breed,
     about,is_female,is_lost,is_spaying_neutering,pic,
-    created_at,updated_at
+    created_at,updated_at,microchip
 ) VALUES(
     $1,$2,$3,
     $4,$5,$6,$7,
     $8,$9,
-    $10,$11
+    $10,$11,$12
 );
 "#;
 
 pub const QUERY_INSERT_PET_EXTERNAL_ID: &str = r#"
-INSERT INTO pet_external_id(external_id,created_at) VALUES ($1,$2);
+INSERT INTO pet_external_id(external_id,created_at,owner_user_id) VALUES ($1,$2,$3);
+"#;
+
+pub const QUERY_GET_USER_UNLINKED_EXTERNAL_IDS: &str = r#"
+SELECT pei.external_id
+FROM pet_external_id AS pei
+LEFT JOIN pet_linked AS pl ON (pl.id_pet_external_id = pei.id)
+WHERE pei.owner_user_id = $1 AND pl.pet_id IS NULL
+ORDER BY pei.created_at ASC, pei.id ASC;
 "#;
 
 pub const QUERY_LINK_PET_WITH_EXTERNAL_ID: &str = r#"
@@ -69,6 +85,49 @@ WHERE pei.external_id = $1;
 
 pub const QUERY_DELETE_PET: &str = r#"DELETE FROM pet WHERE id=$1 AND user_app_id=$2;"#;
 
+pub const QUERY_DELETE_PET_EXTERNAL_IDS_BY_PET_ID: &str = r#"
+DELETE FROM pet_external_id AS pexid WHERE pexid.id IN (
+    SELECT plink.id_pet_external_id FROM pet_linked AS plink
+    WHERE plink.pet_id = $1
+);
+"#;
+
+pub const QUERY_COUNT_USER_PETS_BY_IDS: &str =
+    r#"SELECT COUNT(*) FROM pet WHERE id IN ($1,$2) AND user_app_id=$3;"#;
+
+/// Moves the records of the pet `$2` onto the pet `$1`. Accesses the pet `$1`
+/// already grants are left on `$2`, they go away with it.
+pub const QUERIES_MOVE_PET_RECORDS: &[&str] = &[
+    "UPDATE pet_health SET pet_id=$1 WHERE pet_id=$2;",
+    "UPDATE pet_weight SET pet_id=$1 WHERE pet_id=$2;",
+    "UPDATE pet_note SET pet_id=$1 WHERE pet_id=$2;",
+    "UPDATE reminder SET pet_id=$1 WHERE pet_id=$2;",
+    "UPDATE found_report SET pet_id=$1 WHERE pet_id=$2;",
+    "UPDATE OR IGNORE pet_access SET pet_id=$1 WHERE pet_id=$2;",
+    "UPDATE pet_share_token SET pet_id=$1 WHERE pet_id=$2;",
+    "UPDATE pet_audit SET pet_id=$1 WHERE pet_id=$2;",
+];
+
+/// Moves the QR tag of the pet `$2` onto the pet `$1` if this has none
+pub const QUERY_MOVE_PET_LINK_IF_UNLINKED: &str = r#"
+UPDATE pet_linked SET pet_id=$1
+WHERE pet_id=$2
+AND NOT EXISTS (SELECT 1 FROM pet_linked WHERE pet_id=$1);
+"#;
+
+/// Picture of the pet `$2` that is not moved because the pet `$1` has one
+pub const QUERY_GET_PET_PIC_NOT_MOVED: &str = r#"
+SELECT dup.pic FROM pet AS dup
+WHERE dup.id=$2 AND dup.pic IS NOT NULL
+AND EXISTS (SELECT 1 FROM pet AS p WHERE p.id=$1 AND p.pic IS NOT NULL);
+"#;
+
+/// Copies the picture of the pet `$2` onto the pet `$1` if this has none
+pub const QUERY_MOVE_PET_PIC_IF_MISSING: &str = r#"
+UPDATE pet SET pic=(SELECT dup.pic FROM pet AS dup WHERE dup.id=$2)
+WHERE id=$1 AND pic IS NULL;
+"#;
+
 pub const QUERY_INSERT_PET_WEIGHT: &str = r#"
 INSERT INTO pet_weight (
     pet_id,weight,created_at
@@ -76,21 +135,37 @@ INSERT INTO pet_weight (
 FROM pet AS p
 LEFT JOIN pet_linked AS pidlink ON (p.id=pidlink.pet_id)
 LEFT JOIN pet_external_id AS peid ON (peid.id=pidlink.id_pet_external_id)
-WHERE peid.external_id=$1 AND user_app_id=$2
+WHERE peid.external_id=$1 AND EXISTS (
+    SELECT 1 FROM pet_member AS pm
+    WHERE pm.pet_id = p.id AND pm.user_id = $2 AND pm.can_edit
+)
 RETURNING id,pet_id,weight AS value,created_at;
 "#;
 
 pub const QUERY_INSERT_PET_HEALTH_RECORD: &str = r#"
 INSERT INTO pet_health (
-    pet_id,health_record,description,created_at
-) SELECT p.id,$3,$4,$5
+    pet_id,health_record,description,created_at,booster_months
+) SELECT p.id,$3,$4,$5,$6
 FROM pet AS p
 LEFT JOIN pet_linked AS pidlink ON (p.id=pidlink.pet_id)
 LEFT JOIN pet_external_id AS peid ON (peid.id=pidlink.id_pet_external_id)
-WHERE peid.external_id=$1 AND user_app_id=$2
+WHERE peid.external_id=$1 AND EXISTS (
+    SELECT 1 FROM pet_member AS pm
+    WHERE pm.pet_id = p.id AND pm.user_id = $2 AND pm.can_edit
+)
 RETURNING id,pet_id,health_record,description,created_at;
 "#;
 
+pub const QUERY_GET_PET_ID_BY_EXTERNAL_AND_USER_ID: &str = r#"
+SELECT p.id
+FROM pet_external_id AS peid
+INNER JOIN pet_linked AS pidlink ON (peid.id = pidlink.id_pet_external_id)
+INNER JOIN pet AS p ON (p.id = pidlink.pet_id)
+WHERE peid.external_id = $1 AND EXISTS (
+    SELECT 1 FROM pet_member AS pm WHERE pm.pet_id = p.id AND pm.user_id = $2
+);
+"#;
+
 pub const QUERY_GET_PET_HEALTH_RECORD: &str = r#"
 SELECT ph.id,ph.pet_id,ph.health_record,ph.description,ph.created_at
 FROM pet_external_id AS peid
@@ -99,9 +174,11 @@ INNER JOIN pet AS p ON (p.id = pidlink.pet_id)
 INNER JOIN pet_health AS ph ON (p.id = ph.pet_id)
 WHERE 
     peid.external_id = $1 
-    AND p.user_app_id = $2
+    AND EXISTS (
+        SELECT 1 FROM pet_member AS pm WHERE pm.pet_id = p.id AND pm.user_id = $2
+    )
     AND ph.health_record = $3
-ORDER BY ph.created_at DESC;
+ORDER BY ph.created_at DESC, ph.id DESC;
 "#;
 
 pub const QUERY_GET_PET_PUBLIC_HEALTH_RECORD: &str = r#"
@@ -113,20 +190,52 @@ INNER JOIN pet_health AS ph ON (p.id = ph.pet_id)
 WHERE 
     peid.external_id = $1
     AND ph.health_record = $2
-ORDER BY ph.created_at DESC;
+ORDER BY ph.created_at DESC, ph.id DESC;
 "#;
 
 pub const QUERY_GET_PET_BY_EXTERNAL_ID: &str = r#"
 SELECT
     p.id,peid.external_id,pw.weight AS last_weight,p.user_app_id,p.pet_name,
     p.birthday,p.breed,p.about,p.is_female,p.is_lost,
-    p.is_spaying_neutering,p.pic,p.created_at,p.updated_at
+    p.is_spaying_neutering,p.microchip,p.pic,p.created_at,p.updated_at
 FROM pet AS p
 LEFT JOIN pet_linked AS pidlink ON (p.id=pidlink.pet_id)
 LEFT JOIN pet_external_id AS peid ON (peid.id=pidlink.id_pet_external_id)
 LEFT JOIN pet_weight pw ON (p.id = pw.pet_id)
 WHERE peid.external_id = $1
-ORDER BY pw.created_at DESC 
+ORDER BY pw.created_at DESC, pw.id DESC
+LIMIT 1;
+"#;
+
+/// Prefix of the query, the external ids are pushed as a bound list closing the `IN (`
+pub const QUERY_GET_PETS_BY_EXTERNAL_IDS: &str = r#"
+SELECT
+    p.id,peid.external_id,pw.weight AS last_weight,p.user_app_id,p.pet_name,
+    p.birthday,p.breed,p.about,p.is_female,p.is_lost,
+    p.is_spaying_neutering,p.microchip,p.pic,p.created_at,p.updated_at
+FROM pet AS p
+INNER JOIN pet_linked AS pidlink ON (p.id=pidlink.pet_id)
+INNER JOIN pet_external_id AS peid ON (peid.id=pidlink.id_pet_external_id)
+LEFT JOIN (
+    SELECT pet_id, weight,
+           ROW_NUMBER() OVER (PARTITION BY pet_id ORDER BY created_at DESC, id DESC) as rn
+    FROM pet_weight
+) pw ON (pw.pet_id = p.id AND pw.rn = 1)
+WHERE peid.external_id IN ("#;
+
+pub const QUERY_GET_PET_BY_EXTERNAL_AND_USER_ID: &str = r#"
+SELECT
+    p.id,peid.external_id,pw.weight AS last_weight,p.user_app_id,p.pet_name,
+    p.birthday,p.breed,p.about,p.is_female,p.is_lost,
+    p.is_spaying_neutering,p.microchip,p.pic,p.created_at,p.updated_at
+FROM pet AS p
+INNER JOIN pet_linked AS pidlink ON (p.id=pidlink.pet_id)
+INNER JOIN pet_external_id AS peid ON (peid.id=pidlink.id_pet_external_id)
+LEFT JOIN pet_weight pw ON (p.id = pw.pet_id)
+WHERE peid.external_id = $1 AND EXISTS (
+    SELECT 1 FROM pet_member AS pm WHERE pm.pet_id = p.id AND pm.user_id = $2
+)
+ORDER BY pw.created_at DESC, pw.id DESC
 LIMIT 1;
 "#;
 
@@ -134,31 +243,176 @@ pub const QUERY_GET_PET_BY_ID: &str = r#"
 SELECT
     p.id,peid.external_id,pw.weight AS last_weight,p.user_app_id,p.pet_name,
     p.birthday,p.breed,p.about,p.is_female,p.is_lost,
-    p.is_spaying_neutering,p.pic,p.created_at,p.updated_at
+    p.is_spaying_neutering,p.microchip,p.pic,p.created_at,p.updated_at
 FROM pet AS p
 LEFT JOIN pet_linked AS pidlink ON (p.id=pidlink.pet_id)
 LEFT JOIN pet_external_id AS peid ON (peid.id=pidlink.id_pet_external_id)
 LEFT JOIN pet_weight pw ON (p.id = pw.pet_id)
+WHERE p.id = $1 AND EXISTS (
+    SELECT 1 FROM pet_member AS pm WHERE pm.pet_id = p.id AND pm.user_id = $2
+)
+ORDER BY pw.created_at DESC, pw.id DESC
+LIMIT 1;
+"#;
+
+pub const QUERY_OFFER_PET_TRANSFER: &str = r#"
+INSERT INTO pet_transfer (
+    pet_id,from_user_id,to_user_id,created_at
+) SELECT p.id,p.user_app_id,u.id,$4
+FROM pet AS p
+JOIN user_app AS u ON (u.email = $3)
+WHERE
+    p.id = $1
+    AND p.user_app_id = $2
+    AND u.id != p.user_app_id
+ON CONFLICT(pet_id) DO UPDATE SET
+    to_user_id = excluded.to_user_id,
+    created_at = excluded.created_at;
+"#;
+
+pub const QUERY_GET_INCOMING_PET_TRANSFERS: &str = r#"
+SELECT
+    pt.pet_id,p.pet_name,u.email AS from_email,pt.created_at
+FROM pet_transfer AS pt
+INNER JOIN pet AS p ON (p.id = pt.pet_id AND p.user_app_id = pt.from_user_id)
+INNER JOIN user_app AS u ON (u.id = pt.from_user_id)
+WHERE pt.to_user_id = $1
+ORDER BY pt.created_at ASC;
+"#;
+
+pub const QUERY_TAKE_PET_TRANSFER: &str = r#"
+DELETE FROM pet_transfer
+WHERE pet_id = $1 AND to_user_id = $2
+RETURNING from_user_id;
+"#;
+
+pub const QUERY_CANCEL_PET_TRANSFER: &str = r#"
+DELETE FROM pet_transfer
+WHERE pet_id = $1 AND (from_user_id = $2 OR to_user_id = $2);
+"#;
+
+pub const QUERY_TRANSFER_PET: &str = r#"
+UPDATE pet
+    SET user_app_id = $3,
+    updated_at = $4
+WHERE id = $1 AND user_app_id = $2;
+"#;
+
+pub const QUERY_TRANSFER_PET_EXTERNAL_IDS: &str = r#"
+UPDATE pet_external_id
+    SET owner_user_id = $2
+WHERE id IN (SELECT plinked.id_pet_external_id FROM pet_linked AS plinked WHERE plinked.pet_id = $1);
+"#;
+
+pub const QUERY_DELETE_PET_ACCESS_OF_USER: &str = r#"
+DELETE FROM pet_access WHERE pet_id = $1 AND user_id = $2;
+"#;
+
+pub const QUERY_GRANT_PET_ACCESS: &str = r#"
+INSERT INTO pet_access (
+    pet_id,user_id,role,created_at
+) SELECT p.id,u.id,$4,$5
+FROM pet AS p
+JOIN user_app AS u ON (u.email = $3)
+WHERE
+    p.id = $1
+    AND p.user_app_id = $2
+    AND u.id != p.user_app_id
+ON CONFLICT(pet_id,user_id) DO UPDATE SET role = excluded.role;
+"#;
+
+pub const QUERY_GET_PET_COLLABORATORS: &str = r#"
+SELECT
+    pa.user_id,u.email,pa.role,pa.created_at
+FROM pet_access AS pa
+INNER JOIN pet AS p ON (p.id = pa.pet_id)
+INNER JOIN user_app AS u ON (u.id = pa.user_id)
 WHERE p.id = $1 AND p.user_app_id = $2
-ORDER BY pw.created_at DESC 
+ORDER BY pa.created_at ASC;
+"#;
+
+pub const QUERY_INSERT_PET_AUDIT: &str = r#"
+INSERT INTO pet_audit(pet_id,user_id,action,field)
+VALUES ($1,$2,$3,$4);
+"#;
+
+pub const QUERY_INSERT_PET_AUDIT_BY_EXTERNAL_ID: &str = r#"
+INSERT INTO pet_audit(pet_id,user_id,action,field)
+SELECT plinked.pet_id,$2,$3,$4
+FROM pet_external_id AS peid
+INNER JOIN pet_linked AS plinked ON (peid.id = plinked.id_pet_external_id)
+WHERE peid.external_id = $1
 LIMIT 1;
 "#;
 
+pub const QUERY_INSERT_PET_AUDIT_BY_NOTE_ID: &str = r#"
+INSERT INTO pet_audit(pet_id,user_id,action,field)
+SELECT pn.pet_id,$2,$3,$4
+FROM pet_note AS pn
+WHERE pn.id = $1;
+"#;
+
+pub const QUERY_GET_PET_AUDIT: &str = r#"
+SELECT
+    u.email,pau.action,pau.field,pau.created_at
+FROM pet_audit AS pau
+INNER JOIN pet AS p ON (p.id = pau.pet_id)
+INNER JOIN user_app AS u ON (u.id = pau.user_id)
+WHERE p.id = $1 AND p.user_app_id = $2
+ORDER BY pau.created_at DESC, pau.id DESC
+LIMIT $3;
+"#;
+
+pub const QUERY_COUNT_USER_PETS: &str = r#"
+SELECT COUNT(*) FROM pet WHERE user_app_id = $1;
+"#;
+
 pub const QUERY_GET_ALL_PETS_USER_ID: &str = r#"
 SELECT
     pet.id,peid.external_id,pw.weight AS last_weight,user_app_id,pet_name,birthday,breed,
-    about,is_female,is_lost,is_spaying_neutering,pic,
+    about,is_female,is_lost,is_spaying_neutering,microchip,pic,
     pet.created_at,pet.updated_at
 FROM pet
 LEFT JOIN pet_linked AS pl ON (pl.pet_id=pet.id)
 LEFT JOIN pet_external_id AS peid ON (peid.id=pl.id_pet_external_id)
 LEFT JOIN (
     SELECT pet_id, weight, 
-           ROW_NUMBER() OVER (PARTITION BY pet_id ORDER BY created_at DESC) as rn
+           ROW_NUMBER() OVER (PARTITION BY pet_id ORDER BY created_at DESC, id DESC) as rn
     FROM pet_weight
 ) pw ON (pw.pet_id = pet.id AND pw.rn = 1)
 WHERE user_app_id = $1
-ORDER BY pet.created_at DESC;
+ORDER BY pet.created_at DESC, pet.id DESC;
+"#;
+
+pub const QUERY_GET_PETS_WITH_COUNTS_USER_ID: &str = r#"
+SELECT
+    pet.id,peid.external_id,pw.weight AS last_weight,user_app_id,pet_name,birthday,breed,
+    about,is_female,is_lost,is_spaying_neutering,microchip,pic,
+    pet.created_at,pet.updated_at,
+    COALESCE(vc.vaccine_count, 0) AS vaccine_count,
+    COALESCE(wc.weight_count, 0) AS weight_count,
+    vc.last_vaccine_date
+FROM pet
+LEFT JOIN pet_linked AS pl ON (pl.pet_id=pet.id)
+LEFT JOIN pet_external_id AS peid ON (peid.id=pl.id_pet_external_id)
+LEFT JOIN (
+    SELECT pet_id, weight, 
+           ROW_NUMBER() OVER (PARTITION BY pet_id ORDER BY created_at DESC, id DESC) as rn
+    FROM pet_weight
+) pw ON (pw.pet_id = pet.id AND pw.rn = 1)
+LEFT JOIN (
+    SELECT pet_id, COUNT(*) AS vaccine_count, MAX(created_at) AS last_vaccine_date
+    FROM pet_health
+    WHERE health_record = 'vaccine'
+    GROUP BY pet_id
+) vc ON (vc.pet_id = pet.id)
+LEFT JOIN (
+    SELECT pet_id, COUNT(*) AS weight_count
+    FROM pet_weight
+    GROUP BY pet_id
+) wc ON (wc.pet_id = pet.id)
+WHERE user_app_id = $1
+ORDER BY pet.created_at DESC, pet.id DESC;
 "#;
 
 pub const QUERY_UPDATE_PET: &str = r#"
@@ -170,10 +424,105 @@ UPDATE pet
     is_female = $7,
     is_lost = $8,
     is_spaying_neutering = $9,
-    updated_at = $10
+    microchip = $10,
+    updated_at = $11
+WHERE id = $1 AND EXISTS (
+    SELECT 1 FROM pet_member AS pm
+    WHERE pm.pet_id = pet.id AND pm.user_id = $2 AND pm.can_edit
+);
+"#;
+
+pub const QUERY_SET_PET_LOST_STATUS: &str = r#"
+UPDATE pet
+    SET is_lost = $3,
+    updated_at = $4
+WHERE id = $1 AND EXISTS (
+    SELECT 1 FROM pet_member AS pm
+    WHERE pm.pet_id = pet.id AND pm.user_id = $2 AND pm.can_edit
+);
+"#;
+
+pub const QUERY_SET_PET_PUBLIC_PIN: &str = r#"
+UPDATE pet
+    SET public_pin = $3,
+    updated_at = $4
 WHERE id = $1 AND user_app_id = $2;
 "#;
 
+pub const QUERY_GET_PET_PUBLIC_PIN: &str = r#"
+SELECT p.public_pin
+FROM pet_external_id AS peid
+INNER JOIN pet_linked AS plinked ON (peid.id=plinked.id_pet_external_id)
+INNER JOIN pet AS p ON (p.id=plinked.pet_id)
+WHERE peid.external_id=$1;
+"#;
+
+pub const QUERY_GET_PET_PIN_LOCKED_UNTIL: &str = r#"
+SELECT ppa.locked_until
+FROM pet_external_id AS peid
+INNER JOIN pet_linked AS plinked ON (peid.id=plinked.id_pet_external_id)
+INNER JOIN pet_pin_attempt AS ppa ON (ppa.pet_id=plinked.pet_id)
+WHERE peid.external_id=$1;
+"#;
+
+pub const QUERY_RECORD_FAILED_PET_PIN_ATTEMPT: &str = r#"
+INSERT INTO pet_pin_attempt(pet_id, failed_count)
+SELECT plinked.pet_id, 1
+FROM pet_external_id AS peid
+INNER JOIN pet_linked AS plinked ON (peid.id=plinked.id_pet_external_id)
+WHERE peid.external_id=$1
+ON CONFLICT(pet_id) DO UPDATE SET
+    failed_count = CASE WHEN failed_count + 1 >= $2 THEN 0 ELSE failed_count + 1 END,
+    locked_until = CASE WHEN failed_count + 1 >= $2 THEN $3 ELSE locked_until END;
+"#;
+
+pub const QUERY_CLEAR_FAILED_PET_PIN_ATTEMPTS: &str = r#"
+DELETE FROM pet_pin_attempt
+WHERE pet_id IN (
+    SELECT plinked.pet_id
+    FROM pet_external_id AS peid
+    INNER JOIN pet_linked AS plinked ON (peid.id=plinked.id_pet_external_id)
+    WHERE peid.external_id=$1
+);
+"#;
+
+pub const QUERY_SET_PET_SPAYING_NEUTERING_STATUS: &str = r#"
+UPDATE pet
+    SET is_spaying_neutering = $3,
+    updated_at = $4
+WHERE id = $1 AND EXISTS (
+    SELECT 1 FROM pet_member AS pm
+    WHERE pm.pet_id = pet.id AND pm.user_id = $2 AND pm.can_edit
+);
+"#;
+
+pub const QUERY_DELETE_EXPIRED_PET_CREATION_TOKENS: &str = r#"
+DELETE FROM pet_creation_token
+WHERE created_at < $1;
+"#;
+
+pub const QUERY_INSERT_PET_CREATION_TOKEN: &str = r#"
+INSERT OR IGNORE INTO pet_creation_token(token,user_app_id,created_at)
+VALUES ($1,$2,$3);
+"#;
+
+pub const QUERY_DELETE_PET_CREATION_TOKEN: &str = r#"
+DELETE FROM pet_creation_token
+WHERE
+    token = $1
+    AND user_app_id = $2;
+"#;
+
+pub const QUERY_SET_PET_PIC: &str = r#"
+UPDATE pet
+    SET pic = $3,
+    updated_at = $4
+WHERE id = $1 AND EXISTS (
+    SELECT 1 FROM pet_member AS pm
+    WHERE pm.pet_id = pet.id AND pm.user_id = $2 AND pm.can_edit
+);
+"#;
+
 pub const QUERY_GET_PET_WEIGHTS_BY_EXTERNAL_AND_USER_ID: &str = r#"
 SELECT 
     pw.id,pw.pet_id,pw.weight AS value,pw.created_at 
@@ -183,8 +532,10 @@ INNER JOIN pet AS p ON (p.id = plinked.pet_id)
 INNER JOIN pet_weight AS pw ON (p.id = pw.pet_id)
 WHERE 
     peid.external_id = $1 AND
-    p.user_app_id = $2
-ORDER BY pw.created_at DESC;
+    EXISTS (
+        SELECT 1 FROM pet_member AS pm WHERE pm.pet_id = p.id AND pm.user_id = $2
+    )
+ORDER BY pw.created_at DESC, pw.id DESC;
 "#;
 
 pub const QUERY_GET_PET_PUBLIC_PIC_BY_EXTERNAL_ID: &str = r#"
@@ -195,6 +546,22 @@ LEFT JOIN pet_external_id AS peid ON (peid.id=plinked.id_pet_external_id)
 WHERE peid.external_id = $1;
 "#;
 
+pub const QUERY_GET_PET_QR_CAPTION_BY_EXTERNAL_ID: &str = r#"
+SELECT p.pet_name,p.breed
+FROM pet AS p
+INNER JOIN pet_linked AS plinked ON (p.id=plinked.pet_id)
+INNER JOIN pet_external_id AS peid ON (peid.id=plinked.id_pet_external_id)
+WHERE peid.external_id = $1;
+"#;
+
+pub const QUERY_GET_PET_PUBLIC_PIC_UPDATED_AT_BY_EXTERNAL_ID: &str = r#"
+SELECT p.updated_at
+FROM pet AS p
+LEFT JOIN pet_linked AS plinked ON (p.id=plinked.pet_id)
+LEFT JOIN pet_external_id AS peid ON (peid.id=plinked.id_pet_external_id)
+WHERE peid.external_id = $1 AND p.pic IS NOT NULL;
+"#;
+
 pub const QUERY_GET_PET_WEIGHTS_BY_EXTERNAL_ID: &str = r#"
 SELECT 
     pw.id,pw.pet_id,pw.weight AS value,pw.created_at 
@@ -203,7 +570,7 @@ INNER JOIN pet_linked AS plinked ON (peid.id = plinked.id_pet_external_id)
 INNER JOIN pet AS p ON (p.id = plinked.pet_id)
 INNER JOIN pet_weight AS pw ON (p.id = pw.pet_id)
 WHERE peid.external_id = $1
-ORDER BY pw.created_at DESC;
+ORDER BY pw.created_at DESC, pw.id DESC;
 "#;
 
 pub const QUERY_DELETE_PET_WEIGHT: &str = r#"
@@ -216,11 +583,73 @@ AND pet_id = (
     INNER JOIN pet AS p ON (p.id = plinked.pet_id)
     WHERE 
         peid.external_id = $2 AND
-        p.user_app_id = $3
+        EXISTS (
+            SELECT 1 FROM pet_member AS pm
+            WHERE pm.pet_id = p.id AND pm.user_id = $3 AND pm.can_edit
+        )
+    LIMIT 1
+);
+"#;
+
+pub const QUERY_DELETE_ALL_PET_WEIGHTS: &str = r#"
+DELETE FROM pet_weight
+WHERE pet_id = (
+    SELECT p.id
+    FROM pet_external_id AS peid
+    INNER JOIN pet_linked AS plinked ON (peid.id = plinked.id_pet_external_id)
+    INNER JOIN pet AS p ON (p.id = plinked.pet_id)
+    WHERE
+        peid.external_id = $1 AND
+        EXISTS (
+            SELECT 1 FROM pet_member AS pm
+            WHERE pm.pet_id = p.id AND pm.user_id = $2 AND pm.can_edit
+        )
     LIMIT 1
 );
 "#;
 
+pub const QUERY_UPDATE_PET_WEIGHT: &str = r#"
+UPDATE pet_weight
+    SET weight = $1,
+    created_at = $2
+WHERE id = $3
+AND pet_id = (
+    SELECT p.id
+    FROM pet_external_id AS peid
+    INNER JOIN pet_linked AS plinked ON (peid.id = plinked.id_pet_external_id)
+    INNER JOIN pet AS p ON (p.id = plinked.pet_id)
+    WHERE 
+        peid.external_id = $4 AND
+        EXISTS (
+            SELECT 1 FROM pet_member AS pm
+            WHERE pm.pet_id = p.id AND pm.user_id = $5 AND pm.can_edit
+        )
+    LIMIT 1
+);
+"#;
+
+pub const QUERY_UPDATE_PET_HEALTH_RECORD: &str = r#"
+UPDATE pet_health
+    SET description = $1,
+    created_at = $2
+WHERE 
+    id = $3
+    AND health_record = $4
+    AND pet_id = (
+        SELECT p.id
+        FROM pet_external_id AS peid
+        INNER JOIN pet_linked AS plinked ON (peid.id = plinked.id_pet_external_id)
+        INNER JOIN pet AS p ON (p.id = plinked.pet_id)
+        WHERE 
+            peid.external_id = $5 AND
+            EXISTS (
+                SELECT 1 FROM pet_member AS pm
+                WHERE pm.pet_id = p.id AND pm.user_id = $6 AND pm.can_edit
+            )
+        LIMIT 1
+    );
+"#;
+
 pub const QUERY_DELETE_PET_HEALTH_RECORD: &str = r#"
 DELETE FROM pet_health 
 WHERE 
@@ -233,36 +662,77 @@ WHERE
         INNER JOIN pet AS p ON (p.id = plinked.pet_id)
         WHERE 
             peid.external_id = $3 AND
-            p.user_app_id = $4
+            EXISTS (
+                SELECT 1 FROM pet_member AS pm
+                WHERE pm.pet_id = p.id AND pm.user_id = $4 AND pm.can_edit
+            )
+        LIMIT 1
+    );
+"#;
+
+pub const QUERY_DELETE_ALL_PET_HEALTH_RECORDS: &str = r#"
+DELETE FROM pet_health
+WHERE
+    health_record = $1
+    AND pet_id = (
+        SELECT p.id
+        FROM pet_external_id AS peid
+        INNER JOIN pet_linked AS plinked ON (peid.id = plinked.id_pet_external_id)
+        INNER JOIN pet AS p ON (p.id = plinked.pet_id)
+        WHERE
+            peid.external_id = $2 AND
+            EXISTS (
+                SELECT 1 FROM pet_member AS pm
+                WHERE pm.pet_id = p.id AND pm.user_id = $3 AND pm.can_edit
+            )
         LIMIT 1
     );
 "#;
 
 pub const QUERY_GET_OWNER_CONTACTS: &str = r#"
 SELECT 
-    id,user_app_id,full_name,contact_value,created_at
+    id,user_app_id,full_name,contact_value,contact_type,is_primary,sort_order,
+    address,latitude,longitude,created_at
 FROM owner_contact
 WHERE user_app_id=$1
-ORDER BY created_at DESC;
+ORDER BY is_primary DESC, sort_order ASC, created_at DESC, id DESC;
 "#;
 
 pub const QUERY_GET_PET_OWNER_CONTACTS: &str = r#"
 SELECT 
-    c.id,c.user_app_id,c.full_name,c.contact_value,c.created_at
+    c.id,c.user_app_id,c.full_name,c.contact_value,c.contact_type,c.is_primary,c.sort_order,
+    c.address,c.latitude,c.longitude,c.created_at
 FROM owner_contact AS c
 LEFT JOIN pet AS p ON (p.user_app_id = c.user_app_id)
 LEFT JOIN pet_linked AS plinked ON (p.id=plinked.pet_id)
 LEFT JOIN pet_external_id AS peid ON (peid.id=plinked.id_pet_external_id)
 WHERE peid.external_id=$1
-ORDER BY c.created_at DESC;
+ORDER BY c.is_primary DESC, c.sort_order ASC, c.created_at DESC, c.id DESC;
 "#;
 
+/// Nothing is inserted when the user already has the same normalized contact,
+/// see [`QUERY_GET_OWNER_CONTACT_BY_VALUE`]
 pub const QUERY_INSERT_NEW_OWNER_CONTACT: &str = r#"
 INSERT INTO owner_contact(
-    user_app_id,full_name,contact_value,created_at
+    user_app_id,full_name,contact_value,contact_type,created_at,address,latitude,longitude
 ) VALUES (
-    $1,$2,$3,$4
-);
+    $1,$2,$3,$4,$5,$6,$7,$8
+)
+ON CONFLICT(user_app_id, normalized_value) DO NOTHING
+RETURNING
+    id,user_app_id,full_name,contact_value,contact_type,is_primary,sort_order,
+    address,latitude,longitude,created_at;
+"#;
+
+/// Normalizes `$2` the same way as the generated `normalized_value` column
+pub const QUERY_GET_OWNER_CONTACT_BY_VALUE: &str = r#"
+SELECT
+    id,user_app_id,full_name,contact_value,contact_type,is_primary,sort_order,
+    address,latitude,longitude,created_at
+FROM owner_contact
+WHERE
+    user_app_id = $1
+    AND normalized_value = lower(replace(replace(replace(replace(trim($2),' ',''),'-',''),'(',''),')',''));
 "#;
 
 pub const QUERY_DELETE_OWNER_CONTACT: &str = r#"
@@ -272,6 +742,114 @@ WHERE
     AND user_app_id = $2;
 "#;
 
+pub const QUERY_UNSET_PRIMARY_OWNER_CONTACTS: &str = r#"
+UPDATE owner_contact
+SET is_primary = 0
+WHERE user_app_id = $1;
+"#;
+
+pub const QUERY_SET_PRIMARY_OWNER_CONTACT: &str = r#"
+UPDATE owner_contact
+SET is_primary = 1
+WHERE
+    id = $1
+    AND user_app_id = $2;
+"#;
+
+pub const QUERY_UPDATE_OWNER_CONTACT_SORT_ORDER: &str = r#"
+UPDATE owner_contact
+SET sort_order = $1
+WHERE
+    id = $2
+    AND user_app_id = $3;
+"#;
+
+pub const QUERY_GET_PET_OWNER_BY_EXTERNAL_ID: &str = r#"
+SELECT
+    u.id,u.email,u.phone_reminder,u.account_role,u.is_subscribed,u.is_enabled,u.weight_unit,
+    u.created_at,u.updated_at
+FROM user_app AS u
+JOIN pet AS p ON (p.user_app_id = u.id)
+JOIN pet_linked AS plinked ON (p.id=plinked.pet_id)
+JOIN pet_external_id AS peid ON (peid.id=plinked.id_pet_external_id)
+WHERE peid.external_id=$1;
+"#;
+
+pub const QUERY_INSERT_FOUND_REPORT: &str = r#"
+INSERT INTO found_report (
+    pet_id,message,callback_contact,created_at
+) SELECT plinked.pet_id,$2,$3,$4
+FROM pet_linked AS plinked
+JOIN pet_external_id AS peid ON (peid.id=plinked.id_pet_external_id)
+WHERE peid.external_id=$1;
+"#;
+
+pub const QUERY_GET_PET_FOUND_REPORTS: &str = r#"
+SELECT
+    fr.message,fr.callback_contact,fr.created_at
+FROM found_report AS fr
+INNER JOIN pet AS p ON (p.id = fr.pet_id)
+WHERE p.id = $1 AND p.user_app_id = $2
+ORDER BY fr.created_at DESC, fr.id DESC
+LIMIT $3;
+"#;
+
+pub const QUERY_INSERT_PET_SCAN: &str = r#"
+INSERT INTO pet_scan (
+    external_id,user_agent,scanned_at
+) SELECT peid.external_id,$2,$3
+FROM pet_linked AS plinked
+JOIN pet_external_id AS peid ON (peid.id=plinked.id_pet_external_id)
+JOIN pet AS p ON (p.id=plinked.pet_id)
+WHERE peid.external_id=$1 AND p.user_app_id IS NOT $4;
+"#;
+
+pub const QUERY_DELETE_PET_SCANS_BEFORE: &str = r#"
+DELETE FROM pet_scan WHERE scanned_at < $1;
+"#;
+
+pub const QUERY_GET_PET_SCAN_STATS: &str = r#"
+SELECT
+    COUNT(ps.id) AS total,
+    COUNT(CASE WHEN ps.scanned_at >= $3 THEN 1 END) AS last_7_days
+FROM pet_external_id AS peid
+INNER JOIN pet_linked AS plinked ON (peid.id=plinked.id_pet_external_id)
+INNER JOIN pet AS p ON (p.id=plinked.pet_id)
+INNER JOIN pet_scan AS ps ON (ps.external_id=peid.external_id)
+WHERE peid.external_id=$1 AND p.user_app_id=$2;
+"#;
+
+pub const QUERY_INSERT_PET_SHARE_TOKEN: &str = r#"
+INSERT INTO pet_share_token (
+    token,pet_id,expires_at,created_by,created_at
+) SELECT $3,p.id,$4,p.user_app_id,$5
+FROM pet AS p
+WHERE p.id=$1 AND p.user_app_id=$2;
+"#;
+
+pub const QUERY_DELETE_PET_SHARE_TOKEN: &str = r#"
+DELETE FROM pet_share_token
+WHERE token=$3 AND pet_id=(
+    SELECT p.id FROM pet AS p WHERE p.id=$1 AND p.user_app_id=$2
+);
+"#;
+
+pub const QUERY_GET_PET_SHARE_TOKENS: &str = r#"
+SELECT pst.token,pst.pet_id,pst.expires_at
+FROM pet_share_token AS pst
+INNER JOIN pet AS p ON (p.id=pst.pet_id)
+WHERE pst.pet_id=$1 AND p.user_app_id=$2 AND pst.expires_at > $3
+ORDER BY pst.expires_at ASC;
+"#;
+
+pub const QUERY_GET_PET_EXTERNAL_ID_BY_SHARE_TOKEN: &str = r#"
+SELECT peid.external_id
+FROM pet_share_token AS pst
+INNER JOIN pet_linked AS plinked ON (plinked.pet_id=pst.pet_id)
+INNER JOIN pet_external_id AS peid ON (peid.id=plinked.id_pet_external_id)
+WHERE pst.token=$1 AND pst.expires_at > $2;
+"#;
+
 pub const QUERY_INSERT_PET_NOTE: &str = r#"
 INSERT INTO pet_note (
     pet_id,title,content,created_at
@@ -279,7 +857,21 @@ INSERT INTO pet_note (
 FROM pet AS p
 WHERE
     p.id = $1 AND
-    p.user_app_id=$2;
+    EXISTS (
+        SELECT 1 FROM pet_member AS pm
+        WHERE pm.pet_id = p.id AND pm.user_id = $2 AND pm.can_edit
+    );
+"#;
+
+pub const QUERY_GET_ALL_PET_NOTES: &str = r#"
+SELECT 
+    pn.id, pn.pet_id, pn.title, pn.content, pn.created_at, pn.updated_at
+FROM pet_note AS pn
+LEFT JOIN pet AS p ON (p.id = pn.pet_id)
+WHERE p.id=$1 AND EXISTS (
+    SELECT 1 FROM pet_member AS pm WHERE pm.pet_id = p.id AND pm.user_id = $2
+)
+ORDER BY pn.created_at DESC, pn.id DESC;
 "#;
 
 pub const QUERY_GET_PET_NOTES: &str = r#"
@@ -287,7 +879,49 @@ SELECT
     pn.id, pn.pet_id, pn.title, pn.content, pn.created_at, pn.updated_at
 FROM pet_note AS pn
 LEFT JOIN pet AS p ON (p.id = pn.pet_id)
-WHERE p.id=$1 AND p.user_app_id=$2;
+WHERE p.id=$1 AND EXISTS (
+    SELECT 1 FROM pet_member AS pm WHERE pm.pet_id = p.id AND pm.user_id = $2
+)
+ORDER BY pn.created_at DESC, pn.id DESC
+LIMIT $3 OFFSET $4;
+"#;
+
+pub const QUERY_COUNT_PET_NOTES: &str = r#"
+SELECT COUNT(pn.id)
+FROM pet_note AS pn
+INNER JOIN pet AS p ON (p.id = pn.pet_id)
+WHERE p.id=$1 AND EXISTS (
+    SELECT 1 FROM pet_member AS pm WHERE pm.pet_id = p.id AND pm.user_id = $2
+);
+"#;
+
+pub const QUERY_SEARCH_PET_NOTES: &str = r#"
+SELECT 
+    pn.id, pn.pet_id, pn.title, pn.content, pn.created_at, pn.updated_at
+FROM pet_note AS pn
+INNER JOIN pet AS p ON (p.id = pn.pet_id)
+WHERE
+    p.id = $1
+    AND EXISTS (
+        SELECT 1 FROM pet_member AS pm WHERE pm.pet_id = p.id AND pm.user_id = $2
+    )
+    AND (pn.title LIKE $3 ESCAPE '\' OR pn.content LIKE $3 ESCAPE '\')
+ORDER BY pn.created_at DESC, pn.id DESC;
+"#;
+
+pub const QUERY_UPDATE_PET_NOTE: &str = r#"
+UPDATE pet_note
+SET title = $1, content = $2, updated_at = $3
+WHERE
+    id = $4
+    AND pet_id IN (
+        SELECT p.id
+        FROM pet AS p
+        WHERE EXISTS (
+            SELECT 1 FROM pet_member AS pm
+            WHERE pm.pet_id = p.id AND pm.user_id = $5 AND pm.can_edit
+        )
+    );
 "#;
 
 pub const QUERY_DELETE_PET_NOTE: &str = r#"
@@ -299,27 +933,172 @@ WHERE
         FROM pet AS p
         WHERE 
             p.id = $2 AND
-            p.user_app_id = $3
+            EXISTS (
+                SELECT 1 FROM pet_member AS pm
+                WHERE pm.pet_id = p.id AND pm.user_id = $3 AND pm.can_edit
+            )
         LIMIT 1
     );
 "#;
 
 pub const QUERY_INSERT_USER_REMINDER: &str = r#"
 INSERT INTO reminder(
-    user_app_id,body,execution_id,notification_type,send_at,user_timezone,created_at
-) VALUES($1,$2,$3,$4,$5,$6,$7);
+    user_app_id,body,execution_id,notification_type,send_at,user_timezone,pet_id,created_at
+) VALUES($1,$2,$3,$4,$5,$6,$7,$8);
+"#;
+
+pub const QUERY_DELETE_PET_REMINDERS: &str = r#"
+DELETE FROM reminder
+WHERE
+    pet_id = $1
+    AND user_app_id = $2
+RETURNING execution_id;
 "#;
 
 pub const QUERY_GET_USER_ACTIVE_REMINDERS: &str = r#"
 SELECT 
     r.id,r.user_app_id,r.body,r.execution_id,
     r.notification_type,r.send_at,r.user_timezone,
-    r.created_at
+    r.pet_id,r.created_at
 FROM reminder AS r
 WHERE r.user_app_id = $1 AND r.send_at>=$2
 "#;
 
+pub const QUERY_GET_USER_REMINDERS: &str = r#"
+SELECT 
+    r.id,r.user_app_id,r.body,r.execution_id,
+    r.notification_type,r.send_at,r.user_timezone,
+    r.pet_id,r.created_at,
+    CASE WHEN r.send_at>=$2 THEN 'scheduled' ELSE 'sent' END AS status
+FROM reminder AS r
+WHERE r.user_app_id = $1 AND r.send_at>=$3
+ORDER BY
+    r.send_at<$2,
+    CASE WHEN r.send_at>=$2 THEN r.send_at END ASC,
+    r.send_at DESC;
+"#;
+
+/// Statuses can arrive out of order, an older one never overwrites a newer one
+pub const QUERY_UPSERT_MESSAGE_DELIVERY_STATUS: &str = r#"
+INSERT INTO message_delivery(wamid,status,updated_at)
+VALUES($1,$2,$3)
+ON CONFLICT(wamid) DO UPDATE SET
+    status = excluded.status,
+    updated_at = excluded.updated_at
+WHERE excluded.updated_at >= message_delivery.updated_at;
+"#;
+
+/// The epoch `updated_at` lets any status reported by the webhook replace the default one
+pub const QUERY_LINK_MESSAGE_DELIVERY_TO_REMINDER: &str = r#"
+INSERT INTO message_delivery(wamid,reminder_id,status,updated_at)
+VALUES($1,$2,'sent','1970-01-01 00:00:00')
+ON CONFLICT(wamid) DO UPDATE SET reminder_id = excluded.reminder_id;
+"#;
+
+pub const QUERY_GET_REMINDER_DELIVERY_STATUS: &str = r#"
+SELECT md.status
+FROM message_delivery AS md
+INNER JOIN reminder AS r ON (r.id = md.reminder_id)
+WHERE md.reminder_id = $1 AND r.user_app_id = $2
+ORDER BY md.updated_at DESC
+LIMIT 1;
+"#;
+
+pub const QUERY_GET_USER_REMINDER: &str = r#"
+SELECT id,user_app_id,body,execution_id,notification_type,send_at,user_timezone,pet_id,created_at
+FROM reminder
+WHERE id = $1 AND user_app_id = $2;
+"#;
+
+pub const QUERY_UPDATE_REMINDER_SCHEDULE: &str = r#"
+UPDATE reminder
+    SET send_at = $3,
+    user_timezone = $4,
+    execution_id = $5
+WHERE id = $1 AND user_app_id = $2;
+"#;
+
+pub const QUERY_INSERT_REMINDER_TEMPLATE: &str = r#"
+INSERT INTO reminder_template(user_app_id,name,body,created_at)
+VALUES($1,$2,$3,$4);
+"#;
+
+pub const QUERY_GET_REMINDER_TEMPLATES: &str = r#"
+SELECT id,name,body
+FROM reminder_template
+WHERE user_app_id = $1
+ORDER BY name ASC, id ASC;
+"#;
+
+pub const QUERY_GET_USER_PETS_VACCINE_BOOSTERS: &str = r#"
+SELECT
+    ph.id,ph.pet_id,p.pet_name,ph.description,ph.created_at,ph.booster_months,
+    EXISTS (
+        SELECT 1 FROM vaccine_digest_sent AS vds
+        WHERE vds.pet_health_id = ph.id AND vds.status = 'upcoming'
+    ) AS announced_upcoming
+FROM pet_health AS ph
+INNER JOIN pet AS p ON (p.id = ph.pet_id)
+WHERE p.user_app_id = $1
+    AND ph.health_record = 'vaccine'
+    AND ph.booster_months > 0
+    AND NOT EXISTS (
+        SELECT 1 FROM vaccine_digest_sent AS vds
+        WHERE vds.pet_health_id = ph.id AND vds.status = 'overdue'
+    )
+    AND NOT EXISTS (
+        SELECT 1 FROM pet_health AS newer
+        WHERE newer.pet_id = ph.pet_id
+            AND newer.health_record = 'vaccine'
+            AND newer.description = ph.description
+            AND (newer.created_at > ph.created_at
+                OR (newer.created_at = ph.created_at AND newer.id > ph.id))
+    )
+ORDER BY p.pet_name,ph.created_at,ph.id;
+"#;
+
+pub const QUERY_GET_VACCINE_DIGEST_RECIPIENTS: &str = r#"
+SELECT
+    u.id,u.email,u.phone_reminder,u.account_role,u.is_subscribed,u.is_enabled,
+    u.weight_unit,u.created_at,u.updated_at,
+    (SELECT r.notification_type FROM reminder AS r
+        WHERE r.user_app_id = u.id ORDER BY r.created_at DESC, r.id DESC LIMIT 1) AS notification_type,
+    (SELECT r.user_timezone FROM reminder AS r
+        WHERE r.user_app_id = u.id ORDER BY r.created_at DESC, r.id DESC LIMIT 1) AS user_timezone
+FROM user_app AS u
+WHERE u.is_enabled = 1 AND EXISTS (
+    SELECT 1 FROM pet_health AS ph
+    INNER JOIN pet AS p ON (p.id = ph.pet_id)
+    WHERE p.user_app_id = u.id
+        AND ph.health_record = 'vaccine'
+        AND ph.booster_months > 0
+        AND NOT EXISTS (
+            SELECT 1 FROM vaccine_digest_sent AS vds
+            WHERE vds.pet_health_id = ph.id AND vds.status = 'overdue'
+        )
+        AND NOT EXISTS (
+            SELECT 1 FROM pet_health AS newer
+            WHERE newer.pet_id = ph.pet_id
+                AND newer.health_record = 'vaccine'
+                AND newer.description = ph.description
+                AND (newer.created_at > ph.created_at
+                    OR (newer.created_at = ph.created_at AND newer.id > ph.id))
+        )
+);
+"#;
+
+pub const QUERY_INSERT_VACCINE_DIGEST_SENT: &str = r#"
+INSERT OR IGNORE INTO vaccine_digest_sent(pet_health_id,status,sent_at)
+VALUES($1,$2,$3);
+"#;
+
 pub const QUERY_DELETE_USER_APP_DATA: &str = r#"
+DELETE FROM pet_scan WHERE external_id IN (
+    SELECT peid.external_id FROM pet_external_id AS peid
+    INNER JOIN pet_linked AS plink ON (plink.id_pet_external_id=peid.id)
+    INNER JOIN pet AS p ON (p.id=plink.pet_id)
+    WHERE p.user_app_id = $1
+);
 DELETE FROM pet_external_id AS pexid WHERE pexid.id IN (
     SELECT plink.id_pet_external_id FROM pet_linked AS plink
     LEFT JOIN pet AS p on (p.id=plink.pet_id)
@@ -327,7 +1106,10 @@ DELETE FROM pet_external_id AS pexid WHERE pexid.id IN (
 );
 DELETE FROM pet WHERE user_app_id = $1;
 DELETE FROM owner_contact WHERE user_app_id = $1;
+UPDATE pet_external_id SET owner_user_id = NULL WHERE owner_user_id = $1;
+DELETE FROM pet_access WHERE user_id = $1;
 DELETE FROM reminder WHERE user_app_id = $1;
+DELETE FROM reminder_template WHERE user_app_id = $1;
 DELETE FROM user_sub_payment WHERE user_id = $1;
 UPDATE user_app SET is_enabled=0,is_subscribed=0,phone_reminder=NULL,updated_at=$2 WHERE id = $1;
 "#;
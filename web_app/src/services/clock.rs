@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+
+/// Clock reading the system time, used outside of tests
+#[derive(Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl crate::services::Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Clock always answering the same instant, makes time-dependent logic
+/// deterministic in tests
+#[cfg(test)]
+#[derive(Clone, Copy)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+#[cfg(test)]
+impl FixedClock {
+    /// Clock stopped at `rfc3339` (e.g. `2024-06-15T12:00:00Z`)
+    pub fn at(rfc3339: &str) -> Self {
+        Self(
+            DateTime::parse_from_rfc3339(rfc3339)
+                .expect("FixedClock needs an RFC 3339 instant")
+                .to_utc(),
+        )
+    }
+}
+
+#[cfg(test)]
+impl crate::services::Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::Clock;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_fixed_clock_today_depends_on_timezone() {
+        let clock = FixedClock::at("2024-06-15T03:00:00Z");
+
+        assert_eq!(clock.now(), clock.now());
+        assert_eq!(
+            clock.today_in(chrono_tz::UTC),
+            NaiveDate::from_ymd_opt(2024, 6, 15).unwrap()
+        );
+        // still the 14th in Mexico City
+        assert_eq!(
+            clock.today_in(chrono_tz::America::Mexico_City),
+            NaiveDate::from_ymd_opt(2024, 6, 14).unwrap()
+        );
+    }
+}
@@ -1,25 +1,279 @@
+pub mod clock;
 pub mod notification;
 pub mod storage;
 
-use crate::api;
+use crate::{api, consts};
 use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
 
 #[async_trait]
 pub trait StorageService {
     async fn save_pic(&self, path: &str, body: Vec<u8>) -> anyhow::Result<()>;
 
-    async fn get_pic_as_bytes(&self, file_name: &str) -> anyhow::Result<Vec<u8>>;
+    /// Saves the picture sending it in parts, so a large body isn't pushed
+    /// in a single request. Falls back to [save_pic](Self::save_pic).
+    async fn save_pic_streaming(&self, path: &str, body: Vec<u8>) -> anyhow::Result<()> {
+        self.save_pic(path, body).await
+    }
+
+    /// Saves the picture with [save_pic_streaming](Self::save_pic_streaming)
+    /// when it exceeds [PIC_MULTIPART_THRESHOLD_BYTES](consts::PIC_MULTIPART_THRESHOLD_BYTES),
+    /// with a simple [save_pic](Self::save_pic) otherwise.
+    async fn upload_pic(&self, path: &str, body: Vec<u8>) -> anyhow::Result<()> {
+        if body.len() > consts::PIC_MULTIPART_THRESHOLD_BYTES {
+            return self.save_pic_streaming(path, body).await;
+        }
+
+        self.save_pic(path, body).await
+    }
+
+    /// Gets a stored picture, `None` when it is gone even if the pet keeps
+    /// its path (e.g. removed by a bucket lifecycle rule).
+    async fn get_pic_as_bytes(&self, file_name: &str) -> anyhow::Result<Option<Vec<u8>>>;
+
+    /// Removes a stored picture, removing a missing one is not an error.
+    async fn delete_pic(&self, file_name: &str) -> anyhow::Result<()>;
 }
 
 #[async_trait]
-pub trait NotificationService {
+pub trait NotificationService: Sync {
     async fn send_reminder_to_phone_number(
         &self,
         info: &api::reminder::ScheduleReminderInfo,
     ) -> anyhow::Result<String>;
 
     async fn cancel_reminder_to_phone_number(&self, execution_id: &str) -> anyhow::Result<()>;
+
+    /// Cancels several scheduled reminders, trying all of them even if one fails.
+    ///
+    /// # Returns
+    /// * The last error raised, if any cancellation failed
+    async fn cancel_reminder_executions(&self, execution_ids: &[String]) -> anyhow::Result<()> {
+        let mut result = Ok(());
+        for execution_id in execution_ids {
+            if let Err(e) = self.cancel_reminder_to_phone_number(execution_id).await {
+                result = Err(e);
+            }
+        }
+
+        result
+    }
+
+    /// Retrieves the WhatsApp message id (wamid) a fired reminder was sent with.
+    ///
+    /// # Returns
+    /// * `None` if the reminder wasn't sent through WhatsApp yet, or the
+    ///   service can't tell
+    async fn get_reminder_message_id(&self, _execution_id: &str) -> anyhow::Result<Option<String>> {
+        Ok(None)
+    }
+}
+
+/// Source of the current time, so time-dependent logic can be tested with a
+/// fixed clock
+pub trait Clock {
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Current date in the given timezone, see
+    /// [get_now_date_in_tz](crate::front::utils::get_now_date_in_tz)
+    fn today_in(&self, tz: Tz) -> NaiveDate {
+        self.now().with_timezone(&tz).date_naive()
+    }
 }
 
 pub type ImplStorageService = Box<dyn StorageService>;
 pub type ImplNotificationService = Box<dyn NotificationService>;
+pub type ImplClock = Box<dyn Clock>;
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use super::*;
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    /// Storage serving `pics` and keeping the calls made to it, clones share
+    /// the calls so they can be checked after boxing one
+    #[derive(Clone, Default)]
+    pub struct FakeStorageService {
+        /// Pictures stored by path
+        pub pics: HashMap<String, Vec<u8>>,
+        /// Picture of the paths not in `pics`, those are missing without it
+        pub default_pic: Option<Vec<u8>>,
+        /// Path and size of the pictures saved in a single request
+        pub saved: Arc<Mutex<Vec<(String, usize)>>>,
+        /// Path and size of the pictures saved in parts
+        pub streamed: Arc<Mutex<Vec<(String, usize)>>>,
+        /// Paths fetched
+        pub fetched: Arc<Mutex<Vec<String>>>,
+        /// Paths deleted
+        pub deleted: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl FakeStorageService {
+        /// Storage serving `pic` for every path
+        pub fn serving(pic: Vec<u8>) -> Self {
+            Self {
+                default_pic: Some(pic),
+                ..Default::default()
+            }
+        }
+    }
+
+    #[async_trait]
+    impl StorageService for FakeStorageService {
+        async fn save_pic(&self, path: &str, body: Vec<u8>) -> anyhow::Result<()> {
+            self.saved
+                .lock()
+                .unwrap()
+                .push((path.to_string(), body.len()));
+            Ok(())
+        }
+
+        async fn save_pic_streaming(&self, path: &str, body: Vec<u8>) -> anyhow::Result<()> {
+            self.streamed
+                .lock()
+                .unwrap()
+                .push((path.to_string(), body.len()));
+            Ok(())
+        }
+
+        async fn get_pic_as_bytes(&self, file_name: &str) -> anyhow::Result<Option<Vec<u8>>> {
+            self.fetched.lock().unwrap().push(file_name.to_string());
+            Ok(self
+                .pics
+                .get(file_name)
+                .or(self.default_pic.as_ref())
+                .cloned())
+        }
+
+        async fn delete_pic(&self, file_name: &str) -> anyhow::Result<()> {
+            self.deleted.lock().unwrap().push(file_name.to_string());
+            Ok(())
+        }
+    }
+
+    /// Notification service keeping the calls made to it in order and failing
+    /// the configured ones, clones share the calls so they can be checked
+    /// after boxing one
+    #[derive(Clone)]
+    pub struct FakeNotificationService {
+        /// Execution id of every reminder scheduled
+        pub execution_id: &'static str,
+        /// WhatsApp message id the [execution_id](Self::execution_id) was sent with
+        pub message_id: Option<&'static str>,
+        /// Whether scheduling a reminder fails
+        pub fail_send: bool,
+        /// Execution whose cancellation fails
+        pub fail_cancel_of: Option<&'static str>,
+        /// Calls made, `send <body>` or `cancel <execution id>`
+        pub calls: Arc<Mutex<Vec<String>>>,
+        /// Reminders scheduled
+        pub sent: Arc<Mutex<Vec<api::reminder::ScheduleReminderInfo>>>,
+    }
+
+    impl Default for FakeNotificationService {
+        fn default() -> Self {
+            Self {
+                execution_id: "execution-id",
+                message_id: None,
+                fail_send: false,
+                fail_cancel_of: None,
+                calls: Arc::default(),
+                sent: Arc::default(),
+            }
+        }
+    }
+
+    impl FakeNotificationService {
+        /// Execution ids cancelled
+        pub fn cancelled(&self) -> Vec<String> {
+            self.calls
+                .lock()
+                .unwrap()
+                .iter()
+                .filter_map(|call| call.strip_prefix("cancel "))
+                .map(str::to_string)
+                .collect()
+        }
+    }
+
+    #[async_trait]
+    impl NotificationService for FakeNotificationService {
+        async fn send_reminder_to_phone_number(
+            &self,
+            info: &api::reminder::ScheduleReminderInfo,
+        ) -> anyhow::Result<String> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("send {}", info.body));
+            if self.fail_send {
+                anyhow::bail!("step function unavailable");
+            }
+            self.sent.lock().unwrap().push(info.clone());
+            Ok(self.execution_id.to_string())
+        }
+
+        async fn cancel_reminder_to_phone_number(&self, execution_id: &str) -> anyhow::Result<()> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("cancel {execution_id}"));
+            if self.fail_cancel_of == Some(execution_id) {
+                anyhow::bail!("execution {execution_id} still running");
+            }
+            Ok(())
+        }
+
+        async fn get_reminder_message_id(
+            &self,
+            execution_id: &str,
+        ) -> anyhow::Result<Option<String>> {
+            Ok(self
+                .message_id
+                .filter(|_| execution_id == self.execution_id)
+                .map(str::to_string))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::FakeStorageService;
+
+    #[ntex::test]
+    async fn test_upload_pic_uses_multipart_only_for_large_bodies() {
+        let storage = FakeStorageService::default();
+
+        storage
+            .upload_pic("pics/small", vec![0; consts::PIC_MULTIPART_THRESHOLD_BYTES])
+            .await
+            .unwrap();
+        storage
+            .upload_pic(
+                "pics/large",
+                vec![0; consts::PIC_MULTIPART_THRESHOLD_BYTES + 1],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *storage.saved.lock().unwrap(),
+            vec![(
+                "pics/small".to_string(),
+                consts::PIC_MULTIPART_THRESHOLD_BYTES
+            )]
+        );
+        assert_eq!(
+            *storage.streamed.lock().unwrap(),
+            vec![(
+                "pics/large".to_string(),
+                consts::PIC_MULTIPART_THRESHOLD_BYTES + 1
+            )]
+        );
+    }
+}
@@ -1,12 +1,106 @@
 use async_trait::async_trait;
 use serde_json::json;
 
-use crate::{api, config};
+use crate::{api, config, consts};
 use anyhow::Context;
 
 #[derive(Clone)]
 pub struct NotificationHandler {
     pub client: aws_sdk_sfn::Client,
+    /// Log the reminders instead of starting their executions, see
+    /// [`AppConfig::is_notifications_dry_run`](config::AppConfig::is_notifications_dry_run)
+    pub dry_run: bool,
+    /// Passes stopping the executions of cancelled reminders, see
+    /// [`stop_executions_until_gone`]
+    pub cancel_max_attempts: u32,
+}
+
+/// Step Functions calls made to cancel reminders
+#[async_trait]
+#[cfg_attr(test, mockall::automock)]
+trait ExecutionStopper {
+    async fn stop_execution(&self, execution_arn: &str) -> anyhow::Result<()>;
+
+    /// Whether the execution is still running, an error if it can't be found
+    async fn is_execution_running(&self, execution_arn: &str) -> anyhow::Result<bool>;
+}
+
+#[async_trait]
+impl ExecutionStopper for aws_sdk_sfn::Client {
+    async fn stop_execution(&self, execution_arn: &str) -> anyhow::Result<()> {
+        self.stop_execution()
+            .execution_arn(execution_arn)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    async fn is_execution_running(&self, execution_arn: &str) -> anyhow::Result<bool> {
+        let rsp = self
+            .describe_execution()
+            .execution_arn(execution_arn)
+            .send()
+            .await?;
+
+        Ok(matches!(
+            rsp.status(),
+            aws_sdk_sfn::types::ExecutionStatus::Running
+        ))
+    }
+}
+
+/// Stops the executions and checks they are gone, passing again over the
+/// ones still running up to `max_attempts` times.
+///
+/// Step Functions is eventually consistent, a just started execution may
+/// not be found by the first stop and would still send the reminder.
+///
+/// # Returns
+/// * An error listing how many executions couldn't be stopped, they are logged
+async fn stop_executions_until_gone(
+    stopper: &(dyn ExecutionStopper + Sync),
+    execution_ids: &[String],
+    max_attempts: u32,
+    retry_delay: std::time::Duration,
+) -> anyhow::Result<()> {
+    let mut pending: Vec<&String> = execution_ids
+        .iter()
+        .filter(|execution_id| !execution_id.starts_with(consts::DRY_RUN_EXECUTION_ID_PREFIX))
+        .collect();
+
+    for attempt in 1..=max_attempts.max(1) {
+        let mut still_running = vec![];
+        for execution_id in pending {
+            // a failed stop is checked below, the execution may be gone already
+            if let Err(e) = stopper.stop_execution(execution_id).await {
+                logfire::warn!(
+                    "reminder execution {execution_id} couldnt be stopped: {error}",
+                    execution_id = execution_id.clone(),
+                    error = e.to_string()
+                );
+            }
+
+            if !matches!(stopper.is_execution_running(execution_id).await, Ok(false)) {
+                still_running.push(execution_id);
+            }
+        }
+
+        pending = still_running;
+        if pending.is_empty() {
+            return Ok(());
+        }
+        if attempt < max_attempts {
+            ntex::time::sleep(retry_delay).await;
+        }
+    }
+
+    logfire::error!(
+        "reminder executions still running after {attempts} attempts: {execution_ids}",
+        attempts = i64::from(max_attempts),
+        execution_ids = format!("{pending:?}")
+    );
+    anyhow::bail!("{} reminder executions couldnt be stopped", pending.len())
 }
 
 #[async_trait]
@@ -15,6 +109,22 @@ impl crate::services::NotificationService for NotificationHandler {
         &self,
         info: &api::reminder::ScheduleReminderInfo,
     ) -> anyhow::Result<String> {
+        if self.dry_run {
+            let execution_id = format!(
+                "{}{}",
+                consts::DRY_RUN_EXECUTION_ID_PREFIX,
+                uuid::Uuid::new_v4()
+            );
+            logfire::info!(
+                "dry-run reminder {execution_id} at {when} through {channels}",
+                execution_id = execution_id.clone(),
+                when = info.when.to_rfc3339(),
+                channels = format!("{:?}", info.channels)
+            );
+
+            return Ok(execution_id);
+        }
+
         let rsp = self
             .client
             .start_execution()
@@ -28,7 +138,9 @@ impl crate::services::NotificationService for NotificationHandler {
                 json!({
                     "when": info.when.to_rfc3339(),
                     "reminder": {
+                        "channels": info.channels,
                         "phone": info.phone_number,
+                        "email": info.email,
                         "body": info.body
                     }
                 })
@@ -41,12 +153,191 @@ impl crate::services::NotificationService for NotificationHandler {
     }
 
     async fn cancel_reminder_to_phone_number(&self, execution_id: &str) -> anyhow::Result<()> {
-        self.client
-            .stop_execution()
+        self.cancel_reminder_executions(&[execution_id.to_string()])
+            .await
+    }
+
+    async fn cancel_reminder_executions(&self, execution_ids: &[String]) -> anyhow::Result<()> {
+        stop_executions_until_gone(
+            &self.client,
+            execution_ids,
+            self.cancel_max_attempts,
+            std::time::Duration::from_millis(consts::REMINDER_CANCEL_RETRY_DELAY_MS),
+        )
+        .await
+    }
+
+    async fn get_reminder_message_id(&self, execution_id: &str) -> anyhow::Result<Option<String>> {
+        if execution_id.starts_with(consts::DRY_RUN_EXECUTION_ID_PREFIX) {
+            return Ok(None);
+        }
+
+        let rsp = self
+            .client
+            .describe_execution()
             .execution_arn(execution_id)
             .send()
             .await?;
 
-        Ok(())
+        Ok(rsp.output().and_then(whatsapp_message_id))
+    }
+}
+
+/// Extracts the WhatsApp message id from the output of a reminder execution,
+/// the `send-reminders` lambda response wrapped in `Payload`
+fn whatsapp_message_id(execution_output: &str) -> Option<String> {
+    let output: serde_json::Value = serde_json::from_str(execution_output).ok()?;
+
+    output["Payload"]["results"]
+        .as_array()?
+        .iter()
+        .filter(|result| result["channel"] == "whatsapp")
+        .find_map(|result| result["message_id"].as_str().map(String::from))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_whatsapp_message_id_from_execution_output() {
+        let output = json!({
+            "StatusCode": 200,
+            "Payload": {
+                "req_id": "req",
+                "msg": "reminder was sent",
+                "results": [
+                    {"channel": "email", "sent": true, "error": null},
+                    {"channel": "whatsapp", "sent": true, "error": null, "message_id": "wamid.1"}
+                ]
+            }
+        });
+
+        assert_eq!(
+            whatsapp_message_id(&output.to_string()).as_deref(),
+            Some("wamid.1")
+        );
+        assert_eq!(whatsapp_message_id(r#"{"Payload":{"results":[]}}"#), None);
+        assert_eq!(whatsapp_message_id("not json"), None);
+    }
+
+    #[ntex::test]
+    async fn test_dry_run_returns_synthetic_execution_id() {
+        use crate::services::NotificationService;
+        use chrono::TimeZone;
+
+        // no endpoint is reachable, any call to AWS would fail
+        let handler = NotificationHandler {
+            client: aws_sdk_sfn::Client::from_conf(
+                aws_sdk_sfn::Config::builder()
+                    .behavior_version(aws_sdk_sfn::config::BehaviorVersion::latest())
+                    .region(aws_sdk_sfn::config::Region::new("us-east-2"))
+                    .endpoint_url("http://127.0.0.1:1")
+                    .build(),
+            ),
+            dry_run: true,
+            cancel_max_attempts: 1,
+        };
+
+        let execution_id = handler
+            .send_reminder_to_phone_number(&api::reminder::ScheduleReminderInfo {
+                user_id: 1,
+                channels: Default::default(),
+                phone_number: Some("5512345678".to_string()),
+                email: None,
+                when: chrono_tz::Tz::America__Mexico_City
+                    .with_ymd_and_hms(2030, 1, 1, 9, 0, 0)
+                    .unwrap(),
+                body: "Paseo".to_string(),
+                pet_id: None,
+            })
+            .await
+            .unwrap();
+
+        assert!(execution_id.starts_with(consts::DRY_RUN_EXECUTION_ID_PREFIX));
+        assert!(
+            handler
+                .cancel_reminder_to_phone_number(&execution_id)
+                .await
+                .is_ok()
+        );
+        assert_eq!(
+            handler
+                .get_reminder_message_id(&execution_id)
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[ntex::test]
+    async fn test_lingering_execution_is_stopped_again() {
+        let execution_ids = vec!["arn:execution:1".to_string(), "arn:execution:2".to_string()];
+
+        let mut stopper = MockExecutionStopper::new();
+        stopper
+            .expect_stop_execution()
+            .withf(|execution_arn| execution_arn == "arn:execution:1")
+            .times(1)
+            .returning(|_| Box::pin(async { Ok(()) }));
+        // not visible yet on the first pass, stopped on the second one
+        let mut stops = 0;
+        stopper
+            .expect_stop_execution()
+            .withf(|execution_arn| execution_arn == "arn:execution:2")
+            .times(2)
+            .returning(move |_| {
+                stops += 1;
+                let stopped = stops > 1;
+                Box::pin(async move {
+                    anyhow::ensure!(stopped, "ExecutionDoesNotExist");
+                    Ok(())
+                })
+            });
+        let mut checks = 0;
+        stopper
+            .expect_is_execution_running()
+            .withf(|execution_arn| execution_arn == "arn:execution:2")
+            .times(2)
+            .returning(move |_| {
+                checks += 1;
+                let running = checks == 1;
+                Box::pin(async move { Ok(running) })
+            });
+        stopper
+            .expect_is_execution_running()
+            .withf(|execution_arn| execution_arn == "arn:execution:1")
+            .times(1)
+            .returning(|_| Box::pin(async { Ok(false) }));
+
+        assert!(
+            stop_executions_until_gone(&stopper, &execution_ids, 3, std::time::Duration::ZERO)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[ntex::test]
+    async fn test_executions_left_running_after_max_attempts() {
+        let mut stopper = MockExecutionStopper::new();
+        stopper
+            .expect_stop_execution()
+            .times(2)
+            .returning(|_| Box::pin(async { Ok(()) }));
+        stopper
+            .expect_is_execution_running()
+            .times(2)
+            .returning(|_| Box::pin(async { Ok(true) }));
+
+        assert!(
+            stop_executions_until_gone(
+                &stopper,
+                &["arn:execution:1".to_string()],
+                2,
+                std::time::Duration::ZERO,
+            )
+            .await
+            .is_err()
+        );
     }
 }
@@ -1,9 +1,77 @@
-use crate::consts;
 use async_trait::async_trait;
+use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart};
+
+use crate::consts;
 
 #[derive(Clone)]
 pub struct StorageHandler {
     pub client: aws_sdk_s3::Client,
+    pub bucket_name: String,
+    pub key_prefix: String,
+}
+
+/// Builds the S3 key of a stored file from the environment prefix.
+///
+/// Every prefix segment is reduced to `[A-Za-z0-9._-]`, so a prefix built
+/// from a developer email or branch name is always a valid, predictable key.
+/// An empty prefix keeps the path as is.
+pub fn build_object_key(key_prefix: &str, path: &str) -> String {
+    let prefix = key_prefix
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            segment
+                .chars()
+                .map(|c| match c {
+                    'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '_' | '-' => c,
+                    _ => '-',
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+    let path = path.trim_start_matches('/');
+
+    if prefix.is_empty() {
+        return path.to_string();
+    }
+
+    format!("{prefix}/{path}")
+}
+
+impl StorageHandler {
+    /// Uploads the body in parts of
+    /// [PIC_MULTIPART_PART_SIZE_BYTES](consts::PIC_MULTIPART_PART_SIZE_BYTES),
+    /// returning the parts needed to complete the upload.
+    async fn upload_parts(
+        &self,
+        key: &str,
+        upload_id: &str,
+        body: &[u8],
+    ) -> anyhow::Result<Vec<CompletedPart>> {
+        let mut parts = Vec::new();
+        for (part_number, chunk) in (1..).zip(body.chunks(consts::PIC_MULTIPART_PART_SIZE_BYTES)) {
+            let part = self
+                .client
+                .upload_part()
+                .bucket(&self.bucket_name)
+                .key(key)
+                .upload_id(upload_id)
+                .part_number(part_number)
+                .body(aws_sdk_s3::primitives::ByteStream::from(chunk.to_vec()))
+                .send()
+                .await?;
+
+            parts.push(
+                CompletedPart::builder()
+                    .set_e_tag(part.e_tag)
+                    .part_number(part_number)
+                    .build(),
+            );
+        }
+
+        Ok(parts)
+    }
 }
 
 #[async_trait]
@@ -13,8 +81,8 @@ impl crate::services::StorageService for StorageHandler {
 
         self.client
             .put_object()
-            .bucket(consts::S3_MAIN_BUCKET_NAME)
-            .key(path)
+            .bucket(&self.bucket_name)
+            .key(build_object_key(&self.key_prefix, path))
             .body(body)
             .send()
             .await?;
@@ -22,21 +90,114 @@ impl crate::services::StorageService for StorageHandler {
         Ok(())
     }
 
-    async fn get_pic_as_bytes(&self, file_name: &str) -> anyhow::Result<Vec<u8>> {
-        let object = self
+    async fn save_pic_streaming(&self, path: &str, body: Vec<u8>) -> anyhow::Result<()> {
+        let key = build_object_key(&self.key_prefix, path);
+        let upload = self
             .client
-            .get_object()
-            .bucket(consts::S3_MAIN_BUCKET_NAME)
-            .key(file_name)
+            .create_multipart_upload()
+            .bucket(&self.bucket_name)
+            .key(&key)
+            .send()
+            .await?;
+        let upload_id = upload
+            .upload_id()
+            .ok_or_else(|| anyhow::anyhow!("multipart upload without id for {key}"))?;
+
+        let uploaded = self.upload_parts(&key, upload_id, &body).await;
+        let parts = match uploaded {
+            Ok(parts) => parts,
+            Err(e) => {
+                // leftover parts are billed until the upload is aborted
+                if let Err(abort_err) = self
+                    .client
+                    .abort_multipart_upload()
+                    .bucket(&self.bucket_name)
+                    .key(&key)
+                    .upload_id(upload_id)
+                    .send()
+                    .await
+                {
+                    logfire::warn!(
+                        "failed to abort multipart upload {upload_id}: {error}",
+                        upload_id = upload_id.to_string(),
+                        error = abort_err.to_string()
+                    );
+                }
+                return Err(e);
+            }
+        };
+
+        self.client
+            .complete_multipart_upload()
+            .bucket(&self.bucket_name)
+            .key(&key)
+            .upload_id(upload_id)
+            .multipart_upload(
+                CompletedMultipartUpload::builder()
+                    .set_parts(Some(parts))
+                    .build(),
+            )
             .send()
             .await?;
 
-        Ok(object
-            .body
-            .collect()
+        Ok(())
+    }
+
+    async fn get_pic_as_bytes(&self, file_name: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let object = match self
+            .client
+            .get_object()
+            .bucket(&self.bucket_name)
+            .key(build_object_key(&self.key_prefix, file_name))
+            .send()
             .await
-            .map(|package| package.into_bytes())?
-            .into_iter()
-            .collect::<Vec<u8>>())
+        {
+            Ok(object) => object,
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_no_such_key()) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Some(
+            object
+                .body
+                .collect()
+                .await
+                .map(|package| package.into_bytes())?
+                .into_iter()
+                .collect::<Vec<u8>>(),
+        ))
+    }
+
+    async fn delete_pic(&self, file_name: &str) -> anyhow::Result<()> {
+        self.client
+            .delete_object()
+            .bucket(&self.bucket_name)
+            .key(build_object_key(&self.key_prefix, file_name))
+            .send()
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_object_key_without_prefix() {
+        assert_eq!(build_object_key("", "pics/123"), "pics/123");
+    }
+
+    #[test]
+    fn test_build_object_key_sanitizes_prefix() {
+        assert_eq!(
+            build_object_key("dev/ana+pets@example.com/", "pics/123"),
+            "dev/ana-pets-example.com/pics/123"
+        );
+        assert_eq!(
+            build_object_key("/local//josé ñ", "/pics/123"),
+            "local/jos---/pics/123"
+        );
     }
 }
@@ -6,15 +6,18 @@
 //! - HTTP client for external API calls
 //! - Time-based One-Time Password (TOTP) generation
 
-use crate::config;
+use crate::{config, consts};
 use anyhow::{Context, anyhow};
 use argon2::Argon2;
 use sqlx::{
     SqlitePool,
     sqlite::{SqliteConnectOptions, SqliteJournalMode},
 };
-use std::{str::FromStr, sync::LazyLock};
-use totp_rs::{Algorithm, Secret, TOTP};
+use std::{
+    str::FromStr,
+    sync::{LazyLock, OnceLock},
+};
+use totp_rs::{Secret, TOTP};
 use uuid::Uuid;
 
 /// Detects image format from magic bytes.
@@ -26,29 +29,38 @@ use uuid::Uuid;
 /// * `bytes` - The image file bytes
 ///
 /// # Returns
-/// File extension string ("jpg", "png", "gif", "webp", etc.)
-pub fn detect_image_format(bytes: &[u8]) -> &'static str {
+/// File extension string ("jpg", "png", "gif", "webp", etc.), `None` if the
+/// format is not recognized
+pub fn sniff_image_format(bytes: &[u8]) -> Option<&'static str> {
     if bytes.len() < 4 {
-        return "jpg"; // Default fallback
+        return None;
     }
 
     // Check magic bytes for common image formats
     match &bytes[0..4] {
-        [0x89, 0x50, 0x4E, 0x47] => "png", // PNG
-        [0xFF, 0xD8, 0xFF, ..] => "jpg",   // JPEG
-        [0x47, 0x49, 0x46, ..] => "gif",   // GIF
-        [0x52, 0x49, 0x46, 0x46] if bytes.len() >= 12 && &bytes[8..12] == b"WEBP" => "webp", // WebP
-        [0x42, 0x4D, ..] => "bmp",         // BMP
+        [0x89, 0x50, 0x4E, 0x47] => Some("png"), // PNG
+        [0xFF, 0xD8, 0xFF, ..] => Some("jpg"),   // JPEG
+        [0x47, 0x49, 0x46, ..] => Some("gif"),   // GIF
+        [0x52, 0x49, 0x46, 0x46] if bytes.len() >= 12 && &bytes[8..12] == b"WEBP" => Some("webp"), // WebP
+        [0x42, 0x4D, ..] => Some("bmp"), // BMP
         // HEIC signatures (ftypheic, ftypheix, ftypheim, ftypmsf1)
         // usually start at offset 4, bytes 4-12 are "ftypheic" etc
         _ if bytes.len() >= 12 => match &bytes[4..12] {
-            b"ftypheic" | b"ftypheix" | b"ftypheim" | b"ftypmsf1" => "heic",
-            _ => "jpg",
+            b"ftypheic" | b"ftypheix" | b"ftypheim" | b"ftypmsf1" => Some("heic"),
+            _ => None,
         },
-        _ => "jpg", // Default fallback
+        _ => None,
     }
 }
 
+/// Detects image format from magic bytes, see [`sniff_image_format`].
+///
+/// # Returns
+/// File extension string, "jpg" if the format is not recognized
+pub fn detect_image_format(bytes: &[u8]) -> &'static str {
+    sniff_image_format(bytes).unwrap_or("jpg")
+}
+
 /// Creates and configures a SQLite connection pool with optional encryption.
 ///
 /// This function establishes a database connection pool that can be configured for
@@ -61,12 +73,16 @@ pub fn detect_image_format(bytes: &[u8]) -> &'static str {
 /// # Database Configuration
 /// ## Encrypted Database (SQLCipher)
 /// When `encrypted` is `true`, the following SQLCipher settings are applied:
-/// - **Cipher Page Size**: 1024 bytes (balance between security and performance)
-/// - **KDF Iterations**: 64,000 (PBKDF2 rounds for key derivation)
-/// - **HMAC Algorithm**: SHA1 (for authenticated encryption)
-/// - **KDF Algorithm**: PBKDF2-HMAC-SHA1 (industry standard)
+/// - **Cipher Page Size**: `DB_CIPHER_PAGE_SIZE`, [`DB_CIPHER_LEGACY_PAGE_SIZE`](crate::consts::DB_CIPHER_LEGACY_PAGE_SIZE) by default
+/// - **KDF Iterations**: `DB_CIPHER_KDF_ITER`, [`DB_CIPHER_LEGACY_KDF_ITER`](crate::consts::DB_CIPHER_LEGACY_KDF_ITER) by default
+/// - **HMAC Algorithm**: `DB_CIPHER_HMAC_ALGORITHM`, [`DB_CIPHER_LEGACY_HMAC_ALGORITHM`](crate::consts::DB_CIPHER_LEGACY_HMAC_ALGORITHM) by default
+/// - **KDF Algorithm**: PBKDF2 with the same hash as the HMAC
 /// - **Journal Mode**: DELETE (secure deletion of journal files)
 ///
+/// The defaults are the settings existing databases were created with, a
+/// database only opens with the settings it was encrypted with, so the pool
+/// is checked right away to fail at startup instead of on the first request.
+///
 /// ## Unencrypted Database
 /// When `encrypted` is `false`, uses standard SQLite with:
 /// - **Foreign Keys**: Enabled for referential integrity
@@ -76,17 +92,33 @@ pub async fn setup_sqlite_db_pool(encrypted: bool) -> anyhow::Result<SqlitePool>
         .get()
         .context("failed to get app config")?;
     if encrypted {
-        return Ok(SqlitePool::connect_with(
+        let hmac = app_config
+            .db_cipher_hmac()
+            .context("DB_CIPHER_HMAC_ALGORITHM must be one of SHA1, SHA256 or SHA512")?;
+        let db_pool = SqlitePool::connect_with(
             SqliteConnectOptions::from_str(&app_config.db_host)?
                 .pragma("key", &app_config.db_pass_encrypt)
-                .pragma("cipher_page_size", "1024")
-                .pragma("kdf_iter", "64000")
-                .pragma("cipher_hmac_algorithm", "HMAC_SHA1")
-                .pragma("cipher_kdf_algorithm", "PBKDF2_HMAC_SHA1")
+                .pragma(
+                    "cipher_page_size",
+                    app_config.db_cipher_page_size().to_string(),
+                )
+                .pragma("kdf_iter", app_config.db_cipher_kdf_iter().to_string())
+                .pragma("cipher_hmac_algorithm", format!("HMAC_{hmac}"))
+                .pragma("cipher_kdf_algorithm", format!("PBKDF2_HMAC_{hmac}"))
                 .pragma("foreign_keys", "ON")
                 .journal_mode(SqliteJournalMode::Delete),
         )
-        .await?);
+        .await?;
+
+        sqlx::query("SELECT count(*) FROM sqlite_master")
+            .execute(&db_pool)
+            .await
+            .context(
+                "the database couldnt be decrypted, if the DB_CIPHER_* settings were \
+                changed it has to be rekeyed with sqlcipher_export first",
+            )?;
+
+        return Ok(db_pool);
     }
 
     Ok(SqlitePool::connect_with(
@@ -154,6 +186,53 @@ pub fn build_random_csrf_key() -> anyhow::Result<[u8; 32]> {
     build_csrf_key(&Uuid::new_v4(), &Uuid::new_v4())
 }
 
+/// Hashes a short secret chosen by a user, e.g. the public PIN of a pet.
+///
+/// Uses Argon2 with a random salt; the result is a PHC string holding the
+/// salt and parameters, ready to be stored and checked with [verify_secret].
+///
+/// # Returns
+/// * `anyhow::Result<String>` - PHC formatted hash
+pub fn hash_secret(secret: &str) -> anyhow::Result<String> {
+    use argon2::password_hash::{PasswordHasher, SaltString, rand_core::OsRng};
+
+    Ok(Argon2::default()
+        .hash_password(secret.as_bytes(), &SaltString::generate(&mut OsRng))
+        .map_err(|err| anyhow!("secret couldn't be hashed: {}", err))?
+        .to_string())
+}
+
+/// Checks a secret against a hash built by [hash_secret].
+///
+/// A malformed hash never matches.
+pub fn verify_secret(secret: &str, hash: &str) -> bool {
+    use argon2::password_hash::{PasswordHash, PasswordVerifier};
+
+    PasswordHash::new(hash)
+        .map(|hash| {
+            Argon2::default()
+                .verify_password(secret.as_bytes(), &hash)
+                .is_ok()
+        })
+        .unwrap_or_default()
+}
+
+/// Builds a random, url safe token that doesn't reveal anything about what it
+/// points to, e.g. the temporary share links of a pet profile.
+///
+/// # Returns
+/// * `String` - [PET_SHARE_TOKEN_BYTES](consts::PET_SHARE_TOKEN_BYTES) random
+///   bytes encoded as unpadded url safe base64
+pub fn generate_opaque_token() -> String {
+    use argon2::password_hash::rand_core::{OsRng, RngCore};
+    use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+
+    let mut bytes = [0u8; consts::PET_SHARE_TOKEN_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+
+    BASE64_URL_SAFE_NO_PAD.encode(bytes)
+}
+
 /// Shared HTTP client for making external API requests.
 ///
 /// This is a globally available, lazily-initialized HTTP client that provides:
@@ -193,61 +272,52 @@ pub fn build_random_csrf_key() -> anyhow::Result<[u8; 32]> {
 /// - Implements automatic retry logic and connection pooling
 pub static REQUEST_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(reqwest::Client::new);
 
-/// TOTP algorithm for cryptographic hashing
-const TOTP_HASH_ALGORITHM: Algorithm = Algorithm::SHA512;
-/// Number of digits in generated TOTP codes
-const TOTP_CODE_DIGITS: usize = 6;
-/// Time window tolerance for TOTP validation (allows previous/next window)
-const TOTP_VALIDATION_SKEW: u8 = 1;
-/// Time duration in seconds for each TOTP code validity window
-const TOTP_TIME_STEP_SECONDS: u64 = 60;
-
-/// Time-based One-Time Password (TOTP) client for two-factor authentication.
+/// Time-based One-Time Password (TOTP) client to verify the reminder phone numbers.
 ///
-/// This client generates and validates TOTP codes using the following configuration:
-/// - **Algorithm**: SHA-512 (more secure than SHA-1 or SHA-256)
-/// - **Digits**: 6 (standard TOTP code length)
-/// - **Skew**: 1 (allows codes from previous/next time window)
-/// - **Step**: 60 seconds (time window for each code)
+/// Built once at startup by [init_totp_client] from the `TOTP_*` values of
+/// [AppConfig](config::AppConfig):
+/// - **Algorithm**: SHA-512 by default
+/// - **Digits**: 6 by default, 6 to 8 allowed
+/// - **Skew**: 1 by default (allows codes from previous/next time window)
+/// - **Step**: 60 seconds by default (time window for each code)
 /// - **Secret**: Derived from application's OTP_SECRET UUID
 ///
-/// # Security Features
-/// - **High Entropy Secret**: Uses UUID as base secret material
-/// - **Strong Hashing**: SHA-512 provides better security than SHA-1
-/// - **Time Synchronization**: 60-second windows balance security and usability
-/// - **Limited Skew**: 1-step tolerance for clock drift
-///
 /// # Examples
 /// ```rust
 /// // Generate current TOTP code
-/// let current_code = TOTP_CLIENT.generate_current()?;
-/// println!("Current code: {}", current_code);
+/// let current_code = totp_client()?.generate_current()?;
 ///
 /// // Verify a user-provided code
-/// let user_code = "123456";
-/// let is_valid = TOTP_CLIENT.check_current(user_code)?;
-///
-/// // Get code for specific timestamp
-/// let timestamp = 1234567890;
-/// let code_at_time = TOTP_CLIENT.generate(timestamp);
+/// let is_valid = totp_client()?.check_current("123456")?;
 /// ```
 ///
 /// # Implementation Notes
 /// - The secret is regenerated on each application restart for security
-/// - Codes are valid for 60 seconds with ±1 step tolerance (total 3 minutes)
+/// - With the defaults codes are valid for 60 seconds with ±1 step tolerance (total 3 minutes)
 /// - Uses the `totp-rs` crate for RFC 6238 compliance
-pub static TOTP_CLIENT: LazyLock<TOTP> = LazyLock::new(|| {
-    TOTP::new(
-        TOTP_HASH_ALGORITHM,
-        TOTP_CODE_DIGITS,
-        TOTP_VALIDATION_SKEW,
-        TOTP_TIME_STEP_SECONDS,
+static TOTP_CLIENT: OnceLock<TOTP> = OnceLock::new();
+
+/// Builds the [TOTP client](TOTP_CLIENT) from the application config.
+///
+/// # Errors
+/// Returns an error if the TOTP parameters are invalid or the client was
+/// already initialized
+pub fn init_totp_client(app_config: &config::AppConfig) -> anyhow::Result<()> {
+    let totp = app_config.build_totp(
         Secret::Raw(config::OTP_SECRET.as_bytes().to_vec())
             .to_bytes()
-            .unwrap(),
-    )
-    .unwrap()
-});
+            .map_err(|e| anyhow!("invalid OTP secret: {e}"))?,
+    )?;
+
+    TOTP_CLIENT
+        .set(totp)
+        .map_err(|_| anyhow!("TOTP client already initialized"))
+}
+
+/// The [TOTP client](TOTP_CLIENT), see [init_totp_client]
+pub fn totp_client() -> anyhow::Result<&'static TOTP> {
+    TOTP_CLIENT.get().context("TOTP client not initialized")
+}
 
 #[cfg(test)]
 mod tests {
@@ -298,5 +368,7 @@ mod tests {
         // Fallback
         assert_eq!(detect_image_format(&[0x00, 0x00, 0x00, 0x00]), "jpg");
         assert_eq!(detect_image_format(&[]), "jpg");
+        assert_eq!(sniff_image_format(&[0x00, 0x00, 0x00, 0x00]), None);
+        assert_eq!(sniff_image_format(&[]), None);
     }
 }
@@ -6,6 +6,7 @@
 //! ## Modules
 //!
 //! - [`whatsapp`] - WhatsApp Business API webhook handlers
+//! - [`schedule`] - Scheduled jobs triggered by the EventBridge scheduler
 //!
 //! ## Future Integrations
 //!
@@ -15,4 +16,5 @@
 //! - Third-party service integrations
 
 pub mod routes;
+pub mod schedule;
 pub mod whatsapp;
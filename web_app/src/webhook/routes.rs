@@ -15,3 +15,17 @@ pub fn whatsapp(cfg: &mut web::ServiceConfig) {
             .service((super::whatsapp::verify, super::whatsapp::receive)),
     );
 }
+
+/// Configures the routes the scheduler calls to run the scheduled jobs.
+///
+/// They are authenticated with the `SCHEDULED_JOBS_TOKEN` bearer token.
+///
+/// # Routes
+/// - `POST /webhook/schedule/vaccine-digest` - Weekly vaccine booster digest
+/// - `POST /webhook/schedule/purge-scans` - Daily purge of old profile visits
+pub fn schedule(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::scope("/webhook/schedule").service((
+        super::schedule::send_vaccine_digests,
+        super::schedule::purge_pet_scans,
+    )));
+}
@@ -0,0 +1,125 @@
+//! Scheduled jobs endpoint handlers
+//!
+//! The jobs are triggered by an EventBridge schedule through an API
+//! destination, see `terraform/main.tf`. The requests carry the
+//! `SCHEDULED_JOBS_TOKEN` as a bearer token, the endpoints are disabled
+//! while it isn't configured.
+
+use crate::{
+    api, config,
+    front::{AppState, errors},
+};
+use ntex::web;
+
+/// Checks the `Authorization` header value against the configured token,
+/// compared in constant time.
+fn is_valid_token(expected_token: Option<&str>, authorization_header: &str) -> bool {
+    let Some(expected) = expected_token.filter(|token| !token.is_empty()) else {
+        return false;
+    };
+    let Some(token) = authorization_header.strip_prefix("Bearer ") else {
+        return false;
+    };
+
+    token.len() == expected.len() && openssl::memcmp::eq(token.as_bytes(), expected.as_bytes())
+}
+
+/// Rejects the request unless it carries the scheduled jobs token, returns
+/// the app config otherwise.
+fn authorize(req: &web::HttpRequest) -> Result<&'static config::AppConfig, web::Error> {
+    let app_config = config::APP_CONFIG
+        .get()
+        .expect("APP_CONFIG should be initialized before starting web server");
+
+    let authorization = req
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if !is_valid_token(app_config.scheduled_jobs_token.as_deref(), authorization) {
+        return Err(errors::UserError::Unauthorized.into());
+    }
+
+    Ok(app_config)
+}
+
+/// Weekly vaccine booster digest (POST)
+///
+/// Called every hour, each owner gets the digest when it is the digest time
+/// in their own timezone, see [`api::reminder::send_vaccine_digests`].
+///
+/// # Returns
+/// - 200 with the number of digests sent
+/// - 401 if the bearer token is missing or wrong
+#[web::post("/vaccine-digest")]
+pub async fn send_vaccine_digests(
+    req: web::HttpRequest,
+    app_state: web::types::State<AppState>,
+) -> Result<impl web::Responder, web::Error> {
+    let app_config = authorize(&req)?;
+
+    let sent = api::reminder::send_vaccine_digests(
+        &app_state.repo,
+        &app_state.notification_service,
+        &app_state.clock,
+        app_config.default_timezone(),
+    )
+    .await
+    .map_err(|e| {
+        errors::ServerError::InternalServerError(format!(
+            "function send_vaccine_digests raised an error: {e}"
+        ))
+    })?;
+    logfire::info!("vaccine digests sent: {sent}", sent = sent as i64);
+
+    Ok(web::HttpResponse::Ok()
+        .content_type("text/plain")
+        .body(sent.to_string()))
+}
+
+/// Daily purge of the old public profile visits (POST)
+///
+/// Deletes the visits older than
+/// [PET_SCAN_RETENTION_DAYS](crate::consts::PET_SCAN_RETENTION_DAYS) days,
+/// see [`api::pet::purge_pet_scans`].
+///
+/// # Returns
+/// - 200 with the number of visits deleted
+/// - 401 if the bearer token is missing or wrong
+#[web::post("/purge-scans")]
+pub async fn purge_pet_scans(
+    req: web::HttpRequest,
+    app_state: web::types::State<AppState>,
+) -> Result<impl web::Responder, web::Error> {
+    authorize(&req)?;
+
+    let deleted = api::pet::purge_pet_scans(&app_state.repo, &app_state.clock)
+        .await
+        .map_err(|e| {
+            errors::ServerError::InternalServerError(format!(
+                "function purge_pet_scans raised an error: {e}"
+            ))
+        })?;
+    logfire::info!("pet scans purged: {deleted}", deleted = deleted as i64);
+
+    Ok(web::HttpResponse::Ok()
+        .content_type("text/plain")
+        .body(deleted.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_token() {
+        assert!(is_valid_token(Some("secret"), "Bearer secret"));
+        assert!(!is_valid_token(Some("secret"), "Bearer secreto"));
+        assert!(!is_valid_token(Some("secret"), "secret"));
+        assert!(!is_valid_token(Some("secret"), ""));
+
+        // not configured, every request is rejected
+        assert!(!is_valid_token(None, "Bearer "));
+        assert!(!is_valid_token(Some(""), "Bearer "));
+    }
+}
@@ -1,14 +1,19 @@
 //! # WhatsApp API Client
 //!
 //! This module provides a client for sending messages to WhatsApp Business API.
-//! It handles authentication and message sending for text, interactive, and document messages.
+//! It handles authentication and message sending for text, interactive, and document messages,
+//! as well as media uploads and downloads.
+//!
+//! Requests are retried with exponential backoff on rate limiting (429),
+//! server errors (5xx) and connection errors; any other error fails fast.
 
 use super::schemas::{
     OutgoingDocumentMessage, OutgoingImageMessage, OutgoingInteractiveMessage, OutgoingTextMessage,
-    WhatsAppMessageResponse,
+    WhatsAppMessageResponse, WhatsAppMessageStatus,
 };
-use crate::config;
+use crate::{config, consts};
 use anyhow::{Context, Result};
+use std::time::Duration;
 
 /// Response from WhatsApp media upload API
 #[derive(Debug, serde::Deserialize)]
@@ -17,6 +22,15 @@ pub struct MediaUploadResponse {
     pub id: String,
 }
 
+/// Response from WhatsApp media URL API
+#[derive(Debug, serde::Deserialize)]
+pub struct MediaUrlResponse {
+    /// Short-lived URL to download the media, requires the auth token
+    pub url: String,
+    /// Media size in bytes
+    pub file_size: Option<u64>,
+}
+
 /// Typing indicator payload
 #[derive(Debug, serde::Serialize)]
 struct TypingIndicator {
@@ -33,6 +47,54 @@ struct TypingIndicatorType {
     indicator_type: String,
 }
 
+/// Retry settings for WhatsApp API requests
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first request
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled on each following one
+    pub base_delay: Duration,
+    /// Upper bound for any single delay
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Exponential backoff delay to wait after the failed `attempt` (1-based)
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: consts::WHATSAPP_MAX_REQUEST_ATTEMPTS,
+            base_delay: Duration::from_millis(consts::WHATSAPP_RETRY_BASE_DELAY_MS),
+            max_delay: Duration::from_millis(consts::WHATSAPP_RETRY_MAX_DELAY_MS),
+        }
+    }
+}
+
+/// Whether a response status is worth retrying (rate limited or server error)
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Delay requested by the server through the `Retry-After` header, in seconds
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
 /// WhatsApp API client for sending messages and uploading media
 #[derive(Clone)]
 pub struct WhatsAppClient {
@@ -40,10 +102,17 @@ pub struct WhatsAppClient {
     client: reqwest::Client,
     /// WhatsApp Business API endpoint for sending messages
     endpoint: String,
+    /// Graph API base URL for media requests
+    graph_api_url: String,
     /// WhatsApp Business phone number ID
     phone_number_id: u64,
     /// Authentication token
     auth_token: String,
+    /// Retry settings for transient failures
+    retry_policy: RetryPolicy,
+    /// Log the messages instead of sending them, see
+    /// [`AppConfig::is_notifications_dry_run`](config::AppConfig::is_notifications_dry_run)
+    dry_run: bool,
 }
 
 impl WhatsAppClient {
@@ -56,8 +125,11 @@ impl WhatsAppClient {
         Ok(Self {
             client: reqwest::Client::new(),
             endpoint: app_config.whatsapp_send_msg_endpoint(),
+            graph_api_url: consts::WHATSAPP_GRAPH_API_URL.to_string(),
             phone_number_id: app_config.whatsapp_business_phone_number_id,
             auth_token: app_config.whatsapp_business_auth.clone(),
+            retry_policy: RetryPolicy::default(),
+            dry_run: app_config.is_notifications_dry_run(),
         })
     }
 
@@ -202,26 +274,25 @@ impl WhatsAppClient {
         mime_type: &str,
         filename: &str,
     ) -> Result<String> {
-        let upload_endpoint = format!(
-            "https://graph.facebook.com/v22.0/{}/media",
-            self.phone_number_id
-        );
+        let upload_endpoint = format!("{}/{}/media", self.graph_api_url, self.phone_number_id);
 
-        // Create multipart form using reqwest
-        let file_part = reqwest::multipart::Part::bytes(file_bytes)
-            .file_name(filename.to_string())
-            .mime_str(mime_type)?;
+        let response = self
+            .send_with_retry(|| {
+                // A multipart form can't be reused, so it's rebuilt on each attempt
+                let file_part = reqwest::multipart::Part::bytes(file_bytes.clone())
+                    .file_name(filename.to_string())
+                    .mime_str(mime_type)?;
 
-        let form = reqwest::multipart::Form::new()
-            .text("messaging_product", "whatsapp")
-            .part("file", file_part);
+                let form = reqwest::multipart::Form::new()
+                    .text("messaging_product", "whatsapp")
+                    .part("file", file_part);
 
-        let response = self
-            .client
-            .post(&upload_endpoint)
-            .header("Authorization", format!("Bearer {}", self.auth_token))
-            .multipart(form)
-            .send()
+                Ok(self
+                    .client
+                    .post(&upload_endpoint)
+                    .header("Authorization", format!("Bearer {}", self.auth_token))
+                    .multipart(form))
+            })
             .await
             .context("Failed to upload media to WhatsApp API")?;
 
@@ -247,18 +318,114 @@ impl WhatsAppClient {
         Ok(upload_response.id)
     }
 
+    /// Downloads media sent by a user (e.g. a photo) as bytes
+    ///
+    /// WhatsApp only sends the media id in the webhook, so the download URL
+    /// is requested first and then the media is fetched from it.
+    ///
+    /// # Arguments
+    /// * `media_id` - Media ID from the incoming message
+    ///
+    /// # Returns
+    /// * `Result<Vec<u8>>` - Media content
+    ///
+    /// # Errors
+    /// Fails if any request fails or the media is bigger than
+    /// [`PIC_PET_MAX_SIZE_BYTES`](consts::PIC_PET_MAX_SIZE_BYTES)
+    pub async fn download_media(&self, media_id: &str) -> Result<Vec<u8>> {
+        let media_endpoint = format!("{}/{}", self.graph_api_url, media_id);
+
+        let response = self
+            .send_with_retry(|| {
+                Ok(self
+                    .client
+                    .get(&media_endpoint)
+                    .header("Authorization", format!("Bearer {}", self.auth_token)))
+            })
+            .await
+            .context("Failed to get media URL from WhatsApp API")?
+            .error_for_status()
+            .context("WhatsApp media URL request returned error status")?;
+
+        let media: MediaUrlResponse = response
+            .json()
+            .await
+            .context("Failed to parse WhatsApp media URL response")?;
+
+        let too_big = |size: u64| size > consts::PIC_PET_MAX_SIZE_BYTES as u64;
+        let too_big_error = || {
+            anyhow::anyhow!(
+                "Media is too big. Maximum size: {} bytes",
+                consts::PIC_PET_MAX_SIZE_BYTES
+            )
+        };
+        if media.file_size.is_some_and(too_big) {
+            return Err(too_big_error());
+        }
+
+        let mut response = self
+            .send_with_retry(|| {
+                Ok(self
+                    .client
+                    .get(&media.url)
+                    .header("Authorization", format!("Bearer {}", self.auth_token)))
+            })
+            .await
+            .context("Failed to download media from WhatsApp")?
+            .error_for_status()
+            .context("WhatsApp media download returned error status")?;
+        if response.content_length().is_some_and(too_big) {
+            return Err(too_big_error());
+        }
+
+        // the declared sizes can be missing or wrong, the download stops
+        // as soon as the limit is passed
+        let mut body = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .context("Failed to read WhatsApp media body")?
+        {
+            if too_big((body.len() + chunk.len()) as u64) {
+                return Err(too_big_error());
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        Ok(body)
+    }
+
     /// Internal method to send any message type to WhatsApp API
+    ///
+    /// In dry-run mode the message is only logged and a fake response returned.
     async fn send_message<T: serde::Serialize>(
         &self,
         message: &T,
     ) -> Result<WhatsAppMessageResponse> {
+        if self.dry_run {
+            logfire::info!(
+                "dry-run WhatsApp message: {message}",
+                message = serde_json::to_string(message).unwrap_or_default()
+            );
+
+            return Ok(WhatsAppMessageResponse {
+                messaging_product: "whatsapp".to_string(),
+                contacts: vec![],
+                messages: vec![WhatsAppMessageStatus {
+                    id: format!("wamid.dry-run.{}", uuid::Uuid::new_v4()),
+                }],
+            });
+        }
+
         let response = self
-            .client
-            .post(&self.endpoint)
-            .header("Authorization", format!("Bearer {}", self.auth_token))
-            .header("Content-Type", "application/json")
-            .json(message)
-            .send()
+            .send_with_retry(|| {
+                Ok(self
+                    .client
+                    .post(&self.endpoint)
+                    .header("Authorization", format!("Bearer {}", self.auth_token))
+                    .header("Content-Type", "application/json")
+                    .json(message))
+            })
             .await
             .context("Failed to send request to WhatsApp API")?;
 
@@ -279,6 +446,56 @@ impl WhatsAppClient {
 
         Ok(whatsapp_response)
     }
+
+    /// Sends the request built by `build_request`, retrying transient failures
+    ///
+    /// Responses with 429/5xx status and connection errors are retried with
+    /// exponential backoff, honoring a `Retry-After` header when present.
+    /// Any other response is returned right away, so callers still check the
+    /// status; the last response or error is returned once attempts run out.
+    async fn send_with_retry<F>(&self, build_request: F) -> Result<reqwest::Response>
+    where
+        F: Fn() -> Result<reqwest::RequestBuilder>,
+    {
+        let mut attempt = 1;
+
+        loop {
+            let attempts_left = attempt < self.retry_policy.max_attempts;
+
+            let delay = match build_request()?.send().await {
+                Ok(response) => {
+                    let status = response.status();
+                    if !attempts_left || !is_retryable_status(status) {
+                        return Ok(response);
+                    }
+
+                    logfire::warn!(
+                        "WhatsApp API returned {status} (attempt {attempt}/{max_attempts}), retrying",
+                        status = status.to_string(),
+                        attempt = i64::from(attempt),
+                        max_attempts = i64::from(self.retry_policy.max_attempts)
+                    );
+                    retry_after(&response).unwrap_or_else(|| self.retry_policy.backoff(attempt))
+                }
+                Err(err) => {
+                    if !attempts_left || !(err.is_connect() || err.is_timeout()) {
+                        return Err(err.into());
+                    }
+
+                    logfire::warn!(
+                        "WhatsApp API request failed (attempt {attempt}/{max_attempts}), retrying: {error}",
+                        attempt = i64::from(attempt),
+                        max_attempts = i64::from(self.retry_policy.max_attempts),
+                        error = err.to_string()
+                    );
+                    self.retry_policy.backoff(attempt)
+                }
+            };
+
+            ntex::time::sleep(delay.min(self.retry_policy.max_delay)).await;
+            attempt += 1;
+        }
+    }
 }
 
 impl Default for WhatsAppClient {
@@ -286,3 +503,193 @@ impl Default for WhatsAppClient {
         Self::new().expect("Failed to create WhatsApp client")
     }
 }
+
+/// Mock WhatsApp API server and client shared by the webhook tests
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use super::*;
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+    };
+
+    pub const SUCCESS_BODY: &str = r#"{"messaging_product":"whatsapp","contacts":[{"input":"521","wa_id":"521"}],"messages":[{"id":"wamid.1"}]}"#;
+
+    /// Local HTTP server answering each connection with the next canned response
+    pub struct MockServer {
+        listener: TcpListener,
+        /// Base URL of the server, e.g. `http://127.0.0.1:1234`
+        pub url: String,
+    }
+
+    impl MockServer {
+        pub fn bind() -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            Self { listener, url }
+        }
+
+        /// Serves `responses` in order, returning the count of requests received
+        pub fn serve(self, responses: Vec<Vec<u8>>) -> Arc<AtomicUsize> {
+            let hits = Arc::new(AtomicUsize::new(0));
+            let server_hits = hits.clone();
+
+            std::thread::spawn(move || {
+                for response in responses {
+                    let (mut stream, _) = self.listener.accept().unwrap();
+                    let mut buf = [0; 8192];
+                    let _ = stream.read(&mut buf);
+                    server_hits.fetch_add(1, Ordering::SeqCst);
+                    stream.write_all(&response).unwrap();
+                }
+            });
+
+            hits
+        }
+    }
+
+    pub fn http_response(status: &str, extra_headers: &str, body: &[u8]) -> Vec<u8> {
+        let mut response = format!(
+            "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n{extra_headers}\r\n",
+            body.len()
+        )
+        .into_bytes();
+        response.extend_from_slice(body);
+        response
+    }
+
+    pub fn json_response(status: &str, extra_headers: &str, body: &str) -> Vec<u8> {
+        http_response(
+            status,
+            &format!("Content-Type: application/json\r\n{extra_headers}"),
+            body.as_bytes(),
+        )
+    }
+
+    /// Client sending every request to `base_url`, with short retry delays
+    pub fn test_client(base_url: &str) -> WhatsAppClient {
+        WhatsAppClient {
+            client: reqwest::Client::new(),
+            endpoint: format!("{base_url}/messages"),
+            graph_api_url: base_url.to_string(),
+            phone_number_id: 1,
+            auth_token: "token".to_string(),
+            retry_policy: RetryPolicy {
+                max_attempts: 3,
+                base_delay: Duration::from_millis(1),
+                max_delay: Duration::from_millis(10),
+            },
+            dry_run: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_utils::*;
+    use super::*;
+    use std::sync::atomic::Ordering;
+
+    #[ntex::test]
+    async fn test_send_message_retries_server_error_then_succeeds() {
+        let server = MockServer::bind();
+        let client = test_client(&server.url);
+        let hits = server.serve(vec![
+            json_response("503 Service Unavailable", "Retry-After: 0\r\n", "{}"),
+            json_response("200 OK", "", SUCCESS_BODY),
+        ]);
+
+        let result = client
+            .send_text_message("521".to_string(), "hola".to_string())
+            .await;
+
+        assert!(result.is_ok(), "{result:?}");
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[ntex::test]
+    async fn test_send_message_fails_fast_on_client_error() {
+        let server = MockServer::bind();
+        let client = test_client(&server.url);
+        let hits = server.serve(vec![
+            json_response("400 Bad Request", "", "{}"),
+            json_response("200 OK", "", SUCCESS_BODY),
+        ]);
+
+        let result = client
+            .send_text_message("521".to_string(), "hola".to_string())
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[ntex::test]
+    async fn test_download_media_fetches_url_then_bytes() {
+        let server = MockServer::bind();
+        let client = test_client(&server.url);
+        let media_url = format!(r#"{{"url":"{}/file","file_size":3}}"#, server.url);
+        let hits = server.serve(vec![
+            json_response("200 OK", "", &media_url),
+            http_response("200 OK", "", &[1, 2, 3]),
+        ]);
+
+        assert_eq!(
+            client.download_media("media123").await.unwrap(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[ntex::test]
+    async fn test_download_media_rejects_big_files() {
+        let server = MockServer::bind();
+        let client = test_client(&server.url);
+        let media_url = format!(
+            r#"{{"url":"{}/file","file_size":{}}}"#,
+            server.url,
+            consts::PIC_PET_MAX_SIZE_BYTES + 1
+        );
+        let hits = server.serve(vec![json_response("200 OK", "", &media_url)]);
+
+        assert!(client.download_media("media123").await.is_err());
+        // the media itself is never requested
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[ntex::test]
+    async fn test_download_media_rejects_big_content_length() {
+        let server = MockServer::bind();
+        let client = test_client(&server.url);
+        let media_url = format!(r#"{{"url":"{}/file"}}"#, server.url);
+        // the body is never sent, only its declared length
+        let big_file = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            consts::PIC_PET_MAX_SIZE_BYTES + 1
+        );
+        server.serve(vec![
+            json_response("200 OK", "", &media_url),
+            big_file.into_bytes(),
+        ]);
+
+        let err = client.download_media("media123").await.unwrap_err();
+        assert!(err.to_string().contains("too big"), "{err}");
+    }
+
+    #[test]
+    fn test_retry_policy_backoff_is_exponential_and_capped() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(300),
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(300));
+    }
+}
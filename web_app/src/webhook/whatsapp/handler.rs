@@ -10,9 +10,18 @@ use super::{
         WebhookPayload,
     },
 };
-use crate::{repo, services};
+use crate::{api, consts, models, repo, services};
 use anyhow::{Context, Result};
 
+/// Reply sent to phone numbers without a Pet-Info account
+const ACCOUNT_NOT_FOUND_MSG: &str = "No se encontró una cuenta asociada a este número de teléfono. Regístrala en https://pet-info.link";
+
+/// Max rows WhatsApp allows in an interactive list
+const INTERACTIVE_LIST_MAX_ROWS: usize = 10;
+
+/// Max characters WhatsApp allows in an interactive row title
+const INTERACTIVE_ROW_TITLE_MAX_CHARS: usize = 24;
+
 /// Processes incoming WhatsApp webhook messages
 ///
 /// Extracts and processes all messages from the webhook payload.
@@ -58,10 +67,91 @@ pub fn process_webhook_statuses(payload: &WebhookPayload) -> Vec<&Status> {
         .collect::<Vec<_>>()
 }
 
+/// Row id prefix of the "more" row that asks for the next page of the pet
+/// list, followed by the offset of that page (e.g. `mas:9`)
+const PET_LIST_MORE_ROW_PREFIX: &str = "mas:";
+
+/// Rows of the pet list page starting at `offset`
+///
+/// A page holds [`INTERACTIVE_LIST_MAX_ROWS`] rows; when more pets are left
+/// the last row is the "more" one, its id carries the offset of the next
+/// page so no state is kept between messages.
+///
+/// # Returns
+///
+/// The rows and the offset of the next page, if any
+fn pet_list_page(pets: &[models::pet::Pet], offset: usize) -> (Vec<InteractiveRow>, Option<usize>) {
+    let pending = pets.get(offset..).unwrap_or_default();
+    let (page_len, next_offset) = if pending.len() > INTERACTIVE_LIST_MAX_ROWS {
+        let page_len = INTERACTIVE_LIST_MAX_ROWS - 1;
+        (page_len, Some(offset + page_len))
+    } else {
+        (pending.len(), None)
+    };
+
+    let mut rows: Vec<InteractiveRow> = pending[..page_len]
+        .iter()
+        .map(|pet| {
+            InteractiveRow::new(
+                format!("mascota:{}", pet.external_id),
+                pet.pet_name
+                    .chars()
+                    .take(INTERACTIVE_ROW_TITLE_MAX_CHARS)
+                    .collect(),
+            )
+        })
+        .collect();
+
+    if let Some(next_offset) = next_offset {
+        rows.push(InteractiveRow::new_with_description(
+            format!("{PET_LIST_MORE_ROW_PREFIX}{next_offset}"),
+            "más".to_string(),
+            format!("{} mascotas más", pets.len() - next_offset),
+        ));
+    }
+
+    (rows, next_offset)
+}
+
+/// Sends the page of the user's pet list starting at `offset`
+///
+/// Choosing the "more" row of the page sends the following one.
+///
+/// # Arguments
+///
+/// * `client` - WhatsApp API client
+/// * `to` - Recipient's WhatsApp ID (phone number)
+/// * `pets` - All the pets of the user
+/// * `offset` - Index of the first pet of the page
+async fn send_pet_list_page(
+    client: &WhatsAppClient,
+    to: &str,
+    pets: &[models::pet::Pet],
+    offset: usize,
+) -> Result<()> {
+    let (rows, _) = pet_list_page(pets, offset);
+
+    let message = OutgoingInteractiveMessage::new_list(
+        to.to_string(),
+        "Mis mascotas".to_string(),
+        "Elige una mascota para ver sus opciones.".to_string(),
+        "mascotas".to_string(),
+        rows,
+    );
+
+    client
+        .send_interactive_message(&message)
+        .await
+        .context("Failed to send pet list message")?;
+
+    Ok(())
+}
+
 /// Sends pet information to a WhatsApp user
 ///
-/// Sends a text message listing all registered pets, followed by an interactive
-/// list message for each pet with options for report and QR code.
+/// Sends a text message followed by a single interactive list with the
+/// user's pets, paginated when they don't fit in one list. Choosing a pet
+/// sends its options (report and QR code).
 ///
 /// # Arguments
 ///
@@ -69,13 +159,11 @@ pub fn process_webhook_statuses(payload: &WebhookPayload) -> Vec<&Status> {
 /// * `to` - Recipient's WhatsApp ID (phone number)
 /// * `user_id` - Database ID of the user
 /// * `repo` - Repository for database access
-/// * `message_id` - The ID of the incoming message (for typing indicator)
 async fn send_pet_info_to_user(
     client: &WhatsAppClient,
     to: &str,
     user_id: i64,
     repo: &repo::ImplAppRepo,
-    message_id: &str,
 ) -> Result<()> {
     let pets = repo.get_all_pets_user_id(user_id).await?;
 
@@ -97,38 +185,265 @@ async fn send_pet_info_to_user(
         )
         .await?;
 
-    // Send interactive list message for each pet
-    for pet in pets {
-        client.send_typing_on(message_id.to_string()).await.ok();
+    send_pet_list_page(client, to, &pets, 0).await
+}
 
-        let pet_name = pet.pet_name.clone();
-        let external_id = pet.external_id;
+/// Sends the next page of the pet list after the owner chose "more"
+///
+/// # Arguments
+///
+/// * `client` - WhatsApp API client
+/// * `message` - The message containing the interactive response
+/// * `repo` - Repository for database access
+/// * `offset` - Index of the first pet of the page, from the "more" row id
+async fn send_next_pet_list_page(
+    client: &WhatsAppClient,
+    message: &Message,
+    repo: &repo::ImplAppRepo,
+    offset: usize,
+) -> Result<()> {
+    let Some(user) = repo.get_user_app_by_phone(&message.from).await? else {
+        client
+            .send_text_message(message.from.clone(), ACCOUNT_NOT_FOUND_MSG.to_string())
+            .await?;
+        return Ok(());
+    };
 
-        let rows = vec![
-            InteractiveRow::new(format!("reporte:{}", external_id), "reporte".into()),
-            InteractiveRow {
-                id: format!("qr:{}", external_id),
-                title: "qr".into(),
-                description: Some("código QR con foto de perfil".into()),
-            },
-        ];
+    let pets = repo.get_all_pets_user_id(user.id).await?;
+    // Pets deleted since the previous page may leave the offset past the end
+    let offset = if offset < pets.len() { offset } else { 0 };
 
-        let message = OutgoingInteractiveMessage::new_list(
-            to.to_string(),
-            pet_name.clone(),
+    send_pet_list_page(client, &message.from, &pets, offset).await
+}
+
+/// Sends the options (report and QR code) of the pet chosen from the list
+///
+/// # Arguments
+///
+/// * `client` - WhatsApp API client
+/// * `to` - Recipient's WhatsApp ID (phone number)
+/// * `pet_external_id` - Pet chosen by the owner
+/// * `repo` - Repository for database access
+async fn send_pet_options(
+    client: &WhatsAppClient,
+    to: &str,
+    pet_external_id: uuid::Uuid,
+    repo: &repo::ImplAppRepo,
+) -> Result<()> {
+    let pet = repo.get_pet_by_external_id(pet_external_id).await?;
+
+    let rows = vec![
+        InteractiveRow::new(format!("reporte:{}", pet_external_id), "reporte".into()),
+        InteractiveRow {
+            id: format!("qr:{}", pet_external_id),
+            title: "qr".into(),
+            description: Some("código QR con foto de perfil".into()),
+        },
+    ];
+
+    let message = OutgoingInteractiveMessage::new_list(
+        to.to_string(),
+        pet.pet_name.clone(),
+        format!(
+            "Su perfil público es: https://pet-info.link/info/{}",
+            pet_external_id
+        ),
+        "opciones".to_string(),
+        rows,
+    );
+
+    client
+        .send_interactive_message(&message)
+        .await
+        .with_context(|| {
             format!(
-                "Su perfil público es: https://pet-info.link/info/{}",
-                external_id
-            ),
-            "opciones".to_string(),
-            rows,
-        );
+                "Failed to send interactive message for pet {}",
+                pet.pet_name
+            )
+        })?;
 
+    Ok(())
+}
+
+/// Asks the owner which pet a received photo belongs to
+///
+/// Sends an interactive list with a row per pet; the media id travels in
+/// the row id (`foto:{external_id}:{media_id}`), so the photo is only
+/// downloaded once the owner picks a pet.
+///
+/// # Arguments
+///
+/// * `client` - WhatsApp API client
+/// * `message` - The message containing the image
+/// * `repo` - Repository for database access
+async fn send_pet_photo_options(
+    client: &WhatsAppClient,
+    message: &Message,
+    repo: &repo::ImplAppRepo,
+) -> Result<()> {
+    let media_id = &message
+        .image
+        .as_ref()
+        .context("No image data in message")?
+        .id;
+
+    let Some(user) = repo.get_user_app_by_phone(&message.from).await? else {
         client
-            .send_interactive_message(&message)
-            .await
-            .with_context(|| format!("Failed to send interactive message for pet {}", pet_name))?;
+            .send_text_message(message.from.clone(), ACCOUNT_NOT_FOUND_MSG.to_string())
+            .await?;
+        return Ok(());
+    };
+
+    let pets = repo.get_all_pets_user_id(user.id).await?;
+    if pets.is_empty() {
+        client
+            .send_text_message(
+                message.from.clone(),
+                "No tienes mascotas registradas en Pet-Info.".to_string(),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let rows = pets
+        .iter()
+        .take(INTERACTIVE_LIST_MAX_ROWS)
+        .map(|pet| {
+            InteractiveRow::new(
+                format!("foto:{}:{}", pet.external_id, media_id),
+                pet.pet_name
+                    .chars()
+                    .take(INTERACTIVE_ROW_TITLE_MAX_CHARS)
+                    .collect(),
+            )
+        })
+        .collect();
+
+    let options = OutgoingInteractiveMessage::new_list(
+        message.from.clone(),
+        "Foto de perfil".to_string(),
+        "¿De cuál de tus mascotas es esta foto?".to_string(),
+        "mascotas".to_string(),
+        rows,
+    );
+
+    client
+        .send_interactive_message(&options)
+        .await
+        .context("Failed to send pet photo options")?;
+
+    Ok(())
+}
+
+/// Downloads a photo sent by the owner and stores it as the pet picture
+///
+/// # Arguments
+///
+/// * `client` - WhatsApp API client
+/// * `message` - The message containing the interactive response
+/// * `pet_external_id` - Pet chosen by the owner
+/// * `media_id` - WhatsApp id of the photo
+/// * `repo` - Repository for database access
+/// * `storage_service` - Service for storing pet images in S3
+/// * `thumbnail_cache` - Cache of resized pet pictures, invalidated for the pet
+async fn attach_photo_to_pet(
+    client: &WhatsAppClient,
+    message: &Message,
+    pet_external_id: uuid::Uuid,
+    media_id: &str,
+    repo: &repo::ImplAppRepo,
+    storage_service: &services::ImplStorageService,
+    thumbnail_cache: &api::thumbnail::ThumbnailCache,
+) -> Result<()> {
+    let user = repo
+        .get_user_app_by_phone(&message.from)
+        .await?
+        .context("No account for the phone number choosing a pet photo")?;
+
+    let result = async {
+        let photo = client.download_media(media_id).await?;
+        api::pet::replace_pet_pic(
+            user.id,
+            pet_external_id,
+            photo,
+            repo,
+            storage_service,
+            thumbnail_cache,
+        )
+        .await
     }
+    .await;
+
+    match result {
+        Ok(pet) => {
+            client
+                .send_text_message(
+                    message.from.clone(),
+                    format!("La foto de {} fue actualizada.", pet.pet_name),
+                )
+                .await?;
+            Ok(())
+        }
+        Err(e) => {
+            client
+                .send_text_message(
+                    message.from.clone(),
+                    format!(
+                        "No se pudo guardar la foto, intenta con otra imagen de máximo {} MB.",
+                        consts::PIC_PET_MAX_SIZE_BYTES / 1_000_000
+                    ),
+                )
+                .await
+                .ok();
+            Err(e.context(format!(
+                "photo could not be attached to pet {pet_external_id}"
+            )))
+        }
+    }
+}
+
+/// Sends the PDF report of a pet as a document
+///
+/// Reports over `max_bytes` are sent as a link to download them from the
+/// site instead, WhatsApp rejects big uploads with an opaque error.
+///
+/// # Arguments
+///
+/// * `client` - WhatsApp API client
+/// * `to` - Recipient's WhatsApp ID (phone number)
+/// * `pet_name` - Name of the pet, used in the file name
+/// * `pdf_bytes` - The generated report
+/// * `max_bytes` - Biggest report sent as a document
+/// * `report_url` - Where the owner can download the report
+async fn send_pet_report(
+    client: &WhatsAppClient,
+    to: &str,
+    pet_name: &str,
+    pdf_bytes: Vec<u8>,
+    max_bytes: u64,
+    report_url: &str,
+) -> Result<()> {
+    if u64::try_from(pdf_bytes.len()).unwrap_or(u64::MAX) > max_bytes {
+        client
+            .send_text_message(
+                to.to_string(),
+                format!(
+                    "El reporte de {pet_name} es demasiado grande para enviarlo por WhatsApp, descárgalo aquí: {report_url}"
+                ),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let filename = format!("reporte_{pet_name}.pdf").to_lowercase();
+    let media_id = client
+        .upload_media(pdf_bytes, "application/pdf", &filename)
+        .await?;
+
+    // Send document message with media ID
+    let document_message = OutgoingDocumentMessage::new_with_id(to.to_string(), media_id, filename);
+
+    client.send_document_message(&document_message).await?;
 
     Ok(())
 }
@@ -143,11 +458,13 @@ async fn send_pet_info_to_user(
 /// * `message` - The message containing the interactive response
 /// * `repo` - Repository for database access
 /// * `storage_service` - Service for accessing pet images from S3
+/// * `thumbnail_cache` - Cache of resized pet pictures for the QR card
 async fn handle_interactive_response(
     client: &WhatsAppClient,
     message: &Message,
     repo: &repo::ImplAppRepo,
     storage_service: &services::ImplStorageService,
+    thumbnail_cache: &api::thumbnail::ThumbnailCache,
 ) -> Result<()> {
     // Show typing indicator while processing the interactive response
     client.send_typing_on(message.id.clone()).await.ok();
@@ -160,8 +477,13 @@ async fn handle_interactive_response(
         .as_ref()
         .context("No list reply in interactive message")?;
 
+    if let Some(offset) = list_reply.id.strip_prefix(PET_LIST_MORE_ROW_PREFIX) {
+        let offset = offset.parse().unwrap_or_default();
+        return send_next_pet_list_page(client, message, repo, offset).await;
+    }
+
     let parts: Vec<&str> = list_reply.id.split(':').collect();
-    if parts.len() != 2 {
+    if !(2..=3).contains(&parts.len()) {
         logfire::warn!(
             "Invalid interactive response ID format: {id}",
             id = &list_reply.id
@@ -176,26 +498,48 @@ async fn handle_interactive_response(
         .with_context(|| format!("Invalid UUID in interactive response: {}", external_id_str))?;
 
     match action {
+        "mascota" => {
+            send_pet_options(client, &message.from, external_id, repo).await?;
+        }
         "reporte" => {
             let pet = repo.get_pet_by_external_id(external_id).await?;
+            let weight_unit = repo
+                .get_user_app_by_phone(&message.from)
+                .await?
+                .map(|user| user.weight_unit)
+                .unwrap_or_default();
+            // WhatsApp messages carry no timezone, the app default is used;
+            // the lighter share preset makes the upload faster
             let pdf_bytes = crate::api::pet::generate_pdf_report_bytes(
                 pet.id,
                 pet.user_app_id,
+                crate::front::utils::default_timezone(),
+                weight_unit,
+                &crate::api::pet::PdfReportOptions::default()
+                    .with_quality(crate::api::pet::PdfQuality::Share),
                 repo,
                 storage_service,
             )
             .await?;
 
-            let filename = format!("reporte_{}.pdf", pet.pet_name).to_lowercase();
-            let media_id = client
-                .upload_media(pdf_bytes, "application/pdf", &filename)
-                .await?;
-
-            // Send document message with media ID
-            let document_message =
-                OutgoingDocumentMessage::new_with_id(message.from.clone(), media_id, filename);
+            let app_config = crate::config::APP_CONFIG
+                .get()
+                .context("failed to get app config")?;
+            let report_url = format!(
+                "{base_url}/pet/pdf_report/{pet_id}",
+                base_url = app_config.base_url(),
+                pet_id = pet.id
+            );
 
-            client.send_document_message(&document_message).await?;
+            send_pet_report(
+                client,
+                &message.from,
+                &pet.pet_name,
+                pdf_bytes,
+                app_config.whatsapp_document_max_bytes(),
+                &report_url,
+            )
+            .await?;
         }
         "qr" => {
             // Get app config for base URL
@@ -208,18 +552,24 @@ async fn handle_interactive_response(
                 external_id = external_id
             );
 
-            // Try to get pet picture
-            let pet_pic = crate::api::pet::get_public_pic(external_id, repo, storage_service)
+            let caption = repo
+                .get_pet_qr_caption(external_id)
                 .await
                 .ok()
-                .flatten();
+                .flatten()
+                .unwrap_or_default();
 
-            // Generate QR code card with picture if available, otherwise simple QR code
-            let qr_code = if let Some(ref pic) = pet_pic {
-                crate::qr::build_qr_card_with_pic(pic, &url)
-            } else {
-                crate::qr::get_qr_code(&url)
-            }
+            // QR code card with the picture, or a default avatar if there is none
+            let qr_code = crate::api::pet::build_pet_qr_code(
+                external_id,
+                &caption.pet_name,
+                &caption.breed,
+                &url,
+                repo,
+                storage_service,
+                thumbnail_cache,
+            )
+            .await
             .with_context(|| format!("qr_code could not be generated for pet {}", external_id))?;
 
             // Upload QR code image to WhatsApp
@@ -236,6 +586,26 @@ async fn handle_interactive_response(
 
             client.send_image_message(&image_message).await?;
         }
+        "foto" => {
+            let Some(media_id) = parts.get(2) else {
+                logfire::warn!(
+                    "Missing media id in interactive response: {id}",
+                    id = &list_reply.id
+                );
+                return Ok(());
+            };
+
+            attach_photo_to_pet(
+                client,
+                message,
+                external_id,
+                media_id,
+                repo,
+                storage_service,
+                thumbnail_cache,
+            )
+            .await?;
+        }
         _ => {
             logfire::warn!(
                 "Unknown action in interactive response: {action}",
@@ -258,6 +628,7 @@ async fn handle_interactive_response(
 /// * `client` - WhatsApp API client for sending messages
 /// * `repo` - Repository for database access
 /// * `storage_service` - Service for accessing pet images from S3
+/// * `thumbnail_cache` - Cache of resized pet pictures for the QR card
 ///
 /// # Returns
 ///
@@ -267,6 +638,7 @@ pub async fn handle_user_message(
     client: &WhatsAppClient,
     repo: &repo::ImplAppRepo,
     storage_service: &services::ImplStorageService,
+    thumbnail_cache: &api::thumbnail::ThumbnailCache,
 ) -> Result<()> {
     match message.msg_type.as_str() {
         "text" if message.text.is_some() => {
@@ -275,22 +647,23 @@ pub async fn handle_user_message(
 
             let user = repo.get_user_app_by_phone(&message.from).await?;
             if let Some(user) = user {
-                send_pet_info_to_user(client, &message.from, user.id, repo, &message.id).await?;
+                send_pet_info_to_user(client, &message.from, user.id, repo).await?;
                 return Ok(());
             }
 
             // User not found, send message with typing indicator already active
             client
-            .send_text_message(
-                message.from.clone(),
-                "No se encontró una cuenta asociada a este número de teléfono. Regístrala en https://pet-info.link".to_string()
-            ).await?;
+                .send_text_message(message.from.clone(), ACCOUNT_NOT_FOUND_MSG.to_string())
+                .await?;
         }
         "interactive" => {
-            handle_interactive_response(client, message, repo, storage_service).await?;
+            handle_interactive_response(client, message, repo, storage_service, thumbnail_cache)
+                .await?;
         }
         "image" if message.image.is_some() => {
-            // TODO: Handle image uploads (e.g., pet photos)
+            client.send_typing_on(message.id.clone()).await.ok();
+
+            send_pet_photo_options(client, message, repo).await?;
         }
         "location" if message.location.is_some() => {
             // TODO: Handle location sharing (e.g., lost pet location)
@@ -311,19 +684,33 @@ pub async fn handle_user_message(
 
 /// Handles status updates for sent messages
 ///
-/// This function processes status updates to track message delivery.
+/// Saves the delivery status by message id (wamid), so the owner of a
+/// reminder can see whether its message was delivered. Untracked statuses
+/// are ignored.
 ///
 /// # Arguments
 ///
 /// * `status` - The status update to handle
+/// * `repo` - Repository for database access
 ///
 /// # Returns
 ///
 /// Result indicating success or failure
-pub async fn handle_message_status(_status: &Status) -> Result<()> {
-    // TODO: Update your database with delivery status if needed
+pub async fn handle_message_status(status: &Status, repo: &repo::ImplAppRepo) -> Result<()> {
+    let Some(delivery_status) = models::reminder::DeliveryStatus::from_webhook(&status.status)
+    else {
+        return Ok(());
+    };
 
-    Ok(())
+    let reported_at = status
+        .timestamp
+        .parse::<i64>()
+        .ok()
+        .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+        .with_context(|| format!("invalid status timestamp: {}", status.timestamp))?;
+
+    repo.upsert_message_delivery_status(&status.id, delivery_status, reported_at)
+        .await
 }
 
 /// Main webhook processor
@@ -336,6 +723,7 @@ pub async fn handle_message_status(_status: &Status) -> Result<()> {
 /// * `client` - WhatsApp API client for sending messages
 /// * `repo` - Repository for database access
 /// * `storage_service` - Service for accessing pet images from S3
+/// * `thumbnail_cache` - Cache of resized pet pictures for the QR card
 ///
 /// # Returns
 ///
@@ -345,11 +733,14 @@ pub async fn process_webhook(
     client: &WhatsAppClient,
     repo: &repo::ImplAppRepo,
     storage_service: &services::ImplStorageService,
+    thumbnail_cache: &api::thumbnail::ThumbnailCache,
 ) -> Result<()> {
     // Process incoming messages
     let messages = process_webhook_messages(&payload);
     for message in messages {
-        if let Err(e) = handle_user_message(message, client, repo, storage_service).await {
+        if let Err(e) =
+            handle_user_message(message, client, repo, storage_service, thumbnail_cache).await
+        {
             logfire::error!("Failed to handle message: {error}", error = e.to_string());
         }
     }
@@ -357,7 +748,7 @@ pub async fn process_webhook(
     // Process status updates
     let statuses = process_webhook_statuses(&payload);
     for status in statuses {
-        if let Err(e) = handle_message_status(status).await {
+        if let Err(e) = handle_message_status(status, repo).await {
             logfire::error!("Failed to handle status: {error}", error = e.to_string());
         }
     }
@@ -368,7 +759,13 @@ pub async fn process_webhook(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::webhook::whatsapp::schemas::*;
+    use crate::{
+        models,
+        repo::MockAppRepo,
+        services::test_utils::FakeStorageService,
+        webhook::whatsapp::{client::test_utils::*, schemas::*},
+    };
+    use std::sync::atomic::Ordering;
 
     #[test]
     fn test_process_webhook_messages() {
@@ -411,4 +808,279 @@ mod tests {
         assert_eq!(messages.len(), 1);
         assert_eq!(messages[0].from, "+9876543210");
     }
+
+    fn png_bytes() -> Vec<u8> {
+        let img = image::RgbaImage::from_pixel(8, 8, image::Rgba([200, 120, 80, 255]));
+        let mut body = Vec::new();
+        img.write_to(
+            &mut std::io::Cursor::new(&mut body),
+            image::ImageFormat::Png,
+        )
+        .unwrap();
+        body
+    }
+
+    #[ntex::test]
+    async fn test_handle_pet_photo_choice_stores_downloaded_photo() {
+        let (user_id, pet_id, pet_external_id) = (7, 3, uuid::Uuid::new_v4());
+        let photo = png_bytes();
+
+        let server = MockServer::bind();
+        let client = test_client(&server.url);
+        let media_url = format!(
+            r#"{{"url":"{}/file","file_size":{}}}"#,
+            server.url,
+            photo.len()
+        );
+        let hits = server.serve(vec![
+            // typing indicator
+            json_response("200 OK", "", SUCCESS_BODY),
+            json_response("200 OK", "", &media_url),
+            http_response("200 OK", "Content-Type: image/png\r\n", &photo),
+            // confirmation message
+            json_response("200 OK", "", SUCCESS_BODY),
+        ]);
+
+        let mut mock_repo = MockAppRepo::new();
+        mock_repo
+            .expect_get_user_app_by_phone()
+            .times(1)
+            .returning(move |_| {
+                let mut user = models::user_app::User::create_default_from_email("a@b.com");
+                user.id = user_id;
+                Box::pin(async move { Ok(Some(user)) })
+            });
+        mock_repo
+            .expect_get_pet_by_external_id_for_user()
+            .withf(move |external_id, id| *external_id == pet_external_id && *id == user_id)
+            .times(1)
+            .returning(move |_, _| {
+                Box::pin(async move {
+                    Ok(models::pet::Pet {
+                        id: pet_id,
+                        external_id: pet_external_id,
+                        user_app_id: user_id,
+                        pet_name: "Firulais".to_string(),
+                        ..Default::default()
+                    })
+                })
+            });
+        mock_repo
+            .expect_set_pet_pic()
+            .withf(move |id, owner_id, path| {
+                *id == pet_id && *owner_id == user_id && *path == format!("pics/{pet_external_id}")
+            })
+            .times(1)
+            .returning(|_, _, _| Box::pin(async move { Ok(()) }));
+        let repo: repo::ImplAppRepo = Box::new(mock_repo);
+
+        let storage = FakeStorageService::default();
+        let storage_service: services::ImplStorageService = Box::new(storage.clone());
+
+        let message: Message = serde_json::from_value(serde_json::json!({
+            "from": "5215512345678",
+            "id": "wamid.2",
+            "timestamp": "1234567890",
+            "type": "interactive",
+            "interactive": {
+                "type": "list_reply",
+                "list_reply": {
+                    "id": format!("foto:{pet_external_id}:media123"),
+                    "title": "Firulais"
+                }
+            }
+        }))
+        .unwrap();
+
+        handle_user_message(
+            &message,
+            &client,
+            &repo,
+            &storage_service,
+            &api::thumbnail::ThumbnailCache::new(1),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(hits.load(Ordering::SeqCst), 4);
+        let saved = storage.saved.lock().unwrap();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].0, format!("pics/{pet_external_id}"));
+    }
+
+    fn user_pets(count: usize) -> Vec<models::pet::Pet> {
+        (0..count)
+            .map(|i| models::pet::Pet {
+                id: i as i64,
+                external_id: uuid::Uuid::new_v4(),
+                pet_name: format!("mascota {i}"),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pet_list_page_adds_more_row_when_pets_left() {
+        let pets = user_pets(12);
+
+        let (rows, next_offset) = pet_list_page(&pets, 0);
+        assert_eq!(rows.len(), INTERACTIVE_LIST_MAX_ROWS);
+        assert_eq!(rows[0].id, format!("mascota:{}", pets[0].external_id));
+        assert_eq!(rows[9].id, "mas:9");
+        assert_eq!(next_offset, Some(9));
+
+        let (rows, next_offset) = pet_list_page(&pets, 9);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[2].id, format!("mascota:{}", pets[11].external_id));
+        assert_eq!(next_offset, None);
+
+        // exactly a full page needs no "more" row
+        let (rows, next_offset) = pet_list_page(&pets[..10], 0);
+        assert_eq!(rows.len(), 10);
+        assert_eq!(next_offset, None);
+    }
+
+    #[ntex::test]
+    async fn test_user_with_many_pets_gets_paginated_list() {
+        let server = MockServer::bind();
+        let client = test_client(&server.url);
+        let hits = server.serve(vec![
+            // typing indicator, intro text and first page
+            json_response("200 OK", "", SUCCESS_BODY),
+            json_response("200 OK", "", SUCCESS_BODY),
+            json_response("200 OK", "", SUCCESS_BODY),
+            // typing indicator and second page after choosing "more"
+            json_response("200 OK", "", SUCCESS_BODY),
+            json_response("200 OK", "", SUCCESS_BODY),
+        ]);
+
+        let mut mock_repo = MockAppRepo::new();
+        mock_repo
+            .expect_get_user_app_by_phone()
+            .times(2)
+            .returning(|_| {
+                let mut user = models::user_app::User::create_default_from_email("a@b.com");
+                user.id = 7;
+                Box::pin(async move { Ok(Some(user)) })
+            });
+        mock_repo
+            .expect_get_all_pets_user_id()
+            .times(2)
+            .returning(|_| Box::pin(async move { Ok(user_pets(12)) }));
+        let repo: repo::ImplAppRepo = Box::new(mock_repo);
+        let storage_service: services::ImplStorageService = Box::<FakeStorageService>::default();
+        let thumbnail_cache = api::thumbnail::ThumbnailCache::new(1);
+
+        let from = "5215512345678";
+        let text: Message = serde_json::from_value(serde_json::json!({
+            "from": from,
+            "id": "wamid.1",
+            "timestamp": "1234567890",
+            "type": "text",
+            "text": { "body": "hola" }
+        }))
+        .unwrap();
+        handle_user_message(&text, &client, &repo, &storage_service, &thumbnail_cache)
+            .await
+            .unwrap();
+
+        let more: Message = serde_json::from_value(serde_json::json!({
+            "from": from,
+            "id": "wamid.2",
+            "timestamp": "1234567890",
+            "type": "interactive",
+            "interactive": {
+                "type": "list_reply",
+                "list_reply": { "id": "mas:9", "title": "más" }
+            }
+        }))
+        .unwrap();
+        handle_user_message(&more, &client, &repo, &storage_service, &thumbnail_cache)
+            .await
+            .unwrap();
+
+        // two list messages instead of one per pet
+        assert_eq!(hits.load(Ordering::SeqCst), 5);
+    }
+
+    #[ntex::test]
+    async fn test_handle_message_status_saves_delivery() {
+        let mut mock_repo = MockAppRepo::new();
+        mock_repo
+            .expect_upsert_message_delivery_status()
+            .withf(|wamid, status, at| {
+                wamid == "wamid.1"
+                    && *status == models::reminder::DeliveryStatus::Delivered
+                    && at.timestamp() == 1234567890
+            })
+            .times(1)
+            .returning(|_, _, _| Box::pin(async move { Ok(()) }));
+        let repo: repo::ImplAppRepo = Box::new(mock_repo);
+
+        let status = |status: &str, timestamp: &str| Status {
+            id: "wamid.1".to_string(),
+            status: status.to_string(),
+            timestamp: timestamp.to_string(),
+            recipient_id: "5215512345678".to_string(),
+        };
+
+        handle_message_status(&status("delivered", "1234567890"), &repo)
+            .await
+            .unwrap();
+        // not tracked, the repo isn't called
+        handle_message_status(&status("deleted", "1234567890"), &repo)
+            .await
+            .unwrap();
+        assert!(
+            handle_message_status(&status("read", "yesterday"), &repo)
+                .await
+                .is_err()
+        );
+    }
+
+    #[ntex::test]
+    async fn test_oversized_report_is_sent_as_link() {
+        let server = MockServer::bind();
+        let client = test_client(&server.url);
+        // only the text message with the link, nothing is uploaded
+        let hits = server.serve(vec![json_response("200 OK", "", SUCCESS_BODY)]);
+
+        send_pet_report(
+            &client,
+            "5215512345678",
+            "Firulais",
+            vec![0; 2_048],
+            1_024,
+            "https://pet-info.link/pet/pdf_report/3",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[ntex::test]
+    async fn test_report_within_limit_is_uploaded() {
+        let server = MockServer::bind();
+        let client = test_client(&server.url);
+        let hits = server.serve(vec![
+            // media upload
+            json_response("200 OK", "", r#"{"id":"media123"}"#),
+            // document message
+            json_response("200 OK", "", SUCCESS_BODY),
+        ]);
+
+        send_pet_report(
+            &client,
+            "5215512345678",
+            "Firulais",
+            vec![0; 512],
+            1_024,
+            "https://pet-info.link/pet/pdf_report/3",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
 }
@@ -8,6 +8,9 @@
 //! The POST endpoint verifies webhook authenticity using mTLS client certificates.
 //! Nginx reverse proxy handles the TLS layer verification and passes headers to this application.
 //! This ensures that requests actually originate from Meta/Facebook.
+//!
+//! As defense in depth, the `X-Hub-Signature-256` HMAC that Meta computes over
+//! the raw body with the app secret is also verified.
 
 use super::{handler, schemas};
 use crate::{
@@ -31,6 +34,52 @@ pub struct VerifyQuery {
     pub challenge: String,
 }
 
+/// Checks the `X-Hub-Signature-256` header value against the raw request body
+///
+/// Meta signs every webhook payload with HMAC-SHA256 using the app secret and
+/// sends it as `sha256=<hex digest>`. The digests are compared in constant time.
+///
+/// # Arguments
+/// * `app_secret` - WhatsApp (Meta) app secret
+/// * `body` - Raw request body, before any JSON parsing
+/// * `signature_header` - Value of the `X-Hub-Signature-256` header
+///
+/// # Returns
+/// True if the signature matches the body
+fn is_valid_signature(app_secret: &str, body: &[u8], signature_header: &str) -> bool {
+    let Some(signature) = signature_header.strip_prefix("sha256=") else {
+        return false;
+    };
+
+    let expected = match hmac_sha256_hex(app_secret.as_bytes(), body) {
+        Ok(digest) => digest,
+        Err(e) => {
+            logfire::error!(
+                "Failed to compute webhook signature: {error}",
+                error = e.to_string()
+            );
+            return false;
+        }
+    };
+
+    let signature = signature.to_ascii_lowercase();
+    signature.len() == expected.len()
+        && openssl::memcmp::eq(signature.as_bytes(), expected.as_bytes())
+}
+
+/// Computes the HMAC-SHA256 of `body` as a lowercase hex string
+fn hmac_sha256_hex(key: &[u8], body: &[u8]) -> anyhow::Result<String> {
+    let pkey = openssl::pkey::PKey::hmac(key)?;
+    let mut signer = openssl::sign::Signer::new(openssl::hash::MessageDigest::sha256(), &pkey)?;
+    signer.update(body)?;
+
+    Ok(signer
+        .sign_to_vec()?
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect())
+}
+
 /// Webhook verification endpoint (GET)
 ///
 /// WhatsApp sends a GET request to verify the webhook URL.
@@ -76,6 +125,9 @@ pub async fn verify(
 /// Nginx handles the TLS layer verification and passes verification headers.
 /// Requests without valid mTLS certificates are rejected with a 403 Forbidden response.
 ///
+/// The `X-Hub-Signature-256` header is then checked against the raw body, requests
+/// with a missing or mismatching signature are rejected with 403 Forbidden.
+///
 /// # Processing
 ///
 /// Process webhook synchronously.
@@ -142,7 +194,18 @@ pub async fn receive(
         }
     }
 
-    // Parse the JSON payload after mTLS verification
+    // Verify Meta's HMAC signature over the raw body before parsing it
+    let signature_header = req
+        .headers()
+        .get("X-Hub-Signature-256")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if !is_valid_signature(&app_config.whatsapp_app_secret, &body, signature_header) {
+        logfire::warn!("Invalid or missing X-Hub-Signature-256 header");
+        return Ok(web::HttpResponse::Forbidden().finish());
+    }
+
+    // Parse the JSON payload after mTLS and signature verification
     let payload: schemas::WebhookPayload = match serde_json::from_slice(&body) {
         Ok(p) => p,
         Err(e) => {
@@ -160,6 +223,7 @@ pub async fn receive(
         &app_state.whatsapp_client,
         &app_state.repo,
         &app_state.storage_service,
+        &app_state.thumbnail_cache,
     )
     .await
     {
@@ -183,4 +247,51 @@ mod tests {
         assert_eq!(query.verify_token, "test123");
         assert_eq!(query.challenge, "challenge123");
     }
+
+    const TEST_APP_SECRET: &str = "test_app_secret";
+    const TEST_BODY: &[u8] = br#"{"object":"whatsapp_business_account","entry":[]}"#;
+    const TEST_SIGNATURE: &str =
+        "sha256=dec6a680679a968e7e0319355b23599cbfc54502c0784668e9618baa0c633756";
+
+    #[test]
+    fn test_is_valid_signature() {
+        assert!(is_valid_signature(
+            TEST_APP_SECRET,
+            TEST_BODY,
+            TEST_SIGNATURE
+        ));
+        assert!(is_valid_signature(
+            TEST_APP_SECRET,
+            TEST_BODY,
+            &TEST_SIGNATURE.to_uppercase().replace("SHA256=", "sha256=")
+        ));
+    }
+
+    #[test]
+    fn test_is_valid_signature_mismatch() {
+        // other secret
+        assert!(!is_valid_signature(
+            "other_secret",
+            TEST_BODY,
+            TEST_SIGNATURE
+        ));
+        // tampered body
+        assert!(!is_valid_signature(
+            TEST_APP_SECRET,
+            br#"{"object":"whatsapp_business_account","entry":[{}]}"#,
+            TEST_SIGNATURE
+        ));
+        // missing prefix, truncated or empty header
+        assert!(!is_valid_signature(
+            TEST_APP_SECRET,
+            TEST_BODY,
+            TEST_SIGNATURE.trim_start_matches("sha256=")
+        ));
+        assert!(!is_valid_signature(
+            TEST_APP_SECRET,
+            TEST_BODY,
+            &TEST_SIGNATURE[..20]
+        ));
+        assert!(!is_valid_signature(TEST_APP_SECRET, TEST_BODY, ""));
+    }
 }
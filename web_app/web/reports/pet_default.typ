@@ -125,6 +125,7 @@
 
 #v(20pt)
 
+{% if include_vaccines %}
 // ==================== VACCINES SECTION ====================
 #block(
     fill: white,
@@ -156,6 +157,8 @@
 
 #v(20pt)
 
+{% endif %}
+{% if include_deworms %}
 // ==================== DEWORMS SECTION ====================
 #block(
     fill: white,
@@ -185,6 +188,8 @@
     )
 ]
 
+{% endif %}
+{% if include_weights %}
 #pagebreak()
 
 // ==================== WEIGHT HISTORY ====================
@@ -204,7 +209,7 @@
         stroke: none,
         row-gutter: 8pt,
         table.header(
-            [*Peso (kg)*],
+            [*Peso ({{ weight_unit }})*],
             [*Fecha*],
             [*Edad*]
         ),
@@ -220,7 +225,9 @@
 
 #v(20pt)
 
+{% endif %}
 // ==================== NOTES SECTION ====================
+{% if include_notes %}
 {% for note in notes %}
 #block(
     fill: rgb("#fef3c7"),
@@ -243,3 +250,4 @@
 ]
 #v(15pt)
 {% endfor %}
+{% endif %}
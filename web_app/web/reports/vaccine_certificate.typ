@@ -0,0 +1,106 @@
+// Pet Info Vaccine Certificate - One page
+#set page(
+    width: 210mm,  // A4
+    height: 297mm,
+    margin: (x: 2cm, y: 2cm),
+    footer: context [
+        #set align(center)
+        #set text(9pt, fill: rgb("#64748b"))
+        #link("https://pet-info.link")[Pet-Info] • Emitido el {{ issued_at | date(format="%v") }}
+    ]
+)
+
+#set text(
+    font: "PT Sans",
+    size: 12pt,
+    fill: rgb("#1e293b")
+)
+
+#show link: underline
+
+// ==================== HEADER ====================
+#align(center)[
+    #text(size: 24pt, weight: "bold", fill: rgb("#0f172a"))[Certificado de Vacunación]
+]
+
+#v(16pt)
+
+// ==================== PET IDENTITY ====================
+#block(
+    inset: 16pt,
+    radius: 12pt,
+    width: 100%,
+    stroke: (paint: rgb("#e2e8f0"), thickness: 1pt)
+)[
+    #grid(
+        columns: (1fr, auto),
+        column-gutter: 20pt,
+        align: (left + horizon, center + horizon),
+
+        [
+            #grid(
+                columns: (auto, 1fr),
+                row-gutter: 8pt,
+                column-gutter: 12pt,
+
+                [*Nombre:*], [{{ pet_name | title }}],
+                [*Fecha de Nacimiento:*], [{{ birthday | date(format="%v") }}],
+                [*Edad:*], [{{ age }}],
+                [*Raza:*], [{{ breed }}],
+                [*Sexo:*], [{% if is_female %}Hembra{% else %}Macho{% endif %}],
+                [*Perfil:*], [#link("{{ pet_link }}")[{{ pet_link }}]],
+            )
+        ],
+
+        [
+            {% if image_filename != 'NO_PIC' %}
+            #image("{{ image_filename }}", height: 80pt)
+            {% else %}
+            #image("qr.png", width: 80pt, height: 80pt)
+            {% endif %}
+        ]
+    )
+]
+
+#v(16pt)
+
+// ==================== VACCINES ====================
+#block(
+    inset: 16pt,
+    radius: 12pt,
+    width: 100%,
+    stroke: (paint: rgb("#e2e8f0"), thickness: 1pt)
+)[
+    #text(size: 14pt, weight: "bold", fill: rgb("#0f172a"))[Vacunas aplicadas]
+    #v(8pt)
+
+    #table(
+        columns: (1fr, auto),
+        align: (left, center),
+        stroke: none,
+        row-gutter: 6pt,
+        table.header(
+            [*Descripción*],
+            [*Fecha*]
+        ),
+        table.hline(stroke: (paint: rgb("#e2e8f0"), thickness: 1pt)),
+        {% for vaccine in vaccines %}
+        [{{ vaccine.description }}],
+        [#text(fill: rgb("#64748b"))[{{ vaccine.created_at | date(format="%v") }}]],
+        table.hline(stroke: (paint: rgb("#f1f5f9"), thickness: 0.5pt)),
+        {% endfor %}
+        {% if vaccines | length == 0 %}
+        table.cell(colspan: 2)[#text(fill: rgb("#64748b"), style: "italic")[Sin vacunas registradas]],
+        {% endif %}
+    )
+]
+
+{% if include_signature %}
+// ==================== SIGNATURE ====================
+#v(1fr)
+#align(center)[
+    #line(length: 60%, stroke: (paint: rgb("#1e293b"), thickness: 0.8pt))
+    #v(4pt)
+    #text(size: 11pt, fill: rgb("#64748b"))[Nombre, firma y cédula del médico veterinario]
+]
+{% endif %}